@@ -0,0 +1,169 @@
+//! Approval prompts for gated tools.
+//!
+//! [`ApprovalPrompt`] answers the harness's `ApprovalRequired` events with a
+//! TUI question: allow the call once (optionally after editing its
+//! arguments), always allow the tool for this session or for the project,
+//! or deny it. "Always" decisions go into the harness's
+//! [`ApprovalStore`](cinch_rs::agent::approvals::ApprovalStore); the project
+//! ones are saved to [`APPROVALS_FILE`] under the home directory, keyed by
+//! project path, so later sessions don't ask again. The file never lives in
+//! the workdir, where a cloned repo or the agent itself could pre-approve
+//! tools.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use cinch_rs::agent::approvals::ApprovalScope;
+use cinch_rs::agent::events::{EventHandler, EventResponse, HarnessEvent};
+use cinch_rs::ui::{QuestionChoice, QuestionResponse, UiState, UserQuestion};
+
+use crate::interact::ask_and_block;
+
+/// Project-scoped "always allow" decisions of every project, relative to
+/// the home directory.
+pub const APPROVALS_FILE: &str = ".config/cinch/approvals.json";
+
+/// Where [`APPROVALS_FILE`] is, or `None` without a home directory.
+pub fn approvals_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(APPROVALS_FILE))
+}
+
+/// Seconds before an approval question times out (treated as a denial).
+const APPROVAL_TIMEOUT_SECS: u64 = 600;
+
+const ALLOW_ONCE: usize = 0;
+const ALWAYS_SESSION: usize = 1;
+const ALWAYS_PROJECT: usize = 2;
+
+/// Asks the user to approve gated tool calls in the TUI.
+pub struct ApprovalPrompt {
+    ui_state: Arc<Mutex<UiState>>,
+}
+
+impl ApprovalPrompt {
+    pub fn new(ui_state: Arc<Mutex<UiState>>) -> Self {
+        Self { ui_state }
+    }
+}
+
+impl EventHandler for ApprovalPrompt {
+    fn on_event(&self, event: &HarnessEvent<'_>) -> Option<EventResponse> {
+        let HarnessEvent::ApprovalRequired {
+            name, arguments, ..
+        } = event
+        else {
+            return None;
+        };
+        let response = ask_and_block(
+            &self.ui_state,
+            approval_question(name, arguments),
+            APPROVAL_TIMEOUT_SECS,
+        );
+        Some(decision(&response, arguments))
+    }
+}
+
+/// The question for a call to `name`. The first choice's body holds the
+/// arguments, so they can be edited before allowing the call once.
+fn approval_question(name: &str, arguments: &str) -> UserQuestion {
+    let pretty = serde_json::from_str::<serde_json::Value>(arguments)
+        .and_then(|v| serde_json::to_string_pretty(&v))
+        .unwrap_or_else(|_| arguments.to_string());
+    let choice = |label: String, body: String, metadata: &str| QuestionChoice {
+        label,
+        body,
+        metadata: metadata.into(),
+    };
+    UserQuestion {
+        prompt: format!("Allow {name}? (e to edit the arguments)"),
+        choices: vec![
+            choice("Allow once".into(), pretty, ""),
+            choice(
+                format!("Always allow {name} this session"),
+                String::new(),
+                "",
+            ),
+            choice(
+                format!("Always allow {name} in this project"),
+                String::new(),
+                &format!("~/{APPROVALS_FILE}"),
+            ),
+            choice("Deny".into(), String::new(), ""),
+        ],
+        editable: true,
+        max_edit_length: None,
+        kind: Default::default(),
+        default: None,
+    }
+}
+
+/// Map the answer to the harness response. Anything but an allow choice
+/// (deny, skip, timeout) denies the call.
+fn decision(response: &QuestionResponse, arguments: &str) -> EventResponse {
+    match response {
+        QuestionResponse::Selected(ALLOW_ONCE) => EventResponse::Approve,
+        QuestionResponse::SelectedEdited {
+            index: ALLOW_ONCE,
+            edited_text,
+        } => {
+            let unchanged = serde_json::from_str::<serde_json::Value>(edited_text).ok()
+                == serde_json::from_str::<serde_json::Value>(arguments).ok();
+            if unchanged {
+                EventResponse::Approve
+            } else {
+                EventResponse::ApproveEdited(edited_text.clone())
+            }
+        }
+        QuestionResponse::Selected(ALWAYS_SESSION)
+        | QuestionResponse::SelectedEdited {
+            index: ALWAYS_SESSION,
+            ..
+        } => EventResponse::ApproveAlways(ApprovalScope::Session),
+        QuestionResponse::Selected(ALWAYS_PROJECT)
+        | QuestionResponse::SelectedEdited {
+            index: ALWAYS_PROJECT,
+            ..
+        } => EventResponse::ApproveAlways(ApprovalScope::Project),
+        QuestionResponse::TimedOut => EventResponse::Deny("no answer from the user".into()),
+        _ => EventResponse::Deny("the user declined".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers_map_to_decisions() {
+        let args = r#"{"message":"wip"}"#;
+        assert!(matches!(
+            decision(&QuestionResponse::Selected(0), args),
+            EventResponse::Approve
+        ));
+        assert!(matches!(
+            decision(&QuestionResponse::Selected(2), args),
+            EventResponse::ApproveAlways(ApprovalScope::Project)
+        ));
+        assert!(matches!(
+            decision(&QuestionResponse::Selected(3), args),
+            EventResponse::Deny(_)
+        ));
+
+        // Reformatting alone isn't an edit.
+        let pretty = approval_question("git_commit", args).choices[0]
+            .body
+            .clone();
+        let edited = |text: &str| QuestionResponse::SelectedEdited {
+            index: 0,
+            edited_text: text.into(),
+        };
+        assert!(matches!(
+            decision(&edited(&pretty), args),
+            EventResponse::Approve
+        ));
+        assert!(matches!(
+            decision(&edited(r#"{"message":"fix: typo"}"#), args),
+            EventResponse::ApproveEdited(ref a) if a.contains("typo")
+        ));
+    }
+}
@@ -0,0 +1,312 @@
+//! User-defined slash commands.
+//!
+//! Each markdown file in `.cinch/commands/` defines a command named after
+//! its file stem: `.cinch/commands/review.md` becomes `/review`. Typing the
+//! command at the input prompt expands the file into the user turn.
+//!
+//! Templates may reference arguments:
+//!
+//! - `$ARGUMENTS` — everything after the command name.
+//! - `$1` … `$9` — individual whitespace-separated arguments.
+//!
+//! If a template uses neither and arguments are given, they are appended
+//! after a blank line. An optional front-matter block sets the text shown
+//! by `/help`; otherwise the first non-empty line is used.
+//!
+//! ```markdown
+//! ---
+//! description: Review the diff against a branch
+//! ---
+//! Review `git diff $1` for bugs and style issues. Focus on: $ARGUMENTS
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::project_config::CINCH_DIR;
+
+/// Subdirectory of [`CINCH_DIR`] holding command templates.
+pub const COMMANDS_DIR: &str = "commands";
+
+/// Commands handled by cinch-code itself; templates can't override them.
+pub const BUILTIN_COMMANDS: &[&str] = &["help", "commit", "pr-description"];
+
+/// A single slash command loaded from a template file.
+#[derive(Debug, Clone)]
+pub struct SlashCommand {
+    /// Command name without the leading `/`.
+    pub name: String,
+    /// One-line description for `/help`.
+    pub description: String,
+    /// Template body (front matter stripped).
+    pub template: String,
+}
+
+impl SlashCommand {
+    /// Parse a template file's contents.
+    pub fn parse(name: impl Into<String>, contents: &str) -> Self {
+        let (description, body) = split_front_matter(contents);
+        let template = body.trim().to_string();
+        let description = description.unwrap_or_else(|| {
+            template
+                .lines()
+                .map(|l| l.trim().trim_start_matches('#').trim())
+                .find(|l| !l.is_empty())
+                .unwrap_or_default()
+                .to_string()
+        });
+        Self {
+            name: name.into(),
+            description,
+            template,
+        }
+    }
+
+    /// Substitute `args` into the template.
+    pub fn expand(&self, args: &str) -> String {
+        let args = args.trim();
+        let positional: Vec<&str> = args.split_whitespace().collect();
+        let uses_args = self.template.contains("$ARGUMENTS")
+            || (1..=9).any(|i| self.template.contains(&format!("${i}")));
+
+        let mut out = self.template.replace("$ARGUMENTS", args);
+        for i in 1..=9 {
+            let value = positional.get(i - 1).copied().unwrap_or_default();
+            out = out.replace(&format!("${i}"), value);
+        }
+        if !uses_args && !args.is_empty() {
+            out.push_str("\n\n");
+            out.push_str(args);
+        }
+        out
+    }
+}
+
+/// Result of interpreting a line of user input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlashInput {
+    /// Plain text, not a command.
+    Text(String),
+    /// `/help` — show the command list.
+    Help,
+    /// `/commit [note]` — commit the agent's changes. Carries the note.
+    Commit(String),
+    /// `/pr-description [base] [note]` — draft a pull request. Carries the
+    /// arguments.
+    PrDescription(String),
+    /// A known command, expanded to the prompt to send.
+    Expanded(String),
+    /// A `/name` that matches no command.
+    Unknown(String),
+}
+
+/// The set of slash commands available in a project.
+#[derive(Debug, Clone, Default)]
+pub struct CommandRegistry {
+    commands: BTreeMap<String, SlashCommand>,
+}
+
+impl CommandRegistry {
+    /// Load commands from the nearest `.cinch/commands/` at or above `workdir`.
+    ///
+    /// Unreadable files are skipped with a warning. Returns an empty registry
+    /// when no commands directory exists.
+    pub fn discover(workdir: &Path) -> Self {
+        match find_commands_dir(workdir) {
+            Some(dir) => Self::load_dir(&dir),
+            None => Self::default(),
+        }
+    }
+
+    /// Load every `*.md` file in `dir`.
+    pub fn load_dir(dir: &Path) -> Self {
+        let mut registry = Self::default();
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return registry;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("md") {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            if !is_valid_name(name) {
+                tracing::warn!(
+                    "Skipping slash command with invalid name: {}",
+                    path.display()
+                );
+                continue;
+            }
+            match std::fs::read_to_string(&path) {
+                Ok(contents) => registry.insert(SlashCommand::parse(name, &contents)),
+                Err(e) => tracing::warn!("Failed to read {}: {e}", path.display()),
+            }
+        }
+        registry
+    }
+
+    /// Add or replace a command. Built-in names are reserved and ignored.
+    pub fn insert(&mut self, command: SlashCommand) {
+        if !BUILTIN_COMMANDS.contains(&command.name.as_str()) {
+            self.commands.insert(command.name.clone(), command);
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SlashCommand> {
+        self.commands.get(name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Interpret a line of user input.
+    pub fn resolve(&self, input: &str) -> SlashInput {
+        let trimmed = input.trim_start();
+        let Some(rest) = trimmed.strip_prefix('/') else {
+            return SlashInput::Text(input.to_string());
+        };
+        let (name, args) = match rest.split_once(char::is_whitespace) {
+            Some((name, args)) => (name, args),
+            None => (rest, ""),
+        };
+        // Paths like "/usr/bin is missing" are not commands.
+        if name.is_empty() || !is_valid_name(name) {
+            return SlashInput::Text(input.to_string());
+        }
+        match name {
+            "help" => return SlashInput::Help,
+            "commit" => return SlashInput::Commit(args.trim().to_string()),
+            "pr-description" => return SlashInput::PrDescription(args.trim().to_string()),
+            _ => {}
+        }
+        match self.commands.get(name) {
+            Some(cmd) => SlashInput::Expanded(cmd.expand(args)),
+            None => SlashInput::Unknown(name.to_string()),
+        }
+    }
+
+    /// Text listing the available commands, for `/help`.
+    pub fn help_text(&self) -> String {
+        let mut out = String::from(
+            "Slash commands:\n  /help  Show this list\n  \
+             /commit [note]  Commit the agent's changes with a generated message\n  \
+             /pr-description [base] [note]  Draft a pull request and push it with gh",
+        );
+        if self.commands.is_empty() {
+            out.push_str(&format!(
+                "\n\nNo custom commands. Add markdown templates to {CINCH_DIR}/{COMMANDS_DIR}/."
+            ));
+        }
+        for cmd in self.commands.values() {
+            out.push_str(&format!("\n  /{}  {}", cmd.name, cmd.description));
+        }
+        out
+    }
+}
+
+fn find_commands_dir(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(CINCH_DIR).join(COMMANDS_DIR))
+        .find(|p| p.is_dir())
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Split a leading `---` front-matter block, returning its `description`.
+fn split_front_matter(contents: &str) -> (Option<String>, &str) {
+    let Some(rest) = contents.strip_prefix("---\n") else {
+        return (None, contents);
+    };
+    let Some((header, body)) = rest.split_once("\n---") else {
+        return (None, contents);
+    };
+    let description = header.lines().find_map(|line| {
+        line.strip_prefix("description:")
+            .map(|d| d.trim().trim_matches('"').to_string())
+    });
+    (description, body.trim_start_matches('-'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> CommandRegistry {
+        let mut r = CommandRegistry::default();
+        r.insert(SlashCommand::parse(
+            "review",
+            "---\ndescription: Review a branch\n---\nReview `git diff $1`. Notes: $ARGUMENTS",
+        ));
+        r.insert(SlashCommand::parse(
+            "changelog",
+            "# Write a changelog\nSummarize.",
+        ));
+        r
+    }
+
+    #[test]
+    fn parses_description() {
+        let r = registry();
+        assert_eq!(r.get("review").unwrap().description, "Review a branch");
+        assert_eq!(r.get("changelog").unwrap().description, "Write a changelog");
+        assert!(!r.get("review").unwrap().template.contains("---"));
+    }
+
+    #[test]
+    fn expands_arguments() {
+        let r = registry();
+        assert_eq!(
+            r.resolve("/review main be strict"),
+            SlashInput::Expanded("Review `git diff main`. Notes: main be strict".into())
+        );
+        assert_eq!(
+            r.resolve("/changelog since v1"),
+            SlashInput::Expanded("# Write a changelog\nSummarize.\n\nsince v1".into())
+        );
+    }
+
+    #[test]
+    fn resolves_help_unknown_and_text() {
+        let r = registry();
+        assert_eq!(r.resolve("/help"), SlashInput::Help);
+        assert_eq!(
+            r.resolve("/commit  mention the bug id"),
+            SlashInput::Commit("mention the bug id".into())
+        );
+        assert_eq!(
+            r.resolve("/pr-description dev closes #4"),
+            SlashInput::PrDescription("dev closes #4".into())
+        );
+        assert_eq!(r.resolve("/nope x"), SlashInput::Unknown("nope".into()));
+        assert_eq!(r.resolve("fix it"), SlashInput::Text("fix it".into()));
+        assert_eq!(
+            r.resolve("/usr/bin/env is broken"),
+            SlashInput::Text("/usr/bin/env is broken".into())
+        );
+        assert!(r.help_text().contains("/review  Review a branch"));
+    }
+
+    #[test]
+    fn discovers_from_nested_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let cmds = dir.path().join(".cinch/commands");
+        std::fs::create_dir_all(&cmds).unwrap();
+        std::fs::write(cmds.join("test.md"), "Run the tests.").unwrap();
+        std::fs::write(cmds.join("notes.txt"), "ignored").unwrap();
+        let nested = dir.path().join("src");
+        std::fs::create_dir_all(&nested).unwrap();
+
+        let r = CommandRegistry::discover(&nested);
+        assert!(r.get("test").is_some());
+        assert!(r.get("notes").is_none());
+    }
+}
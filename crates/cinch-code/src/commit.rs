@@ -0,0 +1,449 @@
+//! `/commit`: commit the agent's changes with a generated message.
+//!
+//! [`ChangedFiles`] records every path the agent writes or edits. On
+//! `/commit`, [`CommitWorkflow`] stages those paths, asks a cheap model for a
+//! conventional-commit message based on the staged diff, shows it for
+//! approval (editable), and commits only the agent's files. Optionally the
+//! first commit of a session moves to a fresh branch.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use cinch_rs::agent::events::{EventHandler, EventResponse, HarnessEvent};
+use cinch_rs::tools::names;
+use cinch_rs::tools::output::ToolOutput;
+use cinch_rs::ui::{QuestionChoice, QuestionResponse, UiState, UserQuestion};
+use cinch_rs::{ChatRequest, Message, OpenRouterClient};
+
+use crate::interact::ask_and_wait;
+use crate::roots::{display_path, split_tool_name};
+use crate::tools::git::{commit_paths, run_git, run_git_with_index, succeeded};
+
+/// Default model for commit message generation.
+pub const DEFAULT_COMMIT_MODEL: &str = "openai/gpt-4o-mini";
+
+/// Maximum diff characters sent to the message model.
+const MAX_DIFF_CHARS: usize = 24_000;
+
+/// Seconds before the approval question times out (treated as cancel).
+const APPROVAL_TIMEOUT_SECS: u64 = 600;
+
+const COMMIT_MESSAGE_PROMPT: &str = "\
+You write git commit messages in the Conventional Commits format.
+
+Rules:
+- First line: `<type>(<optional scope>): <summary>`, at most 72 characters, \
+  imperative mood, no trailing period. Types: feat, fix, refactor, docs, test, \
+  chore, perf, build, ci, style.
+- If the change needs explanation, add a blank line and a short body wrapped \
+  at 72 characters describing what changed and why.
+- Describe only what the diff shows.
+- Output the commit message only — no code fences, no commentary.";
+
+// ── Changed-file tracking ───────────────────────────────────────────
+
+/// Paths changed by `write_file` / `edit_file` during a session.
+///
+/// Fed by [`HarnessEvent::FileEdited`], so failed and rolled-back calls are
+/// not recorded.
+///
+/// Cheap to clone; clones share the same set. Register a clone as an event
+/// handler (e.g. in a [`CompositeEventHandler`](cinch_rs::agent::events::CompositeEventHandler))
+/// and read it back later.
+#[derive(Clone, Default)]
+pub struct ChangedFiles(Arc<Mutex<BTreeSet<String>>>);
+
+impl ChangedFiles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the `path` argument of a file-mutation tool call.
+    ///
+    /// Paths from an extra workspace root's tools are recorded as
+    /// `root:path` (see [`crate::roots`]); `/commit` only covers the
+    /// primary root.
+    pub fn record(&self, tool_name: &str, arguments: &str) {
+        let path = serde_json::from_str::<serde_json::Value>(arguments)
+            .ok()
+            .and_then(|v| v.get("path").and_then(|p| p.as_str()).map(String::from));
+        if let Some(path) = path {
+            self.record_path(tool_name, &path);
+        }
+    }
+
+    /// Record `path` as changed by `tool_name`, if that is a file-mutation tool.
+    fn record_path(&self, tool_name: &str, path: &str) {
+        let (root, tool) = split_tool_name(tool_name);
+        if tool != names::WRITE_FILE && tool != names::EDIT_FILE {
+            return;
+        }
+        if let Ok(mut files) = self.0.lock() {
+            files.insert(display_path(root, path));
+        }
+    }
+
+    /// All recorded paths, sorted.
+    pub fn paths(&self) -> Vec<String> {
+        self.0
+            .lock()
+            .map(|f| f.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut files) = self.0.lock() {
+            files.clear();
+        }
+    }
+}
+
+impl EventHandler for ChangedFiles {
+    fn on_event(&self, event: &HarnessEvent<'_>) -> Option<EventResponse> {
+        if let HarnessEvent::FileEdited { name, path, .. } = event {
+            self.record_path(name, path);
+        }
+        None
+    }
+}
+
+// ── Workflow ────────────────────────────────────────────────────────
+
+/// Settings and state for `/commit`.
+pub struct CommitWorkflow {
+    workdir: String,
+    model: String,
+    /// Create `<prefix><session>` on the first commit, if set.
+    branch_prefix: Option<String>,
+    branch: Mutex<Option<String>>,
+    changed: ChangedFiles,
+}
+
+impl CommitWorkflow {
+    pub fn new(
+        workdir: impl Into<String>,
+        model: impl Into<String>,
+        changed: ChangedFiles,
+    ) -> Self {
+        Self {
+            workdir: workdir.into(),
+            model: model.into(),
+            branch_prefix: None,
+            branch: Mutex::new(None),
+            changed,
+        }
+    }
+
+    /// Commit on a per-session branch named `<prefix><unix-timestamp>`.
+    pub fn with_branch_prefix(mut self, prefix: Option<String>) -> Self {
+        self.branch_prefix = prefix;
+        self
+    }
+
+    /// Agent-changed paths that still exist on disk (rejected new files in
+    /// review mode never get created).
+    pub fn pending_paths(&self) -> Vec<String> {
+        self.changed
+            .paths()
+            .into_iter()
+            .filter(|p| Path::new(&self.workdir).join(p).exists())
+            .collect()
+    }
+
+    /// Diff of the agent's changes against `HEAD`, including new files.
+    ///
+    /// Built in a throwaway index, so nothing is staged in the user's index
+    /// until they approve the commit.
+    pub async fn diff(&self, paths: &[String]) -> Result<String, String> {
+        let index = std::env::temp_dir().join(format!(
+            "cinch-commit-index-{}-{}",
+            std::process::id(),
+            cinch_rs::platform::epoch_millis()
+        ));
+        let diff = self.diff_in_index(&index, paths).await;
+        let _ = std::fs::remove_file(&index);
+        diff
+    }
+
+    async fn diff_in_index(&self, index: &Path, paths: &[String]) -> Result<String, String> {
+        let out = run_git_with_index(&self.workdir, index, &["read-tree", "HEAD"]).await;
+        if !succeeded(&out) {
+            return Err(format!("git read-tree failed: {out}"));
+        }
+        // Intent-to-add makes untracked files show up in `git diff HEAD`
+        // without staging their content.
+        let mut add_args = vec!["add", "--intent-to-add", "--"];
+        add_args.extend(paths.iter().map(|s| s.as_str()));
+        let out = run_git_with_index(&self.workdir, index, &add_args).await;
+        if !succeeded(&out) {
+            return Err(format!("git add failed: {out}"));
+        }
+
+        let mut diff_args = vec!["diff", "HEAD", "--"];
+        diff_args.extend(paths.iter().map(|s| s.as_str()));
+        let out = run_git_with_index(&self.workdir, index, &diff_args).await;
+        match ToolOutput::parse(&out) {
+            Some(output) if output.success() => Ok(output.stdout),
+            _ => Err(format!("git diff failed: {out}")),
+        }
+    }
+
+    /// Ask the commit model for a message describing `diff`.
+    pub async fn generate_message(
+        &self,
+        client: &OpenRouterClient,
+        diff: &str,
+        hint: &str,
+    ) -> Result<String, String> {
+        let mut user = format!("Diff:\n{}", truncate_chars(diff, MAX_DIFF_CHARS));
+        if !hint.trim().is_empty() {
+            user.push_str(&format!("\n\nAuthor's note: {}", hint.trim()));
+        }
+        let body = ChatRequest {
+            model: Some(self.model.clone()),
+            messages: vec![Message::system(COMMIT_MESSAGE_PROMPT), Message::user(user)],
+            max_tokens: 512,
+            temperature: 0.2,
+            ..Default::default()
+        };
+        let completion = client.chat(&body).await?;
+        let text = completion
+            .content
+            .ok_or_else(|| "Empty commit message from model".to_string())?;
+        Ok(clean_message(&text))
+    }
+
+    /// Switch to the session branch if one is configured and not yet created.
+    async fn ensure_branch(&self) -> Result<Option<String>, String> {
+        let Some(ref prefix) = self.branch_prefix else {
+            return Ok(None);
+        };
+        if let Some(name) = self.branch.lock().ok().and_then(|b| b.clone()) {
+            return Ok(Some(name));
+        }
+        let stamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let name = format!("{prefix}{stamp}");
+        // `checkout -b` carries uncommitted changes over to the new branch.
+        let out = run_git(&self.workdir, &["checkout", "-b", &name]).await;
+        if !succeeded(&out) {
+            return Err(format!("could not create branch {name}: {out}"));
+        }
+        if let Ok(mut b) = self.branch.lock() {
+            *b = Some(name.clone());
+        }
+        Ok(Some(name))
+    }
+
+    /// Commit `paths` with `message`, on the session branch if configured.
+    pub async fn commit(&self, paths: &[String], message: &str) -> Result<String, String> {
+        let branch = self.ensure_branch().await?;
+        let out = commit_paths(&self.workdir, paths, message).await;
+        if !succeeded(&out) {
+            return Err(out);
+        }
+        self.changed.clear();
+        let first_line = message.lines().next().unwrap_or_default();
+        Ok(match branch {
+            Some(b) => format!("Committed on {b}: {first_line}"),
+            None => format!("Committed: {first_line}"),
+        })
+    }
+}
+
+/// Run the interactive `/commit` flow and return a status line for the
+/// output pane. `hint` is any text typed after `/commit`.
+pub async fn commit_interactively(
+    ui_state: &Arc<Mutex<UiState>>,
+    client: &OpenRouterClient,
+    workflow: &CommitWorkflow,
+    hint: &str,
+) -> String {
+    let paths = workflow.pending_paths();
+    if paths.is_empty() {
+        return "Nothing to commit: the agent has not changed any files.".into();
+    }
+    let diff = match workflow.diff(&paths).await {
+        Ok(d) if d.trim().is_empty() => {
+            return "Nothing to commit: the agent's files match HEAD.".into();
+        }
+        Ok(d) => d,
+        Err(e) => return format!("Commit failed: {e}"),
+    };
+    let message = match workflow.generate_message(client, &diff, hint).await {
+        Ok(m) => m,
+        Err(e) => return format!("Commit failed: could not generate message: {e}"),
+    };
+
+    let question = UserQuestion {
+        prompt: format!("Commit {} file(s)? (e to edit the message)", paths.len()),
+        choices: vec![
+            QuestionChoice {
+                label: "Commit".into(),
+                body: message.clone(),
+                metadata: paths.join(", "),
+            },
+            QuestionChoice {
+                label: "Cancel".into(),
+                body: diff,
+                metadata: String::new(),
+            },
+        ],
+        editable: true,
+        max_edit_length: None,
+        ..Default::default()
+    };
+    let message = match ask_and_wait(ui_state, question, APPROVAL_TIMEOUT_SECS).await {
+        QuestionResponse::Selected(0) => message,
+        QuestionResponse::SelectedEdited {
+            index: 0,
+            edited_text,
+        } => edited_text,
+        _ => return "Commit cancelled.".into(),
+    };
+
+    match workflow.commit(&paths, &message).await {
+        Ok(status) => status,
+        Err(e) => format!("Commit failed: {e}"),
+    }
+}
+
+/// Strip code fences and surrounding whitespace from a model reply.
+pub(crate) fn clean_message(text: &str) -> String {
+    let trimmed = text.trim();
+    let unfenced = trimmed
+        .strip_prefix("```")
+        .and_then(|rest| rest.strip_suffix("```"))
+        .map(|inner| inner.split_once('\n').map_or(inner, |(_, body)| body))
+        .unwrap_or(trimmed);
+    unfenced.trim().to_string()
+}
+
+pub(crate) fn truncate_chars(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((end, _)) => {
+            #[allow(clippy::string_slice)] // end from char_indices
+            let head = &text[..end];
+            format!("{head}\n[diff truncated]")
+        }
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cinch_rs::test_support::{ScriptedTransport, text_reply};
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(status.status.success(), "git {args:?} failed");
+    }
+
+    fn repo() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        git(dir.path(), &["init", "-q"]);
+        git(dir.path(), &["config", "user.email", "t@example.com"]);
+        git(dir.path(), &["config", "user.name", "t"]);
+        std::fs::write(dir.path().join("a.txt"), "a\n").unwrap();
+        git(dir.path(), &["add", "a.txt"]);
+        git(dir.path(), &["commit", "-qm", "init"]);
+        dir
+    }
+
+    #[test]
+    fn changed_files_records_mutations_only() {
+        let changed = ChangedFiles::new();
+        changed.record("edit_file", r#"{"path":"b.rs"}"#);
+        changed.record("read_file", r#"{"path":"c.rs"}"#);
+        changed.clone().record("write_file", r#"{"path":"a.rs"}"#);
+        changed.record("infra__edit_file", r#"{"path":"main.tf"}"#);
+        assert_eq!(changed.paths(), vec!["a.rs", "b.rs", "infra:main.tf"]);
+    }
+
+    #[test]
+    fn changed_files_follow_file_edited_events() {
+        let changed = ChangedFiles::new();
+        changed.on_event(&HarnessEvent::ToolExecuting {
+            name: "write_file",
+            arguments: r#"{"path":"failed.rs"}"#,
+            summary: "",
+        });
+        changed.on_event(&HarnessEvent::FileEdited {
+            name: "infra__edit_file",
+            path: "main.tf",
+            diff: "",
+            stats: Default::default(),
+        });
+        assert_eq!(changed.paths(), vec!["infra:main.tf"]);
+    }
+
+    #[test]
+    fn clean_message_strips_fences() {
+        assert_eq!(clean_message("```text\nfix: x\n```"), "fix: x");
+        assert_eq!(clean_message("  feat: y \n"), "feat: y");
+    }
+
+    #[tokio::test]
+    async fn commits_only_agent_files_on_session_branch() {
+        let dir = repo();
+        let workdir = dir.path().to_string_lossy().to_string();
+        std::fs::write(dir.path().join("a.txt"), "changed\n").unwrap();
+        std::fs::write(dir.path().join("new.txt"), "new\n").unwrap();
+        std::fs::write(dir.path().join("user.txt"), "mine\n").unwrap();
+        git(dir.path(), &["add", "user.txt"]);
+
+        let changed = ChangedFiles::new();
+        changed.record("edit_file", r#"{"path":"a.txt"}"#);
+        changed.record("write_file", r#"{"path":"new.txt"}"#);
+        changed.record("write_file", r#"{"path":"rejected.txt"}"#);
+        let wf = CommitWorkflow::new(&workdir, "m", changed.clone())
+            .with_branch_prefix(Some("cinch/".into()));
+
+        let paths = wf.pending_paths();
+        assert_eq!(paths, vec!["a.txt", "new.txt"]);
+        let diff = wf.diff(&paths).await.unwrap();
+        assert!(diff.contains("+changed") && diff.contains("+new"));
+
+        let status = wf.commit(&paths, "feat: add new").await.unwrap();
+        assert!(status.starts_with("Committed on cinch/"), "got: {status}");
+        assert!(changed.paths().is_empty());
+
+        let staged = run_git(&workdir, &["diff", "--cached", "--name-only"]).await;
+        assert_eq!(ToolOutput::parse(&staged).unwrap().stdout, "user.txt\n");
+    }
+    #[tokio::test]
+    async fn cancelled_commit_leaves_the_index_alone() {
+        let dir = repo();
+        let workdir = dir.path().to_string_lossy().to_string();
+        std::fs::write(dir.path().join("a.txt"), "changed\n").unwrap();
+        std::fs::write(dir.path().join("new.txt"), "new\n").unwrap();
+        let status = || async { run_git(&workdir, &["status", "--porcelain"]).await };
+        let before = status().await;
+
+        let changed = ChangedFiles::new();
+        changed.record("edit_file", r#"{"path":"a.txt"}"#);
+        changed.record("write_file", r#"{"path":"new.txt"}"#);
+        let wf = CommitWorkflow::new(&workdir, "m", changed);
+        let client = OpenRouterClient::with_transport(
+            "key",
+            ScriptedTransport::repeating(text_reply("feat: add new")),
+        );
+        // Quitting answers the approval question with `Skipped`.
+        let ui_state = Arc::new(Mutex::new(UiState::default()));
+        ui_state.lock().unwrap().quit_requested = true;
+
+        let outcome = commit_interactively(&ui_state, &client, &wf, "").await;
+        assert_eq!(outcome, "Commit cancelled.");
+        let strip = |s: String| ToolOutput::parse(&s).unwrap().stdout;
+        assert_eq!(strip(status().await), strip(before));
+        assert!(strip(status().await).contains("?? new.txt"));
+    }
+}
@@ -5,12 +5,26 @@
 //! and [`build_tool_set`](CodeConfig::build_tool_set).
 
 use std::path::PathBuf;
+use std::sync::Arc;
 
-use cinch_rs::agent::config::HarnessConfig;
-use cinch_rs::tools::core::ToolSet;
+use cinch_rs::ProviderSort;
+use cinch_rs::agent::approvals::ApprovalStore;
+use cinch_rs::agent::config::{HarnessConfig, HarnessGitContextConfig};
+use cinch_rs::tools::core::{CommonToolsConfig, ToolSet};
+use cinch_rs::tools::host::ShellEnv;
+use cinch_rs::tools::limits::{ProcessLimits, SubprocessBudget};
+use cinch_rs::tools::read_tracker::ReadTracker;
 
+use crate::approval::approvals_path;
+use crate::commit::{ChangedFiles, CommitWorkflow, DEFAULT_COMMIT_MODEL};
+use crate::pr_description::PrDescriptionWorkflow;
 use crate::prompt::coding_system_prompt;
-use crate::tools::{GIT_CHECKOUT, GIT_COMMIT, GitToolsExt};
+use crate::review::Changeset;
+use crate::roots::{WorkspaceRoot, roots_prompt_section};
+use crate::tools::staged::{StagedEditFile, StagedReadFile, StagedWriteFile};
+use crate::tools::{
+    APPROVAL_REQUIRED_GIT_TOOLS, CommandTool, CommandToolConfig, GitToolsExt, project_tools,
+};
 
 /// Configuration for a coding agent session.
 ///
@@ -27,10 +41,61 @@ pub struct CodeConfig {
     pub max_tokens: u32,
     /// Sampling temperature. Default: `0.3`.
     pub temperature: f32,
+    /// Stop a run once its estimated cost reaches this many USD.
+    /// Default: `None` (no limit).
+    pub max_cost_usd: Option<f64>,
+    /// How OpenRouter ranks providers for the model. Default: `None`
+    /// (OpenRouter's load balancing).
+    pub provider_sort: Option<ProviderSort>,
     /// Working directory for file/git tools. Default: `"."`.
     pub workdir: String,
+    /// Additional workspace roots, each with its own namespaced tools.
+    /// Default: empty. See [`crate::roots`].
+    pub extra_roots: Vec<WorkspaceRoot>,
     /// Enable streaming for LLM responses. Default: `true`.
     pub streaming: bool,
+    /// Shell command patterns blocked in addition to the cinch-rs defaults.
+    pub blocked_commands: Vec<String>,
+    /// Tools gated on approval in addition to the state-changing git tools.
+    pub approval_required_tools: Vec<String>,
+    /// Project-specific text appended to the coding system prompt.
+    pub system_prompt_extra: Option<String>,
+    /// Project-defined shell command tools.
+    pub command_tools: Vec<CommandToolConfig>,
+    /// Register `build`, `test`, and `lint` tools for the detected project
+    /// type (see [`crate::tools::project`]). Default: `true`.
+    pub project_tools: bool,
+    /// Stage file edits for review instead of writing them directly.
+    /// Default: `false`. See [`build_staged_tool_set`](Self::build_staged_tool_set).
+    pub review: bool,
+    /// Give the agent only non-mutating tools (see
+    /// [`ToolSet::read_only_view`]). Default: `false`.
+    pub read_only: bool,
+    /// Model used to write `/commit` messages and `/pr-description`
+    /// drafts. Default: `None`
+    /// ([`DEFAULT_COMMIT_MODEL`](crate::commit::DEFAULT_COMMIT_MODEL)).
+    pub commit_model: Option<String>,
+    /// When set, `/commit` moves the session's work to a new branch named
+    /// `<prefix><timestamp>` on first use. Default: `None`.
+    pub commit_branch_prefix: Option<String>,
+    /// Model that titles and summarizes each session for
+    /// `--list-sessions`. `None` disables titles. Default:
+    /// [`DEFAULT_COMMIT_MODEL`](crate::commit::DEFAULT_COMMIT_MODEL).
+    pub title_model: Option<String>,
+    /// Directories outside the workdir that file tools may access.
+    /// Default: empty.
+    pub allowed_paths: Vec<PathBuf>,
+    /// Environment for the `shell` tool. Default: the full host environment.
+    pub shell_env: ShellEnv,
+    /// CPU, memory, and output limits for shell and command tool
+    /// processes. Default: none.
+    pub process_limits: ProcessLimits,
+    /// Wall-time budget shared by every spawned process in a run, reset by
+    /// the harness when each run starts. Default: `None`.
+    pub subprocess_budget: Option<Arc<SubprocessBudget>>,
+    /// Write `audit.jsonl` of shell commands, file mutations, and network
+    /// calls into each session directory. Default: `false`.
+    pub audit_log: bool,
 }
 
 impl Default for CodeConfig {
@@ -40,8 +105,26 @@ impl Default for CodeConfig {
             max_rounds: 50,
             max_tokens: 16384,
             temperature: 0.3,
+            max_cost_usd: None,
+            provider_sort: None,
             workdir: ".".to_string(),
+            extra_roots: Vec::new(),
             streaming: true,
+            blocked_commands: Vec::new(),
+            approval_required_tools: Vec::new(),
+            system_prompt_extra: None,
+            command_tools: Vec::new(),
+            project_tools: true,
+            review: false,
+            read_only: false,
+            commit_model: None,
+            commit_branch_prefix: None,
+            title_model: Some(DEFAULT_COMMIT_MODEL.to_string()),
+            allowed_paths: Vec::new(),
+            shell_env: ShellEnv::default(),
+            process_limits: ProcessLimits::default(),
+            subprocess_budget: None,
+            audit_log: false,
         }
     }
 }
@@ -54,7 +137,9 @@ impl CodeConfig {
     /// - Project instructions from AGENTS.md hierarchy
     /// - MEMORY.md index loading from the project root
     /// - Session directories co-located with the project
-    /// - Approval gating on `git_commit`
+    /// - Approval gating on `git_commit`, with "always allow" decisions
+    ///   kept in [`APPROVALS_FILE`](crate::approval::APPROVALS_FILE) under the
+    ///   home directory
     ///
     /// Note: tool usage guidelines are injected automatically by the
     /// harness at run time via [`ToolSet::generate_guidelines`].
@@ -62,25 +147,156 @@ impl CodeConfig {
         let memory_file = PathBuf::from(&self.workdir).join("MEMORY.md");
         let sessions_dir = PathBuf::from(&self.workdir).join(".agents/sessions");
 
-        let mut config = HarnessConfig::new(self.model.clone(), coding_system_prompt())
+        let mut approval: Vec<String> = APPROVAL_REQUIRED_GIT_TOOLS
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+        for tool in &self.approval_required_tools {
+            if !approval.contains(tool) {
+                approval.push(tool.clone());
+            }
+        }
+        let extra_approval: Vec<String> = self
+            .extra_roots
+            .iter()
+            .flat_map(|root| approval.iter().map(|tool| root.tool_name(tool)))
+            .collect();
+        approval.extend(extra_approval);
+
+        let mut config = HarnessConfig::new(self.model.clone(), self.system_prompt())
             .with_max_rounds(self.max_rounds)
             .with_max_tokens(self.max_tokens)
             .with_temperature(self.temperature)
             .with_streaming(self.streaming)
             .with_project_root(&self.workdir)
             .with_memory_file(memory_file)
-            .with_approval_required_tools(vec![GIT_COMMIT.to_string(), GIT_CHECKOUT.to_string()]);
+            .with_approval_required_tools(approval)
+            .with_prompt_registry(true)
+            .with_git_context(HarnessGitContextConfig::for_workdir(&self.workdir));
 
         config.session.sessions_dir = sessions_dir;
+        config.session.audit_log = self.audit_log;
+        config.session.title_model = self.title_model.clone();
+        config.cache.workdir = PathBuf::from(&self.workdir);
+        config.max_cost_usd = self.max_cost_usd;
+        if let Some(sort) = self.provider_sort {
+            config = config.with_provider_sort(sort);
+        }
+        if let Some(ref budget) = self.subprocess_budget {
+            config = config.with_subprocess_budget(budget.clone());
+        }
+        if let Some(approvals) = approvals_path() {
+            match ApprovalStore::load_for_project(&approvals, &self.workdir) {
+                Ok(store) => config = config.with_approval_store(store),
+                Err(e) => tracing::warn!("Ignoring {}: {e}", approvals.display()),
+            }
+        }
 
         config
     }
 
-    /// Build a [`ToolSet`] with common filesystem tools and git tools.
+    /// The coding system prompt plus the workspace roots section (when there
+    /// are extra roots) and any project-specific additions.
+    pub fn system_prompt(&self) -> String {
+        let mut prompt = coding_system_prompt();
+        let roots = roots_prompt_section(&self.workdir, &self.extra_roots);
+        if !roots.is_empty() {
+            prompt.push_str("\n\n");
+            prompt.push_str(&roots);
+        }
+        if let Some(ref extra) = self.system_prompt_extra
+            && !extra.trim().is_empty()
+        {
+            prompt.push_str("\n\n");
+            prompt.push_str(extra.trim());
+        }
+        prompt
+    }
+
+    /// Build the `/commit` workflow for files recorded in `changed`.
+    pub fn build_commit_workflow(&self, changed: ChangedFiles) -> CommitWorkflow {
+        let model = self
+            .commit_model
+            .clone()
+            .unwrap_or_else(|| DEFAULT_COMMIT_MODEL.to_string());
+        CommitWorkflow::new(self.workdir.clone(), model, changed)
+            .with_branch_prefix(self.commit_branch_prefix.clone())
+    }
+
+    /// Build the `/pr-description` workflow. Drafts are written by the
+    /// commit model.
+    pub fn build_pr_description_workflow(&self) -> PrDescriptionWorkflow {
+        let model = self
+            .commit_model
+            .clone()
+            .unwrap_or_else(|| DEFAULT_COMMIT_MODEL.to_string());
+        PrDescriptionWorkflow::new(self.workdir.clone(), model)
+    }
+
+    /// Build a [`ToolSet`] with common filesystem tools, git tools, any
+    /// project-defined command tools, and namespaced tools for each extra
+    /// root.
     pub fn build_tool_set(&self) -> ToolSet {
-        ToolSet::new()
-            .with_common_tools(&self.workdir)
-            .with_git_tools(&self.workdir)
+        let mut common = CommonToolsConfig::default();
+        for pattern in &self.blocked_commands {
+            common = common.shell_block_command(pattern.to_lowercase());
+        }
+        for path in &self.allowed_paths {
+            common = common.allow_path(path.clone());
+        }
+        common = common
+            .shell_env(self.shell_env.clone())
+            .shell_limits(self.process_limits);
+        if let Some(ref budget) = self.subprocess_budget {
+            common = common.subprocess_budget(budget.clone());
+        }
+
+        let mut tools = ToolSet::new()
+            .with_common_tools_configured(&self.workdir, common.clone())
+            .with_git_tools(&self.workdir);
+        if self.project_tools {
+            for mut tool in project_tools(&self.workdir) {
+                tool = tool.limits(self.process_limits);
+                if let Some(ref budget) = self.subprocess_budget {
+                    tool = tool.budget(budget.clone());
+                }
+                tools = tools.with(tool);
+            }
+        }
+        // Registered after the project tools so a `[[tools]]` entry named
+        // `build`, `test`, or `lint` replaces the detected one.
+        for tool in &self.command_tools {
+            let mut command =
+                CommandTool::new(tool.clone(), self.workdir.clone()).limits(self.process_limits);
+            if let Some(ref budget) = self.subprocess_budget {
+                command = command.budget(budget.clone());
+            }
+            tools = tools.with(command);
+        }
+        for root in &self.extra_roots {
+            tools = root.add_tools(tools, &common);
+        }
+        tools
+    }
+
+    /// Like [`build_tool_set`](Self::build_tool_set), but `read_file`,
+    /// `edit_file`, and `write_file` stage changes in `changeset` instead of
+    /// writing to disk. Apply them with [`crate::review::review_changeset`].
+    pub fn build_staged_tool_set(&self, changeset: Arc<Changeset>) -> ToolSet {
+        let tracker = Arc::new(ReadTracker::new());
+        self.build_tool_set()
+            .with(
+                StagedReadFile::new(self.workdir.clone(), changeset.clone(), tracker.clone())
+                    .allowed_paths(self.allowed_paths.clone()),
+            )
+            .with(
+                StagedEditFile::new(self.workdir.clone(), changeset.clone(), tracker.clone())
+                    .allowed_paths(self.allowed_paths.clone()),
+            )
+            .with(
+                StagedWriteFile::new(self.workdir.clone(), changeset, tracker)
+                    .allowed_paths(self.allowed_paths.clone()),
+            )
     }
 }
 
@@ -140,6 +356,13 @@ mod tests {
             harness.session.sessions_dir,
             PathBuf::from("/tmp/test-project/.agents/sessions")
         );
+        assert!(!harness.session.audit_log);
+
+        let audited = CodeConfig {
+            audit_log: true,
+            ..Default::default()
+        };
+        assert!(audited.build_harness_config().session.audit_log);
     }
 
     #[test]
@@ -156,6 +379,102 @@ mod tests {
                 .approval_required_tools
                 .contains(&"git_checkout".to_string())
         );
+        for tool in ["git_stash", "git_restore", "git_cherry_pick"] {
+            assert!(harness.approval_required_tools.contains(&tool.to_string()));
+        }
+    }
+
+    #[test]
+    fn project_additions_flow_into_harness_config() {
+        let config = CodeConfig {
+            approval_required_tools: vec!["shell".into(), "git_commit".into()],
+            system_prompt_extra: Some("Use tabs.".into()),
+            ..Default::default()
+        };
+        let harness = config.build_harness_config();
+        let approval = &harness.approval_required_tools;
+        assert_eq!(approval.last().map(String::as_str), Some("shell"));
+        assert_eq!(approval.iter().filter(|t| *t == "git_commit").count(), 1);
+        assert!(harness.system_prompt.unwrap().ends_with("Use tabs."));
+    }
+
+    #[tokio::test]
+    async fn blocked_commands_are_enforced() {
+        let config = CodeConfig {
+            workdir: "/tmp".into(),
+            blocked_commands: vec!["Cargo Publish".into()],
+            ..Default::default()
+        };
+        let tools = config.build_tool_set();
+        let result = tools
+            .execute("shell", r#"{"command":"cargo publish --dry-run"}"#)
+            .await;
+        assert!(result.contains("blocked"), "got: {result}");
+    }
+
+    #[test]
+    fn build_tool_set_includes_command_tools() {
+        let config = CodeConfig {
+            command_tools: vec![CommandToolConfig {
+                name: "run_lints".into(),
+                description: "Run lints".into(),
+                command: "true".into(),
+                mutation: false,
+            }],
+            ..Default::default()
+        };
+        let defs = config.build_tool_set().definitions();
+        assert!(defs.iter().any(|d| d.function.name == "run_lints"));
+    }
+
+    #[test]
+    fn command_tools_replace_detected_project_tools() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("go.mod"), "module example.com/x\n").unwrap();
+        let config = CodeConfig {
+            workdir: dir.path().to_string_lossy().into_owned(),
+            command_tools: vec![CommandToolConfig {
+                name: "test".into(),
+                description: "Run the fast tests".into(),
+                command: "go test -short ./...".into(),
+                mutation: false,
+            }],
+            ..Default::default()
+        };
+        let defs = config.build_tool_set().definitions();
+        let description = |name: &str| {
+            defs.iter()
+                .find(|d| d.function.name == name)
+                .map(|d| d.function.description.clone())
+        };
+        assert!(description("build").unwrap().contains("`go build ./...`"));
+        assert_eq!(description("test").unwrap(), "Run the fast tests");
+    }
+
+    #[test]
+    fn extra_roots_get_namespaced_tools_and_approvals() {
+        let config = CodeConfig {
+            workdir: "/app".into(),
+            extra_roots: WorkspaceRoot::from_paths(&["/infra".into()], &[]),
+            ..Default::default()
+        };
+        let tools = config.build_tool_set();
+        assert!(tools.has_tool("read_file"));
+        assert!(tools.has_tool("infra__read_file"));
+        assert!(tools.has_tool("infra__git_diff"));
+
+        let harness = config.build_harness_config();
+        assert!(
+            harness
+                .approval_required_tools
+                .contains(&"infra__git_commit".to_string())
+        );
+        assert!(
+            harness
+                .system_prompt
+                .unwrap()
+                .contains("## Workspace roots")
+        );
     }
 
     #[test]
@@ -0,0 +1,371 @@
+//! `cinch-code init`: generate a starter `AGENTS.md`.
+//!
+//! Scans the repository root for build manifests and tool configs
+//! (Cargo, npm/pnpm/yarn, Python, Go, Make) and writes the build, test, and
+//! lint commands it finds, plus detected conventions, to `AGENTS.md`. The
+//! harness loads that file through
+//! [`ProjectInstructions`](cinch_rs::agent::project_instructions::ProjectInstructions)
+//! whenever a project root is set, so no further wiring is needed.
+
+use crate::tools::ProjectKind;
+use std::path::{Path, PathBuf};
+
+/// Commands and conventions detected in a repository.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepoScan {
+    pub build: Vec<String>,
+    pub test: Vec<String>,
+    pub lint: Vec<String>,
+    pub conventions: Vec<String>,
+}
+
+impl RepoScan {
+    /// Inspect the files at `root` (not recursive).
+    pub fn scan(root: &Path) -> Self {
+        let mut scan = Self::default();
+        scan.scan_cargo(root);
+        scan.scan_node(root);
+        scan.scan_python(root);
+        scan.scan_go(root);
+        scan.scan_make(root);
+        scan.scan_misc(root);
+        scan
+    }
+
+    /// Inspect only the manifests of one ecosystem at `root`.
+    pub fn scan_project(root: &Path, kind: ProjectKind) -> Self {
+        let mut scan = Self::default();
+        match kind {
+            ProjectKind::Cargo => scan.scan_cargo(root),
+            ProjectKind::Node => scan.scan_node(root),
+            ProjectKind::Python => scan.scan_python(root),
+            ProjectKind::Go => scan.scan_go(root),
+        }
+        scan
+    }
+
+    fn scan_cargo(&mut self, root: &Path) {
+        let Some(manifest) = read_toml(&root.join("Cargo.toml")) else {
+            return;
+        };
+        let ws = if manifest.get("workspace").is_some() {
+            " --workspace"
+        } else {
+            ""
+        };
+        push(&mut self.build, format!("cargo build{ws}"));
+        push(&mut self.test, format!("cargo test{ws}"));
+        push(
+            &mut self.lint,
+            format!("cargo clippy{ws} --all-targets -- -D warnings"),
+        );
+        push(&mut self.lint, "cargo fmt --all --check".into());
+
+        let edition = manifest
+            .get("package")
+            .or_else(|| manifest.get("workspace").and_then(|w| w.get("package")))
+            .and_then(|p| p.get("edition"))
+            .and_then(|e| e.as_str());
+        if let Some(edition) = edition {
+            push(&mut self.conventions, format!("Rust edition {edition}."));
+        }
+        if let Some(f) = first_existing(root, &["rust-toolchain.toml", "rust-toolchain"]) {
+            push(
+                &mut self.conventions,
+                format!("Rust toolchain is pinned in `{f}`."),
+            );
+        }
+        if let Some(f) = first_existing(root, &["rustfmt.toml", ".rustfmt.toml"]) {
+            push(
+                &mut self.conventions,
+                format!("Format Rust with rustfmt (settings in `{f}`)."),
+            );
+        }
+        if let Some(f) = first_existing(root, &["clippy.toml", ".clippy.toml"]) {
+            push(
+                &mut self.conventions,
+                format!("Clippy settings live in `{f}`."),
+            );
+        }
+    }
+
+    fn scan_node(&mut self, root: &Path) {
+        let Some(pkg) = std::fs::read_to_string(root.join("package.json"))
+            .ok()
+            .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+        else {
+            return;
+        };
+        let pm = if root.join("pnpm-lock.yaml").exists() {
+            "pnpm"
+        } else if root.join("yarn.lock").exists() {
+            "yarn"
+        } else if root.join("bun.lockb").exists() || root.join("bun.lock").exists() {
+            "bun"
+        } else {
+            "npm"
+        };
+        let scripts = pkg.get("scripts").and_then(|s| s.as_object());
+        let has = |name: &str| scripts.is_some_and(|s| s.contains_key(name));
+        let run = |name: &str| match (pm, name) {
+            ("npm", "test") => "npm test".to_string(),
+            ("npm", _) => format!("npm run {name}"),
+            _ => format!("{pm} {name}"),
+        };
+
+        push(&mut self.build, format!("{pm} install"));
+        if has("build") {
+            push(&mut self.build, run("build"));
+        }
+        if has("test") {
+            push(&mut self.test, run("test"));
+        }
+        for name in ["lint", "typecheck", "format"] {
+            if has(name) {
+                push(&mut self.lint, run(name));
+            }
+        }
+
+        push(
+            &mut self.conventions,
+            format!("Use `{pm}` for JavaScript dependencies."),
+        );
+        if root.join("tsconfig.json").exists() {
+            push(
+                &mut self.conventions,
+                "TypeScript project (`tsconfig.json`).".into(),
+            );
+        }
+        if let Some(f) = first_existing(
+            root,
+            &[
+                ".prettierrc",
+                ".prettierrc.json",
+                "prettier.config.js",
+                ".prettierrc.yaml",
+            ],
+        ) {
+            push(
+                &mut self.conventions,
+                format!("Format with Prettier (`{f}`)."),
+            );
+        }
+        if let Some(f) = first_existing(
+            root,
+            &[
+                "eslint.config.js",
+                "eslint.config.mjs",
+                ".eslintrc.json",
+                ".eslintrc.js",
+            ],
+        ) {
+            push(&mut self.conventions, format!("Lint with ESLint (`{f}`)."));
+        }
+    }
+
+    fn scan_python(&mut self, root: &Path) {
+        let pyproject = std::fs::read_to_string(root.join("pyproject.toml")).ok();
+        let has_setup = root.join("setup.py").exists();
+        if pyproject.is_none() && !has_setup {
+            return;
+        }
+        let text = pyproject.unwrap_or_default();
+        let prefix = if root.join("uv.lock").exists() {
+            "uv run "
+        } else if root.join("poetry.lock").exists() {
+            "poetry run "
+        } else {
+            ""
+        };
+
+        if text.contains("[build-system]") {
+            let build = if prefix == "poetry run " {
+                "poetry build"
+            } else {
+                "python -m build"
+            };
+            push(&mut self.build, build.into());
+        }
+        if text.contains("pytest")
+            || root.join("pytest.ini").exists()
+            || root.join("tests").is_dir()
+        {
+            push(&mut self.test, format!("{prefix}pytest"));
+        }
+        if text.contains("[tool.ruff") || root.join("ruff.toml").exists() {
+            push(&mut self.lint, format!("{prefix}ruff check ."));
+            push(&mut self.lint, format!("{prefix}ruff format --check ."));
+        }
+        if text.contains("[tool.mypy") || root.join("mypy.ini").exists() {
+            push(&mut self.lint, format!("{prefix}mypy ."));
+        }
+    }
+
+    fn scan_go(&mut self, root: &Path) {
+        if !root.join("go.mod").exists() {
+            return;
+        }
+        push(&mut self.build, "go build ./...".into());
+        push(&mut self.test, "go test ./...".into());
+        push(&mut self.lint, "go vet ./...".into());
+        push(&mut self.conventions, "Format Go with `gofmt`.".into());
+    }
+
+    fn scan_make(&mut self, root: &Path) {
+        let Ok(makefile) = std::fs::read_to_string(root.join("Makefile")) else {
+            return;
+        };
+        let targets: Vec<&str> = makefile
+            .lines()
+            .filter(|l| !l.starts_with(['\t', ' ', '#', '.']))
+            .filter_map(|l| l.split_once(':').map(|(t, _)| t.trim()))
+            .collect();
+        for target in ["build", "test", "lint", "check", "fmt"] {
+            if !targets.contains(&target) {
+                continue;
+            }
+            let list = match target {
+                "build" => &mut self.build,
+                "test" => &mut self.test,
+                _ => &mut self.lint,
+            };
+            push(list, format!("make {target}"));
+        }
+    }
+
+    fn scan_misc(&mut self, root: &Path) {
+        if root.join(".editorconfig").exists() {
+            push(&mut self.conventions, "Follow `.editorconfig`.".into());
+        }
+        if root.join(".github/workflows").is_dir() {
+            push(
+                &mut self.conventions,
+                "CI is defined in `.github/workflows/`; keep local checks in sync with it.".into(),
+            );
+        }
+        if root.join("CLAUDE.md").exists() {
+            push(
+                &mut self.conventions,
+                "`CLAUDE.md` is also loaded as project instructions.".into(),
+            );
+        }
+    }
+
+    /// Render the scan as `AGENTS.md` content.
+    pub fn render(&self) -> String {
+        let mut out = String::from(
+            "# AGENTS.md\n\n\
+             Instructions for coding agents working in this repository.\n\
+             Generated by `cinch-code init`; edit freely.\n",
+        );
+        for (heading, items, code) in [
+            ("Build", &self.build, true),
+            ("Test", &self.test, true),
+            ("Lint & format", &self.lint, true),
+            ("Conventions", &self.conventions, false),
+        ] {
+            out.push_str(&format!("\n## {heading}\n\n"));
+            if items.is_empty() {
+                out.push_str("_None detected; add them here._\n");
+            }
+            for item in items {
+                if code {
+                    out.push_str(&format!("- `{item}`\n"));
+                } else {
+                    out.push_str(&format!("- {item}\n"));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Write `AGENTS.md` at `root`. Refuses to overwrite unless `force`.
+pub fn run_init(root: &Path, force: bool) -> Result<PathBuf, String> {
+    let path = root.join("AGENTS.md");
+    if path.exists() && !force {
+        return Err(format!(
+            "{} already exists (use --force to overwrite)",
+            path.display()
+        ));
+    }
+    let content = RepoScan::scan(root).render();
+    std::fs::write(&path, content).map_err(|e| format!("cannot write {}: {e}", path.display()))?;
+    Ok(path)
+}
+
+fn push(list: &mut Vec<String>, item: String) {
+    if !list.contains(&item) {
+        list.push(item);
+    }
+}
+
+fn first_existing<'a>(root: &Path, names: &[&'a str]) -> Option<&'a str> {
+    names.iter().copied().find(|n| root.join(n).exists())
+}
+
+fn read_toml(path: &Path) -> Option<toml::Value> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|s| toml::from_str(&s).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cinch_rs::agent::project_instructions::ProjectInstructions;
+
+    #[test]
+    fn scans_cargo_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = []\n[workspace.package]\nedition = \"2024\"\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("rustfmt.toml"), "max_width = 100\n").unwrap();
+
+        let scan = RepoScan::scan(root);
+        assert_eq!(scan.build, vec!["cargo build --workspace"]);
+        assert_eq!(scan.test, vec!["cargo test --workspace"]);
+        assert!(scan.conventions.contains(&"Rust edition 2024.".to_string()));
+        assert!(scan.conventions.iter().any(|c| c.contains("rustfmt.toml")));
+    }
+
+    #[test]
+    fn scans_node_scripts_and_makefile() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(
+            root.join("package.json"),
+            r#"{"scripts":{"build":"tsc","test":"vitest","lint":"eslint ."}}"#,
+        )
+        .unwrap();
+        std::fs::write(root.join("pnpm-lock.yaml"), "").unwrap();
+        std::fs::write(root.join("Makefile"), "test:\n\tpnpm test\n.PHONY: test\n").unwrap();
+
+        let scan = RepoScan::scan(root);
+        assert_eq!(scan.build, vec!["pnpm install", "pnpm build"]);
+        assert_eq!(scan.test, vec!["pnpm test", "make test"]);
+        assert_eq!(scan.lint, vec!["pnpm lint"]);
+    }
+
+    #[test]
+    fn init_writes_loadable_agents_md() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("go.mod"), "module example.com/x\n").unwrap();
+
+        let path = run_init(root, false).unwrap();
+        assert!(run_init(root, false).is_err());
+        assert!(run_init(root, true).is_ok());
+
+        let content = std::fs::read_to_string(path).unwrap();
+        assert!(content.contains("- `go test ./...`"));
+        assert!(content.contains("## Conventions"));
+
+        let instructions = ProjectInstructions::load(Some(root));
+        assert!(instructions.prompt.contains("go test ./..."));
+    }
+}
@@ -0,0 +1,51 @@
+//! Small helpers for blocking on TUI questions from async code.
+
+use std::sync::{Arc, Mutex};
+
+use cinch_rs::ui::{QuestionResponse, UiState, UserQuestion, ask_question, poll_question};
+
+/// Poll interval while waiting for the user.
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(200);
+
+/// Ask `question` and wait for the answer.
+///
+/// Returns [`QuestionResponse::Skipped`] if the user quits while the
+/// question is open.
+pub(crate) async fn ask_and_wait(
+    ui_state: &Arc<Mutex<UiState>>,
+    question: UserQuestion,
+    timeout_secs: u64,
+) -> QuestionResponse {
+    ask_question(ui_state, question, timeout_secs);
+    loop {
+        if let Some(response) = poll_question(ui_state) {
+            return response;
+        }
+        if ui_state.lock().map(|s| s.quit_requested).unwrap_or(true) {
+            return QuestionResponse::Skipped;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// [`ask_and_wait`] for synchronous code such as event handlers. Parks the
+/// tokio worker with `block_in_place`, so it needs the multi-threaded
+/// runtime.
+pub(crate) fn ask_and_block(
+    ui_state: &Arc<Mutex<UiState>>,
+    question: UserQuestion,
+    timeout_secs: u64,
+) -> QuestionResponse {
+    ask_question(ui_state, question, timeout_secs);
+    tokio::task::block_in_place(|| {
+        loop {
+            if let Some(response) = poll_question(ui_state) {
+                return response;
+            }
+            if ui_state.lock().map(|s| s.quit_requested).unwrap_or(true) {
+                return QuestionResponse::Skipped;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    })
+}
@@ -32,12 +32,33 @@
 //!
 //! # Interactive mode (default)
 //! cinch-code --workdir /path/to/project
+//!
+//! # Headless one-shot mode for scripts and CI
+//! cinch-code --prompt "Fix the failing test" --output-format json
 //! ```
 
+pub mod approval;
+pub mod commands;
+pub mod commit;
 pub mod config;
+pub mod init;
+mod interact;
+pub mod output;
+pub mod pr_description;
+pub mod pr_review;
+pub mod project_config;
 pub mod prompt;
+pub mod review;
+pub mod roots;
 pub mod tools;
+pub mod watch;
 
+pub use commands::{CommandRegistry, SlashCommand, SlashInput};
+pub use commit::{ChangedFiles, CommitWorkflow};
 pub use config::CodeConfig;
+pub use init::RepoScan;
+pub use output::{JsonEventHandler, OutputFormat, RunSummary};
+pub use project_config::ProjectConfig;
 pub use prompt::coding_system_prompt;
+pub use review::Changeset;
 pub use tools::GitToolsExt;
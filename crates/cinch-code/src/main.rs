@@ -4,23 +4,118 @@
 //! and git tools. Reads the API key from the `OPENROUTER_KEY` environment
 //! variable.
 //!
+//! Markdown templates in `.cinch/commands/` become slash commands (`/review`,
+//! `/changelog`, ...); type `/help` at the input prompt to list them.
+//!
+//! With `--review`, file edits are staged and shown as a per-hunk diff review
+//! at the end of each turn; only accepted (or edited) hunks are written.
+//! `--read-only` instead hides every mutating tool (writes, shell, git
+//! commits), leaving the agent to read and search.
+//!
+//! `/commit` stages the files the agent changed, drafts a conventional-commit
+//! message with a cheap model, and commits after you approve (or edit) it.
+//! `/pr-description [base]` drafts a pull request title and body from the
+//! session's task, plan, test results, and the branch diff, and can open
+//! (or update) the pull request with `gh`.
+//!
+//! `cinch-code init` scans the repository and writes a starter `AGENTS.md`
+//! with detected build, test, and lint commands. `AGENTS.md` and `CLAUDE.md`
+//! files are loaded as project instructions on every run.
+//!
+//! Passing `--workdir` more than once adds extra roots (e.g. app + infra
+//! repos). Their tools are namespaced by root name (`infra__read_file`) and
+//! paths are relative to each root.
+//!
+//! `--watch <glob>` re-runs `--prompt` (headless) whenever matching files
+//! change, debounced so a burst of saves triggers one run.
+//!
+//! `.rhai` scripts in `.cinch/tools/` become extra tools (`fn run(...)`) and
+//! lifecycle hooks (`pre_tool_use`, `on_stop`, ...); see
+//! `cinch_rs::tools::script` for the format.
+//!
+//! `--agent <name>` runs as a named agent template: its prompt is added to
+//! the system prompt and its tool selection and settings apply. Built-ins are
+//! `researcher`, `coder`, `reviewer`, and `committer`; `.cinch/agents.toml`
+//! adds or overrides templates (see `cinch_rs::agent::templates`).
+//!
+//! `cinch-code review [--against main]` reviews the branch's diff with
+//! read-only tools and reports findings (severity, file, line, suggestion)
+//! in the TUI, or as JSON with `--output-format json` for CI annotations.
+//!
+//! `cinch-code export [SESSION]` writes a saved session as OpenAI Chat or
+//! Anthropic Messages JSON; `--import <file>` starts from a conversation in
+//! either format (see `cinch_rs::api::interop`).
+//!
+//! `cinch-code import-session <FILE>` converts a Claude Code or Codex session
+//! transcript into a saved session that `--resume` can continue.
+//!
+//! Per-project settings are read from the nearest `.cinch/config.toml` at or
+//! above the workdir; explicit CLI flags take precedence over the file.
+//!
 //! # Examples
 //!
 //! ```sh
 //! # Interactive mode (opens TUI)
 //! cinch-code --workdir /path/to/project
 //!
+//! # Interactive, printing inline instead of full-screen
+//! cinch-code --inline
+//!
 //! # One-shot mode
 //! cinch-code --prompt "Add error handling to src/main.rs"
+//!
+//! # Keep the API docs in sync with the route handlers
+//! cinch-code --prompt "Update docs/api.md for these route changes" --watch "src/routes/**"
+//!
+//! # CI review step: exits 0 on success, 1 on failure, 3 over budget
+//! git diff | cinch-code --prompt "review this diff" --no-tui --max-cost 0.50
+//!
+//! # Review the branch as the read-only reviewer agent
+//! cinch-code --agent reviewer --prompt "Review the changes on this branch"
+//!
+//! # Structured PR review for CI annotations (exits 1 on error findings)
+//! cinch-code review --against main --output-format json
+//!
+//! # Hand the latest session to other tooling, then pick it back up
+//! cinch-code export latest --format anthropic --output session.json
+//! cinch-code --import session.json
+//!
+//! # Headless one-shot mode (newline-delimited JSON events)
+//! cinch-code --prompt "Add error handling" --output-format stream-json
 //! ```
 
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
-use cinch_code::CodeConfig;
+use cinch_code::approval::ApprovalPrompt;
+use cinch_code::commit::commit_interactively;
+use cinch_code::output::{
+    EXIT_FAILURE, EXIT_USAGE, attach_piped_input, print_json_line, read_piped_stdin,
+};
+use cinch_code::pr_description::{PrDescriptionWorkflow, pr_description_interactively};
+use cinch_code::pr_review::{
+    Findings, REVIEW_SYSTEM_PROMPT, ReviewReport, load_diff, review_task, review_tools,
+};
+use cinch_code::review::review_changeset;
+use cinch_code::roots::WorkspaceRoot;
+use cinch_code::watch::{FileWatcher, watch_prompt};
+use cinch_code::{
+    ChangedFiles, Changeset, CodeConfig, CommandRegistry, CommitWorkflow, JsonEventHandler,
+    OutputFormat, ProjectConfig, RunSummary, SlashInput,
+};
+use cinch_rs::agent::LifecycleHookAdapter;
+use cinch_rs::agent::config_sources::HarnessSettings;
 use cinch_rs::agent::harness::Harness;
+use cinch_rs::agent::project_instructions::ProjectInstructions;
 use cinch_rs::agent::session::SessionManager;
+use cinch_rs::agent::session_import::ExternalFormat;
+use cinch_rs::agent::templates::{AgentTemplate, AgentTemplateRegistry};
+use cinch_rs::api::interop::{self, ChatFormat};
+use cinch_rs::context::summarizer::StructuredState;
 use cinch_rs::prelude::*;
+use cinch_rs::tools::script::{ScriptHooks, load_scripts};
+use cinch_rs::ui::tracing::LogFile;
+use cinch_rs::{MessageRole, ProviderSort};
 use clap::Parser;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
@@ -33,25 +128,46 @@ struct Cli {
     #[arg(long)]
     prompt: Option<String>,
 
-    /// Model to use for completions.
-    #[arg(long, default_value = "minimax/minimax-m2.5")]
-    model: String,
+    /// Model to use for completions [default: minimax/minimax-m2.5].
+    #[arg(long)]
+    model: Option<String>,
 
-    /// Working directory for file and git operations.
+    /// Working directory for file and git operations. Repeat to add more
+    /// roots (e.g. app + infra repos); the first is the primary root and the
+    /// others get namespaced tools such as `infra__read_file`.
     #[arg(long, default_value = ".")]
-    workdir: String,
+    workdir: Vec<String>,
+
+    /// Maximum agentic round-trips [default: 50].
+    #[arg(long)]
+    max_rounds: Option<u32>,
 
-    /// Maximum agentic round-trips.
-    #[arg(long, default_value_t = 50)]
-    max_rounds: u32,
+    /// Maximum tokens per LLM response [default: 16384].
+    #[arg(long)]
+    max_tokens: Option<u32>,
+
+    /// Sampling temperature [default: 0.3].
+    #[arg(long)]
+    temperature: Option<f32>,
+
+    /// Stop the run once its estimated cost reaches this many USD
+    /// (exit code 3).
+    #[arg(long, value_name = "USD")]
+    max_cost: Option<f64>,
 
-    /// Maximum tokens per LLM response.
-    #[arg(long, default_value_t = 16384)]
-    max_tokens: u32,
+    /// Rank OpenRouter providers by price, throughput, or latency.
+    #[arg(long, value_name = "SORT")]
+    provider_sort: Option<ProviderSort>,
 
-    /// Sampling temperature.
-    #[arg(long, default_value_t = 0.3)]
-    temperature: f32,
+    /// Run as a named agent template (researcher, coder, reviewer,
+    /// committer, or one defined in `.cinch/agents.toml`).
+    #[arg(long, value_name = "NAME")]
+    agent: Option<String>,
+
+    /// Run without the TUI and print the final response to stdout. Implied
+    /// by `--output-format json|stream-json` and by piped stdin.
+    #[arg(long)]
+    no_tui: bool,
 
     /// Resume a previous session. Pass a session/trace ID, or "latest" to
     /// resume the most recently updated session.
@@ -61,6 +177,112 @@ struct Cli {
     /// List saved sessions and exit.
     #[arg(long)]
     list_sessions: bool,
+
+    /// Start from a conversation exported as OpenAI Chat or Anthropic
+    /// Messages JSON, then continue it interactively.
+    #[arg(long, value_name = "FILE", conflicts_with = "resume")]
+    import: Option<PathBuf>,
+
+    /// Format of the `--import` file (openai or anthropic). Detected from
+    /// the file when omitted.
+    #[arg(long, value_name = "FORMAT", requires = "import")]
+    import_format: Option<ChatFormat>,
+
+    /// Disable the terminal bell on completion, errors, and questions.
+    #[arg(long)]
+    no_bell: bool,
+
+    /// Also post desktop notifications (requires the `desktop-notify` feature).
+    #[arg(long)]
+    desktop_notify: bool,
+
+    /// Print output inline on the normal screen instead of opening the
+    /// full-screen TUI.
+    #[arg(long)]
+    inline: bool,
+
+    /// Which log lines the TUI log pane captures, e.g.
+    /// `info,hyper=warn,cinch_rs::agent=debug`. Defaults to `info` with
+    /// HTTP client internals at `warn`.
+    #[arg(long, value_name = "DIRECTIVES", value_parser = LogFilter::parse)]
+    log_filter: Option<LogFilter>,
+
+    /// Also append captured log lines to this file, rotating it at 10 MiB
+    /// and keeping three old files.
+    #[arg(long, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+
+    /// Stage file edits and review them hunk by hunk at the end of each turn
+    /// before anything is written to disk.
+    #[arg(long)]
+    review: bool,
+
+    /// Hide every tool that writes files, runs commands, or changes git
+    /// state, so the agent can only read and search.
+    #[arg(long, conflicts_with = "review")]
+    read_only: bool,
+
+    /// Let file tools also access this directory outside the workdir.
+    /// Repeatable.
+    #[arg(long, value_name = "DIR")]
+    allow_path: Vec<PathBuf>,
+
+    /// Output format. `json` and `stream-json` skip the TUI and require
+    /// `--prompt`.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    output_format: OutputFormat,
+
+    /// Re-run `--prompt` whenever files matching this glob change (relative
+    /// to the workdir, e.g. "src/routes/**/*.rs"). Runs without the TUI;
+    /// each run gets the full `--max-rounds` / `--max-tokens` budget.
+    #[arg(long, value_name = "GLOB")]
+    watch: Option<String>,
+
+    /// Quiet period in milliseconds before a burst of changes triggers a run.
+    #[arg(long, default_value_t = 500, requires = "watch")]
+    watch_debounce_ms: u64,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Scan the repository and write a starter AGENTS.md.
+    Init {
+        /// Overwrite an existing AGENTS.md.
+        #[arg(long)]
+        force: bool,
+    },
+    /// Write a saved session's conversation as OpenAI Chat or Anthropic
+    /// Messages JSON.
+    Export {
+        /// Session/trace ID, or "latest".
+        #[arg(default_value = "latest")]
+        session: String,
+        /// Output format: openai or anthropic.
+        #[arg(long, default_value = "openai")]
+        format: ChatFormat,
+        /// Write to this file instead of stdout.
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Review the branch's changes against a base branch and report
+    /// findings (severity, file, line, suggestion). Uses read-only tools;
+    /// exits 1 when any finding is an error.
+    Review {
+        /// Base branch to diff against (from the merge base).
+        #[arg(long, default_value = cinch_code::pr_review::DEFAULT_BASE)]
+        against: String,
+    },
+    /// Import a Claude Code or Codex session transcript as a saved session.
+    ImportSession {
+        /// Session JSONL file to import.
+        path: PathBuf,
+        /// Transcript format: claude-code or codex (detected if omitted).
+        #[arg(long)]
+        format: Option<ExternalFormat>,
+    },
 }
 
 /// Detect the git repository root for the current directory.
@@ -73,6 +295,14 @@ fn detect_git_root() -> Option<String> {
         .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
 }
 
+/// Canonicalize `path`, falling back to the path as given.
+fn canonical_path(path: &str) -> String {
+    std::fs::canonicalize(path)
+        .unwrap_or_else(|_| PathBuf::from(path))
+        .to_string_lossy()
+        .to_string()
+}
+
 /// Load messages from a saved session checkpoint.
 ///
 /// Pass a trace ID directly, or `"latest"` to resolve the most recently
@@ -81,6 +311,7 @@ fn detect_git_root() -> Option<String> {
 struct ResumedSession {
     messages: Vec<Message>,
     trace_id: String,
+    structured_state: Option<StructuredState>,
 }
 
 fn load_session(sessions_dir: &std::path::Path, resume_id: &str) -> Result<ResumedSession, String> {
@@ -105,16 +336,279 @@ fn load_session(sessions_dir: &std::path::Path, resume_id: &str) -> Result<Resum
     Ok(ResumedSession {
         messages: checkpoint.messages,
         trace_id,
+        structured_state: checkpoint.structured_state,
     })
 }
 
+/// Export a saved session's messages as pretty-printed JSON.
+fn export_session(
+    sessions_dir: &std::path::Path,
+    session: &str,
+    format: ChatFormat,
+    output: Option<&std::path::Path>,
+) -> Result<(), String> {
+    let resumed = load_session(sessions_dir, session)?;
+    let exported = interop::export_messages(&resumed.messages, format);
+    let json = serde_json::to_string_pretty(&exported).map_err(|e| e.to_string())?;
+    match output {
+        Some(path) => std::fs::write(path, json + "\n")
+            .map_err(|e| format!("cannot write {}: {e}", path.display())),
+        None => {
+            println!("{json}");
+            Ok(())
+        }
+    }
+}
+
+/// Read a conversation exported by other tooling, detecting its format
+/// unless one is given.
+fn import_conversation(
+    path: &std::path::Path,
+    format: Option<ChatFormat>,
+) -> Result<Vec<Message>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+    let value: serde_json::Value =
+        serde_json::from_str(&text).map_err(|e| format!("{} is not JSON: {e}", path.display()))?;
+    let format = format.unwrap_or_else(|| ChatFormat::detect(&value));
+    interop::import_messages(&value, format)
+}
+
+/// Resolve `--agent`: the built-in templates, overridden by the project's
+/// `.cinch/agents.toml` when present.
+fn load_agent_template(workdir: &str, name: &str) -> Result<AgentTemplate, String> {
+    let mut registry = AgentTemplateRegistry::builtin();
+    let file = std::path::Path::new(workdir).join(".cinch/agents.toml");
+    if file.is_file() {
+        registry = registry.with_file(file)?;
+    }
+    registry.require(name).cloned()
+}
+
+/// Run a single prompt without the TUI.
+///
+/// `text` prints the final response to stdout with a one-line summary on
+/// stderr; `json` / `stream-json` print JSON lines. Returns the process
+/// exit code (see [`RunSummary::exit_code`]).
+async fn run_headless(
+    client: &OpenRouterClient,
+    tools: &ToolSet,
+    hooks: &ScriptHooks,
+    harness_config: HarnessConfig,
+    system_prompt: String,
+    prompt: String,
+    format: OutputFormat,
+) -> i32 {
+    let json = JsonEventHandler::new(format == OutputFormat::StreamJson);
+    let files_changed = json.changed_files();
+    let handler = CompositeEventHandler::new()
+        .with(json)
+        .with(LifecycleHookAdapter::new(hooks.clone()));
+    let messages = vec![Message::system(system_prompt), Message::user(prompt)];
+
+    match Harness::new(client, tools, harness_config)
+        .with_event_handler(&handler)
+        .run(messages)
+        .await
+    {
+        Ok(result) => {
+            let summary = RunSummary::from_result(&result, files_changed.paths());
+            if format == OutputFormat::Text {
+                println!("{}", summary.text);
+                let status = if summary.budget_exceeded {
+                    "stopped at cost limit"
+                } else if summary.finished {
+                    "finished"
+                } else {
+                    "round limit reached"
+                };
+                eprintln!(
+                    "[{status}] {} rounds, ${:.4}, {} file(s) edited",
+                    summary.rounds,
+                    summary.cost_usd,
+                    summary.files_changed.len()
+                );
+            } else {
+                print_json_line(&summary);
+            }
+            summary.exit_code()
+        }
+        Err(e) => {
+            if format == OutputFormat::Text {
+                eprintln!("Error: {e}");
+            } else {
+                print_json_line(&serde_json::json!({
+                    "type": "error",
+                    "error": e,
+                }));
+            }
+            EXIT_FAILURE
+        }
+    }
+}
+
+/// Start the TUI on its own thread, with tracing routed to its log pane.
+fn spawn_ui(workdir: &str, cli: &Cli) -> (Arc<Mutex<UiState>>, std::thread::JoinHandle<()>) {
+    // UI state shared between harness event handler and TUI.
+    let ui_state = Arc::new(Mutex::new(UiState::default()));
+
+    // Set up tracing → TUI log buffer.
+    let filter = cli.log_filter.clone().unwrap_or_default();
+    let (mut tracing_layer, log_buffer) = UiTracingLayer::with_filter(filter);
+    if let Some(ref path) = cli.log_file {
+        match LogFile::open(path) {
+            Ok(file) => tracing_layer = tracing_layer.with_log_file(file),
+            Err(e) => eprintln!("cannot open log file {}: {e}", path.display()),
+        }
+    }
+    tracing_subscriber::registry().with(tracing_layer).init();
+
+    let tui_config = cinch_tui::TuiConfig {
+        mode: if cli.inline {
+            cinch_tui::TuiMode::Inline
+        } else {
+            cinch_tui::TuiMode::FullScreen
+        },
+        workdir: PathBuf::from(workdir),
+        log_buffer: Some(log_buffer),
+        log_dir: PathBuf::from(workdir).join(".agents/logs"),
+        notifications: cinch_tui::NotificationConfig {
+            bell: !cli.no_bell,
+            desktop: cli.desktop_notify,
+            app_name: "cinch-code".into(),
+        },
+        ..Default::default()
+    };
+    let tui_handle = cinch_tui::spawn_tui(ui_state.clone(), tui_config);
+    (ui_state, tui_handle)
+}
+
+/// A `cinch-code review` run: the reviewer agent over the branch diff.
+struct ReviewRun<'a> {
+    client: &'a OpenRouterClient,
+    tools: &'a ToolSet,
+    hooks: &'a ScriptHooks,
+    against: &'a str,
+    diff: &'a str,
+}
+
+impl ReviewRun<'_> {
+    /// Run the reviewer and show its findings in the TUI when `ui_state` is
+    /// given, else print them (a [`ReviewReport`] line for JSON formats).
+    /// Returns the exit code.
+    async fn run(
+        &self,
+        harness_config: HarnessConfig,
+        format: OutputFormat,
+        ui_state: Option<&Arc<Mutex<UiState>>>,
+    ) -> i32 {
+        let findings = Findings::new();
+        let tools = review_tools(self.tools, &findings);
+        let mut handler =
+            CompositeEventHandler::new().with(LifecycleHookAdapter::new(self.hooks.clone()));
+        handler = match ui_state {
+            Some(ui_state) => {
+                push_user_message(ui_state, &format!("Review against {}", self.against));
+                handler.with(UiEventHandler::new(ui_state.clone()))
+            }
+            None => handler.with(JsonEventHandler::new(format == OutputFormat::StreamJson)),
+        };
+        let messages = vec![
+            Message::system(REVIEW_SYSTEM_PROMPT),
+            Message::user(review_task(self.against, self.diff)),
+        ];
+
+        let result = Harness::new(self.client, &tools, harness_config)
+            .with_event_handler(&handler)
+            .run(messages)
+            .await;
+        let summary = match result {
+            Ok(result) => result.text(),
+            Err(e) => {
+                match (ui_state, format) {
+                    (Some(ui_state), _) => push_agent_text(ui_state, &format!("Error: {e}")),
+                    (None, OutputFormat::Text) => eprintln!("Error: {e}"),
+                    (None, _) => print_json_line(&serde_json::json!({
+                        "type": "error",
+                        "error": e,
+                    })),
+                }
+                return EXIT_FAILURE;
+            }
+        };
+
+        let report = ReviewReport::new(self.against, summary, findings.sorted());
+        match (ui_state, format) {
+            (Some(ui_state), _) => push_agent_text(ui_state, &report.render()),
+            (None, OutputFormat::Text) => println!("{}", report.render()),
+            (None, _) => print_json_line(&report),
+        }
+        report.exit_code()
+    }
+}
+
+/// Re-run `prompt` each time the watcher reports a debounced batch of
+/// changes. Never returns; stop with Ctrl-C.
+#[allow(clippy::too_many_arguments)]
+async fn run_watch(
+    client: &OpenRouterClient,
+    tools: &ToolSet,
+    hooks: &ScriptHooks,
+    harness_config: HarnessConfig,
+    system_prompt: String,
+    prompt: String,
+    mut watcher: FileWatcher,
+    format: OutputFormat,
+) {
+    eprintln!("Watching {} (Ctrl-C to stop)", watcher.pattern());
+    loop {
+        let changed = watcher.next_batch().await;
+        eprintln!("{} file(s) changed, running...", changed.len());
+
+        let json = JsonEventHandler::new(format == OutputFormat::StreamJson);
+        let changed_files = json.changed_files();
+        let handler = CompositeEventHandler::new()
+            .with(json)
+            .with(LifecycleHookAdapter::new(hooks.clone()));
+        let messages = vec![
+            Message::system(system_prompt.clone()),
+            Message::user(watch_prompt(&prompt, &changed)),
+        ];
+        let result = Harness::new(client, tools, harness_config.clone())
+            .with_event_handler(&handler)
+            .run(messages)
+            .await;
+        let files_changed = changed_files.paths();
+        match result {
+            Ok(result) => {
+                let summary = RunSummary::from_result(&result, files_changed.clone());
+                if format == OutputFormat::Text {
+                    println!("{}", summary.text);
+                    eprintln!(
+                        "Run finished: {} rounds, ${:.4}, {} file(s) edited",
+                        summary.rounds,
+                        summary.cost_usd,
+                        summary.files_changed.len()
+                    );
+                } else {
+                    print_json_line(&summary);
+                }
+            }
+            Err(e) => eprintln!("Run failed: {e}"),
+        }
+        // Don't let the agent's own edits re-trigger the next run.
+        watcher.ignore(&files_changed);
+    }
+}
+
 /// Ask the user for free-text input via the TUI question system.
 async fn get_user_input(ui_state: &Arc<Mutex<UiState>>) -> Option<String> {
     let question = UserQuestion {
-        prompt: "Enter your message".to_string(),
+        prompt: "Enter your message (/help for commands)".to_string(),
         choices: vec![],
         editable: false,
         max_edit_length: None,
+        ..Default::default()
     };
     ask_question(ui_state, question, 86400);
 
@@ -133,12 +627,61 @@ async fn get_user_input(ui_state: &Arc<Mutex<UiState>>) -> Option<String> {
     }
 }
 
+/// What the interactive input loop needs to handle slash commands.
+struct SlashContext<'a> {
+    commands: &'a CommandRegistry,
+    client: &'a OpenRouterClient,
+    commit: &'a CommitWorkflow,
+    pr: &'a PrDescriptionWorkflow,
+}
+
+/// Read the next user turn, handling slash commands.
+///
+/// `/help`, `/commit`, `/pr-description`, and unknown commands are handled
+/// in place and the user is asked again. Returns the text as typed and the (possibly
+/// expanded) prompt to send.
+async fn next_user_turn(
+    ui_state: &Arc<Mutex<UiState>>,
+    ctx: &SlashContext<'_>,
+    messages: &[Message],
+) -> Option<(String, String)> {
+    loop {
+        let text = get_user_input(ui_state).await?;
+        match ctx.commands.resolve(&text) {
+            SlashInput::Text(prompt) => return Some((text, prompt)),
+            SlashInput::Expanded(prompt) => return Some((text, prompt)),
+            SlashInput::Help => push_agent_text(ui_state, &ctx.commands.help_text()),
+            SlashInput::Commit(note) => {
+                push_user_message(ui_state, &text);
+                update_phase(ui_state, "Preparing commit");
+                let status = commit_interactively(ui_state, ctx.client, ctx.commit, &note).await;
+                push_agent_text(ui_state, &status);
+                update_phase(ui_state, "Idle");
+            }
+            SlashInput::PrDescription(args) => {
+                push_user_message(ui_state, &text);
+                update_phase(ui_state, "Drafting PR description");
+                let status =
+                    pr_description_interactively(ui_state, ctx.client, ctx.pr, messages, &args)
+                        .await;
+                push_agent_text(ui_state, &status);
+                update_phase(ui_state, "Idle");
+            }
+            SlashInput::Unknown(name) => push_agent_text(
+                ui_state,
+                &format!("Unknown command /{name}. Type /help for the list."),
+            ),
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
 
     // Resolve working directory: git root > canonicalize > fallback.
-    let workdir = if cli.workdir == "." {
+    let primary = cli.workdir.first().map(String::as_str).unwrap_or(".");
+    let workdir = if primary == "." {
         detect_git_root()
             .or_else(|| {
                 std::fs::canonicalize(".")
@@ -147,11 +690,68 @@ async fn main() {
             })
             .unwrap_or_else(|| ".".to_string())
     } else {
-        std::fs::canonicalize(&cli.workdir)
-            .unwrap_or_else(|_| PathBuf::from(&cli.workdir))
-            .to_string_lossy()
-            .to_string()
+        canonical_path(primary)
     };
+    let extra_root_paths: Vec<String> = cli
+        .workdir
+        .iter()
+        .skip(1)
+        .map(|p| canonical_path(p))
+        .collect();
+
+    if let Some(Command::Init { force }) = cli.command {
+        let root = std::path::Path::new(&workdir);
+        match cinch_code::init::run_init(root, force) {
+            Ok(path) => {
+                let loaded = ProjectInstructions::load(Some(root));
+                println!(
+                    "Wrote {} ({} bytes of project instructions will be loaded).",
+                    path.display(),
+                    loaded.prompt.len()
+                );
+            }
+            Err(e) => {
+                eprintln!("Error: {e}");
+                std::process::exit(EXIT_FAILURE);
+            }
+        }
+        return;
+    }
+
+    if let Some(Command::Export {
+        ref session,
+        format,
+        ref output,
+    }) = cli.command
+    {
+        let sessions_dir = PathBuf::from(&workdir).join(".agents/sessions");
+        if let Err(e) = export_session(&sessions_dir, session, format, output.as_deref()) {
+            eprintln!("Error: {e}");
+            std::process::exit(EXIT_FAILURE);
+        }
+        return;
+    }
+
+    if let Some(Command::ImportSession { ref path, format }) = cli.command {
+        let sessions_dir = PathBuf::from(&workdir).join(".agents/sessions");
+        let imported = SessionManager::new(&sessions_dir)
+            .map_err(|e| format!("cannot open sessions dir: {e}"))
+            .and_then(|mgr| mgr.import_external(path, format));
+        match imported {
+            Ok(manifest) => {
+                println!(
+                    "Imported {} rounds as session {}",
+                    manifest.last_round, manifest.trace_id
+                );
+                println!("\nResume with: cinch-code --resume {}", manifest.trace_id);
+            }
+            Err(e) => {
+                eprintln!("Error: {e}");
+                std::process::exit(EXIT_FAILURE);
+            }
+        }
+        return;
+    }
 
     // Handle --list-sessions before any TUI/API setup.
     if cli.list_sessions {
@@ -163,11 +763,15 @@ async fn main() {
                         println!("No saved sessions.");
                     } else {
                         sessions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
-                        println!("{:<40} {:>6}  PREVIEW", "SESSION ID", "ROUNDS");
+                        println!("{:<40} {:>6}  TITLE", "SESSION ID", "ROUNDS");
                         println!("{}", "-".repeat(80));
                         for s in &sessions {
-                            let preview: String = s.message_preview.chars().take(60).collect();
-                            println!("{:<40} {:>6}  {}", s.trace_id, s.last_round, preview);
+                            let title = s.title.as_deref().unwrap_or(&s.message_preview);
+                            let title: String = title.chars().take(60).collect();
+                            println!("{:<40} {:>6}  {}", s.trace_id, s.last_round, title);
+                            if let Some(ref summary) = s.summary {
+                                println!("{:<49}{summary}", "");
+                            }
                         }
                         println!("\nResume with: cinch-code --resume <SESSION ID>");
                     }
@@ -179,25 +783,160 @@ async fn main() {
         return;
     }
 
-    // Build config from CLI args.
-    let config = CodeConfig {
-        model: cli.model,
-        max_rounds: cli.max_rounds,
-        max_tokens: cli.max_tokens,
-        temperature: cli.temperature,
+    // Build config: defaults < .cinch/config.toml < CLI flags.
+    let mut config = CodeConfig {
         workdir: workdir.clone(),
-        streaming: true,
+        extra_roots: WorkspaceRoot::from_paths(&extra_root_paths, &[]),
+        ..Default::default()
     };
+    match ProjectConfig::discover_and_load(std::path::Path::new(&workdir)) {
+        Ok(Some((path, project))) => {
+            tracing::debug!("Loaded project config from {}", path.display());
+            project.apply_to(&mut config);
+        }
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("Error: {e}");
+            std::process::exit(EXIT_USAGE);
+        }
+    }
+    if let Some(ref model) = cli.model {
+        config.model = model.clone();
+    }
+    if let Some(v) = cli.max_rounds {
+        config.max_rounds = v;
+    }
+    if let Some(v) = cli.max_tokens {
+        config.max_tokens = v;
+    }
+    if let Some(v) = cli.temperature {
+        config.temperature = v;
+    }
+    if let Some(v) = cli.max_cost {
+        config.max_cost_usd = Some(v);
+    }
+    if let Some(v) = cli.provider_sort {
+        config.provider_sort = Some(v);
+    }
 
-    let tools = config.build_tool_set();
-    let harness_config = config.build_harness_config();
+    for dir in &cli.allow_path {
+        config
+            .allowed_paths
+            .push(std::path::Path::new(&workdir).join(dir));
+    }
+    if cli.review {
+        config.review = true;
+    }
+    if cli.read_only {
+        config.read_only = true;
+    }
+
+    let template = match cli
+        .agent
+        .as_deref()
+        .map(|name| load_agent_template(&workdir, name))
+    {
+        None => None,
+        Some(Ok(template)) => Some(template),
+        Some(Err(e)) => {
+            eprintln!("Error: {e}");
+            std::process::exit(EXIT_USAGE);
+        }
+    };
+    if let Some(ref template) = template {
+        let role = format!(
+            "## Agent role: {}\n\n{}",
+            template.name, template.system_prompt
+        );
+        config.system_prompt_extra = Some(match config.system_prompt_extra.take() {
+            Some(extra) => format!("{role}\n\n{extra}"),
+            None => role,
+        });
+    }
+
+    if config.review && !config.extra_roots.is_empty() {
+        eprintln!("Error: review mode supports a single --workdir");
+        std::process::exit(EXIT_USAGE);
+    }
+    if config.review && config.read_only {
+        eprintln!("Error: review mode and read-only mode can't be combined");
+        std::process::exit(EXIT_USAGE);
+    }
+    let changeset = config.review.then(|| Arc::new(Changeset::new()));
+    let mut tools = match changeset {
+        Some(ref cs) => config.build_staged_tool_set(cs.clone()),
+        None => config.build_tool_set(),
+    };
+    let scripts = match load_scripts(std::path::Path::new(&workdir).join(".cinch/tools")) {
+        Ok(scripts) => scripts,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            std::process::exit(EXIT_USAGE);
+        }
+    };
+    if let Err(e) = scripts.register_tools(&tools) {
+        eprintln!("Error: {e}");
+        std::process::exit(EXIT_USAGE);
+    }
+    let hooks = scripts.hooks;
+    let mut harness_config = config.build_harness_config();
+    if let Some(ref template) = template {
+        tools = match template.tool_set(&tools) {
+            Ok(selected) => selected,
+            Err(e) => {
+                eprintln!("Error: {e}");
+                std::process::exit(EXIT_USAGE);
+            }
+        };
+        template.apply_settings(&mut harness_config);
+        // Explicit flags still win over the template.
+        HarnessSettings {
+            model: cli.model.clone(),
+            max_rounds: cli.max_rounds,
+            max_tokens: cli.max_tokens,
+            temperature: cli.temperature,
+            max_cost_usd: cli.max_cost,
+            provider_sort: cli.provider_sort,
+            ..Default::default()
+        }
+        .apply(&mut harness_config);
+        harness_config.agent_name = Some(template.name.clone());
+    }
+    if config.read_only {
+        tools = tools.read_only_view();
+    }
+
+    // Slash commands apply to --prompt as well as interactive input.
+    let commands = CommandRegistry::discover(std::path::Path::new(&workdir));
+    let initial_prompt = match cli.prompt.as_deref().map(|p| commands.resolve(p)) {
+        None => None,
+        Some(SlashInput::Text(prompt)) | Some(SlashInput::Expanded(prompt)) => {
+            cli.prompt.clone().map(|typed| (typed, prompt))
+        }
+        Some(SlashInput::Help) => {
+            println!("{}", commands.help_text());
+            return;
+        }
+        Some(SlashInput::Unknown(name)) => {
+            eprintln!("Error: unknown command /{name}");
+            std::process::exit(EXIT_USAGE);
+        }
+        Some(SlashInput::Commit(_)) => {
+            eprintln!("Error: /commit is only available at the interactive prompt");
+            std::process::exit(EXIT_USAGE);
+        }
+        Some(SlashInput::PrDescription(_)) => {
+            eprintln!("Error: /pr-description is only available at the interactive prompt");
+            std::process::exit(EXIT_USAGE);
+        }
+    };
 
     // API client.
     let api_key = match std::env::var("OPENROUTER_KEY") {
         Ok(key) => key,
         Err(_) => {
             eprintln!("Error: OPENROUTER_KEY environment variable is not set");
-            std::process::exit(1);
+            std::process::exit(EXIT_FAILURE);
         }
     };
 
@@ -209,29 +948,114 @@ async fn main() {
         Ok(c) => c,
         Err(e) => {
             eprintln!("Error: failed to create API client: {e}");
-            std::process::exit(1);
+            std::process::exit(EXIT_FAILURE);
         }
     };
 
-    // UI state shared between harness event handler and TUI.
-    let ui_state = Arc::new(Mutex::new(UiState::default()));
+    if let Some(Command::Review { ref against }) = cli.command {
+        if changeset.is_some() {
+            eprintln!("Error: `review` can't stage edits; drop --review or `review` from config");
+            std::process::exit(EXIT_USAGE);
+        }
+        let diff = match load_diff(&workdir, against).await {
+            Ok(diff) => diff,
+            Err(e) => {
+                eprintln!("Error: {e}");
+                std::process::exit(EXIT_FAILURE);
+            }
+        };
+        let review = ReviewRun {
+            client: &client,
+            tools: &tools,
+            hooks: &hooks,
+            against,
+            diff: &diff,
+        };
+        let code = if cli.no_tui || cli.output_format != OutputFormat::Text {
+            review.run(harness_config, cli.output_format, None).await
+        } else {
+            let (ui_state, tui_handle) = spawn_ui(&workdir, &cli);
+            let code = review
+                .run(harness_config, cli.output_format, Some(&ui_state))
+                .await;
+            ui_state.lock().unwrap().running = false;
+            tui_handle.join().ok();
+            code
+        };
+        std::process::exit(code);
+    }
 
-    // Set up tracing → TUI log buffer.
-    let (tracing_layer, log_buffer) = UiTracingLayer::new();
-    tracing_subscriber::registry().with(tracing_layer).init();
+    // Watch mode: re-run the prompt on every debounced change.
+    if let Some(pattern) = cli.watch {
+        let Some((_, prompt)) = initial_prompt else {
+            eprintln!("Error: --watch requires --prompt");
+            std::process::exit(EXIT_USAGE);
+        };
+        if changeset.is_some() {
+            eprintln!("Error: review mode needs the TUI; drop --review or `review` from config");
+            std::process::exit(EXIT_USAGE);
+        }
+        run_watch(
+            &client,
+            &tools,
+            &hooks,
+            harness_config,
+            config.system_prompt(),
+            prompt,
+            FileWatcher::new(&workdir, pattern)
+                .with_debounce(std::time::Duration::from_millis(cli.watch_debounce_ms)),
+            cli.output_format,
+        )
+        .await;
+        return;
+    }
 
-    // Spawn TUI on a dedicated thread.
-    let tui_config = cinch_tui::TuiConfig {
-        workdir: PathBuf::from(&workdir),
-        log_buffer: Some(log_buffer),
-        ..Default::default()
-    };
-    let tui_handle = cinch_tui::spawn_tui(ui_state.clone(), tui_config);
+    // Headless mode: no TUI; the prompt comes from --prompt and/or stdin.
+    let piped_input = read_piped_stdin();
+    if cli.no_tui || cli.output_format != OutputFormat::Text || piped_input.is_some() {
+        let Some(prompt) = attach_piped_input(initial_prompt.map(|(_, p)| p), piped_input) else {
+            eprintln!("Error: running without the TUI requires --prompt or piped stdin");
+            std::process::exit(EXIT_USAGE);
+        };
+        if changeset.is_some() {
+            eprintln!("Error: review mode needs the TUI; drop --review or `review` from config");
+            std::process::exit(EXIT_USAGE);
+        }
+        let code = run_headless(
+            &client,
+            &tools,
+            &hooks,
+            harness_config,
+            config.system_prompt(),
+            prompt,
+            cli.output_format,
+        )
+        .await;
+        std::process::exit(code);
+    }
+
+    let (ui_state, tui_handle) = spawn_ui(&workdir, &cli);
 
     // Event handler: UI state updater.
-    let ui_handler = UiEventHandler::new(ui_state.clone());
+    // Changed-file tracking feeds `/commit`.
+    let changed_files = ChangedFiles::new();
+    let ui_handler = CompositeEventHandler::new()
+        .with(UiEventHandler::new(ui_state.clone()))
+        .with(changed_files.clone())
+        .with(LifecycleHookAdapter::new(hooks))
+        .with(ApprovalPrompt::new(ui_state.clone()));
+    let commit_workflow = config.build_commit_workflow(changed_files);
+    let pr_workflow = config.build_pr_description_workflow();
+    let slash = SlashContext {
+        commands: &commands,
+        client: &client,
+        commit: &commit_workflow,
+        pr: &pr_workflow,
+    };
 
     // Conversation loop — optionally resume from a previous session.
+    // The summarizer state is handed to the first run only.
+    let mut resumed_state = None;
     let mut messages = if let Some(ref resume_id) = cli.resume {
         match load_session(&harness_config.session.sessions_dir, resume_id) {
             Ok(resumed) => {
@@ -243,6 +1067,7 @@ async fn main() {
                         resumed.messages.len()
                     ),
                 );
+                resumed_state = resumed.structured_state;
                 resumed.messages
             }
             Err(e) => {
@@ -252,28 +1077,54 @@ async fn main() {
                 return;
             }
         }
+    } else if let Some(ref path) = cli.import {
+        match import_conversation(path, cli.import_format) {
+            Ok(mut imported) => {
+                push_agent_text(
+                    &ui_state,
+                    &format!(
+                        "Imported {} messages from {}",
+                        imported.len(),
+                        path.display()
+                    ),
+                );
+                if imported
+                    .first()
+                    .is_none_or(|m| m.role != MessageRole::System)
+                {
+                    imported.insert(0, Message::system(config.system_prompt()));
+                }
+                imported
+            }
+            Err(e) => {
+                push_agent_text(&ui_state, &format!("Failed to import conversation: {e}"));
+                ui_state.lock().unwrap().running = false;
+                tui_handle.join().ok();
+                return;
+            }
+        }
     } else {
-        vec![Message::system(cinch_code::coding_system_prompt())]
+        vec![Message::system(config.system_prompt())]
     };
 
     // First turn: when resuming, ask for user input first; otherwise use
     // --prompt or interactive input.
     {
-        let first_prompt = if cli.resume.is_some() {
+        let (typed, first_prompt) = if cli.resume.is_some() || cli.import.is_some() {
             // Resuming — get a new user message to continue the conversation.
-            match get_user_input(&ui_state).await {
-                Some(text) => text,
+            match next_user_turn(&ui_state, &slash, &messages).await {
+                Some(turn) => turn,
                 None => {
                     ui_state.lock().unwrap().quit_requested = true;
                     tui_handle.join().ok();
                     return;
                 }
             }
-        } else if let Some(p) = cli.prompt {
-            p
+        } else if let Some(turn) = initial_prompt {
+            turn
         } else {
-            match get_user_input(&ui_state).await {
-                Some(text) => text,
+            match next_user_turn(&ui_state, &slash, &messages).await {
+                Some(turn) => turn,
                 None => {
                     ui_state.lock().unwrap().quit_requested = true;
                     tui_handle.join().ok();
@@ -282,7 +1133,7 @@ async fn main() {
             }
         };
 
-        push_user_message(&ui_state, &first_prompt);
+        push_user_message(&ui_state, &typed);
         messages.push(Message::user(&first_prompt));
     }
 
@@ -297,19 +1148,28 @@ async fn main() {
         // Run the agent harness for this turn, retrying on transient API errors.
         const MAX_RETRIES: u32 = 3;
         let mut attempt = 0;
+        // The harness assembles its sections (git status among them) into
+        // the system message, so start every turn from the bare prompt.
+        if let Some(system) = messages.iter_mut().find(|m| m.role == MessageRole::System) {
+            system.content = Some(config.system_prompt());
+        }
         let turn_ok = loop {
             attempt += 1;
-            let result = Harness::new(&client, &tools, harness_config.clone())
+            let harness = Harness::new(&client, &tools, harness_config.clone())
                 .with_event_handler(&ui_handler)
                 .with_stop_signal(|| {
                     let s = ui_state_stop.lock().unwrap();
                     s.quit_requested || s.interrupt_requested
-                })
-                .run(messages.clone())
-                .await;
+                });
+            let harness = match resumed_state.clone() {
+                Some(state) => harness.with_structured_state(state),
+                None => harness,
+            };
+            let result = harness.run(messages.clone()).await;
 
             match result {
                 Ok(r) => {
+                    resumed_state = None;
                     let text = r.text();
                     let session_id = r.trace_id.clone();
                     messages = r.messages;
@@ -339,6 +1199,7 @@ async fn main() {
                             &ui_state,
                             &format!("Error (attempt {attempt}/{MAX_RETRIES}): {e}"),
                         );
+                        update_phase(&ui_state, &format!("Error: {e}"));
                         break false;
                     }
                     push_agent_text(
@@ -373,11 +1234,27 @@ async fn main() {
             break;
         }
 
+        // Review staged edits before anything touches disk. The outcome is
+        // passed to the model with the next message so it knows what landed.
+        let mut review_note = None;
+        if let Some(ref cs) = changeset
+            && !cs.is_empty()
+        {
+            let outcome = review_changeset(&ui_state, cs, std::path::Path::new(&workdir)).await;
+            let summary = outcome.summary();
+            push_agent_text(&ui_state, &summary);
+            review_note = Some(summary);
+        }
+
         // Get next user input.
-        match get_user_input(&ui_state).await {
-            Some(text) => {
-                push_user_message(&ui_state, &text);
-                messages.push(Message::user(&text));
+        match next_user_turn(&ui_state, &slash, &messages).await {
+            Some((typed, prompt)) => {
+                push_user_message(&ui_state, &typed);
+                let prompt = match review_note {
+                    Some(note) => format!("[{note}]\n\n{prompt}"),
+                    None => prompt,
+                };
+                messages.push(Message::user(&prompt));
             }
             None => break,
         }
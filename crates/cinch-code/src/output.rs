@@ -0,0 +1,385 @@
+//! Headless output modes for scripted (CI) use.
+//!
+//! In one-shot mode `cinch-code` can skip the TUI and write machine-readable
+//! output to stdout instead:
+//!
+//! - [`OutputFormat::Json`] — a single [`RunSummary`] object when the run ends.
+//! - [`OutputFormat::StreamJson`] — one JSON object per line as harness events
+//!   arrive (via [`JsonEventHandler`]), followed by the summary.
+//!
+//! With `--no-tui`, [`OutputFormat::Text`] prints the agent's final response
+//! to stdout instead. Piped stdin is attached to the prompt (see
+//! [`attach_piped_input`]), and the process exit code reports the outcome
+//! ([`RunSummary::exit_code`]), so `git diff | cinch-code --prompt "review"
+//! --no-tui --max-cost 0.50` works as a CI step.
+
+use std::io::{IsTerminal, Read, Write};
+
+use cinch_rs::agent::events::{EventHandler, EventResponse, HarnessEvent, HarnessResult};
+use cinch_rs::tools::output::ToolOutput;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::commit::ChangedFiles;
+
+/// The agent finished on its own.
+pub const EXIT_SUCCESS: i32 = 0;
+/// The run failed or hit the round limit without finishing.
+pub const EXIT_FAILURE: i32 = 1;
+/// Invalid arguments or configuration.
+pub const EXIT_USAGE: i32 = 2;
+/// The run was stopped by `--max-cost`.
+pub const EXIT_BUDGET_EXCEEDED: i32 = 3;
+
+/// Output format for one-shot runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Interactive TUI (default), or plain text with `--no-tui`.
+    #[default]
+    Text,
+    /// Print a single JSON summary when the run finishes.
+    Json,
+    /// Print newline-delimited JSON events, then the summary.
+    StreamJson,
+}
+
+/// Final structured result of a headless run.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSummary {
+    /// Always `"result"`, so stream consumers can tell it apart from events.
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub trace_id: String,
+    /// Concatenated LLM text output.
+    pub text: String,
+    /// Whether the agent finished naturally (vs hitting the round limit).
+    pub finished: bool,
+    /// Whether the run was stopped by the cost limit.
+    pub budget_exceeded: bool,
+    pub rounds: u32,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// Total cost, including paid tool calls.
+    pub cost_usd: f64,
+    /// Part of `cost_usd` reported by paid tools.
+    pub tool_cost_usd: f64,
+    /// Fraction of request content a provider prompt cache could reuse.
+    pub prompt_prefix_reuse: f64,
+    /// Rounds whose request broke the previous round's prompt prefix.
+    pub prompt_prefix_breaks: usize,
+    /// Workspace-relative paths written or edited during the run.
+    pub files_changed: Vec<String>,
+}
+
+impl RunSummary {
+    /// Build a summary from a harness result and the set of touched files.
+    pub fn from_result(result: &HarnessResult, files_changed: Vec<String>) -> Self {
+        Self {
+            kind: "result",
+            trace_id: result.trace_id.clone(),
+            text: result.text(),
+            finished: result.finished,
+            budget_exceeded: result.budget_exceeded,
+            rounds: result.rounds_used,
+            prompt_tokens: result.total_prompt_tokens,
+            completion_tokens: result.total_completion_tokens,
+            cost_usd: result.estimated_cost_usd,
+            tool_cost_usd: result.tool_costs_usd.values().sum(),
+            prompt_prefix_reuse: result.prompt_cache.reuse_ratio(),
+            prompt_prefix_breaks: result.prompt_cache.breaks.len(),
+            files_changed,
+        }
+    }
+
+    /// Process exit code for this outcome.
+    pub fn exit_code(&self) -> i32 {
+        if self.budget_exceeded {
+            EXIT_BUDGET_EXCEEDED
+        } else if self.finished {
+            EXIT_SUCCESS
+        } else {
+            EXIT_FAILURE
+        }
+    }
+}
+
+/// Read all of stdin if it is piped (not a terminal). Returns `None` for a
+/// terminal or empty input.
+pub fn read_piped_stdin() -> Option<String> {
+    let mut stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        return None;
+    }
+    let mut input = String::new();
+    stdin.read_to_string(&mut input).ok()?;
+    (!input.trim().is_empty()).then_some(input)
+}
+
+/// Combine the prompt with piped input: the input is appended in a
+/// `<stdin>` block, or used as the prompt on its own when there is none.
+pub fn attach_piped_input(prompt: Option<String>, input: Option<String>) -> Option<String> {
+    match (prompt, input) {
+        (Some(prompt), Some(input)) => Some(format!(
+            "{prompt}\n\n<stdin>\n{}\n</stdin>",
+            input.trim_end()
+        )),
+        (prompt, input) => prompt.or(input),
+    }
+}
+
+/// Event handler for headless runs.
+///
+/// Always records which files the file-mutation tools touched. When
+/// `stream` is `true`, also prints each relevant event as a JSON line.
+pub struct JsonEventHandler {
+    stream: bool,
+    files_changed: ChangedFiles,
+}
+
+impl JsonEventHandler {
+    pub fn new(stream: bool) -> Self {
+        Self {
+            stream,
+            files_changed: ChangedFiles::new(),
+        }
+    }
+
+    /// Paths passed to `write_file` / `edit_file` so far, sorted.
+    pub fn files_changed(&self) -> Vec<String> {
+        self.files_changed.paths()
+    }
+
+    /// Shared handle to the changed-file set, readable after the handler
+    /// is moved into a composite.
+    pub fn changed_files(&self) -> ChangedFiles {
+        self.files_changed.clone()
+    }
+}
+
+/// Convert a harness event to its JSON line representation.
+///
+/// Returns `None` for events that are too noisy or internal for consumers
+/// (deltas, context snapshots).
+pub fn event_to_json(event: &HarnessEvent<'_>) -> Option<serde_json::Value> {
+    let value = match event {
+        HarnessEvent::RoundStart {
+            round,
+            max_rounds,
+            context_usage,
+            ..
+        } => json!({
+            "type": "round_start",
+            "round": round,
+            "max_rounds": max_rounds,
+            "context_pct": context_usage.usage_pct,
+        }),
+        HarnessEvent::Text(text) => json!({"type": "text", "text": text}),
+        HarnessEvent::Reasoning(text) => json!({"type": "reasoning", "text": text}),
+        HarnessEvent::ToolExecuting {
+            name,
+            arguments,
+            summary,
+        } => json!({
+            "type": "tool_executing",
+            "name": name,
+            "arguments": arguments,
+            "summary": summary,
+        }),
+        HarnessEvent::ToolResult {
+            name,
+            call_id,
+            result,
+        } => json!({
+            "type": "tool_result",
+            "name": name,
+            "call_id": call_id,
+            "result": result,
+            "output": ToolOutput::parse(result).map(|o| o.to_json()),
+        }),
+        HarnessEvent::FileEdited {
+            path, diff, stats, ..
+        } => json!({
+            "type": "file_edited",
+            "path": path,
+            "diff": diff,
+            "added": stats.added,
+            "removed": stats.removed,
+        }),
+        HarnessEvent::TokenUsage {
+            prompt_tokens,
+            completion_tokens,
+        } => json!({
+            "type": "token_usage",
+            "prompt_tokens": prompt_tokens,
+            "completion_tokens": completion_tokens,
+        }),
+        HarnessEvent::ApprovalRequired {
+            name, arguments, ..
+        } => json!({
+            "type": "approval_required",
+            "name": name,
+            "arguments": arguments,
+        }),
+        HarnessEvent::Finished => json!({"type": "finished"}),
+        HarnessEvent::RoundEnd { decisions } => {
+            json!({"type": "round_end", "decisions": decisions})
+        }
+        HarnessEvent::RoundLimitReached { max_rounds } => {
+            json!({"type": "round_limit_reached", "max_rounds": max_rounds})
+        }
+        HarnessEvent::CostLimitReached {
+            cost_usd,
+            max_cost_usd,
+        } => json!({
+            "type": "cost_limit_reached",
+            "cost_usd": cost_usd,
+            "max_cost_usd": max_cost_usd,
+        }),
+        HarnessEvent::ResourceLimitExceeded {
+            name,
+            limit,
+            detail,
+        } => json!({
+            "type": "resource_limit_exceeded",
+            "name": name,
+            "limit": limit.label(),
+            "detail": detail,
+        }),
+        HarnessEvent::SessionStarting { trace_id } => {
+            json!({"type": "session_starting", "trace_id": trace_id})
+        }
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Print a JSON value as a single line on stdout.
+pub fn print_json_line(value: &impl Serialize) {
+    if let Ok(line) = serde_json::to_string(value) {
+        let mut stdout = std::io::stdout().lock();
+        let _ = writeln!(stdout, "{line}");
+        let _ = stdout.flush();
+    }
+}
+
+impl EventHandler for JsonEventHandler {
+    fn on_event(&self, event: &HarnessEvent<'_>) -> Option<EventResponse> {
+        self.files_changed.on_event(event);
+        if self.stream
+            && let Some(value) = event_to_json(event)
+        {
+            print_json_line(&value);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_mutated_files_only() {
+        let handler = JsonEventHandler::new(false);
+        for (name, path) in [("edit_file", "src/b.rs"), ("write_file", "src/a.rs")] {
+            handler.on_event(&HarnessEvent::FileEdited {
+                name,
+                path,
+                diff: "",
+                stats: Default::default(),
+            });
+        }
+        // Only calls that changed a file count, not every call that ran.
+        handler.on_event(&HarnessEvent::ToolExecuting {
+            name: "write_file",
+            arguments: r#"{"path":"src/c.rs","content":""}"#,
+            summary: "",
+        });
+        assert_eq!(handler.files_changed(), vec!["src/a.rs", "src/b.rs"]);
+    }
+
+    #[test]
+    fn event_json_shapes() {
+        let text = event_to_json(&HarnessEvent::Text("hi")).unwrap();
+        assert_eq!(text["type"], "text");
+        assert_eq!(text["text"], "hi");
+
+        assert!(event_to_json(&HarnessEvent::TextDelta("h")).is_none());
+
+        let decisions = cinch_rs::agent::RoundDecisions {
+            round: 2,
+            cache_hits: 1,
+            ..Default::default()
+        };
+        let end = event_to_json(&HarnessEvent::RoundEnd {
+            decisions: &decisions,
+        })
+        .unwrap();
+        assert_eq!(end["decisions"]["cache_hits"], 1);
+        assert_eq!(end["decisions"]["outcome"], "tool_calls");
+    }
+
+    #[test]
+    fn summary_serializes_with_type_tag() {
+        let result = HarnessResult {
+            trace_id: "tr-1".into(),
+            messages: vec![],
+            text_output: vec!["done".into()],
+            annotations: vec![],
+            total_prompt_tokens: 10,
+            total_completion_tokens: 5,
+            rounds_used: 2,
+            finished: true,
+            budget_exceeded: false,
+            estimated_cost_usd: 0.01,
+            tool_costs_usd: Default::default(),
+            structured_output: None,
+            prompt_cache: Default::default(),
+            tool_stats: Default::default(),
+            files_changed: Vec::new(),
+        };
+        let summary = RunSummary::from_result(&result, vec!["a.rs".into()]);
+        let v = serde_json::to_value(&summary).unwrap();
+        assert_eq!(v["type"], "result");
+        assert_eq!(v["rounds"], 2);
+        assert_eq!(v["files_changed"][0], "a.rs");
+        assert_eq!(summary.exit_code(), EXIT_SUCCESS);
+    }
+
+    #[test]
+    fn exit_code_reflects_outcome() {
+        let summary = |finished, budget_exceeded| RunSummary {
+            kind: "result",
+            trace_id: String::new(),
+            text: String::new(),
+            finished,
+            budget_exceeded,
+            rounds: 1,
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            cost_usd: 0.0,
+            tool_cost_usd: 0.0,
+            prompt_prefix_reuse: 1.0,
+            prompt_prefix_breaks: 0,
+            files_changed: vec![],
+        };
+        assert_eq!(summary(true, false).exit_code(), EXIT_SUCCESS);
+        assert_eq!(summary(false, false).exit_code(), EXIT_FAILURE);
+        assert_eq!(summary(false, true).exit_code(), EXIT_BUDGET_EXCEEDED);
+    }
+
+    #[test]
+    fn piped_input_is_attached_to_prompt() {
+        let p = attach_piped_input(Some("review this diff".into()), Some("+a\n".into()));
+        assert_eq!(p.unwrap(), "review this diff\n\n<stdin>\n+a\n</stdin>");
+        assert_eq!(
+            attach_piped_input(None, Some("fix it".into())).as_deref(),
+            Some("fix it")
+        );
+        assert_eq!(
+            attach_piped_input(Some("x".into()), None).as_deref(),
+            Some("x")
+        );
+        assert!(attach_piped_input(None, None).is_none());
+    }
+}
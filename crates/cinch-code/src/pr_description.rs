@@ -0,0 +1,397 @@
+//! `/pr-description`: draft a pull request from the session.
+//!
+//! [`SessionSummary`] pulls the task (first user message), the plan (the
+//! last `submit_plan` summary or `todo` list), and the last test run out of
+//! the conversation. [`PrDescriptionWorkflow`] combines it with the branch
+//! diff against the base (see [`load_diff`]) and asks a cheap model for a
+//! title and markdown body. After approval (editable) the draft can be
+//! pushed with the GitHub CLI, creating the pull request for the current
+//! branch or updating the one that already exists.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use cinch_rs::tools::names;
+use cinch_rs::ui::{QuestionChoice, QuestionResponse, UiState, UserQuestion};
+use cinch_rs::{ChatRequest, Message, MessageRole, OpenRouterClient};
+use tokio::process::Command;
+
+use crate::commit::{clean_message, truncate_chars};
+use crate::interact::ask_and_wait;
+use crate::pr_review::{DEFAULT_BASE, load_diff};
+use crate::tools::TEST;
+
+/// Name of the plan-submission tool in plan-execute mode.
+const SUBMIT_PLAN: &str = "submit_plan";
+
+/// Maximum diff characters sent to the model.
+const MAX_DIFF_CHARS: usize = 24_000;
+
+/// Maximum characters of test output sent to the model; the tail is kept,
+/// where test runners print their summary.
+const MAX_TEST_CHARS: usize = 4_000;
+
+/// Seconds before the approval question times out (treated as cancel).
+const APPROVAL_TIMEOUT_SECS: u64 = 600;
+
+const PR_DESCRIPTION_PROMPT: &str = "\
+You write pull request descriptions.
+
+Output format:
+- First line: the title, at most 72 characters, imperative mood, no trailing \
+  period, no markdown.
+- A blank line, then a markdown body with the sections `## Summary` (what \
+  changed and why, 1-3 sentences), `## Changes` (bullet points), and \
+  `## Testing` (what was run and the result; say so if nothing was run).
+- Describe only what the task, plan, diff, and test results show.
+- Output the description only — no code fences, no commentary.";
+
+// ── Session context ─────────────────────────────────────────────────
+
+/// What the session was about, extracted from its messages.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionSummary {
+    /// The first user message.
+    pub task: Option<String>,
+    /// The last submitted plan, or the last `todo` list.
+    pub plan: Option<String>,
+    /// Output of the last `test` tool call, or `shell` call running tests.
+    pub tests: Option<String>,
+}
+
+impl SessionSummary {
+    pub fn from_messages(messages: &[Message]) -> Self {
+        let task = messages
+            .iter()
+            .find(|m| m.role == MessageRole::User)
+            .and_then(|m| m.content.clone());
+
+        let mut calls: HashMap<&str, (&str, &str)> = HashMap::new();
+        let mut submitted = None;
+        let mut todo = None;
+        let mut tests = None;
+        for message in messages {
+            for call in message.tool_calls.iter().flatten() {
+                let (name, arguments) = (&call.function.name, &call.function.arguments);
+                calls.insert(&call.id, (name, arguments));
+                if name == SUBMIT_PLAN {
+                    submitted = serde_json::from_str::<serde_json::Value>(arguments)
+                        .ok()
+                        .and_then(|v| v.get("summary")?.as_str().map(String::from));
+                }
+            }
+            let Some((name, arguments)) =
+                message.tool_call_id.as_deref().and_then(|id| calls.get(id))
+            else {
+                continue;
+            };
+            let content = message.content.clone().unwrap_or_default();
+            if *name == names::TODO {
+                todo = Some(content);
+            } else if *name == TEST || (*name == names::SHELL && runs_tests(arguments)) {
+                tests = Some(content);
+            }
+        }
+
+        Self {
+            task,
+            plan: submitted.or(todo),
+            tests,
+        }
+    }
+
+    /// The user prompt for the model.
+    fn prompt(&self, diff: &str, hint: &str) -> String {
+        let mut out = String::new();
+        if let Some(ref task) = self.task {
+            out.push_str(&format!("Task:\n{}\n\n", task.trim()));
+        }
+        if let Some(ref plan) = self.plan {
+            out.push_str(&format!("Plan:\n{}\n\n", plan.trim()));
+        }
+        match self.tests {
+            Some(ref tests) => out.push_str(&format!(
+                "Test results:\n{}\n\n",
+                tail_chars(tests.trim(), MAX_TEST_CHARS)
+            )),
+            None => out.push_str("Test results: no tests were run in this session.\n\n"),
+        }
+        out.push_str(&format!("Diff:\n{}", truncate_chars(diff, MAX_DIFF_CHARS)));
+        if !hint.trim().is_empty() {
+            out.push_str(&format!("\n\nAuthor's note: {}", hint.trim()));
+        }
+        out
+    }
+}
+
+/// Whether a `shell` call's command looks like a test run.
+fn runs_tests(arguments: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(arguments)
+        .ok()
+        .and_then(|v| v.get("command")?.as_str().map(|c| c.contains("test")))
+        .unwrap_or(false)
+}
+
+fn tail_chars(text: &str, max: usize) -> String {
+    let count = text.chars().count();
+    if count <= max {
+        return text.to_string();
+    }
+    let tail: String = text.chars().skip(count - max).collect();
+    format!("[earlier output truncated]\n{tail}")
+}
+
+// ── Draft ───────────────────────────────────────────────────────────
+
+/// A pull request title and body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrDraft {
+    pub title: String,
+    pub body: String,
+}
+
+impl PrDraft {
+    /// Parse a title line followed by the body, as written by the model or
+    /// edited by the user.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = clean_message(text);
+        let (title, body) = text.split_once('\n').unwrap_or((&text, ""));
+        let title = title
+            .trim()
+            .trim_start_matches('#')
+            .trim()
+            .trim_start_matches("Title:")
+            .trim();
+        if title.is_empty() {
+            return None;
+        }
+        Some(Self {
+            title: title.to_string(),
+            body: body.trim().to_string(),
+        })
+    }
+
+    /// Title, blank line, body.
+    pub fn render(&self) -> String {
+        format!("{}\n\n{}", self.title, self.body)
+    }
+}
+
+// ── Workflow ────────────────────────────────────────────────────────
+
+/// Settings for `/pr-description`.
+pub struct PrDescriptionWorkflow {
+    workdir: String,
+    model: String,
+}
+
+impl PrDescriptionWorkflow {
+    pub fn new(workdir: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            workdir: workdir.into(),
+            model: model.into(),
+        }
+    }
+
+    /// Ask the model for a draft describing the session and `diff`.
+    pub async fn generate(
+        &self,
+        client: &OpenRouterClient,
+        session: &SessionSummary,
+        diff: &str,
+        hint: &str,
+    ) -> Result<PrDraft, String> {
+        let body = ChatRequest {
+            model: Some(self.model.clone()),
+            messages: vec![
+                Message::system(PR_DESCRIPTION_PROMPT),
+                Message::user(session.prompt(diff, hint)),
+            ],
+            max_tokens: 1024,
+            temperature: 0.2,
+            ..Default::default()
+        };
+        let completion = client.chat(&body).await?;
+        let text = completion.content.unwrap_or_default();
+        PrDraft::parse(&text).ok_or_else(|| "Empty PR description from model".to_string())
+    }
+
+    /// Create the pull request for the current branch with `gh`, or update
+    /// its title and body if one is already open.
+    pub async fn push(&self, draft: &PrDraft, base: &str) -> Result<String, String> {
+        let created = self
+            .gh(&[
+                "pr",
+                "create",
+                "--base",
+                base,
+                "--title",
+                &draft.title,
+                "--body",
+                &draft.body,
+            ])
+            .await;
+        let error = match created {
+            Ok(url) => return Ok(format!("Opened pull request: {}", url.trim())),
+            Err(e) => e,
+        };
+        if !error.contains("already exists") {
+            return Err(error);
+        }
+        let url = self
+            .gh(&["pr", "edit", "--title", &draft.title, "--body", &draft.body])
+            .await?;
+        Ok(format!("Updated pull request: {}", url.trim()))
+    }
+
+    async fn gh(&self, args: &[&str]) -> Result<String, String> {
+        let output = Command::new("gh")
+            .args(args)
+            .current_dir(&self.workdir)
+            .output()
+            .await
+            .map_err(|e| format!("failed to run gh: {e}"))?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        }
+    }
+}
+
+/// Run the interactive `/pr-description` flow and return a status line
+/// (or the draft) for the output pane. `args` is an optional base branch
+/// followed by a note for the model.
+pub async fn pr_description_interactively(
+    ui_state: &Arc<Mutex<UiState>>,
+    client: &OpenRouterClient,
+    workflow: &PrDescriptionWorkflow,
+    messages: &[Message],
+    args: &str,
+) -> String {
+    let (base, hint) = match args.trim().split_once(char::is_whitespace) {
+        Some((base, hint)) => (base, hint),
+        None if args.trim().is_empty() => (DEFAULT_BASE, ""),
+        None => (args.trim(), ""),
+    };
+    let diff = match load_diff(&workflow.workdir, base).await {
+        Ok(d) => d,
+        Err(e) => return format!("PR description failed: {e}"),
+    };
+    let session = SessionSummary::from_messages(messages);
+    let draft = match workflow.generate(client, &session, &diff, hint).await {
+        Ok(d) => d,
+        Err(e) => return format!("PR description failed: could not generate it: {e}"),
+    };
+
+    let question = UserQuestion {
+        prompt: format!("Pull request against {base} (e to edit)"),
+        choices: vec![
+            QuestionChoice {
+                label: "Push with gh".into(),
+                body: draft.render(),
+                metadata: format!("gh pr create --base {base}"),
+            },
+            QuestionChoice {
+                label: "Show only".into(),
+                body: draft.render(),
+                metadata: String::new(),
+            },
+            QuestionChoice {
+                label: "Cancel".into(),
+                body: diff,
+                metadata: String::new(),
+            },
+        ],
+        editable: true,
+        max_edit_length: None,
+        ..Default::default()
+    };
+    let (index, draft) = match ask_and_wait(ui_state, question, APPROVAL_TIMEOUT_SECS).await {
+        QuestionResponse::Selected(index @ (0 | 1)) => (index, draft),
+        QuestionResponse::SelectedEdited {
+            index: index @ (0 | 1),
+            edited_text,
+        } => match PrDraft::parse(&edited_text) {
+            Some(edited) => (index, edited),
+            None => return "PR description cancelled: the edited title is empty.".into(),
+        },
+        _ => return "PR description cancelled.".into(),
+    };
+    if index == 1 {
+        return draft.render();
+    }
+    match workflow.push(&draft, base).await {
+        Ok(status) => status,
+        Err(e) => format!("Push failed: {e}\n\n{}", draft.render()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cinch_rs::{CallType, FunctionCallData, ToolCall};
+
+    fn call(id: &str, name: &str, arguments: &str) -> ToolCall {
+        ToolCall {
+            id: id.into(),
+            call_type: CallType::Function,
+            function: FunctionCallData {
+                name: name.into(),
+                arguments: arguments.into(),
+            },
+        }
+    }
+
+    #[test]
+    fn session_summary_finds_task_plan_and_tests() {
+        let messages = vec![
+            Message::system("sys"),
+            Message::user("Fix the parser"),
+            Message::assistant_tool_calls(vec![
+                call("1", SUBMIT_PLAN, r#"{"summary":"1. fix 2. test"}"#),
+                call("2", names::TODO, r#"{"action":"list"}"#),
+            ]),
+            Message::tool_result("1", "Plan accepted"),
+            Message::tool_result("2", "[ ] fix"),
+            Message::assistant_tool_calls(vec![
+                call("3", names::SHELL, r#"{"command":"cargo test"}"#),
+                call("4", names::SHELL, r#"{"command":"ls"}"#),
+            ]),
+            Message::tool_result("3", "test result: FAILED"),
+            Message::tool_result("4", "src"),
+            Message::assistant_tool_calls(vec![call("5", TEST, "{}")]),
+            Message::tool_result("5", "test result: ok"),
+            Message::user("Thanks"),
+        ];
+        let summary = SessionSummary::from_messages(&messages);
+        assert_eq!(summary.task.as_deref(), Some("Fix the parser"));
+        assert_eq!(summary.plan.as_deref(), Some("1. fix 2. test"));
+        assert_eq!(summary.tests.as_deref(), Some("test result: ok"));
+
+        let prompt = summary.prompt("+fix", "closes #4");
+        assert!(prompt.starts_with("Task:\nFix the parser\n\nPlan:\n1. fix 2. test"));
+        assert!(prompt.ends_with("Diff:\n+fix\n\nAuthor's note: closes #4"));
+        assert!(
+            SessionSummary::default()
+                .prompt("", "")
+                .contains("no tests were run")
+        );
+    }
+
+    #[test]
+    fn draft_parses_title_and_body() {
+        let draft = PrDraft::parse("```markdown\n# Fix parser\n\n## Summary\nFixed.\n```").unwrap();
+        assert_eq!(draft.title, "Fix parser");
+        assert_eq!(draft.body, "## Summary\nFixed.");
+        assert_eq!(draft.render(), "Fix parser\n\n## Summary\nFixed.");
+        assert_eq!(PrDraft::parse("Title: Only a title").unwrap().body, "");
+        assert!(PrDraft::parse("  \n").is_none());
+    }
+
+    #[test]
+    fn tail_chars_keeps_the_end() {
+        assert_eq!(tail_chars("abc", 5), "abc");
+        assert_eq!(tail_chars("abcdef", 2), "[earlier output truncated]\nef");
+    }
+}
@@ -0,0 +1,282 @@
+//! `cinch-code review`: review a branch's changes and report findings.
+//!
+//! [`load_diff`] diffs the working tree against the merge base with the
+//! target branch (default [`DEFAULT_BASE`]). The reviewer agent gets that
+//! diff in its task, the [read-only view](cinch_rs::tools::core::ToolSet::read_only_view)
+//! of the coding tools to look around the codebase, and a
+//! [`report_finding`](REPORT_FINDING) tool that records each problem as a
+//! structured [`Finding`] (severity, file, line, message, suggestion). The
+//! binary shows the findings in the TUI or prints a [`ReviewReport`] as JSON
+//! for CI annotations.
+
+use std::sync::{Arc, Mutex};
+
+use cinch_rs::tools::core::{FnTool, ToolSet};
+use cinch_rs::tools::output::ToolOutput;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::output::{EXIT_FAILURE, EXIT_SUCCESS};
+use crate::tools::git::{run_git, succeeded};
+
+/// Branch reviewed against when `--against` is not given.
+pub const DEFAULT_BASE: &str = "main";
+
+/// Name of the tool the reviewer reports findings with.
+pub const REPORT_FINDING: &str = "report_finding";
+
+/// Maximum diff characters included in the review task; the reviewer can
+/// read the files for the rest.
+const MAX_DIFF_CHARS: usize = 60_000;
+
+/// System prompt of the reviewer agent.
+pub const REVIEW_SYSTEM_PROMPT: &str = "\
+You are a meticulous code reviewer. You are given the diff of a branch against \
+its base. Look for bugs, security problems, missing error handling, missing \
+tests, and changes that break existing behavior; read the surrounding code \
+when the diff alone isn't enough. Do not comment on formatting a formatter \
+would fix.
+
+Report every problem with the `report_finding` tool, one call per problem, \
+pointing at the changed line in the new version of the file. Use severity \
+`error` for bugs and vulnerabilities, `warning` for likely problems, and \
+`info` for improvements. When you are done, reply with a one-paragraph \
+overall assessment. You cannot modify files.";
+
+/// How serious a finding is. Maps onto CI annotation levels.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// A bug or vulnerability; fails the review.
+    Error,
+    /// A likely problem.
+    Warning,
+    /// An improvement.
+    Info,
+}
+
+/// One problem found in the diff.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Finding {
+    /// How serious the problem is.
+    pub severity: Severity,
+    /// Path of the file, relative to the repository root.
+    pub file: String,
+    /// Line in the new version of the file, if the problem has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    /// What is wrong.
+    pub message: String,
+    /// How to fix it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+impl Finding {
+    /// `error src/lib.rs:12: message`, with the suggestion on the next line.
+    pub fn render(&self) -> String {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        };
+        let location = match self.line {
+            Some(line) => format!("{}:{line}", self.file),
+            None => self.file.clone(),
+        };
+        let mut out = format!("{severity} {location}: {}", self.message);
+        if let Some(ref suggestion) = self.suggestion {
+            out.push_str(&format!("\n  suggestion: {suggestion}"));
+        }
+        out
+    }
+}
+
+/// Findings reported during a review. Cheap to clone; clones share the
+/// same list.
+#[derive(Clone, Default)]
+pub struct Findings(Arc<Mutex<Vec<Finding>>>);
+
+impl Findings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The `report_finding` tool, recording into this list.
+    pub fn tool(&self) -> FnTool {
+        let findings = self.clone();
+        FnTool::typed(
+            REPORT_FINDING,
+            "Record one problem found in the diff: severity (error, warning, info), file, \
+             line in the new version, message, and an optional suggested fix.",
+            move |finding: Finding| {
+                let findings = findings.clone();
+                async move {
+                    let rendered = finding.render();
+                    findings.0.lock().unwrap().push(finding);
+                    format!("Recorded: {rendered}")
+                }
+            },
+        )
+    }
+
+    /// The findings so far, most severe first.
+    pub fn sorted(&self) -> Vec<Finding> {
+        let mut findings = self.0.lock().unwrap().clone();
+        findings.sort_by(|a, b| (a.severity, &a.file, a.line).cmp(&(b.severity, &b.file, b.line)));
+        findings
+    }
+}
+
+/// Read-only `tools` plus the `report_finding` tool.
+pub fn review_tools(tools: &ToolSet, findings: &Findings) -> ToolSet {
+    tools.read_only_view().with(findings.tool())
+}
+
+/// The diff of the working tree against the merge base of `against` and
+/// `HEAD`, so only the branch's own changes are reviewed.
+pub async fn load_diff(workdir: &str, against: &str) -> Result<String, String> {
+    let base = run_git(workdir, &["merge-base", against, "HEAD"]).await;
+    let base = match ToolOutput::parse(&base) {
+        Some(output) if output.success() => output.stdout.trim().to_string(),
+        _ => return Err(format!("cannot find the merge base with '{against}'")),
+    };
+    let diff = run_git(workdir, &["diff", &base]).await;
+    if !succeeded(&diff) {
+        return Err(format!("git diff failed: {}", diff.trim()));
+    }
+    let diff = ToolOutput::parse(&diff)
+        .map(|o| o.stdout)
+        .unwrap_or_default();
+    if diff.trim().is_empty() {
+        return Err(format!("no changes against '{against}'"));
+    }
+    Ok(diff)
+}
+
+/// The reviewer's task: the diff (truncated to a size the context can
+/// take) and what to do with it.
+pub fn review_task(against: &str, diff: &str) -> String {
+    let (diff, note) = if diff.len() > MAX_DIFF_CHARS {
+        #[allow(clippy::string_slice)] // index from floor_char_boundary
+        let head = &diff[..diff.floor_char_boundary(MAX_DIFF_CHARS)];
+        (
+            head,
+            "\n\n(The diff is truncated; use `git_diff` or read the files for the rest.)",
+        )
+    } else {
+        (diff, "")
+    };
+    format!(
+        "Review the changes on this branch against `{against}`. Report each problem with \
+         `{REPORT_FINDING}`.\n\n```diff\n{diff}\n```{note}"
+    )
+}
+
+/// The result of a review, as printed for `--output-format json`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReviewReport {
+    /// Always `"review"`.
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    /// Branch the changes were compared against.
+    pub against: String,
+    /// The reviewer's overall assessment.
+    pub summary: String,
+    /// Findings, most severe first.
+    pub findings: Vec<Finding>,
+}
+
+impl ReviewReport {
+    pub fn new(
+        against: impl Into<String>,
+        summary: impl Into<String>,
+        findings: Vec<Finding>,
+    ) -> Self {
+        Self {
+            kind: "review",
+            against: against.into(),
+            summary: summary.into(),
+            findings,
+        }
+    }
+
+    /// Findings followed by the summary, for the terminal.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        if self.findings.is_empty() {
+            out.push_str("No findings.\n");
+        }
+        for finding in &self.findings {
+            out.push_str(&finding.render());
+            out.push('\n');
+        }
+        if !self.summary.is_empty() {
+            out.push('\n');
+            out.push_str(&self.summary);
+        }
+        out.trim_end().to_string()
+    }
+
+    /// [`EXIT_FAILURE`] when any finding is an error, so CI fails the check.
+    pub fn exit_code(&self) -> i32 {
+        if self.findings.iter().any(|f| f.severity == Severity::Error) {
+            EXIT_FAILURE
+        } else {
+            EXIT_SUCCESS
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cinch_rs::tools::core::Tool;
+
+    #[tokio::test]
+    async fn report_finding_records_structured_findings() {
+        let findings = Findings::new();
+        let tool = findings.tool();
+        tool.execute(
+            r#"{"severity":"warning","file":"src/b.rs","message":"unwrap on user input"}"#,
+        )
+        .await;
+        let result = tool
+            .execute(
+                r#"{"severity":"error","file":"src/a.rs","line":12,"message":"off by one","suggestion":"use ..="}"#,
+            )
+            .await;
+        assert_eq!(
+            result,
+            "Recorded: error src/a.rs:12: off by one\n  suggestion: use ..="
+        );
+
+        let report = ReviewReport::new("main", "Mostly fine.", findings.sorted());
+        assert_eq!(report.findings[0].file, "src/a.rs");
+        assert_eq!(report.exit_code(), EXIT_FAILURE);
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["type"], "review");
+        assert_eq!(json["findings"][0]["severity"], "error");
+        assert!(json["findings"][1].get("line").is_none());
+        assert!(report.render().ends_with("\n\nMostly fine."));
+    }
+
+    #[test]
+    fn review_tools_are_read_only() {
+        let tools = ToolSet::new().with_common_tools("/tmp");
+        let review = review_tools(&tools, &Findings::new());
+        assert!(review.has_tool(REPORT_FINDING));
+        assert!(review.has_tool("read_file"));
+        assert!(!review.has_tool("write_file"));
+    }
+
+    #[test]
+    fn review_task_truncates_long_diffs() {
+        let task = review_task("main", &"+x\n".repeat(MAX_DIFF_CHARS));
+        assert!(task.contains("against `main`"));
+        assert!(task.ends_with("read the files for the rest.)"));
+        assert!(review_task("dev", "+y").ends_with("```diff\n+y\n```"));
+    }
+}
@@ -0,0 +1,339 @@
+//! Per-project configuration from `.cinch/config.toml`.
+//!
+//! The file is discovered by walking up from the working directory, so a
+//! config at the repository root applies to every subdirectory. All fields
+//! are optional; anything unset falls back to [`CodeConfig`] defaults, and
+//! explicit CLI flags override the file.
+//!
+//! # Example
+//!
+//! ```toml
+//! model = "anthropic/claude-sonnet-4"
+//! max_rounds = 80
+//! blocked_commands = ["cargo publish", "terraform apply"]
+//! approval_required_tools = ["shell"]
+//! allowed_paths = ["../shared-protos"]
+//! system_prompt = "Prefer small commits. Run `cargo test` after edits."
+//! audit_log = true
+//!
+//! [shell_env]
+//! inherit = ["PATH", "HOME", "CARGO_*"]
+//! env_file = ".env.agent"
+//! vars = { CARGO_TARGET_DIR = "target/agent" }
+//!
+//! [limits]
+//! cpu_secs = 300
+//! memory_mb = 4096
+//! max_output_bytes = 1_000_000
+//! run_budget_secs = 1800
+//!
+//! [[tools]]
+//! name = "run_lints"
+//! description = "Run the project's lint suite"
+//! command = "make lint"
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use cinch_rs::ProviderSort;
+use cinch_rs::tools::host::ShellEnv;
+use cinch_rs::tools::limits::SubprocessBudget;
+use serde::Deserialize;
+
+use crate::config::CodeConfig;
+use crate::tools::CommandToolConfig;
+
+/// Directory holding project-level cinch files.
+pub const CINCH_DIR: &str = ".cinch";
+/// Config file name inside [`CINCH_DIR`].
+pub const CONFIG_FILE: &str = "config.toml";
+
+/// Contents of `.cinch/config.toml`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectConfig {
+    pub model: Option<String>,
+    pub max_rounds: Option<u32>,
+    pub max_tokens: Option<u32>,
+    pub temperature: Option<f32>,
+    /// Stop a run once its estimated cost reaches this many USD.
+    pub max_cost_usd: Option<f64>,
+    /// Provider ranking: `"price"`, `"throughput"`, or `"latency"`.
+    pub provider_sort: Option<ProviderSort>,
+    pub streaming: Option<bool>,
+    /// Stage file edits for review before writing (same as `--review`).
+    pub review: Option<bool>,
+    /// Hide every mutating tool from the agent (same as `--read-only`).
+    pub read_only: Option<bool>,
+    /// Register detected `build`, `test`, and `lint` tools. Default: `true`.
+    pub project_tools: Option<bool>,
+    /// Extra shell command patterns to block, added to the defaults.
+    #[serde(default)]
+    pub blocked_commands: Vec<String>,
+    /// Directories outside the workdir that file tools may access
+    /// (relative paths are resolved against the workdir).
+    #[serde(default)]
+    pub allowed_paths: Vec<PathBuf>,
+    /// Environment for the `shell` tool: allowlisted host variables, an
+    /// env file (relative to the workdir), and explicit variables.
+    pub shell_env: Option<ShellEnv>,
+    /// Resource limits for processes started by `shell` and `[[tools]]`.
+    pub limits: Option<LimitsConfig>,
+    /// Extra tools that require approval, added to the git mutation tools.
+    #[serde(default)]
+    pub approval_required_tools: Vec<String>,
+    /// Record commands, file mutations, and network calls in each session's
+    /// `audit.jsonl`.
+    pub audit_log: Option<bool>,
+    /// Text appended to the coding system prompt.
+    pub system_prompt: Option<String>,
+    /// Model used to write `/commit` messages.
+    pub commit_model: Option<String>,
+    /// Branch prefix for per-session `/commit` branches (e.g. `"cinch/"`).
+    pub commit_branch_prefix: Option<String>,
+    /// Model that titles and summarizes each session.
+    pub title_model: Option<String>,
+    /// Project-specific tools backed by shell commands.
+    #[serde(default)]
+    pub tools: Vec<CommandToolConfig>,
+}
+
+/// The `[limits]` table.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LimitsConfig {
+    /// CPU seconds per process.
+    pub cpu_secs: Option<u64>,
+    /// Address space per process, in MiB.
+    pub memory_mb: Option<u64>,
+    /// Output captured per command before it is killed.
+    pub max_output_bytes: Option<usize>,
+    /// Wall time shared by every spawned process in a run.
+    pub run_budget_secs: Option<u64>,
+}
+
+impl LimitsConfig {
+    fn apply_to(&self, config: &mut CodeConfig) {
+        let limits = &mut config.process_limits;
+        if let Some(secs) = self.cpu_secs {
+            *limits = limits.cpu_time(Duration::from_secs(secs));
+        }
+        if let Some(mb) = self.memory_mb {
+            *limits = limits.memory_bytes(mb * 1024 * 1024);
+        }
+        if let Some(bytes) = self.max_output_bytes {
+            *limits = limits.max_output_bytes(bytes);
+        }
+        if let Some(secs) = self.run_budget_secs {
+            config.subprocess_budget =
+                Some(Arc::new(SubprocessBudget::new(Duration::from_secs(secs))));
+        }
+    }
+}
+
+impl ProjectConfig {
+    /// Parse a config file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+        toml::from_str(&contents).map_err(|e| format!("invalid {}: {e}", path.display()))
+    }
+
+    /// Find the nearest `.cinch/config.toml` at or above `start`.
+    pub fn discover(start: &Path) -> Option<PathBuf> {
+        start
+            .ancestors()
+            .map(|dir| dir.join(CINCH_DIR).join(CONFIG_FILE))
+            .find(|p| p.is_file())
+    }
+
+    /// Discover and load the config for `workdir`.
+    ///
+    /// Returns `Ok(None)` when no file exists; parse errors are surfaced so
+    /// a typo doesn't silently fall back to defaults.
+    pub fn discover_and_load(workdir: &Path) -> Result<Option<(PathBuf, Self)>, String> {
+        match Self::discover(workdir) {
+            Some(path) => Self::load(&path).map(|c| Some((path, c))),
+            None => Ok(None),
+        }
+    }
+
+    /// Apply the file's settings on top of `config`.
+    pub fn apply_to(&self, config: &mut CodeConfig) {
+        if let Some(ref model) = self.model {
+            config.model = model.clone();
+        }
+        if let Some(v) = self.max_rounds {
+            config.max_rounds = v;
+        }
+        if let Some(v) = self.max_tokens {
+            config.max_tokens = v;
+        }
+        if let Some(v) = self.temperature {
+            config.temperature = v;
+        }
+        if let Some(v) = self.max_cost_usd {
+            config.max_cost_usd = Some(v);
+        }
+        if let Some(v) = self.provider_sort {
+            config.provider_sort = Some(v);
+        }
+        if let Some(v) = self.streaming {
+            config.streaming = v;
+        }
+        if let Some(v) = self.review {
+            config.review = v;
+        }
+        if let Some(v) = self.read_only {
+            config.read_only = v;
+        }
+        if let Some(v) = self.project_tools {
+            config.project_tools = v;
+        }
+        if let Some(v) = self.audit_log {
+            config.audit_log = v;
+        }
+        config
+            .blocked_commands
+            .extend(self.blocked_commands.iter().cloned());
+        let workdir = Path::new(&config.workdir).to_path_buf();
+        config
+            .allowed_paths
+            .extend(self.allowed_paths.iter().map(|p| workdir.join(p)));
+        if let Some(ref env) = self.shell_env {
+            config.shell_env = env.clone();
+        }
+        if let Some(ref limits) = self.limits {
+            limits.apply_to(config);
+        }
+        config
+            .approval_required_tools
+            .extend(self.approval_required_tools.iter().cloned());
+        if self.system_prompt.is_some() {
+            config.system_prompt_extra = self.system_prompt.clone();
+        }
+        config.command_tools.extend(self.tools.iter().cloned());
+        if self.commit_model.is_some() {
+            config.commit_model = self.commit_model.clone();
+        }
+        if self.commit_branch_prefix.is_some() {
+            config.commit_branch_prefix = self.commit_branch_prefix.clone();
+        }
+        if self.title_model.is_some() {
+            config.title_model = self.title_model.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_full_config() {
+        let cfg: ProjectConfig = toml::from_str(
+            r#"
+            model = "m"
+            max_rounds = 7
+            provider_sort = "throughput"
+            blocked_commands = ["cargo publish"]
+            approval_required_tools = ["shell"]
+            system_prompt = "Be terse."
+
+            [[tools]]
+            name = "lint"
+            description = "Run lints"
+            command = "make lint"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(cfg.model.as_deref(), Some("m"));
+        assert_eq!(cfg.max_rounds, Some(7));
+        assert_eq!(cfg.provider_sort, Some(ProviderSort::Throughput));
+        assert_eq!(cfg.tools.len(), 1);
+        assert!(!cfg.tools[0].mutation);
+    }
+
+    #[test]
+    fn parses_shell_env_table() {
+        let cfg: ProjectConfig = toml::from_str(
+            r#"
+            [shell_env]
+            inherit = ["PATH", "CARGO_*"]
+            env_file = ".env.agent"
+            vars = { CARGO_TARGET_DIR = "target/agent" }
+            "#,
+        )
+        .unwrap();
+        let mut code = CodeConfig::default();
+        cfg.apply_to(&mut code);
+        let env = code.shell_env;
+        assert_eq!(env.inherit, Some(vec!["PATH".into(), "CARGO_*".into()]));
+        assert_eq!(env.env_file, Some(PathBuf::from(".env.agent")));
+        assert_eq!(env.vars["CARGO_TARGET_DIR"], "target/agent");
+        assert!(
+            toml::from_str::<ProjectConfig>(
+                "[shell_env]
+inherit_all = true"
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn parses_limits_table() {
+        let cfg: ProjectConfig = toml::from_str(
+            r#"
+            [limits]
+            cpu_secs = 60
+            memory_mb = 512
+            max_output_bytes = 4096
+            run_budget_secs = 900
+            "#,
+        )
+        .unwrap();
+        let mut code = CodeConfig::default();
+        cfg.apply_to(&mut code);
+        assert_eq!(code.process_limits.cpu_time, Some(Duration::from_secs(60)));
+        assert_eq!(code.process_limits.memory_bytes, Some(512 * 1024 * 1024));
+        assert_eq!(code.process_limits.max_output_bytes, Some(4096));
+        let budget = code.subprocess_budget.unwrap();
+        assert_eq!(budget.limit(), Duration::from_secs(900));
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert!(toml::from_str::<ProjectConfig>("modle = \"typo\"").is_err());
+    }
+
+    #[test]
+    fn apply_overrides_and_extends() {
+        let cfg = ProjectConfig {
+            max_rounds: Some(5),
+            blocked_commands: vec!["npm publish".into()],
+            approval_required_tools: vec!["shell".into()],
+            ..Default::default()
+        };
+        let mut code = CodeConfig::default();
+        cfg.apply_to(&mut code);
+        assert_eq!(code.max_rounds, 5);
+        assert_eq!(code.max_tokens, CodeConfig::default().max_tokens);
+        assert_eq!(code.blocked_commands, vec!["npm publish"]);
+        assert_eq!(code.approval_required_tools, vec!["shell"]);
+    }
+
+    #[test]
+    fn discover_walks_up() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".cinch")).unwrap();
+        std::fs::write(dir.path().join(".cinch/config.toml"), "max_rounds = 3\n").unwrap();
+        let nested = dir.path().join("a/b");
+        std::fs::create_dir_all(&nested).unwrap();
+
+        let (path, cfg) = ProjectConfig::discover_and_load(&nested).unwrap().unwrap();
+        assert_eq!(path, dir.path().join(".cinch/config.toml"));
+        assert_eq!(cfg.max_rounds, Some(3));
+    }
+}
@@ -0,0 +1,404 @@
+//! Review-before-apply for file edits.
+//!
+//! In review mode the file tools write into a [`Changeset`] instead of the
+//! filesystem (see [`crate::tools::staged`]). At the end of each turn the
+//! pending changes are split into hunks and presented one at a time through
+//! the TUI question system; the user accepts, rejects, or edits each hunk,
+//! and only the resulting content is written to disk.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use cinch_rs::ui::{QuestionChoice, QuestionResponse, UiState, UserQuestion};
+use similar::{Algorithm, DiffTag, capture_diff_slices};
+
+use crate::interact::ask_and_wait;
+
+/// Lines of unchanged context shown around each hunk.
+const CONTEXT_LINES: usize = 3;
+
+/// Seconds before an unanswered hunk question times out (treated as reject).
+const REVIEW_TIMEOUT_SECS: u64 = 3600;
+
+/// A file with staged, not-yet-written content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingFile {
+    /// Content on disk when the file was first staged (`None` = new file).
+    pub original: Option<String>,
+    /// Content the agent wants to write.
+    pub proposed: String,
+}
+
+/// Pending file writes, keyed by workdir-relative path.
+///
+/// Shared between the staged file tools (which write into it) and the
+/// review step (which drains it).
+#[derive(Debug, Default)]
+pub struct Changeset {
+    files: Mutex<BTreeMap<String, PendingFile>>,
+}
+
+impl Changeset {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Staged content for `path`, if any.
+    pub fn proposed(&self, path: &str) -> Option<String> {
+        self.files
+            .lock()
+            .ok()
+            .and_then(|f| f.get(path).map(|p| p.proposed.clone()))
+    }
+
+    /// Stage new content for `path`. `original` is only recorded the first
+    /// time a path is staged, so it always reflects the on-disk state.
+    pub fn stage(&self, path: &str, original: Option<String>, proposed: String) {
+        if let Ok(mut files) = self.files.lock() {
+            files
+                .entry(path.to_string())
+                .and_modify(|p| p.proposed = proposed.clone())
+                .or_insert(PendingFile { original, proposed });
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.lock().map(|f| f.is_empty()).unwrap_or(true)
+    }
+
+    /// Remove and return all pending files.
+    pub fn take(&self) -> BTreeMap<String, PendingFile> {
+        self.files
+            .lock()
+            .map(|mut f| std::mem::take(&mut *f))
+            .unwrap_or_default()
+    }
+}
+
+/// A contiguous changed region between the original and proposed content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// Range of original lines replaced by this hunk.
+    pub old: std::ops::Range<usize>,
+    /// Range of proposed lines inserted by this hunk.
+    pub new: std::ops::Range<usize>,
+}
+
+/// What to do with a single hunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HunkDecision {
+    Accept,
+    Reject,
+    /// Replace the hunk's lines with this text.
+    Edit(String),
+}
+
+/// A pending file split into reviewable hunks.
+#[derive(Debug, Clone)]
+pub struct FileDiff {
+    pub path: String,
+    pub is_new: bool,
+    old_lines: Vec<String>,
+    new_lines: Vec<String>,
+    pub hunks: Vec<Hunk>,
+}
+
+impl FileDiff {
+    pub fn new(path: impl Into<String>, pending: &PendingFile) -> Self {
+        let old_lines = split_lines(pending.original.as_deref().unwrap_or_default());
+        let new_lines = split_lines(&pending.proposed);
+        let hunks = capture_diff_slices(Algorithm::Myers, &old_lines, &new_lines)
+            .iter()
+            .map(|op| op.as_tag_tuple())
+            .filter(|(tag, _, _)| *tag != DiffTag::Equal)
+            .map(|(_, old, new)| Hunk { old, new })
+            .collect();
+        Self {
+            path: path.into(),
+            is_new: pending.original.is_none(),
+            old_lines,
+            new_lines,
+            hunks,
+        }
+    }
+
+    /// Unified-diff style rendering of one hunk with surrounding context.
+    pub fn render_hunk(&self, index: usize) -> String {
+        let hunk = &self.hunks[index];
+        let ctx_start = hunk.old.start.saturating_sub(CONTEXT_LINES);
+        let ctx_end = (hunk.old.end + CONTEXT_LINES).min(self.old_lines.len());
+
+        let mut out = format!(
+            "@@ -{},{} +{},{} @@\n",
+            hunk.old.start + 1,
+            hunk.old.len(),
+            hunk.new.start + 1,
+            hunk.new.len()
+        );
+        for line in &self.old_lines[ctx_start..hunk.old.start] {
+            push_line(&mut out, ' ', line);
+        }
+        for line in &self.old_lines[hunk.old.clone()] {
+            push_line(&mut out, '-', line);
+        }
+        for line in &self.new_lines[hunk.new.clone()] {
+            push_line(&mut out, '+', line);
+        }
+        for line in &self.old_lines[hunk.old.end..ctx_end] {
+            push_line(&mut out, ' ', line);
+        }
+        out
+    }
+
+    /// Proposed text of one hunk (the starting point for an edit).
+    pub fn proposed_text(&self, index: usize) -> String {
+        self.new_lines[self.hunks[index].new.clone()].concat()
+    }
+
+    /// Build the final content from per-hunk decisions.
+    ///
+    /// `decisions` must have one entry per hunk.
+    pub fn apply(&self, decisions: &[HunkDecision]) -> String {
+        let mut out = String::new();
+        let mut old_pos = 0;
+        for (hunk, decision) in self.hunks.iter().zip(decisions) {
+            out.push_str(&self.old_lines[old_pos..hunk.old.start].concat());
+            match decision {
+                HunkDecision::Accept => out.push_str(&self.new_lines[hunk.new.clone()].concat()),
+                HunkDecision::Reject => out.push_str(&self.old_lines[hunk.old.clone()].concat()),
+                HunkDecision::Edit(text) => {
+                    out.push_str(text);
+                    if !text.is_empty() && !text.ends_with('\n') {
+                        out.push('\n');
+                    }
+                }
+            }
+            old_pos = hunk.old.end;
+        }
+        out.push_str(&self.old_lines[old_pos..].concat());
+        out
+    }
+}
+
+/// Counts reported after a review.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReviewOutcome {
+    /// Paths written to disk.
+    pub written: Vec<String>,
+    pub accepted_hunks: usize,
+    pub rejected_hunks: usize,
+    pub edited_hunks: usize,
+    /// Write failures, as `"path: error"`.
+    pub errors: Vec<String>,
+}
+
+impl ReviewOutcome {
+    /// One-line summary for the output pane and the next user turn.
+    pub fn summary(&self) -> String {
+        let mut out = format!(
+            "Change review: {} hunk(s) accepted, {} rejected, {} edited; wrote {} file(s)",
+            self.accepted_hunks,
+            self.rejected_hunks,
+            self.edited_hunks,
+            self.written.len()
+        );
+        if !self.written.is_empty() {
+            out.push_str(&format!(" ({})", self.written.join(", ")));
+        }
+        for e in &self.errors {
+            out.push_str(&format!("\nError writing {e}"));
+        }
+        out
+    }
+}
+
+const CHOICE_ACCEPT: usize = 0;
+const CHOICE_REJECT: usize = 1;
+const CHOICE_EDIT: usize = 2;
+const CHOICE_ACCEPT_FILE: usize = 3;
+const CHOICE_REJECT_FILE: usize = 4;
+
+/// Walk the user through every pending hunk and write the results.
+///
+/// Each hunk is a question with Accept / Reject / Edit / Accept rest of file
+/// / Reject rest of file. Editing (`e` on the Edit choice) replaces the
+/// hunk's lines with the edited text. Skipping or timing out rejects.
+pub async fn review_changeset(
+    ui_state: &Arc<Mutex<UiState>>,
+    changeset: &Changeset,
+    workdir: &Path,
+) -> ReviewOutcome {
+    let mut outcome = ReviewOutcome::default();
+
+    for (path, pending) in changeset.take() {
+        let diff = FileDiff::new(&path, &pending);
+        if diff.hunks.is_empty() {
+            continue;
+        }
+
+        let mut decisions = Vec::with_capacity(diff.hunks.len());
+        let mut rest: Option<HunkDecision> = None;
+        for i in 0..diff.hunks.len() {
+            let decision = match rest {
+                Some(ref d) => d.clone(),
+                None => {
+                    let (decision, file_wide) = ask_hunk(ui_state, &diff, i).await;
+                    if file_wide {
+                        rest = Some(decision.clone());
+                    }
+                    decision
+                }
+            };
+            match decision {
+                HunkDecision::Accept => outcome.accepted_hunks += 1,
+                HunkDecision::Reject => outcome.rejected_hunks += 1,
+                HunkDecision::Edit(_) => outcome.edited_hunks += 1,
+            }
+            decisions.push(decision);
+        }
+
+        let content = diff.apply(&decisions);
+        let unchanged = match pending.original {
+            Some(ref original) => *original == content,
+            // A new file with every hunk rejected is never created.
+            None => decisions.iter().all(|d| *d == HunkDecision::Reject),
+        };
+        if unchanged {
+            continue;
+        }
+
+        let full_path = workdir.join(&path);
+        let result = match full_path.parent() {
+            Some(parent) => std::fs::create_dir_all(parent),
+            None => Ok(()),
+        }
+        .and_then(|()| std::fs::write(&full_path, &content));
+        match result {
+            Ok(()) => outcome.written.push(path),
+            Err(e) => outcome.errors.push(format!("{path}: {e}")),
+        }
+    }
+
+    outcome
+}
+
+/// Ask about one hunk. Returns the decision and whether it applies to the
+/// rest of the file.
+async fn ask_hunk(
+    ui_state: &Arc<Mutex<UiState>>,
+    diff: &FileDiff,
+    index: usize,
+) -> (HunkDecision, bool) {
+    let rendered = diff.render_hunk(index);
+    let choice = |label: &str, body: String| QuestionChoice {
+        label: label.to_string(),
+        body,
+        metadata: String::new(),
+    };
+    let question = UserQuestion {
+        prompt: format!(
+            "Review {}{} — hunk {}/{}",
+            diff.path,
+            if diff.is_new { " (new file)" } else { "" },
+            index + 1,
+            diff.hunks.len()
+        ),
+        choices: vec![
+            choice("Accept", rendered.clone()),
+            choice("Reject", rendered.clone()),
+            choice("Edit (press e)", diff.proposed_text(index)),
+            choice("Accept rest of file", rendered.clone()),
+            choice("Reject rest of file", rendered),
+        ],
+        editable: true,
+        max_edit_length: None,
+        ..Default::default()
+    };
+    let response = ask_and_wait(ui_state, question, REVIEW_TIMEOUT_SECS).await;
+
+    match response {
+        QuestionResponse::Selected(CHOICE_ACCEPT) => (HunkDecision::Accept, false),
+        QuestionResponse::Selected(CHOICE_ACCEPT_FILE) => (HunkDecision::Accept, true),
+        QuestionResponse::Selected(CHOICE_REJECT_FILE) => (HunkDecision::Reject, true),
+        // Selecting Edit without editing keeps the proposal as-is.
+        QuestionResponse::Selected(CHOICE_EDIT) => (HunkDecision::Accept, false),
+        QuestionResponse::SelectedEdited {
+            index: CHOICE_EDIT,
+            edited_text,
+        } => (HunkDecision::Edit(edited_text), false),
+        QuestionResponse::Selected(CHOICE_REJECT) => (HunkDecision::Reject, false),
+        // Skipped, timed out, or an edit of a diff view.
+        _ => (HunkDecision::Reject, false),
+    }
+}
+
+fn split_lines(text: &str) -> Vec<String> {
+    text.split_inclusive('\n').map(String::from).collect()
+}
+
+fn push_line(out: &mut String, prefix: char, line: &str) {
+    out.push(prefix);
+    out.push_str(line);
+    if !line.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(original: Option<&str>, proposed: &str) -> PendingFile {
+        PendingFile {
+            original: original.map(String::from),
+            proposed: proposed.into(),
+        }
+    }
+
+    #[test]
+    fn changeset_keeps_first_original() {
+        let cs = Changeset::new();
+        cs.stage("a.rs", Some("v0".into()), "v1".into());
+        cs.stage("a.rs", Some("v1".into()), "v2".into());
+        assert_eq!(cs.proposed("a.rs").as_deref(), Some("v2"));
+        let files = cs.take();
+        assert_eq!(files["a.rs"], pending(Some("v0"), "v2"));
+        assert!(cs.is_empty());
+    }
+
+    #[test]
+    fn hunks_can_be_accepted_independently() {
+        let diff = FileDiff::new(
+            "f.txt",
+            &pending(Some("a\nb\nc\nd\ne\nf\ng\nh\n"), "a\nB\nc\nd\ne\nf\ng\nH\n"),
+        );
+        assert_eq!(diff.hunks.len(), 2);
+
+        let content = diff.apply(&[HunkDecision::Accept, HunkDecision::Reject]);
+        assert_eq!(content, "a\nB\nc\nd\ne\nf\ng\nh\n");
+
+        let content = diff.apply(&[HunkDecision::Reject, HunkDecision::Edit("X".into())]);
+        assert_eq!(content, "a\nb\nc\nd\ne\nf\ng\nX\n");
+    }
+
+    #[test]
+    fn render_includes_context() {
+        let diff = FileDiff::new(
+            "f.txt",
+            &pending(Some("1\n2\n3\n4\n5\n"), "1\n2\nthree\n4\n5\n"),
+        );
+        assert_eq!(
+            diff.render_hunk(0),
+            "@@ -3,1 +3,1 @@\n 1\n 2\n-3\n+three\n 4\n 5\n"
+        );
+    }
+
+    #[test]
+    fn new_file_is_single_hunk() {
+        let diff = FileDiff::new("new.rs", &pending(None, "fn main() {}\n"));
+        assert!(diff.is_new);
+        assert_eq!(diff.hunks.len(), 1);
+        assert_eq!(diff.apply(&[HunkDecision::Accept]), "fn main() {}\n");
+    }
+}
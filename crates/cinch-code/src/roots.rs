@@ -0,0 +1,226 @@
+//! Multi-root workspaces.
+//!
+//! The first `--workdir` is the primary root: its tools keep their plain
+//! names (`read_file`, `git_status`, ...) and it hosts sessions, memory, and
+//! project instructions. Every additional root gets its own file, shell, and
+//! git tools under a namespaced name, `<root>__<tool>` (e.g.
+//! `infra__read_file`), whose paths are relative to that root. The system
+//! prompt lists the roots so the model knows which prefix targets which
+//! directory.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use cinch_rs::tools::common::{EditFile, FindFiles, Grep, ListDir, ReadFile, Shell, WriteFile};
+use cinch_rs::tools::core::{CommonToolsConfig, ToolSet};
+use cinch_rs::tools::read_tracker::ReadTracker;
+
+use crate::tools::GitToolsExt;
+
+/// Separator between a root name and a tool name.
+pub const ROOT_SEPARATOR: &str = "__";
+
+/// An additional workspace root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceRoot {
+    /// Short identifier used as the tool prefix (`[a-z0-9-]`).
+    pub name: String,
+    /// Absolute (or as-given) path of the root directory.
+    pub path: String,
+}
+
+impl WorkspaceRoot {
+    /// Name each of `paths` after its final component, de-duplicating
+    /// collisions with each other and with `taken` by appending `-2`, `-3`, ...
+    pub fn from_paths(paths: &[String], taken: &[&str]) -> Vec<Self> {
+        let mut used: Vec<String> = taken.iter().map(|s| s.to_string()).collect();
+        let mut roots = Vec::with_capacity(paths.len());
+        for path in paths {
+            let base = sanitize_name(
+                &Path::new(path)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+            );
+            let mut name = base.clone();
+            let mut n = 2;
+            while used.contains(&name) {
+                name = format!("{base}-{n}");
+                n += 1;
+            }
+            used.push(name.clone());
+            roots.push(Self {
+                name,
+                path: path.clone(),
+            });
+        }
+        roots
+    }
+
+    /// The namespaced name of `tool` for this root.
+    pub fn tool_name(&self, tool: &str) -> String {
+        format!("{}{ROOT_SEPARATOR}{tool}", self.name)
+    }
+
+    /// Register this root's file, shell, and git tools on `tools` under
+    /// namespaced names.
+    pub fn add_tools(&self, tools: ToolSet, common: &CommonToolsConfig) -> ToolSet {
+        let tracker = Arc::new(ReadTracker::new());
+        let wd = self.path.clone();
+        let allowed = &common.allowed_paths;
+        let mut shell = Shell::new(wd.clone())
+            .blocked_commands(common.shell_blocked_commands.clone())
+            .allowed_paths(allowed.clone())
+            .env(common.shell_env.clone())
+            .limits(common.shell_limits);
+        if let Some(ref budget) = common.subprocess_budget {
+            shell = shell.budget(budget.clone());
+        }
+        let root_tools = ToolSet::new()
+            .with(
+                ReadFile::new(wd.clone())
+                    .with_tracker(tracker.clone())
+                    .allowed_paths(allowed.clone()),
+            )
+            .with(ListDir::new(wd.clone()).allowed_paths(allowed.clone()))
+            .with(
+                Grep::new(wd.clone())
+                    .max_matches(common.grep_max_matches)
+                    .allowed_paths(allowed.clone()),
+            )
+            .with(
+                FindFiles::new(wd.clone())
+                    .max_results(common.find_max_results)
+                    .allowed_paths(allowed.clone()),
+            )
+            .with(shell)
+            .with(EditFile::new(wd.clone(), tracker.clone()).allowed_paths(allowed.clone()))
+            .with(WriteFile::new(wd.clone(), tracker).allowed_paths(allowed.clone()))
+            .with_git_tools(wd);
+        tools.with_namespaced(
+            &format!("{}{ROOT_SEPARATOR}", self.name),
+            &format!("[root `{}`: {}] ", self.name, self.path),
+            root_tools,
+        )
+    }
+}
+
+/// Split a possibly namespaced tool name into `(root, tool)`.
+pub fn split_tool_name(name: &str) -> (Option<&str>, &str) {
+    match name.split_once(ROOT_SEPARATOR) {
+        Some((root, tool)) if !root.is_empty() && !tool.is_empty() => (Some(root), tool),
+        _ => (None, name),
+    }
+}
+
+/// Display form of a root-relative path: `root:path` for extra roots,
+/// the bare path for the primary root.
+pub fn display_path(root: Option<&str>, path: &str) -> String {
+    match root {
+        Some(root) => format!("{root}:{path}"),
+        None => path.to_string(),
+    }
+}
+
+/// System prompt section describing the workspace roots. Empty when there
+/// is only the primary root.
+pub fn roots_prompt_section(primary: &str, extra: &[WorkspaceRoot]) -> String {
+    if extra.is_empty() {
+        return String::new();
+    }
+    let mut out = format!(
+        "## Workspace roots\n\n\
+         This session spans several directories. Tools without a prefix operate on \
+         the primary root `{primary}`. Each additional root has its own tools named \
+         `<root>{ROOT_SEPARATOR}<tool>`, and paths passed to them are relative to that root:\n"
+    );
+    for root in extra {
+        out.push_str(&format!(
+            "- `{}` → {} (e.g. `{}`)\n",
+            root.name,
+            root.path,
+            root.tool_name("read_file")
+        ));
+    }
+    out.push_str(
+        "\nWhen reporting file paths from an additional root, write them as `<root>:<path>`.",
+    );
+    out
+}
+
+fn sanitize_name(raw: &str) -> String {
+    let name: String = raw
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let name = name.trim_matches('-').to_string();
+    if name.is_empty() {
+        "root".to_string()
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_sanitized_and_unique() {
+        let roots = WorkspaceRoot::from_paths(
+            &[
+                "/src/Infra Repo".into(),
+                "/a/api".into(),
+                "/b/api".into(),
+                "/".into(),
+            ],
+            &["api-2"],
+        );
+        let names: Vec<&str> = roots.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["infra-repo", "api", "api-3", "root"]);
+    }
+
+    #[test]
+    fn tool_names_round_trip() {
+        let root = &WorkspaceRoot::from_paths(&["/x/infra".into()], &[])[0];
+        let name = root.tool_name("read_file");
+        assert_eq!(name, "infra__read_file");
+        assert_eq!(split_tool_name(&name), (Some("infra"), "read_file"));
+        assert_eq!(split_tool_name("read_file"), (None, "read_file"));
+        assert_eq!(display_path(Some("infra"), "main.tf"), "infra:main.tf");
+    }
+
+    #[tokio::test]
+    async fn root_tools_operate_on_their_root() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("main.tf"), "resource {}\n").unwrap();
+        let root = WorkspaceRoot {
+            name: "infra".into(),
+            path: dir.path().to_string_lossy().to_string(),
+        };
+        let tools = root.add_tools(ToolSet::new(), &CommonToolsConfig::default());
+        assert!(tools.has_tool("infra__git_status"));
+        assert!(!tools.has_tool("read_file"));
+
+        let out = tools
+            .execute("infra__read_file", r#"{"path":"main.tf"}"#)
+            .await;
+        assert!(out.contains("resource"), "got: {out}");
+    }
+
+    #[test]
+    fn prompt_section_lists_roots() {
+        assert!(roots_prompt_section("/app", &[]).is_empty());
+        let roots = WorkspaceRoot::from_paths(&["/infra".into()], &[]);
+        let section = roots_prompt_section("/app", &roots);
+        assert!(section.contains("`infra` → /infra"));
+        assert!(section.contains("infra__read_file"));
+    }
+}
@@ -0,0 +1,181 @@
+//! Project-defined tools backed by shell commands.
+//!
+//! Declared in `.cinch/config.toml` under `[[tools]]`. Each tool runs its
+//! command with `sh -c` in the working directory; the model's optional
+//! `input` string is passed via the `CINCH_TOOL_INPUT` environment variable
+//! rather than interpolated into the command line. Commands share the
+//! `shell` tool's [`ProcessLimits`] and subprocess budget.
+
+use cinch_rs::ToolDef;
+use cinch_rs::tools::core::{DEFAULT_MAX_RESULT_BYTES, Tool, ToolFuture, truncate_result};
+use cinch_rs::tools::host::ShellKind;
+use cinch_rs::tools::limits::{ProcessLimits, SubprocessBudget, output_with_limits};
+use cinch_rs::tools::output::ToolOutput;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// Declaration of a command-backed tool.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CommandToolConfig {
+    /// Tool name exposed to the model.
+    pub name: String,
+    /// Description shown to the model.
+    pub description: String,
+    /// Shell command to run.
+    pub command: String,
+    /// Whether the command modifies the workspace (invalidates the tool
+    /// result cache). Default: `false`.
+    #[serde(default)]
+    pub mutation: bool,
+}
+
+/// Arguments for a command tool.
+#[derive(Deserialize, JsonSchema)]
+pub struct CommandToolArgs {
+    /// Optional free-form input, exposed to the command as `$CINCH_TOOL_INPUT`.
+    #[serde(default)]
+    pub input: Option<String>,
+}
+
+/// A tool that runs a fixed shell command.
+pub struct CommandTool {
+    config: CommandToolConfig,
+    workdir: String,
+    limits: ProcessLimits,
+    budget: Option<Arc<SubprocessBudget>>,
+}
+
+impl CommandTool {
+    pub fn new(config: CommandToolConfig, workdir: impl Into<String>) -> Self {
+        Self {
+            config,
+            workdir: workdir.into(),
+            limits: ProcessLimits::default(),
+            budget: None,
+        }
+    }
+
+    /// Limit CPU time, memory, and output of the command.
+    pub fn limits(mut self, limits: ProcessLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Charge the command's wall time against `budget`.
+    pub fn budget(mut self, budget: Arc<SubprocessBudget>) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+}
+
+impl Tool for CommandTool {
+    fn definition(&self) -> ToolDef {
+        ToolDef::new(
+            self.config.name.clone(),
+            self.config.description.clone(),
+            cinch_rs::json_schema_for::<CommandToolArgs>(),
+        )
+    }
+
+    fn is_mutation(&self) -> bool {
+        self.config.mutation
+    }
+
+    fn runs_commands(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, arguments: &str) -> ToolFuture<'_> {
+        let command = self.config.command.clone();
+        let workdir = self.workdir.clone();
+        let limits = self.limits;
+        let budget = self.budget.clone();
+        let arguments = arguments.to_string();
+        Box::pin(async move {
+            let args: CommandToolArgs =
+                serde_json::from_str(&arguments).unwrap_or(CommandToolArgs { input: None });
+            if let Some(ref budget) = budget
+                && budget.is_exhausted()
+            {
+                return format!(
+                    "Error: command not run\n{}",
+                    budget.exhausted_trip().report()
+                );
+            }
+
+            let mut cmd = ShellKind::host().command(&command);
+            cmd.current_dir(&workdir)
+                .env("CINCH_TOOL_INPUT", args.input.unwrap_or_default());
+            let started = std::time::Instant::now();
+            let run = output_with_limits(&mut cmd, &limits);
+            let result = match budget {
+                None => run.await,
+                Some(ref budget) => {
+                    let remaining = budget.remaining();
+                    let outcome = tokio::time::timeout(remaining, run).await;
+                    budget.record(started.elapsed());
+                    match outcome {
+                        Ok(result) => result,
+                        Err(_) => {
+                            return format!(
+                                "Error: command stopped after {}s\n{}",
+                                remaining.as_secs(),
+                                budget.exhausted_trip().report()
+                            );
+                        }
+                    }
+                }
+            };
+
+            let (formatted, trip) = match result {
+                Ok((output, trip)) => (
+                    ToolOutput::from_process(&output, started.elapsed()).render(),
+                    trip,
+                ),
+                Err(e) => (format!("Error: failed to run command: {e}"), None),
+            };
+            let formatted = truncate_result(formatted, DEFAULT_MAX_RESULT_BYTES);
+            match trip {
+                Some(trip) => format!("{}\n{}", formatted.trim_end(), trip.report()),
+                None => formatted,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool(command: &str) -> CommandTool {
+        CommandTool::new(
+            CommandToolConfig {
+                name: "echo_input".into(),
+                description: "Echo the input".into(),
+                command: command.into(),
+                mutation: false,
+            },
+            "/tmp",
+        )
+    }
+
+    #[test]
+    fn command_tool_definition() {
+        let t = tool("true");
+        assert_eq!(t.definition().function.name, "echo_input");
+        assert!(!t.is_mutation());
+    }
+
+    #[tokio::test]
+    async fn command_tool_passes_input_via_env() {
+        let result = tool("printf %s \"$CINCH_TOOL_INPUT\"")
+            .execute(r#"{"input":"hi; rm -rf x"}"#)
+            .await;
+        let output = ToolOutput::parse(&result).unwrap();
+        assert!(output.success());
+        assert!(output.duration.is_some());
+        assert_eq!(output.stdout, "hi; rm -rf x");
+    }
+}
@@ -1,7 +1,6 @@
 //! Git tool implementations for the coding agent.
 //!
-//! Provides six git-aware tools that follow the cinch-rs
-//! [`Tool`] trait pattern:
+//! Provides git-aware tools that follow the cinch-rs [`Tool`] trait pattern:
 //!
 //! | Tool | Name | Purpose |
 //! |------|------|---------|
@@ -11,40 +10,60 @@
 //! | [`GitCommit`] | `git_commit` | Stage files and create a commit |
 //! | [`GitBranch`] | `git_branch` | List, create, or delete branches |
 //! | [`GitCheckout`] | `git_checkout` | Switch branches or restore files |
+//! | [`GitStash`] | `git_stash` | Stash and restore uncommitted changes |
+//! | [`GitRestore`] | `git_restore` | Discard changes to files or unstage them |
+//! | [`GitCherryPick`] | `git_cherry_pick` | Apply commits onto the current branch |
+//! | [`GitBlame`] | `git_blame` | Show who last changed each line |
+//! | [`GitShow`] | `git_show` | Show a commit or a file at a revision |
+//!
+//! `git_commit`, `git_checkout`, `git_stash`, `git_restore`, and
+//! `git_cherry_pick` change repository state; [`CodeConfig`](crate::CodeConfig)
+//! gates them on user approval (see [`APPROVAL_REQUIRED_GIT_TOOLS`](super::APPROVAL_REQUIRED_GIT_TOOLS)).
 
 use cinch_rs::ToolDef;
 use cinch_rs::tools::core::{DEFAULT_MAX_RESULT_BYTES, Tool, ToolFuture, truncate_result};
+use cinch_rs::tools::output::ToolOutput;
 use cinch_rs::tools::spec::ToolSpec;
 use schemars::JsonSchema;
 use serde::Deserialize;
+use std::path::Path;
 use tokio::process::Command;
 
 // ── Helper ──────────────────────────────────────────────────────────
 
-/// Run a git command in the given directory and return formatted output.
-async fn run_git(workdir: &str, args: &[&str]) -> String {
-    let result = Command::new("git")
-        .args(args)
-        .current_dir(workdir)
-        .output()
-        .await;
+/// Run a git command in the given directory and return its rendered
+/// [`ToolOutput`].
+pub(crate) async fn run_git(workdir: &str, args: &[&str]) -> String {
+    run_git_command(Command::new("git").args(args).current_dir(workdir)).await
+}
 
-    match result {
-        Ok(output) => {
-            let code = output.status.code().unwrap_or(-1);
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let stderr = String::from_utf8_lossy(&output.stderr);
+/// [`run_git`] with `index` as the index file instead of the repository's
+/// (`GIT_INDEX_FILE`), so staging commands leave the user's index alone.
+pub(crate) async fn run_git_with_index(workdir: &str, index: &Path, args: &[&str]) -> String {
+    run_git_command(
+        Command::new("git")
+            .args(args)
+            .current_dir(workdir)
+            .env("GIT_INDEX_FILE", index),
+    )
+    .await
+}
 
-            if stderr.is_empty() || output.status.success() {
-                format!("[exit: {code}]\n{stdout}")
-            } else {
-                format!("[exit: {code}]\n{stdout}\n[stderr]\n{stderr}")
-            }
-        }
+async fn run_git_command(cmd: &mut Command) -> String {
+    let started = std::time::Instant::now();
+    let result = cmd.output().await;
+
+    match result {
+        Ok(output) => ToolOutput::from_process(&output, started.elapsed()).render(),
         Err(e) => format!("Error: failed to run git: {e}"),
     }
 }
 
+/// Whether a [`run_git`] result is a run that exited 0.
+pub(crate) fn succeeded(result: &str) -> bool {
+    ToolOutput::parse(result).is_some_and(|o| o.success())
+}
+
 // ── GitStatus ───────────────────────────────────────────────────────
 
 /// Arguments for `git_status`.
@@ -270,7 +289,8 @@ impl Tool for GitLog {
 pub struct GitCommitArgs {
     /// Commit message.
     pub message: String,
-    /// Files to stage before committing. If empty, commits whatever is already staged.
+    /// Files to stage and commit. Only these paths are committed, leaving any
+    /// other staged changes alone. If empty, commits whatever is already staged.
     #[serde(default)]
     pub paths: Option<Vec<String>>,
 }
@@ -326,36 +346,42 @@ impl Tool for GitCommit {
                 return "Error: commit message must not be empty".to_string();
             }
 
-            // Validate paths.
-            if let Some(ref paths) = args.paths {
-                for p in paths {
-                    if p.contains("..") {
-                        return "Error: path traversal not allowed".to_string();
-                    }
-                }
-            }
-
-            // Stage files if paths are provided.
-            if let Some(ref paths) = args.paths
-                && !paths.is_empty()
-            {
-                let path_refs: Vec<&str> = paths.iter().map(|s| s.as_str()).collect();
-                let mut add_args = vec!["add"];
-                add_args.extend(path_refs);
-
-                let add_result = run_git(&workdir, &add_args).await;
-                if add_result.contains("[exit: 1]") || add_result.starts_with("Error:") {
-                    return format!("Error staging files: {add_result}");
-                }
+            let paths = args.paths.unwrap_or_default();
+            if paths.iter().any(|p| p.contains("..")) {
+                return "Error: path traversal not allowed".to_string();
             }
 
-            // Commit.
-            let commit_result = run_git(&workdir, &["commit", "-m", &args.message]).await;
-            truncate_result(commit_result, DEFAULT_MAX_RESULT_BYTES)
+            truncate_result(
+                commit_paths(&workdir, &paths, &args.message).await,
+                DEFAULT_MAX_RESULT_BYTES,
+            )
         })
     }
 }
 
+/// Stage `paths` and commit them with `message`.
+///
+/// When `paths` is non-empty only those paths are committed
+/// (`git commit -- <paths>`), so anything the user had staged separately
+/// stays out of the agent's commit. With no paths, commits the index as-is.
+pub async fn commit_paths(workdir: &str, paths: &[String], message: &str) -> String {
+    if !paths.is_empty() {
+        let mut add_args = vec!["add", "--"];
+        add_args.extend(paths.iter().map(|s| s.as_str()));
+        let add_result = run_git(workdir, &add_args).await;
+        if !succeeded(&add_result) {
+            return format!("Error staging files: {add_result}");
+        }
+    }
+
+    let mut commit_args = vec!["commit", "-m", message];
+    if !paths.is_empty() {
+        commit_args.push("--");
+        commit_args.extend(paths.iter().map(|s| s.as_str()));
+    }
+    run_git(workdir, &commit_args).await
+}
+
 // ── GitBranch ───────────────────────────────────────────────────────
 
 /// Arguments for `git_branch`.
@@ -530,6 +556,535 @@ impl Tool for GitCheckout {
     }
 }
 
+// ── Validation ──────────────────────────────────────────────────────
+
+/// Reject revisions and paths that git could parse as options, and paths
+/// that escape the workdir.
+fn validate_arg(kind: &str, value: &str) -> Result<(), String> {
+    if value.is_empty() {
+        return Err(format!("Error: {kind} must not be empty"));
+    }
+    if value.starts_with('-') {
+        return Err(format!("Error: {kind} must not start with '-'"));
+    }
+    if kind == "path" && value.contains("..") {
+        return Err("Error: path traversal not allowed".to_string());
+    }
+    Ok(())
+}
+
+/// Whether `rev` is a commit id (full or abbreviated hex) rather than a ref
+/// such as `HEAD` or a branch, whose target moves.
+fn is_commit_id(rev: &str) -> bool {
+    (7..=64).contains(&rev.len()) && rev.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+// ── GitStash ────────────────────────────────────────────────────────
+
+/// Arguments for `git_stash`.
+#[derive(Deserialize, JsonSchema)]
+pub struct GitStashArgs {
+    /// One of `list`, `show`, `push`, `pop`, `apply`. Default: `list`.
+    #[serde(default)]
+    pub action: Option<String>,
+    /// Message for `push`.
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Stash index for `show`, `pop`, and `apply` (stash@{index}). Default: 0.
+    #[serde(default)]
+    pub index: Option<u32>,
+    /// Include untracked files when pushing.
+    #[serde(default)]
+    pub include_untracked: Option<bool>,
+}
+
+/// Save, list, or restore stashed changes (`git stash`).
+///
+/// `drop` and `clear` are deliberately unsupported: they destroy work with
+/// no way back.
+pub struct GitStash {
+    workdir: String,
+}
+
+impl GitStash {
+    pub fn new(workdir: impl Into<String>) -> Self {
+        Self {
+            workdir: workdir.into(),
+        }
+    }
+}
+
+impl Tool for GitStash {
+    fn definition(&self) -> ToolDef {
+        ToolSpec::builder(super::GIT_STASH)
+            .purpose("Stash uncommitted changes or restore them later")
+            .when_to_use(
+                "When you need a clean working tree temporarily (e.g. to reproduce a bug on \
+                 HEAD) and want to bring the changes back afterwards",
+            )
+            .when_not_to_use(
+                "When you want to discard changes permanently — use git_restore. \
+                 Do not stash the user's work without a clear reason",
+            )
+            .parameters_for::<GitStashArgs>()
+            .example(
+                "git_stash(action='push', message='wip parser')",
+                "[exit: 0]\nSaved working directory and index state On main: wip parser",
+            )
+            .example(
+                "git_stash(action='list')",
+                "[exit: 0]\nstash@{0}: On main: wip parser",
+            )
+            .example(
+                "git_stash(action='pop')",
+                "[exit: 0]\nDropped refs/stash@{0}",
+            )
+            .build()
+            .to_tool_def()
+    }
+
+    fn is_mutation(&self) -> bool {
+        true
+    }
+
+    /// `list` and `show` only read the stash.
+    fn mutates_call(&self, arguments: &str) -> bool {
+        let action = serde_json::from_str::<GitStashArgs>(arguments)
+            .ok()
+            .and_then(|args| args.action);
+        !matches!(action.as_deref(), None | Some("list" | "show"))
+    }
+
+    fn execute(&self, arguments: &str) -> ToolFuture<'_> {
+        let workdir = self.workdir.clone();
+        let arguments = arguments.to_string();
+        Box::pin(async move {
+            let args: GitStashArgs = serde_json::from_str(&arguments).unwrap_or(GitStashArgs {
+                action: None,
+                message: None,
+                index: None,
+                include_untracked: None,
+            });
+
+            let stash_ref = format!("stash@{{{}}}", args.index.unwrap_or(0));
+            let mut cmd_args = vec!["stash"];
+            match args.action.as_deref().unwrap_or("list") {
+                "list" => cmd_args.push("list"),
+                "show" => cmd_args.extend(["show", "-p", stash_ref.as_str()]),
+                "pop" => cmd_args.extend(["pop", stash_ref.as_str()]),
+                "apply" => cmd_args.extend(["apply", stash_ref.as_str()]),
+                "push" => {
+                    cmd_args.push("push");
+                    if args.include_untracked.unwrap_or(false) {
+                        cmd_args.push("--include-untracked");
+                    }
+                    if let Some(ref msg) = args.message {
+                        cmd_args.extend(["-m", msg.as_str()]);
+                    }
+                }
+                other => {
+                    return format!(
+                        "Error: unsupported stash action '{other}' \
+                         (expected list, show, push, pop, or apply)"
+                    );
+                }
+            }
+
+            truncate_result(run_git(&workdir, &cmd_args).await, DEFAULT_MAX_RESULT_BYTES)
+        })
+    }
+}
+
+// ── GitRestore ──────────────────────────────────────────────────────
+
+/// Arguments for `git_restore`.
+#[derive(Deserialize, JsonSchema)]
+pub struct GitRestoreArgs {
+    /// Files or directories to restore.
+    pub paths: Vec<String>,
+    /// Unstage the paths instead of discarding working-tree changes.
+    #[serde(default)]
+    pub staged: Option<bool>,
+    /// Restore content from this commit instead of the index (e.g. 'HEAD~1').
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+/// Discard working-tree changes or unstage files (`git restore`).
+pub struct GitRestore {
+    workdir: String,
+}
+
+impl GitRestore {
+    pub fn new(workdir: impl Into<String>) -> Self {
+        Self {
+            workdir: workdir.into(),
+        }
+    }
+}
+
+impl Tool for GitRestore {
+    fn definition(&self) -> ToolDef {
+        ToolSpec::builder(super::GIT_RESTORE)
+            .purpose(
+                "Restore files to a committed state, discarding local changes, or unstage them",
+            )
+            .when_to_use(
+                "When an edit went wrong and you need to recover a file's previous content, \
+                 or to unstage files with staged=true",
+            )
+            .when_not_to_use(
+                "When the user may want to keep the changes — this permanently discards \
+                 uncommitted edits to the given paths. Use git_stash to set work aside instead",
+            )
+            .parameters_for::<GitRestoreArgs>()
+            .example("git_restore(paths=['src/lib.rs'])", "[exit: 0]\n")
+            .example(
+                "git_restore(paths=['src/lib.rs'], source='HEAD~1')",
+                "[exit: 0]\n",
+            )
+            .build()
+            .to_tool_def()
+    }
+
+    fn is_mutation(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, arguments: &str) -> ToolFuture<'_> {
+        let workdir = self.workdir.clone();
+        let arguments = arguments.to_string();
+        Box::pin(async move {
+            let args: GitRestoreArgs = match serde_json::from_str(&arguments) {
+                Ok(a) => a,
+                Err(_) => return "Error: 'paths' argument is required".to_string(),
+            };
+            if args.paths.is_empty() {
+                return "Error: paths must not be empty".to_string();
+            }
+            for p in &args.paths {
+                if let Err(e) = validate_arg("path", p) {
+                    return e;
+                }
+            }
+
+            let source_flag;
+            let mut cmd_args = vec!["restore"];
+            if args.staged.unwrap_or(false) {
+                cmd_args.push("--staged");
+            }
+            if let Some(ref source) = args.source {
+                if let Err(e) = validate_arg("source", source) {
+                    return e;
+                }
+                source_flag = format!("--source={source}");
+                cmd_args.push(&source_flag);
+            }
+            cmd_args.push("--");
+            cmd_args.extend(args.paths.iter().map(|s| s.as_str()));
+
+            truncate_result(run_git(&workdir, &cmd_args).await, DEFAULT_MAX_RESULT_BYTES)
+        })
+    }
+}
+
+// ── GitCherryPick ───────────────────────────────────────────────────
+
+/// Arguments for `git_cherry_pick`.
+#[derive(Deserialize, JsonSchema)]
+pub struct GitCherryPickArgs {
+    /// Commits to apply, in order. Ignored when `abort` is set.
+    #[serde(default)]
+    pub commits: Vec<String>,
+    /// Apply the changes without committing.
+    #[serde(default)]
+    pub no_commit: Option<bool>,
+    /// Abort an in-progress cherry-pick.
+    #[serde(default)]
+    pub abort: Option<bool>,
+}
+
+/// Apply commits from elsewhere onto the current branch (`git cherry-pick`).
+pub struct GitCherryPick {
+    workdir: String,
+}
+
+impl GitCherryPick {
+    pub fn new(workdir: impl Into<String>) -> Self {
+        Self {
+            workdir: workdir.into(),
+        }
+    }
+}
+
+impl Tool for GitCherryPick {
+    fn definition(&self) -> ToolDef {
+        ToolSpec::builder(super::GIT_CHERRY_PICK)
+            .purpose("Apply existing commits onto the current branch")
+            .when_to_use(
+                "When the user asks to bring a specific fix or change from another branch \
+                 onto the current one",
+            )
+            .when_not_to_use(
+                "When you need to inspect a commit first — use git_show. \
+                 On conflicts, resolve the files or call with abort=true",
+            )
+            .parameters_for::<GitCherryPickArgs>()
+            .example(
+                "git_cherry_pick(commits=['abc1234'])",
+                "[exit: 0]\n[main def5678] Fix parser bug",
+            )
+            .example("git_cherry_pick(abort=true)", "[exit: 0]\n")
+            .build()
+            .to_tool_def()
+    }
+
+    fn is_mutation(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, arguments: &str) -> ToolFuture<'_> {
+        let workdir = self.workdir.clone();
+        let arguments = arguments.to_string();
+        Box::pin(async move {
+            let args: GitCherryPickArgs =
+                serde_json::from_str(&arguments).unwrap_or(GitCherryPickArgs {
+                    commits: vec![],
+                    no_commit: None,
+                    abort: None,
+                });
+
+            if args.abort.unwrap_or(false) {
+                return truncate_result(
+                    run_git(&workdir, &["cherry-pick", "--abort"]).await,
+                    DEFAULT_MAX_RESULT_BYTES,
+                );
+            }
+            if args.commits.is_empty() {
+                return "Error: commits must not be empty".to_string();
+            }
+            for c in &args.commits {
+                if let Err(e) = validate_arg("commit", c) {
+                    return e;
+                }
+            }
+
+            let mut cmd_args = vec!["cherry-pick"];
+            if args.no_commit.unwrap_or(false) {
+                cmd_args.push("--no-commit");
+            }
+            cmd_args.extend(args.commits.iter().map(|s| s.as_str()));
+
+            truncate_result(run_git(&workdir, &cmd_args).await, DEFAULT_MAX_RESULT_BYTES)
+        })
+    }
+}
+
+// ── GitBlame ────────────────────────────────────────────────────────
+
+/// Arguments for `git_blame`.
+#[derive(Deserialize, JsonSchema)]
+pub struct GitBlameArgs {
+    /// File to annotate.
+    pub path: String,
+    /// First line to annotate (1-based).
+    #[serde(default)]
+    pub start_line: Option<u32>,
+    /// Last line to annotate (inclusive). Requires `start_line`.
+    #[serde(default)]
+    pub end_line: Option<u32>,
+    /// Annotate the file as of this revision instead of the working tree.
+    #[serde(default)]
+    pub rev: Option<String>,
+}
+
+/// Show which commit last touched each line of a file (`git blame`).
+pub struct GitBlame {
+    workdir: String,
+}
+
+impl GitBlame {
+    pub fn new(workdir: impl Into<String>) -> Self {
+        Self {
+            workdir: workdir.into(),
+        }
+    }
+}
+
+impl Tool for GitBlame {
+    fn definition(&self) -> ToolDef {
+        ToolSpec::builder(super::GIT_BLAME)
+            .purpose("Show the commit, author, and date that last changed each line of a file")
+            .when_to_use(
+                "When you need to know why or when a piece of code was introduced. \
+                 Pass start_line/end_line to keep the output small",
+            )
+            .when_not_to_use("When you just need the file content — use read_file instead")
+            .parameters_for::<GitBlameArgs>()
+            .example(
+                "git_blame(path='src/lib.rs', start_line=10, end_line=12)",
+                "[exit: 0]\nabc1234 (Ada 2024-01-02 10:00:00 +0000 10) fn parse() {",
+            )
+            .disambiguate(
+                "Seeing the full change a line came from",
+                "git_show",
+                "git_blame finds the commit; git_show displays it",
+            )
+            .build()
+            .to_tool_def()
+    }
+
+    fn cacheable(&self) -> bool {
+        true
+    }
+
+    /// Only blames of a fixed commit; the working tree and refs change.
+    fn caches_call(&self, arguments: &str) -> bool {
+        serde_json::from_str::<GitBlameArgs>(arguments)
+            .is_ok_and(|args| args.rev.as_deref().is_some_and(is_commit_id))
+    }
+
+    fn execute(&self, arguments: &str) -> ToolFuture<'_> {
+        let workdir = self.workdir.clone();
+        let arguments = arguments.to_string();
+        Box::pin(async move {
+            let args: GitBlameArgs = match serde_json::from_str(&arguments) {
+                Ok(a) => a,
+                Err(_) => return "Error: 'path' argument is required".to_string(),
+            };
+            if let Err(e) = validate_arg("path", &args.path) {
+                return e;
+            }
+
+            let range;
+            let mut cmd_args = vec!["blame"];
+            match (args.start_line, args.end_line) {
+                (Some(start), Some(end)) if end < start => {
+                    return "Error: end_line must not be before start_line".to_string();
+                }
+                (Some(start), end) => {
+                    range = match end {
+                        Some(end) => format!("-L{start},{end}"),
+                        None => format!("-L{start},"),
+                    };
+                    cmd_args.push(&range);
+                }
+                (None, Some(_)) => return "Error: end_line requires start_line".to_string(),
+                (None, None) => {}
+            }
+            if let Some(ref rev) = args.rev {
+                if let Err(e) = validate_arg("rev", rev) {
+                    return e;
+                }
+                cmd_args.push(rev);
+            }
+            cmd_args.push("--");
+            cmd_args.push(&args.path);
+
+            truncate_result(run_git(&workdir, &cmd_args).await, DEFAULT_MAX_RESULT_BYTES)
+        })
+    }
+}
+
+// ── GitShow ─────────────────────────────────────────────────────────
+
+/// Arguments for `git_show`.
+#[derive(Deserialize, JsonSchema)]
+pub struct GitShowArgs {
+    /// Commit, tag, or other revision. Default: HEAD.
+    #[serde(default)]
+    pub rev: Option<String>,
+    /// Show this file's content as of `rev` instead of the commit.
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Show only the diffstat, not the full patch.
+    #[serde(default)]
+    pub stat: Option<bool>,
+}
+
+/// Show a commit or a file at a revision (`git show`).
+pub struct GitShow {
+    workdir: String,
+}
+
+impl GitShow {
+    pub fn new(workdir: impl Into<String>) -> Self {
+        Self {
+            workdir: workdir.into(),
+        }
+    }
+}
+
+impl Tool for GitShow {
+    fn definition(&self) -> ToolDef {
+        ToolSpec::builder(super::GIT_SHOW)
+            .purpose("Show a commit's message and patch, or a file's content at a revision")
+            .when_to_use(
+                "When you need to inspect what a specific commit changed, or recover how a \
+                 file looked at an earlier revision",
+            )
+            .when_not_to_use(
+                "When you need a list of commits — use git_log. For uncommitted changes \
+                 use git_diff",
+            )
+            .parameters_for::<GitShowArgs>()
+            .example(
+                "git_show(rev='abc1234', stat=true)",
+                "[exit: 0]\ncommit abc1234\n\n    Fix parser\n\n src/parse.rs | 4 ++--",
+            )
+            .example(
+                "git_show(rev='HEAD~3', path='src/lib.rs')",
+                "[exit: 0]\n<file content at HEAD~3>",
+            )
+            .build()
+            .to_tool_def()
+    }
+
+    fn cacheable(&self) -> bool {
+        true
+    }
+
+    /// Only shows of a fixed commit; `HEAD` and other refs move.
+    fn caches_call(&self, arguments: &str) -> bool {
+        serde_json::from_str::<GitShowArgs>(arguments)
+            .is_ok_and(|args| args.rev.as_deref().is_some_and(is_commit_id))
+    }
+
+    fn execute(&self, arguments: &str) -> ToolFuture<'_> {
+        let workdir = self.workdir.clone();
+        let arguments = arguments.to_string();
+        Box::pin(async move {
+            let args: GitShowArgs = serde_json::from_str(&arguments).unwrap_or(GitShowArgs {
+                rev: None,
+                path: None,
+                stat: None,
+            });
+
+            let rev = args.rev.unwrap_or_else(|| "HEAD".to_string());
+            if let Err(e) = validate_arg("rev", &rev) {
+                return e;
+            }
+
+            let object;
+            let mut cmd_args = vec!["show"];
+            if let Some(ref path) = args.path {
+                if let Err(e) = validate_arg("path", path) {
+                    return e;
+                }
+                object = format!("{rev}:{path}");
+                cmd_args.push(&object);
+            } else {
+                if args.stat.unwrap_or(false) {
+                    cmd_args.push("--stat");
+                }
+                cmd_args.push(&rev);
+            }
+
+            truncate_result(run_git(&workdir, &cmd_args).await, DEFAULT_MAX_RESULT_BYTES)
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -638,4 +1193,68 @@ mod tests {
         let result = tool.execute(r#"{}"#).await;
         assert!(result.contains("Error"));
     }
+
+    #[test]
+    fn extended_tool_definitions() {
+        assert_eq!(
+            GitStash::new("/tmp").definition().function.name,
+            "git_stash"
+        );
+        assert!(GitStash::new("/tmp").is_mutation());
+        assert!(GitRestore::new("/tmp").is_mutation());
+        assert!(GitCherryPick::new("/tmp").is_mutation());
+        assert!(GitBlame::new("/tmp").cacheable());
+        assert!(!GitShow::new("/tmp").is_mutation());
+    }
+
+    #[test]
+    fn calls_are_classified_by_their_arguments() {
+        let stash = GitStash::new("/tmp");
+        assert!(!stash.mutates_call("{}"));
+        assert!(!stash.mutates_call(r#"{"action":"show","index":1}"#));
+        assert!(stash.mutates_call(r#"{"action":"pop"}"#));
+
+        let show = GitShow::new("/tmp");
+        assert!(!show.caches_call("{}"));
+        assert!(!show.caches_call(r#"{"rev":"HEAD~3"}"#));
+        assert!(!show.caches_call(r#"{"rev":"main"}"#));
+        assert!(show.caches_call(r#"{"rev":"abc1234","stat":true}"#));
+
+        let blame = GitBlame::new("/tmp");
+        assert!(!blame.caches_call(r#"{"path":"src/lib.rs"}"#));
+        assert!(blame.caches_call(r#"{"path":"src/lib.rs","rev":"abc1234def"}"#));
+    }
+
+    #[tokio::test]
+    async fn extended_tools_reject_option_injection() {
+        let result = GitShow::new("/tmp")
+            .execute(r#"{"rev":"--output=/tmp/x"}"#)
+            .await;
+        assert!(result.starts_with("Error"), "got: {result}");
+        let result = GitCherryPick::new("/tmp")
+            .execute(r#"{"commits":["-n"]}"#)
+            .await;
+        assert!(result.starts_with("Error"), "got: {result}");
+        let result = GitRestore::new("/tmp")
+            .execute(r#"{"paths":["../etc/passwd"]}"#)
+            .await;
+        assert!(result.contains("traversal"), "got: {result}");
+    }
+
+    #[tokio::test]
+    async fn git_stash_rejects_drop() {
+        let result = GitStash::new("/tmp").execute(r#"{"action":"drop"}"#).await;
+        assert!(result.contains("unsupported"), "got: {result}");
+    }
+
+    #[tokio::test]
+    async fn git_blame_validates_range() {
+        let tool = GitBlame::new("/tmp");
+        let result = tool
+            .execute(r#"{"path":"a.rs","start_line":5,"end_line":2}"#)
+            .await;
+        assert!(result.contains("end_line"), "got: {result}");
+        let result = tool.execute(r#"{"path":"a.rs","end_line":2}"#).await;
+        assert!(result.contains("requires start_line"), "got: {result}");
+    }
 }
@@ -1,11 +1,20 @@
 //! Git and coding-specific tools for the coding agent.
 //!
 //! Provides git-aware tools and the [`GitToolsExt`] trait for easy
-//! registration on a [`ToolSet`](cinch_rs::tools::core::ToolSet).
+//! registration on a [`ToolSet`](cinch_rs::tools::core::ToolSet), plus
+//! [`project`] build/test/lint tools for the detected project type.
 
+pub mod command;
 pub mod git;
+pub mod project;
+pub mod staged;
 
-pub use git::{GitBranch, GitCheckout, GitCommit, GitDiff, GitLog, GitStatus};
+pub use command::{CommandTool, CommandToolConfig};
+pub use git::{
+    GitBlame, GitBranch, GitCheckout, GitCherryPick, GitCommit, GitDiff, GitLog, GitRestore,
+    GitShow, GitStash, GitStatus,
+};
+pub use project::{CheckSummary, ProjectAction, ProjectKind, ProjectTool, project_tools};
 
 // ── Tool name constants ─────────────────────────────────────────────
 
@@ -15,6 +24,24 @@ pub const GIT_LOG: &str = "git_log";
 pub const GIT_COMMIT: &str = "git_commit";
 pub const GIT_BRANCH: &str = "git_branch";
 pub const GIT_CHECKOUT: &str = "git_checkout";
+pub const GIT_STASH: &str = "git_stash";
+pub const GIT_RESTORE: &str = "git_restore";
+pub const GIT_CHERRY_PICK: &str = "git_cherry_pick";
+pub const GIT_BLAME: &str = "git_blame";
+pub const GIT_SHOW: &str = "git_show";
+pub const BUILD: &str = "build";
+pub const TEST: &str = "test";
+pub const LINT: &str = "lint";
+
+/// Git tools that rewrite history or discard work, gated on approval by
+/// [`CodeConfig`](crate::CodeConfig).
+pub const APPROVAL_REQUIRED_GIT_TOOLS: &[&str] = &[
+    GIT_COMMIT,
+    GIT_CHECKOUT,
+    GIT_STASH,
+    GIT_RESTORE,
+    GIT_CHERRY_PICK,
+];
 
 // ── Extension trait ─────────────────────────────────────────────────
 
@@ -43,6 +70,11 @@ impl GitToolsExt for cinch_rs::tools::core::ToolSet {
             .with(GitLog::new(wd.clone()))
             .with(GitCommit::new(wd.clone()))
             .with(GitBranch::new(wd.clone()))
-            .with(GitCheckout::new(wd))
+            .with(GitCheckout::new(wd.clone()))
+            .with(GitStash::new(wd.clone()))
+            .with(GitRestore::new(wd.clone()))
+            .with(GitCherryPick::new(wd.clone()))
+            .with(GitBlame::new(wd.clone()))
+            .with(GitShow::new(wd))
     }
 }
@@ -0,0 +1,412 @@
+//! `build`, `test`, and `lint` tools for the detected project type.
+//!
+//! [`ProjectKind::detect`] recognizes a Cargo, npm (or pnpm/yarn/bun),
+//! Python (pip, poetry, uv), or Go project from the manifests at the workdir
+//! root, and [`project_tools`] turns the commands
+//! [`RepoScan`](crate::init::RepoScan) finds for it into tools. Each tool
+//! runs like a [`CommandTool`] and then appends a `[summary]` section parsed
+//! from the output — test counts, failing tests, and compiler or linter
+//! diagnostics with their locations — so the model doesn't have to guess
+//! commands through `shell` or read a whole log to find the one error.
+
+use super::command::{CommandTool, CommandToolConfig};
+use crate::init::RepoScan;
+use cinch_rs::ToolDef;
+use cinch_rs::tools::core::{Tool, ToolFuture};
+use cinch_rs::tools::limits::{ProcessLimits, SubprocessBudget};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Most failing tests or diagnostics listed in a summary.
+const MAX_SUMMARY_ITEMS: usize = 20;
+
+/// Ecosystem of a project, which decides its commands and output format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectKind {
+    /// `Cargo.toml`.
+    Cargo,
+    /// `package.json`.
+    Node,
+    /// `pyproject.toml` or `setup.py`.
+    Python,
+    /// `go.mod`.
+    Go,
+}
+
+impl ProjectKind {
+    /// The project type at `root`, checking manifests in the order above.
+    pub fn detect(root: &Path) -> Option<Self> {
+        if root.join("Cargo.toml").exists() {
+            Some(Self::Cargo)
+        } else if root.join("package.json").exists() {
+            Some(Self::Node)
+        } else if root.join("pyproject.toml").exists() || root.join("setup.py").exists() {
+            Some(Self::Python)
+        } else if root.join("go.mod").exists() {
+            Some(Self::Go)
+        } else {
+            None
+        }
+    }
+}
+
+/// Which of the three project tools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectAction {
+    Build,
+    Test,
+    Lint,
+}
+
+impl ProjectAction {
+    /// Tool name.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Build => super::BUILD,
+            Self::Test => super::TEST,
+            Self::Lint => super::LINT,
+        }
+    }
+
+    fn description(self, command: &str) -> String {
+        let what = match self {
+            Self::Build => "Build the project",
+            Self::Test => "Run the project's test suite",
+            Self::Lint => "Run the project's linters and format checks",
+        };
+        format!(
+            "{what} (`{command}`). Use this instead of running the command through `shell`. \
+             The output ends with a [summary] of failing tests and diagnostics with their \
+             file locations."
+        )
+    }
+}
+
+/// The `build`, `test`, and `lint` tools for the project at `workdir`, or
+/// none when its type isn't recognized. Actions without a detected command
+/// are left out.
+pub fn project_tools(workdir: &str) -> Vec<ProjectTool> {
+    let root = Path::new(workdir);
+    let Some(kind) = ProjectKind::detect(root) else {
+        return Vec::new();
+    };
+    let scan = RepoScan::scan_project(root, kind);
+    [
+        (ProjectAction::Build, &scan.build),
+        (ProjectAction::Test, &scan.test),
+        (ProjectAction::Lint, &scan.lint),
+    ]
+    .into_iter()
+    .filter(|(_, commands)| !commands.is_empty())
+    .map(|(action, commands)| ProjectTool::new(kind, action, commands.join(" && "), workdir))
+    .collect()
+}
+
+/// A preconfigured build, test, or lint command with parsed output.
+pub struct ProjectTool {
+    kind: ProjectKind,
+    inner: CommandTool,
+}
+
+impl ProjectTool {
+    pub fn new(
+        kind: ProjectKind,
+        action: ProjectAction,
+        command: impl Into<String>,
+        workdir: impl Into<String>,
+    ) -> Self {
+        let command = command.into();
+        let config = CommandToolConfig {
+            name: action.name().into(),
+            description: action.description(&command),
+            command,
+            // Builds write artifacts (and `npm install` writes node_modules).
+            mutation: action == ProjectAction::Build,
+        };
+        Self {
+            kind,
+            inner: CommandTool::new(config, workdir),
+        }
+    }
+
+    /// Limit CPU time, memory, and output of the command.
+    pub fn limits(mut self, limits: ProcessLimits) -> Self {
+        self.inner = self.inner.limits(limits);
+        self
+    }
+
+    /// Charge the command's wall time against `budget`.
+    pub fn budget(mut self, budget: Arc<SubprocessBudget>) -> Self {
+        self.inner = self.inner.budget(budget);
+        self
+    }
+}
+
+impl Tool for ProjectTool {
+    fn definition(&self) -> ToolDef {
+        let mut def = self.inner.definition();
+        def.function.parameters = serde_json::json!({"type": "object", "properties": {}});
+        def
+    }
+
+    fn is_mutation(&self) -> bool {
+        self.inner.is_mutation()
+    }
+
+    fn runs_commands(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, _arguments: &str) -> ToolFuture<'_> {
+        Box::pin(async move {
+            let result = self.inner.execute("{}").await;
+            match CheckSummary::parse(self.kind, &result).render() {
+                Some(summary) => format!("{}\n[summary]\n{summary}", result.trim_end()),
+                None => result,
+            }
+        })
+    }
+}
+
+/// What a build, test, or lint run reported, parsed from its output.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CheckSummary {
+    /// Passed tests, when the runner reports a count.
+    pub passed: Option<u32>,
+    /// Failed tests, when the runner reports a count.
+    pub failed: Option<u32>,
+    /// Names of failing tests.
+    pub failures: Vec<String>,
+    /// Compiler and linter messages as `path:line[:col]: message`.
+    pub diagnostics: Vec<String>,
+}
+
+impl CheckSummary {
+    /// Parse the output of a `kind` project's command.
+    pub fn parse(kind: ProjectKind, output: &str) -> Self {
+        let mut summary = Self::default();
+        let lines: Vec<&str> = output.lines().collect();
+        for (i, line) in lines.iter().enumerate() {
+            let trimmed = line.trim();
+            match kind {
+                ProjectKind::Cargo => summary.parse_cargo_line(trimmed, &lines[i + 1..]),
+                ProjectKind::Node => summary.parse_node_line(trimmed),
+                ProjectKind::Python => summary.parse_python_line(trimmed),
+                ProjectKind::Go => summary.parse_go_line(trimmed),
+            }
+        }
+        summary.failures.dedup();
+        summary.diagnostics.dedup();
+        summary
+    }
+
+    /// Cargo: `test result:` totals, `test x ... FAILED`, and `error` or
+    /// `warning` headers with their `-->` location on a following line.
+    fn parse_cargo_line(&mut self, line: &str, rest: &[&str]) {
+        if let Some(totals) = line.strip_prefix("test result:") {
+            add(&mut self.passed, count_before(totals, "passed"));
+            add(&mut self.failed, count_before(totals, "failed"));
+        } else if let Some(name) = line
+            .strip_prefix("test ")
+            .and_then(|l| l.strip_suffix(" ... FAILED"))
+        {
+            self.failures.push(name.to_string());
+        } else if line.starts_with("error") || line.starts_with("warning") {
+            let location = rest
+                .iter()
+                .take(3)
+                .find_map(|l| l.trim().strip_prefix("--> "));
+            if let Some(location) = location {
+                self.diagnostics.push(format!("{location}: {line}"));
+            }
+        }
+    }
+
+    /// Jest/Vitest `Tests:` totals and `●`/`FAIL` lines, plus `tsc` errors.
+    fn parse_node_line(&mut self, line: &str) {
+        if line.starts_with("Tests") {
+            add(&mut self.passed, count_before(line, "passed"));
+            add(&mut self.failed, count_before(line, "failed"));
+        } else if let Some(name) = line.strip_prefix("● ") {
+            self.failures.push(name.trim().to_string());
+        } else if let Some(name) = line.strip_prefix("FAIL ").filter(|n| n.contains(" > ")) {
+            self.failures.push(name.trim().to_string());
+        } else if line.contains("error TS") {
+            self.diagnostics.push(line.to_string());
+        }
+    }
+
+    /// pytest `=== N failed, M passed ===` totals and `FAILED` lines, plus
+    /// located ruff/mypy messages.
+    fn parse_python_line(&mut self, line: &str) {
+        if line.starts_with('=') && line.ends_with('=') && line.contains(" in ") {
+            add(&mut self.passed, count_before(line, "passed"));
+            add(&mut self.failed, count_before(line, "failed"));
+        } else if let Some(name) = line.strip_prefix("FAILED ") {
+            self.failures.push(name.to_string());
+        } else if is_located(line) {
+            self.diagnostics.push(line.to_string());
+        }
+    }
+
+    /// `--- FAIL`/`--- PASS` lines and located compiler or vet messages.
+    fn parse_go_line(&mut self, line: &str) {
+        if let Some(rest) = line.strip_prefix("--- FAIL: ") {
+            let name = rest.split_whitespace().next().unwrap_or(rest);
+            self.failures.push(name.to_string());
+            add(&mut self.failed, Some(1));
+        } else if line.starts_with("--- PASS: ") {
+            add(&mut self.passed, Some(1));
+        } else if is_located(line) {
+            self.diagnostics.push(line.to_string());
+        }
+    }
+
+    /// The `[summary]` body, or `None` when nothing was recognized.
+    pub fn render(&self) -> Option<String> {
+        let mut lines = Vec::new();
+        if self.passed.is_some() || self.failed.is_some() {
+            lines.push(format!(
+                "Tests: {} passed, {} failed",
+                self.passed.unwrap_or(0),
+                self.failed.unwrap_or(0)
+            ));
+        }
+        for (heading, items) in [
+            ("Failing tests", &self.failures),
+            ("Diagnostics", &self.diagnostics),
+        ] {
+            if items.is_empty() {
+                continue;
+            }
+            lines.push(format!("{heading} ({}):", items.len()));
+            lines.extend(
+                items
+                    .iter()
+                    .take(MAX_SUMMARY_ITEMS)
+                    .map(|item| format!("- {item}")),
+            );
+            if items.len() > MAX_SUMMARY_ITEMS {
+                lines.push(format!("- ... ({} more)", items.len() - MAX_SUMMARY_ITEMS));
+            }
+        }
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+}
+
+/// Add `n` to a running total.
+fn add(total: &mut Option<u32>, n: Option<u32>) {
+    if let Some(n) = n {
+        *total = Some(total.unwrap_or(0) + n);
+    }
+}
+
+/// The number right before `word`, e.g. `3` in `"3 passed; 0 failed"`.
+fn count_before(text: &str, word: &str) -> Option<u32> {
+    let words: Vec<&str> = text
+        .split(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | '|' | '='))
+        .filter(|w| !w.is_empty())
+        .collect();
+    words
+        .windows(2)
+        .find(|pair| pair[1] == word)
+        .and_then(|pair| pair[0].parse().ok())
+}
+
+/// Whether `line` starts with a `path:line:` location, e.g.
+/// `./main.go:3:2: undefined: x`.
+fn is_located(line: &str) -> bool {
+    let mut parts = line.splitn(3, ':');
+    let (Some(path), Some(number), Some(_)) = (parts.next(), parts.next(), parts.next()) else {
+        return false;
+    };
+    path.contains('.')
+        && !path.contains(char::is_whitespace)
+        && !number.is_empty()
+        && number.chars().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_project_tools_from_the_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let workdir = dir.path().to_str().unwrap();
+        assert!(project_tools(workdir).is_empty());
+
+        std::fs::write(dir.path().join("go.mod"), "module example.com/x\n").unwrap();
+        assert_eq!(ProjectKind::detect(dir.path()), Some(ProjectKind::Go));
+        let tools = project_tools(workdir);
+        let names: Vec<String> = tools.iter().map(|t| t.definition().function.name).collect();
+        assert_eq!(names, ["build", "test", "lint"]);
+        assert!(tools[0].is_mutation());
+        assert!(!tools[1].is_mutation());
+        assert!(
+            tools[1]
+                .definition()
+                .function
+                .description
+                .contains("`go test ./...`")
+        );
+    }
+
+    #[test]
+    fn parses_cargo_failures_and_diagnostics() {
+        let output = "\
+error[E0308]: mismatched types
+  --> src/lib.rs:3:5
+   |
+test parser::tests::round_trip ... FAILED
+test parser::tests::empty ... ok
+test result: FAILED. 7 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out
+test result: ok. 2 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out
+";
+        let summary = CheckSummary::parse(ProjectKind::Cargo, output);
+        assert_eq!(summary.passed, Some(9));
+        assert_eq!(summary.failed, Some(1));
+        assert_eq!(summary.failures, ["parser::tests::round_trip"]);
+        assert_eq!(
+            summary.diagnostics,
+            ["src/lib.rs:3:5: error[E0308]: mismatched types"]
+        );
+        assert!(
+            summary
+                .render()
+                .unwrap()
+                .starts_with("Tests: 9 passed, 1 failed\nFailing tests (1):")
+        );
+    }
+
+    #[test]
+    fn parses_pytest_jest_and_go_output() {
+        let pytest = CheckSummary::parse(
+            ProjectKind::Python,
+            "FAILED tests/test_api.py::test_get - KeyError\n\
+             src/app.py:12: error: Incompatible types\n\
+             ===== 1 failed, 4 passed in 0.31s =====",
+        );
+        assert_eq!((pytest.passed, pytest.failed), (Some(4), Some(1)));
+        assert_eq!(pytest.failures, ["tests/test_api.py::test_get - KeyError"]);
+        assert_eq!(
+            pytest.diagnostics,
+            ["src/app.py:12: error: Incompatible types"]
+        );
+
+        let jest = CheckSummary::parse(
+            ProjectKind::Node,
+            "  ● math › adds\nTests:       1 failed, 5 passed, 6 total",
+        );
+        assert_eq!((jest.passed, jest.failed), (Some(5), Some(1)));
+        assert_eq!(jest.failures, ["math › adds"]);
+
+        let go = CheckSummary::parse(
+            ProjectKind::Go,
+            "./main.go:3:2: undefined: x\n--- FAIL: TestAdd (0.00s)\nFAIL\texample.com/x",
+        );
+        assert_eq!(go.failures, ["TestAdd"]);
+        assert_eq!(go.diagnostics, ["./main.go:3:2: undefined: x"]);
+        assert_eq!(CheckSummary::parse(ProjectKind::Go, "ok\tx").render(), None);
+    }
+}
@@ -0,0 +1,357 @@
+//! File tools that stage writes in a [`Changeset`] instead of touching disk.
+//!
+//! Drop-in replacements for `read_file`, `edit_file`, and `write_file` used
+//! in review mode. They share the definitions of the cinch-rs originals so
+//! the model sees no difference; reads of a staged path return the staged
+//! content so follow-up edits compose. Other tools (`grep`, `shell`) still
+//! see the on-disk state until the changes are reviewed and applied.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use cinch_rs::ToolDef;
+use cinch_rs::tools::common::{EditFile, EditFileArgs, ReadFile, WriteFile, WriteFileArgs};
+use cinch_rs::tools::core::{DEFAULT_MAX_RESULT_BYTES, Tool, ToolFuture, truncate_result};
+use cinch_rs::tools::paths::{resolve_in_workdir, workdir_relative};
+use cinch_rs::tools::read_tracker::ReadTracker;
+use serde::Deserialize;
+
+use crate::review::Changeset;
+
+/// Arguments for `read_file` (mirrors the cinch-rs schema).
+#[derive(Deserialize)]
+struct ReadArgs {
+    path: String,
+    #[serde(default)]
+    offset: Option<u32>,
+    #[serde(default)]
+    limit: Option<u32>,
+}
+
+/// Default line limit, matching the cinch-rs `read_file`.
+const DEFAULT_READ_LINE_LIMIT: usize = 2000;
+
+/// The changeset key for `path`: relative to `workdir` and normalized, so
+/// every alias of a file (`a.rs`, `./a.rs`, `/wd/a.rs`) stages into one entry.
+fn staged_key(workdir: &str, path: &str) -> String {
+    workdir_relative(Path::new(workdir), path)
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// `read_file` that prefers staged content.
+pub struct StagedReadFile {
+    inner: ReadFile,
+    workdir: String,
+    changeset: Arc<Changeset>,
+    tracker: Arc<ReadTracker>,
+}
+
+impl StagedReadFile {
+    pub fn new(
+        workdir: impl Into<String>,
+        changeset: Arc<Changeset>,
+        tracker: Arc<ReadTracker>,
+    ) -> Self {
+        let workdir = workdir.into();
+        Self {
+            inner: ReadFile::new(workdir.clone()).with_tracker(tracker.clone()),
+            workdir,
+            changeset,
+            tracker,
+        }
+    }
+
+    /// Also allow paths under these directories (see [`resolve_in_workdir`]).
+    pub fn allowed_paths(mut self, paths: Vec<PathBuf>) -> Self {
+        self.inner = self.inner.allowed_paths(paths);
+        self
+    }
+}
+
+impl Tool for StagedReadFile {
+    fn definition(&self) -> ToolDef {
+        self.inner.definition()
+    }
+
+    fn prompt_guidelines(&self) -> Vec<String> {
+        self.inner.prompt_guidelines()
+    }
+
+    fn execute(&self, arguments: &str) -> ToolFuture<'_> {
+        let staged = serde_json::from_str::<ReadArgs>(arguments)
+            .ok()
+            .and_then(|args| {
+                let key = staged_key(&self.workdir, &args.path);
+                self.changeset.proposed(&key).map(|c| (args, key, c))
+            });
+        let Some((args, key, content)) = staged else {
+            return self.inner.execute(arguments);
+        };
+
+        let abs = Path::new(&self.workdir).join(key);
+        self.tracker.record_read(&abs.to_string_lossy(), &content);
+
+        let offset = args.offset.unwrap_or(1).max(1) as usize;
+        let limit = args.limit.map_or(DEFAULT_READ_LINE_LIMIT, |l| l as usize);
+        let mut output = format!("[{} has pending edits awaiting review]\n", args.path);
+        for (i, line) in content.lines().enumerate().skip(offset - 1).take(limit) {
+            output.push_str(&format!("L{}: {line}\n", i + 1));
+        }
+        Box::pin(async move { truncate_result(output, DEFAULT_MAX_RESULT_BYTES) })
+    }
+}
+
+/// `edit_file` that applies the replacement to staged (or on-disk) content
+/// and stages the result.
+pub struct StagedEditFile {
+    inner: EditFile,
+    workdir: String,
+    allowed_paths: Vec<PathBuf>,
+    changeset: Arc<Changeset>,
+    tracker: Arc<ReadTracker>,
+}
+
+impl StagedEditFile {
+    pub fn new(
+        workdir: impl Into<String>,
+        changeset: Arc<Changeset>,
+        tracker: Arc<ReadTracker>,
+    ) -> Self {
+        let workdir = workdir.into();
+        Self {
+            inner: EditFile::new(workdir.clone(), tracker.clone()),
+            workdir,
+            allowed_paths: Vec::new(),
+            changeset,
+            tracker,
+        }
+    }
+
+    /// Also allow paths under these directories (see [`resolve_in_workdir`]).
+    pub fn allowed_paths(mut self, paths: Vec<PathBuf>) -> Self {
+        self.inner = self.inner.allowed_paths(paths.clone());
+        self.allowed_paths = paths;
+        self
+    }
+}
+
+impl Tool for StagedEditFile {
+    fn definition(&self) -> ToolDef {
+        self.inner.definition()
+    }
+
+    fn prompt_guidelines(&self) -> Vec<String> {
+        self.inner.prompt_guidelines()
+    }
+
+    fn is_mutation(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, arguments: &str) -> ToolFuture<'_> {
+        let result = (|| {
+            let args: EditFileArgs = serde_json::from_str(arguments)
+                .map_err(|_| "Error: 'path', 'old_string', and 'new_string' are required")?;
+            let abs = resolve_in_workdir(&self.workdir, &args.path, &self.allowed_paths)
+                .map_err(|e| format!("Error: {e}"))?;
+            let abs_str = abs.to_string_lossy().to_string();
+            if !self.tracker.has_been_read(&abs_str) {
+                return Err("Error: You must read this file before editing it. \
+                            Use read_file first."
+                    .to_string());
+            }
+
+            let key = staged_key(&self.workdir, &args.path);
+            let on_disk = std::fs::read_to_string(&abs).ok();
+            let current = match self.changeset.proposed(&key).or(on_disk.clone()) {
+                Some(c) => c,
+                None => return Err(format!("Error reading '{}': file not found", args.path)),
+            };
+
+            let count = current.matches(&args.old_string).count();
+            let replace_all = args.replace_all.unwrap_or(false);
+            if count == 0 {
+                return Err(format!(
+                    "Error: old_string not found in {}. \
+                     Verify the exact text (including whitespace and indentation).",
+                    args.path
+                ));
+            }
+            if count > 1 && !replace_all {
+                return Err(format!(
+                    "Error: old_string found {count} times in {}. \
+                     Provide more surrounding context to make it unique, or set replace_all=true.",
+                    args.path
+                ));
+            }
+
+            let updated = if replace_all {
+                current.replace(&args.old_string, &args.new_string)
+            } else {
+                current.replacen(&args.old_string, &args.new_string, 1)
+            };
+            self.tracker.record_write(&abs_str, &updated);
+            self.changeset.stage(&key, on_disk, updated);
+            Ok(format!(
+                "Staged edit to {}: replaced {count} occurrence{} (pending review)",
+                args.path,
+                if count == 1 { "" } else { "s" }
+            ))
+        })();
+        let out = result.unwrap_or_else(|e| e.to_string());
+        Box::pin(async move { out })
+    }
+}
+
+/// `write_file` that stages the full content.
+pub struct StagedWriteFile {
+    inner: WriteFile,
+    workdir: String,
+    allowed_paths: Vec<PathBuf>,
+    changeset: Arc<Changeset>,
+    tracker: Arc<ReadTracker>,
+}
+
+impl StagedWriteFile {
+    pub fn new(
+        workdir: impl Into<String>,
+        changeset: Arc<Changeset>,
+        tracker: Arc<ReadTracker>,
+    ) -> Self {
+        let workdir = workdir.into();
+        Self {
+            inner: WriteFile::new(workdir.clone(), tracker.clone()),
+            workdir,
+            allowed_paths: Vec::new(),
+            changeset,
+            tracker,
+        }
+    }
+
+    /// Also allow paths under these directories (see [`resolve_in_workdir`]).
+    pub fn allowed_paths(mut self, paths: Vec<PathBuf>) -> Self {
+        self.inner = self.inner.allowed_paths(paths.clone());
+        self.allowed_paths = paths;
+        self
+    }
+}
+
+impl Tool for StagedWriteFile {
+    fn definition(&self) -> ToolDef {
+        self.inner.definition()
+    }
+
+    fn is_mutation(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, arguments: &str) -> ToolFuture<'_> {
+        let out = match serde_json::from_str::<WriteFileArgs>(arguments) {
+            Err(_) => "Error: 'path' and 'content' arguments are required".to_string(),
+            Ok(args) => match resolve_in_workdir(&self.workdir, &args.path, &self.allowed_paths) {
+                Err(e) => format!("Error: {e}"),
+                Ok(abs) => {
+                    let abs_str = abs.to_string_lossy().to_string();
+                    let key = staged_key(&self.workdir, &args.path);
+                    let on_disk = std::fs::read_to_string(&abs).ok();
+                    let exists = on_disk.is_some() || self.changeset.proposed(&key).is_some();
+                    if exists && !self.tracker.has_been_read(&abs_str) {
+                        "Error: You must read this file before overwriting it. \
+                     Use read_file first."
+                            .to_string()
+                    } else {
+                        let lines = args.content.lines().count();
+                        self.tracker.record_write(&abs_str, &args.content);
+                        self.changeset.stage(&key, on_disk, args.content);
+                        format!(
+                            "Staged {lines} line{} for {} (pending review)",
+                            if lines == 1 { "" } else { "s" },
+                            args.path
+                        )
+                    }
+                }
+            },
+        };
+        Box::pin(async move { out })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tools(
+        dir: &Path,
+    ) -> (
+        StagedReadFile,
+        StagedEditFile,
+        StagedWriteFile,
+        Arc<Changeset>,
+    ) {
+        let workdir = dir.to_string_lossy().to_string();
+        let cs = Arc::new(Changeset::new());
+        let tracker = Arc::new(ReadTracker::new());
+        (
+            StagedReadFile::new(workdir.clone(), cs.clone(), tracker.clone()),
+            StagedEditFile::new(workdir.clone(), cs.clone(), tracker.clone()),
+            StagedWriteFile::new(workdir, cs.clone(), tracker),
+            cs,
+        )
+    }
+
+    #[tokio::test]
+    async fn edits_are_staged_not_written() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "hello\n").unwrap();
+        let (read, edit, _, cs) = tools(dir.path());
+
+        read.execute(r#"{"path":"a.txt"}"#).await;
+        let out = edit
+            .execute(r#"{"path":"a.txt","old_string":"hello","new_string":"bye"}"#)
+            .await;
+        assert!(out.starts_with("Staged edit"), "got: {out}");
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "hello\n"
+        );
+
+        let out = read.execute(r#"{"path":"a.txt"}"#).await;
+        assert!(out.contains("L1: bye"), "got: {out}");
+        assert_eq!(cs.take()["a.txt"].original.as_deref(), Some("hello\n"));
+    }
+
+    #[tokio::test]
+    async fn write_requires_read_for_existing_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "x").unwrap();
+        let (_, _, write, cs) = tools(dir.path());
+
+        let out = write.execute(r#"{"path":"a.txt","content":"y"}"#).await;
+        assert!(out.contains("must read"), "got: {out}");
+        let out = write.execute(r#"{"path":"new.txt","content":"y\n"}"#).await;
+        assert!(out.starts_with("Staged 1 line"), "got: {out}");
+        assert!(cs.take()["new.txt"].original.is_none());
+        assert!(!dir.path().join("new.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn aliases_of_a_file_stage_one_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one two\n").unwrap();
+        let (read, edit, _, cs) = tools(dir.path());
+        let absolute = dir.path().join("a.txt");
+
+        read.execute(r#"{"path":"a.txt"}"#).await;
+        edit.execute(r#"{"path":"./a.txt","old_string":"one","new_string":"1"}"#)
+            .await;
+        let args = serde_json::json!({"path": absolute, "old_string": "two", "new_string": "2"});
+        let out = edit.execute(&args.to_string()).await;
+        assert!(out.starts_with("Staged edit"), "got: {out}");
+
+        let staged = cs.take();
+        assert_eq!(staged.len(), 1, "{:?}", staged.keys().collect::<Vec<_>>());
+        assert_eq!(staged["a.txt"].proposed, "1 2\n");
+        assert_eq!(staged["a.txt"].original.as_deref(), Some("one two\n"));
+    }
+}
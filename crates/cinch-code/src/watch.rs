@@ -0,0 +1,229 @@
+//! Watch mode: re-run a prompt whenever files matching a glob change.
+//!
+//! [`FileWatcher`] polls the workdir for modification-time changes on
+//! paths matching the pattern (same glob syntax as conditional project
+//! rules: `**`, `*`, `?`). [`FileWatcher::next_batch`] waits for a change,
+//! then keeps collecting until the tree has been quiet for the debounce
+//! window, so a burst of saves triggers a single run.
+//!
+//! Runs are sequential. Changes that land while the agent is running stay
+//! queued and trigger the next run; changes the agent made itself are
+//! dropped with [`FileWatcher::ignore`] so an agent that edits watched files
+//! does not re-trigger itself forever.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use cinch_rs::agent::project_instructions::glob_matches;
+
+/// Default quiet period before a batch of changes triggers a run.
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// How often the tree is rescanned.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Directories never descended into.
+const SKIP_DIRS: &[&str] = &["target", "node_modules"];
+
+/// Polling file watcher for a single glob under a root directory.
+pub struct FileWatcher {
+    root: PathBuf,
+    pattern: String,
+    debounce: Duration,
+    /// Last observed mtime per workdir-relative path.
+    snapshot: BTreeMap<String, SystemTime>,
+    /// Changes seen but not yet handed out by `next_batch`.
+    pending: BTreeSet<String>,
+}
+
+impl FileWatcher {
+    /// Create a watcher and take the initial snapshot; existing files do
+    /// not count as changes.
+    pub fn new(root: impl Into<PathBuf>, pattern: impl Into<String>) -> Self {
+        let mut watcher = Self {
+            root: root.into(),
+            pattern: pattern.into(),
+            debounce: DEFAULT_DEBOUNCE,
+            snapshot: BTreeMap::new(),
+            pending: BTreeSet::new(),
+        };
+        watcher.snapshot = watcher.scan();
+        watcher
+    }
+
+    /// Set the quiet period before a batch is handed out.
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// The watched glob.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Rescan and queue every path that was added, modified, or removed
+    /// since the previous poll. Returns `true` if anything new was queued.
+    pub fn poll(&mut self) -> bool {
+        let current = self.scan();
+        let before = self.pending.len();
+        for (path, mtime) in &current {
+            if self.snapshot.get(path) != Some(mtime) {
+                self.pending.insert(path.clone());
+            }
+        }
+        for path in self.snapshot.keys() {
+            if !current.contains_key(path) {
+                self.pending.insert(path.clone());
+            }
+        }
+        self.snapshot = current;
+        self.pending.len() > before
+    }
+
+    /// Drop queued changes to `paths` (e.g. files the agent just wrote).
+    pub fn ignore(&mut self, paths: &[String]) {
+        self.poll();
+        for path in paths {
+            self.pending.remove(path.trim_start_matches("./"));
+        }
+    }
+
+    /// Wait until at least one matching file changes and no further change
+    /// has been seen for the debounce window, then return the sorted
+    /// changed paths.
+    pub async fn next_batch(&mut self) -> Vec<String> {
+        let debounce = self.debounce;
+        loop {
+            self.poll();
+            if !self.pending.is_empty() {
+                break;
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+        let mut quiet = Duration::ZERO;
+        while quiet < debounce {
+            let step = POLL_INTERVAL.min(debounce - quiet);
+            tokio::time::sleep(step).await;
+            if self.poll() {
+                quiet = Duration::ZERO;
+            } else {
+                quiet += step;
+            }
+        }
+        std::mem::take(&mut self.pending).into_iter().collect()
+    }
+
+    fn scan(&self) -> BTreeMap<String, SystemTime> {
+        let mut out = BTreeMap::new();
+        self.scan_dir(&self.root, &mut out);
+        out
+    }
+
+    fn scan_dir(&self, dir: &Path, out: &mut BTreeMap<String, SystemTime>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            if meta.is_dir() {
+                if !name.starts_with('.') && !SKIP_DIRS.contains(&name.as_ref()) {
+                    self.scan_dir(&path, out);
+                }
+                continue;
+            }
+            let Ok(rel) = path.strip_prefix(&self.root) else {
+                continue;
+            };
+            let rel = rel.to_string_lossy().replace('\\', "/");
+            if glob_matches(&self.pattern, &rel) {
+                let mtime = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                out.insert(rel, mtime);
+            }
+        }
+    }
+}
+
+/// Build the prompt for one watch run: the user's prompt followed by the
+/// list of files that triggered it.
+pub fn watch_prompt(prompt: &str, changed: &[String]) -> String {
+    let mut out = format!("{prompt}\n\nFiles changed since the last run:\n");
+    for path in changed {
+        out.push_str(&format!("- {path}\n"));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn touch(path: &Path, content: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+        // Force a distinct mtime even on coarse-grained filesystems.
+        let later = SystemTime::now() + Duration::from_secs(5);
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+    }
+
+    #[test]
+    fn poll_reports_matching_changes_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        touch(&root.join("src/routes/a.rs"), "a");
+        let mut watcher = FileWatcher::new(root, "src/routes/**/*.rs");
+        assert!(!watcher.poll());
+
+        touch(&root.join("src/routes/a.rs"), "a2");
+        touch(&root.join("src/routes/v1/b.rs"), "b");
+        touch(&root.join("src/other.rs"), "x");
+        touch(&root.join("target/src/routes/c.rs"), "c");
+        assert!(watcher.poll());
+        assert_eq!(
+            watcher.pending.iter().cloned().collect::<Vec<_>>(),
+            vec!["src/routes/a.rs", "src/routes/v1/b.rs"]
+        );
+
+        std::fs::remove_file(root.join("src/routes/a.rs")).unwrap();
+        watcher.ignore(&["src/routes/v1/b.rs".to_string()]);
+        assert_eq!(
+            watcher.pending.iter().cloned().collect::<Vec<_>>(),
+            vec!["src/routes/a.rs"]
+        );
+    }
+
+    #[tokio::test]
+    async fn next_batch_debounces() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        let mut watcher = FileWatcher::new(&root, "*.md").with_debounce(Duration::from_millis(300));
+
+        let writer = tokio::spawn(async move {
+            touch(&root.join("a.md"), "1");
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            touch(&root.join("b.md"), "2");
+        });
+        let batch = watcher.next_batch().await;
+        writer.await.unwrap();
+        assert_eq!(batch, vec!["a.md", "b.md"]);
+        assert!(watcher.pending.is_empty());
+    }
+
+    #[test]
+    fn prompt_lists_changed_files() {
+        let p = watch_prompt("Sync docs", &["src/a.rs".into()]);
+        assert!(p.starts_with("Sync docs\n\n"));
+        assert!(p.ends_with("- src/a.rs\n"));
+    }
+}
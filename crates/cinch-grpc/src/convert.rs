@@ -0,0 +1,246 @@
+//! Conversions between protobuf types and cinch-rs types.
+
+use cinch_rs::Message;
+use cinch_rs::agent::approvals::ApprovalScope;
+use cinch_rs::agent::config::HarnessConfig;
+use cinch_rs::agent::events::{HarnessEvent, HarnessResult};
+
+use crate::pb::{self, event::Kind};
+
+/// Convert a harness event to its protobuf form, or `None` for events not
+/// part of the contract (deltas, context management internals).
+pub fn event_to_proto(event: &HarnessEvent<'_>) -> Option<pb::Event> {
+    let kind = match event {
+        HarnessEvent::RoundStart {
+            round,
+            max_rounds,
+            context_usage,
+            ..
+        } => Kind::RoundStart(pb::RoundStart {
+            round: *round,
+            max_rounds: *max_rounds,
+            context_pct: context_usage.usage_pct,
+        }),
+        HarnessEvent::Text(text) => Kind::Text(pb::Text {
+            text: text.to_string(),
+        }),
+        HarnessEvent::Reasoning(text) => Kind::Reasoning(pb::Text {
+            text: text.to_string(),
+        }),
+        HarnessEvent::ToolExecuting {
+            name,
+            arguments,
+            summary,
+        } => Kind::ToolExecuting(pb::ToolExecuting {
+            name: name.to_string(),
+            arguments: arguments.to_string(),
+            summary: summary.to_string(),
+        }),
+        HarnessEvent::ToolResult {
+            name,
+            call_id,
+            result,
+        } => Kind::ToolResult(pb::ToolResult {
+            name: name.to_string(),
+            call_id: call_id.to_string(),
+            result: result.to_string(),
+        }),
+        HarnessEvent::FileEdited {
+            path, diff, stats, ..
+        } => Kind::FileEdited(pb::FileEdited {
+            path: path.to_string(),
+            diff: diff.to_string(),
+            added: stats.added as u32,
+            removed: stats.removed as u32,
+        }),
+        HarnessEvent::TokenUsage {
+            prompt_tokens,
+            completion_tokens,
+        } => Kind::TokenUsage(pb::TokenUsage {
+            prompt_tokens: *prompt_tokens,
+            completion_tokens: *completion_tokens,
+        }),
+        HarnessEvent::ApprovalRequired {
+            name,
+            arguments,
+            call_id,
+        } => Kind::ApprovalRequired(pb::ApprovalRequired {
+            name: name.to_string(),
+            arguments: arguments.to_string(),
+            call_id: call_id.to_string(),
+        }),
+        HarnessEvent::Finished => Kind::Finished(pb::Finished {}),
+        HarnessEvent::RoundLimitReached { max_rounds } => {
+            Kind::RoundLimitReached(pb::RoundLimitReached {
+                max_rounds: *max_rounds,
+            })
+        }
+        HarnessEvent::CostLimitReached {
+            cost_usd,
+            max_cost_usd,
+        } => Kind::CostLimitReached(pb::CostLimitReached {
+            cost_usd: *cost_usd,
+            max_cost_usd: *max_cost_usd,
+        }),
+        _ => return None,
+    };
+    Some(pb::Event { kind: Some(kind) })
+}
+
+/// The `TurnCompleted` event for a finished harness run.
+pub fn turn_completed(result: &HarnessResult) -> pb::Event {
+    pb::Event {
+        kind: Some(Kind::TurnCompleted(pb::TurnCompleted {
+            text: result.text(),
+            finished: result.finished,
+            budget_exceeded: result.budget_exceeded,
+            rounds: result.rounds_used,
+            prompt_tokens: result.total_prompt_tokens,
+            completion_tokens: result.total_completion_tokens,
+            cost_usd: result.estimated_cost_usd,
+        })),
+    }
+}
+
+/// The `RunFailed` event.
+pub fn run_failed(error: impl Into<String>) -> pb::Event {
+    pb::Event {
+        kind: Some(Kind::RunFailed(pb::RunFailed {
+            error: error.into(),
+        })),
+    }
+}
+
+/// Apply a request's [`pb::RunConfig`] on top of the service's base config.
+pub fn harness_config(base: &HarnessConfig, config: &pb::RunConfig) -> HarnessConfig {
+    let fresh = HarnessConfig::new(&config.model, &config.system_prompt);
+    let mut harness = HarnessConfig {
+        model: fresh.model,
+        routing: fresh.routing,
+        system_prompt: fresh.system_prompt,
+        ..base.clone()
+    };
+    if config.max_rounds > 0 {
+        harness = harness.with_max_rounds(config.max_rounds);
+    }
+    if let Some(max_cost_usd) = config.max_cost_usd {
+        harness = harness.with_max_cost_usd(max_cost_usd);
+    }
+    if let Some(temperature) = config.temperature {
+        harness = harness.with_temperature(temperature);
+    }
+    // Session-scoped "always allow" decisions belong to this run only.
+    let store = harness.approval_store.new_session();
+    harness
+        .with_approval_required_tools(config.approval_required_tools.clone())
+        .with_approval_store(store)
+}
+
+/// The "always allow" scope of an [`pb::ApproveRequest`], if any.
+pub fn approval_scope(scope: pb::ApprovalScope) -> Option<ApprovalScope> {
+    match scope {
+        pb::ApprovalScope::Once => None,
+        pb::ApprovalScope::Session => Some(ApprovalScope::Session),
+        pb::ApprovalScope::Project => Some(ApprovalScope::Project),
+    }
+}
+
+/// Initial conversation: `system_prompt` first (unless the caller already
+/// sent a system message), then the request's messages.
+pub fn initial_messages(
+    system_prompt: &str,
+    messages: &[pb::ChatMessage],
+) -> Result<Vec<Message>, String> {
+    let mut out = Vec::with_capacity(messages.len() + 1);
+    if messages.first().is_none_or(|m| m.role != "system") {
+        out.push(Message::system(system_prompt));
+    }
+    for message in messages {
+        out.push(match message.role.as_str() {
+            "system" => Message::system(&message.content),
+            "user" => Message::user(&message.content),
+            "assistant" => Message::assistant_text(&message.content),
+            other => return Err(format!("unsupported message role '{other}'")),
+        });
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chat(role: &str, content: &str) -> pb::ChatMessage {
+        pb::ChatMessage {
+            role: role.into(),
+            content: content.into(),
+        }
+    }
+
+    #[test]
+    fn events_map_to_oneof_variants() {
+        let event = event_to_proto(&HarnessEvent::ToolExecuting {
+            name: "grep",
+            arguments: "{}",
+            summary: "",
+        })
+        .unwrap();
+        assert_eq!(
+            event.kind,
+            Some(Kind::ToolExecuting(pb::ToolExecuting {
+                name: "grep".into(),
+                arguments: "{}".into(),
+                summary: String::new(),
+            }))
+        );
+        assert!(event_to_proto(&HarnessEvent::PreCompaction).is_none());
+    }
+
+    #[test]
+    fn system_prompt_is_prepended_once() {
+        let messages = initial_messages("sys", &[chat("user", "hi")]).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].content.as_deref(), Some("sys"));
+
+        let messages = initial_messages("sys", &[chat("system", "own"), chat("user", "hi")]);
+        assert_eq!(messages.unwrap()[0].content.as_deref(), Some("own"));
+
+        let err = initial_messages("sys", &[chat("tool", "x")]).unwrap_err();
+        assert!(err.contains("'tool'"), "{err}");
+    }
+
+    #[test]
+    fn request_config_overrides_base() {
+        let base = HarnessConfig::default().with_max_rounds(7);
+        let config = harness_config(
+            &base,
+            &pb::RunConfig {
+                model: "m".into(),
+                system_prompt: "s".into(),
+                max_cost_usd: Some(0.25),
+                approval_required_tools: vec!["shell".into()],
+                ..Default::default()
+            },
+        );
+        assert_eq!(config.model, "m");
+        assert_eq!(config.max_rounds, 7);
+        assert_eq!(config.max_cost_usd, Some(0.25));
+        assert_eq!(config.approval_required_tools, vec!["shell".to_string()]);
+    }
+
+    #[test]
+    fn runs_do_not_share_session_approvals() {
+        let base = HarnessConfig::default();
+        let first = harness_config(&base, &pb::RunConfig::default());
+        first
+            .approval_store
+            .allow("shell", ApprovalScope::Session)
+            .unwrap();
+        let second = harness_config(&base, &pb::RunConfig::default());
+        assert!(!second.approval_store.allows("shell"));
+        assert_eq!(
+            approval_scope(pb::ApprovalScope::Project),
+            Some(ApprovalScope::Project)
+        );
+    }
+}
@@ -0,0 +1,45 @@
+//! gRPC service wrapper for cinch-rs agent runs.
+//!
+//! Exposes the harness over a typed protobuf contract
+//! (`proto/cinch/v1/agent.proto`) so services in any language can embed
+//! cinch agents:
+//!
+//! | RPC | Purpose |
+//! |-----|---------|
+//! | `StartRun` | Create a run from a `RunConfig` and initial messages; starts the first turn |
+//! | `StreamEvents` | Server stream of the run's events, replayed from the start |
+//! | `SendMessage` | Queue the next user message (or `close` the run) |
+//! | `Approve` | Approve or deny a tool call awaiting approval, by call ID |
+//!
+//! A run is a conversation: each turn ends with a `TurnCompleted` event, then
+//! the run waits for `SendMessage`. Tools named in
+//! `RunConfig.approval_required_tools` emit `ApprovalRequired` and wait for
+//! `Approve` (denied after [`DEFAULT_APPROVAL_TIMEOUT`] by default).
+//!
+//! The `cinch-grpc` binary serves [`CinchService`] with an OpenRouter client
+//! built from `OPENROUTER_KEY`. It binds loopback by default and needs an
+//! auth token to listen anywhere else; runs only get file and shell tools
+//! for a `workdir` inside a configured root. Embedders can mount it in their own tonic
+//! server and supply tools per run:
+//!
+//! ```ignore
+//! let service = CinchService::new(client)
+//!     .with_tool_factory(|config| my_tools(config));
+//! tonic::transport::Server::builder()
+//!     .add_service(service.into_server())
+//!     .serve("127.0.0.1:50051".parse()?)
+//!     .await?;
+//! ```
+//!
+//! Generated message and client/server types live in [`pb`]; clients in
+//! other languages generate their own from the same `.proto` file.
+
+pub mod convert;
+pub mod service;
+
+pub use service::{CinchService, DEFAULT_APPROVAL_TIMEOUT, ToolFactory};
+
+/// Types generated from `proto/cinch/v1/agent.proto`.
+pub mod pb {
+    tonic::include_proto!("cinch.v1");
+}
@@ -0,0 +1,106 @@
+//! gRPC server hosting cinch agent runs.
+//!
+//! # Usage
+//!
+//! ```bash
+//! OPENROUTER_KEY=sk-... cargo run -p cinch-grpc -- --workdir-root ~/src
+//! OPENROUTER_KEY=sk-... CINCH_GRPC_TOKEN=secret cargo run -p cinch-grpc -- \
+//!   --addr 0.0.0.0:50051 --workdir-root /srv/projects
+//! ```
+//!
+//! Listening beyond loopback requires a token (`--auth-token` or
+//! `CINCH_GRPC_TOKEN`), sent by clients as `authorization: Bearer <token>`.
+//! Runs may only set `workdir` inside a `--workdir-root`.
+//!
+//! Then drive it with any gRPC client, e.g. grpcurl:
+//!
+//! ```bash
+//! grpcurl -plaintext -import-path proto -proto cinch/v1/agent.proto \
+//!   -d '{"config": {"model": "anthropic/claude-sonnet-4"},
+//!        "messages": [{"role": "user", "content": "hi"}]}' \
+//!   localhost:50051 cinch.v1.AgentService/StartRun
+//! ```
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use cinch_grpc::CinchService;
+use cinch_rs::OpenRouterClient;
+use cinch_rs::agent::approvals::ApprovalStore;
+use cinch_rs::agent::config::HarnessConfig;
+use clap::Parser;
+
+/// gRPC server hosting cinch agent runs.
+#[derive(Parser)]
+#[command(about = "Serve cinch agent runs over gRPC")]
+struct Args {
+    /// Address to listen on.
+    #[arg(long, default_value = "127.0.0.1:50051")]
+    addr: SocketAddr,
+
+    /// Seconds a tool call waits for Approve before it is denied.
+    #[arg(long, default_value_t = 300)]
+    approval_timeout_secs: u64,
+
+    /// Bearer token clients must send on every call. Defaults to
+    /// `CINCH_GRPC_TOKEN`; required unless `--addr` is a loopback address.
+    #[arg(long)]
+    auth_token: Option<String>,
+
+    /// Directory runs may set their `workdir` in (repeatable). Without
+    /// one, runs get no file or shell tools.
+    #[arg(long)]
+    workdir_root: Vec<PathBuf>,
+
+    /// JSON file of tools approved with "always allow" for the project
+    /// (`APPROVAL_SCOPE_PROJECT`). Without it, project-scoped approvals
+    /// last for the run.
+    #[arg(long)]
+    approvals_file: Option<PathBuf>,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), String> {
+    let args = Args::parse();
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .init();
+
+    let api_key = std::env::var("OPENROUTER_KEY")
+        .map_err(|_| "OPENROUTER_KEY environment variable is not set".to_string())?;
+    let client = OpenRouterClient::with_headers(
+        api_key,
+        "https://github.com/tacryt-socryp/cinch-rs",
+        "cinch-grpc",
+    )?;
+    let mut base_config = HarnessConfig::default();
+    if let Some(path) = args.approvals_file {
+        base_config = base_config.with_approval_store(ApprovalStore::load(path)?);
+    }
+    let mut service = CinchService::new(client)
+        .with_base_config(base_config)
+        .with_approval_timeout(Duration::from_secs(args.approval_timeout_secs))
+        .with_workdir_roots(args.workdir_root);
+    let token = args
+        .auth_token
+        .or_else(|| std::env::var("CINCH_GRPC_TOKEN").ok())
+        .filter(|t| !t.is_empty());
+    match token {
+        Some(token) => service = service.with_auth_token(token),
+        None if !args.addr.ip().is_loopback() => {
+            return Err(format!(
+                "refusing to listen on {} without --auth-token or CINCH_GRPC_TOKEN",
+                args.addr
+            ));
+        }
+        None => {}
+    }
+
+    tracing::info!("cinch-grpc listening on {}", args.addr);
+    tonic::transport::Server::builder()
+        .add_service(service.into_server())
+        .serve(args.addr)
+        .await
+        .map_err(|e| format!("server error: {e}"))
+}
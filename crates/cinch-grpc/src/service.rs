@@ -0,0 +1,464 @@
+//! [`CinchService`]: the `AgentService` implementation.
+//!
+//! Each run owns a background task that drives one harness turn at a time
+//! and then waits for the next `SendMessage`. Events are appended to a
+//! per-run log, so `StreamEvents` always replays from the start and any
+//! number of streams can follow the same run.
+//!
+//! Runs can get a shell, so the service is locked down by configuration:
+//! [`CinchService::with_auth_token`] requires a bearer token on every call,
+//! and a run's `workdir` must lie inside one of the
+//! [`workdir roots`](CinchService::with_workdir_roots).
+
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use cinch_rs::agent::config::HarnessConfig;
+use cinch_rs::agent::events::{EventHandler, EventResponse, HarnessEvent};
+use cinch_rs::agent::harness::Harness;
+use cinch_rs::tools::core::ToolSet;
+use cinch_rs::{Message, OpenRouterClient};
+use futures::Stream;
+use tokio::sync::{mpsc, watch};
+use tonic::{Request, Response, Status};
+
+use crate::convert;
+use crate::pb::{self, agent_service_server::AgentService};
+
+/// Default time a tool call waits for `Approve` before it is denied.
+pub const DEFAULT_APPROVAL_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Closed runs kept around so late `StreamEvents` calls can still replay
+/// them. Older closed runs are forgotten.
+const MAX_CLOSED_RUNS: usize = 64;
+
+/// Builds the tool set for a new run.
+pub type ToolFactory = dyn Fn(&pb::RunConfig) -> ToolSet + Send + Sync;
+
+/// gRPC service hosting cinch agent runs.
+///
+/// ```ignore
+/// let service = CinchService::new(client).with_base_config(
+///     HarnessConfig::default().with_memory_prompt(None),
+/// );
+/// tonic::transport::Server::builder()
+///     .add_service(service.into_server())
+///     .serve(addr)
+///     .await?;
+/// ```
+///
+/// Approval waits block a runtime worker via `block_in_place`, so the
+/// service must run on a multi-threaded tokio runtime.
+#[derive(Clone)]
+pub struct CinchService {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    client: Arc<OpenRouterClient>,
+    base_config: HarnessConfig,
+    tool_factory: Arc<ToolFactory>,
+    approval_timeout: Duration,
+    auth_token: Option<String>,
+    workdir_roots: Vec<PathBuf>,
+    runs: Mutex<Registry>,
+    next_id: AtomicU64,
+}
+
+#[derive(Default)]
+struct Registry {
+    runs: HashMap<String, Arc<Run>>,
+    closed: VecDeque<String>,
+}
+
+impl CinchService {
+    /// Serve runs through `client`. Runs get the built-in file and shell
+    /// tools when their config sets `workdir`, and no tools otherwise. No
+    /// workdir is accepted until [`with_workdir_roots`](Self::with_workdir_roots)
+    /// allows some.
+    pub fn new(client: OpenRouterClient) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                client: Arc::new(client),
+                base_config: HarnessConfig::default(),
+                tool_factory: Arc::new(default_tools),
+                approval_timeout: DEFAULT_APPROVAL_TIMEOUT,
+                auth_token: None,
+                workdir_roots: Vec::new(),
+                runs: Mutex::new(Registry::default()),
+                next_id: AtomicU64::new(1),
+            }),
+        }
+    }
+
+    /// Config every run starts from; each request's `RunConfig` overrides
+    /// the model, system prompt, and limits (builder pattern).
+    pub fn with_base_config(mut self, config: HarnessConfig) -> Self {
+        self.inner_mut().base_config = config;
+        self
+    }
+
+    /// Build each run's tool set with `factory` instead of the default
+    /// (builder pattern).
+    pub fn with_tool_factory(
+        mut self,
+        factory: impl Fn(&pb::RunConfig) -> ToolSet + Send + Sync + 'static,
+    ) -> Self {
+        self.inner_mut().tool_factory = Arc::new(factory);
+        self
+    }
+
+    /// How long a tool call waits for `Approve` before being denied
+    /// (builder pattern).
+    pub fn with_approval_timeout(mut self, timeout: Duration) -> Self {
+        self.inner_mut().approval_timeout = timeout;
+        self
+    }
+
+    /// Require `authorization: Bearer <token>` metadata on every call
+    /// (builder pattern).
+    pub fn with_auth_token(mut self, token: impl Into<String>) -> Self {
+        self.inner_mut().auth_token = Some(token.into());
+        self
+    }
+
+    /// Directories a run's `workdir` may be in (builder pattern). Roots
+    /// that don't exist are skipped.
+    pub fn with_workdir_roots(mut self, roots: impl IntoIterator<Item = PathBuf>) -> Self {
+        self.inner_mut().workdir_roots = roots
+            .into_iter()
+            .filter_map(|root| std::fs::canonicalize(root).ok())
+            .collect();
+        self
+    }
+
+    /// Wrap in the generated tonic server.
+    pub fn into_server(self) -> pb::agent_service_server::AgentServiceServer<Self> {
+        pb::agent_service_server::AgentServiceServer::new(self)
+    }
+
+    fn inner_mut(&mut self) -> &mut Inner {
+        Arc::get_mut(&mut self.inner).expect("configure CinchService before cloning it")
+    }
+
+    /// Check the request's bearer token, when one is configured.
+    fn authorize<T>(&self, request: &Request<T>) -> Result<(), Status> {
+        let Some(ref token) = self.inner.auth_token else {
+            return Ok(());
+        };
+        let given = request
+            .metadata()
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .unwrap_or_default();
+        if constant_time_eq(given.as_bytes(), token.as_bytes()) {
+            Ok(())
+        } else {
+            Err(Status::unauthenticated("missing or invalid bearer token"))
+        }
+    }
+
+    /// The canonical form of `workdir`, if it is inside a workdir root.
+    fn check_workdir(&self, workdir: &str) -> Result<String, Status> {
+        let path = std::fs::canonicalize(workdir)
+            .map_err(|e| Status::invalid_argument(format!("workdir '{workdir}': {e}")))?;
+        if !self
+            .inner
+            .workdir_roots
+            .iter()
+            .any(|root| path.starts_with(root))
+        {
+            return Err(Status::permission_denied(format!(
+                "workdir '{workdir}' is outside the server's workdir roots"
+            )));
+        }
+        Ok(path.to_string_lossy().into_owned())
+    }
+
+    fn run(&self, run_id: &str) -> Result<Arc<Run>, Status> {
+        self.inner
+            .runs
+            .lock()
+            .unwrap()
+            .runs
+            .get(run_id)
+            .cloned()
+            .ok_or_else(|| Status::not_found(format!("unknown run '{run_id}'")))
+    }
+}
+
+/// Compare without leaking the position of the first difference.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn default_tools(config: &pb::RunConfig) -> ToolSet {
+    match config.workdir {
+        Some(ref workdir) => ToolSet::new().with_common_tools(workdir.clone()),
+        None => ToolSet::new(),
+    }
+}
+
+/// Input for a run's next turn.
+enum Inbox {
+    Message(String),
+    Close,
+}
+
+/// One conversation.
+struct Run {
+    log: Mutex<RunLog>,
+    /// Bumped on every log change so streams wake up.
+    updates: watch::Sender<u64>,
+    inbox: mpsc::UnboundedSender<Inbox>,
+    /// Senders for the decisions on tool calls awaiting approval, by call ID.
+    pending_approvals: Mutex<HashMap<String, std::sync::mpsc::Sender<EventResponse>>>,
+}
+
+#[derive(Default)]
+struct RunLog {
+    events: Vec<pb::Event>,
+    closed: bool,
+}
+
+impl Run {
+    fn push(&self, event: pb::Event) {
+        self.log.lock().unwrap().events.push(event);
+        self.updates.send_modify(|n| *n += 1);
+    }
+
+    fn close(&self) {
+        self.log.lock().unwrap().closed = true;
+        self.updates.send_modify(|n| *n += 1);
+    }
+
+    /// Event `index`, `Ok(None)` if not yet logged, `Err` once closed.
+    fn event(&self, index: usize) -> Result<Option<pb::Event>, ()> {
+        let log = self.log.lock().unwrap();
+        match log.events.get(index) {
+            Some(event) => Ok(Some(event.clone())),
+            None if log.closed => Err(()),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Event handler for one turn: logs events and waits for approvals.
+struct RunHandler {
+    run: Arc<Run>,
+    approval_timeout: Duration,
+}
+
+impl EventHandler for RunHandler {
+    fn on_event(&self, event: &HarnessEvent<'_>) -> Option<EventResponse> {
+        let proto = convert::event_to_proto(event)?;
+        let HarnessEvent::ApprovalRequired { call_id, .. } = event else {
+            self.run.push(proto);
+            return None;
+        };
+        let (tx, rx) = std::sync::mpsc::channel();
+        self.run
+            .pending_approvals
+            .lock()
+            .unwrap()
+            .insert(call_id.to_string(), tx);
+        self.run.push(proto);
+        let decision = tokio::task::block_in_place(|| rx.recv_timeout(self.approval_timeout));
+        self.run.pending_approvals.lock().unwrap().remove(*call_id);
+        Some(decision.unwrap_or_else(|_| EventResponse::Deny("approval timed out".into())))
+    }
+}
+
+/// Drive a run: one harness turn per inbox message until closed or failed.
+async fn drive(
+    service: Arc<Inner>,
+    run_id: String,
+    run: Arc<Run>,
+    tools: ToolSet,
+    config: HarnessConfig,
+    mut messages: Vec<Message>,
+    mut inbox: mpsc::UnboundedReceiver<Inbox>,
+) {
+    loop {
+        let handler = RunHandler {
+            run: run.clone(),
+            approval_timeout: service.approval_timeout,
+        };
+        let outcome = Harness::new(&service.client, &tools, config.clone())
+            .with_event_handler(&handler)
+            .run(messages)
+            .await;
+        match outcome {
+            Ok(result) => {
+                run.push(convert::turn_completed(&result));
+                messages = result.messages;
+            }
+            Err(e) => {
+                run.push(convert::run_failed(e));
+                break;
+            }
+        }
+        match inbox.recv().await {
+            Some(Inbox::Message(text)) => messages.push(Message::user(text)),
+            Some(Inbox::Close) | None => break,
+        }
+    }
+    run.close();
+
+    let mut registry = service.runs.lock().unwrap();
+    registry.closed.push_back(run_id);
+    while registry.closed.len() > MAX_CLOSED_RUNS {
+        if let Some(old) = registry.closed.pop_front() {
+            registry.runs.remove(&old);
+        }
+    }
+}
+
+type EventStream = Pin<Box<dyn Stream<Item = Result<pb::Event, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl AgentService for CinchService {
+    async fn start_run(
+        &self,
+        request: Request<pb::StartRunRequest>,
+    ) -> Result<Response<pb::StartRunResponse>, Status> {
+        self.authorize(&request)?;
+        let request = request.into_inner();
+        let mut run_config = request
+            .config
+            .ok_or_else(|| Status::invalid_argument("config is required"))?;
+        if run_config.model.is_empty() {
+            return Err(Status::invalid_argument("config.model is required"));
+        }
+        if let Some(ref workdir) = run_config.workdir {
+            run_config.workdir = Some(self.check_workdir(workdir)?);
+        }
+        let messages = convert::initial_messages(&run_config.system_prompt, &request.messages)
+            .map_err(Status::invalid_argument)?;
+        let config = convert::harness_config(&self.inner.base_config, &run_config);
+        let tools = (self.inner.tool_factory)(&run_config);
+
+        let (inbox_tx, inbox_rx) = mpsc::unbounded_channel();
+        let run = Arc::new(Run {
+            log: Mutex::new(RunLog::default()),
+            updates: watch::Sender::new(0),
+            inbox: inbox_tx,
+            pending_approvals: Mutex::new(HashMap::new()),
+        });
+        let run_id = format!(
+            "run-{}-{}",
+            cinch_rs::platform::epoch_secs(),
+            self.inner.next_id.fetch_add(1, Ordering::Relaxed)
+        );
+        self.inner
+            .runs
+            .lock()
+            .unwrap()
+            .runs
+            .insert(run_id.clone(), run.clone());
+        tokio::spawn(drive(
+            self.inner.clone(),
+            run_id.clone(),
+            run,
+            tools,
+            config,
+            messages,
+            inbox_rx,
+        ));
+        Ok(Response::new(pb::StartRunResponse { run_id }))
+    }
+
+    type StreamEventsStream = EventStream;
+
+    async fn stream_events(
+        &self,
+        request: Request<pb::StreamEventsRequest>,
+    ) -> Result<Response<Self::StreamEventsStream>, Status> {
+        self.authorize(&request)?;
+        let run = self.run(&request.into_inner().run_id)?;
+        let updates = run.updates.subscribe();
+        let stream = futures::stream::unfold(
+            (run, updates, 0usize),
+            |(run, mut updates, index)| async move {
+                loop {
+                    match run.event(index) {
+                        Ok(Some(event)) => return Some((Ok(event), (run, updates, index + 1))),
+                        Ok(None) => {
+                            if updates.changed().await.is_err() {
+                                return None;
+                            }
+                        }
+                        Err(()) => return None,
+                    }
+                }
+            },
+        );
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    async fn send_message(
+        &self,
+        request: Request<pb::SendMessageRequest>,
+    ) -> Result<Response<pb::SendMessageResponse>, Status> {
+        self.authorize(&request)?;
+        let request = request.into_inner();
+        let run = self.run(&request.run_id)?;
+        let input = if request.close {
+            Inbox::Close
+        } else {
+            Inbox::Message(request.content)
+        };
+        run.inbox
+            .send(input)
+            .map_err(|_| Status::failed_precondition("run has ended"))?;
+        Ok(Response::new(pb::SendMessageResponse {}))
+    }
+
+    async fn approve(
+        &self,
+        request: Request<pb::ApproveRequest>,
+    ) -> Result<Response<pb::ApproveResponse>, Status> {
+        self.authorize(&request)?;
+        let request = request.into_inner();
+        let run = self.run(&request.run_id)?;
+        let tx = take_pending(&mut run.pending_approvals.lock().unwrap(), &request.call_id)?;
+        let always = convert::approval_scope(request.always_allow());
+        let decision = if request.approved && !request.arguments.is_empty() {
+            EventResponse::ApproveEdited(request.arguments)
+        } else if let Some(scope) = always.filter(|_| request.approved) {
+            EventResponse::ApproveAlways(scope)
+        } else if request.approved {
+            EventResponse::Approve
+        } else {
+            EventResponse::Deny(request.reason)
+        };
+        tx.send(decision)
+            .map_err(|_| Status::failed_precondition("approval request expired"))?;
+        Ok(Response::new(pb::ApproveResponse {}))
+    }
+}
+
+/// Remove the pending approval for `call_id`. An empty `call_id` picks the
+/// only pending call, and is ambiguous while several are pending.
+fn take_pending<T>(pending: &mut HashMap<String, T>, call_id: &str) -> Result<T, Status> {
+    let key = if call_id.is_empty() {
+        match pending.len() {
+            0 => None,
+            1 => pending.keys().next().cloned(),
+            _ => {
+                return Err(Status::invalid_argument(
+                    "call_id is required while several tool calls await approval",
+                ));
+            }
+        }
+    } else {
+        Some(call_id.to_string())
+    };
+    key.and_then(|key| pending.remove(&key))
+        .ok_or_else(|| Status::failed_precondition("no tool call awaiting approval"))
+}
@@ -0,0 +1,284 @@
+//! Integration tests for the cinch-grpc service.
+//!
+//! These tests serve `CinchService` on a random port, talk to it through the
+//! generated client, and answer LLM requests from a scripted transport.
+
+use cinch_grpc::CinchService;
+use cinch_grpc::pb::agent_service_client::AgentServiceClient;
+use cinch_grpc::pb::{self, event::Kind};
+use cinch_rs::test_support::{ScriptedTransport, test_config, text_reply, tool_call_reply};
+use cinch_rs::tools::core::{FnTool, ToolSet};
+use cinch_rs::{OpenRouterClient, ToolDef};
+use tokio_stream::StreamExt;
+use tonic::transport::Channel;
+
+fn echo_call() -> String {
+    tool_call_reply("c1", "echo", &serde_json::json!({}))
+}
+
+fn echo_tools(_config: &pb::RunConfig) -> ToolSet {
+    let def = ToolDef::new("echo", "Echo", serde_json::json!({"type": "object"}));
+    ToolSet::new().with(FnTool::new(def, |_: serde_json::Value| async {
+        "echoed".to_string()
+    }))
+}
+
+/// Serve a service whose model replies with `replies` and return a client.
+async fn spawn_test_server(replies: Vec<String>) -> AgentServiceClient<Channel> {
+    spawn_configured(replies, |service| service).await
+}
+
+/// Like [`spawn_test_server`], with extra service configuration.
+async fn spawn_configured(
+    replies: Vec<String>,
+    configure: impl FnOnce(CinchService) -> CinchService,
+) -> AgentServiceClient<Channel> {
+    let client = OpenRouterClient::with_transport("key", ScriptedTransport::new(replies));
+    let service = configure(
+        CinchService::new(client)
+            .with_base_config(test_config(""))
+            .with_tool_factory(echo_tools),
+    );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .add_service(service.into_server())
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener)),
+    );
+    AgentServiceClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap()
+}
+
+async fn start(client: &mut AgentServiceClient<Channel>, approval: &[&str]) -> String {
+    client
+        .start_run(pb::StartRunRequest {
+            config: Some(pb::RunConfig {
+                model: "test/model".into(),
+                system_prompt: "sys".into(),
+                approval_required_tools: approval.iter().map(|s| s.to_string()).collect(),
+                ..Default::default()
+            }),
+            messages: vec![pb::ChatMessage {
+                role: "user".into(),
+                content: "hi".into(),
+            }],
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .run_id
+}
+
+/// Read events until `stop` matches, returning the kinds seen.
+async fn read_until(
+    events: &mut tonic::Streaming<pb::Event>,
+    stop: impl Fn(&Kind) -> bool,
+) -> Vec<Kind> {
+    let mut seen = Vec::new();
+    while let Some(event) = events.next().await {
+        let kind = event.unwrap().kind.unwrap();
+        let done = stop(&kind);
+        seen.push(kind);
+        if done {
+            break;
+        }
+    }
+    seen
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn turns_continue_with_send_message_until_closed() {
+    let mut client =
+        spawn_test_server(vec![echo_call(), text_reply("first"), text_reply("second")]).await;
+    let run_id = start(&mut client, &[]).await;
+    let mut events = client
+        .stream_events(pb::StreamEventsRequest {
+            run_id: run_id.clone(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let first = read_until(&mut events, |k| matches!(k, Kind::TurnCompleted(_))).await;
+    assert!(
+        first
+            .iter()
+            .any(|k| matches!(k, Kind::ToolResult(r) if r.result == "echoed"))
+    );
+    let Some(Kind::TurnCompleted(done)) = first.last() else {
+        panic!("{first:?}");
+    };
+    assert_eq!(done.text, "first");
+    assert!(done.finished);
+
+    client
+        .send_message(pb::SendMessageRequest {
+            run_id: run_id.clone(),
+            content: "again".into(),
+            close: false,
+        })
+        .await
+        .unwrap();
+    let second = read_until(&mut events, |k| matches!(k, Kind::TurnCompleted(_))).await;
+    assert!(matches!(second.last(), Some(Kind::TurnCompleted(t)) if t.text == "second"));
+
+    client
+        .send_message(pb::SendMessageRequest {
+            run_id: run_id.clone(),
+            content: String::new(),
+            close: true,
+        })
+        .await
+        .unwrap();
+    assert!(
+        events.next().await.is_none(),
+        "stream ends when the run closes"
+    );
+
+    // A late subscriber replays the whole run.
+    let replay: Vec<_> = client
+        .stream_events(pb::StreamEventsRequest { run_id })
+        .await
+        .unwrap()
+        .into_inner()
+        .collect()
+        .await;
+    assert_eq!(
+        replay
+            .iter()
+            .filter(|e| matches!(e.as_ref().unwrap().kind, Some(Kind::TurnCompleted(_))))
+            .count(),
+        2
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn approval_required_tools_wait_for_approve() {
+    let mut client = spawn_test_server(vec![echo_call(), text_reply("ok")]).await;
+    let run_id = start(&mut client, &["echo"]).await;
+    let mut events = client
+        .stream_events(pb::StreamEventsRequest {
+            run_id: run_id.clone(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let seen = read_until(&mut events, |k| matches!(k, Kind::ApprovalRequired(_))).await;
+    let Some(Kind::ApprovalRequired(asked)) = seen.last() else {
+        panic!("{seen:?}");
+    };
+    assert_eq!(
+        (asked.name.as_str(), asked.call_id.as_str()),
+        ("echo", "c1")
+    );
+
+    let err = client
+        .approve(pb::ApproveRequest {
+            run_id: run_id.clone(),
+            approved: true,
+            call_id: "other".into(),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::FailedPrecondition);
+    client
+        .approve(pb::ApproveRequest {
+            run_id: run_id.clone(),
+            approved: false,
+            reason: "not now".into(),
+            call_id: asked.call_id.clone(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let rest = read_until(&mut events, |k| matches!(k, Kind::TurnCompleted(_))).await;
+    assert!(
+        !rest
+            .iter()
+            .any(|k| matches!(k, Kind::ToolResult(r) if r.result == "echoed")),
+        "denied tool must not run: {rest:?}"
+    );
+
+    let err = client
+        .approve(pb::ApproveRequest {
+            run_id,
+            approved: true,
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::FailedPrecondition);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unknown_runs_and_bad_requests_are_rejected() {
+    let mut client = spawn_test_server(Vec::new()).await;
+    let err = client
+        .send_message(pb::SendMessageRequest {
+            run_id: "nope".into(),
+            content: "hi".into(),
+            close: false,
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::NotFound);
+
+    let err = client
+        .start_run(pb::StartRunRequest::default())
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn calls_need_the_auth_token() {
+    let mut client = spawn_configured(Vec::new(), |s| s.with_auth_token("secret")).await;
+    let request = || pb::SendMessageRequest {
+        run_id: "nope".into(),
+        ..Default::default()
+    };
+    let err = client.send_message(request()).await.unwrap_err();
+    assert_eq!(err.code(), tonic::Code::Unauthenticated);
+
+    let mut authed = tonic::Request::new(request());
+    authed
+        .metadata_mut()
+        .insert("authorization", "Bearer secret".parse().unwrap());
+    let err = client.send_message(authed).await.unwrap_err();
+    assert_eq!(err.code(), tonic::Code::NotFound);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn workdir_must_be_inside_a_root() {
+    let root = tempfile::tempdir().unwrap();
+    let outside = tempfile::tempdir().unwrap();
+    let roots = vec![root.path().to_path_buf()];
+    let mut client =
+        spawn_configured(vec![text_reply("ok")], |s| s.with_workdir_roots(roots)).await;
+    let start_in = |workdir: &std::path::Path| pb::StartRunRequest {
+        config: Some(pb::RunConfig {
+            model: "test/model".into(),
+            workdir: Some(workdir.to_string_lossy().into_owned()),
+            ..Default::default()
+        }),
+        messages: Vec::new(),
+    };
+
+    let err = client
+        .start_run(start_in(outside.path()))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::PermissionDenied);
+    let escape = root
+        .path()
+        .join("..")
+        .join(outside.path().file_name().unwrap());
+    let err = client.start_run(start_in(&escape)).await.unwrap_err();
+    assert_eq!(err.code(), tonic::Code::PermissionDenied);
+    client.start_run(start_in(root.path())).await.unwrap();
+}
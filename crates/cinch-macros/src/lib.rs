@@ -0,0 +1,286 @@
+//! Derive macros for [cinch-rs](https://docs.rs/cinch-rs) tools.
+//!
+//! Use them through the re-export, `cinch_rs::CinchTool`; the generated code
+//! refers to `::cinch_rs` paths.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::punctuated::Punctuated;
+use syn::{DeriveInput, Expr, ExprLit, Ident, Lit, LitStr, Meta, Token, Type, parse_macro_input};
+
+/// Implement `Tool` for a struct from its doc comment, a typed argument
+/// struct, and an async method.
+///
+/// ```ignore
+/// /// Look up the definition of a word.
+/// #[derive(CinchTool)]
+/// #[tool(
+///     args = LookupWordArgs,
+///     when_to_use = "When the user asks what a word means",
+///     when_not_to_use = "When the user wants a translation — use translate instead",
+///     cacheable,
+/// )]
+/// struct LookupWord;
+///
+/// impl LookupWord {
+///     async fn call(&self, args: LookupWordArgs) -> String {
+///         format!("{}: ...", args.word)
+///     }
+/// }
+/// ```
+///
+/// The first paragraph of the doc comment is the tool's purpose, and the
+/// tool name defaults to the struct name in snake case. The argument type
+/// must implement `Deserialize` and `JsonSchema`; the method may return a
+/// `String` or a `Result<String, E: Display>` (errors become `Error: ...`
+/// results).
+///
+/// `#[tool(...)]` keys:
+///
+/// | Key | Meaning |
+/// |-----|---------|
+/// | `args = Type` | Argument struct (required) |
+/// | `when_to_use = "..."` | Required |
+/// | `when_not_to_use = "..."` | Required |
+/// | `name = "..."` | Tool name (default: snake-case struct name) |
+/// | `purpose = "..."` | Overrides the doc comment |
+/// | `method = ident` | Method to call (default: `call`) |
+/// | `output_format = "..."` | Description of the result |
+/// | `example("input", "output")` | Usage example; repeatable |
+/// | `call_example(r#"{"json": "args"}"#, "result")` | Few-shot call; repeatable |
+/// | `disambiguate("scenario", "tool", "reason")` | Repeatable |
+/// | `cacheable`, `mutation`, `runs_commands`, `accesses_network` | Flags |
+///
+/// Besides the `Tool` impl, the derive adds an inherent `tool_spec()`
+/// returning the tool's `ToolSpec`.
+#[proc_macro_derive(CinchTool, attributes(tool))]
+pub fn derive_cinch_tool(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct ToolAttrs {
+    name: Option<LitStr>,
+    args: Option<Type>,
+    method: Option<Ident>,
+    purpose: Option<LitStr>,
+    when_to_use: Option<LitStr>,
+    when_not_to_use: Option<LitStr>,
+    output_format: Option<LitStr>,
+    examples: Vec<(LitStr, LitStr)>,
+    call_examples: Vec<(LitStr, LitStr)>,
+    disambiguations: Vec<(LitStr, LitStr, LitStr)>,
+    cacheable: bool,
+    mutation: bool,
+    runs_commands: bool,
+    accesses_network: bool,
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let attrs = parse_tool_attrs(&input)?;
+    let ident = &input.ident;
+    let missing = |key: &str| {
+        syn::Error::new(
+            Span::call_site(),
+            format!("#[derive(CinchTool)] requires #[tool({key} = ...)]"),
+        )
+    };
+
+    let args = attrs.args.ok_or_else(|| missing("args"))?;
+    let when_to_use = attrs.when_to_use.ok_or_else(|| missing("when_to_use"))?;
+    let when_not_to_use = attrs
+        .when_not_to_use
+        .ok_or_else(|| missing("when_not_to_use"))?;
+    let purpose = match attrs.purpose {
+        Some(purpose) => purpose,
+        None => doc_purpose(&input).ok_or_else(|| {
+            syn::Error::new_spanned(
+                ident,
+                "#[derive(CinchTool)] needs a doc comment or #[tool(purpose = ...)]",
+            )
+        })?,
+    };
+    let name = attrs
+        .name
+        .unwrap_or_else(|| LitStr::new(&snake_case(&ident.to_string()), ident.span()));
+    let method = attrs
+        .method
+        .unwrap_or_else(|| Ident::new("call", Span::call_site()));
+
+    let output_format = attrs
+        .output_format
+        .map(|format| quote!(.output_format(#format)));
+    let examples = attrs
+        .examples
+        .iter()
+        .map(|(input, output)| quote!(.example(#input, #output)));
+    let call_examples = attrs
+        .call_examples
+        .iter()
+        .map(|(arguments, result)| quote!(.call_example_json(#arguments, #result)));
+    let disambiguations = attrs
+        .disambiguations
+        .iter()
+        .map(|(scenario, tool, reason)| quote!(.disambiguate(#scenario, #tool, #reason)));
+    let flag = |enabled: bool, method: &str| {
+        let method = Ident::new(method, Span::call_site());
+        enabled.then(|| quote!(fn #method(&self) -> bool { true }))
+    };
+    let cacheable = flag(attrs.cacheable, "cacheable");
+    let mutation = flag(attrs.mutation, "is_mutation");
+    let runs_commands = flag(attrs.runs_commands, "runs_commands");
+    let accesses_network = flag(attrs.accesses_network, "accesses_network");
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// The tool's structured specification.
+            pub fn tool_spec() -> ::cinch_rs::tools::spec::ToolSpec {
+                ::cinch_rs::tools::spec::ToolSpec::builder(#name)
+                    .purpose(#purpose)
+                    .when_to_use(#when_to_use)
+                    .when_not_to_use(#when_not_to_use)
+                    .parameters_for::<#args>()
+                    #output_format
+                    #(#examples)*
+                    #(#call_examples)*
+                    #(#disambiguations)*
+                    .build()
+            }
+        }
+
+        impl #impl_generics ::cinch_rs::tools::core::Tool for #ident #ty_generics #where_clause {
+            fn definition(&self) -> ::cinch_rs::ToolDef {
+                Self::tool_spec().to_tool_def()
+            }
+
+            fn execute(&self, arguments: &str) -> ::cinch_rs::tools::core::ToolFuture<'_> {
+                let parsed = ::cinch_rs::tools::core::parse_tool_args::<#args>(arguments);
+                ::std::boxed::Box::pin(async move {
+                    match parsed {
+                        ::std::result::Result::Ok(args) => {
+                            ::cinch_rs::tools::core::IntoToolResult::into_tool_result(
+                                self.#method(args).await,
+                            )
+                        }
+                        ::std::result::Result::Err(error) => error,
+                    }
+                })
+            }
+
+            fn call_examples(&self) -> ::std::vec::Vec<::cinch_rs::tools::spec::ToolCallExample> {
+                Self::tool_spec().call_examples
+            }
+
+            fn disambiguation(&self) -> ::std::vec::Vec<::cinch_rs::tools::spec::DisambiguationExample> {
+                Self::tool_spec().disambiguation
+            }
+
+            #cacheable
+            #mutation
+            #runs_commands
+            #accesses_network
+        }
+    })
+}
+
+fn parse_tool_attrs(input: &DeriveInput) -> syn::Result<ToolAttrs> {
+    let mut attrs = ToolAttrs::default();
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("tool")) {
+        attr.parse_nested_meta(|meta| {
+            let key = meta
+                .path
+                .get_ident()
+                .map(Ident::to_string)
+                .unwrap_or_default();
+            match key.as_str() {
+                "name" => attrs.name = Some(meta.value()?.parse()?),
+                "args" => attrs.args = Some(meta.value()?.parse()?),
+                "method" => attrs.method = Some(meta.value()?.parse()?),
+                "purpose" => attrs.purpose = Some(meta.value()?.parse()?),
+                "when_to_use" => attrs.when_to_use = Some(meta.value()?.parse()?),
+                "when_not_to_use" => attrs.when_not_to_use = Some(meta.value()?.parse()?),
+                "output_format" => attrs.output_format = Some(meta.value()?.parse()?),
+                "example" => {
+                    let [input, output] = string_list(&meta, "example")?;
+                    attrs.examples.push((input, output));
+                }
+                "call_example" => {
+                    let [arguments, result] = string_list(&meta, "call_example")?;
+                    attrs.call_examples.push((arguments, result));
+                }
+                "disambiguate" => {
+                    let [scenario, tool, reason] = string_list(&meta, "disambiguate")?;
+                    attrs.disambiguations.push((scenario, tool, reason));
+                }
+                "cacheable" => attrs.cacheable = true,
+                "mutation" => attrs.mutation = true,
+                "runs_commands" => attrs.runs_commands = true,
+                "accesses_network" => attrs.accesses_network = true,
+                _ => return Err(meta.error("unknown #[tool] key")),
+            }
+            Ok(())
+        })?;
+    }
+    Ok(attrs)
+}
+
+/// Parse `key("a", "b", ...)` into exactly `N` string literals.
+fn string_list<const N: usize>(
+    meta: &syn::meta::ParseNestedMeta,
+    key: &str,
+) -> syn::Result<[LitStr; N]> {
+    let content;
+    syn::parenthesized!(content in meta.input);
+    let items: Punctuated<LitStr, Token![,]> = Punctuated::parse_terminated(&content)?;
+    let items: Vec<LitStr> = items.into_iter().collect();
+    items
+        .try_into()
+        .map_err(|_| meta.error(format!("{key}(...) takes {N} string arguments")))
+}
+
+/// First paragraph of the doc comment, joined into one line, without a
+/// trailing period (`ToolSpec` adds one).
+fn doc_purpose(input: &DeriveInput) -> Option<LitStr> {
+    let mut lines = Vec::new();
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("doc")) {
+        if let Meta::NameValue(nv) = &attr.meta
+            && let Expr::Lit(ExprLit {
+                lit: Lit::Str(doc), ..
+            }) = &nv.value
+        {
+            let line = doc.value().trim().to_string();
+            if line.is_empty() && !lines.is_empty() {
+                break;
+            }
+            if !line.is_empty() {
+                lines.push(line);
+            }
+        }
+    }
+    if lines.is_empty() {
+        return None;
+    }
+    let purpose = lines.join(" ");
+    let purpose = purpose.trim_end_matches('.');
+    Some(LitStr::new(purpose, input.ident.span()))
+}
+
+fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
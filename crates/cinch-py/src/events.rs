@@ -0,0 +1,129 @@
+//! Harness events as JSON objects.
+//!
+//! Every object has a `"type"` key naming the event (`"text"`,
+//! `"tool_executing"`, `"tool_result"`, `"finished"`, ...). Streaming deltas
+//! and internal context-management events are omitted.
+
+use cinch_rs::agent::events::HarnessEvent;
+use cinch_rs::tools::output::ToolOutput;
+use serde_json::json;
+
+/// Convert a harness event to a JSON object, or `None` for events not
+/// exposed to Python.
+pub fn event_to_json(event: &HarnessEvent<'_>) -> Option<serde_json::Value> {
+    let value = match event {
+        HarnessEvent::RoundStart {
+            round,
+            max_rounds,
+            context_usage,
+            ..
+        } => json!({
+            "type": "round_start",
+            "round": round,
+            "max_rounds": max_rounds,
+            "context_pct": context_usage.usage_pct,
+        }),
+        HarnessEvent::Text(text) => json!({"type": "text", "text": text}),
+        HarnessEvent::Reasoning(text) => json!({"type": "reasoning", "text": text}),
+        HarnessEvent::ToolCallsReceived { round, count } => json!({
+            "type": "tool_calls_received",
+            "round": round,
+            "count": count,
+        }),
+        HarnessEvent::ToolExecuting {
+            name,
+            arguments,
+            summary,
+        } => json!({
+            "type": "tool_executing",
+            "name": name,
+            "arguments": arguments,
+            "summary": summary,
+        }),
+        HarnessEvent::ToolResult {
+            name,
+            call_id,
+            result,
+        } => json!({
+            "type": "tool_result",
+            "name": name,
+            "call_id": call_id,
+            "result": result,
+            "output": ToolOutput::parse(result).map(|o| o.to_json()),
+        }),
+        HarnessEvent::FileEdited {
+            path, diff, stats, ..
+        } => json!({
+            "type": "file_edited",
+            "path": path,
+            "diff": diff,
+            "added": stats.added,
+            "removed": stats.removed,
+        }),
+        HarnessEvent::TokenUsage {
+            prompt_tokens,
+            completion_tokens,
+        } => json!({
+            "type": "token_usage",
+            "prompt_tokens": prompt_tokens,
+            "completion_tokens": completion_tokens,
+        }),
+        HarnessEvent::Finished => json!({"type": "finished"}),
+        HarnessEvent::RoundLimitReached { max_rounds } => {
+            json!({"type": "round_limit_reached", "max_rounds": max_rounds})
+        }
+        HarnessEvent::CostLimitReached {
+            cost_usd,
+            max_cost_usd,
+        } => json!({
+            "type": "cost_limit_reached",
+            "cost_usd": cost_usd,
+            "max_cost_usd": max_cost_usd,
+        }),
+        HarnessEvent::ResourceLimitExceeded {
+            name,
+            limit,
+            detail,
+        } => json!({
+            "type": "resource_limit_exceeded",
+            "name": name,
+            "limit": limit.label(),
+            "detail": detail,
+        }),
+        HarnessEvent::SessionStarting { trace_id } => {
+            json!({"type": "session_starting", "trace_id": trace_id})
+        }
+        _ => return None,
+    };
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tool_events_carry_name_and_payload() {
+        let value = event_to_json(&HarnessEvent::ToolResult {
+            name: "grep",
+            call_id: "c1",
+            result: "3 matches",
+        })
+        .unwrap();
+        assert_eq!(value["type"], "tool_result");
+        assert_eq!(value["name"], "grep");
+        assert_eq!(value["result"], "3 matches");
+        assert!(value["output"].is_null());
+
+        let result = ToolOutput::new(1, "", "boom\n").render();
+        let value = event_to_json(&HarnessEvent::ToolResult {
+            name: "shell",
+            call_id: "c2",
+            result: &result,
+        })
+        .unwrap();
+        assert_eq!(value["output"]["exit_code"], 1);
+        assert_eq!(value["output"]["stderr"], "boom\n");
+        assert!(event_to_json(&HarnessEvent::PreCompaction).is_none());
+    }
+}
@@ -0,0 +1,339 @@
+//! `Harness`: run the agent loop from Python.
+//!
+//! [`PyHarness::run`] blocks until the run ends and returns a [`RunResult`].
+//! [`PyHarness::stream`] starts the run in the background and returns an
+//! [`EventStream`] that yields each event as a dict; once exhausted, its
+//! `result` attribute holds the [`RunResult`]. A stream dropped before it is
+//! exhausted does not cancel the run.
+
+use std::sync::mpsc::{Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+use cinch_rs::agent::config::HarnessConfig;
+use cinch_rs::agent::events::{EventHandler, EventResponse, HarnessEvent, HarnessResult};
+use cinch_rs::agent::harness::Harness;
+use cinch_rs::tools::core::ToolSet;
+use cinch_rs::{Message, OpenRouterClient};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyString;
+use pythonize::{depythonize, pythonize};
+
+use crate::events::event_to_json;
+use crate::runtime;
+use crate::tool::PyToolSet;
+
+/// System prompt used when none is given.
+const DEFAULT_SYSTEM_PROMPT: &str = "You are a helpful assistant.";
+
+/// Outcome of a harness run.
+#[pyclass(module = "cinch", frozen, skip_from_py_object)]
+#[derive(Clone)]
+pub struct RunResult {
+    /// Unique trace ID for the run.
+    #[pyo3(get)]
+    trace_id: String,
+    /// Final text output of the agent.
+    #[pyo3(get)]
+    text: String,
+    /// Whether the agent finished on its own (vs. a round or cost limit).
+    #[pyo3(get)]
+    finished: bool,
+    /// Whether the run stopped at `max_cost_usd`.
+    #[pyo3(get)]
+    budget_exceeded: bool,
+    /// Rounds executed.
+    #[pyo3(get)]
+    rounds: u32,
+    /// Prompt tokens consumed.
+    #[pyo3(get)]
+    prompt_tokens: u64,
+    /// Completion tokens consumed.
+    #[pyo3(get)]
+    completion_tokens: u64,
+    /// Estimated cost in USD.
+    #[pyo3(get)]
+    cost_usd: f64,
+    structured_output: Option<serde_json::Value>,
+    messages: Vec<Message>,
+}
+
+#[pymethods]
+impl RunResult {
+    /// Parsed structured output, when an output schema was requested.
+    #[getter]
+    fn structured_output<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyAny>>> {
+        self.structured_output
+            .as_ref()
+            .map(|value| pythonize(py, value).map_err(|e| PyValueError::new_err(e.to_string())))
+            .transpose()
+    }
+
+    /// Full conversation as a list of message dicts.
+    #[getter]
+    fn messages<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        pythonize(py, &self.messages).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "RunResult(finished={}, rounds={}, cost_usd={:.4})",
+            self.finished, self.rounds, self.cost_usd
+        )
+    }
+}
+
+impl From<HarnessResult> for RunResult {
+    fn from(result: HarnessResult) -> Self {
+        Self {
+            text: result.text(),
+            trace_id: result.trace_id,
+            finished: result.finished,
+            budget_exceeded: result.budget_exceeded,
+            rounds: result.rounds_used,
+            prompt_tokens: result.total_prompt_tokens,
+            completion_tokens: result.total_completion_tokens,
+            cost_usd: result.estimated_cost_usd,
+            structured_output: result.structured_output,
+            messages: result.messages,
+        }
+    }
+}
+
+/// An agent harness: model, system prompt, tools, and limits.
+#[pyclass(name = "Harness", module = "cinch", frozen)]
+pub struct PyHarness {
+    client: Arc<OpenRouterClient>,
+    tools: Arc<ToolSet>,
+    config: HarnessConfig,
+    system_prompt: String,
+}
+
+#[pymethods]
+impl PyHarness {
+    /// `Harness(model, system_prompt=None, tools=None, api_key=None,
+    /// max_rounds=None, max_cost_usd=None, temperature=None)`.
+    ///
+    /// `api_key` defaults to the `OPENROUTER_KEY` environment variable.
+    #[new]
+    #[pyo3(signature = (
+        model,
+        system_prompt=None,
+        tools=None,
+        api_key=None,
+        max_rounds=None,
+        max_cost_usd=None,
+        temperature=None,
+    ))]
+    fn new(
+        model: String,
+        system_prompt: Option<String>,
+        tools: Option<PyRef<'_, PyToolSet>>,
+        api_key: Option<String>,
+        max_rounds: Option<u32>,
+        max_cost_usd: Option<f64>,
+        temperature: Option<f32>,
+    ) -> PyResult<Self> {
+        let api_key = match api_key.or_else(|| std::env::var("OPENROUTER_KEY").ok()) {
+            Some(key) => key,
+            None => {
+                return Err(PyValueError::new_err(
+                    "api_key not given and OPENROUTER_KEY is not set",
+                ));
+            }
+        };
+        let client = OpenRouterClient::new(api_key).map_err(PyRuntimeError::new_err)?;
+        let system_prompt = system_prompt.unwrap_or_else(|| DEFAULT_SYSTEM_PROMPT.to_string());
+        let mut config = HarnessConfig::new(model, system_prompt.clone());
+        if let Some(max_rounds) = max_rounds {
+            config = config.with_max_rounds(max_rounds);
+        }
+        if let Some(max_cost_usd) = max_cost_usd {
+            config = config.with_max_cost_usd(max_cost_usd);
+        }
+        if let Some(temperature) = temperature {
+            config = config.with_temperature(temperature);
+        }
+        let tools = tools.map_or_else(|| Arc::new(ToolSet::new()), |t| t.inner.clone());
+        Ok(Self::from_parts(
+            Arc::new(client),
+            tools,
+            config,
+            system_prompt,
+        ))
+    }
+
+    /// Run to completion and return a [`RunResult`].
+    ///
+    /// `prompt` is either a user message (the system prompt is prepended)
+    /// or a full list of `{"role": ..., "content": ...}` message dicts.
+    fn run(&self, py: Python<'_>, prompt: Bound<'_, PyAny>) -> PyResult<RunResult> {
+        let messages = self.messages(&prompt)?;
+        let (client, tools, config) = (&self.client, &self.tools, self.config.clone());
+        py.detach(|| runtime().block_on(Harness::new(client, tools, config).run(messages)))
+            .map(RunResult::from)
+            .map_err(PyRuntimeError::new_err)
+    }
+
+    /// Start a run in the background and iterate over its events.
+    fn stream(&self, prompt: Bound<'_, PyAny>) -> PyResult<EventStream> {
+        let messages = self.messages(&prompt)?;
+        let (tx, rx) = std::sync::mpsc::channel();
+        let client = self.client.clone();
+        let tools = self.tools.clone();
+        let config = self.config.clone();
+        runtime().spawn(async move {
+            let handler = ChannelHandler(tx.clone());
+            let outcome = Harness::new(&client, &tools, config)
+                .with_event_handler(&handler)
+                .run(messages)
+                .await;
+            let _ = tx.send(StreamItem::Done(Box::new(outcome)));
+        });
+        Ok(EventStream {
+            rx: Mutex::new(Some(rx)),
+            result: Mutex::new(None),
+        })
+    }
+}
+
+impl PyHarness {
+    pub(crate) fn from_parts(
+        client: Arc<OpenRouterClient>,
+        tools: Arc<ToolSet>,
+        config: HarnessConfig,
+        system_prompt: String,
+    ) -> Self {
+        Self {
+            client,
+            tools,
+            config,
+            system_prompt,
+        }
+    }
+
+    /// Initial messages for a `str` prompt or a list of message dicts.
+    fn messages(&self, prompt: &Bound<'_, PyAny>) -> PyResult<Vec<Message>> {
+        if let Ok(text) = prompt.cast::<PyString>() {
+            return Ok(vec![
+                Message::system(&self.system_prompt),
+                Message::user(text.to_string()),
+            ]);
+        }
+        depythonize(prompt).map_err(|e| PyValueError::new_err(format!("invalid messages: {e}")))
+    }
+}
+
+/// Item sent from a background run to its [`EventStream`].
+enum StreamItem {
+    Event(serde_json::Value),
+    Done(Box<Result<HarnessResult, String>>),
+}
+
+/// Forwards converted events to an [`EventStream`].
+struct ChannelHandler(Sender<StreamItem>);
+
+impl EventHandler for ChannelHandler {
+    fn on_event(&self, event: &HarnessEvent<'_>) -> Option<EventResponse> {
+        if let Some(value) = event_to_json(event) {
+            let _ = self.0.send(StreamItem::Event(value));
+        }
+        None
+    }
+}
+
+/// Iterator over the events of a background run.
+#[pyclass(module = "cinch", frozen)]
+pub struct EventStream {
+    /// `None` once the run has ended.
+    rx: Mutex<Option<Receiver<StreamItem>>>,
+    result: Mutex<Option<RunResult>>,
+}
+
+#[pymethods]
+impl EventStream {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// Wait for the next event. Raises `RuntimeError` if the run failed.
+    fn __next__<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyAny>>> {
+        let mut guard = self.rx.lock().unwrap();
+        let Some(rx) = guard.take() else {
+            return Ok(None);
+        };
+        let (rx, item) = py.detach(move || {
+            let item = rx.recv();
+            (rx, item)
+        });
+        match item {
+            Ok(StreamItem::Event(value)) => {
+                *guard = Some(rx);
+                pythonize(py, &value)
+                    .map(Some)
+                    .map_err(|e| PyValueError::new_err(e.to_string()))
+            }
+            Ok(StreamItem::Done(outcome)) => {
+                let result = (*outcome).map_err(PyRuntimeError::new_err)?;
+                *self.result.lock().unwrap() = Some(result.into());
+                Ok(None)
+            }
+            Err(_) => Err(PyRuntimeError::new_err("harness run ended unexpectedly")),
+        }
+    }
+
+    /// The [`RunResult`], once the stream is exhausted; `None` before.
+    #[getter]
+    fn result(&self) -> Option<RunResult> {
+        self.result.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cinch_rs::test_support::{ScriptedTransport, test_config, text_reply};
+
+    fn harness(reply: &str) -> PyHarness {
+        let client = OpenRouterClient::with_transport(
+            "key",
+            ScriptedTransport::repeating(text_reply(reply)),
+        );
+        PyHarness::from_parts(
+            Arc::new(client),
+            Arc::new(ToolSet::new()),
+            test_config("sys"),
+            "sys".into(),
+        )
+    }
+
+    #[test]
+    fn run_returns_the_final_text() {
+        let harness = harness("done");
+        Python::attach(|py| {
+            let prompt = PyString::new(py, "hi").into_any();
+            let result = harness.run(py, prompt).unwrap();
+            assert!(result.finished);
+            assert_eq!(result.text, "done");
+            assert_eq!(result.messages[0].content.as_deref(), Some("sys"));
+        });
+    }
+
+    #[test]
+    fn stream_yields_events_then_result() {
+        let harness = harness("streamed");
+        Python::attach(|py| {
+            let prompt = PyString::new(py, "hi").into_any();
+            let stream = harness.stream(prompt).unwrap();
+            let mut types = Vec::new();
+            while let Some(event) = stream.__next__(py).unwrap() {
+                let kind: String = event.get_item("type").unwrap().extract().unwrap();
+                types.push(kind);
+            }
+            assert!(types.contains(&"text".to_string()), "{types:?}");
+            assert_eq!(types.last().map(String::as_str), Some("finished"));
+            assert_eq!(stream.result().unwrap().text, "streamed");
+            assert!(stream.__next__(py).unwrap().is_none());
+        });
+    }
+}
@@ -0,0 +1,76 @@
+//! Python bindings for cinch-rs.
+//!
+//! Builds the `cinch` extension module with [maturin](https://www.maturin.rs):
+//!
+//! ```sh
+//! cd crates/cinch-py && maturin develop --release
+//! ```
+//!
+//! The agent loop, tool dispatch, and HTTP all run in Rust on a shared tokio
+//! runtime; Python only supplies tools and consumes results. The GIL is
+//! released while the harness runs and re-acquired per Python tool call.
+//!
+//! ```python
+//! import cinch
+//!
+//! def word_count(text: str) -> str:
+//!     """Count the words in a piece of text."""
+//!     return str(len(text.split()))
+//!
+//! tools = cinch.ToolSet(workdir=".")  # common file/shell tools
+//! tools.add(cinch.Tool(word_count, parameters={
+//!     "type": "object",
+//!     "properties": {"text": {"type": "string"}},
+//!     "required": ["text"],
+//! }))
+//!
+//! harness = cinch.Harness("anthropic/claude-sonnet-4", tools=tools, max_rounds=10)
+//!
+//! # Blocking run.
+//! result = harness.run("How many words are in README.md?")
+//! print(result.text, result.cost_usd)
+//!
+//! # Or iterate over events as they happen.
+//! stream = harness.stream("Summarize src/")
+//! for event in stream:
+//!     if event["type"] == "tool_executing":
+//!         print("->", event["name"])
+//! print(stream.result.text)
+//! ```
+//!
+//! # Modules
+//!
+//! - [`tool`] — `Tool` (a Python callable as a cinch tool) and `ToolSet`.
+//! - [`harness`] — `Harness`, `RunResult`, and the `EventStream` iterator.
+//! - [`events`] — harness events as JSON objects (dicts on the Python side).
+
+pub mod events;
+pub mod harness;
+pub mod tool;
+
+use std::sync::OnceLock;
+
+use pyo3::prelude::*;
+
+/// Runtime shared by every harness in the process.
+pub(crate) fn runtime() -> &'static tokio::runtime::Runtime {
+    static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .thread_name("cinch-py")
+            .build()
+            .expect("failed to start tokio runtime")
+    })
+}
+
+/// The `cinch` Python module.
+#[pymodule]
+fn cinch(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<tool::PyTool>()?;
+    m.add_class::<tool::PyToolSet>()?;
+    m.add_class::<harness::PyHarness>()?;
+    m.add_class::<harness::RunResult>()?;
+    m.add_class::<harness::EventStream>()?;
+    Ok(())
+}
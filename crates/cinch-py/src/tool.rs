@@ -0,0 +1,245 @@
+//! Python callables as cinch tools.
+//!
+//! A `Tool` wraps a Python function. The model's JSON arguments are passed
+//! as keyword arguments; a `str` return value becomes the tool result as-is,
+//! `None` becomes empty, and anything else is serialized to JSON. Exceptions
+//! become `Error: ...` results so the model can self-correct.
+
+use std::sync::Arc;
+
+use cinch_rs::ToolDef;
+use cinch_rs::tools::core::{Tool, ToolFuture, ToolSet};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyString};
+use pythonize::{depythonize, pythonize};
+
+/// A Python callable exposed to the model as a tool.
+#[pyclass(name = "Tool", module = "cinch", frozen, skip_from_py_object)]
+#[derive(Clone)]
+pub struct PyTool {
+    definition: ToolDef,
+    func: Arc<Py<PyAny>>,
+    /// Python code can do anything, so tools count as mutations unless
+    /// declared `read_only`.
+    read_only: bool,
+}
+
+#[pymethods]
+impl PyTool {
+    /// `Tool(func, name=None, description=None, parameters=None, read_only=False)`.
+    ///
+    /// `name` defaults to `func.__name__`, `description` to its docstring,
+    /// and `parameters` (a JSON Schema dict) to an object with no properties.
+    /// Pass `read_only=True` for tools without side effects so they stay
+    /// available while planning and in read-only modes.
+    #[new]
+    #[pyo3(signature = (func, name=None, description=None, parameters=None, read_only=false))]
+    fn new(
+        func: Bound<'_, PyAny>,
+        name: Option<String>,
+        description: Option<String>,
+        parameters: Option<Bound<'_, PyAny>>,
+        read_only: bool,
+    ) -> PyResult<Self> {
+        if !func.is_callable() {
+            return Err(PyValueError::new_err("func must be callable"));
+        }
+        let name = match name {
+            Some(name) => name,
+            None => func.getattr("__name__")?.extract()?,
+        };
+        let description = match description {
+            Some(description) => description,
+            None => func
+                .py()
+                .import("inspect")?
+                .call_method1("getdoc", (&func,))?
+                .extract::<Option<String>>()?
+                .unwrap_or_else(|| format!("Call {name}")),
+        };
+        let parameters = match parameters {
+            Some(schema) => depythonize(&schema)
+                .map_err(|e| PyValueError::new_err(format!("invalid parameters: {e}")))?,
+            None => serde_json::json!({"type": "object", "properties": {}}),
+        };
+        Ok(Self {
+            definition: ToolDef::new(name, description, parameters),
+            func: Arc::new(func.unbind()),
+            read_only,
+        })
+    }
+
+    /// The tool name the model sees.
+    #[getter]
+    fn name(&self) -> String {
+        self.definition.function.name.clone()
+    }
+
+    /// The tool description the model sees.
+    #[getter]
+    fn description(&self) -> String {
+        self.definition.function.description.clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Tool({:?})", self.definition.function.name)
+    }
+}
+
+impl PyTool {
+    /// Call the Python function with JSON `arguments` (GIL must be held).
+    fn call(&self, py: Python<'_>, arguments: &str) -> Result<String, String> {
+        let args: serde_json::Value = if arguments.trim().is_empty() {
+            serde_json::json!({})
+        } else {
+            serde_json::from_str(arguments).map_err(|e| format!("invalid arguments: {e}"))?
+        };
+        let args = pythonize(py, &args).map_err(|e| e.to_string())?;
+        let func = self.func.bind(py);
+        let value = match args.cast::<PyDict>() {
+            Ok(kwargs) => func.call((), Some(kwargs)),
+            Err(_) => func.call1((args,)),
+        }
+        .map_err(|e| e.to_string())?;
+
+        if value.is_none() {
+            Ok(String::new())
+        } else if let Ok(text) = value.cast::<PyString>() {
+            Ok(text.to_string())
+        } else {
+            match depythonize::<serde_json::Value>(&value) {
+                Ok(json) => Ok(json.to_string()),
+                Err(_) => Ok(value.str().map_err(|e| e.to_string())?.to_string()),
+            }
+        }
+    }
+}
+
+impl Tool for PyTool {
+    fn definition(&self) -> ToolDef {
+        self.definition.clone()
+    }
+
+    fn is_mutation(&self) -> bool {
+        !self.read_only
+    }
+
+    fn execute(&self, arguments: &str) -> ToolFuture<'_> {
+        let tool = self.clone();
+        let arguments = arguments.to_string();
+        Box::pin(async move {
+            let name = tool.definition.function.name.clone();
+            // Python code may block; keep it off the async workers.
+            let outcome =
+                tokio::task::spawn_blocking(move || Python::attach(|py| tool.call(py, &arguments)))
+                    .await;
+            match outcome {
+                Ok(Ok(result)) => result,
+                Ok(Err(e)) => format!("Error: {name}: {e}"),
+                Err(e) => format!("Error: {name}: tool task failed: {e}"),
+            }
+        })
+    }
+}
+
+/// A set of tools available to a harness.
+#[pyclass(name = "ToolSet", module = "cinch", frozen)]
+pub struct PyToolSet {
+    pub(crate) inner: Arc<ToolSet>,
+}
+
+#[pymethods]
+impl PyToolSet {
+    /// `ToolSet(tools=None, workdir=None)`.
+    ///
+    /// With `workdir`, the built-in file and shell tools are registered,
+    /// rooted at that directory.
+    #[new]
+    #[pyo3(signature = (tools=None, workdir=None))]
+    fn new(tools: Option<Vec<PyRef<'_, PyTool>>>, workdir: Option<String>) -> Self {
+        let mut set = ToolSet::new();
+        if let Some(workdir) = workdir {
+            set = set.with_common_tools(workdir);
+        }
+        for tool in tools.into_iter().flatten() {
+            set.register(tool.clone());
+        }
+        Self {
+            inner: Arc::new(set),
+        }
+    }
+
+    /// Register another tool.
+    fn add(&self, tool: PyRef<'_, PyTool>) {
+        self.inner.register(tool.clone());
+    }
+
+    /// Names of the registered tools.
+    fn names(&self) -> Vec<String> {
+        self.inner
+            .definitions()
+            .into_iter()
+            .map(|d| d.function.name)
+            .collect()
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+
+    fn py_tool(source: &str, func: &str) -> PyTool {
+        Python::attach(|py| {
+            let code = CString::new(source).unwrap();
+            let module = PyModule::from_code(py, &code, c"tools.py", c"tools").unwrap();
+            PyTool::new(module.getattr(func).unwrap(), None, None, None, false).unwrap()
+        })
+    }
+
+    const SOURCE: &str = r#"
+def shout(text, times=1):
+    """Upper-case the text."""
+    return " ".join([text.upper()] * times)
+
+def stats(text):
+    return {"chars": len(text)}
+
+def fail():
+    raise ValueError("nope")
+"#;
+
+    #[test]
+    fn name_and_description_come_from_the_function() {
+        let def = py_tool(SOURCE, "shout").definition();
+        assert_eq!(def.function.name, "shout");
+        assert_eq!(def.function.description, "Upper-case the text.");
+    }
+
+    #[test]
+    fn tools_are_mutations_unless_read_only() {
+        assert!(py_tool(SOURCE, "shout").is_mutation());
+        let read_only = Python::attach(|py| {
+            let code = CString::new(SOURCE).unwrap();
+            let module = PyModule::from_code(py, &code, c"tools.py", c"tools").unwrap();
+            PyTool::new(module.getattr("stats").unwrap(), None, None, None, true).unwrap()
+        });
+        assert!(!read_only.is_mutation());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn executes_with_keyword_arguments() {
+        let shout = py_tool(SOURCE, "shout");
+        assert_eq!(shout.execute(r#"{"text":"hi","times":2}"#).await, "HI HI");
+        let stats = py_tool(SOURCE, "stats");
+        assert_eq!(stats.execute(r#"{"text":"abc"}"#).await, r#"{"chars":3}"#);
+        let fail = py_tool(SOURCE, "fail").execute("{}").await;
+        assert!(fail.starts_with("Error: fail:"), "{fail}");
+        assert!(fail.contains("nope"), "{fail}");
+    }
+}
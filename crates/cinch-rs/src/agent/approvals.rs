@@ -0,0 +1,302 @@
+//! Remembered "always allow" approval decisions.
+//!
+//! When a user approves a call to a tool in
+//! [`approval_required_tools`](super::config::HarnessConfig::approval_required_tools)
+//! with [`EventResponse::ApproveAlways`](super::events::EventResponse::ApproveAlways),
+//! the tool is added to the harness's [`ApprovalStore`] and later calls to it
+//! run without asking. The [`ApprovalScope`] decides how long the decision
+//! lasts: for the rest of the session (in memory, shared by every clone of
+//! the store) or for the project (also written to the store's JSON file, so
+//! the next session starts with it).
+//!
+//! Keep the file out of the project itself: anything in the workdir can be
+//! committed by a cloned repo or written by the agent's own tools, and
+//! either would switch the approval gate off. [`ApprovalStore::load_for_project`]
+//! reads one user-level file holding every project's decisions, keyed by the
+//! canonical project path.
+//!
+//! ```ignore
+//! let store = ApprovalStore::load_for_project("~/.config/cinch/approvals.json", ".")?;
+//! let config = HarnessConfig::default()
+//!     .with_approval_required_tools(vec!["shell".into()])
+//!     .with_approval_store(store);
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+/// How long an "always allow" decision lasts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalScope {
+    /// Until the process exits.
+    Session,
+    /// Persisted to the store's file (falls back to [`Session`](Self::Session)
+    /// for stores without one).
+    Project,
+}
+
+/// On-disk form of the project-scoped decisions.
+#[derive(Default, Serialize, Deserialize)]
+struct ApprovalFile {
+    #[serde(default)]
+    always_allow: BTreeSet<String>,
+}
+
+/// On-disk form of a user-level file: decisions per canonical project path.
+#[derive(Default, Serialize, Deserialize)]
+struct ProjectsFile {
+    #[serde(default)]
+    projects: BTreeMap<String, ApprovalFile>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    session: BTreeSet<String>,
+    project: BTreeSet<String>,
+    path: Option<PathBuf>,
+    /// Entry in a [`ProjectsFile`]; `None` for a single-project file.
+    key: Option<String>,
+}
+
+/// Tools the user chose to always allow.
+///
+/// Cheap to clone; clones share the same decisions, so a store handed to
+/// [`HarnessConfig::with_approval_store`](super::config::HarnessConfig::with_approval_store)
+/// can be inspected or edited while the agent runs.
+#[derive(Debug, Clone, Default)]
+pub struct ApprovalStore(Arc<Mutex<Inner>>);
+
+impl ApprovalStore {
+    /// A session-only store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A store whose project-scoped decisions are read from and saved to
+    /// `path` (JSON). A missing file starts empty.
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, String> {
+        let path = path.into();
+        let file: ApprovalFile = if path.exists() {
+            let json = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read approvals: {e}"))?;
+            serde_json::from_str(&json).map_err(|e| format!("Failed to parse approvals: {e}"))?
+        } else {
+            ApprovalFile::default()
+        };
+        Ok(Self(Arc::new(Mutex::new(Inner {
+            session: BTreeSet::new(),
+            project: file.always_allow,
+            path: Some(path),
+            key: None,
+        }))))
+    }
+
+    /// A store whose project-scoped decisions for `project_root` live in the
+    /// user-level file `path`, next to other projects' decisions. The
+    /// project is keyed by its canonical path. A missing file or entry
+    /// starts empty.
+    pub fn load_for_project(
+        path: impl Into<PathBuf>,
+        project_root: impl AsRef<Path>,
+    ) -> Result<Self, String> {
+        let path = path.into();
+        let root = project_root.as_ref();
+        let key = std::fs::canonicalize(root)
+            .unwrap_or_else(|_| root.to_path_buf())
+            .to_string_lossy()
+            .into_owned();
+        let mut file = read_projects(&path)?;
+        Ok(Self(Arc::new(Mutex::new(Inner {
+            session: BTreeSet::new(),
+            project: file.projects.remove(&key).unwrap_or_default().always_allow,
+            path: Some(path),
+            key: Some(key),
+        }))))
+    }
+
+    /// A store for a new session: the same project-scoped decisions and
+    /// file, no session-scoped ones. Use it to keep one session's "always
+    /// allow" from leaking into others served by the same process.
+    pub fn new_session(&self) -> Self {
+        let inner = self.0.lock().unwrap();
+        Self(Arc::new(Mutex::new(Inner {
+            session: BTreeSet::new(),
+            project: inner.project.clone(),
+            path: inner.path.clone(),
+            key: inner.key.clone(),
+        })))
+    }
+
+    /// The file project-scoped decisions are saved to, if any.
+    pub fn path(&self) -> Option<PathBuf> {
+        self.0.lock().unwrap().path.clone()
+    }
+
+    /// Whether calls to `tool` are always allowed.
+    pub fn allows(&self, tool: &str) -> bool {
+        let inner = self.0.lock().unwrap();
+        inner.session.contains(tool) || inner.project.contains(tool)
+    }
+
+    /// Always allow `tool` from now on. Project-scoped decisions are saved
+    /// immediately; on a write error the decision still holds for the
+    /// session.
+    pub fn allow(&self, tool: &str, scope: ApprovalScope) -> Result<(), String> {
+        let mut inner = self.0.lock().unwrap();
+        match (scope, inner.path.clone()) {
+            (ApprovalScope::Project, Some(path)) => {
+                inner.project.insert(tool.to_string());
+                save(&path, inner.key.as_deref(), &inner.project).inspect_err(|_| {
+                    inner.session.insert(tool.to_string());
+                })
+            }
+            _ => {
+                inner.session.insert(tool.to_string());
+                Ok(())
+            }
+        }
+    }
+
+    /// Ask again for `tool`, in both scopes.
+    pub fn forget(&self, tool: &str) -> Result<(), String> {
+        let mut inner = self.0.lock().unwrap();
+        inner.session.remove(tool);
+        if inner.project.remove(tool)
+            && let Some(path) = inner.path.clone()
+        {
+            save(&path, inner.key.as_deref(), &inner.project)?;
+        }
+        Ok(())
+    }
+
+    /// Always-allowed tools with their scope, sorted by name.
+    pub fn allowed(&self) -> Vec<(String, ApprovalScope)> {
+        let inner = self.0.lock().unwrap();
+        let mut allowed: Vec<_> = inner
+            .project
+            .iter()
+            .map(|t| (t.clone(), ApprovalScope::Project))
+            .chain(
+                inner
+                    .session
+                    .difference(&inner.project)
+                    .map(|t| (t.clone(), ApprovalScope::Session)),
+            )
+            .collect();
+        allowed.sort_by(|a, b| a.0.cmp(&b.0));
+        allowed
+    }
+}
+
+/// Read a user-level approvals file; a missing file is empty.
+fn read_projects(path: &Path) -> Result<ProjectsFile, String> {
+    if !path.exists() {
+        return Ok(ProjectsFile::default());
+    }
+    let json =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read approvals: {e}"))?;
+    serde_json::from_str(&json).map_err(|e| format!("Failed to parse approvals: {e}"))
+}
+
+/// Write the project-scoped decisions, creating parent directories. With a
+/// `key`, only that project's entry of the user-level file is replaced.
+fn save(path: &Path, key: Option<&str>, tools: &BTreeSet<String>) -> Result<(), String> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create approvals dir: {e}"))?;
+    }
+    let file = ApprovalFile {
+        always_allow: tools.clone(),
+    };
+    let json = match key {
+        Some(key) => {
+            let mut projects = read_projects(path)?;
+            if file.always_allow.is_empty() {
+                projects.projects.remove(key);
+            } else {
+                projects.projects.insert(key.to_string(), file);
+            }
+            serde_json::to_string_pretty(&projects)
+        }
+        None => serde_json::to_string_pretty(&file),
+    }
+    .map_err(|e| format!("Failed to serialize approvals: {e}"))?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write approvals: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_decisions_are_shared_by_clones_but_not_saved() {
+        let store = ApprovalStore::new();
+        let clone = store.clone();
+        assert!(!store.allows("shell"));
+        clone.allow("shell", ApprovalScope::Project).unwrap();
+        assert!(store.allows("shell"));
+        assert_eq!(
+            store.allowed(),
+            vec![("shell".to_string(), ApprovalScope::Session)]
+        );
+        assert!(store.path().is_none());
+        assert!(!store.new_session().allows("shell"));
+    }
+
+    #[test]
+    fn project_decisions_persist() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".agents/approvals.json");
+        let store = ApprovalStore::load(&path).unwrap();
+        store.allow("git_commit", ApprovalScope::Project).unwrap();
+        store.allow("shell", ApprovalScope::Session).unwrap();
+
+        let reloaded = ApprovalStore::load(&path).unwrap();
+        assert!(reloaded.allows("git_commit"));
+        assert!(!reloaded.allows("shell"));
+        assert!(store.new_session().allows("git_commit"));
+
+        reloaded.forget("git_commit").unwrap();
+        assert!(!ApprovalStore::load(&path).unwrap().allows("git_commit"));
+    }
+
+    #[test]
+    fn user_file_keeps_projects_apart() {
+        let dir = tempfile::tempdir().unwrap();
+        let (one, two) = (dir.path().join("one"), dir.path().join("two"));
+        std::fs::create_dir_all(&one).unwrap();
+        std::fs::create_dir_all(&two).unwrap();
+        let path = dir.path().join("config/approvals.json");
+
+        let store = ApprovalStore::load_for_project(&path, &one).unwrap();
+        store.allow("shell", ApprovalScope::Project).unwrap();
+        ApprovalStore::load_for_project(&path, &two)
+            .unwrap()
+            .allow("git_commit", ApprovalScope::Project)
+            .unwrap();
+
+        let reloaded = ApprovalStore::load_for_project(&path, one.join("../one")).unwrap();
+        assert!(reloaded.allows("shell"));
+        assert!(!reloaded.allows("git_commit"));
+        reloaded.forget("shell").unwrap();
+        let other = ApprovalStore::load_for_project(&path, &two).unwrap();
+        assert!(other.allows("git_commit"));
+        assert!(
+            !ApprovalStore::load_for_project(&path, &one)
+                .unwrap()
+                .allows("shell")
+        );
+    }
+
+    #[test]
+    fn bad_file_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("approvals.json");
+        std::fs::write(&path, "not json").unwrap();
+        assert!(ApprovalStore::load(&path).is_err());
+    }
+}
@@ -0,0 +1,247 @@
+//! Append-only audit log of consequential tool calls.
+//!
+//! With [`HarnessSessionConfig::audit_log`](super::config::HarnessSessionConfig::audit_log)
+//! set, every call to a tool that runs commands, accesses the network, or
+//! mutates state is appended to `{sessions_dir}/{trace_id}/audit.jsonl`, one
+//! [`AuditEntry`] per line. Entries record when the call happened, who
+//! approved it, and how it ended; calls that were denied or never ran are
+//! logged too. A call that runs gets a [`AuditOutcome::Started`] entry just
+//! before it is dispatched and a second entry with its outcome once the round
+//! ends, so a call cut short by a crash is still on record. The file is only
+//! ever appended to, so it survives checkpoint cleanup and resumed runs.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::tools::core::ToolSet;
+use crate::tools::output::ToolOutput;
+
+/// File name of the audit log inside a session directory.
+pub const AUDIT_FILE: &str = "audit.jsonl";
+
+/// Why a tool call is audited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditKind {
+    /// The tool runs commands ([`Tool::runs_commands`](crate::tools::core::Tool::runs_commands)).
+    Command,
+    /// The tool accesses the network ([`Tool::accesses_network`](crate::tools::core::Tool::accesses_network)).
+    Network,
+    /// The tool changes files or other state ([`Tool::is_mutation`](crate::tools::core::Tool::is_mutation)).
+    Mutation,
+}
+
+impl AuditKind {
+    /// The kind of a call to `name` with `arguments`, or `None` if it isn't
+    /// audited.
+    pub fn of(tools: &ToolSet, name: &str, arguments: &str) -> Option<Self> {
+        if tools.is_command_tool(name) {
+            Some(Self::Command)
+        } else if tools.is_network_tool(name) {
+            Some(Self::Network)
+        } else if tools.is_mutation_call(name, arguments) {
+            Some(Self::Mutation)
+        } else {
+            None
+        }
+    }
+}
+
+/// How a call got (or didn't get) permission to run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+pub enum Approval {
+    /// The tool isn't in `approval_required_tools`.
+    NotRequired,
+    /// The event handler approved the call.
+    Approved,
+    /// The event handler approved the call after editing its arguments;
+    /// the entry's `arguments` are the edited ones that ran.
+    Edited { original_arguments: String },
+    /// The user had chosen to always allow the tool, so nobody was asked
+    /// (see [`ApprovalStore`](super::approvals::ApprovalStore)).
+    AlwaysAllowed,
+    /// Approval was required but no handler answered, so the call ran.
+    Unanswered,
+    /// The event handler denied the call.
+    Denied { reason: String },
+    /// The event handler replied with a message instead of running the call.
+    Redirected,
+    /// Loop detection refused a repeated call.
+    LoopBlocked,
+    /// The call's tool category had no budget left.
+    BudgetExceeded,
+}
+
+/// How an audited call ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    /// The call is about to run. A later entry with the same `call_id`
+    /// records how it ended; without one, the run stopped while it ran.
+    Started,
+    /// The call ran and succeeded.
+    Ok,
+    /// The call ran and returned an error or a non-zero exit code.
+    Failed,
+    /// The call was denied, blocked, or skipped.
+    NotRun,
+    /// The call ran, but its file change was undone because a later call in
+    /// the round failed (see
+    /// [`PartialFailurePolicy::RollBack`](super::config::PartialFailurePolicy::RollBack)).
+    RolledBack,
+}
+
+/// One line of the audit log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Milliseconds since the Unix epoch when the entry was written.
+    pub timestamp_ms: u64,
+    pub trace_id: String,
+    pub round: u32,
+    pub call_id: String,
+    pub tool: String,
+    pub kind: AuditKind,
+    /// Raw JSON arguments of the call.
+    pub arguments: String,
+    pub approval: Approval,
+    pub outcome: AuditOutcome,
+    /// Exit code, for calls whose result is a [`ToolOutput`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+}
+
+impl AuditEntry {
+    /// Outcome and exit code read from a tool result. `ran` is false for
+    /// calls that were denied or skipped.
+    pub fn outcome_of(ran: bool, result: &str) -> (AuditOutcome, Option<i32>) {
+        if !ran {
+            return (AuditOutcome::NotRun, None);
+        }
+        match ToolOutput::parse(result) {
+            Some(output) if output.success() => (AuditOutcome::Ok, Some(output.exit_code)),
+            Some(output) => (AuditOutcome::Failed, Some(output.exit_code)),
+            None if result.starts_with("Error") => (AuditOutcome::Failed, None),
+            None => (AuditOutcome::Ok, None),
+        }
+    }
+}
+
+/// Writer for one run's audit log.
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+    trace_id: String,
+}
+
+impl AuditLog {
+    /// Log for run `trace_id` in `sessions_dir/{trace_id}/audit.jsonl`. The
+    /// directory is created on first write.
+    pub fn new(sessions_dir: &Path, trace_id: &str) -> Self {
+        Self {
+            path: sessions_dir.join(trace_id).join(AUDIT_FILE),
+            trace_id: trace_id.to_string(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn trace_id(&self) -> &str {
+        &self.trace_id
+    }
+
+    /// Append one entry.
+    pub fn append(&self, entry: &AuditEntry) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+        }
+        let mut line = serde_json::to_string(entry)
+            .map_err(|e| format!("Failed to serialize audit entry: {e}"))?;
+        line.push('\n');
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| format!("Failed to append to {}: {e}", self.path.display()))
+    }
+
+    /// Read every entry of the log at `path`.
+    pub fn read(path: &Path) -> Result<Vec<AuditEntry>, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        text.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str(line).map_err(|e| format!("Malformed audit entry: {e}"))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(call_id: &str, approval: Approval) -> AuditEntry {
+        AuditEntry {
+            timestamp_ms: 1_700_000_000_000,
+            trace_id: "tr-1".into(),
+            round: 1,
+            call_id: call_id.into(),
+            tool: "shell".into(),
+            kind: AuditKind::Command,
+            arguments: r#"{"command":"ls"}"#.into(),
+            approval,
+            outcome: AuditOutcome::Ok,
+            exit_code: Some(0),
+        }
+    }
+
+    #[test]
+    fn appends_entries_as_json_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path(), "tr-1");
+        let first = entry("c1", Approval::NotRequired);
+        let second = entry(
+            "c2",
+            Approval::Denied {
+                reason: "no".into(),
+            },
+        );
+        log.append(&first).unwrap();
+        log.append(&second).unwrap();
+
+        assert_eq!(log.path(), dir.path().join("tr-1").join(AUDIT_FILE));
+        let text = std::fs::read_to_string(log.path()).unwrap();
+        assert_eq!(text.lines().count(), 2);
+        assert!(text.contains(r#""decision":"denied","reason":"no""#));
+        assert_eq!(AuditLog::read(log.path()).unwrap(), vec![first, second]);
+    }
+
+    #[test]
+    fn outcome_reads_exit_codes() {
+        let failed = ToolOutput::new(2, "", "boom").render();
+        assert_eq!(
+            AuditEntry::outcome_of(true, &failed),
+            (AuditOutcome::Failed, Some(2))
+        );
+        assert_eq!(
+            AuditEntry::outcome_of(true, "Wrote 3 lines to a.rs"),
+            (AuditOutcome::Ok, None)
+        );
+        assert_eq!(
+            AuditEntry::outcome_of(true, "Error: no such file"),
+            (AuditOutcome::Failed, None)
+        );
+        assert_eq!(
+            AuditEntry::outcome_of(false, "Tool 'shell' was denied"),
+            (AuditOutcome::NotRun, None)
+        );
+    }
+}
@@ -0,0 +1,346 @@
+//! Blackboard: key-value state shared across an agent tree.
+//!
+//! Sub-agents normally coordinate only through the text summaries they
+//! return to their parent. A [`Blackboard`] lets every agent in a tree read
+//! and write structured values directly — a list of files already claimed,
+//! a shared findings table, a flag that the build is broken — through the
+//! `blackboard_get` and `blackboard_set` tools.
+//!
+//! The blackboard lives in [`SharedResources`](super::sub_agent::SharedResources)
+//! and is shared by reference with every child. To give agents access,
+//! register the tools on the parent's tool set; the delegation tools then
+//! register them for each child too, attributed to the child's name:
+//!
+//! ```ignore
+//! let shared = SharedResources::new(500_000, trace_id).inherit(&config);
+//! shared.blackboard.register_tools(&tools, "root");
+//! let mut changes = shared.blackboard.subscribe();
+//! tokio::spawn(async move {
+//!     while let Ok(change) = changes.recv().await {
+//!         println!("{} set {} (v{})", change.writer, change.key, change.version);
+//!     }
+//! });
+//! ```
+//!
+//! Values are JSON; [`Blackboard::get_as`] and [`Blackboard::set_as`] give
+//! typed access from Rust.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tokio::sync::broadcast;
+
+use crate::ToolDef;
+use crate::tools::core::{Tool, ToolFuture, ToolSet};
+use crate::tools::names;
+
+/// Buffered change events per subscriber before the oldest are dropped.
+const CHANGE_CHANNEL_CAPACITY: usize = 256;
+
+/// A stored value with its provenance.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BlackboardEntry {
+    pub value: serde_json::Value,
+    /// Incremented on every write to the key, starting at 1.
+    pub version: u64,
+    /// Agent that wrote this version.
+    pub writer: String,
+}
+
+/// A write to the blackboard, broadcast to subscribers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlackboardChange {
+    pub key: String,
+    /// The new value, or `None` when the key was removed.
+    pub value: Option<serde_json::Value>,
+    /// Version after the write (the removed version for removals).
+    pub version: u64,
+    pub writer: String,
+}
+
+/// Thread-safe key-value store shared by an agent tree.
+pub struct Blackboard {
+    entries: RwLock<BTreeMap<String, BlackboardEntry>>,
+    changes: broadcast::Sender<BlackboardChange>,
+}
+
+impl std::fmt::Debug for Blackboard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Blackboard")
+            .field("keys", &self.keys())
+            .finish()
+    }
+}
+
+impl Default for Blackboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Blackboard {
+    /// Create an empty blackboard.
+    pub fn new() -> Self {
+        Self {
+            entries: RwLock::new(BTreeMap::new()),
+            changes: broadcast::channel(CHANGE_CHANNEL_CAPACITY).0,
+        }
+    }
+
+    /// The entry for `key`.
+    pub fn get(&self, key: &str) -> Option<BlackboardEntry> {
+        self.entries.read().unwrap().get(key).cloned()
+    }
+
+    /// The value for `key` deserialized as `T`. `Err` if the stored value
+    /// has a different shape.
+    pub fn get_as<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, String> {
+        match self.get(key) {
+            Some(entry) => serde_json::from_value(entry.value)
+                .map(Some)
+                .map_err(|e| format!("blackboard key '{key}': {e}")),
+            None => Ok(None),
+        }
+    }
+
+    /// Store `value` under `key` and return the new version.
+    pub fn set(&self, key: &str, value: serde_json::Value, writer: &str) -> u64 {
+        let version = {
+            let mut entries = self.entries.write().unwrap();
+            let version = entries.get(key).map_or(1, |e| e.version + 1);
+            entries.insert(
+                key.to_string(),
+                BlackboardEntry {
+                    value: value.clone(),
+                    version,
+                    writer: writer.to_string(),
+                },
+            );
+            version
+        };
+        // No subscribers is fine.
+        let _ = self.changes.send(BlackboardChange {
+            key: key.to_string(),
+            value: Some(value),
+            version,
+            writer: writer.to_string(),
+        });
+        version
+    }
+
+    /// Serialize `value` and store it under `key`.
+    pub fn set_as<T: Serialize>(&self, key: &str, value: &T, writer: &str) -> Result<u64, String> {
+        let value = serde_json::to_value(value).map_err(|e| e.to_string())?;
+        Ok(self.set(key, value, writer))
+    }
+
+    /// Remove `key`, returning its last entry.
+    pub fn remove(&self, key: &str, writer: &str) -> Option<BlackboardEntry> {
+        let removed = self.entries.write().unwrap().remove(key)?;
+        let _ = self.changes.send(BlackboardChange {
+            key: key.to_string(),
+            value: None,
+            version: removed.version,
+            writer: writer.to_string(),
+        });
+        Some(removed)
+    }
+
+    /// All keys, sorted.
+    pub fn keys(&self) -> Vec<String> {
+        self.entries.read().unwrap().keys().cloned().collect()
+    }
+
+    /// A copy of every entry.
+    pub fn snapshot(&self) -> BTreeMap<String, BlackboardEntry> {
+        self.entries.read().unwrap().clone()
+    }
+
+    /// Receive every subsequent write. A subscriber that falls more than
+    /// 256 changes behind skips the oldest (`RecvError::Lagged`).
+    pub fn subscribe(&self) -> broadcast::Receiver<BlackboardChange> {
+        self.changes.subscribe()
+    }
+
+    /// Register `blackboard_get` and `blackboard_set` on `tools`, with
+    /// writes attributed to `agent`.
+    pub fn register_tools(self: &Arc<Self>, tools: &ToolSet, agent: &str) {
+        tools.register(BlackboardGetTool {
+            blackboard: Arc::clone(self),
+        });
+        tools.register(BlackboardSetTool {
+            blackboard: Arc::clone(self),
+            agent: agent.to_string(),
+        });
+    }
+}
+
+// ── Tools ───────────────────────────────────────────────────────────
+
+/// Arguments for `blackboard_get`.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct BlackboardGetArgs {
+    /// Key to read. Omit to list all keys with their versions and writers.
+    #[serde(default)]
+    pub key: Option<String>,
+}
+
+/// Reads the shared blackboard.
+pub struct BlackboardGetTool {
+    blackboard: Arc<Blackboard>,
+}
+
+impl Tool for BlackboardGetTool {
+    fn definition(&self) -> ToolDef {
+        ToolDef::new(
+            names::BLACKBOARD_GET,
+            "Read a value from the blackboard shared with your parent and sibling agents. \
+             Omit key to list the keys currently set.",
+            crate::json_schema_for::<BlackboardGetArgs>(),
+        )
+    }
+
+    fn execute(&self, arguments: &str) -> ToolFuture<'_> {
+        let arguments = arguments.to_string();
+        Box::pin(async move {
+            let args: BlackboardGetArgs = match crate::tools::core::parse_tool_args(&arguments) {
+                Ok(a) => a,
+                Err(e) => return e,
+            };
+            match args.key {
+                Some(key) => match self.blackboard.get(&key) {
+                    Some(entry) => serde_json::json!({
+                        "key": key,
+                        "value": entry.value,
+                        "version": entry.version,
+                        "writer": entry.writer,
+                    })
+                    .to_string(),
+                    None => format!("Key '{key}' is not set."),
+                },
+                None => {
+                    let snapshot = self.blackboard.snapshot();
+                    if snapshot.is_empty() {
+                        return "The blackboard is empty.".into();
+                    }
+                    snapshot
+                        .iter()
+                        .map(|(key, e)| format!("{key} (v{}, by {})", e.version, e.writer))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                }
+            }
+        })
+    }
+}
+
+/// Arguments for `blackboard_set`.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct BlackboardSetArgs {
+    /// Key to write.
+    pub key: String,
+    /// Any JSON value. `null` removes the key.
+    pub value: serde_json::Value,
+}
+
+/// Writes to the shared blackboard.
+pub struct BlackboardSetTool {
+    blackboard: Arc<Blackboard>,
+    agent: String,
+}
+
+impl Tool for BlackboardSetTool {
+    fn definition(&self) -> ToolDef {
+        ToolDef::new(
+            names::BLACKBOARD_SET,
+            "Write a JSON value to the blackboard shared with your parent and sibling agents, \
+             replacing any previous value. Use it for state others need (claimed files, \
+             findings, decisions); set null to remove a key.",
+            crate::json_schema_for::<BlackboardSetArgs>(),
+        )
+    }
+
+    fn execute(&self, arguments: &str) -> ToolFuture<'_> {
+        let arguments = arguments.to_string();
+        Box::pin(async move {
+            let args: BlackboardSetArgs = match crate::tools::core::parse_tool_args(&arguments) {
+                Ok(a) => a,
+                Err(e) => return e,
+            };
+            if args.value.is_null() {
+                return match self.blackboard.remove(&args.key, &self.agent) {
+                    Some(_) => format!("Removed '{}'.", args.key),
+                    None => format!("Key '{}' was not set.", args.key),
+                };
+            }
+            let version = self.blackboard.set(&args.key, args.value, &self.agent);
+            format!("Set '{}' (v{version}).", args.key)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_values_and_versions() {
+        let board = Blackboard::new();
+        assert_eq!(board.set_as("files", &vec!["a.rs"], "root").unwrap(), 1);
+        assert_eq!(
+            board
+                .set_as("files", &vec!["a.rs", "b.rs"], "child")
+                .unwrap(),
+            2
+        );
+
+        let files: Vec<String> = board.get_as("files").unwrap().unwrap();
+        assert_eq!(files, ["a.rs", "b.rs"]);
+        assert_eq!(board.get("files").unwrap().writer, "child");
+        assert!(board.get_as::<u32>("files").is_err());
+        assert_eq!(board.get_as::<u32>("missing").unwrap(), None);
+    }
+
+    #[test]
+    fn writes_are_broadcast() {
+        let board = Blackboard::new();
+        let mut changes = board.subscribe();
+        board.set("k", serde_json::json!(1), "a");
+        board.remove("k", "b");
+
+        let set = changes.try_recv().unwrap();
+        assert_eq!((set.key.as_str(), set.version), ("k", 1));
+        assert_eq!(set.value, Some(serde_json::json!(1)));
+        let removed = changes.try_recv().unwrap();
+        assert_eq!(removed.value, None);
+        assert_eq!(removed.writer, "b");
+    }
+
+    #[tokio::test]
+    async fn tools_read_and_write() {
+        let board = Arc::new(Blackboard::new());
+        let tools = ToolSet::new();
+        board.register_tools(&tools, "worker");
+
+        let out = tools
+            .execute("blackboard_set", r#"{"key":"status","value":{"ok":true}}"#)
+            .await;
+        assert_eq!(out, "Set 'status' (v1).");
+        let out = tools.execute("blackboard_get", r#"{"key":"status"}"#).await;
+        assert!(out.contains(r#""value":{"ok":true}"#), "{out}");
+        assert!(out.contains(r#""writer":"worker""#), "{out}");
+        assert_eq!(
+            tools.execute("blackboard_get", "{}").await,
+            "status (v1, by worker)"
+        );
+
+        tools
+            .execute("blackboard_set", r#"{"key":"status","value":null}"#)
+            .await;
+        assert!(board.keys().is_empty());
+    }
+}
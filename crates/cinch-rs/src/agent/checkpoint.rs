@@ -6,13 +6,23 @@
 //!
 //! Persistence is handled by [`super::session::SessionManager`]. This module
 //! defines the serializable [`Checkpoint`] struct.
+//!
+//! A checkpoint written mid-round (or from an older version) can violate
+//! message-sequence invariants that providers enforce. Loading a checkpoint
+//! through the session manager runs [`Checkpoint::repair()`] so a resumed
+//! conversation is never rejected on its first request.
 
 use crate::Message;
+use crate::api::normalize::{ConversationIssue, sanitize_messages, validate_messages};
+use crate::context::summarizer::StructuredState;
 use serde::{Deserialize, Serialize};
 
 /// Serializable checkpoint of harness state.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Checkpoint {
+    /// Schema version ([`CHECKPOINT_VERSION`](super::migration::CHECKPOINT_VERSION)).
+    #[serde(default)]
+    pub version: u32,
     /// Trace ID for the run.
     pub trace_id: String,
     /// All messages up to the checkpoint.
@@ -29,4 +39,104 @@ pub struct Checkpoint {
     pub estimated_cost_usd: f64,
     /// Timestamp of the checkpoint.
     pub timestamp: String,
+    /// Configured agent that wrote the checkpoint
+    /// ([`HarnessConfig::agent_name`](crate::agent::HarnessConfig::agent_name)).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    /// Summarizer state of a run using
+    /// [`SummaryStrategy::StructuredState`](crate::context::summarizer::SummaryStrategy::StructuredState).
+    /// Pass it to [`Harness::with_structured_state`](crate::agent::harness::Harness::with_structured_state)
+    /// when resuming so later compactions merge into it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured_state: Option<StructuredState>,
+}
+
+impl Checkpoint {
+    /// Check the messages for tool-call / tool-result pairing problems.
+    pub fn validate(&self) -> Vec<ConversationIssue> {
+        validate_messages(&self.messages)
+    }
+
+    /// Fix any problems reported by [`validate()`](Self::validate) and return
+    /// them. A trailing assistant tool-call message whose calls never ran is
+    /// dropped so the model decides again; orphaned results are dropped and
+    /// missing results are replaced with a placeholder (see
+    /// [`sanitize_messages()`]).
+    pub fn repair(&mut self) -> Vec<ConversationIssue> {
+        let issues = self.validate();
+        if issues.is_empty() {
+            return issues;
+        }
+        if issues.contains(&ConversationIssue::DanglingToolCalls) {
+            self.messages.pop();
+        }
+        let messages = std::mem::take(&mut self.messages);
+        self.messages = sanitize_messages(messages);
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CallType, FunctionCallData, MessageRole, ToolCall};
+
+    fn checkpoint(messages: Vec<Message>) -> Checkpoint {
+        Checkpoint {
+            version: crate::agent::migration::CHECKPOINT_VERSION,
+            trace_id: "tr".into(),
+            messages,
+            text_output: Vec::new(),
+            round: 1,
+            total_prompt_tokens: 0,
+            total_completion_tokens: 0,
+            estimated_cost_usd: 0.0,
+            timestamp: String::new(),
+            agent: None,
+            structured_state: None,
+        }
+    }
+
+    fn call(id: &str) -> ToolCall {
+        ToolCall {
+            id: id.into(),
+            call_type: CallType::Function,
+            function: FunctionCallData {
+                name: "shell".into(),
+                arguments: "{}".into(),
+            },
+        }
+    }
+
+    #[test]
+    fn repair_drops_dangling_calls_and_fills_missing_results() {
+        let mut cp = checkpoint(vec![
+            Message::system("sys"),
+            Message::user("task"),
+            Message::assistant_tool_calls(vec![call("a"), call("b")]),
+            Message::tool_result("a", "ok"),
+            Message::assistant_tool_calls(vec![call("c")]),
+        ]);
+        let issues = cp.repair();
+        assert_eq!(issues.len(), 2);
+        assert!(cp.validate().is_empty());
+        assert_eq!(cp.messages.len(), 5);
+        assert_eq!(cp.messages[4].tool_call_id.as_deref(), Some("b"));
+        assert!(
+            cp.messages
+                .last()
+                .is_some_and(|m| m.role == MessageRole::Tool)
+        );
+    }
+
+    #[test]
+    fn repair_leaves_valid_checkpoints_alone() {
+        let mut cp = checkpoint(vec![
+            Message::user("task"),
+            Message::assistant_tool_calls(vec![call("a")]),
+            Message::tool_result("a", "ok"),
+        ]);
+        assert!(cp.repair().is_empty());
+        assert_eq!(cp.messages.len(), 3);
+    }
 }
@@ -35,13 +35,18 @@
 //! ```
 
 use crate::ReasoningConfig;
+use crate::agent::approvals::ApprovalStore;
 use crate::agent::plan_execute::PlanExecuteConfig;
 use crate::agent::project_instructions::ProjectInstructions;
+use crate::agent::prompt::{ReminderInjection, SystemReminder};
+use crate::agent::warm_start::WarmStartStep;
 use crate::api::retry::RetryConfig;
 use crate::api::router::RoutingStrategy;
 use crate::context::eviction::EvictionConfig;
 use crate::context::summarizer::SummarizerConfig;
+use crate::{DataCollection, MaxPrice, ProviderPreferences, ProviderSort};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 // ── Generic toggle ────────────────────────────────────────────────
 
@@ -101,6 +106,16 @@ pub struct HarnessSessionConfig {
     /// Whether to delete round checkpoint files on successful completion,
     /// keeping only the manifest. Default: `true`.
     pub cleanup_on_success: bool,
+    /// Append every command, network, and mutating tool call to
+    /// `audit.jsonl` in the session directory (see
+    /// [`audit`](crate::agent::audit)). Independent of `enabled`.
+    /// Default: `false`.
+    pub audit_log: bool,
+    /// Model that writes a one-line title and short summary into the
+    /// manifest after each run (see
+    /// [`generate_session_title`](crate::agent::session::generate_session_title)).
+    /// Pick a cheap one. Default: `None` (no titles).
+    pub title_model: Option<String>,
 }
 
 impl Default for HarnessSessionConfig {
@@ -109,6 +124,8 @@ impl Default for HarnessSessionConfig {
             enabled: true,
             sessions_dir: PathBuf::from(".agents/sessions"),
             cleanup_on_success: true,
+            audit_log: false,
+            title_model: None,
         }
     }
 }
@@ -119,6 +136,8 @@ impl HarnessSessionConfig {
             enabled: false,
             sessions_dir: PathBuf::from(".agents/sessions"),
             cleanup_on_success: true,
+            audit_log: false,
+            title_model: None,
         }
     }
 }
@@ -134,6 +153,12 @@ pub struct HarnessCacheConfig {
     pub max_entries: usize,
     /// Maximum age (in rounds) before a cache entry is evicted.
     pub max_age_rounds: u32,
+    /// Optional disk-backed cache shared across runs. Default: `None`.
+    pub persistent: Option<PersistentCacheConfig>,
+    /// Directory the cached tools resolve relative paths against, so a
+    /// mutation through an absolute or `..` path invalidates the entries for
+    /// the same file. Default: `.`.
+    pub workdir: PathBuf,
 }
 
 impl Default for HarnessCacheConfig {
@@ -142,6 +167,8 @@ impl Default for HarnessCacheConfig {
             enabled: true,
             max_entries: 100,
             max_age_rounds: 10,
+            persistent: None,
+            workdir: PathBuf::from("."),
         }
     }
 }
@@ -152,8 +179,488 @@ impl HarnessCacheConfig {
             enabled: false,
             max_entries: 100,
             max_age_rounds: 10,
+            persistent: None,
+            workdir: PathBuf::from("."),
+        }
+    }
+}
+
+/// Configuration for the disk-backed tool result cache
+/// ([`DiskToolCache`](crate::tools::disk_cache::DiskToolCache)).
+#[derive(Debug, Clone)]
+pub struct PersistentCacheConfig {
+    /// Directory holding cache entries (e.g. `.cinch/tool-cache`).
+    pub dir: PathBuf,
+    /// Root directory the cached tools operate on, used to fingerprint the
+    /// content results depend on.
+    pub workdir: PathBuf,
+    /// Entry time-to-live.
+    pub ttl: Duration,
+}
+
+impl PersistentCacheConfig {
+    pub fn new(dir: impl Into<PathBuf>, workdir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            workdir: workdir.into(),
+            ttl: crate::tools::disk_cache::DEFAULT_DISK_CACHE_TTL,
+        }
+    }
+}
+
+// ── Speculation config ────────────────────────────────────────────
+
+/// Configuration for speculative next-round prefetch.
+///
+/// See [`speculation`](super::speculation) for how predictions are made and
+/// verified. Disabled by default.
+#[derive(Debug, Clone)]
+pub struct HarnessSpeculationConfig {
+    /// Whether speculative prefetch is enabled.
+    pub enabled: bool,
+    /// Tools whose results may be predicted. Only rounds where every call
+    /// is one of these (with a previously seen result) are speculated.
+    /// Default: `read_file`, `list_dir`, `grep`, `find_files`.
+    pub tools: Vec<String>,
+    /// Maximum fraction of differing lines between a predicted and an
+    /// actual tool result for the speculative response to be kept.
+    /// Default: `0.0` (results must match exactly).
+    pub max_divergence: f64,
+}
+
+impl Default for HarnessSpeculationConfig {
+    fn default() -> Self {
+        use crate::tools::names;
+        Self {
+            enabled: false,
+            tools: [
+                names::READ_FILE,
+                names::LIST_DIR,
+                names::GREP,
+                names::FIND_FILES,
+            ]
+            .map(String::from)
+            .to_vec(),
+            max_divergence: 0.0,
+        }
+    }
+}
+
+// ── Loop detection config ─────────────────────────────────────────
+
+/// Configuration for tool-call loop detection.
+///
+/// See [`loop_detect`](super::loop_detect). Enabled by default; repeated
+/// calls are only reported, not blocked, unless `block_repeats` is set.
+#[derive(Debug, Clone)]
+pub struct HarnessLoopDetectionConfig {
+    /// Whether loop detection is enabled.
+    pub enabled: bool,
+    /// Number of identical calls with identical results that count as a
+    /// loop. Default: 3.
+    pub threshold: u32,
+    /// Refuse further repetitions of a detected loop with an error result.
+    /// Default: `false`.
+    pub block_repeats: bool,
+}
+
+impl Default for HarnessLoopDetectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 3,
+            block_repeats: false,
+        }
+    }
+}
+
+// ── Failure escalation config ─────────────────────────────────────
+
+/// Configuration for escalating repeated tool failures.
+///
+/// See [`FailureEscalation`](crate::tools::reflection::FailureEscalation).
+/// Enabled by default.
+#[derive(Debug, Clone)]
+pub struct HarnessFailureEscalationConfig {
+    /// Whether repeated failures are escalated.
+    pub enabled: bool,
+    /// Number of similar failures in a row of one tool before the first
+    /// escalation. Default: 3.
+    pub threshold: u32,
+}
+
+impl Default for HarnessFailureEscalationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 3,
+        }
+    }
+}
+
+// ── Degenerate response config ────────────────────────────────────
+
+/// Configuration for degenerate-response detection and retry routing.
+///
+/// See [`degenerate`](super::degenerate). Enabled by default; empty
+/// responses are always retried.
+#[derive(Debug, Clone)]
+pub struct HarnessDegenerateConfig {
+    /// Whether non-empty responses are checked (whitespace, token loops,
+    /// prompt echo).
+    pub enabled: bool,
+    /// Repetitions of a trailing token run that count as a loop. Default: 8.
+    pub min_loop_repeats: u32,
+    /// Models for successive retries of an empty or degenerate response
+    /// (the first retry uses the first model). Retries past the end of the
+    /// list keep the routed model. Default: empty.
+    pub fallback_models: Vec<String>,
+}
+
+impl Default for HarnessDegenerateConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_loop_repeats: 8,
+            fallback_models: Vec::new(),
+        }
+    }
+}
+
+// ── Pacing config ─────────────────────────────────────────────────
+
+/// Default reminder injected once a run enters its wrap-up window.
+pub const DEFAULT_WRAP_UP_REMINDER: &str = "Time is almost up. Stop exploring: finish the step \
+     you are on, then give your final answer with what you have. Mention anything left undone.";
+
+/// Configuration for deadline-aware round pacing.
+///
+/// See [`pacing`](super::pacing). Disabled (no deadline) by default.
+#[derive(Debug, Clone)]
+pub struct HarnessPacingConfig {
+    /// Wall-clock time the run should finish within, measured from the
+    /// start of [`Harness::run()`](super::harness::Harness::run). `None`
+    /// disables pacing.
+    pub deadline: Option<Duration>,
+    /// Fraction of the deadline that, once remaining, starts the wrap-up
+    /// window. Default: `0.2`.
+    pub wrap_up_fraction: f64,
+    /// Model to route wrap-up rounds to instead of the routing strategy's
+    /// choice. Default: `None` (keep the routed model).
+    pub fast_model: Option<String>,
+    /// Cap on `max_tokens` during wrap-up rounds. Default: `Some(1024)`.
+    pub wrap_up_max_tokens: Option<u32>,
+    /// Reminder injected into each wrap-up round.
+    pub reminder: String,
+}
+
+impl Default for HarnessPacingConfig {
+    fn default() -> Self {
+        Self {
+            deadline: None,
+            wrap_up_fraction: 0.2,
+            fast_model: None,
+            wrap_up_max_tokens: Some(1024),
+            reminder: DEFAULT_WRAP_UP_REMINDER.to_string(),
+        }
+    }
+}
+
+impl HarnessPacingConfig {
+    /// Pace a run to finish within `deadline`, with default thresholds.
+    pub fn with_deadline(deadline: Duration) -> Self {
+        Self {
+            deadline: Some(deadline),
+            ..Default::default()
+        }
+    }
+}
+
+// ── Generation config ─────────────────────────────────────────────
+
+/// Stage of a run that [`GenerationParams`] can be set for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GenerationPhase {
+    /// Rounds of the plan-execute planning phase.
+    Planning,
+    /// Tool-use rounds after planning (every round when plan-execute is
+    /// disabled).
+    Execution,
+    /// The last round of a run, and continuations of a final answer cut off
+    /// by `max_tokens`.
+    FinalAnswer,
+}
+
+impl GenerationPhase {
+    /// The phase of round `round` (0-based).
+    pub(crate) fn of_round(round: u32, max_rounds: u32, planning: bool, continuing: bool) -> Self {
+        if continuing || round + 1 >= max_rounds {
+            Self::FinalAnswer
+        } else if planning {
+            Self::Planning
+        } else {
+            Self::Execution
+        }
+    }
+}
+
+/// Generation settings for one [`GenerationPhase`]. Unset fields fall back
+/// to [`HarnessConfig::max_tokens`] and [`HarnessConfig::temperature`]; no
+/// stop sequences are sent unless `stop` is set.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GenerationParams {
+    pub max_tokens: Option<u32>,
+    pub temperature: Option<f32>,
+    /// Sequences that end the response when generated.
+    pub stop: Option<Vec<String>>,
+}
+
+impl GenerationParams {
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    pub fn with_stop<S: Into<String>>(mut self, stop: impl IntoIterator<Item = S>) -> Self {
+        self.stop = Some(stop.into_iter().map(Into::into).collect());
+        self
+    }
+}
+
+/// Per-phase [`GenerationParams`]. All phases use the run-wide settings by
+/// default.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HarnessGenerationConfig {
+    pub planning: GenerationParams,
+    pub execution: GenerationParams,
+    pub final_answer: GenerationParams,
+}
+
+impl HarnessGenerationConfig {
+    pub fn for_phase(&self, phase: GenerationPhase) -> &GenerationParams {
+        match phase {
+            GenerationPhase::Planning => &self.planning,
+            GenerationPhase::Execution => &self.execution,
+            GenerationPhase::FinalAnswer => &self.final_answer,
+        }
+    }
+
+    pub fn for_phase_mut(&mut self, phase: GenerationPhase) -> &mut GenerationParams {
+        match phase {
+            GenerationPhase::Planning => &mut self.planning,
+            GenerationPhase::Execution => &mut self.execution,
+            GenerationPhase::FinalAnswer => &mut self.final_answer,
+        }
+    }
+}
+
+/// Generation settings of one request, with the phase overrides applied.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RoundGeneration {
+    pub(crate) max_tokens: u32,
+    pub(crate) temperature: f32,
+    pub(crate) stop: Option<Vec<String>>,
+}
+
+impl RoundGeneration {
+    /// `max_tokens`, capped at the context budget's current output reserve
+    /// so a reply never outgrows the room the budget left for it.
+    pub(crate) fn max_tokens_within(&self, output_reserve: Option<usize>) -> u32 {
+        match output_reserve {
+            Some(reserve) if reserve > 0 => self
+                .max_tokens
+                .min(u32::try_from(reserve).unwrap_or(u32::MAX)),
+            _ => self.max_tokens,
+        }
+    }
+}
+
+// ── Warm-start config ─────────────────────────────────────────────
+
+/// Context gathered concurrently before the first round.
+///
+/// See [`warm_start`](super::warm_start). Disabled (no steps) by default.
+#[derive(Debug, Clone)]
+pub struct HarnessWarmStartConfig {
+    /// Steps to run. Empty disables warm start.
+    pub steps: Vec<WarmStartStep>,
+    /// Directory the built-in steps inspect. Default: `.`.
+    pub workdir: PathBuf,
+    /// Time allowed for all steps together; steps still running at the
+    /// deadline are left out. Default: 10s.
+    pub deadline: Duration,
+}
+
+impl Default for HarnessWarmStartConfig {
+    fn default() -> Self {
+        Self {
+            steps: Vec::new(),
+            workdir: PathBuf::from("."),
+            deadline: Duration::from_secs(10),
+        }
+    }
+}
+
+impl HarnessWarmStartConfig {
+    /// Repository map, git status, and open TODOs for `workdir`.
+    pub fn for_workdir(workdir: impl Into<PathBuf>) -> Self {
+        Self {
+            steps: vec![
+                WarmStartStep::RepoMap { max_entries: 200 },
+                WarmStartStep::GitStatus,
+                WarmStartStep::Todos { max_matches: 30 },
+            ],
+            workdir: workdir.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Add a step.
+    pub fn with_step(mut self, step: WarmStartStep) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// Set the deadline for all steps together.
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Whether any steps are configured.
+    pub fn is_enabled(&self) -> bool {
+        !self.steps.is_empty()
+    }
+}
+
+// ── Change report config ──────────────────────────────────────────
+
+/// Configuration for the
+/// [`HarnessResult::files_changed`](super::events::HarnessResult::files_changed)
+/// report.
+///
+/// Before a mutation tool with a `path` argument first touches a file, the
+/// harness snapshots it into a [`SnapshotStore`](crate::tools::snapshot::SnapshotStore);
+/// after the run each snapshot is compared with the file on disk. Files
+/// changed only by shell commands are not seen. Disabled by default.
+#[derive(Debug, Clone)]
+pub struct HarnessChangeReportConfig {
+    /// Whether the report is built.
+    pub enabled: bool,
+    /// Directory relative `path` arguments are resolved against; reported
+    /// paths are relative to it. Default: `.`.
+    pub workdir: PathBuf,
+    /// Attach a unified diff to each changed file. Default: `false`.
+    pub include_diffs: bool,
+}
+
+impl Default for HarnessChangeReportConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            workdir: PathBuf::from("."),
+            include_diffs: false,
+        }
+    }
+}
+
+impl HarnessChangeReportConfig {
+    /// Report changes to files under `workdir`.
+    pub fn for_workdir(workdir: impl Into<PathBuf>) -> Self {
+        Self {
+            enabled: true,
+            workdir: workdir.into(),
+            include_diffs: false,
         }
     }
+
+    /// Attach a unified diff to each changed file.
+    pub fn with_diffs(mut self, include_diffs: bool) -> Self {
+        self.include_diffs = include_diffs;
+        self
+    }
+}
+
+// ── Git context config ────────────────────────────────────────────
+
+/// Live git status in the system prompt.
+///
+/// See [`git_context`](super::prompt::git_context). Only used with the
+/// prompt registry ([`HarnessConfig::use_prompt_registry`]). The status is
+/// read once when each run starts, not refreshed between rounds. Disabled
+/// by default.
+#[derive(Debug, Clone)]
+pub struct HarnessGitContextConfig {
+    /// Whether the section is added.
+    pub enabled: bool,
+    /// Repository directory. Default: `.`.
+    pub workdir: PathBuf,
+    /// Approximate token cap for the whole section. Default: 400.
+    pub max_tokens: usize,
+    /// Uncommitted files listed before the rest are counted. Default: 20.
+    pub max_dirty_files: usize,
+    /// Recent commits listed. Default: 5.
+    pub max_commits: usize,
+}
+
+impl Default for HarnessGitContextConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            workdir: PathBuf::from("."),
+            max_tokens: 400,
+            max_dirty_files: 20,
+            max_commits: 5,
+        }
+    }
+}
+
+impl HarnessGitContextConfig {
+    /// Show the git status of the repository at `workdir`.
+    pub fn for_workdir(workdir: impl Into<PathBuf>) -> Self {
+        Self {
+            enabled: true,
+            workdir: workdir.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Set the approximate token cap.
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+}
+
+// ── Partial failure policy ────────────────────────────────────────
+
+/// What to do with the rest of a round when one of its tool calls fails
+/// (returns a result starting with `Error`).
+///
+/// Policies other than [`Continue`](Self::Continue) execute the round's
+/// calls one at a time, in order, so that "the remaining calls" is well
+/// defined. Calls that are skipped get a `Skipped:` result.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum PartialFailurePolicy {
+    /// Run every call regardless of failures (in parallel where possible).
+    #[default]
+    Continue,
+    /// Skip the calls after the first failure (an `Error` result or a
+    /// non-zero exit code).
+    StopRemaining,
+    /// Skip the calls after the first failure and restore the files changed
+    /// by the round's mutation calls. Only mutations with a `path` argument
+    /// (resolved against `workdir`) are snapshotted; e.g. shell commands
+    /// are not undone.
+    RollBack {
+        /// Directory relative `path` arguments are resolved against.
+        workdir: PathBuf,
+    },
 }
 
 // ── Memory config ─────────────────────────────────────────────────
@@ -207,6 +714,14 @@ pub struct HarnessConfig {
     pub max_rounds: u32,
     /// Maximum tokens per LLM response.
     pub max_tokens: u32,
+    /// How many times a final answer cut off by `max_tokens`
+    /// (`finish_reason == "length"`) is continued with a follow-up request
+    /// and stitched together. Each continuation uses a round. `0` returns
+    /// truncated answers as-is. Default: `2`.
+    pub max_continuations: u32,
+    /// Stop the run once the estimated cost reaches this many USD. Checked
+    /// before each round, so a run can overshoot by at most one request.
+    pub max_cost_usd: Option<f64>,
     /// Sampling temperature.
     pub temperature: f32,
     /// Optional OpenRouter plugins (web-search, response-healing, etc.).
@@ -215,6 +730,15 @@ pub struct HarnessConfig {
     pub reasoning: Option<ReasoningConfig>,
     /// Retry configuration for transient API failures.
     pub retry: RetryConfig,
+    /// How many times a streamed response whose connection drops
+    /// mid-generation is resumed (after a [`retry`](Self::retry) backoff)
+    /// before the round fails. Text-only partial responses are continued
+    /// where they stopped; partials with tool-call fragments are requested
+    /// again from scratch. Default: `2`.
+    pub max_stream_resumes: u32,
+    /// Per-phase overrides of `max_tokens` and `temperature`, plus stop
+    /// sequences. Default: no overrides.
+    pub generation: HarnessGenerationConfig,
 
     // ── Advanced module configs (all default to enabled) ──
     /// Model routing strategy. Defaults to single-model (uses `model` field).
@@ -236,6 +760,9 @@ pub struct HarnessConfig {
     /// When a tool in this list is about to execute, the harness emits
     /// an `ApprovalRequired` event and waits for the handler's response.
     pub approval_required_tools: Vec<String>,
+    /// Tools the user chose to always allow; calls to them skip the
+    /// `ApprovalRequired` event. Default: an empty session-only store.
+    pub approval_store: ApprovalStore,
     /// Force sequential tool execution. When `true`, tool calls within a
     /// round execute one at a time in order, never in parallel. Use this
     /// for tool sets that include destructive operations where same-round
@@ -247,16 +774,23 @@ pub struct HarnessConfig {
     /// same-file mutations and shell calls are ordered.
     /// Default: [`PerFileForMutations`](crate::tools::dag::SequentialPolicy::PerFileForMutations).
     pub sequential_policy: crate::tools::dag::SequentialPolicy,
+    /// What to do with the rest of a round when one of its tool calls
+    /// fails. Default: [`PartialFailurePolicy::Continue`].
+    pub partial_failure: PartialFailurePolicy,
     /// Context window size in tokens (for context layout thresholds).
     pub context_window_tokens: usize,
     /// Number of recent messages to keep in the raw recency window.
     pub keep_recent_messages: usize,
+    /// Token budget for the raw recency window. When set, the window is
+    /// sized by tokens and `keep_recent_messages` is ignored. Default: `None`.
+    pub keep_recent_tokens: Option<usize>,
     /// System prompt (used for context layout prefix).
     pub system_prompt: Option<String>,
     /// File-based memory instructions injected into the system prompt.
     /// When `Some`, the harness appends these instructions to the system
     /// message so the agent knows how to use `memory/` for persistent
-    /// learnings and scratchpad notes. Defaults to [`default_memory_prompt()`](super::memory::default_memory_prompt).
+    /// learnings and scratchpad notes. Defaults to [`default_memory_prompt()`](super::memory::default_memory_prompt)
+    /// with the `memory` feature, `None` without it.
     /// Set to `None` to disable, or provide a custom string to override.
     pub memory_prompt: Option<String>,
     /// Optional JSON Schema for structured output. When set, the final LLM
@@ -264,6 +798,11 @@ pub struct HarnessConfig {
     /// attempt to parse the output as JSON and store it in
     /// `HarnessResult::structured_output`.
     pub output_schema: Option<serde_json::Value>,
+    /// Assistant prefix the final answer must continue from (e.g. `"{"`).
+    /// Sent as a trailing assistant message on the last round, and on every
+    /// round without tools when [`output_schema`](Self::output_schema) is
+    /// set, then prepended to the response text. Default: `None`.
+    pub final_answer_prefill: Option<String>,
     /// Memory system configuration (MEMORY.md index loading).
     pub memory_config: MemoryConfig,
     /// Project-level instructions loaded from AGENTS.md hierarchy.
@@ -274,8 +813,19 @@ pub struct HarnessConfig {
     /// descriptions to fit within the token limit before each API request.
     /// Default: `None` (no budget enforcement).
     pub tool_budget: Option<crate::tools::ToolBudget>,
+    /// Categories whose [`max_calls`](crate::tools::ToolCategory::max_calls)
+    /// limit calls per run. Calls past a limit are refused at dispatch with
+    /// an error result. Default: empty.
+    pub category_budgets: Vec<crate::tools::ToolCategory>,
     /// Use compact tool definitions, expanding on first use. Default: `false`.
     pub progressive_tools: bool,
+    /// Insert the tools' [call examples](crate::tools::core::Tool::call_examples)
+    /// as assistant tool-call + tool-result demonstrations between the
+    /// system prompt and the task (see
+    /// [`few_shot_messages`](crate::tools::spec::few_shot_messages)). Worth
+    /// enabling for weak models that get argument formats wrong.
+    /// Default: `false`.
+    pub few_shot_tool_examples: bool,
     /// Use [`PromptRegistry`](super::prompt::PromptRegistry) for system prompt
     /// assembly instead of manual `inject_prompt_extras`. When `true`, the
     /// harness builds a registry with the standard sections (memory prompt,
@@ -293,6 +843,64 @@ pub struct HarnessConfig {
     ///
     /// Default: `false`.
     pub prompt_caching: bool,
+    /// Speculative next-round prefetch. Disabled by default.
+    pub speculation: HarnessSpeculationConfig,
+    /// Tool-call loop detection. Enabled by default.
+    pub loop_detection: HarnessLoopDetectionConfig,
+    /// Escalation of repeated tool failures. Enabled by default.
+    pub failure_escalation: HarnessFailureEscalationConfig,
+    /// Provider routing preferences sent with every request the harness
+    /// makes: rounds, summarization, argument repair, memory consolidation,
+    /// and sub-agents. Default: `None`.
+    pub provider: Option<ProviderPreferences>,
+    /// Degenerate-response detection and retry routing.
+    pub degenerate: HarnessDegenerateConfig,
+    /// Deadline-aware round pacing. Disabled by default.
+    pub pacing: HarnessPacingConfig,
+    /// Context gathered before the first round and added to the system
+    /// prompt. Disabled by default.
+    pub warm_start: HarnessWarmStartConfig,
+    /// Report of the files changed during the run. Disabled by default.
+    pub change_report: HarnessChangeReportConfig,
+    /// Live git status as a dynamic prompt registry section. Disabled by
+    /// default.
+    pub git_context: HarnessGitContextConfig,
+    /// Custom system reminders, registered after the built-in defaults. A
+    /// reminder with the same name as a default replaces it.
+    pub reminders: Vec<SystemReminder>,
+    /// How fired reminders are added to the conversation. Default:
+    /// [`ReminderInjection::UserMessage`].
+    pub reminder_injection: ReminderInjection,
+    /// When a tool call's arguments are not valid JSON and can't be healed
+    /// locally (see [`repair`](crate::tools::repair)), ask the model to
+    /// resend just the arguments before executing the call. Default: `true`.
+    pub resend_malformed_arguments: bool,
+    /// Directory for archiving compacted messages. When set, each run
+    /// writes the messages every compaction drops to `<dir>/<trace_id>/`
+    /// and registers the [`RecallHistoryTool`](crate::tools::RecallHistoryTool)
+    /// so the model can search them. Default: `None`.
+    pub history_archive_dir: Option<PathBuf>,
+    /// Size the context budget's output reserve from a moving average of
+    /// observed completion sizes rather than reserving `max_tokens` every
+    /// round (see [`ContextBudget`](crate::context::ContextBudget)).
+    /// Only applies to the budget the harness creates. Default: `true`.
+    pub adaptive_output_reserve: bool,
+    /// Message normalization applied to each round's request. `None` looks
+    /// up the model family's normalizer with
+    /// [`MessageNormalizer::for_model()`](crate::api::MessageNormalizer::for_model)
+    /// for the round's model; set `Some(MessageNormalizer::default())` to
+    /// send messages unchanged. Default: `None`.
+    pub message_normalizer: Option<crate::api::MessageNormalizer>,
+    /// Name of the configured agent this run acts as. Recorded in round
+    /// checkpoints so a [`HandoffRunner`](crate::agent::handoff::HandoffRunner)
+    /// chain resumes with the right agent. Default: `None`.
+    pub agent_name: Option<String>,
+    /// Cumulative wall time for processes spawned by tools that share this
+    /// budget (see [`Shell::budget`](crate::tools::common::Shell::budget)).
+    /// The harness resets it when a run starts, so every run gets the full
+    /// allowance; a [`HandoffRunner`](crate::agent::handoff::HandoffRunner)
+    /// resets it once for the whole chain. Default: `None`.
+    pub subprocess_budget: Option<std::sync::Arc<crate::tools::limits::SubprocessBudget>>,
 }
 
 impl HarnessConfig {
@@ -334,6 +942,26 @@ impl HarnessConfig {
         self
     }
 
+    /// Set how many times a final answer truncated by `max_tokens` is
+    /// continued. `0` disables continuation.
+    pub fn with_max_continuations(mut self, max_continuations: u32) -> Self {
+        self.max_continuations = max_continuations;
+        self
+    }
+
+    /// Set how many times an interrupted stream is resumed. `0` fails the
+    /// round on the first dropped connection.
+    pub fn with_max_stream_resumes(mut self, max_stream_resumes: u32) -> Self {
+        self.max_stream_resumes = max_stream_resumes;
+        self
+    }
+
+    /// Stop the run once its estimated cost reaches `max_cost_usd`.
+    pub fn with_max_cost_usd(mut self, max_cost_usd: f64) -> Self {
+        self.max_cost_usd = Some(max_cost_usd);
+        self
+    }
+
     /// Set the maximum tokens per LLM response.
     pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
         self.max_tokens = max_tokens;
@@ -346,6 +974,27 @@ impl HarnessConfig {
         self
     }
 
+    /// Override `max_tokens`, `temperature`, or stop sequences for one
+    /// phase of the run.
+    pub fn with_phase_generation(
+        mut self,
+        phase: GenerationPhase,
+        params: GenerationParams,
+    ) -> Self {
+        *self.generation.for_phase_mut(phase) = params;
+        self
+    }
+
+    /// Generation settings for a request in `phase`.
+    pub(crate) fn generation_for(&self, phase: GenerationPhase) -> RoundGeneration {
+        let params = self.generation.for_phase(phase);
+        RoundGeneration {
+            max_tokens: params.max_tokens.unwrap_or(self.max_tokens),
+            temperature: params.temperature.unwrap_or(self.temperature),
+            stop: params.stop.clone(),
+        }
+    }
+
     /// Set the OpenRouter plugins.
     pub fn with_plugins(mut self, plugins: Vec<crate::Plugin>) -> Self {
         self.plugins = Some(plugins);
@@ -379,6 +1028,14 @@ impl HarnessConfig {
         self
     }
 
+    /// Remember "always allow" decisions in `store` (e.g. one loaded with
+    /// [`ApprovalStore::load_for_project`] to keep project-scoped decisions
+    /// on disk, outside the project).
+    pub fn with_approval_store(mut self, store: ApprovalStore) -> Self {
+        self.approval_store = store;
+        self
+    }
+
     /// Set the memory prompt (file-based persistent instructions).
     /// Pass `None` to disable the memory system.
     pub fn with_memory_prompt(mut self, prompt: Option<String>) -> Self {
@@ -392,6 +1049,12 @@ impl HarnessConfig {
         self
     }
 
+    /// Set the assistant prefix for final answers.
+    pub fn with_final_answer_prefill(mut self, prefill: impl Into<String>) -> Self {
+        self.final_answer_prefill = Some(prefill.into());
+        self
+    }
+
     /// Set a custom planning-phase prompt for the plan-execute workflow.
     ///
     /// This prompt is injected as a user message at the start of the planning
@@ -443,6 +1106,45 @@ impl HarnessConfig {
         self
     }
 
+    /// Set how the summarizer compresses history during compaction. The
+    /// local strategies (extractive, map-reduce, custom) make no LLM call.
+    pub fn with_summary_strategy(
+        mut self,
+        strategy: crate::context::summarizer::SummaryStrategy,
+    ) -> Self {
+        self.summarizer.config.strategy = strategy;
+        self
+    }
+
+    /// Archive compacted messages under `dir` and give the model a
+    /// `recall_history` tool to search them.
+    pub fn with_history_archive(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.history_archive_dir = Some(dir.into());
+        self
+    }
+
+    /// Size the raw recency window by a token budget instead of
+    /// [`keep_recent_messages`](Self::keep_recent_messages), so ten tiny tool
+    /// results and ten large file reads aren't protected from compaction
+    /// alike.
+    pub fn with_keep_recent_tokens(mut self, tokens: usize) -> Self {
+        self.keep_recent_tokens = Some(tokens);
+        self
+    }
+
+    /// Reserve output tokens based on observed completion sizes (`true`)
+    /// or always reserve the full `max_tokens` (`false`).
+    pub fn with_adaptive_output_reserve(mut self, enabled: bool) -> Self {
+        self.adaptive_output_reserve = enabled;
+        self
+    }
+
+    /// Override the per-model message normalizer for every round.
+    pub fn with_message_normalizer(mut self, normalizer: crate::api::MessageNormalizer) -> Self {
+        self.message_normalizer = Some(normalizer);
+        self
+    }
+
     /// Enable progressive tool loading: send compact tool descriptions by
     /// default, expanding to full descriptions on first use.
     pub fn with_progressive_tools(mut self, enabled: bool) -> Self {
@@ -450,12 +1152,27 @@ impl HarnessConfig {
         self
     }
 
+    /// Demonstrate tool calls with the tools' call examples before the
+    /// task. See [`few_shot_tool_examples`](Self::few_shot_tool_examples).
+    pub fn with_few_shot_tool_examples(mut self, enabled: bool) -> Self {
+        self.few_shot_tool_examples = enabled;
+        self
+    }
+
     /// Set a tool definition budget.
     pub fn with_tool_budget(mut self, budget: crate::tools::ToolBudget) -> Self {
         self.tool_budget = Some(budget);
         self
     }
 
+    /// Limit calls per run to `category`'s tools, e.g.
+    /// `ToolCategory::new("search", &["grep", "find_files"], "...").max_calls(20)`
+    /// or `ToolCategory::mutations(5)`. Repeatable.
+    pub fn with_category_budget(mut self, category: crate::tools::ToolCategory) -> Self {
+        self.category_budgets.push(category);
+        self
+    }
+
     /// Enable or disable `PromptRegistry`-based system prompt assembly.
     ///
     /// When enabled, the harness uses [`build_default_prompt_registry`](super::harness::build_default_prompt_registry)
@@ -472,6 +1189,13 @@ impl HarnessConfig {
         self
     }
 
+    /// Set what happens to the rest of a round when one of its tool calls
+    /// fails.
+    pub fn with_partial_failure_policy(mut self, policy: PartialFailurePolicy) -> Self {
+        self.partial_failure = policy;
+        self
+    }
+
     /// Enable or disable prompt caching for the OpenRouter API.
     ///
     /// When enabled, the harness annotates the system message and the last
@@ -483,6 +1207,183 @@ impl HarnessConfig {
         self
     }
 
+    /// Enable or disable asking the model to resend malformed tool-call
+    /// arguments that local healing couldn't fix.
+    pub fn with_resend_malformed_arguments(mut self, enabled: bool) -> Self {
+        self.resend_malformed_arguments = enabled;
+        self
+    }
+
+    /// Enable or disable speculative next-round prefetch.
+    ///
+    /// When enabled, rounds that only call previously seen read-only tools
+    /// send the next request early with the predicted results, and keep the
+    /// response if the real results match. See
+    /// [`speculation`](super::speculation).
+    pub fn with_speculative_prefetch(mut self, enabled: bool) -> Self {
+        self.speculation.enabled = enabled;
+        self
+    }
+
+    /// Set tool-call loop detection options. See
+    /// [`loop_detect`](super::loop_detect).
+    pub fn with_loop_detection(mut self, loop_detection: HarnessLoopDetectionConfig) -> Self {
+        self.loop_detection = loop_detection;
+        self
+    }
+
+    /// Set repeated-failure escalation options. See
+    /// [`reflection`](crate::tools::reflection).
+    pub fn with_failure_escalation(
+        mut self,
+        failure_escalation: HarnessFailureEscalationConfig,
+    ) -> Self {
+        self.failure_escalation = failure_escalation;
+        self
+    }
+
+    /// Set provider routing preferences for every request. Retries of
+    /// degenerate responses start from these and skip the provider that
+    /// failed.
+    pub fn with_provider(mut self, provider: ProviderPreferences) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
+    /// Try these providers first, in order.
+    pub fn with_provider_order<S: Into<String>>(
+        mut self,
+        providers: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.provider.get_or_insert_default().order =
+            Some(providers.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Allow or forbid providers outside the preferred order.
+    pub fn with_allow_fallbacks(mut self, allow: bool) -> Self {
+        self.provider.get_or_insert_default().allow_fallbacks = Some(allow);
+        self
+    }
+
+    /// Set the provider data collection policy.
+    /// [`DataCollection::Deny`] excludes providers that store or train on
+    /// inputs.
+    pub fn with_data_collection(mut self, policy: DataCollection) -> Self {
+        self.provider.get_or_insert_default().data_collection = Some(policy);
+        self
+    }
+
+    /// Only use providers with a zero data retention policy.
+    pub fn with_zero_data_retention(mut self, enabled: bool) -> Self {
+        self.provider.get_or_insert_default().zdr = Some(enabled);
+        self
+    }
+
+    /// Skip providers priced above `max_price`.
+    pub fn with_max_price(mut self, max_price: MaxPrice) -> Self {
+        self.provider.get_or_insert_default().max_price = Some(max_price);
+        self
+    }
+
+    /// Rank providers by price, throughput, or latency.
+    /// [`ProviderSort::Throughput`] suits interactive front ends.
+    pub fn with_provider_sort(mut self, sort: ProviderSort) -> Self {
+        self.provider.get_or_insert_default().sort = Some(sort);
+        self
+    }
+
+    /// Only use providers that support every request parameter.
+    pub fn with_require_parameters(mut self, require: bool) -> Self {
+        self.provider.get_or_insert_default().require_parameters = Some(require);
+        self
+    }
+
+    /// Pace the run to finish within `deadline`: as time runs low, rounds
+    /// switch to the pacing `fast_model`, shrink `max_tokens`, skip any
+    /// remaining planning, and get a wrap-up reminder. See
+    /// [`pacing`](super::pacing).
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.pacing.deadline = Some(deadline);
+        self
+    }
+
+    /// Set deadline pacing options. See [`pacing`](super::pacing).
+    pub fn with_pacing(mut self, pacing: HarnessPacingConfig) -> Self {
+        self.pacing = pacing;
+        self
+    }
+
+    /// Title and summarize each session with `model` after the run. See
+    /// [`HarnessSessionConfig::title_model`].
+    pub fn with_session_titles(mut self, model: impl Into<String>) -> Self {
+        self.session.title_model = Some(model.into());
+        self
+    }
+
+    /// Gather workspace context before the first round. See
+    /// [`warm_start`](super::warm_start).
+    pub fn with_warm_start(mut self, warm_start: HarnessWarmStartConfig) -> Self {
+        self.warm_start = warm_start;
+        self
+    }
+
+    /// Report the files changed during the run in
+    /// [`HarnessResult::files_changed`](super::events::HarnessResult::files_changed).
+    pub fn with_change_report(mut self, change_report: HarnessChangeReportConfig) -> Self {
+        self.change_report = change_report;
+        self
+    }
+
+    /// Add the current branch, uncommitted files, and recent commits to the
+    /// system prompt. Requires [`with_prompt_registry`](Self::with_prompt_registry).
+    pub fn with_git_context(mut self, git_context: HarnessGitContextConfig) -> Self {
+        self.git_context = git_context;
+        self
+    }
+
+    /// Add a custom system reminder (e.g. a domain-specific nudge).
+    ///
+    /// See [`SystemReminder::every_n_rounds()`],
+    /// [`SystemReminder::at_round()`], and
+    /// [`SystemReminder::when_context_above()`] for common frequencies.
+    pub fn with_reminder(mut self, reminder: SystemReminder) -> Self {
+        self.reminders.push(reminder);
+        self
+    }
+
+    /// Set how fired reminders are added to the conversation.
+    ///
+    /// [`ReminderInjection::EphemeralSystem`] sends them as system messages
+    /// at the end of the outgoing request without storing them, so they
+    /// never accumulate in the history or shift the cached prompt prefix.
+    pub fn with_reminder_injection(mut self, injection: ReminderInjection) -> Self {
+        self.reminder_injection = injection;
+        self
+    }
+
+    /// Persist cacheable tool results in `dir` across runs, for tools that
+    /// operate on `workdir`. See
+    /// [`DiskToolCache`](crate::tools::disk_cache::DiskToolCache).
+    pub fn with_persistent_tool_cache(
+        mut self,
+        dir: impl Into<PathBuf>,
+        workdir: impl Into<PathBuf>,
+    ) -> Self {
+        self.cache.persistent = Some(PersistentCacheConfig::new(dir, workdir));
+        self
+    }
+
+    /// Reset `budget` at the start of every run. Pass the same budget to
+    /// the tools that should draw from it.
+    pub fn with_subprocess_budget(
+        mut self,
+        budget: std::sync::Arc<crate::tools::limits::SubprocessBudget>,
+    ) -> Self {
+        self.subprocess_budget = Some(budget);
+        self
+    }
+
     /// Set project instructions directly.
     ///
     /// If the instructions contain compaction instructions, they are
@@ -502,10 +1403,14 @@ impl Default for HarnessConfig {
             model: crate::DEFAULT_MODEL.to_string(),
             max_rounds: 10,
             max_tokens: 1024,
+            max_continuations: 2,
+            max_cost_usd: None,
             temperature: 0.7,
             plugins: None,
             reasoning: None,
             retry: RetryConfig::default(),
+            max_stream_resumes: 2,
+            generation: HarnessGenerationConfig::default(),
             routing: RoutingStrategy::default(),
             eviction: HarnessEvictionConfig::default(),
             summarizer: HarnessSummarizerConfig::default(),
@@ -514,19 +1419,45 @@ impl Default for HarnessConfig {
             plan_execute: HarnessPlanExecuteConfig::default(),
             streaming: false,
             approval_required_tools: Vec::new(),
+            approval_store: ApprovalStore::new(),
             sequential_tools: false,
             sequential_policy: crate::tools::dag::SequentialPolicy::PerFileForMutations,
+            partial_failure: PartialFailurePolicy::default(),
             context_window_tokens: 200_000,
             keep_recent_messages: 10,
+            keep_recent_tokens: None,
             system_prompt: None,
+            #[cfg(feature = "memory")]
             memory_prompt: Some(crate::agent::memory::default_memory_prompt()),
+            #[cfg(not(feature = "memory"))]
+            memory_prompt: None,
             output_schema: None,
+            final_answer_prefill: None,
             memory_config: MemoryConfig::default(),
             project_instructions: None,
             tool_budget: None,
+            category_budgets: Vec::new(),
             progressive_tools: false,
+            few_shot_tool_examples: false,
             use_prompt_registry: false,
             prompt_caching: false,
+            speculation: HarnessSpeculationConfig::default(),
+            loop_detection: HarnessLoopDetectionConfig::default(),
+            failure_escalation: HarnessFailureEscalationConfig::default(),
+            provider: None,
+            degenerate: HarnessDegenerateConfig::default(),
+            pacing: HarnessPacingConfig::default(),
+            warm_start: HarnessWarmStartConfig::default(),
+            change_report: HarnessChangeReportConfig::default(),
+            git_context: HarnessGitContextConfig::default(),
+            reminders: Vec::new(),
+            reminder_injection: ReminderInjection::default(),
+            resend_malformed_arguments: true,
+            history_archive_dir: None,
+            adaptive_output_reserve: true,
+            message_normalizer: None,
+            agent_name: None,
+            subprocess_budget: None,
         }
     }
 }
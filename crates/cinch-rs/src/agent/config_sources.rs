@@ -0,0 +1,381 @@
+//! Layered configuration loading for [`HarnessConfig`].
+//!
+//! [`HarnessConfig::from_sources()`] merges, from lowest to highest
+//! precedence:
+//!
+//! 1. [`HarnessConfig::default()`],
+//! 2. a TOML file (when given),
+//! 3. `CINCH_*` environment variables (`CINCH_MODEL`, `CINCH_MAX_ROUNDS`, ...),
+//! 4. programmatic overrides, typically parsed CLI flags.
+//!
+//! Every layer is a partial [`HarnessSettings`]: fields left unset fall
+//! through to the layer below. Only the settings binaries routinely expose
+//! are covered; everything else is set on the returned config directly.
+//!
+//! ```toml
+//! # cinch.toml
+//! model = "anthropic/claude-sonnet-4"
+//! max_rounds = 30
+//! temperature = 0.2
+//! approval_required_tools = ["shell", "write_file"]
+//! ```
+//!
+//! ```ignore
+//! let overrides = HarnessSettings { max_rounds: cli.max_rounds, ..Default::default() };
+//! let config = HarnessConfig::from_sources(Some(Path::new("cinch.toml")), overrides)?;
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use figment::Figment;
+use figment::providers::{Env, Format, Serialized, Toml};
+use serde::{Deserialize, Serialize};
+
+use super::config::HarnessConfig;
+use crate::api::router::RoutingStrategy;
+use crate::{DataCollection, ProviderSort};
+
+/// Prefix of environment variables read by [`HarnessConfig::from_sources()`].
+pub const ENV_PREFIX: &str = "CINCH_";
+
+/// Keys read from the environment. Other `CINCH_*` variables (such as
+/// those set for hook commands) are ignored.
+const ENV_KEYS: &[&str] = &[
+    "model",
+    "max_rounds",
+    "max_tokens",
+    "max_cost_usd",
+    "temperature",
+    "system_prompt",
+    "streaming",
+    "context_window_tokens",
+    "keep_recent_messages",
+    "keep_recent_tokens",
+    "sequential_tools",
+    "approval_required_tools",
+    "prompt_caching",
+    "sessions_dir",
+    "audit_log",
+    "history_archive_dir",
+    "provider_order",
+    "allow_fallbacks",
+    "data_collection",
+    "zdr",
+    "max_prompt_price",
+    "max_completion_price",
+    "provider_sort",
+    "require_parameters",
+];
+
+/// One layer of harness settings. Unset fields leave the lower layer's
+/// value in place.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HarnessSettings {
+    /// Model used for every round (sets single-model routing).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Maximum agent rounds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_rounds: Option<u32>,
+    /// Per-response output token limit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    /// Spend limit in USD.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_cost_usd: Option<f64>,
+    /// Sampling temperature (0.0–2.0).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    /// System prompt.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    /// Stream responses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub streaming: Option<bool>,
+    /// Context window size in tokens.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_window_tokens: Option<usize>,
+    /// Messages kept in the raw recency window.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_recent_messages: Option<usize>,
+    /// Token budget for the raw recency window (overrides
+    /// `keep_recent_messages`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_recent_tokens: Option<usize>,
+    /// Run tool calls one at a time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sequential_tools: Option<bool>,
+    /// Tools that need approval before running.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approval_required_tools: Option<Vec<String>>,
+    /// Enable provider prompt caching.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_caching: Option<bool>,
+    /// Root directory for session directories.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sessions_dir: Option<PathBuf>,
+    /// Write an audit log of consequential tool calls.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audit_log: Option<bool>,
+    /// Directory for archiving compacted history.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history_archive_dir: Option<PathBuf>,
+    /// Providers to try first, in order.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_order: Option<Vec<String>>,
+    /// Allow providers outside `provider_order`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_fallbacks: Option<bool>,
+    /// Provider data collection policy (`"allow"` or `"deny"`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_collection: Option<DataCollection>,
+    /// Only use zero data retention providers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zdr: Option<bool>,
+    /// Maximum provider price in USD per million prompt tokens.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_prompt_price: Option<f64>,
+    /// Maximum provider price in USD per million completion tokens.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_completion_price: Option<f64>,
+    /// Provider ranking (`"price"`, `"throughput"`, or `"latency"`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_sort: Option<ProviderSort>,
+    /// Only use providers supporting every request parameter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub require_parameters: Option<bool>,
+}
+
+impl HarnessSettings {
+    /// Check value ranges, naming the offending setting.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.model.as_deref().is_some_and(|m| m.trim().is_empty()) {
+            return Err("invalid config: model must not be empty".into());
+        }
+        if self.max_rounds == Some(0) {
+            return Err("invalid config: max_rounds must be at least 1".into());
+        }
+        if self.max_tokens == Some(0) {
+            return Err("invalid config: max_tokens must be at least 1".into());
+        }
+        if let Some(cost) = self.max_cost_usd
+            && !(cost.is_finite() && cost > 0.0)
+        {
+            return Err(format!(
+                "invalid config: max_cost_usd must be positive, got {cost}"
+            ));
+        }
+        if let Some(t) = self.temperature
+            && !(0.0..=2.0).contains(&t)
+        {
+            return Err(format!(
+                "invalid config: temperature must be between 0.0 and 2.0, got {t}"
+            ));
+        }
+        if self.context_window_tokens == Some(0) {
+            return Err("invalid config: context_window_tokens must be at least 1".into());
+        }
+        for (name, price) in [
+            ("max_prompt_price", self.max_prompt_price),
+            ("max_completion_price", self.max_completion_price),
+        ] {
+            if let Some(p) = price
+                && !(p.is_finite() && p >= 0.0)
+            {
+                return Err(format!(
+                    "invalid config: {name} must not be negative, got {p}"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply the set fields onto `config`.
+    pub fn apply(self, config: &mut HarnessConfig) {
+        if let Some(model) = self.model {
+            config.routing = RoutingStrategy::Single(model.clone());
+            config.model = model;
+        }
+        if let Some(v) = self.max_rounds {
+            config.max_rounds = v;
+        }
+        if let Some(v) = self.max_tokens {
+            config.max_tokens = v;
+        }
+        if let Some(v) = self.max_cost_usd {
+            config.max_cost_usd = Some(v);
+        }
+        if let Some(v) = self.temperature {
+            config.temperature = v;
+        }
+        if let Some(v) = self.system_prompt {
+            config.system_prompt = Some(v);
+        }
+        if let Some(v) = self.streaming {
+            config.streaming = v;
+        }
+        if let Some(v) = self.context_window_tokens {
+            config.context_window_tokens = v;
+        }
+        if let Some(v) = self.keep_recent_messages {
+            config.keep_recent_messages = v;
+        }
+        if let Some(v) = self.keep_recent_tokens {
+            config.keep_recent_tokens = Some(v);
+        }
+        if let Some(v) = self.sequential_tools {
+            config.sequential_tools = v;
+        }
+        if let Some(v) = self.approval_required_tools {
+            config.approval_required_tools = v;
+        }
+        if let Some(v) = self.prompt_caching {
+            config.prompt_caching = v;
+        }
+        if let Some(v) = self.sessions_dir {
+            config.session.sessions_dir = v;
+        }
+        if let Some(v) = self.audit_log {
+            config.session.audit_log = v;
+        }
+        if let Some(v) = self.history_archive_dir {
+            config.history_archive_dir = Some(v);
+        }
+        if let Some(v) = self.provider_order {
+            config.provider.get_or_insert_default().order = Some(v);
+        }
+        if let Some(v) = self.allow_fallbacks {
+            config.provider.get_or_insert_default().allow_fallbacks = Some(v);
+        }
+        if let Some(v) = self.data_collection {
+            config.provider.get_or_insert_default().data_collection = Some(v);
+        }
+        if let Some(v) = self.zdr {
+            config.provider.get_or_insert_default().zdr = Some(v);
+        }
+        if self.max_prompt_price.is_some() || self.max_completion_price.is_some() {
+            let max_price = config
+                .provider
+                .get_or_insert_default()
+                .max_price
+                .get_or_insert_default();
+            if let Some(v) = self.max_prompt_price {
+                max_price.prompt = Some(v);
+            }
+            if let Some(v) = self.max_completion_price {
+                max_price.completion = Some(v);
+            }
+        }
+        if let Some(v) = self.provider_sort {
+            config.provider.get_or_insert_default().sort = Some(v);
+        }
+        if let Some(v) = self.require_parameters {
+            config.provider.get_or_insert_default().require_parameters = Some(v);
+        }
+    }
+}
+
+impl HarnessConfig {
+    /// Build a config from defaults, an optional TOML `file`, `CINCH_*`
+    /// environment variables, and `overrides`, in increasing precedence
+    /// (see [`config_sources`](super::config_sources)).
+    ///
+    /// Errors name the source and setting at fault: a missing file, a
+    /// malformed value, an unknown key, or an out-of-range value.
+    pub fn from_sources(file: Option<&Path>, overrides: HarnessSettings) -> Result<Self, String> {
+        let mut figment = Figment::new();
+        if let Some(path) = file {
+            if !path.is_file() {
+                return Err(format!("config file not found: {}", path.display()));
+            }
+            figment = figment.merge(Toml::file(path));
+        }
+        let settings: HarnessSettings = figment
+            .merge(Env::prefixed(ENV_PREFIX).only(ENV_KEYS))
+            .merge(Serialized::defaults(overrides))
+            .extract()
+            .map_err(|e| format!("invalid config: {e}"))?;
+        settings.validate()?;
+
+        let mut config = HarnessConfig::default();
+        settings.apply(&mut config);
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use figment::Jail;
+
+    #[test]
+    fn layers_apply_in_precedence_order() {
+        Jail::expect_with(|jail| {
+            jail.create_file(
+                "cinch.toml",
+                r#"
+                model = "from/file"
+                max_rounds = 30
+                temperature = 0.2
+                approval_required_tools = ["shell"]
+                provider_order = ["Anthropic"]
+                data_collection = "deny"
+                provider_sort = "throughput"
+                max_completion_price = 15.0
+                "#,
+            )?;
+            jail.set_env("CINCH_MAX_ROUNDS", "40");
+            jail.set_env("CINCH_STREAMING", "true");
+            jail.set_env("CINCH_TOOL_NAME", "ignored");
+
+            let overrides = HarnessSettings {
+                temperature: Some(0.9),
+                ..Default::default()
+            };
+            let config =
+                HarnessConfig::from_sources(Some(Path::new("cinch.toml")), overrides).unwrap();
+            assert_eq!(config.model, "from/file");
+            assert!(matches!(config.routing, RoutingStrategy::Single(ref m) if m == "from/file"));
+            assert_eq!(config.max_rounds, 40);
+            assert!(config.streaming);
+            assert_eq!(config.temperature, 0.9);
+            assert_eq!(config.approval_required_tools, vec!["shell".to_string()]);
+            let provider = config.provider.as_ref().unwrap();
+            assert_eq!(provider.order, Some(vec!["Anthropic".to_string()]));
+            assert_eq!(provider.data_collection, Some(DataCollection::Deny));
+            assert_eq!(provider.sort, Some(ProviderSort::Throughput));
+            assert_eq!(provider.max_price.unwrap().completion, Some(15.0));
+            // Untouched settings keep their defaults.
+            assert_eq!(config.max_tokens, HarnessConfig::default().max_tokens);
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn reports_validation_errors() {
+        Jail::expect_with(|jail| {
+            jail.create_file("bad.toml", "temperature = 3.5")?;
+            let err = HarnessConfig::from_sources(Some(Path::new("bad.toml")), Default::default())
+                .unwrap_err();
+            assert!(err.contains("temperature"), "{err}");
+
+            jail.create_file("typo.toml", "max_round = 5")?;
+            let err = HarnessConfig::from_sources(Some(Path::new("typo.toml")), Default::default())
+                .unwrap_err();
+            assert!(err.contains("max_round"), "{err}");
+
+            jail.create_file("price.toml", "max_prompt_price = -1.0")?;
+            let err =
+                HarnessConfig::from_sources(Some(Path::new("price.toml")), Default::default())
+                    .unwrap_err();
+            assert!(err.contains("max_prompt_price"), "{err}");
+
+            let err =
+                HarnessConfig::from_sources(Some(Path::new("missing.toml")), Default::default())
+                    .unwrap_err();
+            assert!(err.starts_with("config file not found"), "{err}");
+            Ok(())
+        });
+    }
+}
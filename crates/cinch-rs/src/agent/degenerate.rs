@@ -0,0 +1,224 @@
+//! Detection of degenerate model responses.
+//!
+//! Besides returning nothing at all, a struggling provider sometimes returns
+//! a response that is technically non-empty but useless: only whitespace, a
+//! token loop (`"the the the the ..."`), or the prompt echoed back. The
+//! harness treats these like empty responses — it retries them, rerouting
+//! the retry to a different provider (and, when
+//! [`HarnessDegenerateConfig::fallback_models`] are configured, a different
+//! model) through [`ProviderPreferences`] before giving up.
+//!
+//! The heuristics are deliberately conservative: each looks for a pattern
+//! a useful answer essentially never has.
+
+use super::config::HarnessDegenerateConfig;
+use crate::{ChatCompletion, Message, MessageRole, ProviderPreferences};
+
+/// Shortest response checked for a prompt echo; short answers like "OK" or
+/// a quoted file name legitimately appear in prompts.
+const MIN_ECHO_CHARS: usize = 40;
+
+/// Longest repeating unit (in whitespace-separated tokens) checked for loops.
+const MAX_LOOP_PERIOD: usize = 12;
+
+/// Why a response was judged degenerate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Degenerate {
+    /// No content, no tool calls, and no completion tokens.
+    Empty,
+    /// Content made only of whitespace.
+    Whitespace,
+    /// The response ends in the same short run of tokens repeated over and
+    /// over, covering at least half of it.
+    RepeatLoop,
+    /// The response is a verbatim copy of (part of) the prompt.
+    PromptEcho,
+}
+
+impl Degenerate {
+    /// Short human-readable label, used in events and logs.
+    pub fn label(self) -> &'static str {
+        match self {
+            Degenerate::Empty => "empty",
+            Degenerate::Whitespace => "whitespace only",
+            Degenerate::RepeatLoop => "repeated token loop",
+            Degenerate::PromptEcho => "prompt echo",
+        }
+    }
+}
+
+/// Check a completion without tool calls. Empty responses (no content and
+/// no completion tokens) are always reported; the other heuristics only
+/// when [`HarnessDegenerateConfig::enabled`] is set.
+pub fn check_completion(
+    completion: &ChatCompletion,
+    prompt: &[Message],
+    config: &HarnessDegenerateConfig,
+) -> Option<Degenerate> {
+    let content = completion.content.as_deref().unwrap_or_default();
+    if content.is_empty() {
+        let completion_tokens = completion
+            .usage
+            .as_ref()
+            .and_then(|u| u.completion_tokens)
+            .unwrap_or(0);
+        return (completion_tokens == 0).then_some(Degenerate::Empty);
+    }
+    if !config.enabled {
+        return None;
+    }
+    detect(content, prompt, config)
+}
+
+/// Check a text response against the degenerate-output heuristics.
+/// `prompt` is the request the response answers.
+pub fn detect(
+    content: &str,
+    prompt: &[Message],
+    config: &HarnessDegenerateConfig,
+) -> Option<Degenerate> {
+    let trimmed = content.trim();
+    if trimmed.is_empty() {
+        return (!content.is_empty()).then_some(Degenerate::Whitespace);
+    }
+    if is_repeat_loop(trimmed, config.min_loop_repeats) {
+        return Some(Degenerate::RepeatLoop);
+    }
+    if is_prompt_echo(trimmed, prompt) {
+        return Some(Degenerate::PromptEcho);
+    }
+    None
+}
+
+/// Whether the text ends in a unit of up to [`MAX_LOOP_PERIOD`] tokens
+/// repeated at least `min_repeats` times, covering half the tokens or more.
+fn is_repeat_loop(text: &str, min_repeats: u32) -> bool {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let n = tokens.len();
+    let min_repeats = min_repeats.max(2) as usize;
+    (1..=MAX_LOOP_PERIOD.min(n / min_repeats)).any(|period| {
+        // Length of the suffix that repeats with this period.
+        let run = (period..n)
+            .rev()
+            .take_while(|&i| tokens[i] == tokens[i - period])
+            .count()
+            + period;
+        run / period >= min_repeats && run * 2 >= n
+    })
+}
+
+/// Whether the text is contained verbatim in the system prompt or the last
+/// user message.
+fn is_prompt_echo(text: &str, prompt: &[Message]) -> bool {
+    if text.len() < MIN_ECHO_CHARS {
+        return false;
+    }
+    let system = prompt.iter().find(|m| m.role == MessageRole::System);
+    let last_user = prompt.iter().rev().find(|m| m.role == MessageRole::User);
+    [system, last_user]
+        .into_iter()
+        .flatten()
+        .filter_map(|m| m.content.as_deref())
+        .any(|source| source.contains(text))
+}
+
+/// Where to send the retry of a degenerate response.
+#[derive(Debug, Clone)]
+pub(crate) struct Reroute {
+    /// Model override for the retry, from the configured fallbacks.
+    pub(crate) model: Option<String>,
+    /// Provider preferences for the retry.
+    pub(crate) provider: ProviderPreferences,
+}
+
+impl Reroute {
+    /// The route for retry `attempt` (1-based), skipping every provider in
+    /// `failed_providers`.
+    pub(crate) fn for_attempt(
+        config: &HarnessDegenerateConfig,
+        base: Option<&ProviderPreferences>,
+        attempt: u32,
+        failed_providers: &[String],
+    ) -> Self {
+        let mut provider = base.cloned().unwrap_or_default();
+        provider.allow_fallbacks = Some(true);
+        if !failed_providers.is_empty() {
+            let ignore = provider.ignore.get_or_insert_with(Vec::new);
+            for failed in failed_providers {
+                if !ignore.contains(failed) {
+                    ignore.push(failed.clone());
+                }
+            }
+            // An explicit order naming a failed provider would route straight back.
+            if let Some(ref mut order) = provider.order {
+                order.retain(|p| !failed_providers.contains(p));
+            }
+        }
+        Self {
+            model: config
+                .fallback_models
+                .get(attempt.saturating_sub(1) as usize)
+                .cloned(),
+            provider,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(content: &str, prompt: &[Message]) -> Option<Degenerate> {
+        detect(content, prompt, &HarnessDegenerateConfig::default())
+    }
+
+    #[test]
+    fn whitespace_and_loops_are_degenerate() {
+        assert_eq!(check(" \n\t ", &[]), Some(Degenerate::Whitespace));
+        let looping = format!("Here is the fix:{}", " the fix".repeat(20));
+        assert_eq!(check(&looping, &[]), Some(Degenerate::RepeatLoop));
+        assert_eq!(check(&"ab ".repeat(30), &[]), Some(Degenerate::RepeatLoop));
+    }
+
+    #[test]
+    fn ordinary_answers_pass() {
+        assert_eq!(check("Done. I fixed the off-by-one in parse().", &[]), None);
+        // A short repeated phrase inside a longer answer is not a loop.
+        let answer = format!(
+            "{} and then the tests pass with the new parser in place, as expected.",
+            "very ".repeat(3)
+        );
+        assert_eq!(check(&answer, &[]), None);
+        // Brief quotes of the prompt are fine.
+        let prompt = [Message::user("rename foo to bar")];
+        assert_eq!(check("foo to bar", &prompt), None);
+    }
+
+    #[test]
+    fn prompt_echo_is_degenerate() {
+        let task = "Summarize the changes in src/lib.rs and list any breaking API changes.";
+        let prompt = [Message::system("You are helpful."), Message::user(task)];
+        assert_eq!(check(task, &prompt), Some(Degenerate::PromptEcho));
+    }
+
+    #[test]
+    fn reroute_skips_failed_providers_and_uses_fallbacks() {
+        let config = HarnessDegenerateConfig {
+            fallback_models: vec!["backup/model".into()],
+            ..Default::default()
+        };
+        let base = ProviderPreferences {
+            order: Some(vec!["A".into(), "B".into()]),
+            ..Default::default()
+        };
+        let route = Reroute::for_attempt(&config, Some(&base), 1, &["A".into()]);
+        assert_eq!(route.model.as_deref(), Some("backup/model"));
+        assert_eq!(route.provider.order, Some(vec!["B".to_string()]));
+        assert_eq!(route.provider.ignore, Some(vec!["A".to_string()]));
+        assert_eq!(route.provider.allow_fallbacks, Some(true));
+
+        let route = Reroute::for_attempt(&config, None, 2, &[]);
+        assert_eq!(route.model, None);
+        assert_eq!(route.provider.ignore, None);
+    }
+}
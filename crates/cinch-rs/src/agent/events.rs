@@ -18,8 +18,13 @@
 //! | Custom `impl EventHandler` | Full control (TUI, metrics, approval gates) |
 
 use crate::Message;
+use crate::agent::approvals::ApprovalScope;
 use crate::agent::plan_execute::Phase;
 use crate::context::{ContextBreakdown, ContextUsage, MessageDetail};
+use crate::tools::diff::DiffStats;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
 use tracing::{debug, info, trace, warn};
 
 // ── Events ─────────────────────────────────────────────────────────
@@ -42,19 +47,51 @@ pub enum HarnessEvent<'a> {
     Text(&'a str),
     /// The LLM is requesting tool calls this round.
     ToolCallsReceived { round: u32, count: usize },
-    /// A single tool is about to be executed.
-    ToolExecuting { name: &'a str, arguments: &'a str },
+    /// A single tool is about to be executed. `summary` is a one-line,
+    /// human-readable form of the arguments for display (see
+    /// [`tools::summary`](crate::tools::summary)).
+    ToolExecuting {
+        name: &'a str,
+        arguments: &'a str,
+        summary: &'a str,
+    },
     /// A single tool finished executing.
     ToolResult {
         name: &'a str,
         call_id: &'a str,
         result: &'a str,
     },
+    /// A successful `edit_file` / `write_file` call changed a file.
+    ///
+    /// Emitted right after the call's `ToolResult`, with the change rebuilt
+    /// by [`FileEdit::from_tool_call`](crate::tools::diff::FileEdit::from_tool_call).
+    /// Not emitted for calls whose change was rolled back (see
+    /// [`PartialFailurePolicy::RollBack`](super::config::PartialFailurePolicy::RollBack)).
+    FileEdited {
+        /// The tool that made the change, e.g. `edit_file`.
+        name: &'a str,
+        path: &'a str,
+        /// Unified diff hunk(s), starting with an `@@` header.
+        diff: &'a str,
+        stats: DiffStats,
+    },
     /// Token usage reported by the API for this round.
     TokenUsage {
         prompt_tokens: u32,
         completion_tokens: u32,
     },
+    /// Running token and cost totals for the run, including sub-agents.
+    ///
+    /// Emitted after each round's usage is recorded (`estimated: false`)
+    /// and, while a streaming round is still generating, every few dozen
+    /// tokens with this round's usage estimated from the streamed text
+    /// (`estimated: true`), so cost displays can tick up live.
+    CostUpdate {
+        prompt_tokens: u64,
+        completion_tokens: u64,
+        cost_usd: f64,
+        estimated: bool,
+    },
     /// The LLM returned reasoning / extended thinking content.
     Reasoning(&'a str),
     /// The agent finished (no more tool calls).
@@ -66,8 +103,48 @@ pub enum HarnessEvent<'a> {
         attempt: u32,
         max_retries: u32,
     },
+    /// The API returned a degenerate response (whitespace only, a token
+    /// loop, or the prompt echoed back; see [`crate::agent::degenerate`]).
+    /// The harness retries it on another provider or fallback model up to
+    /// `max_retries` times.
+    DegenerateResponse {
+        round: u32,
+        reason: &'a str,
+        attempt: u32,
+        max_retries: u32,
+    },
+    /// The final answer was cut off by `max_tokens`; the harness asks the
+    /// model to continue it (continuation `attempt` of `max_continuations`)
+    /// and appends the rest to the same text output.
+    LengthContinuation {
+        round: u32,
+        attempt: u32,
+        max_continuations: u32,
+    },
+    /// A streamed response's connection dropped mid-generation; the harness
+    /// resumes it (resume `attempt` of `max_resumes`) instead of failing
+    /// the round. `kept_chars` of streamed text are kept and continued;
+    /// `0` means the response is requested again from scratch, so text
+    /// already streamed for this round should be discarded.
+    StreamInterrupted {
+        error: &'a str,
+        attempt: u32,
+        max_resumes: u32,
+        kept_chars: usize,
+    },
+    /// A round ended. `decisions` records what the harness decided for it
+    /// — routing, context management, reminders, caching — in one place,
+    /// including choices that emit no event of their own.
+    RoundEnd { decisions: &'a RoundDecisions },
     /// The agent hit the round limit without finishing.
     RoundLimitReached { max_rounds: u32 },
+    /// The run was stopped because its estimated cost reached
+    /// [`HarnessConfig::max_cost_usd`](crate::agent::config::HarnessConfig::max_cost_usd).
+    CostLimitReached { cost_usd: f64, max_cost_usd: f64 },
+    /// The run entered the wrap-up window of its
+    /// [`pacing`](crate::agent::pacing) deadline at `round`, with
+    /// `remaining` time left. Emitted once per run.
+    PacingEngaged { round: u32, remaining: Duration },
 
     // ── Advanced module events ──
     /// Context eviction occurred: tool results were replaced with placeholders.
@@ -77,6 +154,11 @@ pub enum HarnessEvent<'a> {
     },
     /// Context summarization occurred: middle-zone messages were compacted.
     Compaction { compaction_number: usize },
+    /// A compaction summary failed the summarizer's quality guard (see
+    /// [`Summarizer::check_summary`](crate::context::summarizer::Summarizer::check_summary))
+    /// and was discarded. When `retrying` is false, compaction is skipped
+    /// and the history is kept as is.
+    CompactionRejected { reason: &'a str, retrying: bool },
     /// Fired before context compaction begins. Handlers can return
     /// `EventResponse::InjectMessage(msg)` to preserve critical state
     /// through compaction by including it in the summarization input.
@@ -89,17 +171,82 @@ pub enum HarnessEvent<'a> {
     CheckpointResumed { round: u32 },
     /// A tool result was served from the cache instead of re-executing.
     ToolCacheHit { name: &'a str, arguments: &'a str },
+    /// A tool call failed under a [`PartialFailurePolicy`](super::config::PartialFailurePolicy)
+    /// other than `Continue`: `skipped` later calls were not run and the file
+    /// changes of `rolled_back` earlier calls were undone.
+    ToolRoundAborted {
+        failed: &'a str,
+        skipped: usize,
+        rolled_back: usize,
+    },
+    /// A process spawned by tool `name` tripped a resource limit (see
+    /// [`limits`](crate::tools::limits)). `detail` is the notice shown to
+    /// the model.
+    ResourceLimitExceeded {
+        name: &'a str,
+        limit: crate::tools::limits::LimitKind,
+        detail: &'a str,
+    },
+    /// The model repeated the same tool call `repeats` times with identical
+    /// results. A reminder about the loop is added to the next round.
+    ToolLoopDetected {
+        name: &'a str,
+        arguments: &'a str,
+        repeats: u32,
+    },
+    /// Tool `name` failed `failures` times in a row with similar errors, and
+    /// a reminder at `level` is added to the next round (see
+    /// [`FailureEscalation`](crate::tools::reflection::FailureEscalation)).
+    ToolFailureEscalated {
+        name: &'a str,
+        failures: u32,
+        level: crate::tools::reflection::EscalationLevel,
+    },
+    /// A call to tool `name` was refused because the run's budget of
+    /// `max_calls` calls to `category` is used up.
+    ToolBudgetExceeded {
+        name: &'a str,
+        category: &'a str,
+        max_calls: u32,
+    },
+    /// A speculative request for `round` was resolved: `accepted` when its
+    /// response was used, `false` when it was discarded because tool results
+    /// or the round's messages diverged from the prediction.
+    SpeculationResolved { round: u32, accepted: bool },
+    /// Warm-start context gathering began for the named `steps` (see
+    /// [`warm_start`](crate::agent::warm_start)).
+    WarmStartStarted { steps: &'a [String] },
+    /// Warm-start gathering ended after `elapsed`: `gathered` steps were
+    /// added to the system prompt, `missing` ones failed, found nothing, or
+    /// missed the deadline.
+    WarmStartFinished {
+        gathered: &'a [String],
+        missing: &'a [String],
+        elapsed: Duration,
+    },
     /// Incremental text content delta (streaming mode only).
     TextDelta(&'a str),
     /// Incremental reasoning delta (streaming mode only).
     ReasoningDelta(&'a str),
     /// A tool execution requires human approval before proceeding.
     /// The event handler should return an `EventResponse` to approve or deny.
-    ApprovalRequired { name: &'a str, arguments: &'a str },
+    ApprovalRequired {
+        name: &'a str,
+        arguments: &'a str,
+        /// ID of the tool call awaiting the decision.
+        call_id: &'a str,
+    },
     /// The agent transitioned from the planning phase to the execution phase.
     PhaseTransition { from: &'a Phase, to: &'a Phase },
     /// The agent submitted a plan (called `submit_plan` during planning).
     PlanSubmitted { summary: &'a str },
+    /// Control passed from one configured agent to another via `handoff`
+    /// (see [`crate::agent::handoff`]).
+    Handoff {
+        from: &'a str,
+        to: &'a str,
+        context_summary: &'a str,
+    },
     /// MEMORY.md was consolidated (post-session, over the line limit).
     MemoryConsolidated {
         lines_before: usize,
@@ -126,6 +273,14 @@ pub enum HarnessEvent<'a> {
         /// Tokens written to the provider's prompt cache.
         cache_write_tokens: u32,
     },
+    /// This round's request no longer starts with the previous round's, so
+    /// the provider's prompt cache cannot reuse the lost part of the prefix.
+    PromptPrefixBroken {
+        round: u32,
+        cause: &'a crate::api::tracing::PrefixBreakCause,
+        /// Leading messages still shared with the previous request.
+        stable_messages: usize,
+    },
     /// Per-message context window snapshot for visualization.
     ///
     /// Emitted once per round after `RoundStart`, carrying detailed
@@ -168,6 +323,63 @@ impl HarnessEvent<'_> {
     }
 }
 
+/// What the harness decided during one round, carried by
+/// [`HarnessEvent::RoundEnd`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RoundDecisions {
+    pub round: u32,
+    /// Model the round's request went to.
+    pub model: String,
+    /// Whether routing, pacing, or a retry picked a model other than
+    /// [`HarnessConfig::model`](crate::agent::config::HarnessConfig::model).
+    pub model_routed: bool,
+    /// Tool definitions offered to the model.
+    pub tools_offered: usize,
+    /// Registered tools not offered (tool selection or filtering, the
+    /// planning phase, or the definition budget).
+    pub tools_withheld: usize,
+    /// Tool results replaced with placeholders before the request.
+    pub evicted_results: usize,
+    /// Characters freed by that eviction.
+    pub evicted_chars: usize,
+    /// Whether older history was compacted into a summary.
+    pub compacted: bool,
+    /// System reminders injected into the request.
+    pub reminders: usize,
+    /// Whether the pacing deadline's wrap-up mode was on.
+    pub wrap_up: bool,
+    /// Whether the final-answer prefill was sent.
+    pub prefilled: bool,
+    /// Whether the response came from a speculative prefetch.
+    pub speculative: bool,
+    /// `max_tokens` requested for the response.
+    pub max_tokens: u32,
+    /// Tool calls in the response.
+    pub tool_calls: usize,
+    /// Tool calls served from the result cache.
+    pub cache_hits: u32,
+    /// Tool calls denied or returning an error.
+    pub tool_errors: u32,
+    pub outcome: RoundOutcome,
+}
+
+/// How a round ended.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundOutcome {
+    /// Tool calls ran; the loop continues.
+    #[default]
+    ToolCalls,
+    /// The model gave its final answer.
+    Finished,
+    /// An empty or degenerate response was discarded for a retry.
+    Retried,
+    /// A final answer cut off by `max_tokens` is continued next round.
+    Continued,
+    /// The plan was submitted and execution starts next round.
+    PlanSubmitted,
+}
+
 /// Response from an event handler for events that support feedback.
 ///
 /// Most events return `None` (no feedback needed). `ApprovalRequired` events
@@ -176,6 +388,13 @@ impl HarnessEvent<'_> {
 pub enum EventResponse {
     /// Approve the pending action.
     Approve,
+    /// Approve the pending tool call, but run it with these JSON arguments
+    /// instead (e.g. a corrected shell command). The model is told about
+    /// the edit in the tool result, and the audit log keeps the original.
+    ApproveEdited(String),
+    /// Approve the pending tool call and stop asking for this tool, for the
+    /// given scope (see [`ApprovalStore`](super::approvals::ApprovalStore)).
+    ApproveAlways(ApprovalScope),
     /// Deny the pending action with a reason (passed back to the LLM).
     Deny(String),
     /// Inject a user message into the conversation before the next round.
@@ -191,8 +410,10 @@ pub enum EventResponse {
 /// Most events are informational and the return value is ignored. For
 /// [`HarnessEvent::ApprovalRequired`], the return value controls whether the
 /// tool executes: return `Some(EventResponse::Approve)` to proceed,
-/// `Some(EventResponse::Deny(reason))` to block with an error sent to the
-/// LLM, or `None` to auto-approve (the default).
+/// `Some(EventResponse::ApproveEdited(arguments))` to proceed with edited
+/// arguments, `Some(EventResponse::ApproveAlways(scope))` to proceed and
+/// skip approval for the tool from now on, `Some(EventResponse::Deny(reason))` to block with an error
+/// sent to the LLM, or `None` to auto-approve (the default).
 ///
 /// # Example
 ///
@@ -567,9 +788,10 @@ impl EventHandler for LoggingHandler {
                 );
                 if let Some(bd) = context_breakdown {
                     debug!(
-                        "  zones: prefix={}t, history={}t, middle={}t, recency={}t",
+                        "  zones: prefix={}t, history={}t (gen {}), middle={}t, recency={}t",
                         bd.prefix_tokens,
                         bd.compressed_history_tokens,
+                        bd.history_generation,
                         bd.middle_tokens,
                         bd.recency_tokens,
                     );
@@ -585,12 +807,15 @@ impl EventHandler for LoggingHandler {
             HarnessEvent::ToolCallsReceived { round, count } => {
                 debug!("{count} tool call(s) in round {round}");
             }
-            HarnessEvent::ToolExecuting { name, .. } => {
-                debug!("Executing tool: {name}");
+            HarnessEvent::ToolExecuting { name, summary, .. } => {
+                debug!("Executing tool: {name} {summary}");
             }
             HarnessEvent::ToolResult { name, result, .. } => {
                 debug!("Tool {name} result: {} bytes", result.len());
             }
+            HarnessEvent::FileEdited { path, stats, .. } => {
+                debug!("Edited {path}: +{} -{}", stats.added, stats.removed);
+            }
             HarnessEvent::Reasoning(text) => {
                 let preview: String = text.chars().take(200).collect();
                 debug!(
@@ -604,9 +829,55 @@ impl EventHandler for LoggingHandler {
             } => {
                 debug!("Tokens: prompt={prompt_tokens}, completion={completion_tokens}");
             }
+            HarnessEvent::CostUpdate {
+                cost_usd,
+                estimated,
+                ..
+            } => {
+                trace!(
+                    "Run cost: ${cost_usd:.4}{}",
+                    if *estimated { " (estimated)" } else { "" }
+                );
+            }
             HarnessEvent::Finished => {
                 info!("Agent finished (no more tool calls)");
             }
+            HarnessEvent::DegenerateResponse {
+                round,
+                reason,
+                attempt,
+                max_retries,
+            } => {
+                warn!(
+                    "Degenerate API response at round {round} ({reason}). \
+                     Retrying on another provider ({attempt}/{max_retries})..."
+                );
+            }
+            HarnessEvent::LengthContinuation {
+                round,
+                attempt,
+                max_continuations,
+            } => {
+                info!(
+                    "Response truncated by max_tokens at round {round}. \
+                     Continuing ({attempt}/{max_continuations})..."
+                );
+            }
+            HarnessEvent::StreamInterrupted {
+                error,
+                attempt,
+                max_resumes,
+                kept_chars,
+            } => {
+                warn!(
+                    "Stream interrupted ({error}); {} ({attempt}/{max_resumes})...",
+                    if *kept_chars > 0 {
+                        "resuming"
+                    } else {
+                        "restarting"
+                    }
+                );
+            }
             HarnessEvent::EmptyResponse {
                 round,
                 attempt,
@@ -617,9 +888,32 @@ impl EventHandler for LoggingHandler {
                      Retrying ({attempt}/{max_retries})..."
                 );
             }
+            HarnessEvent::RoundEnd { decisions } => {
+                debug!(
+                    "Round {} ended ({:?}): model {}, {} tools offered, {} tool calls, {} cached",
+                    decisions.round,
+                    decisions.outcome,
+                    decisions.model,
+                    decisions.tools_offered,
+                    decisions.tool_calls,
+                    decisions.cache_hits,
+                );
+            }
             HarnessEvent::RoundLimitReached { max_rounds } => {
                 info!("Agent hit round limit ({max_rounds})");
             }
+            HarnessEvent::CostLimitReached {
+                cost_usd,
+                max_cost_usd,
+            } => {
+                warn!("Cost limit reached: ${cost_usd:.4} of ${max_cost_usd:.4} budget");
+            }
+            HarnessEvent::PacingEngaged { round, remaining } => {
+                info!(
+                    "Round {round}: {:.1}s left before the deadline — wrapping up",
+                    remaining.as_secs_f64()
+                );
+            }
             HarnessEvent::Eviction {
                 freed_chars,
                 evicted_count,
@@ -629,6 +923,13 @@ impl EventHandler for LoggingHandler {
             HarnessEvent::Compaction { compaction_number } => {
                 info!("Context compaction #{compaction_number} completed");
             }
+            HarnessEvent::CompactionRejected { reason, retrying } => {
+                if *retrying {
+                    warn!("Compaction summary rejected ({reason}); retrying");
+                } else {
+                    warn!("Compaction summary rejected ({reason}); keeping the history");
+                }
+            }
             HarnessEvent::PreCompaction => {
                 debug!("Pre-compaction event fired");
             }
@@ -641,9 +942,67 @@ impl EventHandler for LoggingHandler {
             HarnessEvent::CheckpointResumed { round } => {
                 info!("Resumed from checkpoint at round {round}");
             }
+            HarnessEvent::SpeculationResolved { round, accepted } => {
+                if *accepted {
+                    debug!("Round {round} served by speculative prefetch");
+                } else {
+                    debug!("Round {round} speculative prefetch discarded");
+                }
+            }
+            HarnessEvent::WarmStartStarted { steps } => {
+                info!("Warm start: gathering {}", steps.join(", "));
+            }
+            HarnessEvent::WarmStartFinished {
+                gathered,
+                missing,
+                elapsed,
+            } => {
+                info!(
+                    "Warm start: {} gathered, {} missing in {:.1}s",
+                    gathered.len(),
+                    missing.len(),
+                    elapsed.as_secs_f64()
+                );
+            }
             HarnessEvent::ToolCacheHit { name, .. } => {
                 debug!("Tool cache hit: {name}");
             }
+            HarnessEvent::ToolRoundAborted {
+                failed,
+                skipped,
+                rolled_back,
+            } => {
+                warn!(
+                    "Tool '{failed}' failed: skipped {skipped} remaining calls, rolled back {rolled_back} files"
+                );
+            }
+            HarnessEvent::ResourceLimitExceeded {
+                name,
+                limit,
+                detail,
+            } => {
+                warn!("Tool '{name}' hit the {limit} limit: {detail}");
+            }
+            HarnessEvent::ToolLoopDetected { name, repeats, .. } => {
+                warn!("Tool loop detected: {name} repeated {repeats} times with identical results");
+            }
+            HarnessEvent::ToolBudgetExceeded {
+                name,
+                category,
+                max_calls,
+            } => {
+                warn!("Tool '{name}' refused: '{category}' budget of {max_calls} calls used up");
+            }
+            HarnessEvent::ToolFailureEscalated {
+                name,
+                failures,
+                level,
+            } => {
+                warn!(
+                    "Tool '{name}' failed {failures} times in a row; escalating ({})",
+                    level.label()
+                );
+            }
             HarnessEvent::TextDelta(delta) => {
                 let preview: String = delta.chars().take(80).collect();
                 trace!("Stream text delta: {preview}");
@@ -663,6 +1022,9 @@ impl EventHandler for LoggingHandler {
             HarnessEvent::PlanSubmitted { summary } => {
                 info!("Plan submitted: {summary}");
             }
+            HarnessEvent::Handoff { from, to, .. } => {
+                info!("Handoff: {from} → {to}");
+            }
             HarnessEvent::MemoryConsolidated {
                 lines_before,
                 lines_after,
@@ -691,6 +1053,15 @@ impl EventHandler for LoggingHandler {
                     "Session finishing: trace_id={trace_id}, finished={finished}, rounds={rounds_used}"
                 );
             }
+            HarnessEvent::PromptPrefixBroken {
+                round,
+                cause,
+                stable_messages,
+            } => {
+                debug!(
+                    "Round {round}: prompt prefix broken by {cause} ({stable_messages} messages stable)"
+                );
+            }
             HarnessEvent::PromptCacheStats {
                 cached_tokens,
                 cache_write_tokens,
@@ -743,11 +1114,41 @@ pub struct HarnessResult {
     pub rounds_used: u32,
     /// Whether the agent finished naturally (vs hitting the round limit).
     pub finished: bool,
-    /// Estimated cost in USD for the run.
+    /// Whether the run was stopped by
+    /// [`HarnessConfig::max_cost_usd`](crate::agent::config::HarnessConfig::max_cost_usd).
+    pub budget_exceeded: bool,
+    /// Estimated cost in USD for the run, including paid tool calls.
     pub estimated_cost_usd: f64,
+    /// Cost reported by paid tools
+    /// ([`Tool::call_cost_usd`](crate::tools::core::Tool::call_cost_usd)),
+    /// keyed by tool name.
+    pub tool_costs_usd: BTreeMap<String, f64>,
     /// Parsed structured output (when `HarnessConfig::output_schema` is set
     /// and the final LLM response is valid JSON).
     pub structured_output: Option<serde_json::Value>,
+    /// Prompt prefix stability across rounds: how much of each request a
+    /// provider prompt cache could reuse, and what broke the prefix.
+    pub prompt_cache: crate::api::tracing::CacheStabilityReport,
+    /// Per-tool call statistics, keyed by tool name.
+    pub tool_stats: BTreeMap<String, ToolStats>,
+    /// Net changes to files touched by mutation tools, sorted by path.
+    /// Empty unless
+    /// [`HarnessConfig::change_report`](crate::agent::config::HarnessConfig::change_report)
+    /// is enabled.
+    pub files_changed: Vec<crate::tools::snapshot::FileChange>,
+}
+
+/// Call statistics for one tool over a run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ToolStats {
+    /// Calls requested by the model, including denied and cached calls.
+    pub calls: u32,
+    /// Calls that were denied or returned an error result.
+    pub errors: u32,
+    /// Calls served from the tool result cache.
+    pub cache_hits: u32,
+    /// Total execution time of the calls that ran.
+    pub total_latency: Duration,
 }
 
 impl HarnessResult {
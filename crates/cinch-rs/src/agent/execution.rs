@@ -5,33 +5,55 @@
 //! approval gates / caching / DAG ordering, saving checkpoints, and retrying
 //! transient failures.
 
-use super::config::HarnessConfig;
-use super::events::{EventHandler, EventResponse, HarnessEvent};
+use super::audit::{Approval, AuditEntry, AuditKind, AuditLog, AuditOutcome};
+use super::config::{HarnessConfig, PartialFailurePolicy, RoundGeneration};
+use super::events::{EventHandler, EventResponse, HarnessEvent, ToolStats};
 use super::harness::{ModuleState, compact_if_needed};
+use super::loop_detect;
+#[cfg(feature = "checkpoint")]
 use crate::agent::checkpoint::Checkpoint;
+#[cfg(feature = "checkpoint")]
 use crate::agent::session::SessionManager;
+use crate::api::normalize::{MessageNormalizer, sanitize_messages};
 use crate::api::retry::{self, RetryConfig};
+use crate::api::tracing::{CostTracker, ModelPricing};
 use crate::context::ContextBudget;
 use crate::context::eviction::{self, ToolResultMeta};
 use crate::context::layout::ContextLayout;
+#[cfg(feature = "checkpoint")]
+use crate::context::summarizer::StructuredState;
+use crate::platform::Instant;
+use crate::tools::cache::path_argument;
 use crate::tools::core::ToolSet;
 use crate::tools::dag as tool_dag;
+use crate::tools::diff::FileEdit;
 use crate::tools::filter::ToolFilter;
-use crate::{CacheControl, ChatCompletion, ChatRequest, Message, MessageRole, OpenRouterClient};
-use tracing::warn;
+use crate::tools::limits::{LimitTrip, capture_trip};
+use crate::tools::repair;
+use crate::tools::snapshot::FileSnapshot;
+use crate::{
+    CacheControl, ChatCompletion, ChatRequest, Message, MessageRole, OpenRouterClient,
+    ProviderPreferences, UsageInfo,
+};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{info, warn};
 
 // ── Send request ──────────────────────────────────────────────────
 
-/// Build and send the chat completion request, handling streaming vs non-streaming.
-pub(crate) async fn send_round_request(
+/// Build the chat completion request for a round.
+#[allow(clippy::too_many_arguments)]
+fn build_round_request(
     config: &HarnessConfig,
-    client: &OpenRouterClient,
     messages: &[Message],
     model_for_round: &str,
+    generation: &RoundGeneration,
+    output_reserve: Option<usize>,
+    provider: Option<&ProviderPreferences>,
     tools_option: &Option<Vec<crate::ToolDef>>,
-    event_handler: &dyn EventHandler,
-    stop_signal: Option<&(dyn Fn() -> bool + Send + Sync)>,
-) -> Result<ChatCompletion, String> {
+    span_id: &str,
+) -> ChatRequest {
     let response_format = if config.output_schema.is_some() {
         Some(crate::ResponseFormat {
             fmt_type: crate::ResponseFormatType::JsonSchema,
@@ -40,44 +62,193 @@ pub(crate) async fn send_round_request(
         None
     };
 
-    let mut request_messages = messages.to_vec();
+    let normalizer = config
+        .message_normalizer
+        .clone()
+        .unwrap_or_else(|| MessageNormalizer::for_model(model_for_round));
+    let mut request_messages = normalizer.apply(sanitize_messages(messages.to_vec()));
     if config.prompt_caching {
         apply_cache_breakpoints(&mut request_messages);
     }
 
-    let body = ChatRequest {
+    ChatRequest {
+        span_id: Some(span_id.to_string()),
         model: Some(model_for_round.to_string()),
         messages: request_messages,
-        max_tokens: config.max_tokens,
-        temperature: config.temperature,
+        max_tokens: generation.max_tokens_within(output_reserve),
+        temperature: generation.temperature,
+        stop: generation.stop.clone(),
         tools: tools_option.clone(),
         plugins: config.plugins.clone(),
         reasoning: config.reasoning.clone(),
         response_format,
+        provider: provider.cloned(),
+        transforms: normalizer.request_transforms(),
         ..Default::default()
-    };
+    }
+}
+
+/// Follow-up asking the model to continue a response whose stream dropped.
+const STREAM_RESUME_PROMPT: &str = "Your previous response was cut off by a connection error. \
+    Continue exactly where it stopped, without repeating any of it.";
+
+/// Estimated streamed tokens between live [`HarnessEvent::CostUpdate`]s.
+const COST_TICK_TOKENS: u64 = 32;
+
+/// Characters per token when estimating usage from streamed text.
+const STREAM_CHARS_PER_TOKEN: u64 = 4;
+
+/// Baseline for the estimated [`HarnessEvent::CostUpdate`]s emitted while
+/// a round streams.
+pub(crate) struct CostTicker<'a> {
+    /// Totals recorded before this round.
+    pub(crate) tracker: &'a CostTracker,
+    pub(crate) pricing: &'a ModelPricing,
+    /// Estimated prompt tokens for this round.
+    pub(crate) prompt_tokens: u32,
+}
+
+impl CostTicker<'_> {
+    /// The running totals once `completion_tokens` have been streamed.
+    fn update(&self, completion_tokens: u64) -> HarnessEvent<'static> {
+        let completion = u32::try_from(completion_tokens).unwrap_or(u32::MAX);
+        HarnessEvent::CostUpdate {
+            prompt_tokens: self.tracker.total_prompt_tokens + u64::from(self.prompt_tokens),
+            completion_tokens: self.tracker.total_completion_tokens + completion_tokens,
+            cost_usd: self.tracker.estimated_cost_usd
+                + self.pricing.estimate_cost(self.prompt_tokens, completion),
+            estimated: true,
+        }
+    }
+}
+
+/// Build and send the chat completion request, handling streaming vs non-streaming.
+/// `max_tokens` is capped at `output_reserve`, the context budget's current
+/// output reserve. With a `ticker`, streaming rounds emit estimated cost updates as they
+/// generate. A stream that drops mid-generation is resumed up to
+/// [`HarnessConfig::max_stream_resumes`] times; an in-band provider error is
+/// returned as an error instead. The usage of every attempt whose response
+/// was discarded (reported, or estimated from what streamed) is appended to
+/// `discarded_usage`, so it can be paid for even when this returns an error.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn send_round_request(
+    config: &HarnessConfig,
+    client: &OpenRouterClient,
+    messages: &[Message],
+    model_for_round: &str,
+    generation: &RoundGeneration,
+    output_reserve: Option<usize>,
+    provider: Option<&ProviderPreferences>,
+    tools_option: &Option<Vec<crate::ToolDef>>,
+    span_id: &str,
+    event_handler: &dyn EventHandler,
+    stop_signal: Option<&(dyn Fn() -> bool + Send + Sync)>,
+    ticker: Option<&CostTicker<'_>>,
+    discarded_usage: &mut Vec<UsageInfo>,
+) -> Result<ChatCompletion, String> {
+    let body = build_round_request(
+        config,
+        messages,
+        model_for_round,
+        generation,
+        output_reserve,
+        provider,
+        tools_option,
+        span_id,
+    );
 
     if config.streaming {
-        let events = retry_api_call(&config.retry, || {
-            client.chat_stream_live(&body, |event| {
-                match event {
-                    crate::api::streaming::StreamEvent::TextDelta(delta) => {
-                        event_handler.on_event(&HarnessEvent::TextDelta(delta));
+        use crate::api::streaming::StreamEvent;
+
+        // Text kept from interrupted attempts; the resumed stream continues it.
+        let mut kept_text = String::new();
+        let mut resumes = 0;
+        let mut request = body.clone();
+        let events = loop {
+            let events = retry_api_call(&config.retry, || {
+                // Streamed characters and the token estimate last reported.
+                let mut streamed_chars = 0u64;
+                let mut reported_tokens = 0u64;
+                client.chat_stream_live(&request, move |event| {
+                    let chars = match event {
+                        crate::api::streaming::StreamEvent::TextDelta(delta) => {
+                            event_handler.on_event(&HarnessEvent::TextDelta(delta));
+                            delta.len()
+                        }
+                        crate::api::streaming::StreamEvent::ReasoningDelta(delta) => {
+                            event_handler.on_event(&HarnessEvent::ReasoningDelta(delta));
+                            delta.len()
+                        }
+                        crate::api::streaming::StreamEvent::ToolCallDelta {
+                            arguments_delta,
+                            ..
+                        } => arguments_delta.len(),
+                        _ => 0,
+                    };
+                    if let Some(ticker) = ticker {
+                        streamed_chars += chars as u64;
+                        let tokens = streamed_chars.div_ceil(STREAM_CHARS_PER_TOKEN);
+                        if tokens >= reported_tokens + COST_TICK_TOKENS {
+                            reported_tokens = tokens;
+                            event_handler.on_event(&ticker.update(tokens));
+                        }
                     }
-                    crate::api::streaming::StreamEvent::ReasoningDelta(delta) => {
-                        event_handler.on_event(&HarnessEvent::ReasoningDelta(delta));
-                    }
-                    _ => {}
-                }
-                // Return false to cancel the stream when stop signal fires.
-                stop_signal.is_none_or(|s| !s())
+                    // Return false to cancel the stream when stop signal fires.
+                    stop_signal.is_none_or(|s| !s())
+                })
             })
-        })
-        .await?;
+            .await?;
+            let prompt_tokens = ticker.map_or(0, |t| t.prompt_tokens);
+            if let Some(error) = events.iter().find_map(|e| match e {
+                StreamEvent::Error(error) => Some(error.clone()),
+                _ => None,
+            }) {
+                discarded_usage.push(attempt_usage(&events, prompt_tokens));
+                return Err(error);
+            }
+            let Some(error) = events.iter().find_map(|e| match e {
+                StreamEvent::Interrupted(error) => Some(error.clone()),
+                _ => None,
+            }) else {
+                break events;
+            };
+            discarded_usage.push(attempt_usage(&events, prompt_tokens));
+            if resumes >= config.max_stream_resumes || stop_signal.is_some_and(|s| s()) {
+                return Err(format!("stream interrupted: {error}"));
+            }
+            resumes += 1;
+            // Text can be continued where it stopped; a half-streamed tool
+            // call cannot, so anything with tool-call fragments starts over.
+            let partial = crate::api::streaming::collect_text(&events);
+            let restart = partial.is_empty()
+                || events
+                    .iter()
+                    .any(|e| matches!(e, StreamEvent::ToolCallDelta { .. }));
+            if restart {
+                kept_text.clear();
+            } else {
+                kept_text.push_str(&partial);
+            }
+            event_handler.on_event(&HarnessEvent::StreamInterrupted {
+                error: &error,
+                attempt: resumes,
+                max_resumes: config.max_stream_resumes,
+                kept_chars: kept_text.len(),
+            });
+            request.messages = body.messages.clone();
+            if !kept_text.is_empty() {
+                request.messages.push(Message::assistant_text(&kept_text));
+                request.messages.push(Message::user(STREAM_RESUME_PROMPT));
+            }
+            crate::platform::sleep(config.retry.delay_for_attempt(resumes - 1)).await;
+        };
 
-        let text = crate::api::streaming::collect_text(&events);
+        let mut text = kept_text;
+        text.push_str(&crate::api::streaming::collect_text(&events));
         let reasoning = crate::api::streaming::collect_reasoning(&events);
         let usage = crate::api::streaming::extract_usage(&events);
+        let finish_reason = crate::api::streaming::extract_finish_reason(&events);
+        let served_by = crate::api::streaming::extract_provider(&events);
         let tool_calls = assemble_tool_calls_from_stream(&events);
 
         Ok(ChatCompletion {
@@ -85,24 +256,280 @@ pub(crate) async fn send_round_request(
             tool_calls,
             usage,
             annotations: vec![],
-            finish_reason: Some("stop".into()),
+            finish_reason: finish_reason.or_else(|| Some("stop".into())),
             reasoning: if reasoning.is_empty() {
                 None
             } else {
                 Some(reasoning)
             },
+            provider: served_by,
         })
     } else {
         retry_api_call(&config.retry, || client.chat(&body)).await
     }
 }
 
+/// Usage of a streamed attempt: as reported, or estimated from the streamed
+/// characters when the stream ended before its usage chunk.
+fn attempt_usage(events: &[crate::api::streaming::StreamEvent], prompt_tokens: u32) -> UsageInfo {
+    use crate::api::streaming::StreamEvent;
+
+    if let Some(usage) = crate::api::streaming::extract_usage(events) {
+        return usage;
+    }
+    let chars: usize = events
+        .iter()
+        .map(|e| match e {
+            StreamEvent::TextDelta(delta) | StreamEvent::ReasoningDelta(delta) => delta.len(),
+            StreamEvent::ToolCallDelta {
+                arguments_delta, ..
+            } => arguments_delta.len(),
+            _ => 0,
+        })
+        .sum();
+    let completion_tokens =
+        u32::try_from((chars as u64).div_ceil(STREAM_CHARS_PER_TOKEN)).unwrap_or(u32::MAX);
+    UsageInfo {
+        prompt_tokens: Some(prompt_tokens),
+        completion_tokens: Some(completion_tokens),
+        total_tokens: Some(prompt_tokens.saturating_add(completion_tokens)),
+        prompt_tokens_details: None,
+    }
+}
+
+/// Send a speculative next-round request. Always non-streaming: its
+/// response may be discarded, so no deltas are emitted.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn send_speculative_request(
+    config: &HarnessConfig,
+    client: &OpenRouterClient,
+    messages: &[Message],
+    model: &str,
+    generation: &RoundGeneration,
+    output_reserve: Option<usize>,
+    tools_option: &Option<Vec<crate::ToolDef>>,
+    span_id: &str,
+) -> Result<ChatCompletion, String> {
+    let body = build_round_request(
+        config,
+        messages,
+        model,
+        generation,
+        output_reserve,
+        config.provider.as_ref(),
+        tools_option,
+        span_id,
+    );
+    retry_api_call(&config.retry, || client.chat(&body)).await
+}
+
+// ── Argument repair ───────────────────────────────────────────────
+
+/// Repair tool calls whose arguments aren't valid JSON: heal them locally,
+/// then (if enabled) ask the model to resend just the arguments. Calls that
+/// can't be repaired are left as-is and surface as tool errors.
+///
+/// Truncated arguments are only completed for read-only tools in a response
+/// that wasn't cut off by `max_tokens` (`finish_reason == "length"`);
+/// completing a mutation's cut-off content would write the truncated text.
+pub(crate) async fn repair_tool_calls(
+    config: &HarnessConfig,
+    client: &OpenRouterClient,
+    model: &str,
+    tools: &ToolSet,
+    tools_option: &Option<Vec<crate::ToolDef>>,
+    finish_reason: Option<&str>,
+    tool_calls: &mut [crate::ToolCall],
+) {
+    let cut_off = finish_reason == Some("length");
+    for call in tool_calls.iter_mut() {
+        let name = &call.function.name;
+        if repair::is_valid_arguments(&call.function.arguments) {
+            continue;
+        }
+        let heal = if cut_off || tools.is_mutation_tool(name) {
+            repair::heal_format
+        } else {
+            repair::heal_arguments
+        };
+        if let Some(healed) = heal(&call.function.arguments) {
+            info!("Healed malformed arguments for tool '{name}'");
+            call.function.arguments = healed;
+            continue;
+        }
+        if !config.resend_malformed_arguments {
+            warn!("Tool '{name}' called with malformed arguments");
+            continue;
+        }
+        match resend_arguments(
+            client,
+            model,
+            config.provider.as_ref(),
+            tools_option,
+            call,
+            heal,
+        )
+        .await
+        {
+            Ok(arguments) => {
+                info!("Model resent valid arguments for tool '{name}'");
+                call.function.arguments = arguments;
+            }
+            Err(e) => warn!("Could not repair arguments for tool '{name}': {e}"),
+        }
+    }
+}
+
+/// Ask the model to resend a call's arguments as a bare JSON object.
+async fn resend_arguments(
+    client: &OpenRouterClient,
+    model: &str,
+    provider: Option<&ProviderPreferences>,
+    tools_option: &Option<Vec<crate::ToolDef>>,
+    call: &crate::ToolCall,
+    heal: fn(&str) -> Option<String>,
+) -> Result<String, String> {
+    let schema = tools_option
+        .iter()
+        .flatten()
+        .find(|t| t.function.name == call.function.name)
+        .map_or_else(String::new, |t| t.function.parameters.to_string());
+    let request = ChatRequest {
+        model: Some(model.to_string()),
+        messages: vec![
+            Message::system(
+                "Your previous tool call had invalid JSON arguments. Reply with only \
+                 the corrected JSON arguments object: no prose, no code fences.",
+            ),
+            Message::user(format!(
+                "Tool: {}\nParameter schema: {schema}\nInvalid arguments:\n{}",
+                call.function.name, call.function.arguments
+            )),
+        ],
+        temperature: 0.0,
+        provider: provider.cloned(),
+        ..Default::default()
+    };
+    let reply = client.chat(&request).await?.content.unwrap_or_default();
+    if repair::is_valid_arguments(&reply) {
+        return Ok(reply);
+    }
+    heal(&reply).ok_or_else(|| "resent arguments are still invalid".to_string())
+}
+
 // ── Tool execution ────────────────────────────────────────────────
 
+/// Cached result for a cacheable call: the in-run cache first, then the
+/// disk cache (promoting disk hits into the in-run cache).
+fn lookup_cached(
+    modules: &mut ModuleState,
+    name: &str,
+    arguments: &str,
+    round: u32,
+) -> Option<String> {
+    if let Some(ref mut cache) = modules.tool_cache
+        && let Some(result) = cache.get(name, arguments)
+    {
+        return Some(result.to_string());
+    }
+    let result = modules.disk_cache.as_mut()?.get(name, arguments)?;
+    if let Some(ref mut cache) = modules.tool_cache {
+        cache.put(name, arguments, result.clone(), round + 1);
+    }
+    Some(result)
+}
+
+/// Append an [`AuditOutcome::Started`] entry for each audited call about to
+/// run, before any of them is dispatched.
+fn record_audit_started(
+    audit: &AuditLog,
+    tools: &ToolSet,
+    round: u32,
+    to_execute: &[&crate::ToolCall],
+    approvals: &HashMap<String, Approval>,
+) {
+    for call in to_execute {
+        let Some(kind) = AuditKind::of(tools, &call.function.name, &call.function.arguments) else {
+            continue;
+        };
+        let entry = AuditEntry {
+            timestamp_ms: crate::platform::epoch_millis(),
+            trace_id: audit.trace_id().to_string(),
+            round,
+            call_id: call.id.clone(),
+            tool: call.function.name.clone(),
+            kind,
+            arguments: call.function.arguments.clone(),
+            approval: approvals
+                .get(&call.id)
+                .cloned()
+                .unwrap_or(Approval::NotRequired),
+            outcome: AuditOutcome::Started,
+            exit_code: None,
+        };
+        if let Err(e) = audit.append(&entry) {
+            warn!("Failed to write audit entry: {e}");
+        }
+    }
+}
+
+/// Append an [`AuditEntry`] with the outcome of each of the round's audited
+/// calls.
+#[allow(clippy::too_many_arguments)]
+fn record_audit<'a>(
+    audit: &AuditLog,
+    tools: &ToolSet,
+    round: u32,
+    tool_calls: &[crate::ToolCall],
+    approvals: &HashMap<String, Approval>,
+    not_run: &HashSet<&str>,
+    rolled_back: &HashSet<String>,
+    results: impl Iterator<Item = &'a (String, String, String, String)>,
+) {
+    // Arguments as run, which differ from the call's when approval edited them.
+    let results: HashMap<&str, (&str, &str)> = results
+        .map(|(id, _, args, result)| (id.as_str(), (args.as_str(), result.as_str())))
+        .collect();
+    for call in tool_calls {
+        let Some(kind) = AuditKind::of(tools, &call.function.name, &call.function.arguments) else {
+            continue;
+        };
+        let (arguments, result) = results
+            .get(call.id.as_str())
+            .copied()
+            .unwrap_or((call.function.arguments.as_str(), ""));
+        let (mut outcome, exit_code) =
+            AuditEntry::outcome_of(!not_run.contains(call.id.as_str()), result);
+        if rolled_back.contains(&call.id) {
+            outcome = AuditOutcome::RolledBack;
+        }
+        let entry = AuditEntry {
+            timestamp_ms: crate::platform::epoch_millis(),
+            trace_id: audit.trace_id().to_string(),
+            round,
+            call_id: call.id.clone(),
+            tool: call.function.name.clone(),
+            kind,
+            arguments: arguments.to_string(),
+            approval: approvals
+                .get(&call.id)
+                .cloned()
+                .unwrap_or(Approval::NotRequired),
+            outcome,
+            exit_code,
+        };
+        if let Err(e) = audit.append(&entry) {
+            warn!("Failed to write audit entry: {e}");
+        }
+    }
+}
+
 /// Execute tool calls for a round: approval gates, caching, dispatch, and bookkeeping.
 ///
 /// Pushes tool result messages into the [`ContextLayout`] and records eviction
 /// metadata using the layout's [`next_message_index()`](ContextLayout::next_message_index).
+/// Returns `(call_id, name, arguments, result)` for every call, with results
+/// as produced by the tools (before context budget advisories).
 #[allow(clippy::too_many_arguments)]
 pub(crate) async fn execute_and_record_tool_calls(
     config: &HarnessConfig,
@@ -116,17 +543,63 @@ pub(crate) async fn execute_and_record_tool_calls(
     modules: &mut ModuleState,
     tool_calls: &[crate::ToolCall],
     round: u32,
-) {
+) -> Vec<(String, String, String, String)> {
     let mut tool_results: Vec<(String, String, String, String)> = Vec::new();
     let mut denied_tools: Vec<(String, String, String)> = Vec::new();
 
     // Check approval gates (must be sequential — we need handler responses).
-    let mut approved_calls: Vec<&crate::ToolCall> = Vec::new();
+    let mut approved_calls: Vec<crate::ToolCall> = Vec::new();
+    let mut approvals: HashMap<String, Approval> = HashMap::new();
+    // Original arguments of calls the responder edited, by call id.
+    let mut edited: HashMap<String, String> = HashMap::new();
     for call in tool_calls {
-        if config.approval_required_tools.contains(&call.function.name) {
+        if let Some(ref mut detector) = modules.loop_detector
+            && detector.should_block(&call.function.name, &call.function.arguments)
+        {
+            if let Some(detected) = detector.record_blocked() {
+                event_handler.on_event(&HarnessEvent::ToolLoopDetected {
+                    name: &detected.name,
+                    arguments: &detected.arguments,
+                    repeats: detected.repeats,
+                });
+            }
+            denied_tools.push((
+                call.id.clone(),
+                call.function.name.clone(),
+                loop_detect::blocked_result(&call.function.name),
+            ));
+            approvals.insert(call.id.clone(), Approval::LoopBlocked);
+            continue;
+        }
+        let is_mutation = tools.is_mutation_call(&call.function.name, &call.function.arguments);
+        if let Some(exceeded) = modules
+            .category_budgets
+            .as_ref()
+            .and_then(|b| b.check(&call.function.name, is_mutation))
+        {
+            event_handler.on_event(&HarnessEvent::ToolBudgetExceeded {
+                name: &call.function.name,
+                category: &exceeded.category,
+                max_calls: exceeded.max_calls,
+            });
+            denied_tools.push((
+                call.id.clone(),
+                call.function.name.clone(),
+                exceeded.tool_result(&call.function.name),
+            ));
+            approvals.insert(call.id.clone(), Approval::BudgetExceeded);
+            continue;
+        }
+        let mut call = call.clone();
+        let approval = if !config.approval_required_tools.contains(&call.function.name) {
+            Approval::NotRequired
+        } else if config.approval_store.allows(&call.function.name) {
+            Approval::AlwaysAllowed
+        } else {
             let response = event_handler.on_event(&HarnessEvent::ApprovalRequired {
                 name: &call.function.name,
                 arguments: &call.function.arguments,
+                call_id: &call.id,
             });
             match response {
                 Some(EventResponse::Deny(reason)) => {
@@ -138,6 +611,7 @@ pub(crate) async fn execute_and_record_tool_calls(
                             call.function.name, reason
                         ),
                     ));
+                    approvals.insert(call.id.clone(), Approval::Denied { reason });
                     continue;
                 }
                 Some(EventResponse::InjectMessage(msg)) => {
@@ -150,19 +624,53 @@ pub(crate) async fn execute_and_record_tool_calls(
                             call.function.name
                         ),
                     ));
+                    approvals.insert(call.id.clone(), Approval::Redirected);
                     continue;
                 }
-                _ => {}
+                Some(EventResponse::Approve) => Approval::Approved,
+                Some(EventResponse::ApproveAlways(scope)) => {
+                    if let Err(e) = config.approval_store.allow(&call.function.name, scope) {
+                        warn!(
+                            "Failed to remember approval for {}: {e}",
+                            call.function.name
+                        );
+                    }
+                    Approval::Approved
+                }
+                Some(EventResponse::ApproveEdited(arguments)) => {
+                    if serde_json::from_str::<serde_json::Value>(&arguments).is_err() {
+                        let reason = "the edited arguments are not valid JSON".to_string();
+                        denied_tools.push((
+                            call.id.clone(),
+                            call.function.name.clone(),
+                            format!("Tool '{}' was not run: {reason}.", call.function.name),
+                        ));
+                        approvals.insert(call.id.clone(), Approval::Denied { reason });
+                        continue;
+                    }
+                    let original = std::mem::replace(&mut call.function.arguments, arguments);
+                    edited.insert(call.id.clone(), original.clone());
+                    Approval::Edited {
+                        original_arguments: original,
+                    }
+                }
+                None => Approval::Unanswered,
             }
+        };
+        approvals.insert(call.id.clone(), approval);
+        if let Some(ref mut budgets) = modules.category_budgets {
+            budgets.charge(&call.function.name, is_mutation);
         }
         approved_calls.push(call);
     }
 
     // Emit executing events for approved tool calls.
     for call in &approved_calls {
+        let summary = tools.summarize_call(&call.function.name, &call.function.arguments);
         event_handler.on_event(&HarnessEvent::ToolExecuting {
             name: &call.function.name,
             arguments: &call.function.arguments,
+            summary: &summary,
         });
     }
 
@@ -171,9 +679,13 @@ pub(crate) async fn execute_and_record_tool_calls(
     let mut to_execute: Vec<&crate::ToolCall> = Vec::new();
 
     for call in &approved_calls {
-        if let Some(ref mut cache) = modules.tool_cache
-            && tools.is_cacheable(&call.function.name)
-            && let Some(cached_result) = cache.get(&call.function.name, &call.function.arguments)
+        if tools.is_cacheable_call(&call.function.name, &call.function.arguments)
+            && let Some(cached_result) = lookup_cached(
+                modules,
+                &call.function.name,
+                &call.function.arguments,
+                round,
+            )
         {
             event_handler.on_event(&HarnessEvent::ToolCacheHit {
                 name: &call.function.name,
@@ -183,24 +695,125 @@ pub(crate) async fn execute_and_record_tool_calls(
                 call.id.clone(),
                 call.function.name.clone(),
                 call.function.arguments.clone(),
-                cached_result.to_string(),
+                cached_result,
             ));
             continue;
         }
         to_execute.push(call);
     }
 
+    // Snapshot files before their first mutation for the change report.
+    if let Some(ref mut store) = modules.snapshot_store {
+        for call in &to_execute {
+            if tools.is_mutation_call(&call.function.name, &call.function.arguments)
+                && let Some(path) = path_argument(&call.function.arguments)
+            {
+                store.record(config.change_report.workdir.join(path));
+            }
+        }
+    }
+
+    // Content of each edited file before this round, so edits can be
+    // diffed against the real file (see `FileEdit::from_tool_call_on`).
+    let mut edit_bases: HashMap<String, String> = HashMap::new();
+    for call in &to_execute {
+        if FileEdit::is_file_edit_tool(&call.function.name)
+            && let Some(path) = path_argument(&call.function.arguments)
+            && !edit_bases.contains_key(&path)
+        {
+            let full = config.change_report.workdir.join(&path);
+            match std::fs::read_to_string(&full) {
+                Ok(content) => {
+                    edit_bases.insert(path, content);
+                }
+                Err(_) if !full.exists() => {
+                    edit_bases.insert(path, String::new());
+                }
+                Err(_) => {}
+            }
+        }
+    }
+
+    if let Some(ref audit) = modules.audit_log {
+        record_audit_started(audit, tools, round, &to_execute, &approvals);
+    }
+
     // Execute remaining tool calls with dependency-aware ordering.
-    let executed = dispatch_tool_execution(config, tools, &to_execute).await;
+    let runs = CallRuns::default();
+    let (executed, skipped, rolled_back) =
+        if config.partial_failure == PartialFailurePolicy::Continue {
+            let executed = dispatch_tool_execution(config, tools, &to_execute, &runs).await;
+            (executed, Vec::new(), HashSet::new())
+        } else {
+            execute_with_failure_policy(
+                &config.partial_failure,
+                tools,
+                &to_execute,
+                &runs,
+                event_handler,
+            )
+            .await
+        };
+    record_tool_stats(
+        &mut modules.tool_stats,
+        &denied_tools,
+        &cache_hits,
+        &executed,
+        &runs.latencies.into_inner().unwrap(),
+    );
+    // Skipped calls never ran, so they are not counted as calls.
+    denied_tools.extend(skipped);
+    for (_, name, args, result) in &executed {
+        let cost = tools.call_cost_usd(name, args, result);
+        if cost > 0.0 {
+            modules.tool_costs.push((name.clone(), cost));
+        }
+    }
 
     // Store executed results in cache and handle invalidation.
     for (_call_id, name, args, result) in &executed {
-        if let Some(ref mut cache) = modules.tool_cache {
-            if tools.is_mutation_tool(name) {
-                cache.invalidate_all();
-            } else if tools.is_cacheable(name) {
+        if tools.is_mutation_call(name, args) {
+            if let Some(ref mut cache) = modules.tool_cache {
+                cache.invalidate_for_mutation(args);
+            }
+            if let Some(ref mut disk) = modules.disk_cache {
+                disk.invalidate(args);
+            }
+        } else if tools.is_cacheable_call(name, args) {
+            if let Some(ref mut cache) = modules.tool_cache {
                 cache.put(name, args, result.clone(), round + 1);
             }
+            if let Some(ref mut disk) = modules.disk_cache
+                && !result.starts_with("Error")
+            {
+                disk.put(name, args, result);
+            }
+        }
+    }
+
+    // Track repeated identical calls.
+    if let Some(ref mut detector) = modules.loop_detector {
+        for (_call_id, name, arguments, result) in cache_hits.iter().chain(&executed) {
+            if let Some(detected) = detector.record(name, arguments, result) {
+                event_handler.on_event(&HarnessEvent::ToolLoopDetected {
+                    name: &detected.name,
+                    arguments: &detected.arguments,
+                    repeats: detected.repeats,
+                });
+            }
+        }
+    }
+
+    // Escalate tools that keep failing the same way.
+    if let Some(ref mut escalation) = modules.failure_escalation {
+        for (_call_id, name, _arguments, result) in cache_hits.iter().chain(&executed) {
+            if let Some(escalated) = escalation.record(tools, name, result) {
+                event_handler.on_event(&HarnessEvent::ToolFailureEscalated {
+                    name: &escalated.name,
+                    failures: escalated.failures,
+                    level: escalated.level,
+                });
+            }
         }
     }
 
@@ -209,7 +822,23 @@ pub(crate) async fn execute_and_record_tool_calls(
         cache.evict_older_than(round + 1, config.cache.max_age_rounds);
     }
 
+    if let Some(ref audit) = modules.audit_log {
+        let not_run: HashSet<&str> = denied_tools.iter().map(|(id, ..)| id.as_str()).collect();
+        let results = cache_hits.iter().chain(&executed);
+        record_audit(
+            audit,
+            tools,
+            round,
+            tool_calls,
+            &approvals,
+            &not_run,
+            &rolled_back,
+            results,
+        );
+    }
+
     // Combine results: denied tools + cache hits + executed.
+    let mut limit_trips = runs.limit_trips.into_inner().unwrap();
     for (call_id, name, reason) in denied_tools {
         tool_results.push((call_id, name, String::new(), reason));
     }
@@ -217,9 +846,22 @@ pub(crate) async fn execute_and_record_tool_calls(
     tool_results.extend(executed);
 
     let total_results = tool_results.len();
+    let raw_results = tool_results.clone();
 
     // Append results to layout with context budget advisories.
     for (i, (call_id, name, arguments, mut result)) in tool_results.into_iter().enumerate() {
+        let limit_trip = limit_trips.remove(&call_id);
+        if name == crate::tools::names::PIN && !result.starts_with("Error") {
+            result = apply_pin_call(&mut modules.tool_metas, &arguments, result);
+        }
+        // The transcript still has the model's arguments; say what ran. After
+        // the result, so error checks still see its `Error` prefix.
+        if let Some(original) = edited.get(&call_id) {
+            result.push_str(&format!(
+                "\n\n[The user edited the arguments before approving. Original: {original}. \
+                 Ran with: {arguments}]"
+            ));
+        }
         if let Some(budget) = context_budget {
             let current_messages = layout.to_messages();
             if let Some(advisory) = budget.advisory(&current_messages) {
@@ -233,6 +875,34 @@ pub(crate) async fn execute_and_record_tool_calls(
             call_id: &call_id,
             result: &result,
         });
+        // Replayed on the file's content, which then advances for later
+        // edits of the same file this round.
+        let edit = path_argument(&arguments)
+            .and_then(|path| edit_bases.get_mut(&path))
+            .and_then(|base| {
+                let (edit, after) = FileEdit::from_tool_call_on(&name, &arguments, &result, base)?;
+                *base = after;
+                Some(edit)
+            })
+            .or_else(|| FileEdit::from_tool_call(&name, &arguments, &result));
+        // A rolled-back edit changed nothing.
+        if let Some(edit) = edit
+            && !rolled_back.contains(&call_id)
+        {
+            event_handler.on_event(&HarnessEvent::FileEdited {
+                name: &name,
+                path: &edit.path,
+                diff: &edit.diff,
+                stats: edit.stats,
+            });
+        }
+        if let Some(trip) = limit_trip {
+            event_handler.on_event(&HarnessEvent::ResourceLimitExceeded {
+                name: &name,
+                limit: trip.kind,
+                detail: &trip.detail,
+            });
+        }
 
         // Track the to_messages() index before pushing, for eviction.
         let message_index = layout.next_message_index();
@@ -250,6 +920,7 @@ pub(crate) async fn execute_and_record_tool_calls(
                     &Message::tool_result(&call_id, result.clone()),
                     config.eviction.config.chars_per_token,
                 ),
+                ..Default::default()
             });
         }
 
@@ -258,9 +929,9 @@ pub(crate) async fn execute_and_record_tool_calls(
             tracker.record_tool_access(&name, &arguments, round as usize);
         }
 
-        // Update tool filter usage counts.
+        // Update tool filter usage counts and profile outcomes.
         if let Some(filter) = tool_filter {
-            filter.record_usage(&name);
+            filter.record_outcome(&name, !result.starts_with("Error"));
         }
 
         // Progressive tool loading: inject extended description on first use.
@@ -291,10 +962,135 @@ pub(crate) async fn execute_and_record_tool_calls(
                 round,
                 event_handler,
                 &modules.file_tracker,
+                &modules.history_archive,
             )
             .await;
         }
     }
+
+    // Remember read-only results as predictions for speculative prefetch.
+    if let Some(ref mut speculation) = modules.speculation {
+        for (_call_id, name, arguments, result) in &raw_results {
+            speculation.record(name, arguments, result);
+        }
+    }
+
+    raw_results
+}
+
+/// Execute calls one at a time until one fails (an `Error` result or a
+/// non-zero exit code), then apply `policy`: skip
+/// the remaining calls and, for [`PartialFailurePolicy::RollBack`], restore
+/// the files the round's mutation calls changed. Returns the executed results,
+/// the skipped calls as `(call_id, name, result)`, and the ids of the calls
+/// whose changes were rolled back.
+async fn execute_with_failure_policy(
+    policy: &PartialFailurePolicy,
+    tools: &ToolSet,
+    to_execute: &[&crate::ToolCall],
+    runs: &CallRuns,
+    event_handler: &dyn EventHandler,
+) -> (
+    Vec<(String, String, String, String)>,
+    Vec<(String, String, String)>,
+    HashSet<String>,
+) {
+    let mut results: Vec<(String, String, String, String)> = Vec::new();
+    // Snapshots taken before each mutation, with the index of its result.
+    let mut snapshots: Vec<(usize, FileSnapshot)> = Vec::new();
+    let mut remaining = to_execute.iter();
+    let mut failed = None;
+
+    for call in remaining.by_ref() {
+        let name = &call.function.name;
+        let arguments = &call.function.arguments;
+        if let PartialFailurePolicy::RollBack { workdir } = policy
+            && tools.is_mutation_call(name, arguments)
+            && let Some(path) = path_argument(arguments)
+        {
+            snapshots.push((results.len(), FileSnapshot::capture(workdir.join(path))));
+        }
+        let result = execute_timed(tools, &call.id, name, arguments, runs).await;
+        // A command that exits non-zero fails too, as in the audit log.
+        let is_error = matches!(
+            AuditEntry::outcome_of(true, &result).0,
+            AuditOutcome::Failed
+        );
+        results.push((call.id.clone(), name.clone(), arguments.clone(), result));
+        if is_error {
+            failed = Some(name.clone());
+            break;
+        }
+    }
+
+    let Some(failed) = failed else {
+        return (results, Vec::new(), HashSet::new());
+    };
+    let skipped: Vec<(String, String, String)> = remaining
+        .map(|call| {
+            (
+                call.id.clone(),
+                call.function.name.clone(),
+                format!(
+                    "Skipped: tool '{failed}' failed earlier in this round, so this call was not run."
+                ),
+            )
+        })
+        .collect();
+
+    // Restore newest first so a path mutated twice ends at its pre-round state.
+    let mut rolled_back = HashSet::new();
+    for (index, snapshot) in snapshots.iter().rev() {
+        let note = match snapshot.restore() {
+            Ok(()) => {
+                // The failed call itself changed nothing to undo.
+                if *index + 1 < results.len() {
+                    rolled_back.insert(results[*index].0.clone());
+                }
+                format!(
+                    "[Rolled back: {} was restored because tool '{failed}' failed in this round.]",
+                    snapshot.path().display()
+                )
+            }
+            Err(e) => {
+                warn!("{e}");
+                format!("[Rollback failed: {e}]")
+            }
+        };
+        let result = &mut results[*index].3;
+        result.push_str("\n\n");
+        result.push_str(&note);
+    }
+
+    event_handler.on_event(&HarnessEvent::ToolRoundAborted {
+        failed: &failed,
+        skipped: skipped.len(),
+        rolled_back: rolled_back.len(),
+    });
+    (results, skipped, rolled_back)
+}
+
+/// Apply a `pin` call to the eviction metadata, replacing the acknowledgement
+/// with an error if nothing matched.
+fn apply_pin_call(tool_metas: &mut [ToolResultMeta], arguments: &str, result: String) -> String {
+    let Ok(args) = serde_json::from_str::<crate::tools::core::PinArgs>(arguments) else {
+        return result;
+    };
+    let pinned = eviction::apply_pin(
+        tool_metas,
+        &args.tool,
+        args.target.as_deref(),
+        args.importance(),
+        args.note.as_deref(),
+    );
+    if pinned == 0 {
+        return format!(
+            "Error: no earlier '{}' result matches{}.",
+            args.tool,
+            args.target.map_or_else(String::new, |t| format!(" '{t}'"))
+        );
+    }
+    result
 }
 
 /// Dispatch tool execution using the appropriate strategy (sequential, DAG, or parallel).
@@ -302,13 +1098,19 @@ async fn dispatch_tool_execution(
     config: &HarnessConfig,
     tools: &ToolSet,
     to_execute: &[&crate::ToolCall],
+    runs: &CallRuns,
 ) -> Vec<(String, String, String, String)> {
     if config.sequential_tools {
         let mut results = Vec::new();
         for call in to_execute {
-            let result = tools
-                .execute(&call.function.name, &call.function.arguments)
-                .await;
+            let result = execute_timed(
+                tools,
+                &call.id,
+                &call.function.name,
+                &call.function.arguments,
+                runs,
+            )
+            .await;
             results.push((
                 call.id.clone(),
                 call.function.name.clone(),
@@ -334,7 +1136,9 @@ async fn dispatch_tool_execution(
                 for wave in waves {
                     if wave.len() == 1 {
                         let call = &wave[0];
-                        let result = tools.execute(&call.name, &call.arguments).await;
+                        let result =
+                            execute_timed(tools, &call.call_id, &call.name, &call.arguments, runs)
+                                .await;
                         results.push((
                             call.call_id.clone(),
                             call.name.clone(),
@@ -349,7 +1153,8 @@ async fn dispatch_tool_execution(
                                 let args = call.arguments.clone();
                                 let call_id = call.call_id.clone();
                                 async move {
-                                    let result = tools.execute(&name, &args).await;
+                                    let result =
+                                        execute_timed(tools, &call_id, &name, &args, runs).await;
                                     (call_id, name, args, result)
                                 }
                             })
@@ -363,9 +1168,14 @@ async fn dispatch_tool_execution(
                 warn!("Dependency cycle in tool calls: {e}. Falling back to sequential.");
                 let mut results = Vec::new();
                 for call in to_execute {
-                    let result = tools
-                        .execute(&call.function.name, &call.function.arguments)
-                        .await;
+                    let result = execute_timed(
+                        tools,
+                        &call.id,
+                        &call.function.name,
+                        &call.function.arguments,
+                        runs,
+                    )
+                    .await;
                     results.push((
                         call.id.clone(),
                         call.function.name.clone(),
@@ -385,7 +1195,7 @@ async fn dispatch_tool_execution(
                 let args = call.function.arguments.clone();
                 let call_id = call.id.clone();
                 async move {
-                    let result = tools.execute(&name, &args).await;
+                    let result = execute_timed(tools, &call_id, &name, &args, runs).await;
                     (call_id, name, args, result)
                 }
             })
@@ -395,9 +1205,14 @@ async fn dispatch_tool_execution(
         // Single call.
         let mut results = Vec::new();
         for call in to_execute {
-            let result = tools
-                .execute(&call.function.name, &call.function.arguments)
-                .await;
+            let result = execute_timed(
+                tools,
+                &call.id,
+                &call.function.name,
+                &call.function.arguments,
+                runs,
+            )
+            .await;
             results.push((
                 call.id.clone(),
                 call.function.name.clone(),
@@ -409,25 +1224,93 @@ async fn dispatch_tool_execution(
     }
 }
 
+/// Execute one tool call, recording its latency under `call_id`.
+async fn execute_timed(
+    tools: &ToolSet,
+    call_id: &str,
+    name: &str,
+    arguments: &str,
+    runs: &CallRuns,
+) -> String {
+    let start = Instant::now();
+    let (result, trip) = capture_trip(tools.execute(name, arguments)).await;
+    runs.latencies
+        .lock()
+        .unwrap()
+        .insert(call_id.to_string(), start.elapsed());
+    if let Some(trip) = trip {
+        runs.limit_trips
+            .lock()
+            .unwrap()
+            .insert(call_id.to_string(), trip);
+    }
+    result
+}
+
+/// What executing a round's calls left besides their results, by call id.
+#[derive(Default)]
+struct CallRuns {
+    latencies: Mutex<HashMap<String, Duration>>,
+    /// Limits the calls [reported](LimitTrip::report) tripping.
+    limit_trips: Mutex<HashMap<String, LimitTrip>>,
+}
+
+/// Add one round's calls to the per-tool run statistics.
+fn record_tool_stats(
+    stats: &mut BTreeMap<String, ToolStats>,
+    denied: &[(String, String, String)],
+    cache_hits: &[(String, String, String, String)],
+    executed: &[(String, String, String, String)],
+    latencies: &HashMap<String, Duration>,
+) {
+    for (_, name, _) in denied {
+        let entry = stats.entry(name.clone()).or_default();
+        entry.calls += 1;
+        entry.errors += 1;
+    }
+    for (_, name, _, result) in cache_hits {
+        let entry = stats.entry(name.clone()).or_default();
+        entry.calls += 1;
+        entry.cache_hits += 1;
+        if result.starts_with("Error") {
+            entry.errors += 1;
+        }
+    }
+    for (call_id, name, _, result) in executed {
+        let entry = stats.entry(name.clone()).or_default();
+        entry.calls += 1;
+        if result.starts_with("Error") {
+            entry.errors += 1;
+        }
+        if let Some(elapsed) = latencies.get(call_id) {
+            entry.total_latency += *elapsed;
+        }
+    }
+}
+
 // ── Checkpointing ─────────────────────────────────────────────────
 
 /// Save a checkpoint for the current round via the [`SessionManager`].
+#[cfg(feature = "checkpoint")]
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn save_round_checkpoint(
     session_manager: &Option<SessionManager>,
     trace_id: &str,
+    agent: Option<&str>,
     messages: &[Message],
     text_output: &[String],
     round: u32,
     total_prompt_tokens: u64,
     total_completion_tokens: u64,
     estimated_cost_usd: f64,
+    structured_state: Option<&StructuredState>,
     event_handler: &dyn EventHandler,
 ) {
     let Some(mgr) = session_manager else {
         return;
     };
     let checkpoint = Checkpoint {
+        version: crate::agent::migration::CHECKPOINT_VERSION,
         trace_id: trace_id.to_string(),
         messages: messages.to_vec(),
         text_output: text_output.to_vec(),
@@ -435,13 +1318,9 @@ pub(crate) fn save_round_checkpoint(
         total_prompt_tokens,
         total_completion_tokens,
         estimated_cost_usd,
-        timestamp: format!(
-            "epoch:{}",
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs()
-        ),
+        timestamp: format!("epoch:{}", crate::platform::epoch_secs()),
+        agent: agent.map(str::to_string),
+        structured_state: structured_state.cloned(),
     };
     match mgr.save_checkpoint(&checkpoint) {
         Ok(path) => {
@@ -483,7 +1362,7 @@ where
                         attempt + 1,
                         config.max_retries,
                     );
-                    tokio::time::sleep(delay).await;
+                    crate::platform::sleep(delay).await;
                     attempt += 1;
                 } else {
                     return Err(e);
@@ -584,6 +1463,389 @@ pub(crate) fn apply_cache_breakpoints(messages: &mut [Message]) {
 mod tests {
     use super::*;
 
+    #[test]
+    fn tool_stats_count_calls_errors_hits_and_latency() {
+        let call = |id: &str, name: &str, result: &str| {
+            (
+                id.to_string(),
+                name.to_string(),
+                "{}".to_string(),
+                result.to_string(),
+            )
+        };
+        let mut stats = BTreeMap::new();
+        let latencies = HashMap::from([
+            ("c1".to_string(), Duration::from_millis(5)),
+            ("c2".to_string(), Duration::from_millis(7)),
+        ]);
+        record_tool_stats(
+            &mut stats,
+            &[("c0".into(), "shell".into(), "denied".into())],
+            &[call("c3", "grep", "hit")],
+            &[
+                call("c1", "grep", "ok"),
+                call("c2", "shell", "Error: failed"),
+            ],
+            &latencies,
+        );
+
+        let grep = stats["grep"];
+        assert_eq!((grep.calls, grep.errors, grep.cache_hits), (2, 0, 1));
+        assert_eq!(grep.total_latency, Duration::from_millis(5));
+        let shell = stats["shell"];
+        assert_eq!((shell.calls, shell.errors, shell.cache_hits), (2, 2, 0));
+        assert_eq!(shell.total_latency, Duration::from_millis(7));
+    }
+
+    #[test]
+    fn audit_records_consequential_calls_with_approvals() {
+        let dir = tempfile::tempdir().unwrap();
+        let tools = ToolSet::new().with_common_tools(dir.path().to_str().unwrap());
+        let call = |id: &str, name: &str| crate::api::interop::tool_call(id, name, "{}".into());
+        let calls = [
+            call("c1", "shell"),
+            call("c2", "read_file"),
+            call("c3", "write_file"),
+            call("c4", "edit_file"),
+        ];
+        let approvals = HashMap::from([
+            ("c1".to_string(), Approval::Approved),
+            ("c2".to_string(), Approval::NotRequired),
+            (
+                "c3".to_string(),
+                Approval::Denied {
+                    reason: "read-only review".into(),
+                },
+            ),
+        ]);
+        let not_run = HashSet::from(["c3"]);
+        let shell_result = crate::tools::output::ToolOutput::new(1, "", "boom").render();
+        let results = [
+            ("c1".into(), "shell".into(), "{}".into(), shell_result),
+            ("c2".into(), "read_file".into(), "{}".into(), "L1: x".into()),
+            (
+                "c4".into(),
+                "edit_file".into(),
+                "{}".into(),
+                "Edited a.rs".into(),
+            ),
+        ];
+        let rolled_back = HashSet::from(["c4".to_string()]);
+
+        let audit = AuditLog::new(dir.path(), "tr-1");
+        record_audit(
+            &audit,
+            &tools,
+            3,
+            &calls,
+            &approvals,
+            &not_run,
+            &rolled_back,
+            results.iter(),
+        );
+
+        let entries = AuditLog::read(audit.path()).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].kind, AuditKind::Command);
+        assert_eq!(entries[0].approval, Approval::Approved);
+        assert_eq!(entries[0].exit_code, Some(1));
+        assert_eq!(entries[0].round, 3);
+        assert_eq!(entries[1].tool, "write_file");
+        assert_eq!(entries[1].kind, AuditKind::Mutation);
+        assert_eq!(
+            entries[1].outcome,
+            crate::agent::audit::AuditOutcome::NotRun
+        );
+        assert!(matches!(entries[1].approval, Approval::Denied { .. }));
+        assert_eq!(entries[2].outcome, AuditOutcome::RolledBack);
+    }
+
+    #[test]
+    fn audit_records_calls_before_they_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let tools = ToolSet::new().with_common_tools(dir.path().to_str().unwrap());
+        let calls = [
+            crate::api::interop::tool_call("c1", "shell", r#"{"command":"make"}"#.to_string()),
+            crate::api::interop::tool_call("c2", "read_file", "{}".to_string()),
+        ];
+        let to_execute: Vec<&crate::ToolCall> = calls.iter().collect();
+        let approvals = HashMap::from([("c1".to_string(), Approval::Approved)]);
+
+        let audit = AuditLog::new(dir.path(), "tr-1");
+        record_audit_started(&audit, &tools, 2, &to_execute, &approvals);
+        let entries = AuditLog::read(audit.path()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].call_id, "c1");
+        assert_eq!(entries[0].outcome, AuditOutcome::Started);
+        assert_eq!(entries[0].approval, Approval::Approved);
+
+        let results = [(
+            "c1".to_string(),
+            "shell".to_string(),
+            r#"{"command":"make"}"#.to_string(),
+            "done".to_string(),
+        )];
+        record_audit(
+            &audit,
+            &tools,
+            2,
+            &calls,
+            &approvals,
+            &HashSet::new(),
+            &HashSet::new(),
+            results.iter(),
+        );
+        let entries = AuditLog::read(audit.path()).unwrap();
+        let outcomes: Vec<_> = entries
+            .iter()
+            .map(|e| (e.call_id.as_str(), e.outcome))
+            .collect();
+        assert_eq!(
+            outcomes,
+            [("c1", AuditOutcome::Started), ("c1", AuditOutcome::Ok)]
+        );
+    }
+
+    #[test]
+    fn audit_records_edited_arguments_as_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let tools = ToolSet::new().with_common_tools(dir.path().to_str().unwrap());
+        let calls = [crate::api::interop::tool_call(
+            "c1",
+            "shell",
+            r#"{"command":"rm -rf build"}"#.to_string(),
+        )];
+        let approvals = HashMap::from([(
+            "c1".to_string(),
+            Approval::Edited {
+                original_arguments: r#"{"command":"rm -rf build"}"#.into(),
+            },
+        )]);
+        let result = crate::tools::output::ToolOutput::new(0, "", "").render();
+        let results = [(
+            "c1".into(),
+            "shell".into(),
+            r#"{"command":"cargo clean"}"#.into(),
+            result,
+        )];
+
+        let audit = AuditLog::new(dir.path(), "tr-1");
+        record_audit(
+            &audit,
+            &tools,
+            1,
+            &calls,
+            &approvals,
+            &HashSet::new(),
+            &HashSet::new(),
+            results.iter(),
+        );
+
+        let entries = AuditLog::read(audit.path()).unwrap();
+        assert_eq!(entries[0].arguments, r#"{"command":"cargo clean"}"#);
+        assert!(matches!(
+            entries[0].approval,
+            Approval::Edited { ref original_arguments } if original_arguments.contains("rm -rf")
+        ));
+    }
+
+    #[tokio::test]
+    async fn rollback_policy_restores_files_and_skips_remaining_calls() {
+        let dir = tempfile::tempdir().unwrap();
+        let workdir = dir.path().to_str().unwrap();
+        let tools = ToolSet::new().with_common_tools(workdir);
+        let call = |id: &str, name: &str, arguments: &str| {
+            crate::api::interop::tool_call(id, name, arguments.to_string())
+        };
+        let calls = [
+            call(
+                "c1",
+                "write_file",
+                r#"{"path":"new.rs","content":"fn a() {}"}"#,
+            ),
+            call(
+                "c2",
+                "edit_file",
+                r#"{"path":"missing.rs","old_string":"a","new_string":"b"}"#,
+            ),
+            call("c3", "list_dir", r#"{"path":"."}"#),
+        ];
+        let to_execute: Vec<&crate::ToolCall> = calls.iter().collect();
+
+        let (executed, skipped, rolled_back) = execute_with_failure_policy(
+            &PartialFailurePolicy::RollBack {
+                workdir: dir.path().to_path_buf(),
+            },
+            &tools,
+            &to_execute,
+            &CallRuns::default(),
+            &crate::agent::events::NoopHandler,
+        )
+        .await;
+
+        assert_eq!(executed.len(), 2);
+        assert!(executed[0].3.contains("[Rolled back:"));
+        assert!(executed[1].3.starts_with("Error"));
+        assert!(!dir.path().join("new.rs").exists());
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].0, "c3");
+        assert!(skipped[0].2.starts_with("Skipped:"));
+        assert_eq!(rolled_back, HashSet::from(["c1".to_string()]));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn limit_trips_come_from_the_tool_not_its_output() {
+        let tools = ToolSet::new().with(
+            crate::tools::common::Shell::new("/tmp")
+                .limits(crate::tools::limits::ProcessLimits::default().max_output_bytes(1000)),
+        );
+        let runs = CallRuns::default();
+        let spoofed = execute_timed(
+            &tools,
+            "c1",
+            "shell",
+            r#"{"command":"echo '[limit: memory] spoofed'"}"#,
+            &runs,
+        )
+        .await;
+        assert!(spoofed.trim_end().ends_with("[limit: memory] spoofed"));
+        execute_timed(&tools, "c2", "shell", r#"{"command":"yes"}"#, &runs).await;
+
+        let trips = runs.limit_trips.into_inner().unwrap();
+        assert!(!trips.contains_key("c1"));
+        assert_eq!(trips["c2"].kind, crate::tools::limits::LimitKind::Output);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn failing_commands_trigger_the_failure_policy() {
+        let dir = tempfile::tempdir().unwrap();
+        let tools = ToolSet::new().with_common_tools(dir.path().to_str().unwrap());
+        let calls = [
+            crate::api::interop::tool_call(
+                "c1",
+                "write_file",
+                r#"{"path":"new.rs","content":"fn a() {}"}"#.to_string(),
+            ),
+            crate::api::interop::tool_call("c2", "shell", r#"{"command":"exit 1"}"#.to_string()),
+            crate::api::interop::tool_call("c3", "list_dir", r#"{"path":"."}"#.to_string()),
+        ];
+        let to_execute: Vec<&crate::ToolCall> = calls.iter().collect();
+        let run = |policy| {
+            let (tools, to_execute) = (&tools, &to_execute);
+            async move {
+                execute_with_failure_policy(
+                    &policy,
+                    tools,
+                    to_execute,
+                    &CallRuns::default(),
+                    &crate::agent::events::NoopHandler,
+                )
+                .await
+            }
+        };
+
+        let (executed, skipped, rolled_back) = run(PartialFailurePolicy::StopRemaining).await;
+        assert_eq!(executed.len(), 2);
+        assert!(executed[1].3.starts_with("[exit: 1]"), "{}", executed[1].3);
+        assert_eq!(skipped[0].0, "c3");
+        assert!(rolled_back.is_empty());
+        assert!(dir.path().join("new.rs").exists());
+
+        std::fs::remove_file(dir.path().join("new.rs")).unwrap();
+        let (_, skipped, rolled_back) = run(PartialFailurePolicy::RollBack {
+            workdir: dir.path().to_path_buf(),
+        })
+        .await;
+        assert_eq!(skipped[0].0, "c3");
+        assert_eq!(rolled_back, HashSet::from(["c1".to_string()]));
+        assert!(!dir.path().join("new.rs").exists());
+    }
+
+    #[test]
+    fn round_requests_use_phase_generation() {
+        use crate::agent::config::{GenerationParams, GenerationPhase};
+
+        let config = HarnessConfig::new("test/model", "")
+            .with_max_tokens(4096)
+            .with_temperature(0.7)
+            .with_max_rounds(5)
+            .with_phase_generation(
+                GenerationPhase::Planning,
+                GenerationParams::default().with_temperature(0.2),
+            )
+            .with_phase_generation(
+                GenerationPhase::FinalAnswer,
+                GenerationParams::default()
+                    .with_max_tokens(512)
+                    .with_stop(["</answer>"]),
+            );
+        let phase = |round, planning, continuing| {
+            GenerationPhase::of_round(round, config.max_rounds, planning, continuing)
+        };
+        assert_eq!(phase(0, true, false), GenerationPhase::Planning);
+        assert_eq!(phase(1, false, false), GenerationPhase::Execution);
+        assert_eq!(phase(2, false, true), GenerationPhase::FinalAnswer);
+        assert_eq!(phase(4, true, false), GenerationPhase::FinalAnswer);
+
+        let request = |phase| {
+            build_round_request(
+                &config,
+                &[Message::user("hi")],
+                "test/model",
+                &config.generation_for(phase),
+                None,
+                None,
+                &None,
+                "span",
+            )
+        };
+        let planning = request(GenerationPhase::Planning);
+        assert_eq!((planning.max_tokens, planning.temperature), (4096, 0.2));
+        assert_eq!(planning.stop, None);
+        let execution = request(GenerationPhase::Execution);
+        assert_eq!((execution.max_tokens, execution.temperature), (4096, 0.7));
+        let final_answer = request(GenerationPhase::FinalAnswer);
+        assert_eq!(
+            (final_answer.max_tokens, final_answer.temperature),
+            (512, 0.7)
+        );
+        assert_eq!(final_answer.stop, Some(vec!["</answer>".to_string()]));
+    }
+
+    #[test]
+    fn round_requests_fit_the_adaptive_output_reserve() {
+        use crate::agent::config::GenerationPhase;
+        use crate::context::ContextBudget;
+
+        let config = HarnessConfig::new("test/model", "").with_max_tokens(4096);
+        let mut budget = ContextBudget::with_calibration("", None)
+            .with_output_reserve(4096)
+            .with_adaptive_output_reserve(true);
+        for _ in 0..5 {
+            budget.record_completion(100);
+        }
+        let reserve = budget.output_reserve();
+        assert!(reserve < 4096);
+
+        let generation = config.generation_for(GenerationPhase::Execution);
+        let request = |output_reserve| {
+            build_round_request(
+                &config,
+                &[Message::user("hi")],
+                "test/model",
+                &generation,
+                output_reserve,
+                None,
+                &None,
+                "span",
+            )
+        };
+        assert_eq!(request(Some(reserve)).max_tokens, reserve as u32);
+        assert_eq!(request(None).max_tokens, 4096);
+    }
+
     #[test]
     fn cache_breakpoints_system_and_last_user() {
         let mut messages = vec![
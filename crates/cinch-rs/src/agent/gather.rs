@@ -40,10 +40,13 @@ use std::any::Any;
 use std::collections::HashSet;
 use std::future::Future;
 use std::pin::Pin;
+#[cfg(feature = "ui")]
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use tokio::task::JoinSet;
+
+use crate::platform::Instant;
 use tracing::{info, warn};
 
 // ── Progress events & observer trait ────────────────────────────────
@@ -122,11 +125,13 @@ pub trait GatherObserver: Send + Sync {
 /// This is the standard observer for agents that use the harness UI
 /// layer (TUI or web).  It formats each event into a phase string and
 /// writes it via [`update_phase`](crate::ui::update_phase).
+#[cfg(feature = "ui")]
 pub struct UiGatherObserver {
     state: Arc<Mutex<crate::ui::UiState>>,
     prefix: String,
 }
 
+#[cfg(feature = "ui")]
 impl UiGatherObserver {
     pub fn new(state: Arc<Mutex<crate::ui::UiState>>, prefix: impl Into<String>) -> Self {
         Self {
@@ -136,6 +141,7 @@ impl UiGatherObserver {
     }
 }
 
+#[cfg(feature = "ui")]
 impl GatherObserver for UiGatherObserver {
     fn on_gather_event(&self, event: &GatherEvent<'_>) {
         let phase = event.phase_string(&self.prefix);
@@ -235,7 +241,7 @@ impl<S: Send + 'static> ContextGatherer<S> {
 
         // Wrap the future with a per-task timeout and type-erase the result.
         let wrapped = Box::pin(async move {
-            match tokio::time::timeout(effective_timeout, future).await {
+            match crate::platform::timeout(effective_timeout, future).await {
                 Ok(value) => Box::new(Some(value)) as Box<dyn Any + Send>,
                 Err(_) => {
                     warn!("{name_for_timeout}: timed out");
@@ -304,15 +310,15 @@ impl<S: Send + 'static> ContextGatherer<S> {
         }
 
         // Collect results as they arrive, up to the global deadline.
-        let deadline_instant = tokio::time::Instant::now() + deadline;
+        let deadline_instant = Instant::now() + deadline;
         loop {
-            let remaining = deadline_instant.saturating_duration_since(tokio::time::Instant::now());
+            let remaining = deadline_instant.saturating_duration_since(Instant::now());
             if remaining.is_zero() {
                 emit_deadline(&observer, &pending);
                 break;
             }
 
-            match tokio::time::timeout(remaining, js.join_next()).await {
+            match crate::platform::timeout(remaining, js.join_next()).await {
                 // A task finished.
                 Ok(Some(Ok((idx, result)))) => {
                     let name = &task_names[idx];
@@ -383,6 +389,7 @@ fn emit_deadline(observer: &Option<Box<dyn GatherObserver>>, pending: &HashSet<S
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::{Arc, Mutex};
 
     #[derive(Default, Debug)]
     struct TestCtx {
@@ -453,8 +460,10 @@ mod tests {
 
     #[tokio::test]
     async fn gather_handles_task_timeout() {
-        let mut ctx = TestCtx::default();
-        ctx.a = "default".into();
+        let mut ctx = TestCtx {
+            a: "default".into(),
+            ..Default::default()
+        };
 
         ContextGatherer::new(Duration::from_secs(5))
             .task(
@@ -482,8 +491,10 @@ mod tests {
 
     #[tokio::test]
     async fn gather_handles_global_deadline() {
-        let mut ctx = TestCtx::default();
-        ctx.a = "untouched".into();
+        let mut ctx = TestCtx {
+            a: "untouched".into(),
+            ..Default::default()
+        };
 
         ContextGatherer::new(Duration::from_millis(100))
             .task(
@@ -0,0 +1,549 @@
+//! Agent-to-agent handoff: one agent transfers control of a task to
+//! another.
+//!
+//! Unlike the [`orchestrator`](super::orchestrator), there is no supervisor:
+//! a [`HandoffRunner`] runs one agent at a time, and each agent gets a
+//! `handoff(target_agent, context_summary)` tool. When an agent calls it, the
+//! runner ends that agent's run after the current round and starts the
+//! target with the original task plus the summary — a compacted context in
+//! place of the previous agent's full conversation.
+//!
+//! - Agents are configured as [`Specialist`]s (name, description, prompt,
+//!   tools, model).
+//! - Every transfer emits [`HarnessEvent::Handoff`] and is recorded as a
+//!   [`HandoffRecord`] in the [`HandoffResult`].
+//! - Each agent's run writes round checkpoints tagged with its name
+//!   ([`HarnessConfig::agent_name`]). [`HandoffRunner::resume`] continues
+//!   from such a checkpoint with the right agent, and completes a handoff
+//!   that was requested in the checkpoint's last round.
+//!
+//! ```ignore
+//! let runner = HandoffRunner::new(client, config)
+//!     .with_agent(Specialist::researcher(read_only_tools))
+//!     .with_agent(Specialist::coder(all_tools));
+//! let result = runner.run("researcher", "Fix the flaky upload test").await?;
+//! for handoff in &result.handoffs {
+//!     println!("{} → {}", handoff.from, handoff.to);
+//! }
+//! ```
+
+use crate::agent::config::HarnessConfig;
+use crate::agent::events::{FnEventHandler, HarnessEvent, HarnessResult, NoopHandler};
+use crate::agent::harness::Harness;
+use crate::agent::orchestrator::{AgentEventHandler, Specialist};
+use crate::context::summarizer::StructuredState;
+use crate::tools::core::{Tool, ToolFuture};
+use crate::{Message, MessageRole, OpenRouterClient, ToolDef};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tracing::info;
+
+/// Name of the handoff tool given to every agent.
+pub const HANDOFF_TOOL: &str = "handoff";
+
+/// Default maximum number of handoffs in one run.
+pub const DEFAULT_MAX_HANDOFFS: u32 = 8;
+
+/// Result text of an accepted handoff call.
+const ACCEPTED_PREFIX: &str = "Handoff accepted";
+
+/// Arguments of the `handoff` tool.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct HandoffArgs {
+    /// Agent to transfer control to.
+    pub target_agent: String,
+    /// Compact summary for the next agent: progress so far, relevant
+    /// findings (paths, decisions, errors), and what remains to be done.
+    pub context_summary: String,
+}
+
+/// One transfer of control.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HandoffRecord {
+    pub from: String,
+    pub to: String,
+    pub context_summary: String,
+    /// Trace ID of the `from` agent's run.
+    pub trace_id: String,
+}
+
+/// Outcome of [`HandoffRunner::run`].
+#[derive(Debug)]
+pub struct HandoffResult {
+    /// Agent that finished the task.
+    pub agent: String,
+    /// That agent's run; its text is the final answer.
+    pub result: HarnessResult,
+    /// Transfers of control, in order.
+    pub handoffs: Vec<HandoffRecord>,
+    /// Tokens used by every agent in the chain.
+    pub total_prompt_tokens: u64,
+    pub total_completion_tokens: u64,
+    /// Estimated cost of every agent in the chain.
+    pub estimated_cost_usd: f64,
+}
+
+impl HandoffResult {
+    /// The final agent's answer.
+    pub fn text(&self) -> String {
+        self.result.text()
+    }
+}
+
+/// Runs a task through a set of agents that hand control to each other.
+pub struct HandoffRunner {
+    client: Arc<OpenRouterClient>,
+    config: HarnessConfig,
+    agents: Vec<Specialist>,
+    max_handoffs: u32,
+    handler: Arc<dyn AgentEventHandler>,
+}
+
+impl HandoffRunner {
+    /// Create a runner. `config` is the base for every agent's harness;
+    /// each agent overrides the model (if set), round limit, and system
+    /// prompt.
+    pub fn new(client: Arc<OpenRouterClient>, config: HarnessConfig) -> Self {
+        Self {
+            client,
+            config,
+            agents: Vec::new(),
+            max_handoffs: DEFAULT_MAX_HANDOFFS,
+            handler: Arc::new(NoopHandler),
+        }
+    }
+
+    /// Add an agent (builder pattern). An agent with the same name replaces
+    /// the earlier one.
+    pub fn with_agent(mut self, agent: Specialist) -> Self {
+        self.agents.retain(|a| a.name != agent.name);
+        self.agents.push(agent);
+        self
+    }
+
+    /// Cap the number of handoffs in one run (builder pattern). Further
+    /// `handoff` calls are refused and the current agent must finish.
+    pub fn with_max_handoffs(mut self, max_handoffs: u32) -> Self {
+        self.max_handoffs = max_handoffs;
+        self
+    }
+
+    /// Receive every agent's events, tagged with the agent name (builder
+    /// pattern).
+    pub fn with_event_handler(mut self, handler: impl AgentEventHandler + 'static) -> Self {
+        self.handler = Arc::new(handler);
+        self
+    }
+
+    /// Run `task`, starting with the agent named `start_agent`.
+    pub async fn run(&self, start_agent: &str, task: &str) -> Result<HandoffResult, String> {
+        let agent = self.agent(start_agent)?;
+        let messages = initial_messages(agent, task, None);
+        self.drive(agent, task.to_string(), messages, Vec::new(), None)
+            .await
+    }
+
+    /// Continue a run from a round checkpoint written by one of its agents.
+    ///
+    /// If the checkpoint's last round called `handoff`, the target agent
+    /// starts with the summary; otherwise the checkpoint's agent continues
+    /// its conversation and summarizer state. Handoffs made before the checkpoint are not
+    /// included in the result.
+    #[cfg(feature = "checkpoint")]
+    pub async fn resume(
+        &self,
+        checkpoint: &crate::agent::checkpoint::Checkpoint,
+    ) -> Result<HandoffResult, String> {
+        let name = checkpoint
+            .agent
+            .as_deref()
+            .ok_or("checkpoint has no agent name; it was not written by a handoff run")?;
+        let agent = self.agent(name)?;
+        let task = checkpoint
+            .messages
+            .iter()
+            .find(|m| m.role == MessageRole::User)
+            .and_then(|m| m.content.clone())
+            .ok_or("checkpoint has no task message")?;
+
+        match pending_handoff(&checkpoint.messages) {
+            Some(args) => {
+                let target = self.agent(&args.target_agent)?;
+                let record = HandoffRecord {
+                    from: agent.name.clone(),
+                    to: target.name.clone(),
+                    context_summary: args.context_summary,
+                    trace_id: checkpoint.trace_id.clone(),
+                };
+                self.emit_handoff(&record);
+                let messages = initial_messages(target, &task, Some(&record));
+                self.drive(target, task, messages, vec![record], None).await
+            }
+            None => {
+                self.drive(
+                    agent,
+                    task,
+                    checkpoint.messages.clone(),
+                    Vec::new(),
+                    checkpoint.structured_state.clone(),
+                )
+                .await
+            }
+        }
+    }
+
+    fn agent(&self, name: &str) -> Result<&Specialist, String> {
+        self.agents
+            .iter()
+            .find(|a| a.name == name)
+            .ok_or_else(|| format!("unknown agent '{name}'"))
+    }
+
+    fn emit_handoff(&self, record: &HandoffRecord) {
+        info!("Handoff: {} → {}", record.from, record.to);
+        self.handler.on_agent_event(
+            &record.from,
+            &HarnessEvent::Handoff {
+                from: &record.from,
+                to: &record.to,
+                context_summary: &record.context_summary,
+            },
+        );
+    }
+
+    /// Run agents until one finishes without handing off. `structured_state`
+    /// seeds the first agent's summarizer.
+    async fn drive(
+        &self,
+        mut agent: &Specialist,
+        task: String,
+        mut messages: Vec<Message>,
+        mut handoffs: Vec<HandoffRecord>,
+        mut structured_state: Option<StructuredState>,
+    ) -> Result<HandoffResult, String> {
+        // One allowance for the whole chain; `agent_config` keeps each
+        // agent's harness from resetting it.
+        if let Some(ref budget) = self.config.subprocess_budget {
+            budget.reset();
+        }
+        let (mut prompt_tokens, mut completion_tokens, mut cost_usd) = (0, 0, 0.0);
+        loop {
+            let requested = Arc::new(Mutex::new(None));
+            let tools = agent.tools.fork().with(HandoffTool {
+                current: agent.name.clone(),
+                targets: self
+                    .agents
+                    .iter()
+                    .filter(|a| a.name != agent.name)
+                    .map(|a| (a.name.clone(), a.description.clone()))
+                    .collect(),
+                allowed: (handoffs.len() as u32) < self.max_handoffs,
+                requested: Arc::clone(&requested),
+            });
+            let name = agent.name.as_str();
+            let handler = FnEventHandler::new(|event| self.handler.on_agent_event(name, event));
+            let stop = Arc::clone(&requested);
+            let mut harness = Harness::new(&self.client, &tools, self.agent_config(agent))
+                .with_event_handler(&handler)
+                .with_stop_signal(move || stop.lock().unwrap().is_some());
+            if let Some(state) = structured_state.take() {
+                harness = harness.with_structured_state(state);
+            }
+            let result = harness.run(messages).await?;
+
+            prompt_tokens += result.total_prompt_tokens;
+            completion_tokens += result.total_completion_tokens;
+            cost_usd += result.estimated_cost_usd;
+
+            let Some(args) = requested.lock().unwrap().take() else {
+                return Ok(HandoffResult {
+                    agent: agent.name.clone(),
+                    result,
+                    handoffs,
+                    total_prompt_tokens: prompt_tokens,
+                    total_completion_tokens: completion_tokens,
+                    estimated_cost_usd: cost_usd,
+                });
+            };
+            let target = self.agent(&args.target_agent)?;
+            let record = HandoffRecord {
+                from: agent.name.clone(),
+                to: target.name.clone(),
+                context_summary: args.context_summary,
+                trace_id: result.trace_id,
+            };
+            self.emit_handoff(&record);
+            messages = initial_messages(target, &task, Some(&record));
+            handoffs.push(record);
+            agent = target;
+        }
+    }
+
+    fn agent_config(&self, agent: &Specialist) -> HarnessConfig {
+        let mut config = self.config.clone().with_max_rounds(agent.max_rounds);
+        if let Some(ref model) = agent.model {
+            config.routing = HarnessConfig::new(model, "").routing;
+            config.model = model.clone();
+        }
+        config.system_prompt = Some(agent.system_prompt.clone());
+        config.agent_name = Some(agent.name.clone());
+        config.subprocess_budget = None;
+        config
+    }
+}
+
+/// First messages of `agent`'s run: its prompt (plus the handoff summary
+/// when taking over) and the task.
+fn initial_messages(agent: &Specialist, task: &str, from: Option<&HandoffRecord>) -> Vec<Message> {
+    let prompt = match from {
+        Some(handoff) => format!(
+            "{}\n\nYou are taking over this task from the '{}' agent. Their summary of \
+             the work so far:\n{}",
+            agent.system_prompt, handoff.from, handoff.context_summary
+        ),
+        None => agent.system_prompt.clone(),
+    };
+    vec![Message::system(prompt), Message::user(task)]
+}
+
+/// The accepted `handoff` call in the last assistant turn of `messages`.
+pub fn pending_handoff(messages: &[Message]) -> Option<HandoffArgs> {
+    let last = messages
+        .iter()
+        .rposition(|m| m.role == MessageRole::Assistant)?;
+    let call = messages[last]
+        .tool_calls
+        .as_ref()?
+        .iter()
+        .find(|c| c.function.name == HANDOFF_TOOL)?;
+    let accepted = messages[last + 1..].iter().any(|m| {
+        m.tool_call_id.as_deref() == Some(call.id.as_str())
+            && m.content
+                .as_deref()
+                .is_some_and(|c| c.starts_with(ACCEPTED_PREFIX))
+    });
+    if !accepted {
+        return None;
+    }
+    serde_json::from_str(&call.function.arguments).ok()
+}
+
+/// The `handoff` tool for one agent's run.
+struct HandoffTool {
+    current: String,
+    /// Other agents: (name, description).
+    targets: Vec<(String, String)>,
+    /// Whether the run's handoff limit still allows a transfer.
+    allowed: bool,
+    requested: Arc<Mutex<Option<HandoffArgs>>>,
+}
+
+impl Tool for HandoffTool {
+    fn definition(&self) -> ToolDef {
+        let mut schema = crate::json_schema_for::<HandoffArgs>();
+        let names: Vec<&str> = self.targets.iter().map(|(n, _)| n.as_str()).collect();
+        if let Some(target) = schema.pointer_mut("/properties/target_agent") {
+            target["enum"] = serde_json::json!(names);
+        }
+        let roster: String = self
+            .targets
+            .iter()
+            .map(|(name, description)| format!("\n- {name}: {description}"))
+            .collect();
+        ToolDef::new(
+            HANDOFF_TOOL,
+            format!(
+                "Transfer the task to another agent better suited to continue it. Your run \
+                 ends after this round and the target sees only the task and your \
+                 context_summary, so make the summary self-contained. Agents:{roster}"
+            ),
+            schema,
+        )
+    }
+
+    /// Transfers control of the run.
+    fn is_mutation(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, arguments: &str) -> ToolFuture<'_> {
+        let arguments = arguments.to_string();
+        Box::pin(async move {
+            let args: HandoffArgs = match serde_json::from_str(&arguments) {
+                Ok(a) => a,
+                Err(e) => {
+                    return format!(
+                        "Error: invalid arguments for handoff: {e}. \
+                         Required: target_agent (string), context_summary (string)."
+                    );
+                }
+            };
+            if args.target_agent == self.current {
+                return "Error: cannot hand off to yourself.".into();
+            }
+            if !self.targets.iter().any(|(n, _)| *n == args.target_agent) {
+                return format!("Error: unknown agent '{}'.", args.target_agent);
+            }
+            if !self.allowed {
+                return "Error: handoff limit reached. Finish the task yourself.".into();
+            }
+            let mut requested = self.requested.lock().unwrap();
+            if requested.is_some() {
+                return "Error: a handoff was already requested this round.".into();
+            }
+            let target = args.target_agent.clone();
+            *requested = Some(args);
+            format!("{ACCEPTED_PREFIX}: '{target}' takes over after this round. Stop here.")
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::orchestrator::FnAgentEventHandler;
+    use crate::test_support::{ScriptedTransport, test_config, text_reply, tool_call_reply};
+    use crate::tools::core::ToolSet;
+    use crate::tools::limits::SubprocessBudget;
+    use std::time::Duration;
+
+    fn handoff_call(target: &str, summary: &str) -> String {
+        let arguments = serde_json::json!({"target_agent": target, "context_summary": summary});
+        tool_call_reply("h1", "handoff", &arguments)
+    }
+
+    fn runner(replies: Vec<String>) -> (HandoffRunner, Arc<Mutex<Vec<String>>>) {
+        let transport = ScriptedTransport::new(replies);
+        let requests = transport.requests();
+        let client = OpenRouterClient::with_transport("key", transport);
+        let runner = HandoffRunner::new(Arc::new(client), test_config(""))
+            .with_agent(Specialist::researcher(ToolSet::new()))
+            .with_agent(Specialist::coder(ToolSet::new()));
+        (runner, requests)
+    }
+
+    #[tokio::test]
+    async fn handoff_transfers_control_with_summary() {
+        let (runner, requests) = runner(vec![
+            handoff_call("coder", "bug is in upload.rs:42"),
+            text_reply("fixed upload.rs"),
+        ]);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&events);
+        let runner = runner.with_event_handler(FnAgentEventHandler::new(move |agent, event| {
+            if let HarnessEvent::Handoff { from, to, .. } = event {
+                seen.lock().unwrap().push(format!("{agent}: {from}->{to}"));
+            }
+            None
+        }));
+
+        let result = runner.run("researcher", "fix upload").await.unwrap();
+        assert_eq!(result.agent, "coder");
+        assert_eq!(result.text(), "fixed upload.rs");
+        assert_eq!(result.handoffs.len(), 1);
+        assert_eq!(result.handoffs[0].context_summary, "bug is in upload.rs:42");
+        assert_eq!(*events.lock().unwrap(), ["researcher: researcher->coder"]);
+
+        // The coder starts fresh: the summary, not the researcher's history.
+        let coder_request = &requests.lock().unwrap()[1];
+        assert!(coder_request.contains("bug is in upload.rs:42"));
+        assert!(!coder_request.contains("Handoff accepted"));
+    }
+
+    #[tokio::test]
+    async fn subprocess_budget_spans_the_whole_chain() {
+        let (mut runner, _) = runner(vec![handoff_call("coder", "s"), text_reply("done")]);
+        let budget = Arc::new(SubprocessBudget::new(Duration::from_secs(60)));
+        budget.record(Duration::from_secs(30));
+        runner.config.subprocess_budget = Some(Arc::clone(&budget));
+        // Stand-in for the researcher's commands charging the budget.
+        let charged = Arc::clone(&budget);
+        let runner = runner.with_event_handler(FnAgentEventHandler::new(move |_, event| {
+            if let HarnessEvent::Handoff { .. } = event {
+                charged.record(Duration::from_secs(5));
+            }
+            None
+        }));
+
+        runner.run("researcher", "task").await.unwrap();
+        // Reset once when the chain started, not again for the coder.
+        assert_eq!(budget.used(), Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn handoff_limit_and_unknown_targets_are_refused() {
+        let (runner, _) = runner(vec![
+            handoff_call("reviewer", "x"),
+            handoff_call("coder", "y"),
+            text_reply("did it myself"),
+        ]);
+        let result = runner
+            .with_max_handoffs(0)
+            .run("researcher", "task")
+            .await
+            .unwrap();
+        assert_eq!(result.agent, "researcher");
+        assert!(result.handoffs.is_empty());
+        let results: Vec<_> = result
+            .result
+            .messages
+            .iter()
+            .filter(|m| m.role == MessageRole::Tool)
+            .filter_map(|m| m.content.as_deref())
+            .collect();
+        assert!(results[0].contains("unknown agent"), "{results:?}");
+        assert!(results[1].contains("limit reached"), "{results:?}");
+    }
+
+    #[test]
+    fn pending_handoff_requires_an_accepted_call() {
+        let call = crate::ToolCall {
+            id: "h1".into(),
+            call_type: crate::CallType::Function,
+            function: crate::FunctionCallData {
+                name: HANDOFF_TOOL.into(),
+                arguments: r#"{"target_agent":"coder","context_summary":"s"}"#.into(),
+            },
+        };
+        let mut messages = vec![
+            Message::user("task"),
+            Message::assistant_tool_calls(vec![call]),
+            Message::tool_result("h1", "Error: unknown agent 'coder'."),
+        ];
+        assert!(pending_handoff(&messages).is_none());
+
+        messages[2] = Message::tool_result("h1", format!("{ACCEPTED_PREFIX}: 'coder'"));
+        assert_eq!(pending_handoff(&messages).unwrap().target_agent, "coder");
+    }
+
+    #[cfg(feature = "checkpoint")]
+    #[tokio::test]
+    async fn resume_completes_a_checkpointed_handoff() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut first_runner, _) =
+            runner(vec![handoff_call("coder", "halfway"), text_reply("done")]);
+        first_runner.config.session = crate::agent::config::HarnessSessionConfig {
+            enabled: true,
+            sessions_dir: dir.path().to_path_buf(),
+            cleanup_on_success: true,
+            audit_log: false,
+            title_model: None,
+        };
+        // The researcher's run ends interrupted, so its checkpoint is kept.
+        let first = first_runner.run("researcher", "task").await.unwrap();
+
+        let mgr = crate::agent::session::SessionManager::new(dir.path()).unwrap();
+        let checkpoint = mgr
+            .load_latest_checkpoint(&first.handoffs[0].trace_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(checkpoint.agent.as_deref(), Some("researcher"));
+
+        let (resumed, _) = runner(vec![text_reply("finished")]);
+        let result = resumed.resume(&checkpoint).await.unwrap();
+        assert_eq!(result.agent, "coder");
+        assert_eq!(result.handoffs[0].context_summary, "halfway");
+        assert_eq!(result.text(), "finished");
+    }
+}
@@ -25,12 +25,27 @@
 //!
 //! See [`build_default_prompt_registry`] for details and customization.
 
-use super::config::HarnessConfig;
-use super::events::{EventHandler, EventResponse, HarnessEvent, HarnessResult};
-use super::execution::{execute_and_record_tool_calls, save_round_checkpoint, send_round_request};
+use super::audit::AuditLog;
+use super::config::{GenerationPhase, HarnessConfig};
+use super::degenerate::{self, Degenerate, Reroute};
+use super::events::{
+    EventHandler, EventResponse, HarnessEvent, HarnessResult, RoundDecisions, RoundOutcome,
+    ToolStats,
+};
+#[cfg(feature = "checkpoint")]
+use super::execution::save_round_checkpoint;
+use super::execution::{
+    CostTicker, execute_and_record_tool_calls, repair_tool_calls, send_round_request,
+    send_speculative_request,
+};
+use super::loop_detect::LoopDetector;
+use super::pacing::Pacer;
+use super::speculation::{PendingSpeculation, Speculation, speculative_messages};
+use super::warm_start::{WARM_START_HEADING, WarmStartInput};
 use crate::agent::plan_execute::{Phase, PlanExecuteConfig};
-use crate::agent::prompt::reminders::{ReminderRegistry, RoundContext};
+use crate::agent::prompt::reminders::{ReminderInjection, ReminderRegistry, RoundContext};
 use crate::agent::prompt::sections::{PromptRegistry, Stability, TurnContext};
+#[cfg(feature = "checkpoint")]
 use crate::agent::session::{
     SessionManager, SessionManifest, SessionStatus, epoch_secs, extract_message_preview,
 };
@@ -38,14 +53,21 @@ use crate::agent::sub_agent::SharedResources;
 use crate::context::eviction::{self, ToolResultMeta};
 use crate::context::file_tracker::FileAccessTracker;
 use crate::context::layout::ContextLayout;
-use crate::context::summarizer::Summarizer;
-use crate::context::{ContextBudget, ContextUsage};
+use crate::context::summarizer::{PinnedEntities, StructuredState, Summarizer};
+use crate::context::{ContextBudget, ContextUsage, HistoryArchive};
+use crate::tools::budget::CategoryBudgets;
 use crate::tools::cache::ToolResultCache;
-use crate::tools::core::ToolSet;
+use crate::tools::core::{ScratchpadTool, ToolSet};
+use crate::tools::disk_cache::DiskToolCache;
 use crate::tools::filter::ToolFilter;
-use crate::{Annotation, ChatRequest, Message, OpenRouterClient};
-use std::collections::HashSet;
-use tracing::{info, warn};
+use crate::tools::reflection::FailureEscalation;
+use crate::tools::selector::EmbeddingToolSelector;
+use crate::tools::snapshot::SnapshotStore;
+use crate::tools::spec::{FEW_SHOT_CALL_ID_PREFIX, FEW_SHOT_PREAMBLE, few_shot_messages};
+use crate::{Annotation, ChatCompletion, ChatRequest, Message, OpenRouterClient};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use tracing::{debug, info, warn};
 
 // ── Harness ────────────────────────────────────────────────────────
 
@@ -101,6 +123,11 @@ pub struct Harness<'a> {
     shared_resources: Option<SharedResources>,
     /// Optional tool filter for dynamic tool selection.
     tool_filter: Option<ToolFilter>,
+    /// Optional embedding-based tool selector (takes precedence over the
+    /// tool filter's keyword matching).
+    tool_selector: Option<Arc<EmbeddingToolSelector>>,
+    /// Summarizer state to continue from, e.g. a resumed checkpoint's.
+    structured_state: Option<StructuredState>,
 }
 
 impl<'a> Harness<'a> {
@@ -115,6 +142,8 @@ impl<'a> Harness<'a> {
             stop_signal: None,
             shared_resources: None,
             tool_filter: None,
+            tool_selector: None,
+            structured_state: None,
         }
     }
 
@@ -137,9 +166,11 @@ impl<'a> Harness<'a> {
         self
     }
 
-    /// Attach shared resources for sub-agent delegation.
+    /// Attach shared resources for sub-agent delegation. Unset provider
+    /// preferences are taken from this harness's config (see
+    /// [`SharedResources::inherit`]).
     pub fn with_shared_resources(mut self, resources: SharedResources) -> Self {
-        self.shared_resources = Some(resources);
+        self.shared_resources = Some(resources.inherit(&self.config));
         self
     }
 
@@ -149,6 +180,24 @@ impl<'a> Harness<'a> {
         self
     }
 
+    /// Attach an embedding-based tool selector. At the start of the run the
+    /// tools most similar to the task (plus the selector's core tools) are
+    /// selected, replacing the tool filter's keyword matching. Reuse the
+    /// same `Arc` across runs to embed each tool only once.
+    pub fn with_tool_selector(mut self, selector: Arc<EmbeddingToolSelector>) -> Self {
+        self.tool_selector = Some(selector);
+        self
+    }
+
+    /// Continue from an earlier run's summarizer state, such as a resumed
+    /// [`Checkpoint::structured_state`](super::checkpoint::Checkpoint::structured_state),
+    /// instead of starting empty. Only used with
+    /// [`SummaryStrategy::StructuredState`](crate::context::summarizer::SummaryStrategy::StructuredState).
+    pub fn with_structured_state(mut self, state: StructuredState) -> Self {
+        self.structured_state = Some(state);
+        self
+    }
+
     /// Conditionally attach a stop signal. If `condition` is `false`, this
     /// is a no-op and the harness runs without a stop signal. Avoids the
     /// `let mut harness = ...; if cond { harness = harness.with_stop_signal(...) }`
@@ -184,6 +233,10 @@ impl<'a> Harness<'a> {
         /// loops while giving transient API hiccups a chance to recover.
         const MAX_EMPTY_RESPONSE_RETRIES: u32 = 3;
 
+        /// Follow-up sent when a final answer is cut off by `max_tokens`.
+        const CONTINUATION_PROMPT: &str = "Your previous response was cut off by the output \
+            token limit. Continue exactly where it stopped, without repeating anything.";
+
         let mut acc = RunAccumulator {
             trace_id: crate::api::tracing::generate_trace_id(),
             text_output: Vec::new(),
@@ -191,8 +244,17 @@ impl<'a> Harness<'a> {
             cost_tracker: crate::api::tracing::CostTracker::new(),
             rounds_used: 0,
             finished: false,
+            budget_exceeded: false,
+            prefix_analyzer: crate::api::tracing::PrefixAnalyzer::new(),
         };
         let mut empty_response_retries: u32 = 0;
+        // Route for retrying an empty or degenerate response, and the
+        // providers that produced one.
+        let mut retry_route: Option<Reroute> = None;
+        let mut failed_providers: Vec<String> = Vec::new();
+        let mut continuations: u32 = 0;
+        // Whether this round continues the previous round's truncated text.
+        let mut continuing = false;
 
         info!(
             "Harness run started: trace_id={}, model={}",
@@ -201,8 +263,36 @@ impl<'a> Harness<'a> {
 
         // ── Initialize modules ──
         let mut modules = init_modules(&self.config);
+        if let Some(ref mut summarizer) = modules.summarizer
+            && let Some(state) = self.structured_state.take()
+        {
+            summarizer.state = state;
+        }
+
+        // Archive compacted history per run and let the model search it.
+        // The recall tool goes on a per-run fork so the caller's (possibly
+        // shared) tool set never points at this run's archive.
+        let run_tools;
+        let tools = match self.config.history_archive_dir {
+            Some(ref dir) => {
+                let archive_dir = dir.join(&acc.trace_id);
+                run_tools = self.tools.fork();
+                run_tools.register(crate::tools::RecallHistoryTool::new(&archive_dir));
+                modules.history_archive = Some(HistoryArchive::new(archive_dir));
+                &run_tools
+            }
+            None => self.tools,
+        };
+
+        if self.config.session.audit_log {
+            modules.audit_log = Some(AuditLog::new(
+                &self.config.session.sessions_dir,
+                &acc.trace_id,
+            ));
+        }
 
         // Load MEMORY.md index if a memory file is configured.
+        #[cfg(feature = "memory")]
         let memory_index_content =
             self.config
                 .memory_config
@@ -214,8 +304,24 @@ impl<'a> Harness<'a> {
                         self.config.memory_config.max_memory_lines,
                     )
                 });
+        #[cfg(not(feature = "memory"))]
+        let memory_index_content: Option<String> = None;
+
+        // ── Warm start ──
+        let warm_start_context = if self.config.warm_start.is_enabled() {
+            warm_start(&self.config, &messages, self.event_handler).await
+        } else {
+            None
+        };
 
         if self.config.use_prompt_registry {
+            // Read once per run; see `prompt::git_context`.
+            let git_status = if self.config.git_context.enabled {
+                crate::agent::prompt::git_context::git_context(&self.config.git_context).await
+            } else {
+                None
+            };
+
             // Extract the existing system message content as the preamble.
             let preamble = messages
                 .iter()
@@ -224,11 +330,27 @@ impl<'a> Harness<'a> {
                 .unwrap_or("")
                 .to_string();
 
-            let registry = build_default_prompt_registry(
+            let mut registry = build_default_prompt_registry(
                 &preamble,
                 &self.config,
                 memory_index_content.as_deref(),
             );
+            if let Some(context) = warm_start_context {
+                registry.register_dynamic(
+                    WARM_START_HEADING,
+                    100,
+                    |_ctx| true,
+                    move |_ctx| context.clone(),
+                );
+            }
+            if let Some(status) = git_status {
+                registry.register_dynamic(
+                    crate::agent::prompt::git_context::GIT_CONTEXT_HEADING,
+                    50,
+                    |_ctx| true,
+                    move |_ctx| status.clone(),
+                );
+            }
 
             let ctx = TurnContext::default();
             let assembled = registry.assemble(&ctx);
@@ -247,13 +369,21 @@ impl<'a> Harness<'a> {
                 memory_index_content.as_deref(),
                 self.config.project_instructions.as_ref(),
             );
+            if let Some(context) = warm_start_context
+                && let Some(content) = messages
+                    .iter_mut()
+                    .find(|m| matches!(m.role, crate::MessageRole::System))
+                    .and_then(|m| m.content.as_mut())
+            {
+                content.push_str(&format!("\n\n## {WARM_START_HEADING}\n\n{context}"));
+            }
         }
 
         // ── Inject tool usage guidelines ──
         // Composition-aware rules from ToolSet::generate_guidelines() are
         // appended to the system prompt so the LLM knows how to use tools
         // together (e.g., "prefer grep over shell('grep ...')").
-        let tool_guidelines = self.tools.generate_guidelines();
+        let tool_guidelines = tools.generate_guidelines();
         if !tool_guidelines.is_empty()
             && let Some(sys_msg) = messages
                 .iter_mut()
@@ -265,12 +395,15 @@ impl<'a> Harness<'a> {
         }
 
         // ── Create initial session manifest ──
+        #[cfg(feature = "checkpoint")]
         if modules.session_manager.is_some() {
             let now = epoch_secs();
             let preview = extract_message_preview(&messages);
             let manifest = SessionManifest {
+                version: crate::agent::migration::MANIFEST_VERSION,
                 trace_id: acc.trace_id.clone(),
                 title: None,
+                summary: None,
                 model: self.config.model.clone(),
                 status: SessionStatus::Running,
                 created_at: now,
@@ -289,6 +422,10 @@ impl<'a> Harness<'a> {
             modules.session_manifest = Some(manifest);
         }
 
+        if let Some(ref budget) = self.config.subprocess_budget {
+            budget.reset();
+        }
+
         // ── Emit SessionStarting ──
         self.event_handler.on_event(&HarnessEvent::SessionStarting {
             trace_id: &acc.trace_id,
@@ -304,20 +441,37 @@ impl<'a> Harness<'a> {
         {
             self.context_budget = Some(
                 ContextBudget::with_calibration(sys_content, None)
-                    .with_output_reserve(self.config.max_tokens as usize),
+                    .with_output_reserve(self.config.max_tokens as usize)
+                    .with_adaptive_output_reserve(self.config.adaptive_output_reserve),
             );
         }
 
         // Get tool definitions (may be filtered, may be compact for progressive loading).
         let all_tool_defs = if self.config.progressive_tools {
-            self.tools.compact_definitions()
+            tools.compact_definitions()
         } else {
-            self.tools.definitions()
+            tools.definitions()
+        };
+        let selected_tool_defs = match (&self.tool_selector, first_user_text(&messages)) {
+            (Some(selector), Some(task)) => {
+                match selector.select(self.client, task, &all_tool_defs).await {
+                    Ok(defs) => Some(defs),
+                    Err(e) => {
+                        warn!("Embedding tool selection failed, using all tools: {e}");
+                        None
+                    }
+                }
+            }
+            _ => None,
         };
-        let full_tool_defs = if let Some(ref filter) = self.tool_filter {
+        let full_tool_defs = if let Some(defs) = selected_tool_defs {
+            defs
+        } else if let Some(ref mut filter) = self.tool_filter {
             let task_keywords = extract_task_keywords(&messages);
             let keyword_refs: Vec<&str> = task_keywords.iter().map(|s| s.as_str()).collect();
-            filter.filter_for_task(&keyword_refs, &all_tool_defs)
+            let defs = filter.filter_for_task(&keyword_refs, &all_tool_defs);
+            filter.record_offered(&defs);
+            defs
         } else {
             all_tool_defs.clone()
         };
@@ -332,7 +486,7 @@ impl<'a> Harness<'a> {
         // Build the planning-phase tool set: read-only tools + submit_plan.
         let planning_tool_defs = if self.config.plan_execute.enabled {
             let pe_config = &self.config.plan_execute.config;
-            let mut defs = pe_config.filter_planning_tools(&full_tool_defs);
+            let mut defs = pe_config.filter_planning_tools(&full_tool_defs, tools);
             defs.push(PlanExecuteConfig::submit_plan_tool_def());
             defs
         } else {
@@ -359,11 +513,19 @@ impl<'a> Harness<'a> {
             (full_tool_defs, planning_tool_defs)
         };
 
+        // ── Few-shot tool demonstrations ──
+        if self.config.few_shot_tool_examples {
+            insert_few_shot_examples(&mut messages, tools, &full_tool_defs);
+        }
+
         // ── Initialize ContextLayout ──
         // The initial messages (system prompt + user task) become the pinned prefix.
         // All subsequent messages flow through the layout's zone management.
         let mut layout = ContextLayout::new(self.config.context_window_tokens)
             .with_keep_recent(self.config.keep_recent_messages);
+        if let Some(tokens) = self.config.keep_recent_tokens {
+            layout = layout.with_keep_recent_tokens(tokens);
+        }
         layout.set_prefix(messages);
 
         // Inject the planning prompt as a conversation message (not prefix).
@@ -381,6 +543,7 @@ impl<'a> Harness<'a> {
         };
 
         let mut tools_option = non_empty_tools(&current_tool_defs);
+        let mut pending_speculation: Option<PendingSpeculation> = None;
 
         for round in 0..self.config.max_rounds {
             // Check stop signal.
@@ -391,14 +554,71 @@ impl<'a> Harness<'a> {
                 break;
             }
 
+            // Check cost budget.
+            if let Some(max_cost_usd) = self.config.max_cost_usd
+                && acc.cost_tracker.estimated_cost_usd >= max_cost_usd
+            {
+                acc.budget_exceeded = true;
+                self.event_handler
+                    .on_event(&HarnessEvent::CostLimitReached {
+                        cost_usd: acc.cost_tracker.estimated_cost_usd,
+                        max_cost_usd,
+                    });
+                break;
+            }
+
             acc.rounds_used = round + 1;
 
+            // ── Deadline pacing ──
+            let wrap_up = modules.pacer.as_ref().and_then(Pacer::wrap_up);
+            if let Some(remaining) = wrap_up {
+                if modules.pacer.as_mut().is_some_and(Pacer::engage) {
+                    self.event_handler.on_event(&HarnessEvent::PacingEngaged {
+                        round: round + 1,
+                        remaining,
+                    });
+                }
+                if phase == Phase::Planning {
+                    info!("Deadline approaching — skipping the rest of the planning phase");
+                    self.event_handler.on_event(&HarnessEvent::PhaseTransition {
+                        from: &Phase::Planning,
+                        to: &Phase::Executing,
+                    });
+                    layout.push_message(Message::user(
+                        &self.config.plan_execute.config.execution_prompt,
+                    ));
+                    phase = Phase::Executing;
+                    current_tool_defs = full_tool_defs.clone();
+                    tools_option = non_empty_tools(&current_tool_defs);
+                }
+            }
+            let pacer = modules.pacer.as_ref().filter(|_| wrap_up.is_some());
+            let mut generation = self.config.generation_for(GenerationPhase::of_round(
+                round,
+                self.config.max_rounds,
+                phase == Phase::Planning,
+                continuing,
+            ));
+            if let Some(p) = pacer {
+                generation.max_tokens = p.max_tokens(generation.max_tokens);
+            }
+            let output_reserve = self
+                .context_budget
+                .as_ref()
+                .map(ContextBudget::output_reserve);
+
             // ── Model routing ──
-            let model_for_round = self
-                .config
-                .routing
-                .model_for_round(round, false)
+            let reroute = retry_route.take();
+            let model_for_round = reroute
+                .as_ref()
+                .and_then(|r| r.model.as_deref())
+                .or_else(|| pacer.and_then(Pacer::fast_model))
+                .unwrap_or_else(|| self.config.routing.model_for_round(round, false))
                 .to_string();
+            let provider_for_round = reroute
+                .as_ref()
+                .map(|r| &r.provider)
+                .or(self.config.provider.as_ref());
             if model_for_round != self.config.model {
                 self.event_handler.on_event(&HarnessEvent::ModelRouted {
                     model: &model_for_round,
@@ -407,7 +627,7 @@ impl<'a> Harness<'a> {
             }
 
             // ── Context management (eviction + summarization) ──
-            evict_if_needed(
+            let (evicted_results, evicted_chars) = evict_if_needed(
                 &self.config,
                 &self.context_budget,
                 &mut layout,
@@ -415,7 +635,7 @@ impl<'a> Harness<'a> {
                 round,
                 self.event_handler,
             );
-            summarize_if_needed(
+            let compacted = summarize_if_needed(
                 &self.config,
                 self.client,
                 &self.context_budget,
@@ -426,6 +646,7 @@ impl<'a> Harness<'a> {
                 round,
                 self.event_handler,
                 &modules.file_tracker,
+                &modules.history_archive,
             )
             .await;
 
@@ -464,38 +685,138 @@ impl<'a> Harness<'a> {
                 total_tool_calls: modules.tool_metas.len(),
                 model: model_for_round.clone(),
             };
-            let reminder_texts = modules.reminders.collect_reminders(&round_ctx);
-            for text in &reminder_texts {
-                layout.push_message(Message::user(text));
+            let mut reminder_texts = modules.reminders.collect_reminders(&round_ctx);
+            reminder_texts.extend(
+                modules
+                    .loop_detector
+                    .as_mut()
+                    .and_then(LoopDetector::take_reminder),
+            );
+            if let Some(ref mut escalation) = modules.failure_escalation {
+                reminder_texts.extend(escalation.take_reminders());
             }
-            // Re-assemble messages if reminders were injected.
-            let api_messages = if reminder_texts.is_empty() {
-                api_messages
-            } else {
-                layout.to_messages()
-            };
+            if wrap_up.is_some()
+                && let Some(ref pacer) = modules.pacer
+            {
+                reminder_texts.push(pacer.reminder().to_string());
+            }
+            let mut api_messages = inject_reminders(
+                self.config.reminder_injection,
+                &mut layout,
+                api_messages,
+                &reminder_texts,
+            );
 
-            // ── Send request ──
-            let completion = send_round_request(
-                &self.config,
-                self.client,
-                &api_messages,
+            // ── Final-answer prefill (request only, never stored) ──
+            let prefill = self.config.final_answer_prefill.as_deref().filter(|_| {
+                !continuing
+                    && (round + 1 == self.config.max_rounds
+                        || (self.config.output_schema.is_some() && tools_option.is_none()))
+            });
+            if let Some(prefill) = prefill {
+                api_messages.push(Message::assistant_text(prefill));
+            }
+
+            // ── Use a speculative response if it still applies ──
+            let speculated = pending_speculation.take().and_then(|spec| {
+                let accepted = spec.model == model_for_round
+                    && spec.generation == generation
+                    && modules
+                        .speculation
+                        .as_ref()
+                        .is_some_and(|s| s.messages_match(&spec.messages, &api_messages));
+                self.event_handler
+                    .on_event(&HarnessEvent::SpeculationResolved {
+                        round: round + 1,
+                        accepted,
+                    });
+                if accepted {
+                    Some(spec.completion)
+                } else {
+                    // The discarded response was still paid for.
+                    if let Some(ref u) = spec.completion.usage {
+                        acc.cost_tracker.record(
+                            u.prompt_tokens.unwrap_or(0),
+                            u.completion_tokens.unwrap_or(0),
+                            &pricing,
+                        );
+                    }
+                    None
+                }
+            });
+
+            // ── Prompt prefix stability ──
+            if let Some(brk) = acc.prefix_analyzer.observe(
+                round + 1,
                 &model_for_round,
-                &tools_option,
-                self.event_handler,
-                self.stop_signal.as_ref().map(|s| s.as_ref()),
-            )
-            .await?;
+                tools_option.as_deref(),
+                &api_messages,
+            ) {
+                self.event_handler
+                    .on_event(&HarnessEvent::PromptPrefixBroken {
+                        round: brk.round,
+                        cause: &brk.cause,
+                        stable_messages: brk.stable_messages,
+                    });
+            }
+
+            // ── Send request ──
+            let speculative = speculated.is_some();
+            let mut completion = match speculated {
+                Some(completion) => completion,
+                None => {
+                    let ticker = CostTicker {
+                        tracker: &acc.cost_tracker,
+                        pricing: &pricing,
+                        prompt_tokens: u32::try_from(usage.estimated_tokens).unwrap_or(u32::MAX),
+                    };
+                    let mut discarded_usage = Vec::new();
+                    let sent = send_round_request(
+                        &self.config,
+                        self.client,
+                        &api_messages,
+                        &model_for_round,
+                        &generation,
+                        output_reserve,
+                        provider_for_round,
+                        &tools_option,
+                        &crate::api::tracing::generate_span_id(&acc.trace_id, round + 1),
+                        self.event_handler,
+                        self.stop_signal.as_ref().map(|s| s.as_ref()),
+                        Some(&ticker),
+                        &mut discarded_usage,
+                    )
+                    .await;
+                    // Interrupted attempts were still paid for.
+                    for u in &discarded_usage {
+                        acc.cost_tracker.record(
+                            u.prompt_tokens.unwrap_or(0),
+                            u.completion_tokens.unwrap_or(0),
+                            &pricing,
+                        );
+                    }
+                    sent?
+                }
+            };
 
             // Track token usage and cost.
             if let Some(ref u) = completion.usage {
                 let pt = u.prompt_tokens.unwrap_or(0);
                 let ct = u.completion_tokens.unwrap_or(0);
                 acc.cost_tracker.record(pt, ct, &pricing);
+                if let Some(ref mut budget) = self.context_budget {
+                    budget.record_completion(ct);
+                }
                 self.event_handler.on_event(&HarnessEvent::TokenUsage {
                     prompt_tokens: pt,
                     completion_tokens: ct,
                 });
+                self.event_handler.on_event(&HarnessEvent::CostUpdate {
+                    prompt_tokens: acc.cost_tracker.total_prompt_tokens,
+                    completion_tokens: acc.cost_tracker.total_completion_tokens,
+                    cost_usd: acc.cost_tracker.estimated_cost_usd,
+                    estimated: false,
+                });
 
                 // Emit prompt cache stats when available.
                 if let Some(ref details) = u.prompt_tokens_details {
@@ -511,6 +832,24 @@ impl<'a> Harness<'a> {
                 }
             }
 
+            let mut decisions = RoundDecisions {
+                round: round + 1,
+                model: model_for_round.clone(),
+                model_routed: model_for_round != self.config.model,
+                tools_offered: tools_option.as_ref().map_or(0, Vec::len),
+                tools_withheld: withheld_tools(&all_tool_defs, tools_option.as_deref()),
+                evicted_results,
+                evicted_chars,
+                compacted,
+                reminders: reminder_texts.len(),
+                wrap_up: wrap_up.is_some(),
+                prefilled: prefill.is_some(),
+                speculative,
+                max_tokens: generation.max_tokens_within(output_reserve),
+                tool_calls: completion.tool_calls.len(),
+                ..Default::default()
+            };
+
             // Emit reasoning content if present.
             if let Some(ref reasoning) = completion.reasoning
                 && !reasoning.is_empty()
@@ -519,19 +858,84 @@ impl<'a> Harness<'a> {
                     .on_event(&HarnessEvent::Reasoning(reasoning));
             }
 
-            // Emit text.
+            // ── Empty / degenerate responses ──
+            // Retried, rerouted away from the provider that produced them,
+            // before being accepted as the agent's final answer.
+            if completion.tool_calls.is_empty()
+                && let Some(kind) = degenerate::check_completion(
+                    &completion,
+                    &api_messages,
+                    &self.config.degenerate,
+                )
+            {
+                empty_response_retries += 1;
+                if empty_response_retries <= MAX_EMPTY_RESPONSE_RETRIES {
+                    if kind == Degenerate::Empty {
+                        self.event_handler.on_event(&HarnessEvent::EmptyResponse {
+                            round: round + 1,
+                            attempt: empty_response_retries,
+                            max_retries: MAX_EMPTY_RESPONSE_RETRIES,
+                        });
+                    } else {
+                        self.event_handler
+                            .on_event(&HarnessEvent::DegenerateResponse {
+                                round: round + 1,
+                                reason: kind.label(),
+                                attempt: empty_response_retries,
+                                max_retries: MAX_EMPTY_RESPONSE_RETRIES,
+                            });
+                    }
+                    failed_providers.extend(completion.provider);
+                    retry_route = Some(Reroute::for_attempt(
+                        &self.config.degenerate,
+                        self.config.provider.as_ref(),
+                        empty_response_retries,
+                        &failed_providers,
+                    ));
+                    // Brief backoff before retrying (500ms * attempt).
+                    crate::platform::sleep(std::time::Duration::from_millis(
+                        500 * u64::from(empty_response_retries),
+                    ))
+                    .await;
+                    decisions.outcome = RoundOutcome::Retried;
+                    self.event_handler.on_event(&HarnessEvent::RoundEnd {
+                        decisions: &decisions,
+                    });
+                    continue;
+                }
+                // Exhausted retries — fall through to the normal exit.
+                warn!(
+                    "{} API response persisted after {MAX_EMPTY_RESPONSE_RETRIES} retries. \
+                     Treating as agent completion.",
+                    kind.label()
+                );
+            }
+
+            // The model continued the prefill; restore it in the answer.
+            if let Some(prefill) = prefill
+                && completion.tool_calls.is_empty()
+            {
+                let text = completion.content.take().unwrap_or_default();
+                completion.content = Some(format!("{prefill}{text}"));
+            }
+
+            // Emit text, stitching a continuation onto the text it continues.
+            let continued = std::mem::take(&mut continuing);
             if let Some(ref text) = completion.content
                 && !text.is_empty()
             {
                 self.event_handler.on_event(&HarnessEvent::Text(text));
-                acc.text_output.push(text.clone());
+                match acc.text_output.last_mut() {
+                    Some(last) if continued => last.push_str(text),
+                    _ => acc.text_output.push(text.clone()),
+                }
             }
 
             // ── Plan-execute: intercept submit_plan ──
             if phase == Phase::Planning
                 && let Some(transition) = handle_plan_submission(
                     &self.config,
-                    self.tools,
+                    tools,
                     &completion,
                     &mut layout,
                     acc.rounds_used,
@@ -543,6 +947,10 @@ impl<'a> Harness<'a> {
                 current_tool_defs = full_tool_defs.clone();
                 tools_option = non_empty_tools(&current_tool_defs);
                 if transition.should_continue {
+                    decisions.outcome = RoundOutcome::PlanSubmitted;
+                    self.event_handler.on_event(&HarnessEvent::RoundEnd {
+                        decisions: &decisions,
+                    });
                     continue;
                 }
             }
@@ -550,9 +958,8 @@ impl<'a> Harness<'a> {
             // Collect annotations (after all borrows of `completion` are done).
             acc.annotations.extend(completion.annotations);
 
-            // If no tool calls at all, the agent is done — unless the API
-            // returned an empty/malformed response (no content, no tool calls,
-            // near-zero tokens). In that case, retry instead of exiting.
+            // If no tool calls at all, the agent is done (empty and degenerate
+            // responses were retried above).
             //
             // Note: pseudo-tool-only rounds (think, todo) are NOT treated as
             // stop signals. The agent may call `todo(add)` or `think` while
@@ -561,38 +968,37 @@ impl<'a> Harness<'a> {
             // path below and the agent gets another round.
             if completion.tool_calls.is_empty() {
                 let has_content = completion.content.as_ref().is_some_and(|c| !c.is_empty());
-                let completion_tokens = completion
-                    .usage
-                    .as_ref()
-                    .and_then(|u| u.completion_tokens)
-                    .unwrap_or(0);
-
-                // An empty response is one where the API returned nothing
-                // useful: no text, no tool calls, and essentially zero
-                // completion tokens. This typically means an OpenRouter
-                // transient failure that returned HTTP 200 with an empty body.
-                if !has_content && completion_tokens == 0 {
-                    empty_response_retries += 1;
-                    if empty_response_retries <= MAX_EMPTY_RESPONSE_RETRIES {
-                        self.event_handler.on_event(&HarnessEvent::EmptyResponse {
+
+                // A final answer cut off by max_tokens: ask for the rest
+                // rather than returning it truncated.
+                if completion.finish_reason.as_deref() == Some("length")
+                    && has_content
+                    && continuations < self.config.max_continuations
+                    && round + 1 < self.config.max_rounds
+                {
+                    continuations += 1;
+                    self.event_handler
+                        .on_event(&HarnessEvent::LengthContinuation {
                             round: round + 1,
-                            attempt: empty_response_retries,
-                            max_retries: MAX_EMPTY_RESPONSE_RETRIES,
+                            attempt: continuations,
+                            max_continuations: self.config.max_continuations,
                         });
-                        // Brief backoff before retrying (500ms * attempt).
-                        tokio::time::sleep(std::time::Duration::from_millis(
-                            500 * u64::from(empty_response_retries),
-                        ))
-                        .await;
-                        continue;
-                    }
-                    // Exhausted retries — fall through to the normal exit.
-                    warn!(
-                        "Empty API response persisted after {MAX_EMPTY_RESPONSE_RETRIES} retries. \
-                         Treating as agent completion."
-                    );
+                    layout.push_message(Message::assistant_text(
+                        completion.content.unwrap_or_default(),
+                    ));
+                    layout.push_message(Message::user(CONTINUATION_PROMPT));
+                    continuing = true;
+                    decisions.outcome = RoundOutcome::Continued;
+                    self.event_handler.on_event(&HarnessEvent::RoundEnd {
+                        decisions: &decisions,
+                    });
+                    continue;
                 }
 
+                decisions.outcome = RoundOutcome::Finished;
+                self.event_handler.on_event(&HarnessEvent::RoundEnd {
+                    decisions: &decisions,
+                });
                 acc.finished = true;
                 self.event_handler.on_event(&HarnessEvent::Finished);
                 break;
@@ -609,50 +1015,217 @@ impl<'a> Harness<'a> {
                     count: completion.tool_calls.len(),
                 });
 
-            layout.push_message(Message::assistant_tool_calls(completion.tool_calls.clone()));
-
-            execute_and_record_tool_calls(
+            repair_tool_calls(
                 &self.config,
-                self.tools,
-                self.event_handler,
                 self.client,
                 &model_for_round,
-                &self.context_budget,
-                &mut self.tool_filter,
-                &mut layout,
-                &mut modules,
-                &completion.tool_calls,
-                round,
+                tools,
+                &tools_option,
+                completion.finish_reason.as_deref(),
+                &mut completion.tool_calls,
             )
             .await;
+            layout.push_message(Message::assistant_tool_calls(history_tool_calls(
+                &completion.tool_calls,
+            )));
+
+            let stats_before = cache_hits_and_errors(&modules.tool_stats);
+
+            // ── Speculative prefetch of the next round ──
+            let speculative = if round + 1 < self.config.max_rounds {
+                modules
+                    .speculation
+                    .as_ref()
+                    .and_then(|s| s.predict(&completion.tool_calls))
+                    .map(|predicted| {
+                        let messages = speculative_messages(layout.to_messages(), &predicted);
+                        (predicted, messages)
+                    })
+            } else {
+                None
+            };
+
+            match speculative {
+                None => {
+                    execute_and_record_tool_calls(
+                        &self.config,
+                        tools,
+                        self.event_handler,
+                        self.client,
+                        &model_for_round,
+                        &self.context_budget,
+                        &mut self.tool_filter,
+                        &mut layout,
+                        &mut modules,
+                        &completion.tool_calls,
+                        round,
+                    )
+                    .await;
+                }
+                Some((predicted, spec_messages)) => {
+                    let next_model = self
+                        .config
+                        .routing
+                        .model_for_round(round + 1, false)
+                        .to_string();
+                    // The next round runs in the same phase unless it is the last.
+                    let next_generation = self.config.generation_for(GenerationPhase::of_round(
+                        round + 1,
+                        self.config.max_rounds,
+                        phase == Phase::Planning,
+                        false,
+                    ));
+                    let request_messages = spec_messages.clone();
+                    let span_id = crate::api::tracing::generate_span_id(&acc.trace_id, round + 2);
+                    let request = send_speculative_request(
+                        &self.config,
+                        self.client,
+                        &request_messages,
+                        &next_model,
+                        &next_generation,
+                        self.context_budget
+                            .as_ref()
+                            .map(ContextBudget::output_reserve),
+                        &tools_option,
+                        &span_id,
+                    );
+                    tokio::pin!(request);
+
+                    // Run the tools while the speculative request is in flight.
+                    let (actual, early) = {
+                        let execution = execute_and_record_tool_calls(
+                            &self.config,
+                            tools,
+                            self.event_handler,
+                            self.client,
+                            &model_for_round,
+                            &self.context_budget,
+                            &mut self.tool_filter,
+                            &mut layout,
+                            &mut modules,
+                            &completion.tool_calls,
+                            round,
+                        );
+                        tokio::pin!(execution);
+                        let mut early = None;
+                        let actual = loop {
+                            tokio::select! {
+                                actual = &mut execution => break actual,
+                                response = &mut request, if early.is_none() => {
+                                    early = Some(response);
+                                }
+                            }
+                        };
+                        (actual, early)
+                    };
+
+                    let actual: Vec<(String, String)> = actual
+                        .into_iter()
+                        .map(|(call_id, _, _, result)| (call_id, result))
+                        .collect();
+                    let holds = modules
+                        .speculation
+                        .as_ref()
+                        .is_some_and(|s| s.predictions_hold(&predicted, &actual));
+                    if holds {
+                        let response = match early {
+                            Some(response) => response,
+                            None => request.await,
+                        };
+                        match response {
+                            Ok(completion) => {
+                                pending_speculation = Some(PendingSpeculation {
+                                    messages: spec_messages,
+                                    model: next_model.clone(),
+                                    generation: next_generation.clone(),
+                                    completion,
+                                });
+                            }
+                            Err(e) => warn!("Speculative request failed: {e}"),
+                        }
+                    } else {
+                        // Dropping `request` cancels it if still in flight.
+                        debug!("Tool results diverged from predictions; discarding speculation");
+                        if let Some(Ok(ChatCompletion {
+                            usage: Some(ref u), ..
+                        })) = early
+                        {
+                            acc.cost_tracker.record(
+                                u.prompt_tokens.unwrap_or(0),
+                                u.completion_tokens.unwrap_or(0),
+                                &pricing,
+                            );
+                        }
+                        self.event_handler
+                            .on_event(&HarnessEvent::SpeculationResolved {
+                                round: round + 2,
+                                accepted: false,
+                            });
+                    }
+                }
+            }
+
+            // ── Paid tool calls ──
+            if !modules.tool_costs.is_empty() {
+                for (tool, cost) in modules.tool_costs.drain(..) {
+                    acc.cost_tracker.record_tool(&tool, cost);
+                }
+                self.event_handler.on_event(&HarnessEvent::CostUpdate {
+                    prompt_tokens: acc.cost_tracker.total_prompt_tokens,
+                    completion_tokens: acc.cost_tracker.total_completion_tokens,
+                    cost_usd: acc.cost_tracker.estimated_cost_usd,
+                    estimated: false,
+                });
+            }
 
             // ── Save checkpoint + update manifest ──
-            let checkpoint_messages = layout.to_messages();
-            save_round_checkpoint(
-                &modules.session_manager,
-                &acc.trace_id,
-                &checkpoint_messages,
-                &acc.text_output,
-                round,
-                acc.cost_tracker.total_prompt_tokens,
-                acc.cost_tracker.total_completion_tokens,
-                acc.cost_tracker.estimated_cost_usd,
-                self.event_handler,
-            );
-            // Update manifest with latest round/token/cost data.
-            if let Some(ref mut manifest) = modules.session_manifest {
-                manifest.last_round = round + 1;
-                manifest.total_prompt_tokens = acc.cost_tracker.total_prompt_tokens;
-                manifest.total_completion_tokens = acc.cost_tracker.total_completion_tokens;
-                manifest.estimated_cost_usd = acc.cost_tracker.estimated_cost_usd;
-                manifest.updated_at = epoch_secs();
-                if let Some(ref mgr) = modules.session_manager
-                    && let Err(e) = mgr.save_manifest(manifest)
-                {
-                    warn!("Failed to update session manifest: {e}");
+            #[cfg(feature = "checkpoint")]
+            {
+                let checkpoint_messages = layout.to_messages();
+                save_round_checkpoint(
+                    &modules.session_manager,
+                    &acc.trace_id,
+                    self.config.agent_name.as_deref(),
+                    &checkpoint_messages,
+                    &acc.text_output,
+                    round,
+                    acc.cost_tracker.total_prompt_tokens,
+                    acc.cost_tracker.total_completion_tokens,
+                    acc.cost_tracker.estimated_cost_usd,
+                    modules
+                        .summarizer
+                        .as_ref()
+                        .filter(|s| {
+                            matches!(
+                                s.config.strategy,
+                                crate::context::summarizer::SummaryStrategy::StructuredState
+                            )
+                        })
+                        .map(|s| &s.state),
+                    self.event_handler,
+                );
+                // Update manifest with latest round/token/cost data.
+                if let Some(ref mut manifest) = modules.session_manifest {
+                    manifest.last_round = round + 1;
+                    manifest.total_prompt_tokens = acc.cost_tracker.total_prompt_tokens;
+                    manifest.total_completion_tokens = acc.cost_tracker.total_completion_tokens;
+                    manifest.estimated_cost_usd = acc.cost_tracker.estimated_cost_usd;
+                    manifest.updated_at = epoch_secs();
+                    if let Some(ref mgr) = modules.session_manager
+                        && let Err(e) = mgr.save_manifest(manifest)
+                    {
+                        warn!("Failed to update session manifest: {e}");
+                    }
                 }
             }
 
+            let (cache_hits, tool_errors) = cache_hits_and_errors(&modules.tool_stats);
+            decisions.cache_hits = cache_hits - stats_before.0;
+            decisions.tool_errors = tool_errors - stats_before.1;
+            self.event_handler.on_event(&HarnessEvent::RoundEnd {
+                decisions: &decisions,
+            });
+
             // Post-round stop signal check — break immediately instead of
             // waiting for the next round's pre-check. This ensures that an
             // interrupt triggered mid-stream takes effect right away.
@@ -664,6 +1237,13 @@ impl<'a> Harness<'a> {
             }
         }
 
+        // Persist tool usage for profile-based filtering in later runs.
+        if let Some(ref mut filter) = self.tool_filter
+            && let Err(e) = filter.save_profile()
+        {
+            warn!("Failed to save agent profile: {e}");
+        }
+
         // ── Emit SessionFinishing ──
         self.event_handler
             .on_event(&HarnessEvent::SessionFinishing {
@@ -680,7 +1260,36 @@ impl<'a> Harness<'a> {
             self.event_handler,
         );
 
+        // Title and summarize the session for listings.
+        #[cfg(feature = "checkpoint")]
+        if let Some(ref model) = self.config.session.title_model
+            && let (Some(mgr), Some(manifest)) =
+                (&modules.session_manager, &mut modules.session_manifest)
+            && let Some(task) = first_user_text(&result.messages)
+        {
+            let answer = result.text_output.last().map_or("", String::as_str);
+            match crate::agent::session::generate_session_title(
+                self.client,
+                model,
+                self.config.provider.as_ref(),
+                task,
+                answer,
+            )
+            .await
+            {
+                Ok((title, summary)) => {
+                    manifest.title = Some(title);
+                    manifest.summary = Some(summary);
+                    if let Err(e) = mgr.save_manifest(manifest) {
+                        warn!("Failed to save session title: {e}");
+                    }
+                }
+                Err(e) => warn!("Session title generation failed: {e}"),
+            }
+        }
+
         // Post-session memory consolidation.
+        #[cfg(feature = "memory")]
         if let Some(ref memory_path) = self.config.memory_config.memory_file {
             let model = self
                 .config
@@ -695,6 +1304,7 @@ impl<'a> Harness<'a> {
                 memory_path,
                 self.config.memory_config.max_memory_lines,
                 model,
+                self.config.provider.as_ref(),
             )
             .await
             {
@@ -724,14 +1334,39 @@ impl<'a> Harness<'a> {
 pub(crate) struct ModuleState {
     pub(crate) summarizer: Option<Summarizer>,
     pub(crate) tool_metas: Vec<ToolResultMeta>,
+    #[cfg(feature = "checkpoint")]
     pub(crate) session_manager: Option<SessionManager>,
+    #[cfg(feature = "checkpoint")]
     pub(crate) session_manifest: Option<SessionManifest>,
+    #[cfg(feature = "checkpoint")]
     pub(crate) cleanup_on_success: bool,
     pub(crate) tool_cache: Option<ToolResultCache>,
+    pub(crate) disk_cache: Option<DiskToolCache>,
+    /// Per-tool call statistics for the run.
+    pub(crate) tool_stats: BTreeMap<String, ToolStats>,
+    /// `(tool, USD)` costs of paid calls not yet added to the run's
+    /// cost tracker.
+    pub(crate) tool_costs: Vec<(String, f64)>,
     pub(crate) reminders: ReminderRegistry,
     pub(crate) file_tracker: Option<FileAccessTracker>,
     /// Tools whose extended descriptions have already been injected.
     pub(crate) expanded_tools: HashSet<String>,
+    /// Speculative prefetch predictions (when enabled).
+    pub(crate) speculation: Option<Speculation>,
+    /// Repeated tool-call tracking (when enabled).
+    pub(crate) loop_detector: Option<LoopDetector>,
+    /// Repeated tool-failure tracking (when enabled).
+    pub(crate) failure_escalation: Option<FailureEscalation>,
+    /// Per-category call counts (when any category has a limit).
+    pub(crate) category_budgets: Option<CategoryBudgets>,
+    /// Cold store for compacted messages (when an archive dir is configured).
+    pub(crate) history_archive: Option<HistoryArchive>,
+    /// Deadline tracking (when a pacing deadline is set).
+    pub(crate) pacer: Option<Pacer>,
+    /// Audit log of consequential tool calls (when enabled).
+    pub(crate) audit_log: Option<AuditLog>,
+    /// Pre-mutation file snapshots for the change report (when enabled).
+    pub(crate) snapshot_store: Option<SnapshotStore>,
 }
 
 /// Values accumulated across rounds during a harness run.
@@ -745,16 +1380,24 @@ struct RunAccumulator {
     cost_tracker: crate::api::tracing::CostTracker,
     rounds_used: u32,
     finished: bool,
+    budget_exceeded: bool,
+    prefix_analyzer: crate::api::tracing::PrefixAnalyzer,
 }
 
 /// Initialize all optional modules from the harness configuration.
 fn init_modules(config: &HarnessConfig) -> ModuleState {
+    let mut reminders = ReminderRegistry::with_defaults();
+    for reminder in &config.reminders {
+        reminders.add(reminder.clone());
+    }
+
     let summarizer = if config.summarizer.enabled {
         Some(Summarizer::new(config.summarizer.config.clone()))
     } else {
         None
     };
 
+    #[cfg(feature = "checkpoint")]
     let session_manager = if config.session.enabled {
         match SessionManager::new(&config.session.sessions_dir) {
             Ok(mgr) => Some(mgr),
@@ -769,8 +1412,19 @@ fn init_modules(config: &HarnessConfig) -> ModuleState {
         None
     };
 
+    let disk_cache = match config.cache.persistent {
+        Some(ref p) if config.cache.enabled => {
+            let cache = DiskToolCache::new(&p.dir, &p.workdir).with_ttl(p.ttl);
+            let pruned = cache.prune_expired();
+            if pruned > 0 {
+                debug!("Pruned {pruned} expired disk cache entries");
+            }
+            Some(cache)
+        }
+        _ => None,
+    };
     let tool_cache = if config.cache.enabled {
-        Some(ToolResultCache::new(config.cache.max_entries))
+        Some(ToolResultCache::new(config.cache.max_entries).with_workdir(&config.cache.workdir))
     } else {
         None
     };
@@ -778,13 +1432,37 @@ fn init_modules(config: &HarnessConfig) -> ModuleState {
     ModuleState {
         summarizer,
         tool_metas: Vec::new(),
+        #[cfg(feature = "checkpoint")]
         session_manager,
+        #[cfg(feature = "checkpoint")]
         session_manifest: None,
+        #[cfg(feature = "checkpoint")]
         cleanup_on_success: config.session.cleanup_on_success,
         tool_cache,
-        reminders: ReminderRegistry::with_defaults(),
+        disk_cache,
+        tool_stats: BTreeMap::new(),
+        tool_costs: Vec::new(),
+        reminders,
         file_tracker: Some(FileAccessTracker::new(5)),
         expanded_tools: HashSet::new(),
+        speculation: config
+            .speculation
+            .enabled
+            .then(|| Speculation::new(config.speculation.clone())),
+        loop_detector: config
+            .loop_detection
+            .enabled
+            .then(|| LoopDetector::new(config.loop_detection.clone())),
+        failure_escalation: config
+            .failure_escalation
+            .enabled
+            .then(|| FailureEscalation::new(config.failure_escalation.threshold)),
+        category_budgets: Some(CategoryBudgets::new(&config.category_budgets))
+            .filter(|b| !b.is_empty()),
+        history_archive: None,
+        pacer: Pacer::new(config.pacing.clone()),
+        audit_log: None,
+        snapshot_store: config.change_report.enabled.then(SnapshotStore::new),
     }
 }
 
@@ -806,6 +1484,10 @@ fn init_modules(config: &HarnessConfig) -> ModuleState {
 /// Sections are only registered when their source data is `Some` and
 /// non-empty, so the assembled prompt never contains empty headings.
 ///
+/// The harness adds the dynamic **Git Status** section (priority 50) itself
+/// when [`HarnessConfig::git_context`] is enabled, since reading it runs
+/// `git`.
+///
 /// # Examples
 ///
 /// Use the default registry via the harness (simplest path):
@@ -936,13 +1618,14 @@ fn finalize_run(
     modules: &mut ModuleState,
     event_handler: &dyn EventHandler,
 ) -> HarnessResult {
-    if !acc.finished {
+    if !acc.finished && !acc.budget_exceeded {
         event_handler.on_event(&HarnessEvent::RoundLimitReached {
             max_rounds: config.max_rounds,
         });
     }
 
     // Finalize session manifest.
+    #[cfg(feature = "checkpoint")]
     if let Some(ref mut manifest) = modules.session_manifest {
         manifest.status = if acc.finished {
             SessionStatus::Completed
@@ -975,6 +1658,9 @@ fn finalize_run(
         acc.rounds_used,
         acc.cost_tracker.summary()
     );
+    if !acc.prefix_analyzer.report().breaks.is_empty() {
+        info!("Prompt cache: {}", acc.prefix_analyzer.report().summary());
+    }
 
     // Try to parse structured output from the last text output.
     let structured_output = if config.output_schema.is_some() {
@@ -994,8 +1680,22 @@ fn finalize_run(
         total_completion_tokens: acc.cost_tracker.total_completion_tokens,
         rounds_used: acc.rounds_used,
         finished: acc.finished,
+        budget_exceeded: acc.budget_exceeded,
         estimated_cost_usd: acc.cost_tracker.estimated_cost_usd,
+        tool_costs_usd: acc.cost_tracker.tool_costs_usd,
         structured_output,
+        prompt_cache: acc.prefix_analyzer.into_report(),
+        tool_stats: std::mem::take(&mut modules.tool_stats),
+        files_changed: modules
+            .snapshot_store
+            .as_ref()
+            .map(|store| {
+                store.changes(
+                    &config.change_report.workdir,
+                    config.change_report.include_diffs,
+                )
+            })
+            .unwrap_or_default(),
     }
 }
 
@@ -1003,7 +1703,8 @@ fn finalize_run(
 ///
 /// Operates on the [`ContextLayout`] by modifying messages in-place via
 /// [`message_at_mut()`](ContextLayout::message_at_mut). Tool result metadata
-/// indices correspond to positions in `to_messages()` output.
+/// indices correspond to positions in `to_messages()` output. Returns the
+/// number of results evicted and the characters freed.
 fn evict_if_needed(
     config: &HarnessConfig,
     budget: &Option<ContextBudget>,
@@ -1011,15 +1712,15 @@ fn evict_if_needed(
     tool_metas: &[ToolResultMeta],
     round: u32,
     event_handler: &dyn EventHandler,
-) {
+) -> (usize, usize) {
     if !config.eviction.enabled || tool_metas.is_empty() {
-        return;
+        return (0, 0);
     }
-    let Some(budget) = budget else { return };
+    let Some(budget) = budget else { return (0, 0) };
     let api_messages = layout.to_messages();
     let usage = budget.estimate_usage(&api_messages);
     if usage.usage_pct < 0.80 {
-        return;
+        return (0, 0);
     }
     let target_tokens = (budget.effective_max_tokens() as f64 * 0.60) as usize;
 
@@ -1078,6 +1779,7 @@ fn evict_if_needed(
             evicted_count,
         });
     }
+    (evicted_count, freed)
 }
 
 /// Summarize (compact) middle-zone messages when context usage is still over 80% after eviction.
@@ -1087,7 +1789,7 @@ fn evict_if_needed(
 /// [`apply_compaction()`](ContextLayout::apply_compaction).
 ///
 /// This is a thin wrapper around [`compact_if_needed()`] called at the start of
-/// each round.
+/// each round. Returns `true` if compaction was performed.
 #[allow(clippy::too_many_arguments)]
 async fn summarize_if_needed(
     config: &HarnessConfig,
@@ -1100,7 +1802,8 @@ async fn summarize_if_needed(
     round: u32,
     event_handler: &dyn EventHandler,
     file_tracker: &Option<FileAccessTracker>,
-) {
+    history_archive: &Option<HistoryArchive>,
+) -> bool {
     compact_if_needed(
         config,
         client,
@@ -1112,8 +1815,9 @@ async fn summarize_if_needed(
         round,
         event_handler,
         file_tracker,
+        history_archive,
     )
-    .await;
+    .await
 }
 
 /// Core compaction logic: summarize middle-zone messages when context usage exceeds 80%.
@@ -1133,6 +1837,7 @@ pub(crate) async fn compact_if_needed(
     round: u32,
     event_handler: &dyn EventHandler,
     file_tracker: &Option<FileAccessTracker>,
+    history_archive: &Option<HistoryArchive>,
 ) -> bool {
     if !config.summarizer.enabled {
         return false;
@@ -1171,75 +1876,160 @@ pub(crate) async fn compact_if_needed(
     }
 
     // Pass the existing compressed history to the summarizer for merging.
+    // (Structured-state summaries keep their own state instead.)
     if let Some(existing) = layout.compressed_history() {
         summ.summary = Some(existing.to_string());
     }
 
+    // Record the middle zone size before compaction for tool_metas reindexing.
+    let middle_len = layout.middle_len();
+    let prefix_and_history_len =
+        layout.to_messages().len() - layout.middle_len() - layout.recency_window_len();
+
+    let summary_text = match summ.summarize_locally(middle, &preservation_notes) {
+        Some(text) => text,
+        None => {
+            let pinned = PinnedEntities {
+                files: file_tracker
+                    .as_ref()
+                    .map(|t| t.paths().map(str::to_string).collect())
+                    .unwrap_or_default(),
+                keywords: extract_task_keywords(layout.prefix()),
+            };
+            let summary = summarize_with_llm(
+                config,
+                client,
+                summ,
+                middle,
+                &preservation_notes,
+                &pinned,
+                model_for_round,
+                event_handler,
+            )
+            .await;
+            match summary {
+                Some(text) => text,
+                None => return false,
+            }
+        }
+    };
+
+    // Use layout's compaction API — this clears the middle zone and
+    // sets the compressed history. The summarizer already merged the
+    // existing summary into the new one.
+    let current_round = summ.boundary_index; // approximate
+    let compacted = history_archive
+        .as_ref()
+        .map(|_| layout.compactable_messages().to_vec());
+    layout.apply_compaction(summary_text.clone(), current_round);
+    summ.apply_summary(summary_text, 0);
+
+    let compaction_number = layout.compaction_count();
+    if let (Some(archive), Some(messages)) = (history_archive, compacted)
+        && let Err(e) = archive.store(compaction_number, &messages)
+    {
+        warn!("Failed to archive compacted history: {e}");
+    }
+    event_handler.on_event(&HarnessEvent::Compaction { compaction_number });
+
+    // Invalidate tool_metas that were in the compacted middle zone
+    // and reindex remaining ones.
+    let middle_start = prefix_and_history_len;
+    let middle_end = middle_start + middle_len;
+    tool_metas.retain(|m| m.message_index < middle_start || m.message_index >= middle_end);
+    // After compaction, the middle is gone and compressed_history now
+    // takes 2 message slots. Adjust indices for messages that were
+    // after the old middle zone.
+    let new_history_slots = 2;
+    let old_history_slots = if layout.compaction_count() > 1 { 2 } else { 0 };
+    let shift = middle_len + old_history_slots;
+    for meta in tool_metas.iter_mut() {
+        if meta.message_index >= middle_end {
+            meta.message_index = meta.message_index.saturating_sub(shift) + new_history_slots;
+        }
+    }
+
+    if layout.history_needs_recompaction() {
+        recompact_history(config, client, layout, summ, model_for_round).await;
+    }
+    true
+}
+
+/// Summarize `middle` with a one-shot LLM call, merging the existing
+/// summary. A summary failing the quality guard is retried with the reason
+/// up to `quality_retries` times. Returns `None` (after logging) if the
+/// call fails or no acceptable summary comes back.
+#[allow(clippy::too_many_arguments)]
+async fn summarize_with_llm(
+    config: &HarnessConfig,
+    client: &OpenRouterClient,
+    summ: &mut Summarizer,
+    middle: &[Message],
+    preservation_notes: &[String],
+    pinned: &PinnedEntities,
+    model_for_round: &str,
+    event_handler: &dyn EventHandler,
+) -> Option<String> {
     let (sys_prompt, mut user_prompt) = summ.build_summarization_request(middle);
 
     // Append preservation notes to the summarization prompt.
     if !preservation_notes.is_empty() {
         user_prompt.push_str("\n\n=== PRESERVATION NOTES ===\n");
-        for note in &preservation_notes {
+        for note in preservation_notes {
             user_prompt.push_str(note);
             user_prompt.push('\n');
         }
     }
     let summary_model = summ.summary_model(model_for_round).to_string();
 
-    let summary_request = ChatRequest {
-        model: Some(summary_model),
-        messages: vec![Message::system(&sys_prompt), Message::user(&user_prompt)],
-        max_tokens: summ.config.max_summary_tokens,
-        temperature: 0.3,
-        ..Default::default()
-    };
-
-    // Record the middle zone size before compaction for tool_metas reindexing.
-    let middle_len = layout.middle_len();
-    let prefix_and_history_len =
-        layout.to_messages().len() - layout.middle_len() - layout.recency_window_len();
+    let mut rejection: Option<String> = None;
+    for attempt in 0..=summ.config.quality_retries {
+        let mut prompt = user_prompt.clone();
+        if let Some(ref reason) = rejection {
+            prompt.push_str(&format!(
+                "\n\n=== PREVIOUS ATTEMPT REJECTED ===\n{reason}. Write the summary again.\n"
+            ));
+        }
+        let summary_request = ChatRequest {
+            model: Some(summary_model.clone()),
+            messages: vec![Message::system(&sys_prompt), Message::user(&prompt)],
+            max_tokens: summ.config.max_summary_tokens,
+            temperature: 0.3,
+            provider: config.provider.clone(),
+            ..Default::default()
+        };
 
-    match client.chat(&summary_request).await {
-        Ok(completion) => {
-            if let Some(ref summary_text) = completion.content {
-                // Use layout's compaction API — this clears the middle zone and
-                // sets the compressed history. The summarizer already merged the
-                // existing summary into the new one.
-                let current_round = summ.boundary_index; // approximate
-                layout.apply_compaction(summary_text.clone(), current_round);
-                summ.apply_summary(summary_text.clone(), 0);
-
-                let compaction_number = layout.compaction_count();
-                event_handler.on_event(&HarnessEvent::Compaction { compaction_number });
-
-                // Invalidate tool_metas that were in the compacted middle zone
-                // and reindex remaining ones.
-                let middle_start = prefix_and_history_len;
-                let middle_end = middle_start + middle_len;
-                tool_metas
-                    .retain(|m| m.message_index < middle_start || m.message_index >= middle_end);
-                // After compaction, the middle is gone and compressed_history now
-                // takes 2 message slots. Adjust indices for messages that were
-                // after the old middle zone.
-                let new_history_slots = 2;
-                let old_history_slots = if layout.compaction_count() > 1 { 2 } else { 0 };
-                let shift = middle_len + old_history_slots;
-                for meta in tool_metas.iter_mut() {
-                    if meta.message_index >= middle_end {
-                        meta.message_index =
-                            meta.message_index.saturating_sub(shift) + new_history_slots;
-                    }
-                }
-                return true;
+        let response = match client.chat(&summary_request).await {
+            Ok(completion) => completion.content?,
+            Err(e) => {
+                warn!("Summarization failed: {e}. Continuing without compaction.");
+                return None;
             }
-            false
+        };
+        let previous_state = summ.state.clone();
+        let text = match summ.summary_from_response(&response) {
+            Ok(text) => text,
+            Err(e) => {
+                warn!("{e}. Continuing without compaction.");
+                return None;
+            }
+        };
+        if !summ.config.quality_guard {
+            return Some(text);
         }
-        Err(e) => {
-            warn!("Summarization failed: {e}. Continuing without compaction.");
-            false
+        match summ.check_summary(&text, middle, pinned) {
+            Ok(()) => return Some(text),
+            Err(reason) => {
+                summ.state = previous_state;
+                event_handler.on_event(&HarnessEvent::CompactionRejected {
+                    reason: &reason,
+                    retrying: attempt < summ.config.quality_retries,
+                });
+                rejection = Some(reason);
+            }
         }
     }
+    None
 }
 
 /// Whether the main loop should `continue` to the next round after a plan transition.
@@ -1271,7 +2061,9 @@ async fn handle_plan_submission(
             .and_then(|v| v.get("summary").and_then(|s| s.as_str()).map(String::from))
             .unwrap_or_else(|| plan_summary.clone());
 
-        layout.push_message(Message::assistant_tool_calls(completion.tool_calls.clone()));
+        layout.push_message(Message::assistant_tool_calls(history_tool_calls(
+            &completion.tool_calls,
+        )));
 
         for call in &completion.tool_calls {
             if PlanExecuteConfig::is_plan_submission(&call.function.name) {
@@ -1323,9 +2115,135 @@ async fn handle_plan_submission(
     None
 }
 
+/// Second-level compaction: re-summarize the compressed history itself
+/// with a stricter budget once it outgrows
+/// [`ContextLayout::history_budget()`]. Local summary strategies keep the
+/// most recent lines that fit instead. Leaves the history unchanged if the
+/// call fails or the result isn't smaller.
+async fn recompact_history(
+    config: &HarnessConfig,
+    client: &OpenRouterClient,
+    layout: &mut ContextLayout,
+    summ: &mut Summarizer,
+    model_for_round: &str,
+) {
+    let Some(history) = layout.compressed_history().map(str::to_string) else {
+        return;
+    };
+    let before = layout.compressed_history_tokens();
+    let target =
+        (layout.history_budget() as f64 * summ.config.recompaction_target_fraction) as usize;
+    if let Some(condensed) = summ.recompact_locally(&history, target) {
+        layout.apply_history_recompaction(condensed.clone());
+        summ.apply_summary(condensed, 0);
+        return;
+    }
+    let (sys_prompt, user_prompt) = summ.build_recompaction_request(&history, target);
+    let request = ChatRequest {
+        model: Some(summ.summary_model(model_for_round).to_string()),
+        messages: vec![Message::system(&sys_prompt), Message::user(&user_prompt)],
+        max_tokens: summ.config.max_summary_tokens,
+        temperature: 0.3,
+        provider: config.provider.clone(),
+        ..Default::default()
+    };
+
+    let response = match client.chat(&request).await {
+        Ok(completion) => completion.content.unwrap_or_default(),
+        Err(e) => {
+            warn!("History re-summarization failed: {e}. Keeping the current history.");
+            return;
+        }
+    };
+    let previous_state = summ.state.clone();
+    let condensed = match summ.summary_from_recompaction(&response) {
+        Ok(text) if !text.is_empty() && text.len() < history.len() => text,
+        Ok(_) => {
+            summ.state = previous_state;
+            warn!("History re-summarization did not shrink the history. Keeping it.");
+            return;
+        }
+        Err(e) => {
+            warn!("{e}. Keeping the current history.");
+            return;
+        }
+    };
+    layout.apply_history_recompaction(condensed.clone());
+    summ.apply_summary(condensed, 0);
+    info!(
+        "Compressed history re-summarized (generation {}): {before} -> {} tokens",
+        layout.history_generation(),
+        layout.compressed_history_tokens()
+    );
+}
+
 // ── Small helpers ──────────────────────────────────────────────────
 
-/// Convert tool defs to `Option`, returning `None` if empty.
+/// Add fired reminders to this round's request, returning the messages to
+/// send. `UserMessage` stores them in the layout; `EphemeralSystem` appends
+/// them to the request only, after the last stored message, so the preceding
+/// prefix is identical to last round's.
+fn inject_reminders(
+    injection: ReminderInjection,
+    layout: &mut ContextLayout,
+    api_messages: Vec<Message>,
+    reminder_texts: &[String],
+) -> Vec<Message> {
+    if reminder_texts.is_empty() {
+        return api_messages;
+    }
+    match injection {
+        ReminderInjection::UserMessage => {
+            for text in reminder_texts {
+                layout.push_message(Message::user(text));
+            }
+            layout.to_messages()
+        }
+        ReminderInjection::EphemeralSystem => {
+            let mut messages = api_messages;
+            messages.extend(reminder_texts.iter().map(Message::system));
+            messages
+        }
+    }
+}
+
+/// Tool calls as stored in history: scratchpad writes keep a placeholder
+/// instead of their content, which lives in the tool.
+fn history_tool_calls(calls: &[crate::ToolCall]) -> Vec<crate::ToolCall> {
+    calls
+        .iter()
+        .cloned()
+        .map(|mut call| {
+            if call.function.name == crate::tools::names::SCRATCHPAD
+                && let Some(arguments) = ScratchpadTool::elide_content(&call.function.arguments)
+            {
+                call.function.arguments = arguments;
+            }
+            call
+        })
+        .collect()
+}
+
+/// Registered tools missing from the tools offered this round.
+fn withheld_tools(registered: &[crate::ToolDef], offered: Option<&[crate::ToolDef]>) -> usize {
+    let offered: HashSet<&str> = offered
+        .unwrap_or_default()
+        .iter()
+        .map(|d| d.function.name.as_str())
+        .collect();
+    registered
+        .iter()
+        .filter(|d| !offered.contains(d.function.name.as_str()))
+        .count()
+}
+
+/// Run totals of cache hits and errors across all tools.
+fn cache_hits_and_errors(stats: &BTreeMap<String, ToolStats>) -> (u32, u32) {
+    stats.values().fold((0, 0), |(hits, errors), s| {
+        (hits + s.cache_hits, errors + s.errors)
+    })
+}
+
 fn non_empty_tools(defs: &[crate::ToolDef]) -> Option<Vec<crate::ToolDef>> {
     if defs.is_empty() {
         None
@@ -1336,25 +2254,88 @@ fn non_empty_tools(defs: &[crate::ToolDef]) -> Option<Vec<crate::ToolDef>> {
 
 /// Extract simple keywords from the first user message for tool filtering.
 fn extract_task_keywords(messages: &[Message]) -> Vec<String> {
-    for msg in messages {
-        if matches!(msg.role, crate::MessageRole::User)
-            && let Some(ref content) = msg.content
-        {
-            // Simple keyword extraction: split on whitespace, take significant words.
-            return content
-                .split_whitespace()
-                .filter(|w| w.len() > 3)
-                .take(10)
-                .map(|w| {
-                    w.to_lowercase()
-                        .trim_matches(|c: char| !c.is_alphanumeric())
-                        .to_string()
-                })
-                .filter(|w| !w.is_empty())
-                .collect();
-        }
+    let Some(content) = first_user_text(messages) else {
+        return Vec::new();
+    };
+    // Simple keyword extraction: split on whitespace, take significant words.
+    content
+        .split_whitespace()
+        .filter(|w| w.len() > 3)
+        .take(10)
+        .map(|w| {
+            w.to_lowercase()
+                .trim_matches(|c: char| !c.is_alphanumeric())
+                .to_string()
+        })
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+/// Run the configured warm-start steps and return the prompt section body,
+/// emitting start and finish events.
+async fn warm_start(
+    config: &HarnessConfig,
+    messages: &[Message],
+    event_handler: &dyn EventHandler,
+) -> Option<String> {
+    let steps: Vec<String> = config
+        .warm_start
+        .steps
+        .iter()
+        .map(|s| s.name().to_string())
+        .collect();
+    event_handler.on_event(&HarnessEvent::WarmStartStarted { steps: &steps });
+
+    let input = WarmStartInput {
+        workdir: config.warm_start.workdir.clone(),
+        task: first_user_text(messages).unwrap_or_default().to_string(),
+        keywords: extract_task_keywords(messages),
+    };
+    let report = super::warm_start::gather(&config.warm_start, input).await;
+    event_handler.on_event(&HarnessEvent::WarmStartFinished {
+        gathered: &report.gathered(),
+        missing: &report.missing,
+        elapsed: report.elapsed,
+    });
+    report.render()
+}
+
+/// Insert call examples of the offered tools before the first user message,
+/// unless an earlier run (a resumed session) already did.
+fn insert_few_shot_examples(
+    messages: &mut Vec<Message>,
+    tools: &ToolSet,
+    offered: &[crate::ToolDef],
+) {
+    let already_present = messages.iter().any(|m| {
+        m.tool_call_id
+            .as_deref()
+            .is_some_and(|id| id.starts_with(FEW_SHOT_CALL_ID_PREFIX))
+    });
+    if already_present {
+        return;
     }
-    Vec::new()
+    let examples: Vec<_> = tools
+        .call_examples()
+        .into_iter()
+        .filter(|(name, _)| offered.iter().any(|d| d.function.name == *name))
+        .collect();
+    let demonstrations = few_shot_messages(&examples);
+    let at = messages
+        .iter()
+        .position(|m| matches!(m.role, crate::MessageRole::User))
+        .unwrap_or(messages.len());
+    messages.splice(at..at, demonstrations);
+}
+
+/// Content of the first user message (the task), skipping the few-shot
+/// preamble.
+fn first_user_text(messages: &[Message]) -> Option<&str> {
+    messages
+        .iter()
+        .filter(|m| matches!(m.role, crate::MessageRole::User))
+        .filter_map(|m| m.content.as_deref())
+        .find(|content| *content != FEW_SHOT_PREAMBLE)
 }
 
 // ── Tests ──────────────────────────────────────────────────────────
@@ -1365,6 +2346,9 @@ mod tests {
     use super::super::events::*;
     use super::super::execution::*;
     use super::*;
+    use crate::test_support::{
+        ScriptedTransport, finished_reply, test_config, text_reply, tool_call_reply,
+    };
 
     #[test]
     fn harness_config_default() {
@@ -1420,8 +2404,13 @@ mod tests {
             total_completion_tokens: 50,
             rounds_used: 2,
             finished: true,
+            budget_exceeded: false,
             estimated_cost_usd: 0.001,
+            tool_costs_usd: Default::default(),
             structured_output: None,
+            prompt_cache: Default::default(),
+            tool_stats: Default::default(),
+            files_changed: Vec::new(),
         };
         assert_eq!(result.text(), "hello\n\nworld");
         assert_eq!(result.total_tokens(), 150);
@@ -1570,6 +2559,72 @@ mod tests {
         assert!(config.progressive_tools);
     }
 
+    #[test]
+    fn speculative_prefetch_is_opt_in() {
+        let config = HarnessConfig::default();
+        assert!(!config.speculation.enabled);
+        assert!(init_modules(&config).speculation.is_none());
+
+        let config = HarnessConfig::new("test-model", "prompt").with_speculative_prefetch(true);
+        assert!(config.speculation.enabled);
+        assert!(config.speculation.tools.iter().any(|t| t == "read_file"));
+        assert!(init_modules(&config).speculation.is_some());
+    }
+
+    #[test]
+    fn custom_reminders_join_defaults() {
+        let defaults = init_modules(&HarnessConfig::default()).reminders.len();
+        let config = HarnessConfig::default().with_reminder(
+            crate::agent::prompt::SystemReminder::every_n_rounds("run-tests", 5, "Run the tests."),
+        );
+        assert_eq!(init_modules(&config).reminders.len(), defaults + 1);
+    }
+
+    #[test]
+    fn ephemeral_reminders_are_not_stored() {
+        let reminders = vec!["Run the tests.".to_string()];
+        let mut layout = ContextLayout::new(100_000);
+        layout.set_prefix(vec![Message::system("sys"), Message::user("task")]);
+
+        let current = layout.to_messages();
+        let sent = inject_reminders(
+            ReminderInjection::EphemeralSystem,
+            &mut layout,
+            current,
+            &reminders,
+        );
+        assert_eq!(sent.len(), 3);
+        assert_eq!(sent[2].role, crate::MessageRole::System);
+        assert_eq!(layout.to_messages().len(), 2);
+
+        let current = layout.to_messages();
+        let sent = inject_reminders(
+            ReminderInjection::UserMessage,
+            &mut layout,
+            current,
+            &reminders,
+        );
+        assert_eq!(sent[2].role, crate::MessageRole::User);
+        assert_eq!(layout.to_messages().len(), 3);
+    }
+
+    #[test]
+    fn persistent_tool_cache_is_opt_in() {
+        assert!(init_modules(&HarnessConfig::default()).disk_cache.is_none());
+        let dir = tempfile::tempdir().unwrap();
+        let config = HarnessConfig::default()
+            .with_persistent_tool_cache(dir.path().join("cache"), dir.path());
+        assert!(init_modules(&config).disk_cache.is_some());
+    }
+
+    #[test]
+    fn max_cost_builder() {
+        let config = HarnessConfig::new("test-model", "prompt");
+        assert!(config.max_cost_usd.is_none());
+        let config = config.with_max_cost_usd(0.5);
+        assert_eq!(config.max_cost_usd, Some(0.5));
+    }
+
     #[test]
     fn harness_config_builder_methods() {
         let config = HarnessConfig::new("test-model", "system prompt")
@@ -1882,6 +2937,7 @@ mod tests {
         let response = handler.on_event(&HarnessEvent::ApprovalRequired {
             name: "shell",
             arguments: "{}",
+            call_id: "c1",
         });
         assert!(response.is_none());
     }
@@ -2053,6 +3109,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn provider_builders_compose() {
+        let config = HarnessConfig::new("m", "")
+            .with_provider_order(["Anthropic", "Google"])
+            .with_allow_fallbacks(false)
+            .with_data_collection(crate::DataCollection::Deny)
+            .with_zero_data_retention(true);
+        let json = serde_json::to_value(config.provider.unwrap()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "order": ["Anthropic", "Google"],
+                "allow_fallbacks": false,
+                "data_collection": "deny",
+                "zdr": true,
+            })
+        );
+    }
+
     #[test]
     fn use_prompt_registry_default_false() {
         let config = HarnessConfig::default();
@@ -2064,4 +3139,827 @@ mod tests {
         let config = HarnessConfig::new("test-model", "prompt").with_prompt_registry(true);
         assert!(config.use_prompt_registry);
     }
+
+    async fn run_scripted(
+        config: HarnessConfig,
+        replies: Vec<String>,
+    ) -> (HarnessResult, Vec<String>) {
+        let transport = ScriptedTransport::new(replies);
+        let requests = transport.requests();
+        let client = OpenRouterClient::with_transport("key", transport);
+        let tools = ToolSet::new();
+        let mut config = config.with_streaming(false).with_memory_prompt(None);
+        config.session = HarnessSessionConfig::disabled();
+        config.plan_execute = HarnessPlanExecuteConfig::disabled();
+        let result = Harness::new(&client, &tools, config)
+            .run(vec![Message::user("write it")])
+            .await
+            .unwrap();
+        let requests = requests.lock().unwrap().clone();
+        (result, requests)
+    }
+
+    #[tokio::test]
+    async fn truncated_answers_are_continued_and_stitched() {
+        let (result, requests) = run_scripted(
+            HarnessConfig::new("test/model", ""),
+            vec![
+                finished_reply("The answer is forty", "length"),
+                text_reply("-two."),
+            ],
+        )
+        .await;
+
+        assert!(result.finished);
+        assert_eq!(result.text_output, ["The answer is forty-two."]);
+        assert!(requests[1].contains("cut off by the output token limit"));
+        assert!(requests[1].contains("The answer is forty"));
+    }
+
+    #[test]
+    fn scratchpad_writes_are_elided_from_history() {
+        let call = |name: &str, arguments: &str| {
+            crate::api::interop::tool_call("c1", name, arguments.to_string())
+        };
+        let calls = [
+            call(
+                crate::tools::names::SCRATCHPAD,
+                r#"{"action":"write","key":"k","content":"a long note"}"#,
+            ),
+            call(crate::tools::names::THINK, r#"{"reasoning":"a long note"}"#),
+        ];
+        let stored = history_tool_calls(&calls);
+        assert!(!stored[0].function.arguments.contains("a long note"));
+        assert_eq!(stored[1].function.arguments, calls[1].function.arguments);
+    }
+
+    #[tokio::test]
+    async fn warm_start_context_is_added_to_the_system_prompt() {
+        use crate::agent::config::HarnessWarmStartConfig;
+        use crate::agent::warm_start::WarmStartStep;
+
+        let transport = ScriptedTransport::new(vec![text_reply("done")]);
+        let requests = transport.requests();
+        let client = OpenRouterClient::with_transport("key", transport);
+        let tools = ToolSet::new();
+        let warm_start = HarnessWarmStartConfig::default()
+            .with_step(WarmStartStep::custom("Task echo", |input| async move {
+                Some(format!("task was: {}", input.task))
+            }))
+            .with_step(WarmStartStep::custom("Nothing", |_| async { None }));
+        let config = test_config("").with_warm_start(warm_start);
+        Harness::new(&client, &tools, config)
+            .run(vec![
+                Message::system("You are an agent."),
+                Message::user("write it"),
+            ])
+            .await
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        let body: serde_json::Value = serde_json::from_str(&requests[0]).unwrap();
+        let system = body["messages"][0]["content"].as_str().unwrap();
+        assert_eq!(
+            system,
+            "You are an agent.\n\n## Workspace Context\n\n### Task echo\n\ntask was: write it"
+        );
+    }
+
+    #[tokio::test]
+    async fn git_status_is_read_when_the_run_starts() {
+        use crate::agent::config::HarnessGitContextConfig;
+
+        let transport = ScriptedTransport::new(vec![text_reply("done")]);
+        let requests = transport.requests();
+        let client = OpenRouterClient::with_transport("key", transport);
+        let tools = ToolSet::new();
+        let config = test_config("").with_prompt_registry(true).with_git_context(
+            HarnessGitContextConfig::for_workdir(env!("CARGO_MANIFEST_DIR")),
+        );
+        Harness::new(&client, &tools, config)
+            .run(vec![Message::system("Preamble"), Message::user("hi")])
+            .await
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        let body: serde_json::Value = serde_json::from_str(&requests[0]).unwrap();
+        let system = body["messages"][0]["content"].as_str().unwrap();
+        assert!(system.contains("## Git Status\n\nBranch: "), "{system}");
+    }
+
+    #[cfg(feature = "checkpoint")]
+    #[tokio::test]
+    async fn session_title_and_summary_are_saved_after_the_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let transport = ScriptedTransport::new(vec![
+            text_reply("done"),
+            text_reply("Title: Fix the parser\nSummary: Fixed it."),
+        ]);
+        let client = OpenRouterClient::with_transport("key", transport);
+        let tools = ToolSet::new();
+        let mut config = HarnessConfig::new("test/model", "")
+            .with_memory_prompt(None)
+            .with_session_titles("test/cheap");
+        config.session.sessions_dir = dir.path().to_path_buf();
+        config.plan_execute = HarnessPlanExecuteConfig::disabled();
+        Harness::new(&client, &tools, config)
+            .run(vec![Message::user("fix the parser")])
+            .await
+            .unwrap();
+
+        let sessions = SessionManager::new(dir.path())
+            .unwrap()
+            .list_sessions()
+            .unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].title.as_deref(), Some("Fix the parser"));
+        assert_eq!(sessions[0].summary.as_deref(), Some("Fixed it."));
+    }
+
+    #[cfg(feature = "checkpoint")]
+    #[tokio::test]
+    async fn checkpoints_keep_the_structured_state() {
+        use crate::agent::checkpoint::Checkpoint;
+        use crate::context::summarizer::SummaryStrategy;
+
+        let dir = tempfile::tempdir().unwrap();
+        let transport = ScriptedTransport::new(vec![
+            tool_call_reply("c1", "think", &serde_json::json!({})),
+            text_reply("done"),
+        ]);
+        let client = OpenRouterClient::with_transport("key", transport);
+        let tools = ToolSet::new();
+        let mut config = HarnessConfig::new("test/model", "")
+            .with_streaming(false)
+            .with_memory_prompt(None);
+        config.session.sessions_dir = dir.path().to_path_buf();
+        config.plan_execute = HarnessPlanExecuteConfig::disabled();
+        config.summarizer.config.strategy = SummaryStrategy::StructuredState;
+        let state = StructuredState {
+            task: "fix the parser".into(),
+            completed_steps: vec!["found the bug".into()],
+            ..Default::default()
+        };
+
+        let saved = std::sync::Arc::new(std::sync::Mutex::new(None));
+        let seen = std::sync::Arc::clone(&saved);
+        let handler = FnEventHandler::new(move |event| {
+            if let HarnessEvent::CheckpointSaved { path, .. } = event {
+                let json = std::fs::read_to_string(path).unwrap();
+                let checkpoint: Checkpoint =
+                    crate::agent::migration::parse_checkpoint(&json).unwrap();
+                *seen.lock().unwrap() = Some(checkpoint.structured_state);
+            }
+            None
+        });
+        Harness::new(&client, &tools, config)
+            .with_event_handler(&handler)
+            .with_structured_state(state.clone())
+            .run(vec![Message::user("fix the parser")])
+            .await
+            .unwrap();
+
+        assert_eq!(saved.lock().unwrap().take(), Some(Some(state)));
+    }
+
+    #[tokio::test]
+    async fn structured_answers_continue_the_prefill() {
+        let config = HarnessConfig::new("test/model", "")
+            .with_output_schema(serde_json::json!({"type": "object"}))
+            .with_final_answer_prefill("{");
+        let (result, requests) = run_scripted(config, vec![text_reply(r#""a": 1}"#)]).await;
+
+        assert_eq!(result.text_output, [r#"{"a": 1}"#]);
+        assert_eq!(result.structured_output, Some(serde_json::json!({"a": 1})));
+        let body: serde_json::Value = serde_json::from_str(&requests[0]).unwrap();
+        let last = body["messages"].as_array().unwrap().last().unwrap().clone();
+        assert_eq!(last["role"], "assistant");
+        assert_eq!(last["content"], "{");
+    }
+
+    #[tokio::test]
+    async fn prefill_waits_for_the_last_round() {
+        let config = HarnessConfig::new("test/model", "")
+            .with_max_rounds(3)
+            .with_final_answer_prefill("Answer:");
+        let (result, requests) = run_scripted(config, vec![text_reply(" done")]).await;
+
+        assert_eq!(result.text_output, [" done"]);
+        assert!(!requests[0].contains("Answer:"), "{}", requests[0]);
+    }
+
+    #[tokio::test]
+    async fn paid_tool_calls_add_to_run_cost() {
+        struct PaidSearch;
+
+        impl crate::tools::core::Tool for PaidSearch {
+            fn definition(&self) -> crate::ToolDef {
+                crate::ToolDef::new("search", "Search", serde_json::json!({"type": "object"}))
+            }
+
+            fn execute(&self, _arguments: &str) -> crate::tools::core::ToolFuture<'_> {
+                Box::pin(async { "3 results".to_string() })
+            }
+
+            fn call_cost_usd(&self, _arguments: &str, _result: &str) -> f64 {
+                0.25
+            }
+        }
+
+        let transport = ScriptedTransport::new(vec![
+            tool_call_reply("c1", "search", &serde_json::json!({})),
+            text_reply("Found it."),
+        ]);
+        let client = OpenRouterClient::with_transport("key", transport);
+        let tools = ToolSet::new().with(PaidSearch);
+        let config = test_config("");
+        let result = Harness::new(&client, &tools, config)
+            .run(vec![Message::user("find it")])
+            .await
+            .unwrap();
+
+        assert!(result.finished);
+        assert_eq!(result.tool_costs_usd.get("search"), Some(&0.25));
+        assert!(result.estimated_cost_usd >= 0.25);
+    }
+
+    #[tokio::test]
+    async fn approval_can_edit_tool_arguments() {
+        use crate::tools::core::FnTool;
+
+        let transport = ScriptedTransport::new(vec![
+            tool_call_reply("c1", "echo", &serde_json::json!({"text": "rm -rf /"})),
+            text_reply("Done."),
+        ]);
+        let requests = transport.requests();
+        let client = OpenRouterClient::with_transport("key", transport);
+        let echo = FnTool::typed("echo", "Echo text", |args: serde_json::Value| async move {
+            match args["text"].as_str().unwrap_or_default() {
+                "ls" => "echoed ls".to_string(),
+                other => format!("Error: cannot echo {other}"),
+            }
+        });
+        let tools = ToolSet::new().with(echo);
+        let handler = FnEventHandler::new(|event| match event {
+            HarnessEvent::ApprovalRequired { .. } => {
+                Some(EventResponse::ApproveEdited(r#"{"text":"ls"}"#.to_string()))
+            }
+            _ => None,
+        });
+        let config = test_config("").with_approval_required_tools(vec!["echo".into()]);
+        Harness::new(&client, &tools, config)
+            .with_event_handler(&handler)
+            .run(vec![Message::user("clean up")])
+            .await
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        let body: serde_json::Value = serde_json::from_str(&requests[1]).unwrap();
+        let result = body["messages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|m| m["role"] == "tool")
+            .unwrap()["content"]
+            .as_str()
+            .unwrap();
+        assert!(result.starts_with("echoed ls"));
+        assert!(result.contains("[The user edited the arguments before approving."));
+        assert!(result.contains(r#"Ran with: {"text":"ls"}"#));
+    }
+
+    #[tokio::test]
+    async fn failed_edited_calls_stay_errors() {
+        use crate::tools::core::FnTool;
+
+        let transport = ScriptedTransport::new(vec![
+            tool_call_reply("c1", "echo", &serde_json::json!({"text": "a"})),
+            text_reply("Done."),
+        ]);
+        let client = OpenRouterClient::with_transport("key", transport);
+        let echo = FnTool::typed("echo", "Echo text", |_: serde_json::Value| async move {
+            "Error: echo is broken".to_string()
+        });
+        let tools = ToolSet::new().with(echo);
+        let results = std::sync::Mutex::new(Vec::new());
+        let handler = FnEventHandler::new(|event| match event {
+            HarnessEvent::ApprovalRequired { .. } => {
+                Some(EventResponse::ApproveEdited(r#"{"text":"b"}"#.to_string()))
+            }
+            HarnessEvent::ToolResult { result, .. } => {
+                results.lock().unwrap().push(result.to_string());
+                None
+            }
+            _ => None,
+        });
+        let config = test_config("").with_approval_required_tools(vec!["echo".into()]);
+        let result = Harness::new(&client, &tools, config)
+            .with_event_handler(&handler)
+            .run(vec![Message::user("echo")])
+            .await
+            .unwrap();
+
+        let results = results.lock().unwrap();
+        assert!(results[0].starts_with("Error"), "{results:?}");
+        assert!(results[0].contains("edited the arguments"));
+        assert_eq!(result.tool_stats["echo"].errors, 1);
+    }
+
+    #[tokio::test]
+    async fn always_allowed_tools_are_not_asked_again() {
+        use crate::agent::approvals::{ApprovalScope, ApprovalStore};
+        use crate::tools::core::FnTool;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let call =
+            |id: &str, text: &str| tool_call_reply(id, "echo", &serde_json::json!({"text": text}));
+        let transport = ScriptedTransport::new(vec![
+            call("c1", "one"),
+            call("c2", "two"),
+            text_reply("Done."),
+        ]);
+        let client = OpenRouterClient::with_transport("key", transport);
+        let echo = FnTool::typed("echo", "Echo text", |_: serde_json::Value| async {
+            "ok".to_string()
+        });
+        let tools = ToolSet::new().with(echo);
+        let asked = AtomicUsize::new(0);
+        let handler = FnEventHandler::new(|event| match event {
+            HarnessEvent::ApprovalRequired { .. } => {
+                asked.fetch_add(1, Ordering::SeqCst);
+                Some(EventResponse::ApproveAlways(ApprovalScope::Session))
+            }
+            _ => None,
+        });
+        let store = ApprovalStore::new();
+        let config = test_config("")
+            .with_approval_required_tools(vec!["echo".into()])
+            .with_approval_store(store.clone());
+        Harness::new(&client, &tools, config)
+            .with_event_handler(&handler)
+            .run(vec![Message::user("echo twice")])
+            .await
+            .unwrap();
+
+        assert_eq!(asked.load(Ordering::SeqCst), 1);
+        assert!(store.allows("echo"));
+    }
+
+    #[tokio::test]
+    async fn few_shot_examples_precede_the_task() {
+        use crate::tools::core::FnTool;
+
+        let transport = ScriptedTransport::new(vec![text_reply("done")]);
+        let requests = transport.requests();
+        let client = OpenRouterClient::with_transport("key", transport);
+        let lookup = FnTool::typed("lookup", "Look up a word", |_: serde_json::Value| async {
+            String::new()
+        })
+        .call_example(serde_json::json!({"word": "cinch"}), "cinch: a sure thing");
+        let tools = ToolSet::new().with(lookup);
+        let config = test_config("").with_few_shot_tool_examples(true);
+        Harness::new(&client, &tools, config)
+            .run(vec![Message::system("sys"), Message::user("define cinch")])
+            .await
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        let body: serde_json::Value = serde_json::from_str(&requests[0]).unwrap();
+        let roles: Vec<_> = body["messages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["role"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            roles,
+            ["system", "user", "assistant", "tool", "assistant", "user"]
+        );
+        let messages = &body["messages"];
+        assert_eq!(
+            messages[2]["tool_calls"][0]["function"]["arguments"],
+            r#"{"word":"cinch"}"#
+        );
+        assert_eq!(messages[3]["content"], "cinch: a sure thing");
+        assert_eq!(messages[5]["content"], "define cinch");
+    }
+
+    #[tokio::test]
+    async fn files_changed_reports_the_runs_mutations() {
+        use crate::agent::config::HarnessChangeReportConfig;
+        use crate::tools::snapshot::ChangeKind;
+
+        let dir = tempfile::tempdir().unwrap();
+        let write = serde_json::json!({"path": "new.txt", "content": "hello"});
+        let transport = ScriptedTransport::new(vec![
+            tool_call_reply("c1", "write_file", &write),
+            text_reply("Wrote it."),
+        ]);
+        let client = OpenRouterClient::with_transport("key", transport);
+        let tools = ToolSet::new().with_common_tools(dir.path().to_str().unwrap());
+        let config = test_config("").with_change_report(
+            HarnessChangeReportConfig::for_workdir(dir.path()).with_diffs(true),
+        );
+        let result = Harness::new(&client, &tools, config)
+            .run(vec![Message::user("write it")])
+            .await
+            .unwrap();
+
+        assert_eq!(result.files_changed.len(), 1);
+        let change = &result.files_changed[0];
+        assert_eq!(change.path, "new.txt");
+        assert_eq!(change.kind, ChangeKind::Added);
+        assert_eq!(change.stats.added, 1);
+        assert!(change.diff.as_deref().unwrap().contains("+hello"));
+    }
+
+    #[tokio::test]
+    async fn recall_tool_stays_off_the_callers_tool_set() {
+        let dir = tempfile::tempdir().unwrap();
+        let transport = ScriptedTransport::new(vec![text_reply("Done.")]);
+        let requests = transport.requests();
+        let client = OpenRouterClient::with_transport("key", transport);
+        let tools = ToolSet::new().with_common_tools(dir.path().to_str().unwrap());
+        let config = test_config("").with_history_archive(dir.path().join("archive"));
+        Harness::new(&client, &tools, config)
+            .run(vec![Message::user("hi")])
+            .await
+            .unwrap();
+
+        // The run offered the tool, but the shared set never gained it.
+        assert!(requests.lock().unwrap()[0].contains(crate::tools::names::RECALL_HISTORY));
+        assert!(!tools.has_tool(crate::tools::names::RECALL_HISTORY));
+    }
+
+    #[tokio::test]
+    async fn file_edits_are_diffed_against_the_previous_content() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "old\n").unwrap();
+        let transport = ScriptedTransport::new(vec![
+            tool_call_reply("c1", "read_file", &serde_json::json!({"path": "a.txt"})),
+            tool_call_reply(
+                "c2",
+                "write_file",
+                &serde_json::json!({"path": "a.txt", "content": "new\n"}),
+            ),
+            text_reply("Done."),
+        ]);
+        let client = OpenRouterClient::with_transport("key", transport);
+        let tools = ToolSet::new().with_common_tools(dir.path().to_str().unwrap());
+        let mut config = test_config("");
+        config.change_report.workdir = dir.path().to_path_buf();
+
+        let diffs = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = std::sync::Arc::clone(&diffs);
+        let handler = FnEventHandler::new(move |event| {
+            if let HarnessEvent::FileEdited { diff, .. } = event {
+                seen.lock().unwrap().push(diff.to_string());
+            }
+            None
+        });
+        Harness::new(&client, &tools, config)
+            .with_event_handler(&handler)
+            .run(vec![Message::user("rewrite it")])
+            .await
+            .unwrap();
+
+        assert_eq!(*diffs.lock().unwrap(), ["@@ -1 +1 @@\n-old\n+new\n"]);
+    }
+
+    #[tokio::test]
+    async fn truncated_mutations_are_not_healed() {
+        let dir = tempfile::tempdir().unwrap();
+        // Cut off by max_tokens before the closing brace.
+        let write = r#"{"choices":[{"message":{"content":null,"tool_calls":[{"id":"c1","type":"function","function":{"name":"write_file","arguments":"{\"path\":\"cut.txt\",\"content\":\"hello\""}}]},"finish_reason":"length"}]}"#;
+        let transport = ScriptedTransport::new(vec![write.to_string(), text_reply("Giving up.")]);
+        let client = OpenRouterClient::with_transport("key", transport);
+        let tools = ToolSet::new().with_common_tools(dir.path().to_str().unwrap());
+        let config = test_config("").with_resend_malformed_arguments(false);
+        let result = Harness::new(&client, &tools, config)
+            .run(vec![Message::user("write it")])
+            .await
+            .unwrap();
+
+        assert!(!dir.path().join("cut.txt").exists());
+        let tool_result = result
+            .messages
+            .iter()
+            .find(|m| m.tool_call_id.as_deref() == Some("c1"))
+            .and_then(|m| m.content.as_deref())
+            .unwrap();
+        assert!(tool_result.starts_with("Error"), "{tool_result}");
+    }
+
+    #[tokio::test]
+    async fn repeated_failures_escalate_with_reminders() {
+        use crate::agent::config::HarnessFailureEscalationConfig;
+
+        let read = |i: u32| {
+            let arguments = serde_json::json!({"path": format!("missing{i}.txt")});
+            tool_call_reply(&format!("c{i}"), "read_file", &arguments)
+        };
+        let transport =
+            ScriptedTransport::new(vec![read(1), read(2), read(3), text_reply("Giving up.")]);
+        let requests = transport.requests();
+        let client = OpenRouterClient::with_transport("key", transport);
+        let dir = tempfile::tempdir().unwrap();
+        let tools = ToolSet::new().with_common_tools(dir.path().to_str().unwrap());
+        let config = test_config("").with_failure_escalation(HarnessFailureEscalationConfig {
+            enabled: true,
+            threshold: 2,
+        });
+        Harness::new(&client, &tools, config)
+            .run(vec![Message::user("read the missing files")])
+            .await
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert!(!requests[1].contains("has failed"));
+        assert!(requests[2].contains("`read_file` has failed 2 times in a row"));
+        assert!(requests[3].contains("Stop retrying it"));
+    }
+
+    #[tokio::test]
+    async fn category_budgets_refuse_calls_past_the_limit() {
+        use crate::tools::ToolCategory;
+
+        let grep = |i: u32| {
+            let arguments = serde_json::json!({"pattern": format!("needle{i}")});
+            tool_call_reply(&format!("c{i}"), "grep", &arguments)
+        };
+        let transport = ScriptedTransport::new(vec![grep(1), grep(2), text_reply("Done.")]);
+        let requests = transport.requests();
+        let client = OpenRouterClient::with_transport("key", transport);
+        let dir = tempfile::tempdir().unwrap();
+        let tools = ToolSet::new().with_common_tools(dir.path().to_str().unwrap());
+        let config = test_config("").with_category_budget(
+            ToolCategory::new("search", &["grep"], "When searching").max_calls(1),
+        );
+        Harness::new(&client, &tools, config)
+            .run(vec![Message::user("search twice")])
+            .await
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert!(!requests[1].contains("budget of"));
+        assert!(requests[2].contains("the 'search' budget of 1 calls for this run is used up"));
+    }
+
+    #[tokio::test]
+    async fn continuations_are_bounded() {
+        let (result, requests) = run_scripted(
+            HarnessConfig::new("test/model", "").with_max_continuations(1),
+            vec![finished_reply("a", "length"), finished_reply("b", "length")],
+        )
+        .await;
+
+        assert!(result.finished);
+        assert_eq!(result.text(), "ab");
+        assert_eq!(requests.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn each_round_ends_with_its_decisions() {
+        let transport = ScriptedTransport::new(vec![
+            finished_reply(&"ok ".repeat(40), "length"),
+            tool_call_reply("c1", "grep", &serde_json::json!({"pattern": "needle"})),
+            text_reply("Done."),
+        ]);
+        let client = OpenRouterClient::with_transport("key", transport);
+        let dir = tempfile::tempdir().unwrap();
+        let tools = ToolSet::new().with_common_tools(dir.path().to_str().unwrap());
+        let log = std::sync::Mutex::new(Vec::new());
+        let handler = FnEventHandler::new(|event| {
+            if let HarnessEvent::RoundEnd { decisions } = event {
+                log.lock().unwrap().push((*decisions).clone());
+            }
+            None
+        });
+        let mut config = test_config("");
+        config.degenerate = HarnessDegenerateConfig {
+            fallback_models: vec!["test/backup".into()],
+            ..Default::default()
+        };
+        Harness::new(&client, &tools, config)
+            .with_event_handler(&handler)
+            .run(vec![Message::user("find the needle")])
+            .await
+            .unwrap();
+
+        let log = log.into_inner().unwrap();
+        let outcomes: Vec<_> = log.iter().map(|d| d.outcome).collect();
+        assert_eq!(
+            outcomes,
+            [
+                RoundOutcome::Retried,
+                RoundOutcome::ToolCalls,
+                RoundOutcome::Finished
+            ]
+        );
+        assert!(!log[0].model_routed);
+        assert_eq!(log[1].model, "test/backup");
+        assert!(log[1].model_routed);
+        assert_eq!(log[1].tool_calls, 1);
+        assert_eq!(log[1].tools_withheld, 0);
+        assert_eq!(log[1].tools_offered, tools.definitions().len());
+        assert_eq!(log[2].round, 3);
+    }
+
+    #[tokio::test]
+    async fn degenerate_responses_are_retried_on_another_provider() {
+        let looping = format!(
+            r#"{{"choices":[{{"message":{{"content":"{}"}},"finish_reason":"length"}}],"provider":"BadCo"}}"#,
+            "ok ".repeat(40)
+        );
+        let config = HarnessConfig {
+            degenerate: HarnessDegenerateConfig {
+                fallback_models: vec!["test/backup".into()],
+                ..Default::default()
+            },
+            ..HarnessConfig::new("test/model", "")
+        };
+        let (result, requests) = run_scripted(config, vec![looping, text_reply("All done.")]).await;
+
+        assert_eq!(result.text(), "All done.");
+        let retry: serde_json::Value = serde_json::from_str(&requests[1]).unwrap();
+        assert_eq!(retry["model"], "test/backup");
+        assert_eq!(retry["provider"]["ignore"], serde_json::json!(["BadCo"]));
+        assert_eq!(retry["provider"]["allow_fallbacks"], true);
+    }
+
+    #[tokio::test]
+    async fn streamed_rounds_tick_cost_before_usage_arrives() {
+        // Distinct words, so the degenerate-loop check stays quiet.
+        let chunk: String = (0..60).map(|i| format!("word{i} ")).collect();
+        let sse = format!(
+            "data: {{\"choices\":[{{\"delta\":{{\"content\":\"{chunk}\"}}}}]}}\n\n\
+             data: {{\"choices\":[{{\"delta\":{{\"content\":\"{chunk}\"}},\"finish_reason\":\"stop\"}}]}}\n\n\
+             data: {{\"choices\":[],\"usage\":{{\"prompt_tokens\":500,\"completion_tokens\":150}}}}\n\n\
+             data: [DONE]\n\n"
+        );
+        let transport = ScriptedTransport::new(vec![sse]);
+        let requests = transport.requests();
+        let client = OpenRouterClient::with_transport("key", transport);
+        let tools = ToolSet::new();
+        let mut config = test_config("").with_streaming(true);
+        config.model = "anthropic/claude-sonnet-4".into();
+
+        let updates = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = std::sync::Arc::clone(&updates);
+        let handler = FnEventHandler::new(move |event| {
+            if let HarnessEvent::CostUpdate {
+                completion_tokens,
+                cost_usd,
+                estimated,
+                ..
+            } = event
+            {
+                seen.lock()
+                    .unwrap()
+                    .push((*completion_tokens, *cost_usd, *estimated));
+            }
+            None
+        });
+        let result = Harness::new(&client, &tools, config)
+            .with_event_handler(&handler)
+            .run(vec![Message::user("write it")])
+            .await
+            .unwrap();
+
+        let body: serde_json::Value = serde_json::from_str(&requests.lock().unwrap()[0]).unwrap();
+        assert_eq!(body["stream_options"]["include_usage"], true);
+
+        let updates = updates.lock().unwrap();
+        let (last, estimates) = updates.split_last().unwrap();
+        assert!(!estimates.is_empty(), "{updates:?}");
+        assert!(
+            estimates
+                .iter()
+                .all(|&(_, cost, estimated)| estimated && cost > 0.0)
+        );
+        assert!(estimates.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(
+            *last,
+            (150, result.estimated_cost_usd, false),
+            "final update is the recorded usage"
+        );
+    }
+
+    /// A response body as network chunks, possibly ending in a read error.
+    type Chunks = Vec<Result<Vec<u8>, String>>;
+
+    /// Transport replying with scripted chunked bodies, one per request.
+    struct ChunkedScripted {
+        replies: std::sync::Mutex<std::collections::VecDeque<Chunks>>,
+        requests: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl crate::api::transport::HttpTransport for ChunkedScripted {
+        fn send(
+            &self,
+            request: crate::api::transport::HttpRequest,
+        ) -> crate::api::transport::TransportFuture<'_> {
+            self.requests.lock().unwrap().push(request.body);
+            let chunks = self.replies.lock().unwrap().pop_front().unwrap();
+            let body = Box::pin(futures::stream::iter(chunks));
+            Box::pin(async move { Ok(crate::api::transport::HttpResponse::new(200, body)) })
+        }
+    }
+
+    fn delta_chunk(text: &str, finish_reason: Option<&str>) -> Result<Vec<u8>, String> {
+        let finish = finish_reason.map_or("null".to_string(), |r| format!("\"{r}\""));
+        Ok(format!(
+            "data: {{\"choices\":[{{\"delta\":{{\"content\":\"{text}\"}},\"finish_reason\":{finish}}}]}}\n\n"
+        )
+        .into_bytes())
+    }
+
+    #[tokio::test]
+    async fn interrupted_streams_are_resumed_and_stitched() {
+        let requests = std::sync::Arc::default();
+        let transport = ChunkedScripted {
+            replies: std::sync::Mutex::new(
+                vec![
+                    vec![
+                        delta_chunk("The answer ", None),
+                        Err("connection reset by peer".into()),
+                    ],
+                    vec![
+                        delta_chunk("is 42.", Some("stop")),
+                        Ok(b"data: [DONE]\n\n".to_vec()),
+                    ],
+                ]
+                .into(),
+            ),
+            requests: std::sync::Arc::clone(&requests),
+        };
+        let client = OpenRouterClient::with_transport("key", transport);
+        let tools = ToolSet::new();
+        let mut config = test_config("").with_streaming(true);
+        config.retry.initial_delay = std::time::Duration::ZERO;
+
+        let interruptions = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = std::sync::Arc::clone(&interruptions);
+        let handler = FnEventHandler::new(move |event| {
+            if let HarnessEvent::StreamInterrupted {
+                attempt,
+                kept_chars,
+                ..
+            } = event
+            {
+                seen.lock().unwrap().push((*attempt, *kept_chars));
+            }
+            None
+        });
+        let result = Harness::new(&client, &tools, config)
+            .with_event_handler(&handler)
+            .run(vec![Message::user("what is the answer?")])
+            .await
+            .unwrap();
+
+        assert!(result.finished);
+        assert_eq!(result.text_output, ["The answer is 42."]);
+        assert_eq!(*interruptions.lock().unwrap(), [(1, 11)]);
+        // The interrupted attempt reported no usage, so it is estimated.
+        assert!(result.total_completion_tokens > 0);
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        let body: serde_json::Value = serde_json::from_str(&requests[1]).unwrap();
+        let messages = body["messages"].as_array().unwrap();
+        let n = messages.len();
+        assert_eq!(messages[n - 2]["content"], "The answer ");
+        assert!(
+            messages[n - 1]["content"]
+                .as_str()
+                .unwrap()
+                .contains("connection error")
+        );
+    }
+
+    #[tokio::test]
+    async fn provider_errors_mid_stream_are_not_resumed() {
+        let requests = std::sync::Arc::default();
+        let transport = ChunkedScripted {
+            replies: std::sync::Mutex::new(
+                vec![vec![
+                    delta_chunk("The answer ", None),
+                    Ok(b"data: {\"error\":{\"code\":502,\"message\":\"Provider disconnected\"}}\n\n".to_vec()),
+                ]]
+                .into(),
+            ),
+            requests: std::sync::Arc::clone(&requests),
+        };
+        let client = OpenRouterClient::with_transport("key", transport);
+        let tools = ToolSet::new();
+        let mut config = test_config("").with_streaming(true);
+        config.retry.initial_delay = std::time::Duration::ZERO;
+
+        let err = Harness::new(&client, &tools, config)
+            .run(vec![Message::user("what is the answer?")])
+            .await
+            .unwrap_err();
+
+        assert!(err.contains("Provider disconnected"), "{err}");
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
 }
@@ -119,12 +119,12 @@ impl<H: LifecycleHook> LifecycleHookAdapter<H> {
 impl<H: LifecycleHook> EventHandler for LifecycleHookAdapter<H> {
     fn on_event(&self, event: &HarnessEvent<'_>) -> Option<EventResponse> {
         match event {
-            HarnessEvent::ApprovalRequired { name, arguments } => {
-                match self.hook.pre_tool_use(name, arguments) {
-                    HookAction::Block(reason) => Some(EventResponse::Deny(reason)),
-                    HookAction::Proceed => None,
-                }
-            }
+            HarnessEvent::ApprovalRequired {
+                name, arguments, ..
+            } => match self.hook.pre_tool_use(name, arguments) {
+                HookAction::Block(reason) => Some(EventResponse::Deny(reason)),
+                HookAction::Proceed => None,
+            },
             HarnessEvent::ToolResult { name, result, .. } => self
                 .hook
                 .post_tool_use(name, result)
@@ -253,8 +253,15 @@ impl ExternalHookRunner {
         command: &str,
         env_vars: &[(&str, &str)],
     ) -> Result<(i32, String), String> {
-        let mut cmd = std::process::Command::new("sh");
-        cmd.arg("-c").arg(command).current_dir(&self.workdir);
+        #[cfg(not(target_arch = "wasm32"))]
+        let mut cmd = crate::tools::host::ShellKind::host().std_command(command);
+        #[cfg(target_arch = "wasm32")]
+        let mut cmd = {
+            let mut cmd = std::process::Command::new("sh");
+            cmd.arg("-c").arg(command);
+            cmd
+        };
+        cmd.current_dir(&self.workdir);
 
         for (key, val) in env_vars {
             cmd.env(key, val);
@@ -358,9 +365,9 @@ impl ExternalHookRunner {
 impl EventHandler for ExternalHookRunner {
     fn on_event(&self, event: &HarnessEvent<'_>) -> Option<EventResponse> {
         match event {
-            HarnessEvent::ApprovalRequired { name, arguments } => {
-                self.run_pre_tool_hooks(name, arguments)
-            }
+            HarnessEvent::ApprovalRequired {
+                name, arguments, ..
+            } => self.run_pre_tool_hooks(name, arguments),
             HarnessEvent::ToolResult { name, result, .. } => self.run_post_tool_hooks(name, result),
             HarnessEvent::SessionStarting { trace_id } => {
                 self.run_session_start_hooks(trace_id);
@@ -437,6 +444,7 @@ mod tests {
         let event = HarnessEvent::ApprovalRequired {
             name: "read_file",
             arguments: "{}",
+            call_id: "c1",
         };
         assert!(adapter.on_event(&event).is_none());
     }
@@ -447,6 +455,7 @@ mod tests {
         let event = HarnessEvent::ApprovalRequired {
             name: "shell",
             arguments: "{}",
+            call_id: "c1",
         };
         let response = adapter.on_event(&event);
         assert!(matches!(response, Some(EventResponse::Deny(ref r)) if r == "Shell blocked"));
@@ -638,6 +647,7 @@ mod tests {
         let event = HarnessEvent::ApprovalRequired {
             name: "test_tool",
             arguments: "{}",
+            call_id: "c1",
         };
         assert!(runner.on_event(&event).is_none());
     }
@@ -655,6 +665,7 @@ mod tests {
         let event = HarnessEvent::ApprovalRequired {
             name: "test_tool",
             arguments: "{}",
+            call_id: "c1",
         };
         let response = runner.on_event(&event);
         assert!(
@@ -696,6 +707,7 @@ mod tests {
         let event = HarnessEvent::ApprovalRequired {
             name: "read_file",
             arguments: "{}",
+            call_id: "c1",
         };
         // Should not block because matcher doesn't match.
         assert!(runner.on_event(&event).is_none());
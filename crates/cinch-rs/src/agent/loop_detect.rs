@@ -0,0 +1,246 @@
+//! Detection of runaway tool-call loops.
+//!
+//! A model that keeps repeating the same call (same tool, same arguments)
+//! and keeps getting the same result back is stuck: further repetitions
+//! only burn rounds. The [`LoopDetector`] tracks the streak of consecutive
+//! identical calls with identical results. Once the streak reaches
+//! [`HarnessLoopDetectionConfig::threshold`], the harness injects a system
+//! reminder describing the loop into the next round and, when
+//! [`HarnessLoopDetectionConfig::block_repeats`] is set, refuses further
+//! repetitions of that call with an error result. Refused repetitions still
+//! count, so the reminder keeps reporting them.
+//!
+//! Any other call in between, or a different result for the same call
+//! (e.g. after an edit), ends the streak.
+
+use super::config::HarnessLoopDetectionConfig;
+use crate::tools::read_tracker::fnv1a;
+
+/// A detected loop: one call repeated with identical results.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ToolLoop {
+    /// Tool name.
+    pub(crate) name: String,
+    /// Call arguments.
+    pub(crate) arguments: String,
+    /// How many times the call has returned the same result in a row.
+    pub(crate) repeats: u32,
+}
+
+/// The latest call and how many times in a row it returned the same result.
+struct Streak {
+    name: String,
+    arguments: String,
+    result_hash: u64,
+    repeats: u32,
+}
+
+impl Streak {
+    fn is_call(&self, name: &str, arguments: &str) -> bool {
+        self.name == name && self.arguments == arguments
+    }
+
+    fn to_loop(&self) -> ToolLoop {
+        ToolLoop {
+            name: self.name.clone(),
+            arguments: self.arguments.clone(),
+            repeats: self.repeats,
+        }
+    }
+}
+
+/// Per-run loop detection state.
+pub(crate) struct LoopDetector {
+    config: HarnessLoopDetectionConfig,
+    streak: Option<Streak>,
+    /// Loops detected since the last [`take_reminder()`](Self::take_reminder).
+    pending: Vec<ToolLoop>,
+}
+
+impl LoopDetector {
+    pub(crate) fn new(config: HarnessLoopDetectionConfig) -> Self {
+        Self {
+            config,
+            streak: None,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Record a call's result. Returns the loop when this call's streak
+    /// reaches the threshold (and on every repetition after that).
+    pub(crate) fn record(&mut self, name: &str, arguments: &str, result: &str) -> Option<ToolLoop> {
+        // Process runs differ in timing even when their output is identical.
+        let hash = fnv1a(&crate::tools::output::without_duration(result));
+        let streak = match self.streak {
+            Some(ref mut streak)
+                if streak.is_call(name, arguments) && streak.result_hash == hash =>
+            {
+                streak.repeats += 1;
+                streak
+            }
+            _ => self.streak.insert(Streak {
+                name: name.to_string(),
+                arguments: arguments.to_string(),
+                result_hash: hash,
+                repeats: 1,
+            }),
+        };
+        if streak.repeats < self.config.threshold {
+            return None;
+        }
+        let detected = streak.to_loop();
+        self.pending.push(detected.clone());
+        Some(detected)
+    }
+
+    /// Whether a call should be refused because it is a known loop.
+    pub(crate) fn should_block(&self, name: &str, arguments: &str) -> bool {
+        self.config.block_repeats
+            && self
+                .streak
+                .as_ref()
+                .is_some_and(|s| s.is_call(name, arguments) && s.repeats >= self.config.threshold)
+    }
+
+    /// Record a repetition refused by [`should_block()`](Self::should_block):
+    /// it extends the streak as if it had returned the same result.
+    pub(crate) fn record_blocked(&mut self) -> Option<ToolLoop> {
+        let streak = self.streak.as_mut()?;
+        streak.repeats += 1;
+        let detected = streak.to_loop();
+        self.pending.push(detected.clone());
+        Some(detected)
+    }
+
+    /// Reminder text describing loops detected since the last call, if any.
+    pub(crate) fn take_reminder(&mut self) -> Option<String> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        let calls: Vec<String> = std::mem::take(&mut self.pending)
+            .into_iter()
+            .map(|l| {
+                format!(
+                    "`{}` with arguments {} ({} times)",
+                    l.name,
+                    crate::context::eviction::summarize_args(&l.arguments, 120),
+                    l.repeats
+                )
+            })
+            .collect();
+        let blocked = if self.config.block_repeats {
+            " Further identical calls will be refused."
+        } else {
+            ""
+        };
+        Some(format!(
+            "[System reminder: you are repeating the same tool call and getting the same \
+             result: {}. Repeating it will not change the outcome. Try a different approach, \
+             use the information you already have, or finish if the task is done.{blocked}]",
+            calls.join("; ")
+        ))
+    }
+}
+
+/// Error result returned in place of a blocked repeated call.
+pub(crate) fn blocked_result(name: &str) -> String {
+    format!(
+        "Error: tool '{name}' was not run because it was already called with these \
+         arguments several times with the same result. Try a different approach."
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::output::ToolOutput;
+
+    fn detector(block_repeats: bool) -> LoopDetector {
+        LoopDetector::new(HarnessLoopDetectionConfig {
+            enabled: true,
+            threshold: 3,
+            block_repeats,
+        })
+    }
+
+    #[test]
+    fn detects_identical_repeats() {
+        let mut d = detector(false);
+        assert!(
+            d.record("grep", r#"{"pattern":"x"}"#, "no matches")
+                .is_none()
+        );
+        assert!(
+            d.record("grep", r#"{"pattern":"x"}"#, "no matches")
+                .is_none()
+        );
+        let detected = d
+            .record("grep", r#"{"pattern":"x"}"#, "no matches")
+            .unwrap();
+        assert_eq!(detected.repeats, 3);
+
+        let reminder = d.take_reminder().unwrap();
+        assert!(reminder.contains("`grep`"));
+        assert!(!reminder.contains("refused"));
+        assert!(d.take_reminder().is_none());
+        assert!(!d.should_block("grep", r#"{"pattern":"x"}"#));
+    }
+
+    #[test]
+    fn changed_result_resets_streak() {
+        let mut d = detector(true);
+        d.record("read_file", r#"{"path":"a"}"#, "v1");
+        d.record("read_file", r#"{"path":"a"}"#, "v1");
+        assert!(d.record("read_file", r#"{"path":"a"}"#, "v2").is_none());
+        assert!(d.record("read_file", r#"{"path":"a"}"#, "v2").is_none());
+        assert!(!d.should_block("read_file", r#"{"path":"a"}"#));
+    }
+
+    #[test]
+    fn timing_differences_are_still_loops() {
+        let mut d = detector(false);
+        for ms in [40, 55] {
+            let result = ToolOutput::new(1, "", "error: E0308\n")
+                .with_duration(std::time::Duration::from_millis(ms))
+                .render();
+            assert!(
+                d.record("shell", r#"{"command":"make"}"#, &result)
+                    .is_none()
+            );
+        }
+        let result = ToolOutput::new(1, "", "error: E0308\n").render();
+        assert!(
+            d.record("shell", r#"{"command":"make"}"#, &result)
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn blocks_known_loops_when_enabled() {
+        let mut d = detector(true);
+        for _ in 0..3 {
+            d.record("shell", r#"{"command":"make"}"#, "error");
+        }
+        assert!(d.should_block("shell", r#"{"command":"make"}"#));
+        assert!(!d.should_block("shell", r#"{"command":"make test"}"#));
+        assert!(d.take_reminder().unwrap().contains("refused"));
+
+        // Refused repetitions keep counting and keep the reminder coming.
+        assert_eq!(d.record_blocked().unwrap().repeats, 4);
+        assert!(d.take_reminder().unwrap().contains("(4 times)"));
+    }
+
+    #[test]
+    fn any_other_call_ends_the_streak() {
+        let mut d = detector(true);
+        for _ in 0..3 {
+            d.record("shell", r#"{"command":"make"}"#, "error");
+        }
+        d.record("read_file", r#"{"path":"Makefile"}"#, "all:");
+        assert!(!d.should_block("shell", r#"{"command":"make"}"#));
+        assert!(
+            d.record("shell", r#"{"command":"make"}"#, "error")
+                .is_none()
+        );
+    }
+}
@@ -10,7 +10,7 @@
 
 use std::path::Path;
 
-use crate::{ChatRequest, Message, OpenRouterClient};
+use crate::{ChatRequest, Message, OpenRouterClient, ProviderPreferences};
 
 /// System prompt for LLM-based memory consolidation.
 ///
@@ -103,6 +103,7 @@ pub async fn consolidate_memory(
     memory_path: &Path,
     max_lines: usize,
     model: &str,
+    provider: Option<&ProviderPreferences>,
 ) -> Result<Option<(usize, usize)>, String> {
     // 1. Read file — if missing, nothing to consolidate.
     let content = match std::fs::read_to_string(memory_path) {
@@ -129,6 +130,7 @@ pub async fn consolidate_memory(
         ],
         max_tokens: 4096,
         temperature: 0.3,
+        provider: provider.cloned(),
         ..Default::default()
     };
 
@@ -247,6 +249,7 @@ mod tests {
             Path::new("/nonexistent/MEMORY.md"),
             200,
             "test-model",
+            None,
         )
         .await;
         assert!(result.is_ok());
@@ -260,7 +263,7 @@ mod tests {
         std::fs::write(&path, "# Memory\n\nSome notes.\n").unwrap();
 
         let client = OpenRouterClient::new("fake-key").unwrap();
-        let result = consolidate_memory(&client, &path, 200, "test-model").await;
+        let result = consolidate_memory(&client, &path, 200, "test-model", None).await;
         assert!(result.is_ok());
         assert!(result.unwrap().is_none());
     }
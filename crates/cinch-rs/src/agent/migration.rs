@@ -0,0 +1,187 @@
+//! Schema versions and migrations for persisted session files.
+//!
+//! Checkpoints and session manifests carry a `version` field. Files are read
+//! as raw JSON, upgraded one version at a time by the migration tables below,
+//! and only then deserialized, so a release that changes a field can still
+//! resume sessions saved by older releases. Files without a `version` field
+//! predate versioning and are treated as version 0.
+//!
+//! A file written by a *newer* release is rejected with an error naming both
+//! versions instead of a serde error about some unrelated field.
+//!
+//! To change the schema: bump the version constant and append a migration
+//! that rewrites the previous version's JSON into the new shape.
+
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+
+/// Current checkpoint schema version.
+pub const CHECKPOINT_VERSION: u32 = 2;
+/// Current session manifest schema version.
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Rewrites a file's JSON from version `N` to `N + 1`.
+type Migration = fn(&mut Value) -> Result<(), String>;
+
+/// `CHECKPOINT_MIGRATIONS[n]` upgrades a version-`n` checkpoint.
+const CHECKPOINT_MIGRATIONS: &[Migration] = &[checkpoint_v0_to_v1, checkpoint_v1_to_v2];
+/// `MANIFEST_MIGRATIONS[n]` upgrades a version-`n` manifest.
+const MANIFEST_MIGRATIONS: &[Migration] = &[manifest_v0_to_v1];
+
+const _: () = assert!(CHECKPOINT_MIGRATIONS.len() == CHECKPOINT_VERSION as usize);
+const _: () = assert!(MANIFEST_MIGRATIONS.len() == MANIFEST_VERSION as usize);
+
+/// Parse a checkpoint file of any supported version.
+pub fn parse_checkpoint<T: DeserializeOwned>(json: &str) -> Result<T, String> {
+    parse("checkpoint", json, CHECKPOINT_MIGRATIONS)
+}
+
+/// Parse a session manifest of any supported version.
+pub fn parse_manifest<T: DeserializeOwned>(json: &str) -> Result<T, String> {
+    parse("manifest", json, MANIFEST_MIGRATIONS)
+}
+
+/// Schema version recorded in a file's JSON (0 when absent).
+pub fn schema_version(value: &Value) -> Result<u32, String> {
+    match value.get("version") {
+        None | Some(Value::Null) => Ok(0),
+        Some(v) => v
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| format!("invalid schema version {v}")),
+    }
+}
+
+fn parse<T: DeserializeOwned>(
+    kind: &str,
+    json: &str,
+    migrations: &[Migration],
+) -> Result<T, String> {
+    let mut value: Value =
+        serde_json::from_str(json).map_err(|e| format!("Failed to parse {kind}: {e}"))?;
+    migrate(kind, &mut value, migrations)?;
+    serde_json::from_value(value).map_err(|e| format!("Failed to parse {kind}: {e}"))
+}
+
+/// Upgrade `value` in place to the latest version in `migrations`.
+fn migrate(kind: &str, value: &mut Value, migrations: &[Migration]) -> Result<(), String> {
+    if !value.is_object() {
+        return Err(format!("Failed to parse {kind}: expected a JSON object"));
+    }
+    let current = migrations.len() as u32;
+    let found = schema_version(value).map_err(|e| format!("Failed to parse {kind}: {e}"))?;
+    if found > current {
+        return Err(format!(
+            "{kind} has schema version {found}, but this build only reads up to version \
+             {current}; it was written by a newer cinch-rs release"
+        ));
+    }
+    for (from, migration) in migrations.iter().enumerate().skip(found as usize) {
+        migration(value).map_err(|e| format!("Failed to migrate {kind} from v{from}: {e}"))?;
+        value["version"] = json!(from + 1);
+    }
+    Ok(())
+}
+
+/// Fill in `key` with `default` when the field is missing or null.
+fn default_field(value: &mut Value, key: &str, default: Value) {
+    if value.get(key).is_none_or(Value::is_null) {
+        value[key] = default;
+    }
+}
+
+/// Unversioned checkpoints may lack the counters and text output that were
+/// added over time; default them instead of failing to resume.
+fn checkpoint_v0_to_v1(value: &mut Value) -> Result<(), String> {
+    if value.get("trace_id").is_none() || value.get("messages").is_none() {
+        return Err("missing trace_id or messages".into());
+    }
+    default_field(value, "text_output", json!([]));
+    default_field(value, "round", json!(0));
+    default_field(value, "total_prompt_tokens", json!(0));
+    default_field(value, "total_completion_tokens", json!(0));
+    default_field(value, "estimated_cost_usd", json!(0.0));
+    default_field(value, "timestamp", json!(""));
+    Ok(())
+}
+
+/// Version 1 checkpoints predate the saved summarizer state; they resume
+/// with an empty structured state.
+fn checkpoint_v1_to_v2(value: &mut Value) -> Result<(), String> {
+    default_field(value, "structured_state", Value::Null);
+    Ok(())
+}
+
+/// Unversioned manifests may lack the preview, cost, and timestamps; a
+/// manifest without a status is assumed to belong to an interrupted run.
+fn manifest_v0_to_v1(value: &mut Value) -> Result<(), String> {
+    if value.get("trace_id").is_none() {
+        return Err("missing trace_id".into());
+    }
+    default_field(value, "model", json!("unknown"));
+    default_field(value, "status", json!("interrupted"));
+    default_field(value, "created_at", json!(0));
+    let created_at = value["created_at"].clone();
+    default_field(value, "updated_at", created_at);
+    default_field(value, "last_round", json!(0));
+    default_field(value, "total_prompt_tokens", json!(0));
+    default_field(value, "total_completion_tokens", json!(0));
+    default_field(value, "estimated_cost_usd", json!(0.0));
+    default_field(value, "message_preview", json!(""));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::checkpoint::Checkpoint;
+    use crate::agent::session::{SessionManifest, SessionStatus};
+
+    #[test]
+    fn unversioned_checkpoint_is_migrated() {
+        let json = r#"{"trace_id":"tr-1","messages":[{"role":"user","content":"hi"}],"round":3}"#;
+        let checkpoint: Checkpoint = parse_checkpoint(json).unwrap();
+        assert_eq!(checkpoint.version, CHECKPOINT_VERSION);
+        assert_eq!(checkpoint.round, 3);
+        assert!(checkpoint.text_output.is_empty());
+        assert_eq!(checkpoint.messages.len(), 1);
+    }
+
+    #[test]
+    fn v1_checkpoint_resumes_without_structured_state() {
+        let json = r#"{"version":1,"trace_id":"tr-1","messages":[],"text_output":[],"round":2,
+            "total_prompt_tokens":0,"total_completion_tokens":0,"estimated_cost_usd":0.0,
+            "timestamp":""}"#;
+        let checkpoint: Checkpoint = parse_checkpoint(json).unwrap();
+        assert_eq!(checkpoint.version, 2);
+        assert_eq!(checkpoint.structured_state, None);
+    }
+
+    #[test]
+    fn unversioned_manifest_is_migrated() {
+        let json = r#"{"trace_id":"tr-1","created_at":50,"last_round":2}"#;
+        let manifest: SessionManifest = parse_manifest(json).unwrap();
+        assert_eq!(manifest.version, MANIFEST_VERSION);
+        assert_eq!(manifest.status, SessionStatus::Interrupted);
+        assert_eq!(manifest.updated_at, 50);
+        assert_eq!(manifest.model, "unknown");
+    }
+
+    #[test]
+    fn future_versions_are_rejected_clearly() {
+        let json = r#"{"version":99,"trace_id":"tr-1","messages":[],"shiny_new_field":{}}"#;
+        let err = parse_checkpoint::<Checkpoint>(json).unwrap_err();
+        assert!(err.contains("schema version 99"), "{err}");
+        assert!(err.contains("newer cinch-rs"), "{err}");
+
+        let err = parse_manifest::<SessionManifest>(r#"{"version":"x"}"#).unwrap_err();
+        assert!(err.contains("invalid schema version"), "{err}");
+    }
+
+    #[test]
+    fn broken_legacy_files_report_the_migration() {
+        let err = parse_checkpoint::<Checkpoint>(r#"{"round":1}"#).unwrap_err();
+        assert!(err.contains("migrate checkpoint from v0"), "{err}");
+        assert!(parse_manifest::<SessionManifest>("[]").is_err());
+    }
+}
@@ -8,10 +8,38 @@
 //! - [`events`] — [`EventHandler`] trait and [`HarnessEvent`] enum for
 //!   observing the loop. Includes [`LoggingHandler`], [`CompositeEventHandler`],
 //!   [`FnEventHandler`], and [`ToolResultHandler`].
+//! - [`approvals`] — [`ApprovalStore`] of tools the user chose to always
+//!   allow, for the session or persisted for the project.
+//! - [`audit`] — append-only JSONL audit log of command, network, and
+//!   mutating tool calls with approval provenance.
 //! - [`checkpoint`] — serializable checkpoint struct for round state.
 //! - [`session`] — per-session directories with manifests and checkpoint management.
+//! - [`migration`] — schema versions and migrations for checkpoint and
+//!   manifest files.
+//! - [`session_import`] — import of Claude Code and Codex session transcripts.
+//! - [`degenerate`] — heuristics for degenerate responses (whitespace,
+//!   token loops, prompt echo), retried on another provider or model.
+//! - [`loop_detect`] — detection of repeated identical tool calls, with a
+//!   reminder (and optional blocking) to break the loop.
+//! - [`pacing`] — deadline-aware pacing that wraps a run up (faster model,
+//!   smaller responses, no further planning) as its deadline approaches.
+//! - [`warm_start`] — context (repo map, git status, TODOs, memory) gathered
+//!   concurrently before the first round and added to the system prompt.
+//! - [`speculation`] — opt-in speculative prefetch of the next round's
+//!   request using predicted read-only tool results.
 //! - [`sub_agent`] — recursive sub-agent delegation with
 //!   [`TokenBudgetSemaphore`] for tree-wide budget control.
+//! - [`blackboard`] — [`Blackboard`] key-value state shared by an agent tree,
+//!   with `blackboard_get`/`blackboard_set` tools and change events.
+//! - [`handoff`] — [`HandoffRunner`] for agents transferring control of a
+//!   task to each other with a compacted context summary.
+//! - [`orchestrator`] — supervisor pattern: an [`Orchestrator`] coordinator
+//!   delegating to named [`Specialist`] agents under a shared token budget.
+//! - [`templates`] — [`AgentTemplateRegistry`] of named agent templates
+//!   (prompt, tools, model, overrides) loadable from TOML.
+//! - [`profile`] — [`AgentProfile`] tool usage statistics persisted across
+//!   runs, used by [`ToolFilter`](crate::tools::filter::ToolFilter) to drop
+//!   unused or failing tools.
 //! - [`plan_execute`] — two-phase workflow: plan with read-only tools first,
 //!   then execute with the full tool set.
 //! - [`memory`] — file-based cross-session memory (MEMORY.md index, topic files).
@@ -23,36 +51,70 @@
 //!   system reminders. See [`harness::build_default_prompt_registry`] for the
 //!   standard harness integration.
 
+pub mod approvals;
+pub mod audit;
+pub mod blackboard;
+#[cfg(feature = "checkpoint")]
 pub mod checkpoint;
 pub mod config;
+#[cfg(feature = "config-sources")]
+pub mod config_sources;
+pub mod degenerate;
 pub mod events;
 pub mod execution;
 pub mod gather;
+pub mod handoff;
 pub mod harness;
 pub mod hooks;
+pub mod loop_detect;
+#[cfg(feature = "memory")]
 pub mod memory;
+#[cfg(feature = "checkpoint")]
+pub mod migration;
+pub mod orchestrator;
+pub mod pacing;
 pub mod plan_execute;
+pub mod profile;
 pub mod project_instructions;
 pub mod prompt;
+#[cfg(feature = "checkpoint")]
 pub mod session;
+#[cfg(feature = "checkpoint")]
+pub mod session_import;
+pub mod speculation;
 pub mod sub_agent;
+#[cfg(feature = "config-sources")]
+pub mod templates;
+pub mod warm_start;
 
 // Re-export commonly used items at the module level.
+pub use approvals::{ApprovalScope, ApprovalStore};
+pub use blackboard::{Blackboard, BlackboardChange};
 pub use config::{HarnessConfig, MemoryConfig};
+#[cfg(feature = "config-sources")]
+pub use config_sources::HarnessSettings;
 pub use events::{
     CompositeEventHandler, EventHandler, EventObserver, EventResponse, FnEventHandler,
-    HarnessEvent, HarnessResult, LoggingHandler, NoopHandler, StatefulToolResultBuilder,
-    ToolResultHandler,
+    HarnessEvent, HarnessResult, LoggingHandler, NoopHandler, RoundDecisions, RoundOutcome,
+    StatefulToolResultBuilder, ToolResultHandler, ToolStats,
 };
-pub use gather::{ContextGatherer, GatherEvent, GatherObserver, UiGatherObserver};
+#[cfg(feature = "ui")]
+pub use gather::UiGatherObserver;
+pub use gather::{ContextGatherer, GatherEvent, GatherObserver};
+pub use handoff::{HandoffRecord, HandoffResult, HandoffRunner};
 pub use harness::{Harness, build_default_prompt_registry};
 pub use hooks::{
     ExternalHookRunner, HookAction, HookConfig, HookEntry, LifecycleHook, LifecycleHookAdapter,
     StopAction,
 };
+pub use orchestrator::{Orchestrator, OrchestratorResult, Specialist};
+pub use profile::{AgentProfile, ToolUsage};
 pub use project_instructions::{ConditionalRule, ProjectInstructions};
 pub use prompt::{
-    PromptRegistry, PromptSection, ReminderFrequency, ReminderRegistry, RoundContext, Stability,
-    SystemPromptBuilder, SystemReminder, TurnContext,
+    PromptRegistry, PromptSection, ReminderFrequency, ReminderInjection, ReminderRegistry,
+    RoundContext, Stability, SystemPromptBuilder, SystemReminder, TurnContext,
 };
 pub use sub_agent::{SharedResources, TokenBudgetSemaphore};
+#[cfg(feature = "config-sources")]
+pub use templates::{AgentTemplate, AgentTemplateRegistry};
+pub use warm_start::{WarmStartInput, WarmStartReport, WarmStartStep};
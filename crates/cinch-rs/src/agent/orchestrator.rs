@@ -0,0 +1,713 @@
+//! Supervisor orchestration: a coordinator agent routing work to named
+//! specialists.
+//!
+//! An [`Orchestrator`] runs a coordinator harness whose only tool is
+//! `delegate`. Each call runs one [`Specialist`] — a child harness with its
+//! own system prompt, tool set, and model — and returns its answer to the
+//! coordinator, which decides what to do next and writes the merged result.
+//!
+//! - **Routing:** the coordinator picks a specialist by name. `handoff_from`
+//!   forwards earlier specialists' outputs into the next one's context, so a
+//!   reviewer can see what the coder produced without the coordinator
+//!   pasting it back.
+//! - **Shared budget:** every agent's token usage is drawn from one
+//!   [`TokenBudgetSemaphore`]. Once it is empty, a running specialist stops
+//!   after its current round, further delegations are refused, and the
+//!   coordinator is told to finish.
+//! - **Combined events:** every event from every agent reaches one
+//!   [`AgentEventHandler`], tagged with the agent's name ([`COORDINATOR`]
+//!   for the coordinator itself).
+//!
+//! Multiple `delegate` calls in one round run concurrently through the
+//! harness's parallel tool execution.
+//!
+//! ```ignore
+//! let orchestrator = Orchestrator::new(client, HarnessConfig::new(model, ""))
+//!     .with_specialist(Specialist::researcher(read_only_tools))
+//!     .with_specialist(Specialist::coder(all_tools))
+//!     .with_token_budget(200_000)
+//!     .with_event_handler(FnAgentEventHandler::new(|agent, event| {
+//!         if let HarnessEvent::Text(text) = event {
+//!             println!("[{agent}] {text}");
+//!         }
+//!         None
+//!     }));
+//! let result = orchestrator.run("Add retries to the HTTP client").await?;
+//! println!("{}", result.text());
+//! ```
+
+use crate::agent::config::{HarnessConfig, HarnessSessionConfig};
+use crate::agent::events::{EventHandler, EventResponse, HarnessEvent, HarnessResult, NoopHandler};
+use crate::agent::harness::Harness;
+use crate::agent::sub_agent::{SubAgentResult, TokenBudgetSemaphore};
+use crate::tools::core::{Tool, ToolFuture, ToolSet};
+use crate::{Message, OpenRouterClient, ToolDef};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
+
+/// Agent name used for the coordinator's own events.
+pub const COORDINATOR: &str = "coordinator";
+
+/// Default maximum characters of a specialist's answer returned to the
+/// coordinator.
+pub const DEFAULT_MAX_RESULT_CHARS: usize = 8000;
+
+const DEFAULT_COORDINATOR_PROMPT: &str = "You are a coordinator supervising a team of \
+specialist agents. Break the task into steps, delegate each step to the best-suited \
+specialist with the `delegate` tool, and check their results. Specialists do not see \
+each other's work unless you pass it via `context` or `handoff_from`. When the work is \
+done, reply with the final answer, merging the specialists' results.";
+
+// ── Specialists ─────────────────────────────────────────────────────
+
+/// A named agent the coordinator can delegate to.
+#[derive(Debug, Clone)]
+pub struct Specialist {
+    /// Name the coordinator uses to address this specialist.
+    pub name: String,
+    /// One-line description shown to the coordinator.
+    pub description: String,
+    /// System prompt of the specialist's harness.
+    pub system_prompt: String,
+    /// Tools available to the specialist.
+    pub tools: Arc<ToolSet>,
+    /// Model override. `None` uses the coordinator's model.
+    pub model: Option<String>,
+    /// Maximum rounds per delegation.
+    pub max_rounds: u32,
+}
+
+impl Specialist {
+    /// Create a specialist with its own prompt and tools.
+    pub fn new(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        system_prompt: impl Into<String>,
+        tools: ToolSet,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            system_prompt: system_prompt.into(),
+            tools: Arc::new(tools),
+            model: None,
+            max_rounds: 15,
+        }
+    }
+
+    /// Run this specialist on a different model (builder pattern).
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Set the maximum rounds per delegation (builder pattern).
+    pub fn with_max_rounds(mut self, max_rounds: u32) -> Self {
+        self.max_rounds = max_rounds;
+        self
+    }
+
+    /// A `researcher` that gathers information. Give it read-only tools.
+    pub fn researcher(tools: ToolSet) -> Self {
+        Self::new(
+            "researcher",
+            "Investigates the codebase or documents and reports findings. Does not modify files.",
+            "You are a research specialist. Investigate the task thoroughly using your \
+             tools and report concrete findings: file paths, line numbers, relevant code, \
+             and open questions. Do not modify anything.",
+            tools,
+        )
+    }
+
+    /// A `coder` that implements changes.
+    pub fn coder(tools: ToolSet) -> Self {
+        Self::new(
+            "coder",
+            "Implements code changes and verifies they build.",
+            "You are a coding specialist. Implement the requested change with minimal, \
+             focused edits that follow the surrounding code's conventions, verify it, and \
+             summarize exactly what you changed.",
+            tools,
+        )
+    }
+
+    /// A `reviewer` that checks work. Give it read-only tools.
+    pub fn reviewer(tools: ToolSet) -> Self {
+        Self::new(
+            "reviewer",
+            "Reviews changes for bugs, missed requirements, and style problems.",
+            "You are a code review specialist. Check the described work against the task: \
+             look for bugs, missed requirements, and convention violations. Reply with \
+             either APPROVED or a numbered list of required fixes.",
+            tools,
+        )
+    }
+}
+
+// ── Events ──────────────────────────────────────────────────────────
+
+/// Observer for the combined event stream of all agents in an
+/// orchestration.
+///
+/// `agent` is the specialist name, or [`COORDINATOR`]. Responses are
+/// honored exactly as for [`EventHandler::on_event`] on the agent that
+/// emitted the event.
+pub trait AgentEventHandler: Send + Sync {
+    fn on_agent_event(&self, agent: &str, event: &HarnessEvent<'_>) -> Option<EventResponse>;
+}
+
+impl AgentEventHandler for NoopHandler {
+    fn on_agent_event(&self, _agent: &str, _event: &HarnessEvent<'_>) -> Option<EventResponse> {
+        None
+    }
+}
+
+/// An [`AgentEventHandler`] backed by a closure.
+pub struct FnAgentEventHandler<F>(F)
+where
+    F: Fn(&str, &HarnessEvent<'_>) -> Option<EventResponse> + Send + Sync;
+
+impl<F> FnAgentEventHandler<F>
+where
+    F: Fn(&str, &HarnessEvent<'_>) -> Option<EventResponse> + Send + Sync,
+{
+    pub fn new(f: F) -> Self {
+        Self(f)
+    }
+}
+
+impl<F> AgentEventHandler for FnAgentEventHandler<F>
+where
+    F: Fn(&str, &HarnessEvent<'_>) -> Option<EventResponse> + Send + Sync,
+{
+    fn on_agent_event(&self, agent: &str, event: &HarnessEvent<'_>) -> Option<EventResponse> {
+        (self.0)(agent, event)
+    }
+}
+
+/// Per-agent [`EventHandler`]: tags events with the agent name and charges
+/// token usage to the shared budget.
+struct TaggedHandler<'a> {
+    agent: &'a str,
+    handler: &'a dyn AgentEventHandler,
+    budget: &'a TokenBudgetSemaphore,
+}
+
+impl EventHandler for TaggedHandler<'_> {
+    fn on_event(&self, event: &HarnessEvent<'_>) -> Option<EventResponse> {
+        if let Some(tokens) = event.total_tokens() {
+            self.budget.acquire(tokens);
+        }
+        self.handler.on_agent_event(self.agent, event)
+    }
+}
+
+// ── Results ─────────────────────────────────────────────────────────
+
+/// Record of one delegation.
+#[derive(Debug, Clone)]
+pub struct SpecialistRun {
+    /// Specialist name.
+    pub name: String,
+    /// Task the coordinator gave it.
+    pub task: String,
+    /// The specialist's full final answer (before truncation for the
+    /// coordinator).
+    pub output: String,
+    /// Whether the specialist finished naturally (vs. hit its round limit).
+    pub finished: bool,
+    pub rounds_used: u32,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// Outcome of [`Orchestrator::run`].
+#[derive(Debug)]
+pub struct OrchestratorResult {
+    /// The coordinator's run; its text is the merged answer.
+    pub coordinator: HarnessResult,
+    /// Every delegation, in completion order.
+    pub specialist_runs: Vec<SpecialistRun>,
+    /// Whether the shared token budget ran out.
+    pub budget_exhausted: bool,
+}
+
+impl OrchestratorResult {
+    /// The coordinator's final answer.
+    pub fn text(&self) -> String {
+        self.coordinator.text()
+    }
+
+    /// Tokens used by the coordinator and all specialists.
+    pub fn total_tokens(&self) -> u64 {
+        self.coordinator.total_tokens()
+            + self
+                .specialist_runs
+                .iter()
+                .map(|r| r.prompt_tokens + r.completion_tokens)
+                .sum::<u64>()
+    }
+
+    /// Estimated cost of the coordinator and all specialists.
+    pub fn total_cost_usd(&self) -> f64 {
+        self.coordinator.estimated_cost_usd
+            + self.specialist_runs.iter().map(|r| r.cost_usd).sum::<f64>()
+    }
+}
+
+// ── Orchestrator ────────────────────────────────────────────────────
+
+/// Supervisor running a coordinator agent over a team of [`Specialist`]s.
+pub struct Orchestrator {
+    client: Arc<OpenRouterClient>,
+    config: HarnessConfig,
+    specialists: Vec<Specialist>,
+    token_budget: u64,
+    max_result_chars: usize,
+    handler: Arc<dyn AgentEventHandler>,
+}
+
+impl Orchestrator {
+    /// Create an orchestrator. `config` drives the coordinator; its
+    /// `system_prompt`, when set, replaces the default coordinator
+    /// instructions. Specialists run with the same settings (model,
+    /// generation, provider, ...) unless they override them.
+    pub fn new(client: Arc<OpenRouterClient>, config: HarnessConfig) -> Self {
+        Self {
+            client,
+            config,
+            specialists: Vec::new(),
+            token_budget: u64::MAX,
+            max_result_chars: DEFAULT_MAX_RESULT_CHARS,
+            handler: Arc::new(NoopHandler),
+        }
+    }
+
+    /// Add a specialist (builder pattern). A specialist with the same name
+    /// replaces the earlier one.
+    pub fn with_specialist(mut self, specialist: Specialist) -> Self {
+        self.specialists.retain(|s| s.name != specialist.name);
+        self.specialists.push(specialist);
+        self
+    }
+
+    /// Cap the tokens used by all agents together (builder pattern).
+    pub fn with_token_budget(mut self, tokens: u64) -> Self {
+        self.token_budget = tokens;
+        self
+    }
+
+    /// Maximum characters of a specialist's answer returned to the
+    /// coordinator (builder pattern).
+    pub fn with_max_result_chars(mut self, max_chars: usize) -> Self {
+        self.max_result_chars = max_chars;
+        self
+    }
+
+    /// Receive the combined event stream of all agents (builder pattern).
+    pub fn with_event_handler(mut self, handler: impl AgentEventHandler + 'static) -> Self {
+        self.handler = Arc::new(handler);
+        self
+    }
+
+    /// Names of the registered specialists.
+    pub fn specialist_names(&self) -> Vec<&str> {
+        self.specialists.iter().map(|s| s.name.as_str()).collect()
+    }
+
+    /// Run the coordinator on `task` until it writes a final answer.
+    pub async fn run(&self, task: &str) -> Result<OrchestratorResult, String> {
+        if self.specialists.is_empty() {
+            return Err("orchestrator has no specialists".into());
+        }
+        let budget = Arc::new(TokenBudgetSemaphore::new(self.token_budget));
+        let runs = Arc::new(Mutex::new(Vec::new()));
+        let tools = ToolSet::new().with(DelegateTool {
+            client: Arc::clone(&self.client),
+            specialists: self.specialists.clone(),
+            config: self.config.clone(),
+            budget: Arc::clone(&budget),
+            handler: Arc::clone(&self.handler),
+            max_result_chars: self.max_result_chars,
+            runs: Arc::clone(&runs),
+        });
+
+        let handler = TaggedHandler {
+            agent: COORDINATOR,
+            handler: self.handler.as_ref(),
+            budget: &budget,
+        };
+        let messages = vec![
+            Message::system(self.coordinator_prompt()),
+            Message::user(task),
+        ];
+        let coordinator = Harness::new(&self.client, &tools, self.config.clone())
+            .with_event_handler(&handler)
+            .run(messages)
+            .await?;
+
+        let specialist_runs = std::mem::take(&mut *runs.lock().unwrap());
+        Ok(OrchestratorResult {
+            coordinator,
+            specialist_runs,
+            budget_exhausted: budget.remaining() == 0,
+        })
+    }
+
+    fn coordinator_prompt(&self) -> String {
+        let base = self
+            .config
+            .system_prompt
+            .as_deref()
+            .filter(|p| !p.is_empty())
+            .unwrap_or(DEFAULT_COORDINATOR_PROMPT);
+        let roster: String = self
+            .specialists
+            .iter()
+            .map(|s| format!("- {}: {}\n", s.name, s.description))
+            .collect();
+        format!("{base}\n\nAvailable specialists:\n{roster}")
+    }
+}
+
+// ── Delegate tool ───────────────────────────────────────────────────
+
+/// Arguments for the coordinator's `delegate` tool.
+#[derive(Debug, Deserialize, JsonSchema)]
+struct DelegateArgs {
+    /// Name of the specialist to run.
+    specialist: String,
+    /// Self-contained task for the specialist.
+    task: String,
+    /// Extra context: findings, decisions, constraints.
+    #[serde(default)]
+    context: Option<String>,
+    /// Specialists whose latest output should be forwarded as context.
+    #[serde(default)]
+    handoff_from: Vec<String>,
+}
+
+struct DelegateTool {
+    client: Arc<OpenRouterClient>,
+    specialists: Vec<Specialist>,
+    /// The coordinator's config, which specialists' configs derive from.
+    config: HarnessConfig,
+    budget: Arc<TokenBudgetSemaphore>,
+    handler: Arc<dyn AgentEventHandler>,
+    max_result_chars: usize,
+    runs: Arc<Mutex<Vec<SpecialistRun>>>,
+}
+
+impl DelegateTool {
+    /// Context for the child: explicit context plus forwarded outputs.
+    fn child_context(&self, args: &DelegateArgs) -> Result<Option<String>, String> {
+        let mut sections: Vec<String> = args.context.iter().cloned().collect();
+        let runs = self.runs.lock().unwrap();
+        for from in &args.handoff_from {
+            let Some(run) = runs.iter().rev().find(|r| &r.name == from) else {
+                return Err(format!(
+                    "Error: no output from '{from}' to hand off. Delegate to it first."
+                ));
+            };
+            sections.push(format!("Output from {from}:\n{}", run.output));
+        }
+        Ok((!sections.is_empty()).then(|| sections.join("\n\n")))
+    }
+
+    /// The coordinator's config with `specialist`'s overrides. Specialists
+    /// get their prompt in the messages and keep no session or memory.
+    fn specialist_config(&self, specialist: &Specialist) -> HarnessConfig {
+        let mut config = self.config.clone().with_max_rounds(specialist.max_rounds);
+        if let Some(ref model) = specialist.model {
+            config.routing = HarnessConfig::new(model, "").routing;
+            config.model = model.clone();
+        }
+        config.system_prompt = None;
+        config.agent_name = Some(specialist.name.clone());
+        config.output_schema = None;
+        config.session = HarnessSessionConfig::disabled();
+        config.memory_prompt = None;
+        // The specialist's tools still draw from the coordinator's budget;
+        // only the coordinator's run resets it.
+        config.subprocess_budget = None;
+        config
+    }
+}
+
+impl Tool for DelegateTool {
+    fn definition(&self) -> ToolDef {
+        let mut schema = crate::json_schema_for::<DelegateArgs>();
+        let names: Vec<&str> = self.specialists.iter().map(|s| s.name.as_str()).collect();
+        if let Some(specialist) = schema.pointer_mut("/properties/specialist") {
+            specialist["enum"] = serde_json::json!(names);
+        }
+        ToolDef::new(
+            "delegate",
+            "Run a specialist agent on a task and return its answer. Use handoff_from to \
+             pass earlier specialists' outputs along. Multiple delegate calls in one round \
+             run concurrently.",
+            schema,
+        )
+    }
+
+    /// Specialists may run mutation tools.
+    fn is_mutation(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, arguments: &str) -> ToolFuture<'_> {
+        let arguments = arguments.to_string();
+        Box::pin(async move {
+            let args: DelegateArgs = match serde_json::from_str(&arguments) {
+                Ok(a) => a,
+                Err(e) => {
+                    return format!(
+                        "Error: invalid arguments for delegate: {e}. \
+                         Required: specialist (string), task (string)."
+                    );
+                }
+            };
+            let Some(specialist) = self.specialists.iter().find(|s| s.name == args.specialist)
+            else {
+                return format!(
+                    "Error: unknown specialist '{}'. Available: {}.",
+                    args.specialist,
+                    self.specialists
+                        .iter()
+                        .map(|s| s.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            };
+            if self.budget.remaining() == 0 {
+                return "Error: the shared token budget is exhausted. Do not delegate \
+                        further; write your final answer from the results so far."
+                    .into();
+            }
+            let context = match self.child_context(&args) {
+                Ok(c) => c,
+                Err(e) => return e,
+            };
+
+            let config = self.specialist_config(specialist);
+            info!(
+                "Delegating to specialist '{}' (model={}, max_rounds={})",
+                specialist.name, config.model, specialist.max_rounds
+            );
+            let prompt = match context {
+                Some(ctx) => format!(
+                    "{}\n\nContext from coordinator:\n{ctx}",
+                    specialist.system_prompt
+                ),
+                None => specialist.system_prompt.clone(),
+            };
+            let messages = vec![Message::system(prompt), Message::user(&args.task)];
+            let handler = TaggedHandler {
+                agent: &specialist.name,
+                handler: self.handler.as_ref(),
+                budget: &self.budget,
+            };
+
+            let budget = &self.budget;
+            let result = match Harness::new(&self.client, &specialist.tools, config)
+                .with_event_handler(&handler)
+                .with_stop_signal(move || budget.remaining() == 0)
+                .run(messages)
+                .await
+            {
+                Ok(result) => result,
+                Err(e) => {
+                    warn!("Specialist '{}' failed: {e}", specialist.name);
+                    return format!("[Specialist '{}' failed] Error: {e}", specialist.name);
+                }
+            };
+
+            let output = result.text();
+            let summary = SubAgentResult {
+                name: specialist.name.clone(),
+                output: truncate(&output, self.max_result_chars),
+                finished: result.finished,
+                rounds_used: result.rounds_used,
+                tokens_consumed: result.total_prompt_tokens + result.total_completion_tokens,
+            }
+            .to_parent_result();
+            self.runs.lock().unwrap().push(SpecialistRun {
+                name: specialist.name.clone(),
+                task: args.task,
+                output,
+                finished: result.finished,
+                rounds_used: result.rounds_used,
+                prompt_tokens: result.total_prompt_tokens,
+                completion_tokens: result.total_completion_tokens,
+                cost_usd: result.estimated_cost_usd,
+            });
+            summary
+        })
+    }
+}
+
+fn truncate(output: &str, max_chars: usize) -> String {
+    if output.chars().count() > max_chars {
+        let mut s: String = output.chars().take(max_chars).collect();
+        s.push_str("\n[output truncated]");
+        s
+    } else {
+        output.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, ScriptedTransport, test_config, tool_call_reply, with_usage};
+
+    fn text_reply(text: &str) -> String {
+        with_usage(&test_support::text_reply(text), 60, 40)
+    }
+
+    fn delegate_call(arguments: &str) -> String {
+        let arguments = serde_json::from_str(arguments).unwrap();
+        with_usage(&tool_call_reply("c1", "delegate", &arguments), 60, 40)
+    }
+
+    fn orchestrator(replies: Vec<String>) -> Orchestrator {
+        let client = OpenRouterClient::with_transport("key", ScriptedTransport::new(replies));
+        Orchestrator::new(Arc::new(client), test_config(""))
+            .with_specialist(Specialist::researcher(ToolSet::new()))
+            .with_specialist(Specialist::reviewer(ToolSet::new()))
+    }
+
+    #[tokio::test]
+    async fn routes_work_and_merges_results() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&events);
+        let orchestrator = orchestrator(vec![
+            delegate_call(r#"{"specialist":"researcher","task":"find it"}"#),
+            text_reply("found in lib.rs"),
+            delegate_call(
+                r#"{"specialist":"reviewer","task":"check","handoff_from":["researcher"]}"#,
+            ),
+            text_reply("APPROVED"),
+            text_reply("done: lib.rs, approved"),
+        ])
+        .with_event_handler(FnAgentEventHandler::new(move |agent, event| {
+            if let HarnessEvent::Text(text) = event {
+                seen.lock().unwrap().push(format!("{agent}: {text}"));
+            }
+            None
+        }));
+
+        let result = orchestrator.run("locate and review").await.unwrap();
+        assert_eq!(result.text(), "done: lib.rs, approved");
+        let names: Vec<_> = result
+            .specialist_runs
+            .iter()
+            .map(|r| r.name.as_str())
+            .collect();
+        assert_eq!(names, ["researcher", "reviewer"]);
+        assert_eq!(result.specialist_runs[0].output, "found in lib.rs");
+        assert_eq!(result.total_tokens(), 500);
+        assert!(!result.budget_exhausted);
+
+        let events = events.lock().unwrap();
+        assert!(
+            events.contains(&"researcher: found in lib.rs".to_string()),
+            "{events:?}"
+        );
+        assert!(events.contains(&"coordinator: done: lib.rs, approved".to_string()));
+    }
+
+    #[tokio::test]
+    async fn exhausted_budget_refuses_delegation() {
+        let orchestrator = orchestrator(vec![
+            delegate_call(r#"{"specialist":"researcher","task":"find it"}"#),
+            text_reply("gave up"),
+        ])
+        .with_token_budget(50);
+
+        let result = orchestrator.run("task").await.unwrap();
+        assert!(result.budget_exhausted);
+        assert!(result.specialist_runs.is_empty());
+        let refusal = result
+            .coordinator
+            .messages
+            .iter()
+            .filter_map(|m| m.content.as_deref())
+            .find(|c| c.contains("budget is exhausted"));
+        assert!(refusal.is_some());
+    }
+
+    #[tokio::test]
+    async fn exhausted_budget_stops_a_running_specialist() {
+        let orchestrator = orchestrator(vec![
+            delegate_call(r#"{"specialist":"researcher","task":"find it"}"#),
+            // The researcher's first round spends the rest of the budget.
+            delegate_call(r#"{"specialist":"researcher","task":"recurse"}"#),
+            text_reply("stopped early"),
+        ])
+        .with_token_budget(150);
+
+        let result = orchestrator.run("task").await.unwrap();
+        assert!(result.budget_exhausted);
+        assert_eq!(result.specialist_runs.len(), 1);
+        assert!(!result.specialist_runs[0].finished);
+        assert_eq!(result.specialist_runs[0].rounds_used, 1);
+        assert_eq!(result.text(), "stopped early");
+    }
+
+    #[test]
+    fn specialists_derive_their_config_from_the_coordinators() {
+        let mut orchestrator = orchestrator(Vec::new());
+        orchestrator.config = orchestrator
+            .config
+            .clone()
+            .with_max_tokens(1234)
+            .with_temperature(0.1);
+        orchestrator.config.system_prompt = Some("coordinate".into());
+        let tool = DelegateTool {
+            client: Arc::clone(&orchestrator.client),
+            specialists: orchestrator.specialists.clone(),
+            config: orchestrator.config.clone(),
+            budget: Arc::new(TokenBudgetSemaphore::new(1)),
+            handler: Arc::new(NoopHandler),
+            max_result_chars: 10,
+            runs: Arc::default(),
+        };
+        let specialist = Specialist::coder(ToolSet::new())
+            .with_model("other/model")
+            .with_max_rounds(3);
+
+        let config = tool.specialist_config(&specialist);
+        assert_eq!((config.max_tokens, config.temperature), (1234, 0.1));
+        assert_eq!(config.model, "other/model");
+        assert_eq!(config.max_rounds, 3);
+        assert_eq!(config.system_prompt, None);
+        assert_eq!(config.agent_name.as_deref(), Some("coder"));
+    }
+
+    #[test]
+    fn schema_lists_specialists_and_prompt_has_roster() {
+        let orchestrator = orchestrator(Vec::new());
+        assert_eq!(orchestrator.specialist_names(), ["researcher", "reviewer"]);
+        let prompt = orchestrator.coordinator_prompt();
+        assert!(prompt.contains("- reviewer: Reviews changes"), "{prompt}");
+
+        let tool = DelegateTool {
+            client: Arc::clone(&orchestrator.client),
+            specialists: orchestrator.specialists.clone(),
+            config: orchestrator.config.clone(),
+            budget: Arc::new(TokenBudgetSemaphore::new(1)),
+            handler: Arc::new(NoopHandler),
+            max_result_chars: 10,
+            runs: Arc::default(),
+        };
+        let params = tool.definition().function.parameters;
+        assert_eq!(
+            params.pointer("/properties/specialist/enum"),
+            Some(&serde_json::json!(["researcher", "reviewer"]))
+        );
+    }
+}
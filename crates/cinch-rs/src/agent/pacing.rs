@@ -0,0 +1,174 @@
+//! Deadline-aware round pacing.
+//!
+//! A run with a round limit but no sense of time tends to end mid-thought:
+//! the model keeps exploring until the limit (or the caller's timeout) cuts
+//! it off. With a deadline set in
+//! [`HarnessPacingConfig`], the [`Pacer`] tracks the time left and, once it
+//! drops below [`HarnessPacingConfig::wrap_up_fraction`] of the deadline,
+//! puts the run into its wrap-up window. Wrap-up rounds:
+//!
+//! - route to [`HarnessPacingConfig::fast_model`] when one is set,
+//! - cap `max_tokens` at [`HarnessPacingConfig::wrap_up_max_tokens`],
+//! - skip the rest of the plan-execute planning phase,
+//! - get the wrap-up reminder injected.
+//!
+//! Pacing never stops a run by itself; combine it with a stop signal or
+//! [`HarnessConfig::max_rounds`](super::config::HarnessConfig::max_rounds)
+//! for a hard limit.
+
+use std::time::Duration;
+
+use super::config::HarnessPacingConfig;
+use crate::platform::Instant;
+
+/// Per-run pacing state.
+pub(crate) struct Pacer {
+    config: HarnessPacingConfig,
+    deadline: Duration,
+    started: Instant,
+    /// Whether the wrap-up window has been announced.
+    engaged: bool,
+}
+
+impl Pacer {
+    /// Start pacing now. `None` when the config has no deadline.
+    pub(crate) fn new(config: HarnessPacingConfig) -> Option<Self> {
+        let deadline = config.deadline?;
+        Some(Self {
+            config,
+            deadline,
+            started: Instant::now(),
+            engaged: false,
+        })
+    }
+
+    /// The time left when a round starting after `elapsed` run time falls
+    /// in the wrap-up window (zero once the deadline has passed), or `None`
+    /// when there is still plenty of time.
+    pub(crate) fn wrap_up_at(&self, elapsed: Duration) -> Option<Duration> {
+        let remaining = self.deadline.saturating_sub(elapsed);
+        let window = self
+            .deadline
+            .mul_f64(self.config.wrap_up_fraction.clamp(0.0, 1.0));
+        (remaining <= window).then_some(remaining)
+    }
+
+    /// [`wrap_up_at()`](Self::wrap_up_at) for a round starting now.
+    pub(crate) fn wrap_up(&self) -> Option<Duration> {
+        self.wrap_up_at(self.started.elapsed())
+    }
+
+    /// Mark the wrap-up window as entered. Returns `true` the first time,
+    /// so the harness announces it once.
+    pub(crate) fn engage(&mut self) -> bool {
+        !std::mem::replace(&mut self.engaged, true)
+    }
+
+    /// The model override for wrap-up rounds.
+    pub(crate) fn fast_model(&self) -> Option<&str> {
+        self.config.fast_model.as_deref()
+    }
+
+    /// `max_tokens` for a wrap-up round, given the configured value.
+    pub(crate) fn max_tokens(&self, configured: u32) -> u32 {
+        self.config
+            .wrap_up_max_tokens
+            .map_or(configured, |cap| configured.min(cap))
+    }
+
+    /// The wrap-up reminder text.
+    pub(crate) fn reminder(&self) -> &str {
+        &self.config.reminder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::events::{FnEventHandler, HarnessEvent};
+    use crate::agent::harness::Harness;
+    use crate::test_support::{ScriptedTransport, test_config, text_reply};
+    use crate::tools::core::ToolSet;
+    use crate::{Message, OpenRouterClient};
+    use std::sync::{Arc, Mutex};
+
+    fn pacer(secs: u64) -> Pacer {
+        Pacer::new(HarnessPacingConfig::with_deadline(Duration::from_secs(
+            secs,
+        )))
+        .unwrap()
+    }
+
+    #[test]
+    fn no_deadline_means_no_pacer() {
+        assert!(Pacer::new(HarnessPacingConfig::default()).is_none());
+    }
+
+    #[test]
+    fn wrap_up_starts_in_the_last_fraction() {
+        let pacer = pacer(100);
+        assert_eq!(pacer.wrap_up_at(Duration::from_secs(10)), None);
+        assert_eq!(pacer.wrap_up_at(Duration::from_secs(79)), None);
+        assert_eq!(
+            pacer.wrap_up_at(Duration::from_secs(85)),
+            Some(Duration::from_secs(15))
+        );
+        assert_eq!(
+            pacer.wrap_up_at(Duration::from_secs(120)),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn engage_fires_once_and_caps_tokens() {
+        let mut pacer = pacer(60);
+        assert!(pacer.engage());
+        assert!(!pacer.engage());
+        assert_eq!(pacer.max_tokens(4096), 1024);
+        assert_eq!(pacer.max_tokens(512), 512);
+    }
+
+    #[tokio::test]
+    async fn wrap_up_rounds_are_fast_short_and_reminded() {
+        let transport = ScriptedTransport::repeating(text_reply("done"));
+        let requests = transport.requests();
+        let client = OpenRouterClient::with_transport("key", transport);
+        let tools = ToolSet::new();
+        let mut config = test_config("")
+            .with_max_tokens(4096)
+            .with_pacing(HarnessPacingConfig {
+                fast_model: Some("test/fast".into()),
+                ..HarnessPacingConfig::with_deadline(Duration::ZERO)
+            });
+        // Pacing also cuts the planning phase short.
+        config.plan_execute.enabled = true;
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&events);
+        let handler = FnEventHandler::new(move |event| {
+            match event {
+                HarnessEvent::PacingEngaged { round, .. } => {
+                    seen.lock().unwrap().push(format!("paced {round}"));
+                }
+                HarnessEvent::PhaseTransition { .. } => {
+                    seen.lock().unwrap().push("phase".into());
+                }
+                _ => {}
+            }
+            None
+        });
+        let result = Harness::new(&client, &tools, config)
+            .with_event_handler(&handler)
+            .run(vec![Message::user("fix the bug")])
+            .await
+            .unwrap();
+
+        assert!(result.finished);
+        assert_eq!(*events.lock().unwrap(), ["paced 1", "phase"]);
+        let requests = requests.lock().unwrap();
+        let body: serde_json::Value = serde_json::from_str(&requests[0]).unwrap();
+        assert_eq!(body["model"], "test/fast");
+        assert_eq!(body["max_tokens"], 1024);
+        assert!(requests[0].contains("Time is almost up"), "{}", requests[0]);
+    }
+}
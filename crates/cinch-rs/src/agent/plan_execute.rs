@@ -13,6 +13,7 @@
 //! Based on patterns from Claude Code (plan mode) and LATS/React.
 
 use crate::ToolDef;
+use crate::tools::core::ToolSet;
 
 /// Phase of a plan-then-execute workflow.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -31,9 +32,14 @@ pub struct PlanExecuteConfig {
     /// Default: 10.
     pub max_planning_rounds: u32,
     /// Tool names allowed during planning. Tools not in this list are hidden
-    /// from the LLM during the planning phase. Any name listed here that
-    /// doesn't correspond to a registered tool is silently ignored.
+    /// from the LLM during the planning phase (unless
+    /// `include_read_only_tools` is set). Any name listed here that doesn't
+    /// correspond to a registered tool is silently ignored.
     pub planning_tools: Vec<String>,
+    /// Also allow every non-mutating tool during planning (see
+    /// [`ToolSet::read_only_view`]), so custom read-only tools don't need to
+    /// be listed in `planning_tools`. Default: `false`.
+    pub include_read_only_tools: bool,
     /// Prompt injected as a user message at the start of the planning phase.
     pub planning_prompt: String,
     /// Prompt injected as a user message when transitioning to execution.
@@ -48,6 +54,9 @@ impl Default for PlanExecuteConfig {
                 // Reasoning tools (free — don't consume rounds).
                 crate::tools::names::THINK.into(),
                 crate::tools::names::TODO.into(),
+                crate::tools::names::SCRATCHPAD.into(),
+                crate::tools::names::PIN.into(),
+                crate::tools::names::RECALL_HISTORY.into(),
                 // Exploration tools.
                 crate::tools::names::READ_FILE.into(),
                 crate::tools::names::LIST_DIR.into(),
@@ -57,6 +66,7 @@ impl Default for PlanExecuteConfig {
                 // Sub-agent delegation (if registered).
                 "delegate_sub_agent".into(),
             ],
+            include_read_only_tools: false,
             planning_prompt: DEFAULT_PLANNING_PROMPT.into(),
             execution_prompt: DEFAULT_EXECUTION_PROMPT.into(),
         }
@@ -92,16 +102,25 @@ created, adapting as needed if you discover something unexpected. Mark todo \
 items as in-progress when you start them and complete when done.";
 
 impl PlanExecuteConfig {
-    /// Filter tool definitions to only include planning-phase tools.
+    /// Filter tool definitions to only include planning-phase tools: those
+    /// listed in `planning_tools`, plus those in `tools`'
+    /// [read-only view](ToolSet::read_only_view) when
+    /// `include_read_only_tools` is set.
     ///
     /// Tools listed in `planning_tools` that don't exist in `all_tools` are
     /// silently skipped — this is intentional so that optional tools like
     /// `delegate_sub_agent` can appear in the default list without requiring
     /// every agent to register them.
-    pub fn filter_planning_tools(&self, all_tools: &[ToolDef]) -> Vec<ToolDef> {
+    pub fn filter_planning_tools(&self, all_tools: &[ToolDef], tools: &ToolSet) -> Vec<ToolDef> {
+        let read_only = self.include_read_only_tools.then(|| tools.read_only_view());
         all_tools
             .iter()
-            .filter(|t| self.planning_tools.contains(&t.function.name))
+            .filter(|t| {
+                self.planning_tools.contains(&t.function.name)
+                    || read_only
+                        .as_ref()
+                        .is_some_and(|r| r.has_tool(&t.function.name))
+            })
             .cloned()
             .collect()
     }
@@ -139,35 +158,54 @@ impl PlanExecuteConfig {
 mod tests {
     use super::*;
 
+    fn tool(name: &str, mutation: bool) -> crate::tools::core::FnTool {
+        crate::tools::core::FnTool::new(
+            ToolDef::new(name, name, serde_json::json!({})),
+            |_: serde_json::Value| async { String::new() },
+        )
+        .mutation(mutation)
+    }
+
     #[test]
     fn filter_planning_tools_includes_defaults() {
         let config = PlanExecuteConfig::default();
-        let all_tools = vec![
-            ToolDef::new("read_file", "Read a file", serde_json::json!({})),
-            ToolDef::new("shell", "Run shell command", serde_json::json!({})),
-            ToolDef::new("save_draft", "Save a draft", serde_json::json!({})),
-        ];
-
-        let planning = config.filter_planning_tools(&all_tools);
-        // read_file and shell are in the default planning tools, save_draft is not.
-        assert_eq!(planning.len(), 2);
+        let tools = ToolSet::new()
+            .with(tool("read_file", false))
+            .with(tool("shell", true))
+            .with(tool("save_draft", true))
+            .with(tool("lookup", false));
+
+        let planning = config.filter_planning_tools(&tools.definitions(), &tools);
+        // Only the allowlist: lookup is read-only but not listed.
         let names: Vec<_> = planning.iter().map(|t| t.function.name.as_str()).collect();
+        assert_eq!(names.len(), 2);
         assert!(names.contains(&"read_file"));
         assert!(names.contains(&"shell"));
         assert!(!names.contains(&"save_draft"));
     }
 
+    #[test]
+    fn filter_planning_tools_can_include_read_only_tools() {
+        let config = PlanExecuteConfig {
+            include_read_only_tools: true,
+            ..PlanExecuteConfig::default()
+        };
+        let tools = ToolSet::new()
+            .with(tool("lookup", false))
+            .with(tool("save_draft", true));
+
+        let planning = config.filter_planning_tools(&tools.definitions(), &tools);
+        let names: Vec<_> = planning.iter().map(|t| t.function.name.as_str()).collect();
+        assert_eq!(names, ["lookup"]);
+    }
+
     #[test]
     fn filter_planning_tools_ignores_unregistered() {
         let config = PlanExecuteConfig::default();
-        // delegate_sub_agent is in the default list but not registered here.
-        let all_tools = vec![ToolDef::new(
-            "read_file",
-            "Read a file",
-            serde_json::json!({}),
-        )];
-
-        let planning = config.filter_planning_tools(&all_tools);
+        // shell is in the default list but not registered here.
+        let tools = ToolSet::new().with(tool("read_file", false));
+
+        let planning = config.filter_planning_tools(&tools.definitions(), &tools);
         assert_eq!(planning.len(), 1);
         assert_eq!(planning[0].function.name, "read_file");
     }
@@ -0,0 +1,142 @@
+//! Persistent per-agent tool usage statistics.
+//!
+//! An [`AgentProfile`] records how often each tool is offered, how often it
+//! is called, and how often those calls succeed, accumulated across runs and
+//! stored as JSON. Attached
+//! to a [`ToolFilter`](crate::tools::filter::ToolFilter) with
+//! [`with_profile_file()`](crate::tools::filter::ToolFilter::with_profile_file),
+//! it lets the filter drop tools an agent never uses or never succeeds with,
+//! and order the rest by how often they are used.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Call and success counts for one tool.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolUsage {
+    /// Total calls across all recorded runs.
+    pub calls: u32,
+    /// Calls whose result was not an error.
+    pub successes: u32,
+    /// Runs in which the tool was offered to the model.
+    #[serde(default)]
+    pub offered_runs: u32,
+    /// Index of the last run the tool was offered in (the profile's
+    /// [`runs`](AgentProfile::runs) count at the time).
+    #[serde(default)]
+    pub last_offered: u32,
+}
+
+/// Tool usage statistics accumulated across an agent's runs.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AgentProfile {
+    /// Number of completed runs recorded in this profile.
+    pub runs: u32,
+    /// Per-tool usage, keyed by tool name.
+    #[serde(default)]
+    pub tools: HashMap<String, ToolUsage>,
+}
+
+impl AgentProfile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a profile from `path`. A missing file yields an empty profile.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read agent profile: {e}"))?;
+        serde_json::from_str(&json).map_err(|e| format!("Failed to parse agent profile: {e}"))
+    }
+
+    /// Write the profile to `path` as JSON, creating parent directories.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create profile dir: {e}"))?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize agent profile: {e}"))?;
+        std::fs::write(path, json).map_err(|e| format!("Failed to write agent profile: {e}"))
+    }
+
+    /// Record one tool call and whether it succeeded.
+    pub fn record_call(&mut self, tool_name: &str, success: bool) {
+        let usage = self.tools.entry(tool_name.to_string()).or_default();
+        usage.calls += 1;
+        if success {
+            usage.successes += 1;
+        }
+    }
+
+    /// Record that `tool_name` was offered to the model in the current run.
+    /// Repeated calls in the same run count once.
+    pub fn record_offered(&mut self, tool_name: &str) {
+        let runs = self.runs;
+        let usage = self.tools.entry(tool_name.to_string()).or_default();
+        if usage.offered_runs > 0 && usage.last_offered == runs {
+            return;
+        }
+        usage.offered_runs += 1;
+        usage.last_offered = runs;
+    }
+
+    /// Mark the end of a run.
+    pub fn finish_run(&mut self) {
+        self.runs += 1;
+    }
+
+    /// Usage for a tool (zero if it was never called).
+    pub fn usage(&self, tool_name: &str) -> ToolUsage {
+        self.tools.get(tool_name).copied().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_calls_and_round_trips() {
+        let mut profile = AgentProfile::new();
+        profile.record_call("grep", true);
+        profile.record_call("grep", false);
+        profile.finish_run();
+        assert_eq!(
+            profile.usage("grep"),
+            ToolUsage {
+                calls: 2,
+                successes: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(profile.usage("shell"), ToolUsage::default());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("profiles/agent.json");
+        assert_eq!(AgentProfile::load(&path).unwrap(), AgentProfile::default());
+        profile.save(&path).unwrap();
+        assert_eq!(AgentProfile::load(&path).unwrap(), profile);
+    }
+
+    #[test]
+    fn offers_count_once_per_run() {
+        let mut profile = AgentProfile::new();
+        profile.record_offered("grep");
+        profile.record_offered("grep");
+        profile.finish_run();
+        profile.record_offered("grep");
+        let usage = profile.usage("grep");
+        assert_eq!((usage.offered_runs, usage.last_offered), (2, 1));
+
+        // Profiles written before offers were counted still load.
+        let old: AgentProfile =
+            serde_json::from_str(r#"{"runs":4,"tools":{"grep":{"calls":2,"successes":2}}}"#)
+                .unwrap();
+        assert_eq!(old.usage("grep").offered_runs, 0);
+    }
+}
@@ -4,6 +4,10 @@
 //! `.cinch/` directory, and local overrides — and presents them as a single
 //! prompt string for injection into the system message.
 //!
+//! For compatibility with repositories set up for Claude Code, `CLAUDE.md`
+//! files are loaded alongside `AGENTS.md`, with their `@path` import lines
+//! expanded inline.
+//!
 //! Files in `.cinch/rules/*.md` may contain YAML frontmatter with `paths:`
 //! globs, making them conditional on which files the agent accesses during a
 //! session. A `## Compaction Instructions` section in any file is extracted
@@ -37,10 +41,13 @@ impl ProjectInstructions {
     ///
     /// Search order:
     /// 1. `~/.config/cinch/AGENTS.md` (user-global)
-    /// 2. `{root}/AGENTS.md`
+    /// 2. `{root}/AGENTS.md`, then `{root}/CLAUDE.md` and `{root}/.claude/CLAUDE.md`
     /// 3. `{root}/.cinch/AGENTS.md`
     /// 4. `{root}/.cinch/rules/*.md` (sorted; YAML frontmatter `paths` → conditional)
-    /// 5. `{root}/AGENTS.local.md`
+    /// 5. `{root}/AGENTS.local.md`, then `{root}/CLAUDE.local.md`
+    ///
+    /// A `CLAUDE.md` whose content matches an already-loaded file (e.g. a
+    /// symlink to `AGENTS.md`) is skipped.
     ///
     /// Unconditional content is concatenated into `prompt`. `## Compaction
     /// Instructions` sections are extracted into `compaction_instructions`.
@@ -66,12 +73,15 @@ impl ProjectInstructions {
             return Self::from_parts(prompt_parts, compaction_parts, conditional_rules);
         };
 
-        // 2. {root}/AGENTS.md
+        // 2. {root}/AGENTS.md, plus CLAUDE.md for compatibility.
         load_unconditional(
             &root.join("AGENTS.md"),
             &mut prompt_parts,
             &mut compaction_parts,
         );
+        for claude in ["CLAUDE.md", ".claude/CLAUDE.md"] {
+            load_claude_md(&root.join(claude), &mut prompt_parts, &mut compaction_parts);
+        }
 
         // 3. {root}/.cinch/AGENTS.md
         load_unconditional(
@@ -113,12 +123,17 @@ impl ProjectInstructions {
             }
         }
 
-        // 5. {root}/AGENTS.local.md
+        // 5. {root}/AGENTS.local.md, then CLAUDE.local.md.
         load_unconditional(
             &root.join("AGENTS.local.md"),
             &mut prompt_parts,
             &mut compaction_parts,
         );
+        load_claude_md(
+            &root.join("CLAUDE.local.md"),
+            &mut prompt_parts,
+            &mut compaction_parts,
+        );
 
         Self::from_parts(prompt_parts, compaction_parts, conditional_rules)
     }
@@ -189,6 +204,64 @@ fn load_unconditional(
     }
 }
 
+/// Load a `CLAUDE.md`-style file: expand `@path` imports, then treat it as
+/// an unconditional instruction file. Skipped when its content duplicates
+/// a part that is already loaded.
+fn load_claude_md(path: &Path, prompt_parts: &mut Vec<String>, compaction_parts: &mut Vec<String>) {
+    let Some(content) = read_optional(path) else {
+        return;
+    };
+    let base = path.parent().unwrap_or(Path::new("."));
+    let expanded = expand_imports(&content, base, 0);
+    let (main, compaction) = extract_compaction_section(&expanded);
+    if prompt_parts.iter().any(|p| p.trim() == main.trim()) {
+        return;
+    }
+    prompt_parts.push(main);
+    if let Some(c) = compaction {
+        compaction_parts.push(c);
+    }
+}
+
+/// Maximum nesting depth for `@path` imports.
+const MAX_IMPORT_DEPTH: usize = 5;
+
+/// Replace lines of the form `@relative/path.md` with the referenced file's
+/// content, recursively. Paths are resolved against `base` (the importing
+/// file's directory); `~/` resolves against the home directory. Lines inside
+/// fenced code blocks and unreadable imports are left as-is.
+fn expand_imports(content: &str, base: &Path, depth: usize) -> String {
+    let mut out = Vec::new();
+    let mut in_fence = false;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_fence = !in_fence;
+        }
+        let import = trimmed
+            .strip_prefix('@')
+            .filter(|p| !in_fence && !p.is_empty() && !p.contains(char::is_whitespace));
+        let resolved = import.and_then(|p| {
+            let path = match p.strip_prefix("~/") {
+                Some(rest) => dirs_path()?.join(rest),
+                None => base.join(p),
+            };
+            let text = read_optional(&path)?;
+            Some(if depth + 1 < MAX_IMPORT_DEPTH {
+                let nested_base = path.parent().unwrap_or(base).to_path_buf();
+                expand_imports(&text, &nested_base, depth + 1)
+            } else {
+                text
+            })
+        });
+        match resolved {
+            Some(text) => out.push(text.trim_end().to_string()),
+            None => out.push(line.to_string()),
+        }
+    }
+    out.join("\n")
+}
+
 /// Extract a `## Compaction Instructions` section from content.
 ///
 /// Returns `(main_content, Option<compaction_section>)`. The compaction
@@ -287,7 +360,7 @@ fn parse_frontmatter(content: &str) -> (Vec<String>, &str) {
 /// `**` matches any number of path segments (including zero).
 /// `*` matches anything except `/`.
 /// Everything else is literal.
-fn glob_matches(pattern: &str, path: &str) -> bool {
+pub fn glob_matches(pattern: &str, path: &str) -> bool {
     glob_matches_inner(pattern.as_bytes(), path.as_bytes())
 }
 
@@ -413,6 +486,42 @@ mod tests {
         assert!(instructions.prompt.contains("My local rules"));
     }
 
+    #[test]
+    fn load_claude_md_with_imports() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::write(root.join("docs/style.md"), "Prefer iterators.").unwrap();
+        fs::write(
+            root.join("CLAUDE.md"),
+            "Run `make test`.\n@docs/style.md\n@missing.md\n```\n@docs/style.md\n```",
+        )
+        .unwrap();
+        fs::write(root.join("CLAUDE.local.md"), "Local Claude note.").unwrap();
+
+        let instructions = ProjectInstructions::load(Some(root));
+        let prompt = &instructions.prompt;
+        assert!(prompt.contains("Run `make test`."));
+        assert!(prompt.contains("Prefer iterators."));
+        // Unresolvable and fenced imports are kept verbatim.
+        assert!(prompt.contains("@missing.md"));
+        assert!(prompt.contains("```\n@docs/style.md\n```"));
+        assert!(prompt.contains("Local Claude note."));
+    }
+
+    #[test]
+    fn claude_md_duplicate_of_agents_md_is_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+
+        fs::write(root.join("AGENTS.md"), "Shared rules.").unwrap();
+        fs::write(root.join("CLAUDE.md"), "Shared rules.\n").unwrap();
+
+        let instructions = ProjectInstructions::load(Some(root));
+        assert_eq!(instructions.prompt.matches("Shared rules.").count(), 1);
+    }
+
     #[test]
     fn extract_compaction_section_present() {
         let content = "# Rules\nBe good.\n\n## Compaction Instructions\nPreserve file paths.\n\n## Other\nStuff.";
@@ -0,0 +1,153 @@
+//! Live git status as a dynamic prompt section.
+//!
+//! With [`HarnessConfig::git_context`](crate::agent::config::HarnessConfig::git_context)
+//! enabled and the prompt registry in use, the harness reads the repository
+//! when a run starts and registers a
+//! [`Stability::Dynamic`](super::Stability::Dynamic) "Git Status" section,
+//! so each run starts with the current branch, uncommitted files, and recent
+//! commits without spending a round on `git_status`. The section is read
+//! once per run, not per round: an interactive session, which runs the
+//! harness once per user turn, sees fresh state every turn, while the rounds
+//! of one run share a system prompt the provider can cache.
+//!
+//! The section is capped at
+//! [`max_tokens`](crate::agent::config::HarnessGitContextConfig::max_tokens);
+//! lines past the cap are dropped. Outside a git repository (or on wasm32)
+//! it renders empty and the registry leaves it out.
+
+use crate::agent::config::HarnessGitContextConfig;
+use crate::context::DEFAULT_CHARS_PER_TOKEN;
+
+/// Heading of the git status prompt section.
+pub const GIT_CONTEXT_HEADING: &str = "Git Status";
+
+/// Current branch, dirty files, and recent commits for
+/// `config.workdir`, or `None` outside a git repository.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn git_context(config: &HarnessGitContextConfig) -> Option<String> {
+    let branch = git(config, &["rev-parse", "--abbrev-ref", "HEAD"]).await?;
+    let status = git(config, &["status", "--porcelain"])
+        .await
+        .unwrap_or_default();
+    let max_commits = config.max_commits.to_string();
+    // Fails in a repository without commits yet.
+    let log = git(config, &["log", "--oneline", "-n", &max_commits])
+        .await
+        .unwrap_or_default();
+    Some(render(config, branch.trim(), &status, &log))
+}
+
+/// No processes on wasm32.
+#[cfg(target_arch = "wasm32")]
+pub async fn git_context(_config: &HarnessGitContextConfig) -> Option<String> {
+    None
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn git(config: &HarnessGitContextConfig, args: &[&str]) -> Option<String> {
+    let output = tokio::process::Command::new("git")
+        .args(args)
+        .current_dir(&config.workdir)
+        .output()
+        .await
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Format the section from raw `git` output, keeping it within the token cap.
+fn render(config: &HarnessGitContextConfig, branch: &str, status: &str, log: &str) -> String {
+    let mut lines = vec![format!("Branch: {branch}")];
+
+    let dirty: Vec<&str> = status.lines().filter(|l| !l.trim().is_empty()).collect();
+    if dirty.is_empty() {
+        lines.push("Working tree clean.".into());
+    } else {
+        lines.push(format!("Uncommitted changes ({}):", dirty.len()));
+        lines.extend(
+            dirty
+                .iter()
+                .take(config.max_dirty_files)
+                .map(|l| l.to_string()),
+        );
+        if dirty.len() > config.max_dirty_files {
+            lines.push(format!(
+                "... ({} more)",
+                dirty.len() - config.max_dirty_files
+            ));
+        }
+    }
+
+    let commits: Vec<&str> = log.lines().filter(|l| !l.trim().is_empty()).collect();
+    if !commits.is_empty() {
+        lines.push(String::new());
+        lines.push("Recent commits:".into());
+        lines.extend(commits.iter().map(|l| l.to_string()));
+    }
+
+    let max_chars = (config.max_tokens as f64 * DEFAULT_CHARS_PER_TOKEN) as usize;
+    let mut out = String::new();
+    for line in lines {
+        if out.len() + line.len() + 1 > max_chars {
+            out.push_str("... (truncated)");
+            break;
+        }
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_branch_changes_and_commits() {
+        let config = HarnessGitContextConfig {
+            max_dirty_files: 1,
+            ..HarnessGitContextConfig::for_workdir(".")
+        };
+        let text = render(
+            &config,
+            "main",
+            " M src/lib.rs\n?? notes.md\n",
+            "abc1234 Fix parser\n",
+        );
+        assert_eq!(
+            text,
+            "Branch: main\nUncommitted changes (2):\n M src/lib.rs\n... (1 more)\n\n\
+             Recent commits:\nabc1234 Fix parser"
+        );
+        assert!(render(&config, "main", "", "").contains("Working tree clean."));
+    }
+
+    #[test]
+    fn stays_within_the_token_cap() {
+        let config = HarnessGitContextConfig {
+            max_tokens: 10,
+            ..HarnessGitContextConfig::for_workdir(".")
+        };
+        let status = "?? some/long/path/to/a/file.rs\n".repeat(20);
+        let text = render(&config, "main", &status, "");
+        assert!(text.starts_with("Branch: main"));
+        assert!(text.ends_with("... (truncated)"));
+        assert!(text.len() <= 10 * DEFAULT_CHARS_PER_TOKEN as usize + "... (truncated)".len());
+    }
+
+    #[tokio::test]
+    async fn reads_the_repository() {
+        let config = HarnessGitContextConfig::for_workdir(env!("CARGO_MANIFEST_DIR"));
+        let text = git_context(&config).await.unwrap();
+        assert!(text.starts_with("Branch: "), "{text}");
+    }
+
+    #[tokio::test]
+    async fn outside_a_repository_is_none() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = HarnessGitContextConfig::for_workdir(dir.path());
+        assert!(git_context(&config).await.is_none());
+    }
+}
@@ -14,13 +14,20 @@
 //!    [`HarnessConfig::with_prompt_registry(true)`](super::config::HarnessConfig::with_prompt_registry)
 //!    is set — see [`build_default_prompt_registry`](super::harness::build_default_prompt_registry).
 //!
+//!    With [`HarnessConfig::git_context`](super::config::HarnessConfig::git_context)
+//!    enabled, the default registry also carries a dynamic
+//!    [`git_context`] section.
+//!
 //! 3. **[`SystemReminder`]** — mid-conversation system messages injected before
 //!    each API call. Used for context warnings, memory nudges, tool guidance, etc.
 
 pub mod builder;
+pub mod git_context;
 pub mod reminders;
 pub mod sections;
 
 pub use builder::SystemPromptBuilder;
-pub use reminders::{ReminderFrequency, ReminderRegistry, RoundContext, SystemReminder};
+pub use reminders::{
+    ReminderFrequency, ReminderInjection, ReminderRegistry, RoundContext, SystemReminder,
+};
 pub use sections::{PromptRegistry, PromptSection, Stability, TurnContext};
@@ -1,14 +1,22 @@
 //! System reminders injected mid-conversation before each API call.
 //!
-//! Unlike static prompt sections, reminders are injected as transient user
-//! messages at specific points during the conversation. They don't persist
-//! across compaction and are not part of the pinned prefix.
+//! Unlike static prompt sections, reminders are injected at specific points
+//! during the conversation. They don't persist across compaction and are not
+//! part of the pinned prefix. [`ReminderInjection`] controls whether they are
+//! stored in the conversation as user messages or sent only with the
+//! outgoing request as system messages.
 //!
 //! Use cases:
 //! - Context usage warnings ("Context at 75%, consider wrapping up")
 //! - Memory nudges ("Check MEMORY.md for past observations")
 //! - Task tracking ("You have 3 pending TODO items")
 //! - Tool guidance ("Prefer grep over shell('grep ...') for file search")
+//!
+//! The harness starts from [`ReminderRegistry::with_defaults()`]; add custom
+//! reminders with
+//! [`HarnessConfig::with_reminder()`](crate::agent::config::HarnessConfig::with_reminder).
+
+use std::sync::Arc;
 
 /// Context available when evaluating reminder conditions.
 #[derive(Debug, Clone)]
@@ -34,18 +42,35 @@ pub enum ReminderFrequency {
     EveryNRounds(u32),
     /// Fire only once (on the first round where the condition is true).
     Once,
+    /// Fire only at round K (1-indexed), if the condition is true then.
+    AtRound(u32),
+}
+
+/// How the harness adds fired reminders to the conversation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReminderInjection {
+    /// Push reminders into the conversation as user messages. They stay in
+    /// the history (and in later requests) until compaction.
+    #[default]
+    UserMessage,
+    /// Append reminders as system messages to the outgoing request only.
+    /// They are never stored, so the conversation stays clean and the
+    /// request prefix up to the last real message is unchanged from the
+    /// previous round, keeping it cacheable.
+    EphemeralSystem,
 }
 
 /// A system reminder that can be injected before API calls.
+#[derive(Clone)]
 pub struct SystemReminder {
     /// Human-readable name for logging/debugging.
     pub name: String,
     /// When to check this reminder.
     pub frequency: ReminderFrequency,
     /// Condition: returns true if the reminder should fire this round.
-    pub condition: Box<dyn Fn(&RoundContext) -> bool + Send + Sync>,
+    pub condition: Arc<dyn Fn(&RoundContext) -> bool + Send + Sync>,
     /// Content generator: returns the reminder text.
-    pub content: Box<dyn Fn(&RoundContext) -> String + Send + Sync>,
+    pub content: Arc<dyn Fn(&RoundContext) -> String + Send + Sync>,
     /// Whether this reminder has already fired (for `Once` frequency).
     fired: bool,
 }
@@ -71,12 +96,57 @@ impl SystemReminder {
         Self {
             name: name.into(),
             frequency,
-            condition: Box::new(condition),
-            content: Box::new(content),
+            condition: Arc::new(condition),
+            content: Arc::new(content),
             fired: false,
         }
     }
 
+    /// A fixed-text reminder that fires every `n` rounds.
+    pub fn every_n_rounds(name: impl Into<String>, n: u32, text: impl Into<String>) -> Self {
+        let text = text.into();
+        Self::new(
+            name,
+            ReminderFrequency::EveryNRounds(n.max(1)),
+            |_| true,
+            move |_| text.clone(),
+        )
+    }
+
+    /// A fixed-text reminder that fires once, at round `round` (1-indexed).
+    pub fn at_round(name: impl Into<String>, round: u32, text: impl Into<String>) -> Self {
+        let text = text.into();
+        Self::new(
+            name,
+            ReminderFrequency::AtRound(round),
+            |_| true,
+            move |_| text.clone(),
+        )
+    }
+
+    /// A fixed-text reminder that fires every round while context usage is
+    /// above `threshold` (a fraction, e.g. `0.5` for 50%). Combine with
+    /// [`with_frequency()`](Self::with_frequency) to fire less often.
+    pub fn when_context_above(
+        name: impl Into<String>,
+        threshold: f64,
+        text: impl Into<String>,
+    ) -> Self {
+        let text = text.into();
+        Self::new(
+            name,
+            ReminderFrequency::EveryRound,
+            move |ctx| ctx.context_usage_pct > threshold,
+            move |_| text.clone(),
+        )
+    }
+
+    /// Replace the frequency, keeping the condition and content.
+    pub fn with_frequency(mut self, frequency: ReminderFrequency) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
     /// Check if this reminder should fire for the given round context.
     fn should_fire(&self, ctx: &RoundContext) -> bool {
         if self.fired && self.frequency == ReminderFrequency::Once {
@@ -89,6 +159,7 @@ impl SystemReminder {
                 ctx.round.is_multiple_of(n) && (self.condition)(ctx)
             }
             ReminderFrequency::Once => (self.condition)(ctx),
+            ReminderFrequency::AtRound(k) => ctx.round == k && (self.condition)(ctx),
         }
     }
 
@@ -140,8 +211,10 @@ impl ReminderRegistry {
         registry
     }
 
-    /// Add a reminder to the registry.
+    /// Add a reminder to the registry, replacing any existing reminder with
+    /// the same name.
     pub fn add(&mut self, reminder: SystemReminder) {
+        self.remove(&reminder.name);
         self.reminders.push(reminder);
     }
 
@@ -321,6 +394,46 @@ mod tests {
         assert_eq!(registry.collect_reminders(&make_ctx(6, 0.0)).len(), 1);
     }
 
+    #[test]
+    fn at_round_fires_only_at_that_round() {
+        let mut registry = ReminderRegistry::new();
+        registry.add(SystemReminder::at_round("nudge", 2, "Check the tests."));
+
+        assert!(registry.collect_reminders(&make_ctx(1, 0.0)).is_empty());
+        assert_eq!(
+            registry.collect_reminders(&make_ctx(2, 0.0)),
+            vec!["Check the tests.".to_string()]
+        );
+        assert!(registry.collect_reminders(&make_ctx(3, 0.0)).is_empty());
+    }
+
+    #[test]
+    fn context_threshold_reminder() {
+        let mut registry = ReminderRegistry::new();
+        registry.add(
+            SystemReminder::when_context_above("ctx", 0.5, "Summarize soon.")
+                .with_frequency(ReminderFrequency::Once),
+        );
+
+        assert!(registry.collect_reminders(&make_ctx(1, 0.4)).is_empty());
+        assert_eq!(registry.collect_reminders(&make_ctx(2, 0.6)).len(), 1);
+        assert!(registry.collect_reminders(&make_ctx(3, 0.7)).is_empty());
+    }
+
+    #[test]
+    fn add_replaces_same_name() {
+        let mut registry = ReminderRegistry::with_defaults();
+        let before = registry.len();
+        registry.add(SystemReminder::every_n_rounds(
+            "context-warning-60",
+            1,
+            "custom",
+        ));
+        assert_eq!(registry.len(), before);
+        let msgs = registry.collect_reminders(&make_ctx(1, 0.1));
+        assert_eq!(msgs, vec!["custom".to_string()]);
+    }
+
     #[test]
     fn default_reminders_include_context_warnings() {
         let mut registry = ReminderRegistry::with_defaults();
@@ -4,8 +4,16 @@
 //! a lightweight `manifest.json` and per-round checkpoint files. The
 //! [`SessionManager`] subsumes all [`CheckpointManager`](super::checkpoint)
 //! functionality plus manifest management.
+//!
+//! Both file types are versioned; loading upgrades older files through
+//! [`migration`](super::migration) and rejects files from newer releases.
 
 use crate::agent::checkpoint::Checkpoint;
+use crate::agent::migration::{
+    CHECKPOINT_VERSION, MANIFEST_VERSION, parse_checkpoint, parse_manifest,
+};
+use crate::agent::session_import::{ExternalFormat, parse_transcript};
+use crate::{ChatRequest, Message, OpenRouterClient, ProviderPreferences};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use tracing::warn;
@@ -15,10 +23,17 @@ use tracing::warn;
 /// Lightweight metadata for a session, stored as `manifest.json`.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SessionManifest {
+    /// Schema version ([`MANIFEST_VERSION`](super::migration::MANIFEST_VERSION)).
+    #[serde(default)]
+    pub version: u32,
     /// Trace ID for the session (also the directory name).
     pub trace_id: String,
     /// Optional human-readable title.
     pub title: Option<String>,
+    /// Short summary of what the session asked for and did (see
+    /// [`generate_session_title`]).
+    #[serde(default)]
+    pub summary: Option<String>,
     /// Model used for this session.
     pub model: String,
     /// Current session status.
@@ -120,9 +135,7 @@ impl SessionManager {
         }
         let json =
             std::fs::read_to_string(&path).map_err(|e| format!("Failed to read manifest: {e}"))?;
-        let manifest: SessionManifest =
-            serde_json::from_str(&json).map_err(|e| format!("Failed to parse manifest: {e}"))?;
-        Ok(Some(manifest))
+        parse_manifest(&json).map(Some)
     }
 
     /// List all sessions by reading each subdirectory's `manifest.json`.
@@ -141,7 +154,7 @@ impl SessionManager {
                 continue;
             }
             match std::fs::read_to_string(&manifest_path) {
-                Ok(json) => match serde_json::from_str::<SessionManifest>(&json) {
+                Ok(json) => match parse_manifest::<SessionManifest>(&json) {
                     Ok(m) => manifests.push(m),
                     Err(e) => {
                         warn!(
@@ -179,6 +192,9 @@ impl SessionManager {
     }
 
     /// Load the latest (highest round) checkpoint for a session.
+    ///
+    /// The checkpoint's messages are validated and repaired (see
+    /// [`Checkpoint::repair()`]) so they can be sent as-is.
     pub fn load_latest_checkpoint(&self, trace_id: &str) -> Result<Option<Checkpoint>, String> {
         let dir = self.session_dir(trace_id);
         if !dir.exists() {
@@ -207,8 +223,17 @@ impl SessionManager {
             Some((_, path)) => {
                 let json = std::fs::read_to_string(&path)
                     .map_err(|e| format!("Failed to read checkpoint: {e}"))?;
-                let checkpoint: Checkpoint = serde_json::from_str(&json)
-                    .map_err(|e| format!("Failed to parse checkpoint: {e}"))?;
+                let mut checkpoint: Checkpoint =
+                    parse_checkpoint(&json).map_err(|e| format!("{e} ({})", path.display()))?;
+                let issues = checkpoint.repair();
+                if !issues.is_empty() {
+                    let issues: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+                    warn!(
+                        "Repaired checkpoint {}: {}",
+                        path.display(),
+                        issues.join(", ")
+                    );
+                }
                 Ok(Some(checkpoint))
             }
             None => Ok(None),
@@ -240,6 +265,84 @@ impl SessionManager {
         Ok(count)
     }
 
+    // ── Import ─────────────────────────────────────────────────────
+
+    /// Import a Claude Code or Codex session file as a new session.
+    ///
+    /// The format is detected when `format` is `None`. The conversation is
+    /// repaired and saved as a checkpoint at its assistant-turn count, and
+    /// the manifest is marked [`Interrupted`](SessionStatus::Interrupted) so
+    /// the session can be resumed like any other.
+    pub fn import_external(
+        &self,
+        path: &Path,
+        format: Option<ExternalFormat>,
+    ) -> Result<SessionManifest, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        let format = format
+            .or_else(|| ExternalFormat::detect(&contents))
+            .ok_or_else(|| format!("Cannot detect the format of {}", path.display()))?;
+        let transcript = parse_transcript(&contents, Some(format))?;
+
+        let model = transcript.model.clone().unwrap_or_else(|| "unknown".into());
+        let estimated_cost_usd = crate::api::tracing::pricing_for_model(&model).estimate_cost(
+            u32::try_from(transcript.prompt_tokens).unwrap_or(u32::MAX),
+            u32::try_from(transcript.completion_tokens).unwrap_or(u32::MAX),
+        );
+        let mut checkpoint = Checkpoint {
+            version: CHECKPOINT_VERSION,
+            trace_id: crate::api::tracing::generate_trace_id(),
+            text_output: transcript
+                .messages
+                .iter()
+                .filter(|m| matches!(m.role, crate::MessageRole::Assistant))
+                .filter_map(|m| m.content.clone())
+                .collect(),
+            round: transcript.assistant_turns().max(1) as u32,
+            total_prompt_tokens: transcript.prompt_tokens,
+            total_completion_tokens: transcript.completion_tokens,
+            estimated_cost_usd,
+            timestamp: format!("epoch:{}", epoch_secs()),
+            agent: None,
+            structured_state: None,
+            messages: transcript.messages,
+        };
+        let issues = checkpoint.repair();
+        if !issues.is_empty() {
+            let issues: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+            warn!(
+                "Repaired imported {}: {}",
+                path.display(),
+                issues.join(", ")
+            );
+        }
+        self.save_checkpoint(&checkpoint)?;
+
+        let file = path.file_name().unwrap_or_default().to_string_lossy();
+        let now = epoch_secs();
+        let manifest = SessionManifest {
+            version: MANIFEST_VERSION,
+            trace_id: checkpoint.trace_id.clone(),
+            title: Some(match transcript.title {
+                Some(title) => format!("{title} (imported from {})", format.label()),
+                None => format!("Imported from {}: {file}", format.label()),
+            }),
+            summary: None,
+            model,
+            status: SessionStatus::Interrupted,
+            created_at: now,
+            updated_at: now,
+            last_round: checkpoint.round,
+            total_prompt_tokens: checkpoint.total_prompt_tokens,
+            total_completion_tokens: checkpoint.total_completion_tokens,
+            estimated_cost_usd,
+            message_preview: extract_message_preview(&checkpoint.messages),
+        };
+        self.save_manifest(&manifest)?;
+        Ok(manifest)
+    }
+
     /// Delete the entire session directory (manifest + all checkpoints).
     pub fn delete_session(&self, trace_id: &str) -> Result<(), String> {
         let dir = self.session_dir(trace_id);
@@ -251,14 +354,83 @@ impl SessionManager {
     }
 }
 
+// ── Session titles ─────────────────────────────────────────────────
+
+/// System prompt for [`generate_session_title`].
+const TITLE_PROMPT: &str = "You name and summarize AI agent sessions for a session list. \
+Reply with exactly two lines:\n\
+Title: <what the session was about, at most 8 words, no trailing period>\n\
+Summary: <one or two sentences: what was asked and what was done>";
+
+/// Longest task excerpt sent to the title model, in characters.
+const TITLE_TASK_CHARS: usize = 2_000;
+/// Longest final-answer excerpt sent to the title model, in characters.
+const TITLE_ANSWER_CHARS: usize = 4_000;
+
+/// Ask `model` for a one-line title and a short summary of a session from
+/// its task and the agent's final answer.
+pub async fn generate_session_title(
+    client: &OpenRouterClient,
+    model: &str,
+    provider: Option<&ProviderPreferences>,
+    task: &str,
+    answer: &str,
+) -> Result<(String, String), String> {
+    let excerpt = |text: &str, max: usize| text.chars().take(max).collect::<String>();
+    let user_prompt = format!(
+        "Task:\n{}\n\nFinal answer:\n{}",
+        excerpt(task, TITLE_TASK_CHARS),
+        excerpt(answer, TITLE_ANSWER_CHARS)
+    );
+    let request = ChatRequest {
+        model: Some(model.to_string()),
+        messages: vec![Message::system(TITLE_PROMPT), Message::user(&user_prompt)],
+        max_tokens: 200,
+        temperature: 0.3,
+        provider: provider.cloned(),
+        ..Default::default()
+    };
+    let completion = client
+        .chat(&request)
+        .await
+        .map_err(|e| format!("Session title LLM call failed: {e}"))?;
+    let reply = completion.content.unwrap_or_default();
+    parse_title_reply(&reply).ok_or_else(|| format!("Unusable session title reply: {reply:?}"))
+}
+
+/// Split a `Title: ... / Summary: ...` reply. Without the labels, the first
+/// line is the title and the rest the summary.
+fn parse_title_reply(reply: &str) -> Option<(String, String)> {
+    let clean = |s: &str| {
+        s.trim_matches(|c: char| c == '"' || c == '*' || c.is_whitespace())
+            .to_string()
+    };
+    let field = |label: &str| {
+        reply.lines().find_map(|line| {
+            let line = line.trim().trim_start_matches("**");
+            let (key, value) = line.split_once(':')?;
+            key.trim_end_matches("**")
+                .eq_ignore_ascii_case(label)
+                .then(|| clean(value))
+        })
+    };
+    let (title, summary) = match (field("title"), field("summary")) {
+        (Some(title), summary) => (title, summary.unwrap_or_default()),
+        (None, _) => {
+            let mut lines = reply.trim().lines();
+            let title = clean(lines.next()?);
+            (title, clean(&lines.collect::<Vec<_>>().join(" ")))
+        }
+    };
+    let title = title.trim_end_matches('.').to_string();
+    (!title.is_empty()).then_some((title, summary))
+}
+
 // ── Helper ─────────────────────────────────────────────────────────
 
 /// Current unix epoch in seconds.
 pub fn epoch_secs() -> u64 {
-    std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs()
+    crate::platform::epoch_secs()
 }
 
 /// Extract the first ~200 characters of the first user message.
@@ -266,6 +438,7 @@ pub fn extract_message_preview(messages: &[crate::Message]) -> String {
     for msg in messages {
         if matches!(msg.role, crate::MessageRole::User)
             && let Some(ref content) = msg.content
+            && content != crate::tools::spec::FEW_SHOT_PREAMBLE
         {
             let preview: String = content.chars().take(200).collect();
             return preview;
@@ -283,8 +456,10 @@ mod tests {
 
     fn make_test_manifest(trace_id: &str) -> SessionManifest {
         SessionManifest {
+            version: MANIFEST_VERSION,
             trace_id: trace_id.into(),
             title: None,
+            summary: None,
             model: "test-model".into(),
             status: SessionStatus::Running,
             created_at: 1000,
@@ -297,8 +472,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn title_replies_are_parsed() {
+        assert_eq!(
+            parse_title_reply("Title: Fix the parser.\nSummary: Fixed a panic on empty input."),
+            Some((
+                "Fix the parser".into(),
+                "Fixed a panic on empty input.".into()
+            ))
+        );
+        assert_eq!(
+            parse_title_reply("**Title:** \"Add tests\"\n**Summary:** Added tests."),
+            Some(("Add tests".into(), "Added tests.".into()))
+        );
+        assert_eq!(
+            parse_title_reply("Bump deps\nUpdated serde."),
+            Some(("Bump deps".into(), "Updated serde.".into()))
+        );
+        assert_eq!(parse_title_reply("  "), None);
+    }
+
     fn make_test_checkpoint(trace_id: &str, round: u32) -> Checkpoint {
         Checkpoint {
+            version: CHECKPOINT_VERSION,
             trace_id: trace_id.into(),
             messages: vec![Message::user("test")],
             text_output: vec!["output".into()],
@@ -307,6 +503,8 @@ mod tests {
             total_completion_tokens: 50,
             estimated_cost_usd: 0.001,
             timestamp: "epoch:1000".into(),
+            agent: None,
+            structured_state: None,
         }
     }
 
@@ -454,4 +652,73 @@ mod tests {
         let mgr = SessionManager::new(dir.path()).unwrap();
         mgr.delete_session("nope").unwrap(); // Should not error.
     }
+
+    #[test]
+    fn import_external_creates_resumable_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let mgr = SessionManager::new(dir.path().join("sessions")).unwrap();
+        // An in-flight Claude Code session: the last tool call never ran.
+        let file = dir.path().join("abc.jsonl");
+        std::fs::write(
+            &file,
+            concat!(
+                r#"{"type":"user","sessionId":"abc","message":{"role":"user","content":"add tests"}}"#,
+                "\n",
+                r#"{"type":"assistant","sessionId":"abc","message":{"id":"m1","model":"claude-sonnet-4","role":"assistant","content":[{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"ls"}}],"usage":{"input_tokens":1000,"output_tokens":100}}}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+
+        let manifest = mgr.import_external(&file, None).unwrap();
+        assert_eq!(manifest.status, SessionStatus::Interrupted);
+        assert_eq!(manifest.model, "claude-sonnet-4");
+        assert_eq!(manifest.message_preview, "add tests");
+        assert_eq!(
+            manifest.title.as_deref(),
+            Some("Imported from Claude Code: abc.jsonl")
+        );
+        assert!(manifest.estimated_cost_usd > 0.0);
+
+        let checkpoint = mgr
+            .load_latest_checkpoint(&manifest.trace_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(checkpoint.messages.len(), 1);
+        assert!(checkpoint.validate().is_empty());
+        assert_eq!(mgr.list_sessions().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn import_external_rejects_unknown_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mgr = SessionManager::new(dir.path().join("sessions")).unwrap();
+        let file = dir.path().join("notes.jsonl");
+        std::fs::write(&file, "{\"hello\":1}\n").unwrap();
+        assert!(mgr.import_external(&file, None).is_err());
+        assert!(mgr.list_sessions().unwrap().is_empty());
+    }
+
+    #[test]
+    fn newer_schema_files_are_skipped_or_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let mgr = SessionManager::new(dir.path()).unwrap();
+        mgr.save_manifest(&make_test_manifest("tr-old")).unwrap();
+        let future = dir.path().join("tr-future");
+        std::fs::create_dir_all(&future).unwrap();
+        std::fs::write(
+            future.join("manifest.json"),
+            r#"{"version":99,"trace_id":"tr-future"}"#,
+        )
+        .unwrap();
+        std::fs::write(future.join("round-001.json"), r#"{"version":99}"#).unwrap();
+
+        let sessions = mgr.list_sessions().unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].trace_id, "tr-old");
+        let err = mgr.load_manifest("tr-future").unwrap_err();
+        assert!(err.contains("newer cinch-rs"), "{err}");
+        let err = mgr.load_latest_checkpoint("tr-future").unwrap_err();
+        assert!(err.contains("round-001.json"), "{err}");
+    }
 }
@@ -0,0 +1,407 @@
+//! Import of session transcripts written by other coding agents.
+//!
+//! Parses Claude Code session files (`~/.claude/projects/<project>/<id>.jsonl`)
+//! and Codex rollout files (`~/.codex/sessions/**/rollout-*.jsonl`) into
+//! cinch [`Message`]s. [`SessionManager::import_external()`](super::session::SessionManager::import_external)
+//! stores the result as a regular session that can be resumed or replayed
+//! in evals.
+//!
+//! Both formats are JSONL with one event per line. Lines that carry no
+//! conversation content (progress events, reasoning, snapshots) are skipped,
+//! as are images and thinking blocks.
+
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use serde_json::{Value, json};
+
+use crate::api::interop::{self, tool_call};
+use crate::{Message, MessageRole};
+
+/// Transcript format of an external agent session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExternalFormat {
+    /// Claude Code session JSONL (Anthropic content blocks per line).
+    ClaudeCode,
+    /// Codex CLI rollout JSONL (OpenAI Responses items per line).
+    Codex,
+}
+
+impl ExternalFormat {
+    /// Guess the format from the first line that identifies it.
+    pub fn detect(contents: &str) -> Option<Self> {
+        contents
+            .lines()
+            .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+            .find_map(|line| {
+                if line.get("payload").is_some() || line.get("instructions").is_some() {
+                    return Some(Self::Codex);
+                }
+                if line.get("sessionId").is_some() || line.get("leafUuid").is_some() {
+                    return Some(Self::ClaudeCode);
+                }
+                None
+            })
+    }
+
+    /// Human-readable name, used in imported session titles.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::ClaudeCode => "Claude Code",
+            Self::Codex => "Codex",
+        }
+    }
+}
+
+impl FromStr for ExternalFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "claude-code" | "claude" => Ok(Self::ClaudeCode),
+            "codex" => Ok(Self::Codex),
+            other => Err(format!(
+                "unknown session format '{other}' (expected claude-code or codex)"
+            )),
+        }
+    }
+}
+
+/// A conversation parsed from an external transcript.
+#[derive(Debug, Clone, Default)]
+pub struct ExternalTranscript {
+    /// The conversation, including any system prompt found in the file.
+    pub messages: Vec<Message>,
+    /// The other agent's session id.
+    pub session_id: Option<String>,
+    /// Session summary, when the file records one.
+    pub title: Option<String>,
+    /// Last model recorded in the transcript.
+    pub model: Option<String>,
+    /// Prompt tokens reported by the provider (including cached tokens).
+    pub prompt_tokens: u64,
+    /// Completion tokens reported by the provider.
+    pub completion_tokens: u64,
+}
+
+impl ExternalTranscript {
+    /// Number of assistant turns in the conversation.
+    pub fn assistant_turns(&self) -> usize {
+        self.messages
+            .iter()
+            .filter(|m| matches!(m.role, MessageRole::Assistant))
+            .count()
+    }
+}
+
+/// Parse a transcript, detecting the format when `format` is `None`.
+pub fn parse_transcript(
+    contents: &str,
+    format: Option<ExternalFormat>,
+) -> Result<ExternalTranscript, String> {
+    let format = format
+        .or_else(|| ExternalFormat::detect(contents))
+        .ok_or("cannot detect the transcript format; pass it explicitly")?;
+    let transcript = match format {
+        ExternalFormat::ClaudeCode => parse_claude_code(contents)?,
+        ExternalFormat::Codex => parse_codex(contents)?,
+    };
+    if transcript.messages.is_empty() {
+        return Err(format!("no {} messages found", format.label()));
+    }
+    Ok(transcript)
+}
+
+/// Parse a Claude Code session file.
+///
+/// Claude Code writes one line per content block, so consecutive lines of
+/// the same role are merged back into one Anthropic message before they are
+/// converted. Sidechain (sub-agent) and meta lines are skipped; usage is
+/// counted once per API message id.
+pub fn parse_claude_code(contents: &str) -> Result<ExternalTranscript, String> {
+    let mut transcript = ExternalTranscript::default();
+    let mut turns: Vec<Value> = Vec::new();
+    let mut counted_ids = HashSet::new();
+
+    for (i, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line: Value = serde_json::from_str(line).map_err(|e| format!("line {}: {e}", i + 1))?;
+        let kind = line.get("type").and_then(Value::as_str).unwrap_or_default();
+        if kind == "summary" {
+            transcript.title = line.get("summary").and_then(Value::as_str).map(Into::into);
+            continue;
+        }
+        if !matches!(kind, "user" | "assistant")
+            || flag(&line, "isSidechain")
+            || flag(&line, "isMeta")
+        {
+            continue;
+        }
+        if transcript.session_id.is_none() {
+            transcript.session_id = line
+                .get("sessionId")
+                .and_then(Value::as_str)
+                .map(Into::into);
+        }
+        let Some(message) = line.get("message") else {
+            continue;
+        };
+        if let Some(model) = message.get("model").and_then(Value::as_str)
+            && !model.starts_with('<')
+        {
+            transcript.model = Some(model.to_string());
+        }
+        if let Some(usage) = message.get("usage") {
+            let id = message
+                .get("id")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            if id.is_empty() || counted_ids.insert(id.to_string()) {
+                transcript.prompt_tokens += [
+                    "input_tokens",
+                    "cache_creation_input_tokens",
+                    "cache_read_input_tokens",
+                ]
+                .iter()
+                .filter_map(|k| usage.get(*k).and_then(Value::as_u64))
+                .sum::<u64>();
+                transcript.completion_tokens += usage
+                    .get("output_tokens")
+                    .and_then(Value::as_u64)
+                    .unwrap_or(0);
+            }
+        }
+
+        let blocks = match message.get("content") {
+            Some(Value::String(text)) => vec![json!({ "type": "text", "text": text })],
+            Some(Value::Array(blocks)) => blocks.clone(),
+            _ => continue,
+        };
+        match turns.last_mut() {
+            Some(last) if last["role"] == kind => {
+                if let Some(content) = last["content"].as_array_mut() {
+                    content.extend(blocks);
+                }
+            }
+            _ => turns.push(json!({ "role": kind, "content": blocks })),
+        }
+    }
+
+    transcript.messages = interop::from_anthropic(&json!({ "messages": turns }))?;
+    Ok(transcript)
+}
+
+/// Parse a Codex rollout file.
+///
+/// Conversation items are `response_item` payloads (or bare items in older
+/// rollouts). `developer` messages become system messages, function calls
+/// attach to the preceding assistant message, and the model comes from the
+/// latest `turn_context`. Token usage is the last cumulative `token_count`.
+pub fn parse_codex(contents: &str) -> Result<ExternalTranscript, String> {
+    let mut transcript = ExternalTranscript::default();
+
+    for (i, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line: Value = serde_json::from_str(line).map_err(|e| format!("line {}: {e}", i + 1))?;
+        let kind = line.get("type").and_then(Value::as_str).unwrap_or_default();
+        let payload = line.get("payload").unwrap_or(&line);
+        match kind {
+            "session_meta" => {
+                transcript.session_id = payload.get("id").and_then(Value::as_str).map(Into::into);
+            }
+            "turn_context" => {
+                if let Some(model) = payload.get("model").and_then(Value::as_str) {
+                    transcript.model = Some(model.to_string());
+                }
+            }
+            "event_msg" => {
+                let total = payload.pointer("/info/total_token_usage");
+                if payload.get("type").and_then(Value::as_str) == Some("token_count")
+                    && let Some(total) = total
+                {
+                    let count = |k: &str| total.get(k).and_then(Value::as_u64).unwrap_or(0);
+                    transcript.prompt_tokens = count("input_tokens");
+                    transcript.completion_tokens = count("output_tokens");
+                }
+            }
+            _ => codex_item(payload, &mut transcript.messages)
+                .map_err(|e| format!("line {}: {e}", i + 1))?,
+        }
+    }
+    Ok(transcript)
+}
+
+/// Append one Codex response item to `messages`.
+fn codex_item(item: &Value, messages: &mut Vec<Message>) -> Result<(), String> {
+    let str_field = |k: &str| item.get(k).and_then(Value::as_str);
+    match str_field("type").unwrap_or_default() {
+        "message" => {
+            let text = codex_text(item.get("content"));
+            if text.is_empty() {
+                return Ok(());
+            }
+            match str_field("role").unwrap_or_default() {
+                "user" => messages.push(Message::user(text)),
+                "assistant" => messages.push(Message::assistant_text(text)),
+                "system" | "developer" => messages.push(Message::system(text)),
+                other => return Err(format!("unsupported role '{other}'")),
+            }
+        }
+        kind @ ("function_call" | "custom_tool_call" | "local_shell_call") => {
+            let id = str_field("call_id").ok_or(format!("{kind} without call_id"))?;
+            let name = str_field("name").unwrap_or("shell");
+            let arguments = match item.get("arguments").or_else(|| item.get("input")) {
+                Some(Value::String(raw)) => raw.clone(),
+                Some(value) => value.to_string(),
+                None => item.get("action").map_or("{}".into(), Value::to_string),
+            };
+            let call = tool_call(id, name, arguments);
+            match messages.last_mut() {
+                Some(last) if matches!(last.role, MessageRole::Assistant) => {
+                    last.tool_calls.get_or_insert_with(Vec::new).push(call);
+                }
+                _ => messages.push(Message {
+                    content: None,
+                    tool_calls: Some(vec![call]),
+                    ..Message::assistant_text("")
+                }),
+            }
+        }
+        kind @ ("function_call_output" | "custom_tool_call_output") => {
+            let id = str_field("call_id").ok_or(format!("{kind} without call_id"))?;
+            let output = match item.get("output") {
+                Some(Value::String(raw)) => raw.clone(),
+                // Older rollouts wrap the output as `{ "content": ..., "success": ... }`.
+                Some(value) => value
+                    .get("content")
+                    .and_then(Value::as_str)
+                    .map_or_else(|| value.to_string(), Into::into),
+                None => String::new(),
+            };
+            messages.push(Message::tool_result(id, output));
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Joined text of Codex `input_text` / `output_text` content parts.
+fn codex_text(content: Option<&Value>) -> String {
+    let Some(parts) = content.and_then(Value::as_array) else {
+        return content
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+    };
+    parts
+        .iter()
+        .filter_map(|p| p.get("text").and_then(Value::as_str))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn flag(line: &Value, key: &str) -> bool {
+    line.get(key).and_then(Value::as_bool) == Some(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLAUDE_CODE: &str = r#"{"type":"summary","summary":"Fix the parser","leafUuid":"u9"}
+{"type":"user","sessionId":"s-1","isSidechain":false,"message":{"role":"user","content":"fix the parser"}}
+{"type":"user","sessionId":"s-1","isMeta":true,"message":{"role":"user","content":"<caveat>"}}
+{"type":"assistant","sessionId":"s-1","message":{"id":"msg_1","model":"claude-sonnet-4","role":"assistant","content":[{"type":"thinking","thinking":"hmm"}],"usage":{"input_tokens":10,"cache_read_input_tokens":90,"output_tokens":5}}}
+{"type":"assistant","sessionId":"s-1","message":{"id":"msg_1","model":"claude-sonnet-4","role":"assistant","content":[{"type":"text","text":"Reading it."},{"type":"tool_use","id":"tu_1","name":"Read","input":{"file_path":"src/parser.rs"}}],"usage":{"input_tokens":10,"cache_read_input_tokens":90,"output_tokens":5}}}
+{"type":"user","sessionId":"s-1","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"tu_1","content":"fn parse() {}"}]}}
+{"type":"assistant","sessionId":"s-1","isSidechain":true,"message":{"id":"msg_x","role":"assistant","content":"sub-agent chatter"}}
+{"type":"assistant","sessionId":"s-1","message":{"id":"msg_2","model":"claude-sonnet-4","role":"assistant","content":[{"type":"text","text":"Done."}],"usage":{"input_tokens":120,"output_tokens":3}}}
+"#;
+
+    const CODEX: &str = r#"{"type":"session_meta","payload":{"id":"c-1","cwd":"/repo"}}
+{"type":"turn_context","payload":{"model":"gpt-5-codex","cwd":"/repo"}}
+{"type":"response_item","payload":{"type":"message","role":"developer","content":[{"type":"input_text","text":"be careful"}]}}
+{"type":"response_item","payload":{"type":"message","role":"user","content":[{"type":"input_text","text":"list files"}]}}
+{"type":"response_item","payload":{"type":"reasoning","summary":[]}}
+{"type":"response_item","payload":{"type":"function_call","name":"shell","arguments":"{\"command\":[\"ls\"]}","call_id":"call_1"}}
+{"type":"response_item","payload":{"type":"function_call_output","call_id":"call_1","output":"{\"output\":\"a.rs\"}"}}
+{"type":"event_msg","payload":{"type":"token_count","info":{"total_token_usage":{"input_tokens":300,"output_tokens":40}}}}
+{"type":"response_item","payload":{"type":"message","role":"assistant","content":[{"type":"output_text","text":"One file: a.rs"}]}}
+"#;
+
+    #[test]
+    fn detects_formats() {
+        assert_eq!(
+            ExternalFormat::detect(CLAUDE_CODE),
+            Some(ExternalFormat::ClaudeCode)
+        );
+        assert_eq!(ExternalFormat::detect(CODEX), Some(ExternalFormat::Codex));
+        assert_eq!(ExternalFormat::detect("{\"x\":1}"), None);
+        assert_eq!("codex".parse(), Ok(ExternalFormat::Codex));
+        assert!("aider".parse::<ExternalFormat>().is_err());
+    }
+
+    #[test]
+    fn parses_claude_code_sessions() {
+        let t = parse_transcript(CLAUDE_CODE, None).unwrap();
+        assert_eq!(t.session_id.as_deref(), Some("s-1"));
+        assert_eq!(t.title.as_deref(), Some("Fix the parser"));
+        assert_eq!(t.model.as_deref(), Some("claude-sonnet-4"));
+        // msg_1 is counted once despite spanning two lines.
+        assert_eq!(t.prompt_tokens, 220);
+        assert_eq!(t.completion_tokens, 8);
+
+        let roles: Vec<_> = t.messages.iter().map(|m| m.role.clone()).collect();
+        assert_eq!(
+            roles,
+            [
+                MessageRole::User,
+                MessageRole::Assistant,
+                MessageRole::Tool,
+                MessageRole::Assistant,
+            ]
+        );
+        let calls = t.messages[1].tool_calls.as_ref().unwrap();
+        assert_eq!(calls[0].function.name, "Read");
+        assert_eq!(t.messages[1].content.as_deref(), Some("Reading it."));
+        assert_eq!(t.messages[2].content.as_deref(), Some("fn parse() {}"));
+        assert_eq!(t.assistant_turns(), 2);
+    }
+
+    #[test]
+    fn parses_codex_rollouts() {
+        let t = parse_transcript(CODEX, Some(ExternalFormat::Codex)).unwrap();
+        assert_eq!(t.session_id.as_deref(), Some("c-1"));
+        assert_eq!(t.model.as_deref(), Some("gpt-5-codex"));
+        assert_eq!((t.prompt_tokens, t.completion_tokens), (300, 40));
+
+        let roles: Vec<_> = t.messages.iter().map(|m| m.role.clone()).collect();
+        assert_eq!(
+            roles,
+            [
+                MessageRole::System,
+                MessageRole::User,
+                MessageRole::Assistant,
+                MessageRole::Tool,
+                MessageRole::Assistant,
+            ]
+        );
+        let call = &t.messages[2].tool_calls.as_ref().unwrap()[0];
+        assert_eq!(call.id, "call_1");
+        assert_eq!(call.function.arguments, r#"{"command":["ls"]}"#);
+        assert_eq!(t.messages[4].content.as_deref(), Some("One file: a.rs"));
+    }
+
+    #[test]
+    fn rejects_empty_and_malformed_transcripts() {
+        assert!(parse_transcript("", None).is_err());
+        assert!(parse_transcript("not json", Some(ExternalFormat::Codex)).is_err());
+        let meta_only = r#"{"type":"session_meta","payload":{"id":"c-1"}}"#;
+        let err = parse_transcript(meta_only, None).unwrap_err();
+        assert!(err.contains("no Codex messages"), "{err}");
+    }
+}
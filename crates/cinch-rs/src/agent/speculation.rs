@@ -0,0 +1,227 @@
+//! Speculative next-round prefetch.
+//!
+//! When every tool call in a round is a fast read-only tool whose result the
+//! harness has seen before (same name and arguments), it can send round N+1
+//! while round N's tools are still running: the request carries the
+//! *predicted* results in place of the real ones. After the tools finish:
+//!
+//! - if any real result diverges from its prediction by more than
+//!   [`HarnessSpeculationConfig::max_divergence`], the in-flight request is
+//!   dropped (cancelled) and round N+1 is sent normally;
+//! - otherwise the speculative response is kept, and round N+1 uses it as
+//!   long as the messages it would have sent still match the speculative
+//!   ones (context management or reminders may have changed them).
+//!
+//! This is an opt-in latency optimization: a rejected speculation costs an
+//! extra request. Enable it with
+//! [`HarnessConfig::with_speculative_prefetch()`](super::config::HarnessConfig::with_speculative_prefetch).
+
+use std::collections::HashMap;
+
+use similar::TextDiff;
+
+use super::config::{HarnessSpeculationConfig, RoundGeneration};
+use crate::{ChatCompletion, Message, MessageRole, ToolCall};
+
+/// Fraction of lines that differ between a prediction and the actual
+/// result: `0.0` for identical text, `1.0` for nothing in common.
+pub fn divergence(predicted: &str, actual: &str) -> f64 {
+    if predicted == actual {
+        return 0.0;
+    }
+    1.0 - f64::from(TextDiff::from_lines(predicted, actual).ratio())
+}
+
+/// A speculative request whose response is waiting to be used by the next
+/// round.
+pub(crate) struct PendingSpeculation {
+    /// Messages the speculative request was sent with.
+    pub(crate) messages: Vec<Message>,
+    /// Model the speculative request was sent to.
+    pub(crate) model: String,
+    /// Generation settings the speculative request was sent with.
+    pub(crate) generation: RoundGeneration,
+    pub(crate) completion: ChatCompletion,
+}
+
+/// Per-run speculation state: the last known result of each eligible call.
+pub(crate) struct Speculation {
+    config: HarnessSpeculationConfig,
+    /// `(tool name, arguments)` → last result.
+    predictions: HashMap<(String, String), String>,
+}
+
+impl Speculation {
+    pub(crate) fn new(config: HarnessSpeculationConfig) -> Self {
+        Self {
+            config,
+            predictions: HashMap::new(),
+        }
+    }
+
+    fn eligible(&self, name: &str) -> bool {
+        self.config.tools.iter().any(|t| t == name)
+    }
+
+    /// Remember the result of an executed call for future predictions.
+    /// Error results are not remembered.
+    pub(crate) fn record(&mut self, name: &str, arguments: &str, result: &str) {
+        if !self.eligible(name) {
+            return;
+        }
+        let key = (name.to_string(), arguments.to_string());
+        if result.starts_with("Error") {
+            self.predictions.remove(&key);
+        } else {
+            self.predictions.insert(key, result.to_string());
+        }
+    }
+
+    /// Predicted `(call_id, result)` for every call, or `None` unless all of
+    /// them are eligible and have been seen before.
+    pub(crate) fn predict(&self, calls: &[ToolCall]) -> Option<Vec<(String, String)>> {
+        if calls.is_empty() {
+            return None;
+        }
+        calls
+            .iter()
+            .map(|call| {
+                if !self.eligible(&call.function.name) {
+                    return None;
+                }
+                let key = (call.function.name.clone(), call.function.arguments.clone());
+                self.predictions
+                    .get(&key)
+                    .map(|result| (call.id.clone(), result.clone()))
+            })
+            .collect()
+    }
+
+    /// Whether every actual `(call_id, result)` is within the divergence
+    /// threshold of its prediction.
+    pub(crate) fn predictions_hold(
+        &self,
+        predicted: &[(String, String)],
+        actual: &[(String, String)],
+    ) -> bool {
+        predicted.len() == actual.len()
+            && predicted.iter().all(|(id, p)| {
+                actual
+                    .iter()
+                    .find(|(aid, _)| aid == id)
+                    .is_some_and(|(_, a)| divergence(p, a) <= self.config.max_divergence)
+            })
+    }
+
+    /// Whether a speculative response built from `speculative` messages can
+    /// stand in for a request with `actual` messages: identical except for
+    /// tool results, which may diverge up to the threshold.
+    pub(crate) fn messages_match(&self, speculative: &[Message], actual: &[Message]) -> bool {
+        speculative.len() == actual.len()
+            && speculative.iter().zip(actual).all(|(s, a)| {
+                if s.role != a.role
+                    || s.tool_call_id != a.tool_call_id
+                    || serde_json::to_value(&s.tool_calls).ok()
+                        != serde_json::to_value(&a.tool_calls).ok()
+                {
+                    return false;
+                }
+                let (sc, ac) = (s.content.as_deref(), a.content.as_deref());
+                match (s.role == MessageRole::Tool, sc, ac) {
+                    (true, Some(sc), Some(ac)) => divergence(sc, ac) <= self.config.max_divergence,
+                    _ => sc == ac,
+                }
+            })
+    }
+}
+
+/// `base` followed by a tool result message for each predicted result.
+pub(crate) fn speculative_messages(
+    mut base: Vec<Message>,
+    predicted: &[(String, String)],
+) -> Vec<Message> {
+    base.extend(
+        predicted
+            .iter()
+            .map(|(id, result)| Message::tool_result(id, result.clone())),
+    );
+    base
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CallType, FunctionCallData};
+
+    fn call(id: &str, name: &str, args: &str) -> ToolCall {
+        ToolCall {
+            id: id.into(),
+            call_type: CallType::Function,
+            function: FunctionCallData {
+                name: name.into(),
+                arguments: args.into(),
+            },
+        }
+    }
+
+    fn speculation(max_divergence: f64) -> Speculation {
+        Speculation::new(HarnessSpeculationConfig {
+            enabled: true,
+            max_divergence,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn divergence_is_line_based() {
+        assert_eq!(divergence("a\nb\n", "a\nb\n"), 0.0);
+        assert_eq!(divergence("a\n", "b\n"), 1.0);
+        let d = divergence("a\nb\nc\nd\n", "a\nb\nc\nX\n");
+        assert!(d > 0.0 && d < 0.5, "got {d}");
+    }
+
+    #[test]
+    fn predicts_only_when_every_call_is_known() {
+        let mut spec = speculation(0.0);
+        let read = call("c1", "read_file", r#"{"path":"a"}"#);
+        assert!(spec.predict(std::slice::from_ref(&read)).is_none());
+
+        spec.record("read_file", r#"{"path":"a"}"#, "contents");
+        spec.record("shell", "{}", "ignored");
+        assert_eq!(
+            spec.predict(std::slice::from_ref(&read)),
+            Some(vec![("c1".into(), "contents".into())])
+        );
+        assert!(
+            spec.predict(&[read.clone(), call("c2", "shell", "{}")])
+                .is_none()
+        );
+
+        spec.record("read_file", r#"{"path":"a"}"#, "Error: gone");
+        assert!(spec.predict(&[read]).is_none());
+    }
+
+    #[test]
+    fn predictions_hold_within_threshold() {
+        let predicted = vec![("c1".to_string(), "a\nb\nc\nd\n".to_string())];
+        let changed = vec![("c1".to_string(), "a\nb\nc\nX\n".to_string())];
+        assert!(speculation(0.0).predictions_hold(&predicted, &predicted));
+        assert!(!speculation(0.0).predictions_hold(&predicted, &changed));
+        assert!(speculation(0.5).predictions_hold(&predicted, &changed));
+    }
+
+    #[test]
+    fn messages_match_allows_only_tool_result_drift() {
+        let spec = speculation(0.5);
+        let base = vec![Message::system("sys"), Message::user("task")];
+        let speculative =
+            speculative_messages(base.clone(), &[("c1".into(), "a\nb\nc\nd\n".into())]);
+        let actual = speculative_messages(base.clone(), &[("c1".into(), "a\nb\nc\nX\n".into())]);
+        assert!(spec.messages_match(&speculative, &actual));
+        assert!(!speculation(0.0).messages_match(&speculative, &actual));
+
+        let mut with_reminder = actual.clone();
+        with_reminder.push(Message::user("reminder"));
+        assert!(!spec.messages_match(&speculative, &with_reminder));
+    }
+}
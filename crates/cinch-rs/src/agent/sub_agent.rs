@@ -10,18 +10,19 @@
 //! Leaf agents have real tools. This prevents the root from wasting context
 //! on raw tool results — it only sees compact task results.
 
+use crate::agent::blackboard::Blackboard;
 use crate::agent::config::HarnessConfig;
 use crate::agent::events::NoopHandler;
 use crate::agent::harness::Harness;
+use crate::platform::{Instant, JoinHandle};
 use crate::tools::core::{Tool, ToolFuture, ToolSet};
-use crate::{Message, OpenRouterClient, ToolDef};
+use crate::tools::names;
+use crate::{Message, OpenRouterClient, ProviderPreferences, ToolDef};
 use schemars::JsonSchema;
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::Instant;
-use tokio::task::JoinHandle;
 use tracing::{debug, info, warn};
 
 /// Budget semaphore for tree-wide token accounting.
@@ -164,6 +165,12 @@ pub struct SharedResources {
     pub depth: u32,
     /// Maximum allowed depth.
     pub max_depth: u32,
+    /// Key-value state shared by the whole tree.
+    pub blackboard: Arc<Blackboard>,
+    /// Provider routing preferences for every agent in the tree (e.g. a
+    /// data collection policy). Default: `None`; see
+    /// [`inherit`](Self::inherit) to take the parent's.
+    pub provider: Option<ProviderPreferences>,
 }
 
 impl SharedResources {
@@ -173,9 +180,28 @@ impl SharedResources {
             root_trace_id: trace_id,
             depth: 0,
             max_depth: 3,
+            blackboard: Arc::new(Blackboard::new()),
+            provider: None,
         }
     }
 
+    /// Apply `provider` preferences to every child agent's requests.
+    pub fn with_provider(mut self, provider: ProviderPreferences) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
+    /// Take defaults from the parent agent's `config`: its provider
+    /// preferences, unless [`with_provider`](Self::with_provider) already
+    /// set some. [`Harness::with_shared_resources`] does this for its own
+    /// copy; call it on the resources handed to the delegation tools too.
+    pub fn inherit(mut self, config: &HarnessConfig) -> Self {
+        if self.provider.is_none() {
+            self.provider = config.provider.clone();
+        }
+        self
+    }
+
     /// Create child resources with incremented depth.
     pub fn child(&self) -> Option<Self> {
         if self.depth >= self.max_depth {
@@ -186,12 +212,34 @@ impl SharedResources {
             root_trace_id: self.root_trace_id.clone(),
             depth: self.depth + 1,
             max_depth: self.max_depth,
+            blackboard: Arc::clone(&self.blackboard),
+            provider: self.provider.clone(),
         })
     }
 
     pub fn can_spawn_child(&self) -> bool {
         self.depth < self.max_depth
     }
+
+    /// The tool set for a child named `name`. A `read_only` child gets the
+    /// parent's [read-only view](ToolSet::read_only_view). When the parent's
+    /// tools include the blackboard tools, the child gets its own copies so
+    /// its writes are attributed to it; otherwise `tools` is shared as-is.
+    fn child_tools(&self, tools: &Arc<ToolSet>, name: &str, read_only: bool) -> Arc<ToolSet> {
+        let blackboard = tools.has_tool(names::BLACKBOARD_SET);
+        if !blackboard && !read_only {
+            return Arc::clone(tools);
+        }
+        let forked = if read_only {
+            tools.read_only_view()
+        } else {
+            tools.fork()
+        };
+        if blackboard {
+            self.blackboard.register_tools(&forked, name);
+        }
+        Arc::new(forked)
+    }
 }
 
 // ── DelegateSubAgentTool ────────────────────────────────────────────
@@ -314,6 +362,8 @@ struct ResolvedConfig {
     max_rounds: u32,
     plan_execute: crate::agent::config::HarnessPlanExecuteConfig,
     system_prompt: String,
+    /// Whether the child only gets non-mutating tools.
+    read_only: bool,
 }
 
 /// Map `DelegateSubAgentArgs` + parent model into concrete config values.
@@ -331,6 +381,7 @@ fn resolve_config(args: &DelegateSubAgentArgs, parent_model: &str) -> ResolvedCo
                 max_rounds: rounds,
                 plan_execute: plan_execute_read_only(rounds),
                 system_prompt: explore_system_prompt(&args.name),
+                read_only: true,
             }
         }
         SubAgentType::Worker => {
@@ -344,6 +395,7 @@ fn resolve_config(args: &DelegateSubAgentArgs, parent_model: &str) -> ResolvedCo
                 max_rounds: rounds,
                 plan_execute: pe,
                 system_prompt: worker_system_prompt(&args.name),
+                read_only: false,
             }
         }
         SubAgentType::Planner => {
@@ -353,13 +405,17 @@ fn resolve_config(args: &DelegateSubAgentArgs, parent_model: &str) -> ResolvedCo
                 max_rounds: rounds,
                 plan_execute: plan_execute_read_only(rounds),
                 system_prompt: planner_system_prompt(&args.name),
+                read_only: true,
             }
         }
     }
 }
 
 /// Build the `HarnessConfig` for a child agent from resolved settings.
-fn build_child_harness_config(resolved: ResolvedConfig) -> (HarnessConfig, String) {
+fn build_child_harness_config(
+    resolved: ResolvedConfig,
+    shared: &SharedResources,
+) -> (HarnessConfig, String) {
     let system_prompt = resolved.system_prompt;
     let config = HarnessConfig {
         model: resolved.model,
@@ -369,6 +425,7 @@ fn build_child_harness_config(resolved: ResolvedConfig) -> (HarnessConfig, Strin
         session: crate::agent::config::HarnessSessionConfig::disabled(),
         memory_prompt: None,
         plan_execute: resolved.plan_execute,
+        provider: shared.provider.clone(),
         ..Default::default()
     };
     (config, system_prompt)
@@ -457,6 +514,11 @@ impl Tool for DelegateSubAgentTool {
         )
     }
 
+    /// A worker child can run any of the parent's tools.
+    fn is_mutation(&self) -> bool {
+        true
+    }
+
     fn execute(&self, arguments: &str) -> ToolFuture<'_> {
         let arguments = arguments.to_string();
         let shared = self.shared.clone();
@@ -511,10 +573,12 @@ impl Tool for DelegateSubAgentTool {
                 args.name, args.agent_type, child_shared.depth, resolved.model, resolved.max_rounds
             );
 
-            let (child_config, system_prompt) = build_child_harness_config(resolved);
+            let read_only = resolved.read_only;
+            let (child_config, system_prompt) = build_child_harness_config(resolved, &child_shared);
             let child_messages =
                 build_child_messages(system_prompt, args.context.as_deref(), args.task);
 
+            let tools = shared.child_tools(&tools, &args.name, read_only);
             let child_harness = Harness::new(&client, &tools, child_config)
                 .with_event_handler(&NoopHandler)
                 .with_shared_resources(child_shared);
@@ -684,6 +748,11 @@ impl Tool for SpawnBackgroundAgentTool {
         )
     }
 
+    /// Same as `delegate_sub_agent`: the child may mutate.
+    fn is_mutation(&self) -> bool {
+        true
+    }
+
     fn execute(&self, arguments: &str) -> ToolFuture<'_> {
         // Clone all Arc/owned data into the future — the future itself is
         // short-lived (returns the ID), but the spawned task lives longer.
@@ -732,16 +801,18 @@ impl Tool for SpawnBackgroundAgentTool {
                 args.name, args.agent_type, resolved.model, resolved.max_rounds
             );
 
-            let (child_config, system_prompt) = build_child_harness_config(resolved);
+            let read_only = resolved.read_only;
+            let (child_config, system_prompt) = build_child_harness_config(resolved, &child_shared);
             let child_messages =
                 build_child_messages(system_prompt, args.context.as_deref(), args.task);
 
-            // Spawn the agent as an independent Tokio task. The concurrency
+            // Spawn the agent as an independent background task. The concurrency
             // permit is acquired *inside* the task so spawn returns immediately
             // even if all slots are busy — the task queues until a slot opens.
             let budget = Arc::clone(&shared.budget);
+            let tools = shared.child_tools(&tools, &agent_name, read_only);
             let task_name = agent_name.clone();
-            let handle = tokio::spawn(async move {
+            let handle = crate::platform::spawn(async move {
                 let _permit = concurrency
                     .acquire()
                     .await
@@ -942,6 +1013,36 @@ mod tests {
         assert!(great.child().is_none());
     }
 
+    #[tokio::test]
+    async fn children_share_the_blackboard_under_their_own_name() {
+        let shared = SharedResources::new(10000, "tr-test".into());
+        let plain = Arc::new(ToolSet::new());
+        assert!(Arc::ptr_eq(&shared.child_tools(&plain, "c", false), &plain));
+
+        let tools = Arc::new(ToolSet::new());
+        shared.blackboard.register_tools(&tools, "root");
+        let child = shared.child().unwrap();
+        let child_tools = child.child_tools(&tools, "scout", false);
+        child_tools
+            .execute(names::BLACKBOARD_SET, r#"{"key":"k","value":1}"#)
+            .await;
+        assert_eq!(shared.blackboard.get("k").unwrap().writer, "scout");
+    }
+
+    #[test]
+    fn read_only_children_get_no_mutation_tools() {
+        let shared = SharedResources::new(10000, "tr-test".into());
+        let write = crate::tools::core::FnTool::new(
+            ToolDef::new("write", "Write", serde_json::json!({})),
+            |_: serde_json::Value| async { String::new() },
+        )
+        .mutation(true);
+        let tools = Arc::new(ToolSet::new().with(write));
+
+        assert!(shared.child_tools(&tools, "w", false).has_tool("write"));
+        assert!(!shared.child_tools(&tools, "e", true).has_tool("write"));
+    }
+
     #[test]
     fn sub_agent_result_format() {
         let result = SubAgentResult {
@@ -1107,6 +1208,7 @@ mod tests {
         let rc = resolve_config(&args, "pm");
         assert_eq!(rc.max_rounds, 10);
         assert!(!rc.plan_execute.enabled);
+        assert!(!rc.read_only);
     }
 
     #[test]
@@ -1123,9 +1225,45 @@ mod tests {
             serde_json::from_str(r#"{"name":"p","task":"t","agent_type":"planner"}"#).unwrap();
         let rc = resolve_config(&args, "pm");
         assert!(rc.plan_execute.enabled);
+        assert!(rc.read_only);
         assert!(rc.system_prompt.contains("planning"));
     }
 
+    #[test]
+    fn child_config_inherits_tree_provider_preferences() {
+        let shared =
+            SharedResources::new(10000, "tr-test".into()).with_provider(ProviderPreferences {
+                data_collection: Some(crate::DataCollection::Deny),
+                ..Default::default()
+            });
+        let args: DelegateSubAgentArgs =
+            serde_json::from_str(r#"{"name":"w","task":"t"}"#).unwrap();
+        let child = shared.child().unwrap();
+        let (config, _) = build_child_harness_config(resolve_config(&args, "pm"), &child);
+        let provider = config.provider.unwrap();
+        assert_eq!(provider.data_collection, Some(crate::DataCollection::Deny));
+    }
+
+    #[test]
+    fn resources_inherit_the_parent_provider() {
+        let parent = HarnessConfig::new("pm", "").with_data_collection(crate::DataCollection::Deny);
+        let shared = SharedResources::new(10000, "tr-test".into()).inherit(&parent);
+        let args: DelegateSubAgentArgs =
+            serde_json::from_str(r#"{"name":"w","task":"t"}"#).unwrap();
+        let (config, _) =
+            build_child_harness_config(resolve_config(&args, "pm"), &shared.child().unwrap());
+        assert_eq!(
+            config.provider.unwrap().data_collection,
+            Some(crate::DataCollection::Deny)
+        );
+
+        // Explicit tree preferences win.
+        let explicit = SharedResources::new(10000, "tr-test".into())
+            .with_provider(ProviderPreferences::default())
+            .inherit(&parent);
+        assert_eq!(explicit.provider.unwrap().data_collection, None);
+    }
+
     #[test]
     fn resolve_config_model_override() {
         let args: DelegateSubAgentArgs =
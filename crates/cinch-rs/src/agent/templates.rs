@@ -0,0 +1,403 @@
+//! Named agent templates: reusable agent definitions loaded from TOML.
+//!
+//! An [`AgentTemplate`] bundles what distinguishes one kind of agent from
+//! another — system prompt, tool selection, model, and harness overrides —
+//! so orchestrators and binaries can ask for `"reviewer"` by name instead of
+//! assembling the pieces in code. [`AgentTemplateRegistry::builtin()`]
+//! provides `researcher`, `coder`, `reviewer`, and `committer`; TOML files
+//! add to or replace them:
+//!
+//! ```toml
+//! # .cinch/agents.toml
+//! [agents.reviewer]
+//! description = "Strict reviewer for the payments service"
+//! system_prompt = "You review payment code. Reject anything without tests."
+//! model = "anthropic/claude-opus-4"
+//! tools = ["read_file", "grep", "shell"]
+//!
+//! [agents.reviewer.config]
+//! max_rounds = 10
+//! temperature = 0.1
+//! ```
+//!
+//! `config` takes the same keys as a `cinch.toml` file (see
+//! [`HarnessSettings`]). Omitting `tools` gives the agent every tool of the
+//! set it is built from.
+//!
+//! ```ignore
+//! let registry = AgentTemplateRegistry::builtin().with_file(".cinch/agents.toml")?;
+//! let orchestrator = Orchestrator::new(client, config)
+//!     .with_specialist(registry.specialist("researcher", &tools)?)
+//!     .with_specialist(registry.specialist("reviewer", &tools)?);
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use figment::Figment;
+use figment::providers::{Format, Toml};
+use serde::Deserialize;
+
+use super::config::HarnessConfig;
+use super::config_sources::HarnessSettings;
+use super::orchestrator::Specialist;
+use crate::api::router::RoutingStrategy;
+use crate::tools::core::ToolSet;
+use crate::tools::names;
+
+/// A named, reusable agent definition.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AgentTemplate {
+    /// Set from the TOML table key.
+    #[serde(skip)]
+    pub name: String,
+    /// One-line description, shown to coordinators choosing an agent.
+    #[serde(default)]
+    pub description: String,
+    /// The agent's system prompt.
+    pub system_prompt: String,
+    /// Model override. `None` keeps the base config's model.
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Names of the tools the agent may use. `None` allows all tools.
+    #[serde(default)]
+    pub tools: Option<Vec<String>>,
+    /// Harness overrides applied on top of the base config.
+    #[serde(default)]
+    pub config: HarnessSettings,
+}
+
+impl AgentTemplate {
+    /// Create a template with all tools and no overrides.
+    pub fn new(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        system_prompt: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            system_prompt: system_prompt.into(),
+            model: None,
+            tools: None,
+            config: HarnessSettings::default(),
+        }
+    }
+
+    /// Set the model override (builder pattern).
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Restrict the agent to the named tools (builder pattern).
+    pub fn with_tools<S: Into<String>>(mut self, tools: impl IntoIterator<Item = S>) -> Self {
+        self.tools = Some(tools.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Set the harness overrides (builder pattern).
+    pub fn with_config(mut self, config: HarnessSettings) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Apply the model and harness overrides to `config`, leaving its
+    /// system prompt alone. Use this when the caller assembles the prompt
+    /// itself (e.g. adds the template's prompt as a section).
+    pub fn apply_settings(&self, config: &mut HarnessConfig) {
+        self.config.clone().apply(config);
+        if let Some(ref model) = self.model {
+            config.routing = RoutingStrategy::Single(model.clone());
+            config.model = model.clone();
+        }
+    }
+
+    /// `base` with this template's overrides, system prompt, and name.
+    pub fn harness_config(&self, base: &HarnessConfig) -> HarnessConfig {
+        let mut config = base.clone();
+        self.apply_settings(&mut config);
+        config.system_prompt = Some(self.system_prompt.clone());
+        config.agent_name = Some(self.name.clone());
+        config
+    }
+
+    /// The subset of `tools` this template allows. Errors if a selected
+    /// tool is not in `tools`, so typos in a TOML file are caught early.
+    pub fn tool_set(&self, tools: &ToolSet) -> Result<ToolSet, String> {
+        let Some(ref selected) = self.tools else {
+            return Ok(tools.fork());
+        };
+        let missing: Vec<&str> = selected
+            .iter()
+            .filter(|name| !tools.has_tool(name))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            return Err(format!(
+                "agent '{}' selects unknown tool(s): {}",
+                self.name,
+                missing.join(", ")
+            ));
+        }
+        Ok(tools.fork_only(selected))
+    }
+
+    /// A [`Specialist`] for orchestrators and handoff runners, drawing its
+    /// tools from `tools`.
+    pub fn specialist(&self, tools: &ToolSet) -> Result<Specialist, String> {
+        let mut specialist = Specialist::new(
+            &self.name,
+            &self.description,
+            &self.system_prompt,
+            self.tool_set(tools)?,
+        );
+        if let Some(model) = self.model.as_ref().or(self.config.model.as_ref()) {
+            specialist = specialist.with_model(model);
+        }
+        if let Some(max_rounds) = self.config.max_rounds {
+            specialist = specialist.with_max_rounds(max_rounds);
+        }
+        Ok(specialist)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.system_prompt.trim().is_empty() {
+            return Err(format!(
+                "agent '{}': system_prompt must not be empty",
+                self.name
+            ));
+        }
+        self.config
+            .validate()
+            .map_err(|e| format!("agent '{}': {e}", self.name))
+    }
+}
+
+/// Templates by name.
+#[derive(Debug, Clone, Default)]
+pub struct AgentTemplateRegistry {
+    templates: BTreeMap<String, AgentTemplate>,
+}
+
+/// Layout of an agents TOML file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TemplateFile {
+    #[serde(default)]
+    agents: BTreeMap<String, AgentTemplate>,
+}
+
+impl AgentTemplateRegistry {
+    /// An empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The built-in `researcher`, `coder`, `reviewer`, and `committer`
+    /// templates. Their tool selections use the
+    /// [common tool](crate::tools::names) names.
+    pub fn builtin() -> Self {
+        let read_only = [
+            names::READ_FILE,
+            names::LIST_DIR,
+            names::GREP,
+            names::FIND_FILES,
+            names::THINK,
+            names::TODO,
+        ];
+        let from_preset = |preset: Specialist| {
+            AgentTemplate::new(preset.name, preset.description, preset.system_prompt)
+        };
+        let committer = AgentTemplate::new(
+            "committer",
+            "Reviews the working tree diff and writes focused commits.",
+            "You are a commit specialist. Inspect the uncommitted changes with git, group \
+             them into logical commits, and commit each with a concise imperative subject \
+             line (under 72 characters) and a body explaining why when it isn't obvious. \
+             Never commit secrets, build artifacts, or unrelated changes.",
+        )
+        .with_tools([names::READ_FILE, names::GREP, names::SHELL, names::THINK])
+        .with_config(HarnessSettings {
+            max_rounds: Some(10),
+            temperature: Some(0.2),
+            ..Default::default()
+        });
+
+        Self::new()
+            .with_template(
+                from_preset(Specialist::researcher(ToolSet::new())).with_tools(read_only),
+            )
+            .with_template(from_preset(Specialist::coder(ToolSet::new())))
+            .with_template(from_preset(Specialist::reviewer(ToolSet::new())).with_tools(read_only))
+            .with_template(committer)
+    }
+
+    /// Add a template, replacing any template with the same name (builder
+    /// pattern).
+    pub fn with_template(mut self, template: AgentTemplate) -> Self {
+        self.templates.insert(template.name.clone(), template);
+        self
+    }
+
+    /// Parse templates from TOML (see the [module docs](self)).
+    pub fn from_toml_str(toml: &str) -> Result<Self, String> {
+        Self::extract(Figment::from(Toml::string(toml)))
+    }
+
+    /// Load templates from a TOML file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        if !path.is_file() {
+            return Err(format!("agents file not found: {}", path.display()));
+        }
+        Self::extract(Figment::from(Toml::file(path)))
+            .map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    /// Add the templates of a TOML file, replacing same-named ones
+    /// (builder pattern).
+    pub fn with_file(self, path: impl AsRef<Path>) -> Result<Self, String> {
+        Ok(self.merge(Self::load(path)?))
+    }
+
+    /// Add every template of `other`, replacing same-named ones.
+    pub fn merge(mut self, other: Self) -> Self {
+        self.templates.extend(other.templates);
+        self
+    }
+
+    fn extract(figment: Figment) -> Result<Self, String> {
+        let file: TemplateFile = figment
+            .extract()
+            .map_err(|e| format!("invalid agents file: {e}"))?;
+        let mut registry = Self::new();
+        for (name, mut template) in file.agents {
+            template.name = name;
+            template.validate()?;
+            registry = registry.with_template(template);
+        }
+        Ok(registry)
+    }
+
+    /// The template named `name`.
+    pub fn get(&self, name: &str) -> Option<&AgentTemplate> {
+        self.templates.get(name)
+    }
+
+    /// Like [`get`](Self::get), with an error listing the known names.
+    pub fn require(&self, name: &str) -> Result<&AgentTemplate, String> {
+        self.get(name).ok_or_else(|| {
+            format!(
+                "unknown agent '{name}' (available: {})",
+                self.names().join(", ")
+            )
+        })
+    }
+
+    /// Template names, sorted.
+    pub fn names(&self) -> Vec<&str> {
+        self.templates.keys().map(String::as_str).collect()
+    }
+
+    /// A [`Specialist`] from the template named `name`.
+    pub fn specialist(&self, name: &str, tools: &ToolSet) -> Result<Specialist, String> {
+        self.require(name)?.specialist(tools)
+    }
+
+    pub fn len(&self) -> usize {
+        self.templates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ToolDef;
+    use crate::tools::core::FnTool;
+
+    fn tools(names: &[&str]) -> ToolSet {
+        let set = ToolSet::new();
+        for name in names {
+            let def = ToolDef::new(*name, "test", serde_json::json!({"type": "object"}));
+            set.register(FnTool::new(def, |_: serde_json::Value| async {
+                String::new()
+            }));
+        }
+        set
+    }
+
+    const TOML: &str = r#"
+        [agents.reviewer]
+        description = "Strict reviewer"
+        system_prompt = "Reject anything without tests."
+        model = "big/model"
+        tools = ["read_file"]
+
+        [agents.reviewer.config]
+        max_rounds = 4
+        temperature = 0.1
+
+        [agents.triager]
+        system_prompt = "Label issues."
+    "#;
+
+    #[test]
+    fn toml_templates_override_builtins() {
+        let registry = AgentTemplateRegistry::builtin()
+            .merge(AgentTemplateRegistry::from_toml_str(TOML).unwrap());
+        assert_eq!(
+            registry.names(),
+            ["coder", "committer", "researcher", "reviewer", "triager"]
+        );
+        let reviewer = registry.get("reviewer").unwrap();
+        assert_eq!(reviewer.description, "Strict reviewer");
+        assert_eq!(reviewer.config.max_rounds, Some(4));
+
+        let config = reviewer.harness_config(&HarnessConfig::new("small/model", "base"));
+        assert_eq!(config.model, "big/model");
+        assert_eq!(config.max_rounds, 4);
+        assert_eq!(config.temperature, 0.1);
+        assert_eq!(config.agent_name.as_deref(), Some("reviewer"));
+        assert_eq!(
+            config.system_prompt.as_deref(),
+            Some("Reject anything without tests.")
+        );
+    }
+
+    #[test]
+    fn tool_selection_is_checked() {
+        let all = tools(&["read_file", "shell"]);
+        let registry = AgentTemplateRegistry::from_toml_str(TOML).unwrap();
+
+        let reviewer = registry.specialist("reviewer", &all).unwrap();
+        assert_eq!(reviewer.tools.names(), ["read_file"]);
+        assert_eq!(reviewer.model.as_deref(), Some("big/model"));
+        assert_eq!(reviewer.max_rounds, 4);
+        assert_eq!(registry.specialist("triager", &all).unwrap().tools.len(), 2);
+
+        let err = registry
+            .specialist("reviewer", &tools(&["shell"]))
+            .unwrap_err();
+        assert!(err.contains("unknown tool(s): read_file"), "{err}");
+        let err = registry.specialist("nobody", &all).unwrap_err();
+        assert!(err.contains("available: reviewer, triager"), "{err}");
+    }
+
+    #[test]
+    fn invalid_files_are_rejected() {
+        let err =
+            AgentTemplateRegistry::from_toml_str("[agents.a]\nsystem_prompt = \"\"").unwrap_err();
+        assert!(err.contains("agent 'a'"), "{err}");
+        let err = AgentTemplateRegistry::from_toml_str(
+            "[agents.a]\nsystem_prompt = \"x\"\n[agents.a.config]\nmax_round = 3",
+        )
+        .unwrap_err();
+        assert!(err.contains("max_round"), "{err}");
+    }
+}
@@ -0,0 +1,463 @@
+//! Warm-start context gathering before the first round.
+//!
+//! With [`HarnessConfig::warm_start`](super::config::HarnessConfig::warm_start)
+//! steps configured, [`Harness::run()`](super::harness::Harness::run) runs
+//! them concurrently through a [`ContextGatherer`] before round 1 and adds
+//! what they found to the system prompt as a dynamic "Workspace Context"
+//! section. This saves the model the handful of exploratory rounds it would
+//! otherwise spend listing files and checking `git status`.
+//!
+//! Built-in steps cover a repository map, git status, open `TODO`/`FIXME`
+//! comments, and memory topic files relevant to the task;
+//! [`WarmStartStep::custom`] adds any other async source. A step that fails,
+//! finds nothing, or misses the deadline is left out of the section.
+//!
+//! ```ignore
+//! let config = HarnessConfig::new("anthropic/claude-sonnet-4", "You are a coding agent.")
+//!     .with_warm_start(
+//!         HarnessWarmStartConfig::for_workdir(".")
+//!             .with_step(WarmStartStep::Memory { dir: "memory".into(), max_files: 3 }),
+//!     );
+//! ```
+
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+
+use super::config::HarnessWarmStartConfig;
+use super::gather::ContextGatherer;
+use crate::platform::Instant;
+
+/// Heading of the system prompt section holding warm-start results.
+pub const WARM_START_HEADING: &str = "Workspace Context";
+
+/// Longest memory topic file excerpt included, in characters.
+const MAX_MEMORY_CHARS: usize = 2_000;
+
+/// Directories never descended into by the repository map and TODO scan.
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules", "dist", "build", "__pycache__"];
+
+/// What a warm-start step knows about the run it prepares.
+#[derive(Debug, Clone, Default)]
+pub struct WarmStartInput {
+    /// Directory the steps inspect.
+    pub workdir: PathBuf,
+    /// The task (first user message).
+    pub task: String,
+    /// Significant lowercase words from the task.
+    pub keywords: Vec<String>,
+}
+
+/// Async producer for a [`WarmStartStep::Custom`] step. Returns `None` when
+/// there is nothing worth adding.
+pub type WarmStartFn =
+    Arc<dyn Fn(WarmStartInput) -> BoxFuture<'static, Option<String>> + Send + Sync>;
+
+/// One source of warm-start context.
+#[derive(Clone)]
+pub enum WarmStartStep {
+    /// Relative paths under the workdir, skipping hidden and build
+    /// directories, up to `max_entries`.
+    RepoMap { max_entries: usize },
+    /// `git status --short --branch` in the workdir.
+    GitStatus,
+    /// `TODO`, `FIXME`, `XXX`, and `HACK` comments, up to `max_matches`.
+    Todos { max_matches: usize },
+    /// Markdown topic files in `dir` that mention the task's keywords,
+    /// best matches first, up to `max_files`.
+    Memory { dir: PathBuf, max_files: usize },
+    /// A caller-supplied step.
+    Custom { name: String, run: WarmStartFn },
+}
+
+impl WarmStartStep {
+    /// A caller-supplied step named `name`.
+    pub fn custom<F, Fut>(name: impl Into<String>, run: F) -> Self
+    where
+        F: Fn(WarmStartInput) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<String>> + Send + 'static,
+    {
+        Self::Custom {
+            name: name.into(),
+            run: Arc::new(move |input| Box::pin(run(input))),
+        }
+    }
+
+    /// Label used for the step's subsection and in events.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::RepoMap { .. } => "Repository map",
+            Self::GitStatus => "Git status",
+            Self::Todos { .. } => "Open TODOs",
+            Self::Memory { .. } => "Relevant memory",
+            Self::Custom { name, .. } => name,
+        }
+    }
+
+    /// Run the step. Built-in steps do blocking file and process I/O, so
+    /// they run on the blocking pool.
+    fn run(&self, input: WarmStartInput) -> BoxFuture<'static, Option<String>> {
+        if let Self::Custom { run, .. } = self {
+            return run(input);
+        }
+        let step = self.clone();
+        Box::pin(async move {
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                tokio::task::spawn_blocking(move || step.run_builtin(&input))
+                    .await
+                    .ok()
+                    .flatten()
+            }
+            #[cfg(target_arch = "wasm32")]
+            {
+                step.run_builtin(&input)
+            }
+        })
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn run_builtin(&self, input: &WarmStartInput) -> Option<String> {
+        match self {
+            Self::RepoMap { max_entries } => repo_map(&input.workdir, *max_entries),
+            Self::GitStatus => git_status(&input.workdir),
+            Self::Todos { max_matches } => open_todos(&input.workdir, *max_matches),
+            Self::Memory { dir, max_files } => relevant_memory(dir, &input.keywords, *max_files),
+            Self::Custom { .. } => None,
+        }
+    }
+
+    /// No filesystem or processes on wasm32.
+    #[cfg(target_arch = "wasm32")]
+    fn run_builtin(&self, _input: &WarmStartInput) -> Option<String> {
+        None
+    }
+}
+
+impl fmt::Debug for WarmStartStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RepoMap { max_entries } => f
+                .debug_struct("RepoMap")
+                .field("max_entries", max_entries)
+                .finish(),
+            Self::GitStatus => f.write_str("GitStatus"),
+            Self::Todos { max_matches } => f
+                .debug_struct("Todos")
+                .field("max_matches", max_matches)
+                .finish(),
+            Self::Memory { dir, max_files } => f
+                .debug_struct("Memory")
+                .field("dir", dir)
+                .field("max_files", max_files)
+                .finish(),
+            Self::Custom { name, .. } => f.debug_struct("Custom").field("name", name).finish(),
+        }
+    }
+}
+
+/// What a warm start gathered.
+#[derive(Debug, Clone, Default)]
+pub struct WarmStartReport {
+    /// `(step name, content)` for each step that produced something, in
+    /// configured order.
+    pub sections: Vec<(String, String)>,
+    /// Names of steps that failed, found nothing, or missed the deadline.
+    pub missing: Vec<String>,
+    /// Wall time of the whole gather.
+    pub elapsed: Duration,
+}
+
+impl WarmStartReport {
+    /// Names of the steps that produced content.
+    pub fn gathered(&self) -> Vec<String> {
+        self.sections.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    /// The prompt section body: one `### <step>` subsection per gathered
+    /// step. `None` when nothing was gathered.
+    pub fn render(&self) -> Option<String> {
+        if self.sections.is_empty() {
+            return None;
+        }
+        let parts: Vec<String> = self
+            .sections
+            .iter()
+            .map(|(name, content)| format!("### {name}\n\n{content}"))
+            .collect();
+        Some(parts.join("\n\n"))
+    }
+}
+
+/// Run `config`'s steps concurrently, within its deadline.
+pub async fn gather(config: &HarnessWarmStartConfig, input: WarmStartInput) -> WarmStartReport {
+    let started = Instant::now();
+    let mut results: Vec<Option<String>> = vec![None; config.steps.len()];
+
+    let mut gatherer = ContextGatherer::new(config.deadline).default_task_timeout(config.deadline);
+    for (idx, step) in config.steps.iter().enumerate() {
+        gatherer = gatherer.task(
+            step.name(),
+            Duration::ZERO,
+            step.run(input.clone()),
+            move |content, results: &mut Vec<Option<String>>| results[idx] = content,
+        );
+    }
+    gatherer.run(&mut results).await;
+
+    let mut report = WarmStartReport::default();
+    for (step, content) in config.steps.iter().zip(results) {
+        match content.filter(|c| !c.trim().is_empty()) {
+            Some(content) => report
+                .sections
+                .push((step.name().to_string(), content.trim_end().to_string())),
+            None => report.missing.push(step.name().to_string()),
+        }
+    }
+    report.elapsed = started.elapsed();
+    report
+}
+
+// ── Built-in steps ─────────────────────────────────────────────────
+
+/// Walk the non-hidden, non-build entries under `root`, sorted by name.
+#[cfg(not(target_arch = "wasm32"))]
+fn walk_workspace(root: &std::path::Path) -> impl Iterator<Item = walkdir::DirEntry> {
+    walkdir::WalkDir::new(root)
+        .min_depth(1)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| {
+            let name = e.file_name().to_string_lossy();
+            let skipped_dir = e.file_type().is_dir() && SKIPPED_DIRS.contains(&&*name);
+            !name.starts_with('.') && !skipped_dir
+        })
+        .filter_map(Result::ok)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn relative(root: &std::path::Path, path: &std::path::Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn repo_map(root: &std::path::Path, max_entries: usize) -> Option<String> {
+    let mut lines = Vec::new();
+    let mut more = 0usize;
+    for entry in walk_workspace(root) {
+        if lines.len() >= max_entries {
+            more += 1;
+            continue;
+        }
+        let mut line = relative(root, entry.path());
+        if entry.file_type().is_dir() {
+            line.push('/');
+        }
+        lines.push(line);
+    }
+    if more > 0 {
+        lines.push(format!("... ({more} more)"));
+    }
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn git_status(root: &std::path::Path) -> Option<String> {
+    let output = std::process::Command::new("git")
+        .args(["status", "--short", "--branch"])
+        .current_dir(root)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn open_todos(root: &std::path::Path, max_matches: usize) -> Option<String> {
+    /// Files larger than this are skipped.
+    const MAX_FILE_BYTES: u64 = 1024 * 1024;
+
+    let marker = regex::Regex::new(r"\b(TODO|FIXME|XXX|HACK)\b").ok()?;
+    let mut lines = Vec::new();
+    'files: for entry in walk_workspace(root) {
+        if !entry.file_type().is_file()
+            || entry.metadata().map_or(true, |m| m.len() > MAX_FILE_BYTES)
+        {
+            continue;
+        }
+        // Non-UTF-8 files are binary as far as this scan is concerned.
+        let Ok(text) = std::fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        for (n, line) in text.lines().enumerate() {
+            if marker.is_match(line) {
+                if lines.len() >= max_matches {
+                    break 'files;
+                }
+                lines.push(format!(
+                    "{}:{}: {}",
+                    relative(root, entry.path()),
+                    n + 1,
+                    line.trim()
+                ));
+            }
+        }
+    }
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn relevant_memory(dir: &std::path::Path, keywords: &[String], max_files: usize) -> Option<String> {
+    if keywords.is_empty() {
+        return None;
+    }
+    let mut scored: Vec<(usize, String, String)> = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "md"))
+        // The index is already in the prompt via `memory_config`.
+        .filter(|e| e.file_name() != "MEMORY.md")
+        .filter_map(|e| {
+            let text = std::fs::read_to_string(e.path()).ok()?;
+            let lower = text.to_lowercase();
+            let score = keywords
+                .iter()
+                .filter(|k| lower.contains(k.as_str()))
+                .count();
+            (score > 0).then(|| (score, e.file_name().to_string_lossy().into_owned(), text))
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+
+    let parts: Vec<String> = scored
+        .into_iter()
+        .take(max_files)
+        .map(|(_, name, text)| {
+            let excerpt: String = text.chars().take(MAX_MEMORY_CHARS).collect();
+            let cut = if excerpt.len() < text.len() {
+                "\n[...]"
+            } else {
+                ""
+            };
+            format!("#### {name}\n\n{}{cut}", excerpt.trim_end())
+        })
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(workdir: &std::path::Path, keywords: &[&str]) -> WarmStartInput {
+        WarmStartInput {
+            workdir: workdir.to_path_buf(),
+            task: String::new(),
+            keywords: keywords.iter().map(|k| k.to_string()).collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn gathers_builtin_steps_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::create_dir_all(dir.path().join("target/debug")).unwrap();
+        std::fs::create_dir_all(dir.path().join(".hidden")).unwrap();
+        std::fs::write(
+            dir.path().join("src/lib.rs"),
+            "fn a() {}\n// TODO: handle errors\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("target/debug/out.rs"), "// TODO: ignored\n").unwrap();
+
+        let config = HarnessWarmStartConfig {
+            steps: vec![
+                WarmStartStep::Todos { max_matches: 10 },
+                WarmStartStep::RepoMap { max_entries: 10 },
+            ],
+            ..HarnessWarmStartConfig::default()
+        };
+        let report = gather(&config, input(dir.path(), &[])).await;
+
+        assert!(report.missing.is_empty());
+        assert_eq!(report.gathered(), ["Open TODOs", "Repository map"]);
+        assert_eq!(report.sections[0].1, "src/lib.rs:2: // TODO: handle errors");
+        assert_eq!(report.sections[1].1, "src/\nsrc/lib.rs");
+        let rendered = report.render().unwrap();
+        assert!(rendered.starts_with("### Open TODOs\n\n"));
+        assert!(rendered.contains("### Repository map\n\nsrc/"));
+    }
+
+    #[test]
+    fn repo_map_notes_entries_past_the_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["a.rs", "b.rs", "c.rs"] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+        assert_eq!(
+            repo_map(dir.path(), 2).as_deref(),
+            Some("a.rs\nb.rs\n... (1 more)")
+        );
+    }
+
+    #[tokio::test]
+    async fn memory_step_ranks_topic_files_by_keyword_hits() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("MEMORY.md"), "parser deploy").unwrap();
+        std::fs::write(dir.path().join("deploy.md"), "Deploy with the parser flag.").unwrap();
+        std::fs::write(dir.path().join("parser.md"), "The parser is slow.").unwrap();
+        std::fs::write(dir.path().join("style.md"), "Use tabs.").unwrap();
+
+        let config = HarnessWarmStartConfig {
+            steps: vec![WarmStartStep::Memory {
+                dir: dir.path().to_path_buf(),
+                max_files: 5,
+            }],
+            ..HarnessWarmStartConfig::default()
+        };
+        let report = gather(&config, input(dir.path(), &["parser", "deploy"])).await;
+
+        let memory = &report.sections[0].1;
+        assert!(memory.starts_with("#### deploy.md"));
+        assert!(memory.contains("#### parser.md"));
+        assert!(!memory.contains("style.md"));
+        assert!(!memory.contains("MEMORY.md"));
+    }
+
+    #[tokio::test]
+    async fn slow_and_empty_steps_are_reported_missing() {
+        let config = HarnessWarmStartConfig {
+            steps: vec![
+                WarmStartStep::custom("fast", |input: WarmStartInput| async move {
+                    Some(format!("task: {}", input.task))
+                }),
+                WarmStartStep::custom("empty", |_| async { Some("  ".to_string()) }),
+                WarmStartStep::custom("slow", |_| async {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    Some("late".to_string())
+                }),
+            ],
+            deadline: Duration::from_millis(100),
+            ..HarnessWarmStartConfig::default()
+        };
+        let report = gather(
+            &config,
+            WarmStartInput {
+                task: "fix it".into(),
+                ..WarmStartInput::default()
+            },
+        )
+        .await;
+
+        assert_eq!(report.sections, [("fast".into(), "task: fix it".into())]);
+        assert_eq!(report.missing, ["empty", "slow"]);
+    }
+}
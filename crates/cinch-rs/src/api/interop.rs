@@ -0,0 +1,492 @@
+//! Conversion between cinch [`Message`] histories and other chat formats.
+//!
+//! Sessions are stored in cinch's own (OpenAI-shaped) message format. The
+//! converters here map a history to and from the request shapes other
+//! tooling speaks, so a session can be exported for replay or evaluation
+//! elsewhere, and a conversation started elsewhere can be imported and
+//! resumed by the harness:
+//!
+//! - [`ChatFormat::OpenAi`] — the OpenAI Chat Completions `messages` array.
+//!   Tool calls map one-to-one.
+//! - [`ChatFormat::Anthropic`] — the Anthropic Messages API: a top-level
+//!   `system` string plus strictly alternating `user` / `assistant`
+//!   messages made of content blocks. Assistant tool calls become
+//!   `tool_use` blocks and tool results become `tool_result` blocks in the
+//!   following user message.
+//!
+//! Both exports are request-body fragments (`{"messages": [...]}`, plus
+//! `"system"` for Anthropic). Imports accept that shape or a bare messages
+//! array. Only text survives the round trip: images, documents, and
+//! thinking blocks are dropped on import.
+
+use serde_json::{Value, json};
+
+use crate::{CallType, FunctionCallData, Message, MessageRole, ToolCall};
+
+/// An external chat message format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatFormat {
+    /// OpenAI Chat Completions messages.
+    OpenAi,
+    /// Anthropic Messages API.
+    Anthropic,
+}
+
+impl ChatFormat {
+    /// Guess the format of an exported conversation: Anthropic when it has
+    /// a top-level `system` field or any `tool_use` / `tool_result` block,
+    /// OpenAI otherwise.
+    pub fn detect(value: &Value) -> Self {
+        if value.get("system").is_some() {
+            return ChatFormat::Anthropic;
+        }
+        let has_anthropic_blocks = messages_array(value).is_ok_and(|messages| {
+            messages
+                .iter()
+                .filter_map(|m| m.get("content").and_then(Value::as_array))
+                .flatten()
+                .any(|block| {
+                    matches!(
+                        block.get("type").and_then(Value::as_str),
+                        Some("tool_use" | "tool_result")
+                    )
+                })
+        });
+        if has_anthropic_blocks {
+            ChatFormat::Anthropic
+        } else {
+            ChatFormat::OpenAi
+        }
+    }
+}
+
+impl std::str::FromStr for ChatFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "openai" => Ok(ChatFormat::OpenAi),
+            "anthropic" => Ok(ChatFormat::Anthropic),
+            other => Err(format!(
+                "unknown chat format '{other}' (expected openai or anthropic)"
+            )),
+        }
+    }
+}
+
+/// Export `messages` in `format`.
+pub fn export_messages(messages: &[Message], format: ChatFormat) -> Value {
+    match format {
+        ChatFormat::OpenAi => to_openai(messages),
+        ChatFormat::Anthropic => to_anthropic(messages),
+    }
+}
+
+/// Import a conversation in `format`.
+pub fn import_messages(value: &Value, format: ChatFormat) -> Result<Vec<Message>, String> {
+    match format {
+        ChatFormat::OpenAi => from_openai(value),
+        ChatFormat::Anthropic => from_anthropic(value),
+    }
+}
+
+// ── OpenAI ──────────────────────────────────────────────────────────
+
+/// Convert to OpenAI Chat Completions messages: `{"messages": [...]}`.
+pub fn to_openai(messages: &[Message]) -> Value {
+    let messages: Vec<Value> = messages
+        .iter()
+        .map(|m| {
+            let mut out = json!({
+                "role": role_name(&m.role),
+                "content": m.content,
+            });
+            if let Some(ref calls) = m.tool_calls {
+                out["tool_calls"] = calls
+                    .iter()
+                    .map(|c| {
+                        json!({
+                            "id": c.id,
+                            "type": "function",
+                            "function": {
+                                "name": c.function.name,
+                                "arguments": c.function.arguments,
+                            },
+                        })
+                    })
+                    .collect();
+            }
+            if let Some(ref id) = m.tool_call_id {
+                out["tool_call_id"] = json!(id);
+            }
+            out
+        })
+        .collect();
+    json!({ "messages": messages })
+}
+
+/// Parse OpenAI Chat Completions messages. The `developer` role is read as
+/// `system`.
+pub fn from_openai(value: &Value) -> Result<Vec<Message>, String> {
+    messages_array(value)?
+        .iter()
+        .enumerate()
+        .map(|(i, m)| {
+            let role = m.get("role").and_then(Value::as_str).unwrap_or_default();
+            let content = text_content(m.get("content"));
+            match role {
+                "system" | "developer" => Ok(Message::system(content.unwrap_or_default())),
+                "user" => Ok(Message::user(content.unwrap_or_default())),
+                "assistant" => {
+                    let calls = m
+                        .get("tool_calls")
+                        .and_then(Value::as_array)
+                        .map(|calls| calls.iter().map(openai_tool_call).collect())
+                        .transpose()
+                        .map_err(|e| format!("message {i}: {e}"))?
+                        .filter(|calls: &Vec<ToolCall>| !calls.is_empty());
+                    Ok(Message {
+                        content,
+                        tool_calls: calls,
+                        ..Message::assistant_text("")
+                    })
+                }
+                "tool" => {
+                    let id = m
+                        .get("tool_call_id")
+                        .and_then(Value::as_str)
+                        .ok_or_else(|| format!("message {i}: tool message without tool_call_id"))?;
+                    Ok(Message::tool_result(id, content.unwrap_or_default()))
+                }
+                other => Err(format!("message {i}: unsupported role '{other}'")),
+            }
+        })
+        .collect()
+}
+
+fn openai_tool_call(call: &Value) -> Result<ToolCall, String> {
+    let id = call.get("id").and_then(Value::as_str);
+    let function = call.get("function");
+    let name = function.and_then(|f| f.get("name")).and_then(Value::as_str);
+    let (Some(id), Some(name)) = (id, name) else {
+        return Err("tool call without id or function name".into());
+    };
+    let arguments = match function.and_then(|f| f.get("arguments")) {
+        Some(Value::String(args)) => args.clone(),
+        Some(Value::Null) | None => "{}".into(),
+        Some(args) => args.to_string(),
+    };
+    Ok(tool_call(id, name, arguments))
+}
+
+// ── Anthropic ───────────────────────────────────────────────────────
+
+/// Convert to an Anthropic Messages request fragment:
+/// `{"system": "...", "messages": [...]}`.
+///
+/// Leading system messages become `system`; later ones (reminders) become
+/// user text. Consecutive same-role messages are merged, since the API
+/// requires strict alternation. Tool call arguments that are not valid
+/// JSON are exported as a JSON string input.
+pub fn to_anthropic(messages: &[Message]) -> Value {
+    let leading = messages
+        .iter()
+        .take_while(|m| m.role == MessageRole::System)
+        .count();
+    let system: Vec<&str> = messages[..leading]
+        .iter()
+        .filter_map(|m| m.content.as_deref())
+        .collect();
+
+    let mut turns: Vec<(&str, Vec<Value>)> = Vec::new();
+    for m in &messages[leading..] {
+        let (role, blocks) = match m.role {
+            MessageRole::Assistant => {
+                let mut blocks = text_block(m.content.as_deref());
+                for call in m.tool_calls.iter().flatten() {
+                    let input = serde_json::from_str(&call.function.arguments)
+                        .unwrap_or_else(|_| Value::String(call.function.arguments.clone()));
+                    blocks.push(json!({
+                        "type": "tool_use",
+                        "id": call.id,
+                        "name": call.function.name,
+                        "input": input,
+                    }));
+                }
+                ("assistant", blocks)
+            }
+            MessageRole::Tool => (
+                "user",
+                vec![json!({
+                    "type": "tool_result",
+                    "tool_use_id": m.tool_call_id.as_deref().unwrap_or_default(),
+                    "content": m.content.as_deref().unwrap_or_default(),
+                })],
+            ),
+            MessageRole::System | MessageRole::User => ("user", text_block(m.content.as_deref())),
+        };
+        if blocks.is_empty() {
+            continue;
+        }
+        match turns.last_mut() {
+            Some((last, content)) if *last == role => content.extend(blocks),
+            _ => turns.push((role, blocks)),
+        }
+    }
+
+    let messages: Vec<Value> = turns
+        .into_iter()
+        .map(|(role, content)| json!({ "role": role, "content": content }))
+        .collect();
+    let mut out = json!({ "messages": messages });
+    if !system.is_empty() {
+        out["system"] = json!(system.join("\n\n"));
+    }
+    out
+}
+
+/// Parse an Anthropic Messages conversation. Each `tool_result` block
+/// becomes a tool message (error results are prefixed with `Error: `);
+/// the text in the same user turn follows as a user message.
+pub fn from_anthropic(value: &Value) -> Result<Vec<Message>, String> {
+    let mut out = Vec::new();
+    if let Some(system) = text_content(value.get("system")).filter(|s| !s.is_empty()) {
+        out.push(Message::system(system));
+    }
+    for (i, m) in messages_array(value)?.iter().enumerate() {
+        let role = m.get("role").and_then(Value::as_str).unwrap_or_default();
+        let blocks = match m.get("content") {
+            Some(Value::String(text)) => vec![json!({ "type": "text", "text": text })],
+            Some(Value::Array(blocks)) => blocks.clone(),
+            _ => Vec::new(),
+        };
+        let mut text = Vec::new();
+        match role {
+            "user" => {
+                for block in &blocks {
+                    match block.get("type").and_then(Value::as_str) {
+                        Some("text") => text.extend(block.get("text").and_then(Value::as_str)),
+                        Some("tool_result") => {
+                            let id = block
+                                .get("tool_use_id")
+                                .and_then(Value::as_str)
+                                .ok_or_else(|| format!("message {i}: tool_result without id"))?;
+                            let mut content =
+                                text_content(block.get("content")).unwrap_or_default();
+                            if block.get("is_error").and_then(Value::as_bool) == Some(true) {
+                                content = format!("Error: {content}");
+                            }
+                            out.push(Message::tool_result(id, content));
+                        }
+                        _ => {}
+                    }
+                }
+                if !text.is_empty() {
+                    out.push(Message::user(text.join("\n\n")));
+                }
+            }
+            "assistant" => {
+                let mut calls = Vec::new();
+                for block in &blocks {
+                    match block.get("type").and_then(Value::as_str) {
+                        Some("text") => text.extend(block.get("text").and_then(Value::as_str)),
+                        Some("tool_use") => {
+                            let id = block.get("id").and_then(Value::as_str);
+                            let name = block.get("name").and_then(Value::as_str);
+                            let (Some(id), Some(name)) = (id, name) else {
+                                return Err(format!("message {i}: tool_use without id or name"));
+                            };
+                            let arguments = match block.get("input") {
+                                Some(Value::String(raw)) => raw.clone(),
+                                Some(input) => input.to_string(),
+                                None => "{}".into(),
+                            };
+                            calls.push(tool_call(id, name, arguments));
+                        }
+                        _ => {}
+                    }
+                }
+                out.push(Message {
+                    content: (!text.is_empty()).then(|| text.join("\n\n")),
+                    tool_calls: (!calls.is_empty()).then_some(calls),
+                    ..Message::assistant_text("")
+                });
+            }
+            other => return Err(format!("message {i}: unsupported role '{other}'")),
+        }
+    }
+    Ok(out)
+}
+
+// ── Helpers ─────────────────────────────────────────────────────────
+
+/// The messages of an exported conversation: a bare array or the
+/// `messages` field of an object.
+fn messages_array(value: &Value) -> Result<&Vec<Value>, String> {
+    value
+        .as_array()
+        .or_else(|| value.get("messages").and_then(Value::as_array))
+        .ok_or_else(|| "expected a messages array or an object with \"messages\"".to_string())
+}
+
+/// Text of a string or of the text parts of a content array.
+fn text_content(content: Option<&Value>) -> Option<String> {
+    match content? {
+        Value::String(text) => Some(text.clone()),
+        Value::Array(parts) => {
+            let texts: Vec<&str> = parts
+                .iter()
+                .filter_map(|p| match p {
+                    Value::String(text) => Some(text.as_str()),
+                    _ => p.get("text").and_then(Value::as_str),
+                })
+                .collect();
+            Some(texts.join("\n\n"))
+        }
+        _ => None,
+    }
+}
+
+/// A single Anthropic text block, or none for empty text.
+fn text_block(text: Option<&str>) -> Vec<Value> {
+    text.filter(|t| !t.is_empty())
+        .map(|t| json!({ "type": "text", "text": t }))
+        .into_iter()
+        .collect()
+}
+
+fn role_name(role: &MessageRole) -> &'static str {
+    match role {
+        MessageRole::System => "system",
+        MessageRole::User => "user",
+        MessageRole::Assistant => "assistant",
+        MessageRole::Tool => "tool",
+    }
+}
+
+pub(crate) fn tool_call(id: &str, name: &str, arguments: String) -> ToolCall {
+    ToolCall {
+        id: id.to_string(),
+        call_type: CallType::Function,
+        function: FunctionCallData {
+            name: name.to_string(),
+            arguments,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversation() -> Vec<Message> {
+        vec![
+            Message::system("You are a coding agent."),
+            Message::user("What's in main.rs?"),
+            Message::assistant_tool_calls(vec![tool_call(
+                "call_1",
+                "read_file",
+                r#"{"path":"main.rs"}"#.into(),
+            )]),
+            Message::tool_result("call_1", "fn main() {}"),
+            Message::system("Reminder: be brief."),
+            Message::assistant_text("An empty main function."),
+        ]
+    }
+
+    fn assert_same(a: &[Message], b: &[Message]) {
+        let a = serde_json::to_value(a).unwrap();
+        let b = serde_json::to_value(b).unwrap();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn openai_round_trips() {
+        let messages = conversation();
+        let exported = to_openai(&messages);
+        assert_eq!(
+            exported["messages"][2]["tool_calls"][0]["function"]["name"],
+            "read_file"
+        );
+        assert_eq!(ChatFormat::detect(&exported), ChatFormat::OpenAi);
+        assert_same(&from_openai(&exported).unwrap(), &messages);
+    }
+
+    #[test]
+    fn anthropic_export_alternates_and_maps_tools() {
+        let exported = to_anthropic(&conversation());
+        assert_eq!(exported["system"], "You are a coding agent.");
+        let roles: Vec<&str> = exported["messages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["role"].as_str().unwrap())
+            .collect();
+        assert_eq!(roles, ["user", "assistant", "user", "assistant"]);
+        let tool_use = &exported["messages"][1]["content"][0];
+        assert_eq!(tool_use["type"], "tool_use");
+        assert_eq!(tool_use["input"]["path"], "main.rs");
+        // The tool result and the later reminder share one user turn.
+        let turn = &exported["messages"][2]["content"];
+        assert_eq!(turn[0]["tool_use_id"], "call_1");
+        assert_eq!(turn[1]["text"], "Reminder: be brief.");
+        assert_eq!(ChatFormat::detect(&exported), ChatFormat::Anthropic);
+    }
+
+    #[test]
+    fn anthropic_import_maps_blocks() {
+        let value = json!({
+            "system": [{ "type": "text", "text": "Be helpful." }],
+            "messages": [
+                { "role": "user", "content": "List files" },
+                { "role": "assistant", "content": [
+                    { "type": "thinking", "thinking": "..." },
+                    { "type": "text", "text": "Listing." },
+                    { "type": "tool_use", "id": "tu_1", "name": "ls", "input": { "path": "." } },
+                ]},
+                { "role": "user", "content": [
+                    { "type": "tool_result", "tool_use_id": "tu_1", "is_error": true,
+                      "content": [{ "type": "text", "text": "permission denied" }] },
+                    { "type": "text", "text": "Try sudo?" },
+                ]},
+            ],
+        });
+        let messages = from_anthropic(&value).unwrap();
+        let roles: Vec<MessageRole> = messages.iter().map(|m| m.role.clone()).collect();
+        assert_eq!(
+            roles,
+            [
+                MessageRole::System,
+                MessageRole::User,
+                MessageRole::Assistant,
+                MessageRole::Tool,
+                MessageRole::User,
+            ]
+        );
+        assert_eq!(messages[2].content.as_deref(), Some("Listing."));
+        let call = &messages[2].tool_calls.as_ref().unwrap()[0];
+        assert_eq!(call.function.arguments, r#"{"path":"."}"#);
+        assert_eq!(messages[3].tool_call_id.as_deref(), Some("tu_1"));
+        assert_eq!(
+            messages[3].content.as_deref(),
+            Some("Error: permission denied")
+        );
+    }
+
+    #[test]
+    fn anthropic_round_trip_keeps_tool_pairs() {
+        let imported = from_anthropic(&to_anthropic(&conversation())).unwrap();
+        assert_eq!(imported.len(), 6);
+        assert_eq!(imported[3].tool_call_id.as_deref(), Some("call_1"));
+        // The mid-conversation reminder comes back as user text.
+        assert_eq!(imported[4].role, MessageRole::User);
+    }
+
+    #[test]
+    fn import_rejects_unknown_shapes() {
+        assert!(from_openai(&json!({ "foo": 1 })).is_err());
+        assert!(from_openai(&json!([{ "role": "wizard", "content": "hi" }])).is_err());
+        assert!("gemini".parse::<ChatFormat>().is_err());
+        assert_eq!("OpenAI".parse::<ChatFormat>(), Ok(ChatFormat::OpenAi));
+    }
+}
@@ -0,0 +1,221 @@
+//! Request/response middleware for [`OpenRouterClient`].
+//!
+//! A [`ClientMiddleware`] sees every chat request the client sends and every
+//! response it receives, without forking the client: inject headers, log or
+//! redact payloads, rewrite requests (e.g. force a provider preference), or
+//! reject requests locally as a guardrail. Register middleware with
+//! [`OpenRouterClient::with_middleware()`]; hooks run in registration order.
+//!
+//! ```ignore
+//! struct Tagging;
+//!
+//! impl ClientMiddleware for Tagging {
+//!     fn headers(&self) -> Vec<(String, String)> {
+//!         vec![("X-Team".into(), "search".into())]
+//!     }
+//!
+//!     fn on_request(&self, request: &mut ChatRequest) -> Result<(), String> {
+//!         request.temperature = request.temperature.min(0.5);
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let client = OpenRouterClient::new(key)?.with_middleware(Tagging);
+//! ```
+
+use std::borrow::Cow;
+
+use super::streaming::StreamEvent;
+use super::transport::{HttpRequest, HttpResponse};
+use crate::{ChatCompletion, ChatRequest, OpenRouterClient};
+
+/// Hooks run around each API call. All methods default to no-ops.
+pub trait ClientMiddleware: Send + Sync {
+    /// Extra HTTP headers attached to every request (chat and embeddings).
+    fn headers(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    /// Inspect or rewrite an outgoing chat request. Returning `Err` rejects
+    /// the request before it is sent; the error is returned to the caller.
+    fn on_request(&self, _request: &mut ChatRequest) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Inspect or rewrite a completion returned by
+    /// [`chat()`](OpenRouterClient::chat). Returning `Err` turns the call
+    /// into an error.
+    fn on_response(
+        &self,
+        _request: &ChatRequest,
+        _completion: &mut ChatCompletion,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Inspect or rewrite the events returned by the streaming methods.
+    /// For [`chat_stream_live()`](OpenRouterClient::chat_stream_live) this
+    /// runs after the live callback has already seen the events.
+    fn on_stream_response(
+        &self,
+        _request: &ChatRequest,
+        _events: &mut Vec<StreamEvent>,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl OpenRouterClient {
+    /// Run every middleware's request hook. Borrows `body` unchanged when no
+    /// middleware is registered.
+    pub(crate) fn prepare_request<'b>(
+        &self,
+        body: &'b ChatRequest,
+    ) -> Result<Cow<'b, ChatRequest>, String> {
+        if self.middleware.is_empty() {
+            return Ok(Cow::Borrowed(body));
+        }
+        let mut request = body.clone();
+        for m in &self.middleware {
+            m.on_request(&mut request)?;
+        }
+        Ok(Cow::Owned(request))
+    }
+
+    /// POST `body` as JSON to `url` through the transport, with the auth
+    /// and attribution headers plus any middleware headers.
+    pub(crate) async fn post(
+        &self,
+        url: &str,
+        body: &impl serde::Serialize,
+    ) -> Result<HttpResponse, String> {
+        let body =
+            serde_json::to_string(body).map_err(|e| format!("failed to serialize request: {e}"))?;
+        let mut headers = vec![
+            (
+                "Authorization".to_string(),
+                format!("Bearer {}", self.api_key),
+            ),
+            ("HTTP-Referer".to_string(), self.referer.clone()),
+            ("X-Title".to_string(), self.title.clone()),
+        ];
+        for m in &self.middleware {
+            headers.extend(m.headers());
+        }
+        let request = HttpRequest {
+            url: url.to_string(),
+            headers,
+            body,
+        };
+        self.transport.send(request).await
+    }
+
+    /// Run every middleware's response hook on a completion.
+    pub(crate) fn finish_response(
+        &self,
+        request: &ChatRequest,
+        mut completion: ChatCompletion,
+    ) -> Result<ChatCompletion, String> {
+        for m in &self.middleware {
+            m.on_response(request, &mut completion)?;
+        }
+        Ok(completion)
+    }
+
+    /// Run every middleware's stream hook on the collected events.
+    pub(crate) fn finish_stream(
+        &self,
+        request: &ChatRequest,
+        mut events: Vec<StreamEvent>,
+    ) -> Result<Vec<StreamEvent>, String> {
+        for m in &self.middleware {
+            m.on_stream_response(request, &mut events)?;
+        }
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Message;
+
+    struct Guardrail;
+
+    impl ClientMiddleware for Guardrail {
+        fn on_request(&self, request: &mut ChatRequest) -> Result<(), String> {
+            let leaks = request
+                .messages
+                .iter()
+                .any(|m| m.content.as_deref().is_some_and(|c| c.contains("sk-")));
+            if leaks {
+                return Err("blocked: request contains an API key".into());
+            }
+            request.max_tokens = request.max_tokens.min(256);
+            Ok(())
+        }
+
+        fn on_response(
+            &self,
+            _request: &ChatRequest,
+            completion: &mut ChatCompletion,
+        ) -> Result<(), String> {
+            if let Some(ref mut content) = completion.content {
+                *content = content.replace("secret", "[redacted]");
+            }
+            Ok(())
+        }
+    }
+
+    fn request(content: &str) -> ChatRequest {
+        ChatRequest {
+            messages: vec![Message::user(content)],
+            max_tokens: 4096,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn without_middleware_requests_are_borrowed() {
+        let client = OpenRouterClient::new("key").unwrap();
+        let body = request("hi");
+        assert!(matches!(
+            client.prepare_request(&body).unwrap(),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn middleware_rewrites_and_rejects_requests() {
+        let client = OpenRouterClient::new("key")
+            .unwrap()
+            .with_middleware(Guardrail);
+        let body = request("hi");
+        let prepared = client.prepare_request(&body).unwrap();
+        assert_eq!(prepared.max_tokens, 256);
+        assert!(
+            client
+                .prepare_request(&request("my key is sk-123"))
+                .unwrap_err()
+                .starts_with("blocked")
+        );
+    }
+
+    #[test]
+    fn middleware_rewrites_responses() {
+        let client = OpenRouterClient::new("key")
+            .unwrap()
+            .with_middleware(Guardrail);
+        let completion = ChatCompletion {
+            content: Some("the secret is 42".into()),
+            tool_calls: vec![],
+            usage: None,
+            annotations: vec![],
+            finish_reason: None,
+            reasoning: None,
+            provider: None,
+        };
+        let completion = client.finish_response(&request("hi"), completion).unwrap();
+        assert_eq!(completion.content.as_deref(), Some("the [redacted] is 42"));
+    }
+}
@@ -3,21 +3,56 @@
 //! These modules handle everything between the [`Harness`](crate::agent::harness::Harness)
 //! loop and the OpenRouter API:
 //!
+//! - [`interop`] — converters between [`Message`](crate::Message) histories
+//!   and the OpenAI Chat / Anthropic Messages formats, for exporting and
+//!   importing conversations.
+//! - [`middleware`] — [`ClientMiddleware`] hooks that inspect or rewrite
+//!   requests and responses (headers, logging, guardrails) without forking
+//!   the client.
+//! - [`recorder`] — opt-in [`DebugRecorder`] capturing the exact (redacted)
+//!   request and response bodies per round span.
+//! - [`normalize`] — [`sanitize_messages()`] (user-message coalescing and
+//!   tool-call/result pairing repair) plus [`MessageNormalizer`] per-model
+//!   message massaging (system-role folding, strict alternation) and
+//!   OpenRouter prompt `transforms`, applied just before each request is sent.
+//! - [`pool`] — [`ConnectionPool`] capping concurrent in-flight requests per
+//!   provider, with queue wait metrics.
 //! - [`retry`] — transient error detection (429, 5xx, network timeouts) with
 //!   configurable exponential backoff and jitter. Never retries 400/401 errors.
 //! - [`streaming`] — SSE parser for incremental text, reasoning, and tool-call
 //!   deltas. Produces [`StreamEvent`](streaming::StreamEvent) values.
 //! - [`router`] — [`RoutingStrategy`] for per-round model selection. Use a
 //!   cheap model for early rounds and a powerful model for later rounds.
+//! - [`transport`] — [`HttpTransport`] trait the client sends requests
+//!   through; [`ReqwestTransport`](transport::ReqwestTransport) natively, an
+//!   embedder-supplied `fetch` transport on wasm32.
 //! - [`tracing`] — correlation IDs (`trace_id` / `span_id`), per-model pricing
-//!   tables, and cumulative [`CostTracker`] for spend monitoring.
+//!   tables, cumulative [`CostTracker`] for spend monitoring, and
+//!   [`PrefixAnalyzer`] for prompt cache prefix stability.
 
+pub mod interop;
+pub mod middleware;
+pub mod normalize;
+pub mod pool;
+pub mod recorder;
 pub mod retry;
 pub mod router;
 pub mod streaming;
 pub mod tracing;
+pub mod transport;
 
 // Re-export commonly used items at the module level.
+pub use interop::ChatFormat;
+pub use middleware::ClientMiddleware;
+pub use normalize::{
+    ConversationIssue, MessageNormalizer, SystemFold, sanitize_messages, validate_messages,
+};
+pub use pool::{ConnectionPool, PoolStats};
+pub use recorder::{DebugRecorder, RecordedExchange};
 pub use retry::RetryConfig;
 pub use router::RoutingStrategy;
-pub use tracing::{CostTracker, generate_span_id, generate_trace_id, pricing_for_model};
+pub use tracing::{
+    CacheStabilityReport, CostTracker, PrefixAnalyzer, PrefixBreak, PrefixBreakCause,
+    generate_span_id, generate_trace_id, pricing_for_model,
+};
+pub use transport::{HttpRequest, HttpResponse, HttpTransport};
@@ -0,0 +1,480 @@
+//! Per-model message normalization applied just before a request is sent.
+//!
+//! The harness builds conversations in the OpenAI chat shape: a leading
+//! system prompt, system reminders mid-conversation, and back-to-back user
+//! messages when reminders or notices are injected. Most models routed by
+//! OpenRouter accept this, but some chat templates reject it (strict
+//! user/assistant alternation, no system role at all) or benefit from
+//! OpenRouter prompt `transforms` such as `middle-out`.
+//!
+//! A [`MessageNormalizer`] describes those adjustments for one model family.
+//! [`MessageNormalizer::for_model()`] looks up the built-in table, matching
+//! on the model name segment like [`pricing_for_model()`](super::tracing::pricing_for_model).
+//! Unknown models get the no-op default.
+//!
+//! Independently of the model, [`sanitize_messages()`] runs first on every
+//! request: it coalesces consecutive user messages and repairs tool-call /
+//! tool-result pairing that eviction, compaction, or reminder injection can
+//! leave broken. Several providers reject such histories outright.
+
+use crate::{Message, MessageRole};
+
+/// Content of the placeholder result added for a tool call whose result is
+/// missing from the conversation.
+pub const MISSING_TOOL_RESULT: &str = "[Result unavailable: removed from context]";
+
+/// How system messages are rewritten for models with limited system-role
+/// support.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SystemFold {
+    /// Leave system messages as they are.
+    #[default]
+    Keep,
+    /// Keep the leading system prompt but turn later system messages (e.g.
+    /// injected reminders) into user messages.
+    NonLeading,
+    /// Turn every system message into user content. The leading system
+    /// prompt is prepended to the first user message.
+    All,
+}
+
+/// Message massaging and request transforms for one model family.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MessageNormalizer {
+    /// How system messages are folded into user messages.
+    pub system_fold: SystemFold,
+    /// Merge consecutive plain-text messages with the same role so the
+    /// conversation alternates strictly. Tool calls and tool results are
+    /// never merged.
+    pub merge_consecutive: bool,
+    /// OpenRouter prompt transforms to request (e.g. `"middle-out"`).
+    pub transforms: Vec<String>,
+}
+
+impl MessageNormalizer {
+    /// Look up the normalizer for a model by name.
+    pub fn for_model(model: &str) -> Self {
+        let name = model.rsplit('/').next().unwrap_or(model).to_lowercase();
+
+        if name.contains("gemma") {
+            // No system role, strict alternation, small context windows.
+            Self {
+                system_fold: SystemFold::All,
+                merge_consecutive: true,
+                transforms: vec!["middle-out".into()],
+            }
+        } else if name.contains("deepseek-r1") || name.contains("deepseek-reasoner") {
+            // Reasoning models that recommend putting instructions in the
+            // user turn.
+            Self {
+                system_fold: SystemFold::All,
+                merge_consecutive: true,
+                transforms: Vec::new(),
+            }
+        } else if name.contains("mistral") || name.contains("mixtral") || name.contains("codestral")
+        {
+            // A single leading system prompt and strict alternation.
+            Self {
+                system_fold: SystemFold::NonLeading,
+                merge_consecutive: true,
+                transforms: Vec::new(),
+            }
+        } else {
+            Self::default()
+        }
+    }
+
+    /// Whether this normalizer leaves messages unchanged.
+    pub fn is_noop(&self) -> bool {
+        self.system_fold == SystemFold::Keep && !self.merge_consecutive
+    }
+
+    /// The request's `transforms` field: `None` when there are none.
+    pub fn request_transforms(&self) -> Option<Vec<String>> {
+        (!self.transforms.is_empty()).then(|| self.transforms.clone())
+    }
+
+    /// Rewrite `messages` for this model family.
+    pub fn apply(&self, messages: Vec<Message>) -> Vec<Message> {
+        if self.is_noop() {
+            return messages;
+        }
+        let mut out: Vec<Message> = Vec::with_capacity(messages.len());
+        let mut leading_system: Option<String> = None;
+
+        for (i, msg) in messages.into_iter().enumerate() {
+            let msg = if msg.role == MessageRole::System {
+                match self.system_fold {
+                    SystemFold::Keep => msg,
+                    SystemFold::NonLeading if i == 0 => msg,
+                    SystemFold::NonLeading => as_user(msg),
+                    SystemFold::All if i == 0 => {
+                        leading_system = msg.content;
+                        continue;
+                    }
+                    SystemFold::All => as_user(msg),
+                }
+            } else {
+                msg
+            };
+
+            if self.merge_consecutive
+                && let Some(prev) = out.last_mut()
+                && mergeable(prev, &msg)
+            {
+                merge_into(prev, &msg);
+                continue;
+            }
+            out.push(msg);
+        }
+
+        if let Some(system) = leading_system {
+            match out.iter_mut().find(|m| m.role == MessageRole::User) {
+                Some(first_user) => {
+                    let rest = first_user.content.take().unwrap_or_default();
+                    first_user.content = Some(format!("{system}\n\n{rest}"));
+                }
+                None => out.insert(0, Message::user(system)),
+            }
+        }
+        out
+    }
+}
+
+/// A violated message-sequence invariant, found by [`validate_messages()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConversationIssue {
+    /// A tool result whose call is not in the preceding assistant message.
+    OrphanedToolResult { call_id: String },
+    /// A tool call with no result before the next assistant message.
+    MissingToolResult { call_id: String },
+    /// The conversation ends with an assistant tool-call message whose calls
+    /// never ran (e.g. the process stopped mid-round).
+    DanglingToolCalls,
+}
+
+impl std::fmt::Display for ConversationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OrphanedToolResult { call_id } => write!(f, "orphaned tool result {call_id}"),
+            Self::MissingToolResult { call_id } => write!(f, "missing result for {call_id}"),
+            Self::DanglingToolCalls => write!(f, "dangling tool calls at end"),
+        }
+    }
+}
+
+/// Check tool-call / tool-result pairing without modifying the messages.
+pub fn validate_messages(messages: &[Message]) -> Vec<ConversationIssue> {
+    let mut issues = Vec::new();
+    let mut open: Vec<&str> = Vec::new();
+    for msg in messages {
+        match msg.role {
+            MessageRole::Tool => {
+                let id = msg.tool_call_id.as_deref().unwrap_or("");
+                match open.iter().position(|open_id| *open_id == id) {
+                    Some(pos) => {
+                        open.remove(pos);
+                    }
+                    None => issues.push(ConversationIssue::OrphanedToolResult {
+                        call_id: id.to_string(),
+                    }),
+                }
+            }
+            MessageRole::Assistant => {
+                issues.extend(
+                    open.drain(..)
+                        .map(|id| ConversationIssue::MissingToolResult {
+                            call_id: id.to_string(),
+                        }),
+                );
+                open.extend(msg.tool_calls.iter().flatten().map(|c| c.id.as_str()));
+            }
+            MessageRole::User | MessageRole::System => {}
+        }
+    }
+    if messages
+        .last()
+        .is_some_and(|m| m.role == MessageRole::Assistant && m.tool_calls.is_some())
+    {
+        issues.push(ConversationIssue::DanglingToolCalls);
+    } else {
+        issues.extend(
+            open.into_iter()
+                .map(|id| ConversationIssue::MissingToolResult {
+                    call_id: id.to_string(),
+                }),
+        );
+    }
+    issues
+}
+
+/// Make a conversation acceptable to strict providers.
+///
+/// - Tool results must directly follow the assistant message that made the
+///   calls. Results with no matching call in that message (orphaned by
+///   compaction) or answering a call twice are dropped; calls with no result get a
+///   [`MISSING_TOOL_RESULT`] placeholder; user or system messages injected
+///   between a call and its results are moved after the results.
+/// - Consecutive plain user messages (reminders, injected prompts, and the
+///   actual user turn) are merged into one.
+pub fn sanitize_messages(messages: Vec<Message>) -> Vec<Message> {
+    let mut out: Vec<Message> = Vec::with_capacity(messages.len());
+    // Calls from the latest assistant message still awaiting a result, in
+    // call order.
+    let mut open: Vec<String> = Vec::new();
+    // Messages that arrived while results were still outstanding.
+    let mut deferred: Vec<Message> = Vec::new();
+
+    for msg in messages {
+        match msg.role {
+            MessageRole::Tool => {
+                let id = msg.tool_call_id.as_deref().unwrap_or("");
+                let Some(pos) = open.iter().position(|open_id| open_id == id) else {
+                    continue;
+                };
+                open.remove(pos);
+                out.push(msg);
+                if open.is_empty() {
+                    out.append(&mut deferred);
+                }
+            }
+            MessageRole::Assistant => {
+                close_open_calls(&mut out, &mut open, &mut deferred);
+                if let Some(ref calls) = msg.tool_calls {
+                    open = calls.iter().map(|c| c.id.clone()).collect();
+                }
+                out.push(msg);
+            }
+            MessageRole::User | MessageRole::System if !open.is_empty() => deferred.push(msg),
+            MessageRole::User | MessageRole::System => out.push(msg),
+        }
+    }
+    close_open_calls(&mut out, &mut open, &mut deferred);
+
+    let mut merged: Vec<Message> = Vec::with_capacity(out.len());
+    for msg in out {
+        if msg.role == MessageRole::User
+            && let Some(prev) = merged.last_mut()
+            && mergeable(prev, &msg)
+        {
+            merge_into(prev, &msg);
+            continue;
+        }
+        merged.push(msg);
+    }
+    merged
+}
+
+/// Add placeholder results for calls still open, then the deferred messages.
+fn close_open_calls(out: &mut Vec<Message>, open: &mut Vec<String>, deferred: &mut Vec<Message>) {
+    for id in open.drain(..) {
+        out.push(Message::tool_result(id, MISSING_TOOL_RESULT));
+    }
+    out.append(deferred);
+}
+
+/// Re-role a system message as a user message.
+fn as_user(msg: Message) -> Message {
+    Message {
+        role: MessageRole::User,
+        ..msg
+    }
+}
+
+/// Append `next`'s content to `prev`, keeping the later cache directive.
+fn merge_into(prev: &mut Message, next: &Message) {
+    let prev_content = prev.content.get_or_insert_with(String::new);
+    prev_content.push_str("\n\n");
+    prev_content.push_str(next.content.as_deref().unwrap_or(""));
+    if next.cache_control.is_some() {
+        prev.cache_control = next.cache_control.clone();
+    }
+}
+
+/// Whether `next` can be merged into `prev`: same role, plain text only.
+fn mergeable(prev: &Message, next: &Message) -> bool {
+    let plain = |m: &Message| {
+        m.tool_calls.is_none() && m.tool_call_id.is_none() && m.role != MessageRole::Tool
+    };
+    prev.role == next.role && plain(prev) && plain(next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversation() -> Vec<Message> {
+        vec![
+            Message::system("You are helpful."),
+            Message::user("Fix the bug."),
+            Message::system("[Reminder: run tests]"),
+            Message::assistant_text("Done."),
+            Message::user("Thanks."),
+            Message::user("One more thing."),
+        ]
+    }
+
+    fn roles(messages: &[Message]) -> Vec<String> {
+        messages.iter().map(|m| m.role.to_string()).collect()
+    }
+
+    fn call(id: &str) -> crate::ToolCall {
+        crate::ToolCall {
+            id: id.into(),
+            call_type: crate::CallType::Function,
+            function: crate::FunctionCallData {
+                name: "read_file".into(),
+                arguments: "{}".into(),
+            },
+        }
+    }
+
+    #[test]
+    fn sanitize_merges_consecutive_user_messages() {
+        let out = sanitize_messages(vec![
+            Message::system("sys"),
+            Message::user("[Reminder]"),
+            Message::user("Do the thing."),
+        ]);
+        assert_eq!(roles(&out), ["system", "user"].map(String::from));
+        assert_eq!(
+            out[1].content.as_deref(),
+            Some("[Reminder]\n\nDo the thing.")
+        );
+    }
+
+    #[test]
+    fn sanitize_repairs_tool_pairing() {
+        let out = sanitize_messages(vec![
+            Message::user("task"),
+            // Orphaned by compaction: its call is gone.
+            Message::tool_result("gone", "old result"),
+            Message::assistant_tool_calls(vec![call("a"), call("b")]),
+            // Injected between the call and its results.
+            Message::user("[Reminder]"),
+            Message::tool_result("a", "A"),
+            Message::tool_result("a", "duplicate"),
+            Message::assistant_tool_calls(vec![call("c")]),
+        ]);
+        assert_eq!(
+            roles(&out),
+            [
+                "user",
+                "assistant",
+                "tool",
+                "tool",
+                "user",
+                "assistant",
+                "tool"
+            ]
+            .map(String::from)
+        );
+        assert_eq!(out[2].content.as_deref(), Some("A"));
+        assert_eq!(out[3].tool_call_id.as_deref(), Some("b"));
+        assert_eq!(out[3].content.as_deref(), Some(MISSING_TOOL_RESULT));
+        assert_eq!(out[4].content.as_deref(), Some("[Reminder]"));
+        assert_eq!(out[6].tool_call_id.as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn sanitize_matches_reused_call_ids_per_turn() {
+        // Some providers number calls per response, so ids repeat.
+        let messages = vec![
+            Message::user("task"),
+            Message::assistant_tool_calls(vec![call("call_0")]),
+            Message::tool_result("call_0", "first"),
+            Message::assistant_tool_calls(vec![call("call_0")]),
+            Message::tool_result("call_0", "second"),
+            Message::assistant_text("done"),
+        ];
+        let out = sanitize_messages(messages);
+        assert_eq!(out.len(), 6);
+        assert_eq!(out[2].content.as_deref(), Some("first"));
+        assert_eq!(out[4].content.as_deref(), Some("second"));
+    }
+
+    #[test]
+    fn sanitize_keeps_valid_conversations() {
+        let messages = vec![
+            Message::system("sys"),
+            Message::user("task"),
+            Message::assistant_tool_calls(vec![call("a")]),
+            Message::tool_result("a", "A"),
+            Message::assistant_text("done"),
+        ];
+        assert_eq!(sanitize_messages(messages).len(), 5);
+    }
+
+    #[test]
+    fn validate_reports_pairing_issues() {
+        let issues = validate_messages(&[
+            Message::user("task"),
+            Message::tool_result("gone", "old"),
+            Message::assistant_tool_calls(vec![call("a")]),
+            Message::assistant_tool_calls(vec![call("b")]),
+        ]);
+        assert_eq!(
+            issues,
+            vec![
+                ConversationIssue::OrphanedToolResult {
+                    call_id: "gone".into()
+                },
+                ConversationIssue::MissingToolResult {
+                    call_id: "a".into()
+                },
+                ConversationIssue::DanglingToolCalls,
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_models_are_untouched() {
+        let n = MessageNormalizer::for_model("anthropic/claude-sonnet-4");
+        assert!(n.is_noop());
+        assert_eq!(n.request_transforms(), None);
+        assert_eq!(n.apply(conversation()).len(), 6);
+    }
+
+    #[test]
+    fn mistral_folds_reminders_and_merges_turns() {
+        let n = MessageNormalizer::for_model("mistralai/mistral-large");
+        let out = n.apply(conversation());
+        assert_eq!(
+            roles(&out),
+            ["system", "user", "assistant", "user"].map(String::from)
+        );
+        assert_eq!(
+            out[1].content.as_deref(),
+            Some("Fix the bug.\n\n[Reminder: run tests]")
+        );
+        assert_eq!(
+            out[3].content.as_deref(),
+            Some("Thanks.\n\nOne more thing.")
+        );
+    }
+
+    #[test]
+    fn gemma_folds_the_system_prompt_into_the_first_user_message() {
+        let n = MessageNormalizer::for_model("google/gemma-3-27b-it");
+        assert_eq!(n.request_transforms(), Some(vec!["middle-out".into()]));
+        let out = n.apply(conversation());
+        assert_eq!(roles(&out), ["user", "assistant", "user"].map(String::from));
+        assert!(
+            out[0]
+                .content
+                .as_deref()
+                .unwrap()
+                .starts_with("You are helpful.\n\nFix the bug.")
+        );
+    }
+
+    #[test]
+    fn tool_messages_are_never_merged() {
+        let n = MessageNormalizer::for_model("mistralai/codestral");
+        let out = n.apply(vec![
+            Message::tool_result("a", "one"),
+            Message::tool_result("b", "two"),
+        ]);
+        assert_eq!(out.len(), 2);
+    }
+}
@@ -0,0 +1,242 @@
+//! Per-provider cap on concurrent in-flight API requests.
+//!
+//! Parallel sub-agents and concurrent helper calls can fan out many requests
+//! at once. A [`ConnectionPool`] attached to the client with
+//! [`OpenRouterClient::with_pool()`](crate::OpenRouterClient::with_pool)
+//! limits how many requests per provider are in flight; excess requests wait
+//! in FIFO order for a slot. The provider is the model id's prefix
+//! (`"anthropic"` for `"anthropic/claude-sonnet-4"`).
+//!
+//! Queue wait times are recorded per provider and exposed through
+//! [`ConnectionPool::stats()`].
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::debug;
+
+use crate::platform::Instant;
+
+/// Default in-flight request cap per provider.
+pub const DEFAULT_MAX_IN_FLIGHT: usize = 8;
+
+/// Provider segment of a model id (`"openai"` for `"openai/gpt-4o"`).
+/// Ids without a `/` are their own provider.
+pub fn provider_for_model(model: &str) -> &str {
+    model
+        .split_once('/')
+        .map_or(model, |(provider, _)| provider)
+}
+
+/// Queue and throughput counters for one provider.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PoolStats {
+    /// Requests that acquired a slot.
+    pub requests: u64,
+    /// Requests that had to wait because the provider was at its cap.
+    pub queued: u64,
+    /// Total time spent waiting for a slot.
+    pub total_wait: Duration,
+    /// Longest single wait.
+    pub max_wait: Duration,
+    /// Requests currently holding a slot.
+    pub in_flight: usize,
+    /// Requests currently waiting for a slot.
+    pub waiting: usize,
+}
+
+impl PoolStats {
+    /// Mean wait per request (zero when there were no requests).
+    pub fn mean_wait(&self) -> Duration {
+        if self.requests == 0 {
+            Duration::ZERO
+        } else {
+            self.total_wait / self.requests as u32
+        }
+    }
+}
+
+struct ProviderSlot {
+    semaphore: Arc<Semaphore>,
+    stats: PoolStats,
+}
+
+/// Caps concurrent in-flight requests per provider and queues the rest.
+pub struct ConnectionPool {
+    default_max: usize,
+    limits: HashMap<String, usize>,
+    providers: Mutex<HashMap<String, ProviderSlot>>,
+}
+
+impl ConnectionPool {
+    /// Create a pool allowing `max_in_flight` concurrent requests per
+    /// provider (at least 1).
+    pub fn new(max_in_flight: usize) -> Self {
+        Self {
+            default_max: max_in_flight.max(1),
+            limits: HashMap::new(),
+            providers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Override the cap for one provider (e.g. a tighter limit for a
+    /// provider with low rate limits).
+    pub fn with_provider_limit(mut self, provider: impl Into<String>, max: usize) -> Self {
+        self.limits.insert(provider.into(), max.max(1));
+        self
+    }
+
+    /// The cap that applies to `provider`.
+    pub fn limit_for(&self, provider: &str) -> usize {
+        self.limits
+            .get(provider)
+            .copied()
+            .unwrap_or(self.default_max)
+    }
+
+    /// Wait for an in-flight slot for `model`'s provider. The slot is
+    /// released when the returned permit is dropped.
+    pub async fn acquire(self: &Arc<Self>, model: &str) -> PoolPermit {
+        let provider = provider_for_model(model).to_string();
+        let semaphore = {
+            let mut providers = self.providers.lock().unwrap();
+            let slot = providers
+                .entry(provider.clone())
+                .or_insert_with(|| ProviderSlot {
+                    semaphore: Arc::new(Semaphore::new(self.limit_for(&provider))),
+                    stats: PoolStats::default(),
+                });
+            slot.stats.waiting += 1;
+            slot.semaphore.clone()
+        };
+
+        let start = Instant::now();
+        let queued = semaphore.available_permits() == 0;
+        let permit = semaphore
+            .acquire_owned()
+            .await
+            .expect("pool semaphore is never closed");
+        let wait = start.elapsed();
+
+        {
+            let mut providers = self.providers.lock().unwrap();
+            if let Some(slot) = providers.get_mut(&provider) {
+                let stats = &mut slot.stats;
+                stats.waiting -= 1;
+                stats.in_flight += 1;
+                stats.requests += 1;
+                stats.total_wait += wait;
+                stats.max_wait = stats.max_wait.max(wait);
+                if queued {
+                    stats.queued += 1;
+                }
+            }
+        }
+        if queued {
+            debug!(
+                "Pool: {provider} request waited {:.0}ms for a slot",
+                wait.as_secs_f64() * 1000.0
+            );
+        }
+
+        PoolPermit {
+            pool: self.clone(),
+            provider,
+            _permit: permit,
+        }
+    }
+
+    /// Stats for one provider, if it has seen any requests.
+    pub fn provider_stats(&self, provider: &str) -> Option<PoolStats> {
+        let providers = self.providers.lock().unwrap();
+        providers.get(provider).map(|slot| slot.stats.clone())
+    }
+
+    /// Stats for every provider seen so far, sorted by provider name.
+    pub fn stats(&self) -> Vec<(String, PoolStats)> {
+        let providers = self.providers.lock().unwrap();
+        let mut out: Vec<(String, PoolStats)> = providers
+            .iter()
+            .map(|(name, slot)| (name.clone(), slot.stats.clone()))
+            .collect();
+        out.sort_by(|a, b| a.0.cmp(&b.0));
+        out
+    }
+}
+
+impl Default for ConnectionPool {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_IN_FLIGHT)
+    }
+}
+
+/// An in-flight slot held for the duration of one request.
+pub struct PoolPermit {
+    pool: Arc<ConnectionPool>,
+    provider: String,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl PoolPermit {
+    /// Provider this slot belongs to.
+    pub fn provider(&self) -> &str {
+        &self.provider
+    }
+}
+
+impl Drop for PoolPermit {
+    fn drop(&mut self) {
+        let mut providers = self.pool.providers.lock().unwrap();
+        if let Some(slot) = providers.get_mut(&self.provider) {
+            slot.stats.in_flight = slot.stats.in_flight.saturating_sub(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn provider_is_model_prefix() {
+        assert_eq!(provider_for_model("anthropic/claude-sonnet-4"), "anthropic");
+        assert_eq!(provider_for_model("local-model"), "local-model");
+    }
+
+    #[tokio::test]
+    async fn caps_in_flight_per_provider() {
+        let pool = Arc::new(ConnectionPool::new(1).with_provider_limit("openai", 2));
+        let a = pool.acquire("anthropic/claude").await;
+        let _o1 = pool.acquire("openai/gpt-4o").await;
+        let _o2 = pool.acquire("openai/gpt-4o-mini").await;
+        assert_eq!(pool.provider_stats("openai").unwrap().in_flight, 2);
+
+        let waiter = {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                let _b = pool.acquire("anthropic/claude-haiku").await;
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        let stats = pool.provider_stats("anthropic").unwrap();
+        assert_eq!((stats.in_flight, stats.waiting), (1, 1));
+
+        drop(a);
+        waiter.await.unwrap();
+        let stats = pool.provider_stats("anthropic").unwrap();
+        assert_eq!(stats.requests, 2);
+        assert_eq!(stats.queued, 1);
+        assert_eq!(stats.in_flight, 0);
+        assert!(stats.max_wait >= Duration::from_millis(20));
+        assert!(stats.mean_wait() > Duration::ZERO);
+    }
+
+    #[test]
+    fn default_pool_uses_default_limit() {
+        let pool = ConnectionPool::default();
+        assert!(pool.stats().is_empty());
+        assert_eq!(pool.limit_for("anyone"), DEFAULT_MAX_IN_FLIGHT);
+    }
+}
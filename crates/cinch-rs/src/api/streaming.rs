@@ -3,7 +3,15 @@
 //! Provides [`StreamEvent`] and the [`OpenRouterClient::chat_stream`] method
 //! for receiving incremental text and reasoning deltas from the LLM. This
 //! allows the harness (or TUI) to display output as it arrives rather than
-//! waiting for the full response.
+//! waiting for the full response. Streaming requests ask for a final usage
+//! chunk, surfaced as [`StreamEvent::Usage`].
+//!
+//! The SSE decoder tolerates what real providers send: events split across
+//! (or packed into) network chunks, CRLF framing, `: keep-alive` comments,
+//! `data:` without a space, and `[DONE]` sentinel variants. A connection
+//! that drops mid-generation ends the stream with
+//! [`StreamEvent::Interrupted`] instead of an error, so the caller can keep
+//! the partial response and resume.
 
 use crate::{ChatRequest, OPENROUTER_URL, OpenRouterClient, UsageInfo};
 use serde::Deserialize;
@@ -25,10 +33,17 @@ pub enum StreamEvent {
     },
     /// Token usage information (sent in the final chunk).
     Usage(UsageInfo),
+    /// Why generation stopped (`"stop"`, `"length"`, `"tool_calls"`, ...).
+    Finish(String),
+    /// Provider that served the request, from the first chunk naming it.
+    Provider(String),
     /// The stream is complete.
     Done,
-    /// An error occurred during streaming.
+    /// The provider reported an error in-band, mid-stream.
     Error(String),
+    /// The connection dropped (or closed early) after the stream started.
+    /// The events before it are the partial response.
+    Interrupted(String),
 }
 
 /// Raw SSE data chunk from the OpenRouter API.
@@ -36,6 +51,16 @@ pub enum StreamEvent {
 struct StreamChunk {
     choices: Option<Vec<StreamChoice>>,
     usage: Option<UsageInfo>,
+    error: Option<StreamError>,
+    #[serde(default)]
+    provider: Option<String>,
+}
+
+/// In-band error payload (`{"error": {"code": 502, "message": "..."}}`).
+#[derive(Deserialize, Debug)]
+struct StreamError {
+    code: Option<serde_json::Value>,
+    message: Option<String>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -75,79 +100,7 @@ impl OpenRouterClient {
     /// This is the foundation for incremental TUI updates and mid-stream
     /// cancellation.
     pub async fn chat_stream(&self, body: &ChatRequest) -> Result<Vec<StreamEvent>, String> {
-        let mut stream_body =
-            serde_json::to_value(body).map_err(|e| format!("failed to serialize request: {e}"))?;
-        stream_body["stream"] = serde_json::Value::Bool(true);
-
-        debug!("Sending streaming chat request");
-
-        let mut resp = self
-            .client
-            .post(OPENROUTER_URL)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("HTTP-Referer", &self.referer)
-            .header("X-Title", &self.title)
-            .json(&stream_body)
-            .send()
-            .await
-            .map_err(|e| format!("streaming request failed: {e}"))?;
-
-        if !resp.status().is_success() {
-            let status = resp.status();
-            let text = resp.text().await.unwrap_or_default();
-            return Err(format!("OpenRouter API HTTP {status}: {text}"));
-        }
-
-        // Read the SSE stream incrementally via chunk() so long responses
-        // (e.g. file-write tool calls) don't hit a single-body timeout.
-        let mut events = Vec::new();
-        let mut buffer = String::new();
-
-        while let Some(chunk) = resp
-            .chunk()
-            .await
-            .map_err(|e| format!("failed to read streaming chunk: {e}"))?
-        {
-            buffer.push_str(&String::from_utf8_lossy(&chunk));
-
-            // Process all complete lines in the buffer.
-            while let Some(newline_pos) = buffer.find('\n') {
-                let line: String = buffer.drain(..=newline_pos).collect();
-                let line = line.trim();
-                if line.is_empty() || line.starts_with(':') {
-                    continue;
-                }
-                if line == "data: [DONE]" {
-                    events.push(StreamEvent::Done);
-                    break;
-                }
-                if let Some(data) = line.strip_prefix("data: ") {
-                    parse_sse_data(data, &mut events);
-                }
-            }
-
-            // If we already received Done, stop reading.
-            if events.iter().any(|e| matches!(e, StreamEvent::Done)) {
-                break;
-            }
-        }
-
-        // Process any remaining data in the buffer (incomplete final line).
-        let remaining = buffer.trim();
-        if !remaining.is_empty()
-            && remaining != "data: [DONE]"
-            && let Some(data) = remaining.strip_prefix("data: ")
-        {
-            parse_sse_data(data, &mut events);
-        }
-
-        // Ensure Done event at the end.
-        if !events.iter().any(|e| matches!(e, StreamEvent::Done)) {
-            events.push(StreamEvent::Done);
-        }
-
-        debug!("Stream completed with {} events", events.len());
-        Ok(events)
+        self.chat_stream_live(body, |_| true).await
     }
 
     /// Send a streaming chat request, invoking `on_event` for each event as
@@ -157,119 +110,239 @@ impl OpenRouterClient {
     /// and reasoning deltas while tool-call argument fragments are still
     /// being received. The full event list is also returned for post-hoc
     /// assembly of tool calls, usage, etc.
+    ///
+    /// Once the first event has arrived, a dropped connection no longer
+    /// fails the call: the events so far are returned, followed by
+    /// [`StreamEvent::Interrupted`].
     pub async fn chat_stream_live(
         &self,
         body: &ChatRequest,
         mut on_event: impl FnMut(&StreamEvent) -> bool,
     ) -> Result<Vec<StreamEvent>, String> {
-        let mut stream_body =
-            serde_json::to_value(body).map_err(|e| format!("failed to serialize request: {e}"))?;
-        stream_body["stream"] = serde_json::Value::Bool(true);
-
-        debug!("Sending live streaming chat request");
-
-        let mut resp = self
-            .client
-            .post(OPENROUTER_URL)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("HTTP-Referer", &self.referer)
-            .header("X-Title", &self.title)
-            .json(&stream_body)
-            .send()
-            .await
-            .map_err(|e| format!("streaming request failed: {e}"))?;
-
-        if !resp.status().is_success() {
-            let status = resp.status();
+        let prepared = self.prepare_request(body)?;
+        let body: &ChatRequest = &prepared;
+        let stream_body = streaming_body(body)?;
+
+        debug!("Sending streaming chat request");
+
+        let _slot = self.acquire_slot(body).await;
+        let mut resp = match self.post(OPENROUTER_URL, &stream_body).await {
+            Ok(resp) => resp,
+            Err(e) => {
+                let error = format!("streaming request failed: {e}");
+                self.record_exchange(body, &stream_body, None, &error);
+                return Err(error);
+            }
+        };
+
+        let status = resp.status;
+        if !resp.is_success() {
             let text = resp.text().await.unwrap_or_default();
+            self.record_exchange(body, &stream_body, Some(status), &text);
             return Err(format!("OpenRouter API HTTP {status}: {text}"));
         }
+        // Raw stream text, kept only when recording.
+        let mut raw = self.recorder.as_ref().map(|_| String::new());
 
+        // Read the SSE stream incrementally via chunk() so long responses
+        // (e.g. file-write tool calls) don't hit a single-body timeout.
+        let mut parser = SseParser::default();
         let mut events = Vec::new();
-        let mut buffer = String::new();
-        let mut done = false;
+        let mut interrupted = None;
         let mut cancelled = false;
 
-        while let Some(chunk) = resp
-            .chunk()
-            .await
-            .map_err(|e| format!("failed to read streaming chunk: {e}"))?
-        {
-            buffer.push_str(&String::from_utf8_lossy(&chunk));
-
-            while let Some(newline_pos) = buffer.find('\n') {
-                let line: String = buffer.drain(..=newline_pos).collect();
-                let line = line.trim();
-                if line.is_empty() || line.starts_with(':') {
-                    continue;
-                }
-                if line == "data: [DONE]" {
-                    let ev = StreamEvent::Done;
-                    on_event(&ev);
-                    events.push(ev);
-                    done = true;
-                    break;
-                }
-                if let Some(data) = line.strip_prefix("data: ") {
-                    let before = events.len();
-                    parse_sse_data(data, &mut events);
-                    // Emit newly parsed events to the callback.
-                    // If the callback returns false, cancel the stream.
-                    for ev in &events[before..] {
-                        if !on_event(ev) {
-                            cancelled = true;
-                            break;
+        loop {
+            let chunk = match resp.chunk().await {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => break,
+                Err(e) => {
+                    let error = format!("failed to read streaming chunk: {e}");
+                    if events.is_empty() {
+                        // Nothing received yet: fail so the request is retried.
+                        if let Some(raw) = raw {
+                            self.record_exchange(body, &stream_body, Some(status), &raw);
                         }
+                        return Err(error);
                     }
-                    if cancelled {
-                        break;
-                    }
+                    interrupted = Some(error);
+                    break;
                 }
+            };
+            if let Some(ref mut raw) = raw {
+                raw.push_str(&String::from_utf8_lossy(&chunk));
             }
-
-            if done || cancelled {
+            let before = events.len();
+            parser.push(&chunk, &mut events);
+            // If the callback returns false, cancel the stream.
+            if !emit(&events[before..], &mut on_event) {
+                cancelled = true;
+                break;
+            }
+            if parser.done {
                 break;
             }
         }
 
         if cancelled {
             debug!("Stream cancelled by callback");
-            if !events.iter().any(|e| matches!(e, StreamEvent::Done)) {
-                events.push(StreamEvent::Done);
-            }
-            return Ok(events);
-        }
-
-        // Process any remaining data in the buffer.
-        let remaining = buffer.trim();
-        if !remaining.is_empty()
-            && remaining != "data: [DONE]"
-            && let Some(data) = remaining.strip_prefix("data: ")
-        {
+        } else {
             let before = events.len();
-            parse_sse_data(data, &mut events);
-            for ev in &events[before..] {
-                if !on_event(ev) {
-                    break;
-                }
+            parser.finish(&mut events);
+            let ended_early = !parser.done
+                && !events.is_empty()
+                && !events.iter().any(|e| matches!(e, StreamEvent::Finish(_)));
+            if interrupted.is_none() && ended_early {
+                interrupted = Some("stream closed before the response finished".into());
+            }
+            if let Some(error) = interrupted {
+                warn!("Stream interrupted: {error}");
+                events.push(StreamEvent::Interrupted(error));
             }
+            emit(&events[before..], &mut on_event);
         }
 
+        // Ensure Done event at the end.
         if !events.iter().any(|e| matches!(e, StreamEvent::Done)) {
             let ev = StreamEvent::Done;
-            on_event(&ev);
+            if !cancelled {
+                on_event(&ev);
+            }
             events.push(ev);
         }
 
-        debug!("Live stream completed with {} events", events.len());
-        Ok(events)
+        debug!("Stream completed with {} events", events.len());
+        if let Some(raw) = raw {
+            self.record_exchange(body, &stream_body, Some(status), &raw);
+        }
+        self.finish_stream(body, events)
+    }
+}
+
+/// Pass `events` to `on_event` in order, stopping as soon as it returns
+/// `false`. Returns whether every event was accepted.
+fn emit(events: &[StreamEvent], on_event: &mut impl FnMut(&StreamEvent) -> bool) -> bool {
+    events.iter().all(on_event)
+}
+
+/// Incremental SSE decoder.
+///
+/// Buffers raw bytes so lines (and UTF-8 characters) split across network
+/// chunks are reassembled, and dispatches a `data:` payload as soon as it
+/// is complete JSON, so providers that omit the blank line between events
+/// still parse. Multi-line `data:` fields are joined per the SSE spec.
+#[derive(Default)]
+struct SseParser {
+    /// Bytes after the last complete line.
+    buffer: Vec<u8>,
+    /// `data:` lines of the event being assembled.
+    data: String,
+    /// Whether the `[DONE]` sentinel has been seen.
+    done: bool,
+}
+
+impl SseParser {
+    /// Feed a network chunk, appending any completed events.
+    fn push(&mut self, chunk: &[u8], events: &mut Vec<StreamEvent>) {
+        self.buffer.extend_from_slice(chunk);
+        let mut start = 0;
+        while !self.done
+            && let Some(len) = self.buffer[start..].iter().position(|&b| b == b'\n')
+        {
+            let line = String::from_utf8_lossy(&self.buffer[start..start + len]).into_owned();
+            start += len + 1;
+            self.line(line.strip_suffix('\r').unwrap_or(&line), events);
+        }
+        self.buffer.drain(..start);
+    }
+
+    /// Flush a final line and event left without a terminating newline.
+    fn finish(&mut self, events: &mut Vec<StreamEvent>) {
+        let rest = std::mem::take(&mut self.buffer);
+        let line = String::from_utf8_lossy(&rest);
+        self.line(line.trim_end_matches('\r'), events);
+        self.dispatch(events);
+    }
+
+    fn line(&mut self, line: &str, events: &mut Vec<StreamEvent>) {
+        if self.done {
+            return;
+        }
+        if line.is_empty() {
+            self.dispatch(events);
+            return;
+        }
+        if line.starts_with(':') {
+            trace!("SSE comment: {line}");
+            return;
+        }
+        // `event:`, `id:` and `retry:` carry nothing the client needs.
+        if let Some(value) = line.strip_prefix("data:") {
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            if !self.data.is_empty() {
+                self.data.push('\n');
+            }
+            self.data.push_str(value);
+            if is_complete_payload(&self.data) {
+                self.dispatch(events);
+            }
+        }
+    }
+
+    /// Turn the pending `data:` payload into events.
+    fn dispatch(&mut self, events: &mut Vec<StreamEvent>) {
+        let data = std::mem::take(&mut self.data);
+        let data = data.trim();
+        if data.is_empty() {
+            return;
+        }
+        if is_done_sentinel(data) {
+            self.done = true;
+            events.push(StreamEvent::Done);
+        } else {
+            parse_sse_data(data, events);
+        }
     }
 }
 
+/// `[DONE]`, allowing for case and surrounding whitespace.
+fn is_done_sentinel(data: &str) -> bool {
+    data.trim().eq_ignore_ascii_case("[DONE]")
+}
+
+/// Whether a (possibly partial, multi-line) `data:` payload is complete.
+fn is_complete_payload(data: &str) -> bool {
+    is_done_sentinel(data) || serde_json::from_str::<serde::de::IgnoredAny>(data).is_ok()
+}
+
+/// Serialize a request for streaming. Asks for a final usage chunk
+/// (`stream_options.include_usage`) so streamed rounds are accounted like
+/// non-streamed ones.
+fn streaming_body(body: &ChatRequest) -> Result<serde_json::Value, String> {
+    let mut stream_body =
+        serde_json::to_value(body).map_err(|e| format!("failed to serialize request: {e}"))?;
+    stream_body["stream"] = serde_json::Value::Bool(true);
+    stream_body["stream_options"] = serde_json::json!({ "include_usage": true });
+    Ok(stream_body)
+}
+
 /// Parse a single SSE `data:` payload into stream events.
 fn parse_sse_data(data: &str, events: &mut Vec<StreamEvent>) {
     match serde_json::from_str::<StreamChunk>(data) {
         Ok(chunk) => {
+            if let Some(error) = chunk.error {
+                let code = error.code.map(|c| format!(" ({c})")).unwrap_or_default();
+                let message = error.message.unwrap_or_else(|| "unknown error".into());
+                warn!("Provider error mid-stream{code}: {message}");
+                events.push(StreamEvent::Error(format!(
+                    "provider error mid-stream{code}: {message}"
+                )));
+            }
+            // Every chunk repeats the provider; keep the first.
+            if let Some(provider) = chunk.provider
+                && !events.iter().any(|e| matches!(e, StreamEvent::Provider(_)))
+            {
+                events.push(StreamEvent::Provider(provider));
+            }
             // Emit usage if present.
             if let Some(usage) = chunk.usage {
                 events.push(StreamEvent::Usage(usage));
@@ -307,9 +380,9 @@ fn parse_sse_data(data: &str, events: &mut Vec<StreamEvent>) {
                             }
                         }
                     }
-                    // If finish_reason is set, mark as done.
-                    if choice.finish_reason.is_some() {
-                        trace!("Stream finish_reason: {:?}", choice.finish_reason);
+                    if let Some(reason) = choice.finish_reason {
+                        trace!("Stream finish_reason: {reason}");
+                        events.push(StreamEvent::Finish(reason));
                     }
                 }
             }
@@ -352,6 +425,22 @@ pub fn extract_usage(events: &[StreamEvent]) -> Option<UsageInfo> {
     None
 }
 
+/// Extract the provider that served the stream (if reported).
+pub fn extract_provider(events: &[StreamEvent]) -> Option<String> {
+    events.iter().find_map(|event| match event {
+        StreamEvent::Provider(provider) => Some(provider.clone()),
+        _ => None,
+    })
+}
+
+/// Extract the finish reason from stream events (if present).
+pub fn extract_finish_reason(events: &[StreamEvent]) -> Option<String> {
+    events.iter().rev().find_map(|event| match event {
+        StreamEvent::Finish(reason) => Some(reason.clone()),
+        _ => None,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -397,4 +486,148 @@ mod tests {
         let events = vec![StreamEvent::TextDelta("hi".into()), StreamEvent::Done];
         assert!(extract_usage(&events).is_none());
     }
+
+    #[test]
+    fn provider_is_parsed_once_from_chunks() {
+        let mut events = Vec::new();
+        for _ in 0..2 {
+            parse_sse_data(
+                r#"{"provider":"Anthropic","choices":[{"delta":{"content":"hi"}}]}"#,
+                &mut events,
+            );
+        }
+        assert_eq!(extract_provider(&events).as_deref(), Some("Anthropic"));
+        let providers = events
+            .iter()
+            .filter(|e| matches!(e, StreamEvent::Provider(_)))
+            .count();
+        assert_eq!(providers, 1);
+    }
+
+    #[test]
+    fn finish_reason_is_parsed_from_chunks() {
+        let mut events = Vec::new();
+        parse_sse_data(
+            r#"{"choices":[{"delta":{"content":"cut"},"finish_reason":null}]}"#,
+            &mut events,
+        );
+        assert_eq!(extract_finish_reason(&events), None);
+        parse_sse_data(
+            r#"{"choices":[{"delta":{},"finish_reason":"length"}]}"#,
+            &mut events,
+        );
+        assert_eq!(extract_finish_reason(&events).as_deref(), Some("length"));
+    }
+
+    #[test]
+    fn streaming_requests_include_usage() {
+        let body = streaming_body(&ChatRequest::default()).unwrap();
+        assert_eq!(body["stream"], true);
+        assert_eq!(body["stream_options"]["include_usage"], true);
+
+        // The usage chunk arrives with no choices.
+        let mut events = Vec::new();
+        parse_sse_data(
+            r#"{"choices":[],"usage":{"prompt_tokens":12,"completion_tokens":3}}"#,
+            &mut events,
+        );
+        let usage = extract_usage(&events).unwrap();
+        assert_eq!(usage.completion_tokens, Some(3));
+    }
+
+    fn parse_chunks(chunks: &[&[u8]]) -> Vec<StreamEvent> {
+        let mut parser = SseParser::default();
+        let mut events = Vec::new();
+        for chunk in chunks {
+            parser.push(chunk, &mut events);
+        }
+        parser.finish(&mut events);
+        events
+    }
+
+    #[test]
+    fn parser_reassembles_split_lines_and_characters() {
+        let stream = "data: {\"choices\":[{\"delta\":{\"content\":\"héllo\"}}]}\n\n".as_bytes();
+        // Split inside the JSON and inside the two-byte `é`.
+        let split = stream.iter().position(|&b| b == 0xC3).unwrap() + 1;
+        let events = parse_chunks(&[&stream[..10], &stream[10..split], &stream[split..]]);
+        assert_eq!(collect_text(&events), "héllo");
+    }
+
+    #[test]
+    fn parser_tolerates_framing_variations() {
+        let stream = concat!(
+            ": OPENROUTER PROCESSING\r\n\r\n",
+            "event: message\r\n",
+            "data:{\"choices\":[{\"delta\":{\"content\":\"a\"}}]}\r\n",
+            // No blank line between events.
+            "data: {\"choices\":[{\"delta\":{\"content\":\"b\"}}]}\n",
+            // A payload split over two data lines.
+            "data: {\"choices\":[{\"delta\":\n",
+            "data: {\"content\":\"c\"}}]}\n\n",
+            "data: [done] \n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"after done\"}}]}\n\n",
+        );
+        let events = parse_chunks(&[stream.as_bytes()]);
+        assert_eq!(collect_text(&events), "abc");
+        assert!(matches!(events.last(), Some(StreamEvent::Done)));
+    }
+
+    #[test]
+    fn in_band_errors_become_error_events() {
+        let events = parse_chunks(&[
+            b"data: {\"error\":{\"code\":502,\"message\":\"Provider disconnected\"}}\n\n",
+        ]);
+        assert!(
+            matches!(&events[..], [StreamEvent::Error(e)] if e.contains("(502): Provider disconnected"))
+        );
+    }
+
+    type Chunks = Vec<Result<Vec<u8>, String>>;
+
+    /// Transport answering with a body delivered as the given chunks.
+    struct Chunked(std::sync::Mutex<Option<Chunks>>);
+
+    impl crate::api::transport::HttpTransport for Chunked {
+        fn send(
+            &self,
+            _request: crate::api::transport::HttpRequest,
+        ) -> crate::api::transport::TransportFuture<'_> {
+            let chunks = self.0.lock().unwrap().take().unwrap();
+            let body = Box::pin(futures::stream::iter(chunks));
+            Box::pin(async move { Ok(crate::api::transport::HttpResponse::new(200, body)) })
+        }
+    }
+
+    fn chunked_client(chunks: Chunks) -> OpenRouterClient {
+        OpenRouterClient::with_transport("key", Chunked(std::sync::Mutex::new(Some(chunks))))
+    }
+
+    #[tokio::test]
+    async fn dropped_connection_returns_partial_stream() {
+        let client = chunked_client(vec![
+            Ok(b"data: {\"choices\":[{\"delta\":{\"content\":\"Hel\"}}]}\n\n".to_vec()),
+            Err("connection reset by peer".into()),
+        ]);
+        let events = client.chat_stream(&ChatRequest::default()).await.unwrap();
+        assert_eq!(collect_text(&events), "Hel");
+        assert!(
+            matches!(&events[..], [_, StreamEvent::Interrupted(e), StreamEvent::Done] if e.contains("connection reset"))
+        );
+
+        // Closing early without a finish reason or [DONE] is an interruption too.
+        let client = chunked_client(vec![Ok(
+            b"data: {\"choices\":[{\"delta\":{\"content\":\"Hel\"}}]}\n\n".to_vec(),
+        )]);
+        let events = client.chat_stream(&ChatRequest::default()).await.unwrap();
+        assert!(
+            events
+                .iter()
+                .any(|e| matches!(e, StreamEvent::Interrupted(_)))
+        );
+
+        // A failure before any event still fails the call, so it is retried.
+        let client = chunked_client(vec![Err("connection reset by peer".into())]);
+        assert!(client.chat_stream(&ChatRequest::default()).await.is_err());
+    }
 }
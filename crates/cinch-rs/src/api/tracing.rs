@@ -1,15 +1,27 @@
-//! Correlation IDs and cost tracking for agent runs.
+//! Correlation IDs, cost tracking, and prompt cache analysis for agent runs.
 //!
 //! Assigns a unique `trace_id` to each harness run and a `span_id` to each
 //! round within it. Tracks cumulative token usage and estimated cost.
+//!
+//! [`PrefixAnalyzer`] fingerprints each round's request (model, tool
+//! definitions, messages). Provider prompt caches reuse the longest prefix
+//! shared with an earlier request, so whenever a round's request no longer
+//! starts with the previous round's, the cached prefix is lost. The analyzer
+//! reports each such [`PrefixBreak`] with its cause and accumulates a
+//! [`CacheStabilityReport`].
 
+use std::collections::BTreeMap;
+use std::fmt;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::SystemTime;
+
+use crate::platform::{SystemTime, UNIX_EPOCH};
+use crate::tools::read_tracker::fnv1a;
+use crate::{Message, MessageRole, ToolDef};
 
 /// Generate a unique trace ID for an agent run.
 pub fn generate_trace_id() -> String {
     let ts = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
+        .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
         .as_nanos();
     // Use a counter to handle sub-nanosecond calls.
@@ -118,7 +130,10 @@ pub fn pricing_for_model(model: &str) -> ModelPricing {
 pub struct CostTracker {
     pub total_prompt_tokens: u64,
     pub total_completion_tokens: u64,
+    /// Token cost plus [`tool_costs_usd`](Self::tool_costs_usd).
     pub estimated_cost_usd: f64,
+    /// Cost reported by paid tools, keyed by tool name.
+    pub tool_costs_usd: BTreeMap<String, f64>,
 }
 
 impl CostTracker {
@@ -133,6 +148,17 @@ impl CostTracker {
         self.estimated_cost_usd += pricing.estimate_cost(prompt_tokens, completion_tokens);
     }
 
+    /// Record the cost of a paid tool call.
+    pub fn record_tool(&mut self, tool: &str, cost_usd: f64) {
+        *self.tool_costs_usd.entry(tool.to_string()).or_default() += cost_usd;
+        self.estimated_cost_usd += cost_usd;
+    }
+
+    /// Total cost of paid tool calls.
+    pub fn tool_cost_usd(&self) -> f64 {
+        self.tool_costs_usd.values().sum()
+    }
+
     /// Total tokens consumed.
     pub fn total_tokens(&self) -> u64 {
         self.total_prompt_tokens + self.total_completion_tokens
@@ -140,16 +166,262 @@ impl CostTracker {
 
     /// Format as a short summary string.
     pub fn summary(&self) -> String {
-        format!(
+        let mut summary = format!(
             "tokens: {} prompt + {} completion = {} total, est. cost: ${:.4}",
             self.total_prompt_tokens,
             self.total_completion_tokens,
             self.total_tokens(),
             self.estimated_cost_usd,
+        );
+        if !self.tool_costs_usd.is_empty() {
+            let tools: Vec<String> = self
+                .tool_costs_usd
+                .iter()
+                .map(|(tool, cost)| format!("{tool} ${cost:.4}"))
+                .collect();
+            summary.push_str(&format!(" (tools: {})", tools.join(", ")));
+        }
+        summary
+    }
+}
+
+// ── Prompt prefix analysis ────────────────────────────────────────
+
+/// What made a round's request diverge from the previous round's prefix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrefixBreakCause {
+    /// Model routing switched models; caches are per model.
+    ModelChanged { from: String, to: String },
+    /// The tool definitions changed (filtering, budgeting, phase switch).
+    ToolsChanged,
+    /// A message that was part of the previous request changed (dynamic
+    /// system prompt content, eviction, or an edited reminder).
+    MessageChanged { index: usize, role: MessageRole },
+    /// Messages from the previous request were removed (compaction).
+    MessagesRemoved { kept: usize },
+}
+
+impl PrefixBreakCause {
+    /// Short stable label, for grouping breaks by cause.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::ModelChanged { .. } => "model_changed",
+            Self::ToolsChanged => "tools_changed",
+            Self::MessageChanged { .. } => "message_changed",
+            Self::MessagesRemoved { .. } => "messages_removed",
+        }
+    }
+}
+
+impl fmt::Display for PrefixBreakCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ModelChanged { from, to } => write!(f, "model changed ({from} -> {to})"),
+            Self::ToolsChanged => write!(f, "tool definitions changed"),
+            Self::MessageChanged { index, role } => {
+                write!(f, "message {index} ({role:?}) changed")
+            }
+            Self::MessagesRemoved { kept } => {
+                write!(f, "history shortened to {kept} messages")
+            }
+        }
+    }
+}
+
+/// A round whose request did not extend the previous round's request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefixBreak {
+    pub round: u32,
+    pub cause: PrefixBreakCause,
+    /// Leading messages still shared with the previous request.
+    pub stable_messages: usize,
+    /// Characters of the previous request that could not be reused.
+    pub lost_chars: usize,
+}
+
+/// Prompt prefix stability over a run.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CacheStabilityReport {
+    /// Rounds observed.
+    pub rounds: u32,
+    /// Every prefix break, in order.
+    pub breaks: Vec<PrefixBreak>,
+    /// Characters of each request covered by the previous request's prefix.
+    pub reused_chars: u64,
+    /// Total characters of every request after the first.
+    pub total_chars: u64,
+}
+
+impl CacheStabilityReport {
+    /// Fraction of request content (after the first round) that a prefix
+    /// cache could have served. `1.0` when there is nothing to compare.
+    pub fn reuse_ratio(&self) -> f64 {
+        if self.total_chars == 0 {
+            1.0
+        } else {
+            self.reused_chars as f64 / self.total_chars as f64
+        }
+    }
+
+    /// Number of breaks per cause label, sorted by label.
+    pub fn breaks_by_cause(&self) -> Vec<(&'static str, usize)> {
+        let mut counts: Vec<(&'static str, usize)> = Vec::new();
+        for b in &self.breaks {
+            match counts
+                .iter_mut()
+                .find(|(label, _)| *label == b.cause.label())
+            {
+                Some((_, n)) => *n += 1,
+                None => counts.push((b.cause.label(), 1)),
+            }
+        }
+        counts.sort();
+        counts
+    }
+
+    /// Format as a short summary string.
+    pub fn summary(&self) -> String {
+        let causes = self
+            .breaks_by_cause()
+            .iter()
+            .map(|(label, n)| format!("{label}={n}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            "prefix reuse {:.0}% over {} rounds, {} break(s){}",
+            self.reuse_ratio() * 100.0,
+            self.rounds,
+            self.breaks.len(),
+            if causes.is_empty() {
+                String::new()
+            } else {
+                format!(" ({causes})")
+            }
         )
     }
 }
 
+struct RequestFingerprint {
+    model: String,
+    tools: u64,
+    /// `(hash, chars, role)` per message.
+    messages: Vec<(u64, usize, MessageRole)>,
+}
+
+impl RequestFingerprint {
+    fn new(model: &str, tools: Option<&[ToolDef]>, messages: &[Message]) -> Self {
+        let tools = tools
+            .and_then(|t| serde_json::to_string(t).ok())
+            .map_or(0, |json| fnv1a(&json));
+        let messages = messages
+            .iter()
+            .map(|m| {
+                let content = m.content.as_deref().unwrap_or("");
+                let calls = m
+                    .tool_calls
+                    .as_ref()
+                    .and_then(|c| serde_json::to_string(c).ok())
+                    .unwrap_or_default();
+                let key = format!(
+                    "{:?}\0{}\0{content}\0{calls}",
+                    m.role,
+                    m.tool_call_id.as_deref().unwrap_or("")
+                );
+                (fnv1a(&key), content.len() + calls.len(), m.role.clone())
+            })
+            .collect();
+        Self {
+            model: model.to_string(),
+            tools,
+            messages,
+        }
+    }
+
+    fn chars(&self, count: usize) -> usize {
+        self.messages.iter().take(count).map(|m| m.1).sum()
+    }
+}
+
+/// Tracks prompt prefix stability across the rounds of a run.
+#[derive(Default)]
+pub struct PrefixAnalyzer {
+    last: Option<RequestFingerprint>,
+    report: CacheStabilityReport,
+}
+
+impl PrefixAnalyzer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fingerprint this round's request and compare it with the previous
+    /// round's. Returns the break if the previous request is no longer a
+    /// prefix of this one.
+    pub fn observe(
+        &mut self,
+        round: u32,
+        model: &str,
+        tools: Option<&[ToolDef]>,
+        messages: &[Message],
+    ) -> Option<PrefixBreak> {
+        let current = RequestFingerprint::new(model, tools, messages);
+        self.report.rounds += 1;
+        let last = self.last.replace(current)?;
+        let current = self.last.as_ref().expect("just set");
+
+        let shared = last
+            .messages
+            .iter()
+            .zip(&current.messages)
+            .take_while(|(a, b)| a.0 == b.0)
+            .count();
+        let (cause, stable) = if last.model != current.model {
+            let cause = PrefixBreakCause::ModelChanged {
+                from: last.model.clone(),
+                to: current.model.clone(),
+            };
+            (Some(cause), 0)
+        } else if last.tools != current.tools {
+            (Some(PrefixBreakCause::ToolsChanged), 0)
+        } else if shared < last.messages.len() {
+            let cause = match current.messages.get(shared) {
+                Some((_, _, role)) => PrefixBreakCause::MessageChanged {
+                    index: shared,
+                    role: role.clone(),
+                },
+                None => PrefixBreakCause::MessagesRemoved { kept: shared },
+            };
+            (Some(cause), shared)
+        } else {
+            (None, shared)
+        };
+
+        let reused = current.chars(stable);
+        self.report.reused_chars += reused as u64;
+        self.report.total_chars += current.chars(current.messages.len()) as u64;
+
+        let cause = cause?;
+        let brk = PrefixBreak {
+            round,
+            cause,
+            stable_messages: stable,
+            lost_chars: last.chars(last.messages.len()).saturating_sub(reused),
+        };
+        self.report.breaks.push(brk.clone());
+        Some(brk)
+    }
+
+    /// Stability report for the rounds observed so far.
+    pub fn report(&self) -> &CacheStabilityReport {
+        &self.report
+    }
+
+    /// Consume the analyzer, returning its report.
+    pub fn into_report(self) -> CacheStabilityReport {
+        self.report
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -191,6 +463,21 @@ mod tests {
         assert!(tracker.estimated_cost_usd > 0.0);
     }
 
+    #[test]
+    fn cost_tracker_includes_tool_costs() {
+        let mut tracker = CostTracker::new();
+        tracker.record(1_000_000, 0, &ModelPricing::default());
+        tracker.record_tool("web_search", 0.005);
+        tracker.record_tool("web_search", 0.005);
+        assert!((tracker.tool_cost_usd() - 0.01).abs() < 1e-9);
+        assert!((tracker.estimated_cost_usd - 3.01).abs() < 1e-9);
+        assert!(
+            tracker.summary().ends_with("(tools: web_search $0.0100)"),
+            "{}",
+            tracker.summary()
+        );
+    }
+
     #[test]
     fn pricing_lookup_known_models() {
         let opus = pricing_for_model("anthropic/claude-opus-4");
@@ -211,4 +498,68 @@ mod tests {
         assert!(summary.contains("tokens:"));
         assert!(summary.contains("cost:"));
     }
+
+    #[test]
+    fn growing_history_keeps_prefix_stable() {
+        let mut analyzer = PrefixAnalyzer::new();
+        let mut messages = vec![Message::system("sys"), Message::user("task")];
+        assert!(analyzer.observe(1, "m", None, &messages).is_none());
+        messages.push(Message::assistant_text("ok"));
+        messages.push(Message::user("more"));
+        assert!(analyzer.observe(2, "m", None, &messages).is_none());
+
+        let report = analyzer.report();
+        assert_eq!(report.rounds, 2);
+        assert!(report.breaks.is_empty());
+        assert!(report.reuse_ratio() > 0.5 && report.reuse_ratio() < 1.0);
+    }
+
+    #[test]
+    fn reports_break_causes() {
+        let mut analyzer = PrefixAnalyzer::new();
+        let base = vec![Message::system("sys"), Message::user("task")];
+        analyzer.observe(1, "m", None, &base);
+
+        let brk = analyzer.observe(2, "other", None, &base).unwrap();
+        assert_eq!(brk.cause.label(), "model_changed");
+        assert_eq!(brk.stable_messages, 0);
+
+        let tool = ToolDef::new("t", "desc", serde_json::json!({"type": "object"}));
+        let brk = analyzer
+            .observe(3, "other", Some(std::slice::from_ref(&tool)), &base)
+            .unwrap();
+        assert_eq!(brk.cause, PrefixBreakCause::ToolsChanged);
+
+        let edited = vec![Message::system("sys"), Message::user("edited")];
+        let brk = analyzer
+            .observe(4, "other", Some(std::slice::from_ref(&tool)), &edited)
+            .unwrap();
+        assert_eq!(
+            brk.cause,
+            PrefixBreakCause::MessageChanged {
+                index: 1,
+                role: MessageRole::User
+            }
+        );
+        assert_eq!(brk.stable_messages, 1);
+        assert_eq!(brk.lost_chars, "task".len());
+
+        let brk = analyzer
+            .observe(5, "other", Some(std::slice::from_ref(&tool)), &edited[..1])
+            .unwrap();
+        assert_eq!(brk.cause, PrefixBreakCause::MessagesRemoved { kept: 1 });
+
+        let report = analyzer.into_report();
+        assert_eq!(report.breaks.len(), 4);
+        assert_eq!(
+            report.breaks_by_cause(),
+            vec![
+                ("message_changed", 1),
+                ("messages_removed", 1),
+                ("model_changed", 1),
+                ("tools_changed", 1)
+            ]
+        );
+        assert!(report.summary().contains("4 break(s)"));
+    }
 }
@@ -0,0 +1,226 @@
+//! Pluggable HTTP transport for [`OpenRouterClient`](crate::OpenRouterClient).
+//!
+//! The client never talks to an HTTP library directly: it builds an
+//! [`HttpRequest`] and hands it to an [`HttpTransport`]. Native builds use
+//! [`ReqwestTransport`] by default. On `wasm32-unknown-unknown` there is no
+//! default; embedders running in the browser or an edge worker implement the
+//! trait over JS `fetch` and pass it to
+//! [`OpenRouterClient::with_transport()`](crate::OpenRouterClient::with_transport).
+//!
+//! ```ignore
+//! struct FetchTransport;
+//!
+//! impl HttpTransport for FetchTransport {
+//!     fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+//!         // JS futures are !Send; wasm32 is single-threaded, so wrapping
+//!         // them in `send_wrapper::SendWrapper` is sound.
+//!         Box::pin(SendWrapper::new(async move {
+//!             let resp = js_fetch(&request.url, &request.headers, &request.body).await?;
+//!             Ok(HttpResponse::from_body(resp.status, resp.text))
+//!         }))
+//!     }
+//! }
+//!
+//! let client = OpenRouterClient::with_transport(key, FetchTransport);
+//! ```
+
+use std::future::Future;
+use std::pin::Pin;
+
+use futures::{Stream, StreamExt};
+
+/// Boxed future returned by [`HttpTransport::send()`].
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<HttpResponse, String>> + Send + 'a>>;
+
+/// Response body as a stream of byte chunks.
+pub type BodyStream = Pin<Box<dyn Stream<Item = Result<Vec<u8>, String>> + Send>>;
+
+/// An outgoing POST request with a JSON body.
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    /// Absolute URL.
+    pub url: String,
+    /// Header name/value pairs, including `Authorization`.
+    pub headers: Vec<(String, String)>,
+    /// Serialized JSON body.
+    pub body: String,
+}
+
+/// A response whose body is read incrementally, so SSE streams are
+/// processed as they arrive.
+pub struct HttpResponse {
+    /// HTTP status code.
+    pub status: u16,
+    body: BodyStream,
+}
+
+impl HttpResponse {
+    /// Wrap a streamed body.
+    pub fn new(status: u16, body: BodyStream) -> Self {
+        Self { status, body }
+    }
+
+    /// A response whose body is already fully read.
+    pub fn from_body(status: u16, body: impl Into<Vec<u8>>) -> Self {
+        let body = body.into();
+        Self::new(status, Box::pin(futures::stream::once(async { Ok(body) })))
+    }
+
+    /// Whether the status is 2xx.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// The next body chunk, or `None` at the end of the body.
+    pub async fn chunk(&mut self) -> Result<Option<Vec<u8>>, String> {
+        self.body.next().await.transpose()
+    }
+
+    /// Read the rest of the body as (lossy) UTF-8 text.
+    pub async fn text(mut self) -> Result<String, String> {
+        let mut bytes = Vec::new();
+        while let Some(chunk) = self.chunk().await? {
+            bytes.extend_from_slice(&chunk);
+        }
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+impl std::fmt::Debug for HttpResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpResponse")
+            .field("status", &self.status)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Sends HTTP requests on behalf of the client.
+pub trait HttpTransport: Send + Sync {
+    /// POST `request` and return the response once headers arrive. Errors
+    /// are for failures to get any response (DNS, connect, timeout); HTTP
+    /// error statuses are returned as responses.
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_>;
+}
+
+/// Default native transport backed by `reqwest`.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ReqwestTransport {
+    /// Build a transport with the client's default timeouts (30s connect,
+    /// 300s between reads).
+    pub fn new() -> Result<Self, String> {
+        let client = reqwest::Client::builder()
+            .user_agent("openrouter-client/0.1")
+            .connect_timeout(std::time::Duration::from_secs(30))
+            .read_timeout(std::time::Duration::from_secs(300))
+            .build()
+            .map_err(|e| format!("failed to build HTTP client: {e}"))?;
+        Ok(Self { client })
+    }
+
+    /// Use a preconfigured `reqwest` client.
+    pub fn from_client(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl HttpTransport for ReqwestTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let mut builder = self
+                .client
+                .post(&request.url)
+                .header("Content-Type", "application/json")
+                .body(request.body);
+            for (name, value) in request.headers {
+                builder = builder.header(name, value);
+            }
+            let resp = builder.send().await.map_err(|e| e.to_string())?;
+            let status = resp.status().as_u16();
+            let body = futures::stream::unfold(resp, |mut resp| async move {
+                match resp.chunk().await {
+                    Ok(Some(bytes)) => Some((Ok(bytes.to_vec()), resp)),
+                    Ok(None) => None,
+                    Err(e) => Some((Err(e.to_string()), resp)),
+                }
+            });
+            Ok(HttpResponse::new(status, Box::pin(body)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChatRequest, Message, OpenRouterClient};
+    use std::sync::{Arc, Mutex};
+
+    /// Transport that records requests and answers with a fixed body.
+    struct Canned {
+        seen: Arc<Mutex<Vec<HttpRequest>>>,
+        body: &'static str,
+    }
+
+    impl HttpTransport for Canned {
+        fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+            self.seen.lock().unwrap().push(request);
+            Box::pin(async move { Ok(HttpResponse::from_body(200, self.body)) })
+        }
+    }
+
+    #[tokio::test]
+    async fn buffered_body_reads_as_text() {
+        let resp = HttpResponse::from_body(200, "hello");
+        assert!(resp.is_success());
+        assert_eq!(resp.text().await.unwrap(), "hello");
+    }
+
+    #[tokio::test]
+    async fn chunks_arrive_in_order() {
+        let chunks = vec![Ok(b"data: a\n".to_vec()), Ok(b"data: b\n".to_vec())];
+        let mut resp = HttpResponse::new(429, Box::pin(futures::stream::iter(chunks)));
+        assert!(!resp.is_success());
+        assert_eq!(resp.chunk().await.unwrap().unwrap(), b"data: a\n");
+        assert_eq!(resp.chunk().await.unwrap().unwrap(), b"data: b\n");
+        assert!(resp.chunk().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn client_sends_through_custom_transport() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let transport = Canned {
+            seen: seen.clone(),
+            body: r#"{"choices":[{"message":{"content":"pong"},"finish_reason":"stop"}]}"#,
+        };
+        let client = OpenRouterClient::with_transport("key-123", transport)
+            .with_attribution("https://example.com", "worker");
+        let request = ChatRequest {
+            model: Some("m".into()),
+            messages: vec![Message::user("ping")],
+            ..Default::default()
+        };
+        let completion = client.chat(&request).await.unwrap();
+        assert_eq!(completion.content.as_deref(), Some("pong"));
+
+        let seen = seen.lock().unwrap();
+        assert!(seen[0].body.contains("ping"));
+        assert!(
+            seen[0]
+                .headers
+                .iter()
+                .any(|(k, v)| k == "Authorization" && v == "Bearer key-123")
+        );
+        assert!(
+            seen[0]
+                .headers
+                .iter()
+                .any(|(k, v)| k == "X-Title" && v == "worker")
+        );
+    }
+}
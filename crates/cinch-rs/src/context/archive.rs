@@ -0,0 +1,238 @@
+//! Cold storage for compacted history.
+//!
+//! Compaction replaces middle-zone messages with a summary, which is lossy:
+//! exact error messages, file contents, and command output are gone once
+//! summarized. A [`HistoryArchive`] writes the messages of each compaction to
+//! disk before they are dropped (`compaction-0001.jsonl`, one message per
+//! line), and [`RecallHistoryTool`](crate::tools::RecallHistoryTool) lets the
+//! model search them and pull relevant excerpts back into context.
+//!
+//! Search is keyword-based: each archived message is scored by how many of
+//! the query's terms it contains, and the best matches are returned as
+//! excerpts centred on the first matching term.
+
+use std::path::{Path, PathBuf};
+
+use crate::Message;
+
+/// Characters of context kept around the first match in an excerpt.
+const EXCERPT_CHARS: usize = 800;
+
+/// On-disk archive of compacted messages, one file per compaction.
+#[derive(Debug, Clone)]
+pub struct HistoryArchive {
+    dir: PathBuf,
+}
+
+/// One search hit from the archive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryExcerpt {
+    /// Compaction the message was archived by (1-based).
+    pub compaction: usize,
+    /// Message role (`"user"`, `"assistant"`, `"tool"`).
+    pub role: String,
+    /// Number of distinct query terms the message contains.
+    pub score: usize,
+    /// Excerpt of the message text around the first match.
+    pub text: String,
+}
+
+impl HistoryArchive {
+    /// Archive compacted messages in `dir`. The directory is created on
+    /// first write.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The archive directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Write the messages dropped by compaction `compaction_number`.
+    pub fn store(&self, compaction_number: usize, messages: &[Message]) -> Result<(), String> {
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create {}: {e}", self.dir.display()))?;
+        let mut lines = String::new();
+        for msg in messages {
+            let json = serde_json::to_string(msg)
+                .map_err(|e| format!("Failed to serialize archived message: {e}"))?;
+            lines.push_str(&json);
+            lines.push('\n');
+        }
+        let path = self.file_path(compaction_number);
+        std::fs::write(&path, lines).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+    }
+
+    /// Load the messages archived by compaction `compaction_number`.
+    pub fn load(&self, compaction_number: usize) -> Result<Vec<Message>, String> {
+        let path = self.file_path(compaction_number);
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        content
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| {
+                serde_json::from_str(l)
+                    .map_err(|e| format!("Invalid archived message in {}: {e}", path.display()))
+            })
+            .collect()
+    }
+
+    /// Compaction numbers present in the archive, ascending.
+    pub fn compactions(&self) -> Vec<usize> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut numbers: Vec<usize> = entries
+            .filter_map(|e| e.ok())
+            .filter_map(|e| {
+                let name = e.file_name().to_string_lossy().into_owned();
+                name.strip_prefix("compaction-")?
+                    .strip_suffix(".jsonl")?
+                    .parse()
+                    .ok()
+            })
+            .collect();
+        numbers.sort_unstable();
+        numbers
+    }
+
+    /// Return up to `limit` excerpts matching `query`, best matches first.
+    /// Ties are broken in favour of more recent compactions.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<HistoryExcerpt> {
+        let terms: Vec<String> = query
+            .split_whitespace()
+            .map(|t| t.to_lowercase())
+            .filter(|t| !t.is_empty())
+            .collect();
+        if terms.is_empty() {
+            return Vec::new();
+        }
+
+        let mut hits = Vec::new();
+        for compaction in self.compactions() {
+            let Ok(messages) = self.load(compaction) else {
+                continue;
+            };
+            for msg in &messages {
+                let text = message_text(msg);
+                let lower = text.to_lowercase();
+                let score = terms.iter().filter(|t| lower.contains(t.as_str())).count();
+                if score == 0 {
+                    continue;
+                }
+                let first = terms
+                    .iter()
+                    .filter_map(|t| lower.find(t.as_str()))
+                    .min()
+                    .unwrap_or(0);
+                hits.push(HistoryExcerpt {
+                    compaction,
+                    role: msg.role.to_string(),
+                    score,
+                    text: excerpt(&text, &lower, first),
+                });
+            }
+        }
+        hits.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| b.compaction.cmp(&a.compaction))
+        });
+        hits.truncate(limit);
+        hits
+    }
+
+    fn file_path(&self, compaction_number: usize) -> PathBuf {
+        self.dir
+            .join(format!("compaction-{compaction_number:04}.jsonl"))
+    }
+}
+
+/// Searchable text of a message: its content plus any tool calls.
+fn message_text(msg: &Message) -> String {
+    let mut text = msg.content.clone().unwrap_or_default();
+    for call in msg.tool_calls.iter().flatten() {
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(&format!(
+            "[called {}({})]",
+            call.function.name, call.function.arguments
+        ));
+    }
+    text
+}
+
+/// Cut about [`EXCERPT_CHARS`] characters of `text` around byte offset
+/// `first` of its lowercased form.
+fn excerpt(text: &str, lower: &str, first: usize) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.len() <= EXCERPT_CHARS {
+        return text.to_string();
+    }
+    // Lowercasing can change byte lengths, so locate the match by chars.
+    #[allow(clippy::string_slice)] // `first` comes from `find` on `lower`
+    let match_char = lower[..first].chars().count().min(chars.len());
+    let start = match_char.saturating_sub(EXCERPT_CHARS / 4);
+    let end = (start + EXCERPT_CHARS).min(chars.len());
+    let start = end.saturating_sub(EXCERPT_CHARS);
+    let mut out = String::new();
+    if start > 0 {
+        out.push_str("...");
+    }
+    out.extend(&chars[start..end]);
+    if end < chars.len() {
+        out.push_str("...");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stores_and_searches_compactions() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = HistoryArchive::new(dir.path().join("history"));
+        archive
+            .store(
+                1,
+                &[
+                    Message::user("Fix the failing parser test"),
+                    Message::tool_result("c1", "error[E0308]: mismatched types in parser.rs"),
+                ],
+            )
+            .unwrap();
+        archive
+            .store(2, &[Message::tool_result("c2", "parser.rs compiled fine")])
+            .unwrap();
+
+        assert_eq!(archive.compactions(), vec![1, 2]);
+        assert_eq!(archive.load(1).unwrap().len(), 2);
+
+        let hits = archive.search("E0308 parser", 5);
+        assert_eq!(hits.len(), 3);
+        assert_eq!(hits[0].compaction, 1);
+        assert_eq!(hits[0].score, 2);
+        assert!(hits[0].text.contains("mismatched types"));
+        // Equal scores: the later compaction ranks first.
+        assert_eq!(hits[1].compaction, 2);
+
+        assert!(archive.search("nonexistent", 5).is_empty());
+        assert!(archive.search("   ", 5).is_empty());
+    }
+
+    #[test]
+    fn long_messages_are_excerpted_around_the_match() {
+        let long = format!("{}NEEDLE{}", "a".repeat(5000), "b".repeat(5000));
+        let lower = long.to_lowercase();
+        let first = lower.find("needle").unwrap();
+        let text = excerpt(&long, &lower, first);
+        assert!(text.contains("NEEDLE"));
+        assert!(text.starts_with("...") && text.ends_with("..."));
+        assert!(text.len() < 1000);
+    }
+}
@@ -21,6 +21,17 @@ const DEFAULT_CONTEXT_WINDOW: usize = 200_000;
 const WARNING_THRESHOLD: f64 = 0.60;
 const CRITICAL_THRESHOLD: f64 = 0.80;
 
+/// Smoothing factor for the completion-size moving average used by the
+/// adaptive output reserve. Higher values react faster to recent rounds.
+const OUTPUT_EWMA_ALPHA: f64 = 0.3;
+
+/// The adaptive output reserve is this multiple of the average completion
+/// size, leaving headroom for an occasional long response.
+const OUTPUT_RESERVE_HEADROOM: f64 = 2.0;
+
+/// Lower bound for the adaptive output reserve, in tokens.
+const MIN_ADAPTIVE_OUTPUT_RESERVE: usize = 512;
+
 /// Tracks context budget consumption across the agent loop.
 ///
 /// Estimates total token usage from message character counts and injects
@@ -29,6 +40,14 @@ const CRITICAL_THRESHOLD: f64 = 0.80;
 /// creates and manages a `ContextBudget` automatically; you only need to
 /// construct one manually for standalone use.
 ///
+/// The output reserve defaults to the per-response `max_tokens` limit. With
+/// [`with_adaptive_output_reserve()`](Self::with_adaptive_output_reserve),
+/// it instead tracks an exponentially weighted moving average of observed
+/// completion sizes (fed by [`record_completion()`](Self::record_completion)),
+/// reserving twice the average but never more than the static reserve.
+/// Agents that mostly emit short tool calls reclaim that headroom for
+/// context.
+///
 /// # Example
 ///
 /// ```ignore
@@ -49,8 +68,13 @@ const CRITICAL_THRESHOLD: f64 = 0.80;
 pub struct ContextBudget {
     /// Maximum context window in tokens.
     max_tokens: usize,
-    /// Tokens reserved for model output (per-response token limit).
+    /// Tokens reserved for model output (per-response token limit). Also
+    /// the upper bound of the adaptive reserve.
     output_reserve: usize,
+    /// Whether the output reserve adapts to observed completion sizes.
+    adaptive_output_reserve: bool,
+    /// Moving average of completion tokens, once any have been recorded.
+    completion_ewma: Option<f64>,
     /// Tokens reserved for system prompt overhead.
     system_reserve: usize,
     /// Size of the system prompt in characters.
@@ -71,6 +95,8 @@ impl ContextBudget {
         Self {
             max_tokens: DEFAULT_CONTEXT_WINDOW,
             output_reserve: 0,
+            adaptive_output_reserve: false,
+            completion_ewma: None,
             system_reserve: 0,
             system_prompt_chars: system_prompt.len(),
             chars_per_token: cpt,
@@ -103,6 +129,39 @@ impl ContextBudget {
         self
     }
 
+    /// Size the output reserve from observed completion sizes instead of
+    /// the static reserve, which becomes its upper bound.
+    pub fn with_adaptive_output_reserve(mut self, enabled: bool) -> Self {
+        self.adaptive_output_reserve = enabled;
+        self
+    }
+
+    /// Record the size of a completion, updating the moving average used by
+    /// the adaptive output reserve.
+    pub fn record_completion(&mut self, completion_tokens: u32) {
+        let tokens = f64::from(completion_tokens);
+        self.completion_ewma = Some(match self.completion_ewma {
+            Some(avg) => OUTPUT_EWMA_ALPHA * tokens + (1.0 - OUTPUT_EWMA_ALPHA) * avg,
+            None => tokens,
+        });
+    }
+
+    /// Tokens currently reserved for model output.
+    ///
+    /// This is the static reserve until adaptive sizing is enabled and at
+    /// least one completion has been recorded.
+    pub fn output_reserve(&self) -> usize {
+        match self.completion_ewma {
+            Some(avg) if self.adaptive_output_reserve => {
+                let adaptive = (avg * OUTPUT_RESERVE_HEADROOM).ceil() as usize;
+                adaptive
+                    .max(MIN_ADAPTIVE_OUTPUT_RESERVE)
+                    .min(self.output_reserve)
+            }
+            _ => self.output_reserve,
+        }
+    }
+
     /// Set tokens reserved for system prompt overhead.
     pub fn with_system_reserve(mut self, tokens: usize) -> Self {
         self.system_reserve = tokens;
@@ -120,7 +179,7 @@ impl ContextBudget {
     /// to ensure the model always has room for its response.
     pub fn effective_max_tokens(&self) -> usize {
         self.max_tokens
-            .saturating_sub(self.output_reserve)
+            .saturating_sub(self.output_reserve())
             .saturating_sub(self.system_reserve)
     }
 
@@ -215,6 +274,7 @@ mod tests {
             tool_calls: None,
             tool_call_id: None,
             cache_control: None,
+            images: Vec::new(),
         }
     }
 
@@ -311,6 +371,35 @@ mod tests {
         assert_eq!(budget.effective_max_tokens(), 0);
     }
 
+    #[test]
+    fn adaptive_output_reserve_tracks_completion_sizes() {
+        let mut budget = ContextBudget::with_calibration("test", None)
+            .with_max_tokens(100_000)
+            .with_output_reserve(8192)
+            .with_adaptive_output_reserve(true);
+        // No completions observed yet: the static reserve applies.
+        assert_eq!(budget.output_reserve(), 8192);
+
+        budget.record_completion(300);
+        assert_eq!(budget.output_reserve(), 600);
+        assert_eq!(budget.effective_max_tokens(), 100_000 - 600);
+
+        // Tiny completions are floored; huge ones are capped at the static reserve.
+        budget.record_completion(0);
+        assert_eq!(budget.output_reserve(), MIN_ADAPTIVE_OUTPUT_RESERVE);
+        for _ in 0..20 {
+            budget.record_completion(50_000);
+        }
+        assert_eq!(budget.output_reserve(), 8192);
+    }
+
+    #[test]
+    fn static_output_reserve_ignores_completions() {
+        let mut budget = ContextBudget::with_calibration("test", None).with_output_reserve(4096);
+        budget.record_completion(100);
+        assert_eq!(budget.output_reserve(), 4096);
+    }
+
     #[test]
     fn output_reserve_makes_thresholds_trigger_earlier() {
         // With output_reserve, effective window is smaller, so same content
@@ -8,9 +8,16 @@
 //!
 //! Highest-ROI context management technique: no LLM call needed, typically
 //! recovers 10-100x more tokens than model reasoning occupies.
+//!
+//! The model can mark results it still needs with the `pin` pseudo-tool
+//! ([`PinTool`](crate::tools::core::PinTool)); see [`apply_pin()`]. Pinned
+//! results carry an [`Importance`] that lowers their eviction priority, and
+//! any note the model attached is kept in the placeholder.
 
 use crate::Message;
 use crate::context::layout::message_tokens;
+use schemars::JsonSchema;
+use serde::Deserialize;
 use std::cmp::Ordering;
 use std::collections::HashSet;
 
@@ -60,8 +67,32 @@ impl EvictionConfig {
     }
 }
 
+/// How important the model considers a tool result.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Importance {
+    /// Not pinned.
+    #[default]
+    Normal,
+    /// Evicted only after all normal results.
+    High,
+    /// Evicted only after all normal and high-importance results.
+    Critical,
+}
+
+impl Importance {
+    /// Multiplier applied to the eviction priority.
+    pub fn weight(self) -> f64 {
+        match self {
+            Self::Normal => 1.0,
+            Self::High => 0.25,
+            Self::Critical => 0.05,
+        }
+    }
+}
+
 /// Metadata tracked alongside each tool result for eviction purposes.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ToolResultMeta {
     /// The tool name that produced this result.
     pub tool_name: String,
@@ -75,6 +106,36 @@ pub struct ToolResultMeta {
     pub char_count: usize,
     /// Estimated token count of the original result.
     pub estimated_tokens: usize,
+    /// Importance assigned by the model via `pin`.
+    pub importance: Importance,
+    /// Note attached by the model via `pin`, kept in the eviction placeholder.
+    pub note: Option<String>,
+}
+
+/// Apply a `pin` call to earlier tool results: every result of `tool` whose
+/// argument summary contains `target`, or only the most recent result of
+/// `tool` when `target` is `None`. Returns how many results were updated.
+pub fn apply_pin(
+    tool_metas: &mut [ToolResultMeta],
+    tool: &str,
+    target: Option<&str>,
+    importance: Importance,
+    note: Option<&str>,
+) -> usize {
+    let mut matching: Vec<&mut ToolResultMeta> = tool_metas
+        .iter_mut()
+        .filter(|m| m.tool_name == tool && target.is_none_or(|t| m.args_summary.contains(t)))
+        .collect();
+    if target.is_none() && matching.len() > 1 {
+        matching.drain(..matching.len() - 1);
+    }
+    for meta in &mut matching {
+        meta.importance = importance;
+        if let Some(note) = note {
+            meta.note = Some(note.to_string());
+        }
+    }
+    matching.len()
 }
 
 /// Compute eviction priority for a tool result. Higher score = evict first.
@@ -86,6 +147,8 @@ pub struct ToolResultMeta {
 /// - **Tool type**: read-only tools (read_file, grep, find_files, list_dir)
 ///   get a 1.5x multiplier since their results can always be re-read from the
 ///   environment, unlike mutation tool results.
+///
+/// The result is scaled by the model-assigned [`Importance::weight()`].
 pub fn eviction_priority(meta: &ToolResultMeta, current_round: usize) -> f64 {
     let age = (current_round.saturating_sub(meta.round)).max(1) as f64;
     let size_factor = (meta.estimated_tokens.max(1) as f64).ln();
@@ -96,12 +159,13 @@ pub fn eviction_priority(meta: &ToolResultMeta, current_round: usize) -> f64 {
         | crate::tools::names::LIST_DIR => 1.5,
         _ => 1.0,
     };
-    age * size_factor * tool_factor
+    age * size_factor * tool_factor * meta.importance.weight()
 }
 
 /// Evict tool results from a message list by priority, replacing them with placeholders.
 ///
-/// Candidates are sorted by [`eviction_priority()`] (highest first): large,
+/// Candidates are grouped by [`Importance`] (normal first, critical last) and
+/// sorted within each group by [`eviction_priority()`] (highest first): large,
 /// old, read-only results are evicted before small, recent, or mutation results.
 /// Stops when the estimated total tokens drops below `target_tokens`.
 ///
@@ -124,9 +188,11 @@ pub fn evict_tool_results(
         })
         .collect();
     candidates.sort_by(|a, b| {
-        eviction_priority(b, current_round)
-            .partial_cmp(&eviction_priority(a, current_round))
-            .unwrap_or(Ordering::Equal)
+        a.importance.cmp(&b.importance).then_with(|| {
+            eviction_priority(b, current_round)
+                .partial_cmp(&eviction_priority(a, current_round))
+                .unwrap_or(Ordering::Equal)
+        })
     });
 
     for meta in candidates {
@@ -144,8 +210,12 @@ pub fn evict_tool_results(
                 continue;
             }
 
+            let note = meta
+                .note
+                .as_ref()
+                .map_or_else(String::new, |n| format!("; note: {n}"));
             let placeholder = format!(
-                "[Cleared: {}({}) — {} chars, round {}]",
+                "[Cleared: {}({}) — {} chars, round {}{note}]",
                 meta.tool_name, meta.args_summary, meta.char_count, meta.round,
             );
 
@@ -244,6 +314,7 @@ mod tests {
                 message_index: 2,
                 char_count: 10000,
                 estimated_tokens: 2857,
+                ..Default::default()
             },
             ToolResultMeta {
                 tool_name: "grep".into(),
@@ -252,6 +323,7 @@ mod tests {
                 message_index: 3,
                 char_count: 10000,
                 estimated_tokens: 2857,
+                ..Default::default()
             },
             ToolResultMeta {
                 tool_name: "read_file".into(),
@@ -260,6 +332,7 @@ mod tests {
                 message_index: 4,
                 char_count: 10000,
                 estimated_tokens: 2857,
+                ..Default::default()
             },
         ];
 
@@ -294,6 +367,7 @@ mod tests {
             message_index: 0,
             char_count: 10000,
             estimated_tokens: 2857,
+            ..Default::default()
         }];
 
         let config = EvictionConfig::new()
@@ -322,6 +396,7 @@ mod tests {
             message_index: 0,
             char_count: 10000,
             estimated_tokens: 2857,
+            ..Default::default()
         }];
 
         let config = EvictionConfig::new().with_min_age(3);
@@ -341,6 +416,7 @@ mod tests {
             message_index: 0,
             char_count: 30_000,
             estimated_tokens: 8572,
+            ..Default::default()
         };
         let small_shell = ToolResultMeta {
             tool_name: "shell".into(),
@@ -349,6 +425,7 @@ mod tests {
             message_index: 1,
             char_count: 50,
             estimated_tokens: 15,
+            ..Default::default()
         };
 
         let current_round = 5;
@@ -379,6 +456,7 @@ mod tests {
                 message_index: 2,
                 char_count: 50,
                 estimated_tokens: 15,
+                ..Default::default()
             },
             ToolResultMeta {
                 tool_name: "read_file".into(),
@@ -387,6 +465,7 @@ mod tests {
                 message_index: 3,
                 char_count: 30_000,
                 estimated_tokens: 8572,
+                ..Default::default()
             },
             ToolResultMeta {
                 tool_name: "grep".into(),
@@ -395,6 +474,7 @@ mod tests {
                 message_index: 4,
                 char_count: 500,
                 estimated_tokens: 143,
+                ..Default::default()
             },
         ];
 
@@ -416,6 +496,84 @@ mod tests {
         );
     }
 
+    #[test]
+    fn pinned_results_are_evicted_last() {
+        let mut messages = vec![
+            make_tool_msg("c1", &"a".repeat(30_000)),
+            make_tool_msg("c2", &"b".repeat(5_000)),
+        ];
+        let mut metas = vec![
+            ToolResultMeta {
+                tool_name: "read_file".into(),
+                args_summary: "path=\"src/big.rs\"".into(),
+                round: 1,
+                message_index: 0,
+                char_count: 30_000,
+                estimated_tokens: 8572,
+                ..Default::default()
+            },
+            ToolResultMeta {
+                tool_name: "shell".into(),
+                args_summary: "command=\"cargo test\"".into(),
+                round: 2,
+                message_index: 1,
+                char_count: 5_000,
+                estimated_tokens: 1429,
+                ..Default::default()
+            },
+        ];
+
+        let pinned = apply_pin(
+            &mut metas,
+            "read_file",
+            Some("big.rs"),
+            Importance::Critical,
+            Some("parser entry point"),
+        );
+        assert_eq!(pinned, 1);
+        assert!(eviction_priority(&metas[0], 5) < eviction_priority(&metas[1], 5));
+
+        let config = EvictionConfig::new().with_min_age(1);
+        evict_tool_results(&mut messages, &metas, 5, 9000, &config);
+        assert!(
+            messages[1]
+                .content
+                .as_ref()
+                .unwrap()
+                .starts_with(EVICTED_PREFIX)
+        );
+        assert!(messages[0].content.as_ref().unwrap().starts_with('a'));
+
+        evict_tool_results(&mut messages, &metas, 5, 0, &config);
+        assert!(
+            messages[0]
+                .content
+                .as_ref()
+                .unwrap()
+                .ends_with("; note: parser entry point]")
+        );
+    }
+
+    #[test]
+    fn pin_without_target_marks_most_recent_result() {
+        let meta = |round| ToolResultMeta {
+            tool_name: "grep".into(),
+            round,
+            ..Default::default()
+        };
+        let mut metas = vec![meta(1), meta(2)];
+        assert_eq!(
+            apply_pin(&mut metas, "grep", None, Importance::High, None),
+            1
+        );
+        assert_eq!(metas[0].importance, Importance::Normal);
+        assert_eq!(metas[1].importance, Importance::High);
+        assert_eq!(
+            apply_pin(&mut metas, "shell", None, Importance::High, None),
+            0
+        );
+    }
+
     #[test]
     fn summarize_args_json() {
         let args = r#"{"path": "src/main.rs", "encoding": "utf-8"}"#;
@@ -94,6 +94,11 @@ impl FileAccessTracker {
         }
     }
 
+    /// Tracked file paths, oldest access first.
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.recent_files.iter().map(|f| f.path.as_str())
+    }
+
     /// Build a preservation note for injection into compaction input.
     ///
     /// Returns an empty string if no files are tracked.
@@ -12,7 +12,8 @@
 //! See: StreamingLLM (attention sinks), "Lost in the Middle" (TACL 2024).
 
 use crate::Message;
-use std::collections::VecDeque;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
 
 /// Estimate tokens for a single message, accounting for content, tool_calls, and tool_call_id.
 pub fn message_tokens(msg: &Message, chars_per_token: f64) -> usize {
@@ -31,12 +32,23 @@ pub fn message_tokens(msg: &Message, chars_per_token: f64) -> usize {
 /// Default number of recent messages to keep in the raw recency window.
 const DEFAULT_KEEP_RECENT: usize = 10;
 
+/// Messages always kept in a token-budgeted recency window, however large.
+const MIN_KEEP_RECENT: usize = 2;
+
 /// Default token threshold to trigger compaction (80% of context window).
 const DEFAULT_T_MAX_FRACTION: f64 = 0.80;
 
 /// Default target tokens after compaction (60% of context window).
 const DEFAULT_T_RETAINED_FRACTION: f64 = 0.60;
 
+/// Default budget for the compressed history zone (15% of context window).
+/// Beyond it, the history is re-summarized (second-level compaction).
+const DEFAULT_HISTORY_BUDGET_FRACTION: f64 = 0.15;
+
+/// Acknowledgement message that follows the compressed history.
+const HISTORY_ACK: &str =
+    "I've reviewed the context summary and will continue from where I left off.";
+
 /// Three-zone context layout manager.
 ///
 /// Manages the assembly of messages for API requests, maintaining three
@@ -61,6 +73,10 @@ pub struct ContextLayout {
     /// Number of recent messages to keep in the raw recency window.
     keep_recent: usize,
 
+    /// Token budget for the raw recency window. When set, it replaces
+    /// `keep_recent` as the window's limit.
+    keep_recent_tokens: Option<usize>,
+
     /// Token threshold to trigger compaction.
     t_max: usize,
 
@@ -79,6 +95,13 @@ pub struct ContextLayout {
 
     /// Round at which last compaction occurred.
     last_compaction_round: usize,
+
+    /// Token budget for the compressed history zone.
+    history_budget: usize,
+
+    /// Number of times the compressed history itself was re-summarized
+    /// (second-level compactions).
+    history_generation: u32,
 }
 
 impl ContextLayout {
@@ -93,9 +116,13 @@ impl ContextLayout {
             recency_window: VecDeque::new(),
             middle: Vec::new(),
             keep_recent: DEFAULT_KEEP_RECENT,
+            keep_recent_tokens: None,
             t_max: (context_window_tokens as f64 * DEFAULT_T_MAX_FRACTION) as usize,
             t_retained: (context_window_tokens as f64 * DEFAULT_T_RETAINED_FRACTION) as usize,
             chars_per_token: crate::context::DEFAULT_CHARS_PER_TOKEN,
+            history_budget: (context_window_tokens as f64 * DEFAULT_HISTORY_BUDGET_FRACTION)
+                as usize,
+            history_generation: 0,
         }
     }
 
@@ -105,6 +132,15 @@ impl ContextLayout {
         self
     }
 
+    /// Size the raw recency window by tokens instead of message count: the
+    /// most recent messages that fit in `tokens` stay raw, so a few large
+    /// file reads take as much of the window as many small results. The
+    /// newest two messages are always kept.
+    pub fn with_keep_recent_tokens(mut self, tokens: usize) -> Self {
+        self.keep_recent_tokens = Some(tokens);
+        self
+    }
+
     /// Set custom compaction thresholds.
     pub fn with_thresholds(mut self, t_max: usize, t_retained: usize) -> Self {
         self.t_max = t_max;
@@ -112,11 +148,24 @@ impl ContextLayout {
         self
     }
 
+    /// Set the token budget for the compressed history zone. When the
+    /// history grows past it, [`history_needs_recompaction()`](Self::history_needs_recompaction)
+    /// reports that it should be re-summarized.
+    pub fn with_history_budget(mut self, tokens: usize) -> Self {
+        self.history_budget = tokens;
+        self
+    }
+
     /// Set the pinned prefix messages (system prompt, persistent rules, etc.).
     pub fn set_prefix(&mut self, messages: Vec<Message>) {
         self.prefix = messages;
     }
 
+    /// The pinned prefix messages.
+    pub fn prefix(&self) -> &[Message] {
+        &self.prefix
+    }
+
     /// Add multiple messages in batch. Each message is pushed through the
     /// normal zone management logic.
     pub fn push_messages(&mut self, msgs: impl IntoIterator<Item = Message>) {
@@ -130,15 +179,31 @@ impl ContextLayout {
     pub fn push_message(&mut self, msg: Message) {
         self.recency_window.push_back(msg);
 
-        // If the recency window exceeds the keep_recent limit, move the
-        // oldest message to the middle zone.
-        while self.recency_window.len() > self.keep_recent {
+        // If the recency window exceeds its limit, move the oldest message
+        // to the middle zone.
+        while self.recency_window_overflows() {
             if let Some(old) = self.recency_window.pop_front() {
                 self.middle.push(old);
             }
         }
     }
 
+    /// Whether the recency window is over its message or token limit.
+    fn recency_window_overflows(&self) -> bool {
+        match self.keep_recent_tokens {
+            Some(budget) => {
+                self.recency_window.len() > MIN_KEEP_RECENT
+                    && self
+                        .recency_window
+                        .iter()
+                        .map(|m| message_tokens(m, self.chars_per_token))
+                        .sum::<usize>()
+                        > budget
+            }
+            None => self.recency_window.len() > self.keep_recent,
+        }
+    }
+
     /// Build the complete message list for an API request.
     pub fn to_messages(&self) -> Vec<Message> {
         let mut msgs = self.prefix.clone();
@@ -148,9 +213,7 @@ impl ContextLayout {
             msgs.push(Message::user(format!(
                 "<context_summary>\n{summary}\n</context_summary>"
             )));
-            msgs.push(Message::assistant_text(
-                "I've reviewed the context summary and will continue from where I left off.",
-            ));
+            msgs.push(Message::assistant_text(HISTORY_ACK));
         }
 
         // Add middle zone messages (not yet compacted).
@@ -221,6 +284,42 @@ impl ContextLayout {
         self.compaction_count
     }
 
+    /// Token budget for the compressed history zone.
+    pub fn history_budget(&self) -> usize {
+        self.history_budget
+    }
+
+    /// Whether the compressed history has outgrown its budget and should be
+    /// re-summarized with [`apply_history_recompaction()`](Self::apply_history_recompaction).
+    pub fn history_needs_recompaction(&self) -> bool {
+        self.compressed_history_tokens() > self.history_budget
+    }
+
+    /// Replace the compressed history with a condensed version of itself
+    /// (second-level compaction) and bump the history generation. The
+    /// middle zone and recency window are untouched.
+    pub fn apply_history_recompaction(&mut self, summary: String) {
+        self.compressed_history = Some(summary);
+        self.history_generation += 1;
+    }
+
+    /// Number of second-level compactions of the compressed history.
+    pub fn history_generation(&self) -> u32 {
+        self.history_generation
+    }
+
+    /// Estimated tokens of the compressed history zone, including its
+    /// wrapping tags and acknowledgement message.
+    pub fn compressed_history_tokens(&self) -> usize {
+        self.compressed_history
+            .as_ref()
+            .map(|s| {
+                let summary_msg_chars = format!("<context_summary>\n{s}\n</context_summary>").len();
+                ((summary_msg_chars + HISTORY_ACK.len()) as f64 / self.chars_per_token) as usize
+            })
+            .unwrap_or(0)
+    }
+
     /// Get the current compressed history summary.
     pub fn compressed_history(&self) -> Option<&str> {
         self.compressed_history.as_deref()
@@ -318,6 +417,11 @@ impl ContextLayout {
         self.keep_recent
     }
 
+    /// Get the recency window's token budget, if it is sized by tokens.
+    pub fn keep_recent_tokens(&self) -> Option<usize> {
+        self.keep_recent_tokens
+    }
+
     /// Build a detailed per-message snapshot for context visualization.
     ///
     /// Returns metadata about every message across all zones, in the same
@@ -373,66 +477,48 @@ impl ContextLayout {
     }
 
     pub fn message_details(&self) -> Vec<MessageDetail> {
-        let mut details = Vec::new();
-        let mut idx = 0;
-
-        // Prefix zone.
-        for msg in &self.prefix {
-            details.push(Self::detail_for_message(
-                msg,
-                ContextZone::Prefix,
-                idx,
-                self.chars_per_token,
-            ));
-            idx += 1;
-        }
-
         // Compressed history zone (synthetic messages).
-        if let Some(ref summary) = self.compressed_history {
-            let user_content = format!("<context_summary>\n{summary}\n</context_summary>");
-            let user_msg = Message::user(&user_content);
-            details.push(Self::detail_for_message(
-                &user_msg,
-                ContextZone::CompressedHistory,
-                idx,
-                self.chars_per_token,
-            ));
-            idx += 1;
-
-            let ack_msg = Message::assistant_text(
-                "I've reviewed the context summary and will continue from where I left off.",
+        let history = self.compressed_history.as_ref().map(|summary| {
+            [
+                Message::user(format!("<context_summary>\n{summary}\n</context_summary>")),
+                Message::assistant_text(HISTORY_ACK),
+            ]
+        });
+        let zoned = self
+            .prefix
+            .iter()
+            .map(|m| (m, ContextZone::Prefix))
+            .chain(
+                history
+                    .iter()
+                    .flatten()
+                    .map(|m| (m, ContextZone::CompressedHistory)),
+            )
+            .chain(self.middle.iter().map(|m| (m, ContextZone::Middle)))
+            .chain(
+                self.recency_window
+                    .iter()
+                    .map(|m| (m, ContextZone::Recency)),
             );
-            details.push(Self::detail_for_message(
-                &ack_msg,
-                ContextZone::CompressedHistory,
-                idx,
-                self.chars_per_token,
-            ));
-            idx += 1;
-        }
-
-        // Middle zone.
-        for msg in &self.middle {
-            details.push(Self::detail_for_message(
-                msg,
-                ContextZone::Middle,
-                idx,
-                self.chars_per_token,
-            ));
-            idx += 1;
-        }
 
-        // Recency window.
-        for msg in &self.recency_window {
-            details.push(Self::detail_for_message(
-                msg,
-                ContextZone::Recency,
-                idx,
-                self.chars_per_token,
-            ));
-            idx += 1;
+        // Tool results carry only the call ID; name them after the call.
+        let mut call_names: HashMap<&str, &str> = HashMap::new();
+        let mut details = Vec::new();
+        for (idx, (msg, zone)) in zoned.enumerate() {
+            let mut detail = Self::detail_for_message(msg, zone, idx, self.chars_per_token);
+            for call in msg.tool_calls.iter().flatten() {
+                call_names.insert(&call.id, &call.function.name);
+            }
+            if detail.tool_name.is_none()
+                && let Some(name) = msg
+                    .tool_call_id
+                    .as_deref()
+                    .and_then(|id| call_names.get(id))
+            {
+                detail.tool_name = Some(name.to_string());
+            }
+            details.push(detail);
         }
-
         details
     }
 
@@ -450,7 +536,7 @@ impl ContextLayout {
         let full_content = Self::message_full_content(msg);
         let preview = Self::make_preview(&full_content, 120);
 
-        // Extract tool name from tool_calls or tool_call_id context.
+        // Tool results are named by `message_details`, which sees the calls.
         let tool_name = msg
             .tool_calls
             .as_ref()
@@ -518,18 +604,7 @@ impl ContextLayout {
     pub fn breakdown(&self) -> ContextBreakdown {
         let prefix_tokens = Self::estimate_tokens_for(&self.prefix, self.chars_per_token);
 
-        let compressed_history_tokens = self
-            .compressed_history
-            .as_ref()
-            .map(|s| {
-                // Account for the wrapping <context_summary> tags and assistant ack message
-                let summary_msg_chars = format!("<context_summary>\n{s}\n</context_summary>").len();
-                let ack_chars =
-                    "I've reviewed the context summary and will continue from where I left off."
-                        .len();
-                ((summary_msg_chars + ack_chars) as f64 / self.chars_per_token) as usize
-            })
-            .unwrap_or(0);
+        let compressed_history_tokens = self.compressed_history_tokens();
 
         let middle_tokens = Self::estimate_tokens_for(&self.middle, self.chars_per_token);
 
@@ -548,12 +623,20 @@ impl ContextLayout {
             middle_tokens,
             recency_tokens,
             total_tokens,
+            compaction_count: self.compaction_count,
+            history_generation: self.history_generation,
         }
     }
+
+    /// Per-message token estimates, attributed to roles and tools.
+    pub fn token_report(&self) -> TokenReport {
+        TokenReport::from_details(&self.message_details())
+    }
 }
 
 /// Which zone a message belongs to in the three-zone context layout.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ContextZone {
     /// Pinned prefix: system prompt + original task.
     Prefix,
@@ -615,6 +698,110 @@ pub struct ContextBreakdown {
     pub recency_tokens: usize,
     /// Total estimated tokens across all zones.
     pub total_tokens: usize,
+    /// Number of (first-level) compactions of the middle zone.
+    pub compaction_count: usize,
+    /// Number of second-level compactions re-summarizing the compressed
+    /// history itself.
+    pub history_generation: u32,
+}
+
+/// Where the context's tokens go: one estimate per message, plus totals
+/// per role and per tool, heaviest first.
+///
+/// Backs "what's eating my context" views; [`ContextBreakdown`] only splits
+/// the total by zone.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TokenReport {
+    /// Estimated tokens across all messages.
+    pub total_tokens: usize,
+    /// One entry per message, in `to_messages()` order.
+    pub messages: Vec<MessageTokens>,
+    /// Totals per message role.
+    pub by_role: Vec<TokenShare>,
+    /// Totals per tool, counting both the call and its result.
+    pub by_tool: Vec<TokenShare>,
+}
+
+/// Token estimate for a single message in a [`TokenReport`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MessageTokens {
+    /// Index of the message in the flat `to_messages()` output.
+    pub message_index: usize,
+    /// Which zone the message belongs to.
+    pub zone: ContextZone,
+    /// Message role (system, user, assistant, tool).
+    pub role: String,
+    /// Tool that made the call or produced the result, if any.
+    pub tool_name: Option<String>,
+    /// Estimated token count.
+    pub tokens: usize,
+}
+
+/// Tokens attributed to one role or tool in a [`TokenReport`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TokenShare {
+    /// Role or tool name.
+    pub name: String,
+    /// Estimated tokens.
+    pub tokens: usize,
+    /// Number of messages counted.
+    pub messages: usize,
+}
+
+impl TokenReport {
+    /// Build a report from a [`ContextLayout::message_details`] snapshot.
+    pub fn from_details(details: &[MessageDetail]) -> Self {
+        let messages: Vec<MessageTokens> = details
+            .iter()
+            .map(|d| MessageTokens {
+                message_index: d.message_index,
+                zone: d.zone.clone(),
+                role: d.role.clone(),
+                tool_name: d.tool_name.clone(),
+                tokens: d.estimated_tokens,
+            })
+            .collect();
+        let by_role = Self::shares(messages.iter().map(|m| (m.role.as_str(), m.tokens)));
+        let by_tool = Self::shares(
+            messages
+                .iter()
+                .filter_map(|m| Some((m.tool_name.as_deref()?, m.tokens))),
+        );
+        Self {
+            total_tokens: messages.iter().map(|m| m.tokens).sum(),
+            messages,
+            by_role,
+            by_tool,
+        }
+    }
+
+    /// The `n` largest messages, heaviest first.
+    pub fn heaviest(&self, n: usize) -> Vec<&MessageTokens> {
+        let mut sorted: Vec<&MessageTokens> = self.messages.iter().collect();
+        sorted.sort_by(|a, b| b.tokens.cmp(&a.tokens));
+        sorted.truncate(n);
+        sorted
+    }
+
+    /// Sum `(name, tokens)` pairs per name, heaviest first (ties by name).
+    fn shares<'a>(entries: impl Iterator<Item = (&'a str, usize)>) -> Vec<TokenShare> {
+        let mut totals: HashMap<&str, (usize, usize)> = HashMap::new();
+        for (name, tokens) in entries {
+            let total = totals.entry(name).or_default();
+            total.0 += tokens;
+            total.1 += 1;
+        }
+        let mut shares: Vec<TokenShare> = totals
+            .into_iter()
+            .map(|(name, (tokens, messages))| TokenShare {
+                name: name.to_string(),
+                tokens,
+                messages,
+            })
+            .collect();
+        shares.sort_by(|a, b| b.tokens.cmp(&a.tokens).then_with(|| a.name.cmp(&b.name)));
+        shares
+    }
 }
 
 #[cfg(test)]
@@ -633,7 +820,7 @@ mod tests {
     fn messages_stay_in_recency_window() {
         let mut layout = ContextLayout::new(200_000).with_keep_recent(5);
         for i in 0..5 {
-            layout.push_message(Message::user(&format!("msg {i}")));
+            layout.push_message(Message::user(format!("msg {i}")));
         }
         assert_eq!(layout.recency_window_len(), 5);
         assert_eq!(layout.middle_len(), 0);
@@ -643,12 +830,36 @@ mod tests {
     fn overflow_moves_to_middle() {
         let mut layout = ContextLayout::new(200_000).with_keep_recent(3);
         for i in 0..6 {
-            layout.push_message(Message::user(&format!("msg {i}")));
+            layout.push_message(Message::user(format!("msg {i}")));
         }
         assert_eq!(layout.recency_window_len(), 3);
         assert_eq!(layout.middle_len(), 3);
     }
 
+    #[test]
+    fn token_budget_sizes_the_recency_window() {
+        // ~100 tokens per large result, a few per small one.
+        let mut layout = ContextLayout::new(200_000)
+            .with_keep_recent(3)
+            .with_keep_recent_tokens(250);
+        for i in 0..10 {
+            layout.push_message(Message::tool_result(format!("s{i}"), "ok"));
+        }
+        assert_eq!(layout.recency_window_len(), 10);
+
+        for i in 0..3 {
+            layout.push_message(Message::tool_result(format!("l{i}"), "x".repeat(350)));
+        }
+        // Two large reads fill the budget; everything older moves out.
+        assert_eq!(layout.recency_window_len(), 2);
+        assert_eq!(layout.middle_len(), 11);
+
+        // The newest two messages stay even when they alone exceed it.
+        layout.push_message(Message::tool_result("huge", "x".repeat(5_000)));
+        assert_eq!(layout.recency_window_len(), 2);
+        assert_eq!(layout.keep_recent_tokens(), Some(250));
+    }
+
     #[test]
     fn to_messages_includes_all_zones() {
         let mut layout = ContextLayout::new(200_000).with_keep_recent(2);
@@ -671,7 +882,7 @@ mod tests {
         layout.set_prefix(vec![Message::system("sys")]);
 
         for i in 0..6 {
-            layout.push_message(Message::user(&format!("msg {i}")));
+            layout.push_message(Message::user(format!("msg {i}")));
         }
 
         assert_eq!(layout.middle_len(), 4);
@@ -685,6 +896,23 @@ mod tests {
         assert_eq!(msgs.len(), 5);
     }
 
+    #[test]
+    fn history_recompaction_tracks_generation() {
+        let mut layout = ContextLayout::new(200_000)
+            .with_keep_recent(1)
+            .with_history_budget(50);
+        layout.push_message(Message::user("a"));
+        layout.push_message(Message::user("b"));
+        layout.apply_compaction("x".repeat(400), 1);
+        assert!(layout.history_needs_recompaction());
+
+        layout.apply_history_recompaction("Condensed.".into());
+        assert!(!layout.history_needs_recompaction());
+        assert_eq!(layout.to_messages().len(), 3);
+        let bd = layout.breakdown();
+        assert_eq!((bd.compaction_count, bd.history_generation), (1, 1));
+    }
+
     #[test]
     fn breakdown_reports_per_zone_tokens() {
         let mut layout = ContextLayout::new(200_000).with_keep_recent(2);
@@ -692,7 +920,7 @@ mod tests {
 
         // Push messages so some end up in middle, some in recency
         for i in 0..4 {
-            layout.push_message(Message::user(&format!("message {i}")));
+            layout.push_message(Message::user(format!("message {i}")));
         }
 
         let bd = layout.breakdown();
@@ -765,4 +993,42 @@ mod tests {
         // Verify the old summary is NOT present (no concatenation).
         assert!(!history.contains("First summary."));
     }
+
+    #[test]
+    fn token_report_attributes_roles_and_tools() {
+        let call = |id: &str, name: &str| crate::ToolCall {
+            id: id.into(),
+            call_type: crate::CallType::Function,
+            function: crate::FunctionCallData {
+                name: name.into(),
+                arguments: "{}".into(),
+            },
+        };
+        let mut layout = ContextLayout::new(200_000).with_keep_recent(10);
+        layout.set_prefix(vec![Message::system("You are helpful.")]);
+        layout.push_message(Message::assistant_tool_calls(vec![
+            call("c1", "read_file"),
+            call("c2", "grep"),
+        ]));
+        layout.push_message(Message::tool_result("c1", "x".repeat(700)));
+        layout.push_message(Message::tool_result("c2", "match"));
+
+        let report = layout.token_report();
+        assert_eq!(report.total_tokens, layout.breakdown().total_tokens);
+        assert_eq!(report.messages.len(), 4);
+        // Results are named after their calls.
+        let names: Vec<_> = report
+            .messages
+            .iter()
+            .map(|m| m.tool_name.as_deref())
+            .collect();
+        assert_eq!(
+            names,
+            [None, Some("read_file"), Some("read_file"), Some("grep")]
+        );
+        assert_eq!(report.by_tool[0].name, "read_file");
+        assert_eq!(report.by_tool[0].messages, 2);
+        assert_eq!(report.by_role[0].name, "tool");
+        assert_eq!(report.heaviest(1)[0].message_index, 2);
+    }
 }
@@ -10,8 +10,9 @@
 //!    placeholders. Highest ROI context recovery: no LLM call needed, typically
 //!    frees 10-100x more tokens than model reasoning occupies.
 //!
-//! 3. **[`summarizer`]** — LLM-based incremental summarization of middle-zone
-//!    messages. Used when eviction alone isn't enough.
+//! 3. **[`summarizer`]** — incremental summarization of middle-zone messages,
+//!    LLM-based by default, with local strategies for offline runs. Used when
+//!    eviction alone isn't enough.
 //!
 //! 4. **[`layout`]** — three-zone message architecture:
 //!    - **Pinned prefix** — system prompt + original task. Never modified.
@@ -19,9 +20,14 @@
 //!    - **Compressed history** — running summary of completed work.
 //!    - **Raw recency window** — last N messages, unmodified. Full fidelity.
 //!
-//! All four systems are integrated into the [`Harness`](crate::agent::harness::Harness)
+//! 5. **[`archive`]** — [`HistoryArchive`] keeps the messages each compaction
+//!    dropped on disk so the model can search them with the `recall_history`
+//!    tool.
+//!
+//! All of these are integrated into the [`Harness`](crate::agent::harness::Harness)
 //! loop and run automatically when enabled (the default).
 
+pub mod archive;
 pub mod budget;
 pub mod eviction;
 pub mod file_tracker;
@@ -29,5 +35,9 @@ pub mod layout;
 pub mod summarizer;
 
 // Re-export commonly used items at the module level.
+pub use archive::HistoryArchive;
 pub use budget::{ContextBudget, ContextUsage, DEFAULT_CHARS_PER_TOKEN};
-pub use layout::{ContextBreakdown, ContextZone, MessageDetail, message_tokens};
+pub use layout::{
+    ContextBreakdown, ContextZone, MessageDetail, MessageTokens, TokenReport, TokenShare,
+    message_tokens,
+};
@@ -4,8 +4,31 @@
 //! the whole history. When tool result eviction alone isn't enough, summarizes
 //! the evicted span and merges it with the existing running summary in a single
 //! cheap LLM call. Based on Factory.ai's dual-threshold mechanism.
+//!
+//! Two LLM strategies are available (see [`SummaryStrategy`]): a prose
+//! summary rewritten on every compaction, or a [`StructuredState`] (task,
+//! completed steps, open questions, per-file facts, decisions) that the model
+//! updates field by field and the summarizer merges, so facts from early
+//! compactions aren't paraphrased away by later ones.
+//!
+//! For offline runs, or when an extra LLM call per compaction costs too
+//! much, the local strategies compress history without a model: extractive
+//! truncation, map-reduce over chunks of messages, or a caller-supplied
+//! closure (see [`Summarizer::summarize_locally`]).
+//!
+//! LLM summaries pass a quality guard before they replace history (see
+//! [`Summarizer::check_summary`]): a summary that is too short, drops
+//! tracked files or every task keyword, or is an apology instead of a
+//! summary is rejected, and compaction is retried or skipped.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
 
 use crate::Message;
+use crate::context::DEFAULT_CHARS_PER_TOKEN;
 
 /// The prompt used for summarization. Instructs the model to produce a concise,
 /// factual summary suitable for injecting into a conversation as context.
@@ -25,6 +48,263 @@ Rules:
   cohesive summary. Do not simply append — integrate, deduplicate, and update. The result \
   must be a standalone summary that replaces the existing one entirely.";
 
+/// The prompt used for [`SummaryStrategy::StructuredState`]. The model
+/// returns only what changed; [`StructuredState::merge`] applies it.
+const STRUCTURED_STATE_PROMPT: &str = "\
+Extract the working state from the following conversation messages as a JSON object \
+with these fields:
+- \"task\": the overall task, in one or two sentences (empty string if unchanged)
+- \"completed_steps\": steps completed in these messages, including files modified
+- \"open_questions\": new unresolved questions, blockers, or remaining work
+- \"resolved_questions\": questions from the existing state that these messages resolved, \
+  copied verbatim
+- \"file_facts\": an object mapping file paths to the key facts about each file learned \
+  in these messages (function names, structure, errors); a file's list replaces its \
+  existing facts, so include still-valid existing facts for files you update
+- \"decisions\": decisions made and failed approaches (what was tried and why it failed)
+
+Rules:
+- Only include facts explicitly stated in the messages. Do not infer or extrapolate.
+- Preserve file paths, function names, and error messages verbatim.
+- Do not repeat entries already in the existing state.
+- Reply with only the JSON object: no prose, no code fences.";
+
+/// The prompt used for second-level compaction of a prose summary.
+const RECOMPACTION_PROMPT: &str = "\
+The following running summary of an agent's work has grown too long. Condense it into a \
+shorter summary that replaces it entirely.
+
+Rules:
+- Keep the current task, the plan state and remaining work, unresolved problems, and \
+  file paths verbatim.
+- Collapse completed work into brief outcomes; drop step-by-step detail.
+- Drop failed approaches unless they explain a decision that still matters.
+- Do not add anything that is not in the summary.";
+
+/// The prompt used for second-level compaction of a [`StructuredState`].
+const STRUCTURED_RECOMPACTION_PROMPT: &str = "\
+The following JSON working state of an agent has grown too long. Return a condensed \
+version of the complete state with the same fields (task, completed_steps, \
+open_questions, file_facts, decisions).
+
+Rules:
+- Keep the task, open questions, and file paths verbatim.
+- Merge related completed steps and decisions into fewer, shorter entries.
+- Keep only the most important facts per file; drop files that no longer matter.
+- Reply with only the JSON object: no prose, no code fences.";
+
+/// Openings of replies that are apologies or refusals, not summaries.
+const META_OPENINGS: &[&str] = &[
+    "i'm sorry",
+    "i am sorry",
+    "sorry,",
+    "i apologize",
+    "i cannot",
+    "i can't",
+    "i'm unable",
+    "i am unable",
+    "as an ai",
+    "unfortunately, i",
+];
+
+/// Longest line kept per message by the local strategies, in characters.
+const MAX_DIGEST_LINE: usize = 200;
+
+/// Caller-supplied summarizer for [`SummaryStrategy::Custom`]: given the
+/// existing summary (if any) and the messages being compacted, returns the
+/// new summary that replaces it.
+pub type SummaryFn = Arc<dyn Fn(Option<&str>, &[Message]) -> String + Send + Sync>;
+
+/// How the summarizer compresses evicted history.
+#[derive(Clone, Default)]
+pub enum SummaryStrategy {
+    /// A prose summary, rewritten (merged by the model) on every compaction.
+    #[default]
+    Prose,
+    /// A [`StructuredState`] updated by targeted merges.
+    StructuredState,
+    /// No LLM call: one line per message (truncated), appended to the
+    /// existing summary, dropping the oldest lines past
+    /// [`max_summary_tokens`](SummarizerConfig::max_summary_tokens).
+    Extractive,
+    /// No LLM call: the messages are split into chunks of `chunk_messages`
+    /// and each chunk (and the existing summary) gets an equal share of the
+    /// budget, so the whole span stays represented rather than only its end.
+    MapReduce { chunk_messages: usize },
+    /// No LLM call: the summary comes from a caller-supplied closure.
+    Custom(SummaryFn),
+}
+
+impl SummaryStrategy {
+    /// A [`Custom`](Self::Custom) strategy running `summarize`.
+    pub fn custom(
+        summarize: impl Fn(Option<&str>, &[Message]) -> String + Send + Sync + 'static,
+    ) -> Self {
+        Self::Custom(Arc::new(summarize))
+    }
+
+    /// Whether compaction makes an LLM call.
+    pub fn uses_llm(&self) -> bool {
+        matches!(self, Self::Prose | Self::StructuredState)
+    }
+}
+
+impl fmt::Debug for SummaryStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Prose => f.write_str("Prose"),
+            Self::StructuredState => f.write_str("StructuredState"),
+            Self::Extractive => f.write_str("Extractive"),
+            Self::MapReduce { chunk_messages } => f
+                .debug_struct("MapReduce")
+                .field("chunk_messages", chunk_messages)
+                .finish(),
+            Self::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+/// Structured working state kept by [`SummaryStrategy::StructuredState`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StructuredState {
+    /// The overall task.
+    pub task: String,
+    /// Completed steps, oldest first.
+    pub completed_steps: Vec<String>,
+    /// Unresolved questions and remaining work.
+    pub open_questions: Vec<String>,
+    /// Key facts per file path.
+    pub file_facts: BTreeMap<String, Vec<String>>,
+    /// Decisions and failed approaches.
+    pub decisions: Vec<String>,
+}
+
+/// A model-produced update to a [`StructuredState`].
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct StateUpdate {
+    task: String,
+    completed_steps: Vec<String>,
+    open_questions: Vec<String>,
+    resolved_questions: Vec<String>,
+    file_facts: BTreeMap<String, Vec<String>>,
+    decisions: Vec<String>,
+}
+
+impl StructuredState {
+    /// Apply a model-produced update (JSON, optionally fenced or slightly
+    /// malformed): a non-empty task replaces the old one, list entries are
+    /// appended without duplicates, resolved questions are removed, and each
+    /// updated file's facts replace that file's previous facts.
+    pub fn merge(&mut self, update_json: &str) -> Result<(), String> {
+        let healed = crate::tools::repair::heal_arguments(update_json);
+        let update: StateUpdate = serde_json::from_str(healed.as_deref().unwrap_or(update_json))
+            .map_err(|e| format!("Invalid structured state update: {e}"))?;
+
+        if !update.task.trim().is_empty() {
+            self.task = update.task;
+        }
+        append_unique(&mut self.completed_steps, update.completed_steps);
+        self.open_questions
+            .retain(|q| !update.resolved_questions.contains(q));
+        append_unique(&mut self.open_questions, update.open_questions);
+        for (path, facts) in update.file_facts {
+            if facts.is_empty() {
+                self.file_facts.remove(&path);
+            } else {
+                self.file_facts.insert(path, facts);
+            }
+        }
+        append_unique(&mut self.decisions, update.decisions);
+        Ok(())
+    }
+
+    /// Render the state as the compressed history text shown to the agent.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        if !self.task.is_empty() {
+            out.push_str(&format!("## Task\n{}\n", self.task));
+        }
+        let mut section = |title: &str, items: &[String]| {
+            if !items.is_empty() {
+                out.push_str(&format!("\n## {title}\n"));
+                for item in items {
+                    out.push_str(&format!("- {item}\n"));
+                }
+            }
+        };
+        section("Completed steps", &self.completed_steps);
+        section("Open questions", &self.open_questions);
+        section("Decisions", &self.decisions);
+        if !self.file_facts.is_empty() {
+            out.push_str("\n## Files\n");
+            for (path, facts) in &self.file_facts {
+                out.push_str(&format!("- {path}\n"));
+                for fact in facts {
+                    out.push_str(&format!("  - {fact}\n"));
+                }
+            }
+        }
+        out.trim_start().to_string()
+    }
+}
+
+/// One line per message: `[role] text`, whitespace collapsed and
+/// truncated, or the tools called for tool-call-only messages.
+fn digest(span: &[Message]) -> Vec<String> {
+    span.iter()
+        .filter_map(|msg| {
+            let text = match (&msg.content, &msg.tool_calls) {
+                (Some(content), _) if !content.trim().is_empty() => {
+                    content.split_whitespace().collect::<Vec<_>>().join(" ")
+                }
+                (_, Some(calls)) if !calls.is_empty() => {
+                    let names: Vec<&str> = calls.iter().map(|c| c.function.name.as_str()).collect();
+                    format!("called {}", names.join(", "))
+                }
+                _ => return None,
+            };
+            Some(truncate_chars(
+                &format!("[{}] {text}", msg.role),
+                MAX_DIGEST_LINE,
+            ))
+        })
+        .collect()
+}
+
+/// The longest suffix of `lines` that fits in `max_chars` (joined by
+/// newlines).
+fn keep_last(lines: &[String], max_chars: usize) -> String {
+    let mut used = 0;
+    let mut start = lines.len();
+    for (i, line) in lines.iter().enumerate().rev() {
+        let len = line.chars().count() + 1;
+        if used + len > max_chars + 1 {
+            break;
+        }
+        used += len;
+        start = i;
+    }
+    lines[start..].join("\n")
+}
+
+fn truncate_chars(s: &str, max_chars: usize) -> String {
+    if s.chars().count() <= max_chars {
+        return s.to_string();
+    }
+    let kept: String = s.chars().take(max_chars.saturating_sub(3)).collect();
+    format!("{kept}...")
+}
+
+fn append_unique(existing: &mut Vec<String>, new: Vec<String>) {
+    for item in new {
+        if !item.trim().is_empty() && !existing.contains(&item) {
+            existing.push(item);
+        }
+    }
+}
+
 /// Configuration for incremental summarization.
 #[derive(Debug, Clone)]
 pub struct SummarizerConfig {
@@ -40,6 +320,20 @@ pub struct SummarizerConfig {
     /// `## Compaction Instructions` sections. Appended to the summarizer
     /// system prompt when present.
     pub compaction_instructions: Option<String>,
+    /// How history is compressed. Default: [`SummaryStrategy::Prose`].
+    pub strategy: SummaryStrategy,
+    /// Target size of a second-level compaction, as a fraction of the
+    /// compressed history budget. Default: `0.5`.
+    pub recompaction_target_fraction: f64,
+    /// Check LLM summaries with [`Summarizer::check_summary`] before they
+    /// replace history. Default: `true`.
+    pub quality_guard: bool,
+    /// Shortest acceptable summary, in characters (capped at a quarter of
+    /// the summarized messages' length). Default: `200`.
+    pub min_summary_chars: usize,
+    /// Further attempts after a summary is rejected before compaction is
+    /// skipped. Default: `1`.
+    pub quality_retries: u32,
 }
 
 impl Default for SummarizerConfig {
@@ -49,10 +343,24 @@ impl Default for SummarizerConfig {
             max_summary_tokens: 2048,
             min_reduction_fraction: 0.20,
             compaction_instructions: None,
+            strategy: SummaryStrategy::default(),
+            recompaction_target_fraction: 0.5,
+            quality_guard: true,
+            min_summary_chars: 200,
+            quality_retries: 1,
         }
     }
 }
 
+/// What a summary must not lose: see [`Summarizer::check_summary`].
+#[derive(Debug, Clone, Default)]
+pub struct PinnedEntities {
+    /// Tracked file paths.
+    pub files: Vec<String>,
+    /// Significant lowercase words from the task.
+    pub keywords: Vec<String>,
+}
+
 /// State for the incremental summarizer.
 #[derive(Debug)]
 pub struct Summarizer {
@@ -60,6 +368,8 @@ pub struct Summarizer {
     pub summary: Option<String>,
     /// Index of the last compaction boundary in the message list.
     pub boundary_index: usize,
+    /// Structured state ([`SummaryStrategy::StructuredState`] only).
+    pub state: StructuredState,
     /// Configuration.
     pub config: SummarizerConfig,
 }
@@ -69,6 +379,7 @@ impl Summarizer {
         Self {
             summary: None,
             boundary_index: 0,
+            state: StructuredState::default(),
             config,
         }
     }
@@ -78,9 +389,14 @@ impl Summarizer {
     /// Returns a (system, user) message pair suitable for a one-shot LLM call.
     pub fn build_summarization_request(&self, span: &[Message]) -> (String, String) {
         let mut content = String::new();
+        let structured = matches!(self.config.strategy, SummaryStrategy::StructuredState);
 
-        // Include existing summary for merge context.
-        if let Some(ref existing) = self.summary {
+        // Include existing summary (or state) for merge context.
+        if structured {
+            content.push_str("=== EXISTING STATE ===\n");
+            content.push_str(&serde_json::to_string_pretty(&self.state).unwrap_or_default());
+            content.push_str("\n\n=== NEW MESSAGES ===\n");
+        } else if let Some(ref existing) = self.summary {
             content.push_str("=== EXISTING SUMMARY ===\n");
             content.push_str(existing);
             content.push_str("\n\n=== NEW MESSAGES TO SUMMARIZE ===\n");
@@ -94,10 +410,15 @@ impl Summarizer {
             content.push_str(&format!("[{role}]: {text}\n\n"));
         }
 
+        let prompt = if structured {
+            STRUCTURED_STATE_PROMPT
+        } else {
+            SUMMARIZATION_PROMPT
+        };
         let sys = if let Some(ref ci) = self.config.compaction_instructions {
-            format!("{SUMMARIZATION_PROMPT}\n\nProject-specific compaction instructions:\n{ci}")
+            format!("{prompt}\n\nProject-specific compaction instructions:\n{ci}")
         } else {
-            SUMMARIZATION_PROMPT.to_string()
+            prompt.to_string()
         };
 
         (sys, content)
@@ -109,6 +430,189 @@ impl Summarizer {
         self.boundary_index = new_boundary;
     }
 
+    /// Build the (system, user) prompt pair for a second-level compaction
+    /// condensing the current compressed history (`summary`) to about
+    /// `target_tokens`.
+    pub fn build_recompaction_request(
+        &self,
+        summary: &str,
+        target_tokens: usize,
+    ) -> (String, String) {
+        let (prompt, content) = match self.config.strategy {
+            SummaryStrategy::StructuredState => (
+                STRUCTURED_RECOMPACTION_PROMPT,
+                serde_json::to_string_pretty(&self.state).unwrap_or_default(),
+            ),
+            _ => (RECOMPACTION_PROMPT, summary.to_string()),
+        };
+        let sys = format!("{prompt}\n\nThe result must be at most about {target_tokens} tokens.");
+        (sys, content)
+    }
+
+    /// Turn a second-level compaction response into the new compressed
+    /// history. For [`SummaryStrategy::StructuredState`] the response is a
+    /// complete state that replaces the current one.
+    pub fn summary_from_recompaction(&mut self, response: &str) -> Result<String, String> {
+        match self.config.strategy {
+            SummaryStrategy::StructuredState => {
+                let healed = crate::tools::repair::heal_arguments(response);
+                self.state = serde_json::from_str(healed.as_deref().unwrap_or(response))
+                    .map_err(|e| format!("Invalid condensed structured state: {e}"))?;
+                Ok(self.state.render())
+            }
+            _ => Ok(response.trim().to_string()),
+        }
+    }
+
+    /// Turn the summarization response into the new compressed history:
+    /// the response itself for [`SummaryStrategy::Prose`], or the rendered
+    /// state after merging the response for
+    /// [`SummaryStrategy::StructuredState`]. On a malformed update the state
+    /// is left unchanged.
+    pub fn summary_from_response(&mut self, response: &str) -> Result<String, String> {
+        match self.config.strategy {
+            SummaryStrategy::StructuredState => {
+                let mut state = self.state.clone();
+                state.merge(response)?;
+                self.state = state;
+                Ok(self.state.render())
+            }
+            _ => Ok(response.to_string()),
+        }
+    }
+
+    /// Compress `span` without a model, merging it with the existing
+    /// summary and `notes` (preservation notes that must survive). Returns
+    /// `None` for the LLM strategies.
+    pub fn summarize_locally(&self, span: &[Message], notes: &[String]) -> Option<String> {
+        let budget = self.budget_chars();
+        let existing = self.summary.as_deref().filter(|s| !s.is_empty());
+        let summary = match &self.config.strategy {
+            SummaryStrategy::Prose | SummaryStrategy::StructuredState => return None,
+            SummaryStrategy::Extractive => {
+                let lines: Vec<String> = existing
+                    .into_iter()
+                    .flat_map(str::lines)
+                    .map(str::to_string)
+                    .chain(digest(span))
+                    .collect();
+                keep_last(&lines, budget)
+            }
+            SummaryStrategy::MapReduce { chunk_messages } => {
+                let parts: Vec<String> = existing
+                    .map(str::to_string)
+                    .into_iter()
+                    .chain(
+                        span.chunks((*chunk_messages).max(1))
+                            .map(|chunk| digest(chunk).join("\n")),
+                    )
+                    .collect();
+                let share = budget / parts.len().max(1);
+                parts
+                    .iter()
+                    .map(|part| truncate_chars(part, share))
+                    .collect::<Vec<_>>()
+                    .join("\n\n")
+            }
+            SummaryStrategy::Custom(summarize) => summarize(existing, span),
+        };
+        if notes.is_empty() {
+            return Some(summary);
+        }
+        Some(format!("{summary}\n\n## Preserved\n{}", notes.join("\n")))
+    }
+
+    /// Condense the compressed history to about `target_tokens` without a
+    /// model, keeping its most recent lines. Returns `None` for the LLM
+    /// strategies.
+    pub fn recompact_locally(&self, summary: &str, target_tokens: usize) -> Option<String> {
+        if self.config.strategy.uses_llm() {
+            return None;
+        }
+        let lines: Vec<String> = summary.lines().map(str::to_string).collect();
+        Some(keep_last(
+            &lines,
+            (target_tokens as f64 * DEFAULT_CHARS_PER_TOKEN) as usize,
+        ))
+    }
+
+    /// Check a summary of `span` before it replaces the history, returning
+    /// why it was rejected. A summary is rejected when it:
+    ///
+    /// - opens with an apology or refusal ("I'm sorry", "As an AI", ...),
+    /// - is shorter than [`min_summary_chars`](SummarizerConfig::min_summary_chars)
+    ///   (or a quarter of the span's length, if smaller),
+    /// - leaves out a pinned file that the span mentions (by path or file
+    ///   name), or
+    /// - mentions none of the pinned keywords that the span mentions.
+    pub fn check_summary(
+        &self,
+        summary: &str,
+        span: &[Message],
+        pinned: &PinnedEntities,
+    ) -> Result<(), String> {
+        let summary = summary.trim();
+        let lower = summary.to_lowercase();
+        if let Some(opening) = META_OPENINGS.iter().find(|o| lower.starts_with(*o)) {
+            return Err(format!(
+                "the reply opens with \"{opening}\" instead of a summary"
+            ));
+        }
+
+        let span_text: String = span
+            .iter()
+            .filter_map(|m| m.content.as_deref())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let min_chars = self
+            .config
+            .min_summary_chars
+            .min(span_text.chars().count() / 4);
+        let chars = summary.chars().count();
+        if chars < min_chars {
+            return Err(format!(
+                "the summary is {chars} characters, below the minimum of {min_chars}"
+            ));
+        }
+
+        let dropped: Vec<&str> = pinned
+            .files
+            .iter()
+            .map(String::as_str)
+            .filter(|path| span_text.contains(path))
+            .filter(|path| {
+                let name = path.rsplit('/').next().unwrap_or(path);
+                !summary.contains(path) && !summary.contains(name)
+            })
+            .collect();
+        if !dropped.is_empty() {
+            return Err(format!(
+                "the summary drops tracked files: {}",
+                dropped.join(", ")
+            ));
+        }
+
+        let span_lower = span_text.to_lowercase();
+        let keywords: Vec<&str> = pinned
+            .keywords
+            .iter()
+            .map(String::as_str)
+            .filter(|k| span_lower.contains(k))
+            .collect();
+        if !keywords.is_empty() && !keywords.iter().any(|k| lower.contains(k)) {
+            return Err(format!(
+                "the summary mentions none of the task's keywords ({})",
+                keywords.join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    /// `max_summary_tokens` in characters.
+    fn budget_chars(&self) -> usize {
+        (self.config.max_summary_tokens as f64 * DEFAULT_CHARS_PER_TOKEN) as usize
+    }
+
     /// Get the model to use for summarization.
     pub fn summary_model<'a>(&'a self, main_model: &'a str) -> &'a str {
         self.config.model.as_deref().unwrap_or(main_model)
@@ -155,6 +659,194 @@ mod tests {
         assert_eq!(summarizer.boundary_index, 5);
     }
 
+    #[test]
+    fn structured_state_merges_targeted_updates() {
+        let mut summarizer = Summarizer::new(SummarizerConfig {
+            strategy: SummaryStrategy::StructuredState,
+            ..SummarizerConfig::default()
+        });
+        let first = r#"{
+            "task": "Fix the parser bug",
+            "completed_steps": ["Read src/parse.rs"],
+            "open_questions": ["Why does parse_expr panic?"],
+            "file_facts": {"src/parse.rs": ["parse_expr at line 40"]}
+        }"#;
+        summarizer.summary_from_response(first).unwrap();
+
+        let second = "```json\n{\"completed_steps\": [\"Read src/parse.rs\", \"Edited src/parse.rs\"], \
+             \"resolved_questions\": [\"Why does parse_expr panic?\"], \
+             \"file_facts\": {\"src/lex.rs\": [\"Token enum\"]}, \
+             \"decisions\": [\"Return Err instead of panicking\"]}\n```";
+        let rendered = summarizer.summary_from_response(second).unwrap();
+
+        let state = &summarizer.state;
+        assert_eq!(state.task, "Fix the parser bug");
+        assert_eq!(state.completed_steps.len(), 2);
+        assert!(state.open_questions.is_empty());
+        assert_eq!(state.file_facts.len(), 2);
+        assert!(rendered.starts_with("## Task\nFix the parser bug"));
+        assert!(rendered.contains("  - parse_expr at line 40"));
+
+        let (system, user) = summarizer.build_summarization_request(&[Message::user("next")]);
+        assert!(system.contains("JSON object"));
+        assert!(user.contains("EXISTING STATE"));
+        assert!(user.contains("Return Err instead of panicking"));
+    }
+
+    #[test]
+    fn malformed_structured_update_keeps_state() {
+        let mut summarizer = Summarizer::new(SummarizerConfig {
+            strategy: SummaryStrategy::StructuredState,
+            ..SummarizerConfig::default()
+        });
+        summarizer
+            .summary_from_response(r#"{"task": "Port the CLI"}"#)
+            .unwrap();
+        assert!(summarizer.summary_from_response("no json here").is_err());
+        assert_eq!(summarizer.state.task, "Port the CLI");
+    }
+
+    #[test]
+    fn recompaction_replaces_structured_state() {
+        let mut summarizer = Summarizer::new(SummarizerConfig {
+            strategy: SummaryStrategy::StructuredState,
+            ..SummarizerConfig::default()
+        });
+        summarizer
+            .summary_from_response(r#"{"task": "Port the CLI", "decisions": ["a", "b", "c"]}"#)
+            .unwrap();
+
+        let (system, user) = summarizer.build_recompaction_request("", 300);
+        assert!(system.contains("about 300 tokens"));
+        assert!(user.contains("\"decisions\""));
+
+        let rendered = summarizer
+            .summary_from_recompaction(r#"{"task": "Port the CLI", "decisions": ["a-c"]}"#)
+            .unwrap();
+        assert_eq!(summarizer.state.decisions, vec!["a-c".to_string()]);
+        assert!(rendered.contains("- a-c"));
+    }
+
+    fn local(strategy: SummaryStrategy, max_summary_tokens: u32) -> Summarizer {
+        Summarizer::new(SummarizerConfig {
+            strategy,
+            max_summary_tokens,
+            ..SummarizerConfig::default()
+        })
+    }
+
+    #[test]
+    fn extractive_keeps_the_most_recent_lines() {
+        let mut summarizer = local(SummaryStrategy::Extractive, 20);
+        summarizer.summary = Some("[user] Port the CLI".into());
+        let span = vec![
+            Message::assistant_text("Reading   src/main.rs\nnow"),
+            Message::tool_result("c1", "x".repeat(500)),
+            Message::user("Also update the README"),
+        ];
+        let summary = summarizer.summarize_locally(&span, &[]).unwrap();
+        assert!(summary.ends_with("[user] Also update the README"));
+        assert!(summary.chars().count() <= 70);
+        assert!(!summary.contains("Port the CLI"));
+
+        let roomy = local(SummaryStrategy::Extractive, 2048);
+        let summary = roomy.summarize_locally(&span[..1], &["keep a.rs".into()]);
+        assert_eq!(
+            summary.as_deref(),
+            Some("[assistant] Reading src/main.rs now\n\n## Preserved\nkeep a.rs")
+        );
+    }
+
+    #[test]
+    fn map_reduce_represents_every_chunk() {
+        let summarizer = local(SummaryStrategy::MapReduce { chunk_messages: 2 }, 60);
+        let span: Vec<Message> = (0..6)
+            .map(|i| Message::user(format!("step {i} {}", "detail ".repeat(20))))
+            .collect();
+        let summary = summarizer.summarize_locally(&span, &[]).unwrap();
+        for i in [0, 2, 4] {
+            assert!(summary.contains(&format!("[user] step {i}")), "{summary}");
+        }
+        assert_eq!(summary.split("\n\n").count(), 3);
+    }
+
+    #[test]
+    fn custom_strategy_gets_the_existing_summary() {
+        let mut summarizer = local(
+            SummaryStrategy::custom(|existing, span| {
+                format!("{} +{}", existing.unwrap_or("none"), span.len())
+            }),
+            2048,
+        );
+        let span = [Message::user("a"), Message::user("b")];
+        assert_eq!(
+            summarizer.summarize_locally(&span, &[]).as_deref(),
+            Some("none +2")
+        );
+        summarizer.summary = Some("seen 4".into());
+        assert_eq!(
+            summarizer.summarize_locally(&span, &[]).as_deref(),
+            Some("seen 4 +2")
+        );
+        assert!(!summarizer.config.strategy.uses_llm());
+        assert_eq!(format!("{:?}", summarizer.config.strategy), "Custom(..)");
+    }
+
+    #[test]
+    fn llm_strategies_are_not_local() {
+        let summarizer = Summarizer::new(SummarizerConfig::default());
+        assert!(
+            summarizer
+                .summarize_locally(&[Message::user("a")], &[])
+                .is_none()
+        );
+        assert!(summarizer.recompact_locally("a\nb", 1).is_none());
+
+        let extractive = local(SummaryStrategy::Extractive, 2048);
+        assert_eq!(
+            extractive
+                .recompact_locally("old line\nnewer line", 3)
+                .as_deref(),
+            Some("newer line")
+        );
+    }
+
+    #[test]
+    fn quality_guard_rejects_lossy_summaries() {
+        let summarizer = Summarizer::new(SummarizerConfig {
+            min_summary_chars: 40,
+            ..SummarizerConfig::default()
+        });
+        let span = vec![
+            Message::user("Fix the parser panic in src/parse.rs"),
+            Message::tool_result("c1", "x".repeat(400)),
+        ];
+        let pinned = PinnedEntities {
+            files: vec!["src/parse.rs".into(), "src/lex.rs".into()],
+            keywords: vec!["parser".into(), "panic".into()],
+        };
+        let check = |summary: &str| summarizer.check_summary(summary, &span, &pinned);
+
+        let good = "Read parse.rs; the parser panics on empty input. Next: return Err.";
+        assert_eq!(check(good), Ok(()));
+
+        let err = check("I'm sorry, but I can't summarize this conversation for you.");
+        assert!(err.unwrap_err().contains("i'm sorry"));
+        assert!(
+            check("Read parse.rs.")
+                .unwrap_err()
+                .contains("below the minimum")
+        );
+        // src/lex.rs never came up, so only src/parse.rs is required.
+        let err = check("Looked into the parser panic and planned a fix for it today.");
+        assert_eq!(
+            err.unwrap_err(),
+            "the summary drops tracked files: src/parse.rs"
+        );
+        let err = check("Read src/parse.rs in full and found the entry point at line 3.");
+        assert!(err.unwrap_err().contains("none of the task's keywords"));
+    }
+
     #[test]
     fn preserves_full_content_in_request() {
         let summarizer = Summarizer::new(SummarizerConfig::default());
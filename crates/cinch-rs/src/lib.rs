@@ -113,6 +113,35 @@
 //! | [`context`] | [`ContextBudget`](context::ContextBudget), three-zone message layout, tool result eviction, summarization |
 //! | [`api`] | Model routing, SSE streaming, retry with backoff, cost tracking |
 //!
+//! # Feature flags
+//!
+//! All features except `wasm-tools` and `scripting` are on by default. A
+//! minimal embedder running a single tool-use loop can use
+//! `default-features = false` and add back what it needs:
+//!
+//! | Feature | Enables |
+//! |---------|---------|
+//! | `cli` | The `cinch` binary (pulls in `clap`) |
+//! | `ui` | The `ui` module: shared UI state for TUI and web front-ends |
+//! | `checkpoint` | `agent::session` and `agent::checkpoint`: manifests and per-round checkpoints |
+//! | `memory` | `agent::memory`: MEMORY.md prompt, index loading, consolidation |
+//! | `web-search` | The `web_search` common tool |
+//! | `config-sources` | `HarnessConfig::from_sources()` (pulls in `figment`) |
+//! | `schema-reexport` | `cinch_rs::schemars` |
+//! | `macros` | `#[derive(CinchTool)]` (`cinch_rs::CinchTool`) |
+//! | `wasm-tools` | `tools::wasm`: sandboxed WebAssembly tool plugins (off by default) |
+//! | `scripting` | `tools::script`: Rhai tools and lifecycle hooks (off by default) |
+//!
+//! # WebAssembly
+//!
+//! The library compiles for `wasm32-unknown-unknown`, so the agent loop can
+//! run in the browser or an edge worker with JS-provided tools. There the
+//! client has no built-in HTTP stack: build it with
+//! [`OpenRouterClient::with_transport()`] and a `fetch`-backed
+//! [`HttpTransport`](api::transport::HttpTransport). The filesystem and
+//! shell tools ([`tools::common`] and `ToolSet::with_common_tools()`) are
+//! native-only, and clocks and timers go through [`platform`].
+//!
 //! # Design principles
 //!
 //! 1. **Opinionated defaults.** The harness makes decisions so callers don't
@@ -141,25 +170,37 @@
 pub mod agent;
 pub mod api;
 pub mod context;
+pub mod platform;
 pub mod prelude;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
 pub mod tools;
+#[cfg(feature = "ui")]
 pub mod ui;
 
+use crate::platform::Instant;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::time::{Duration, Instant};
+use std::sync::Arc;
 use tracing::{debug, trace};
 
 // Re-export pseudo-tools for convenience.
-pub use tools::core::{ThinkTool, TodoTool};
+pub use tools::core::{ScratchpadTool, ThinkTool, TodoTool};
 
 // Re-export schemars for downstream crates.
+#[cfg(feature = "schema-reexport")]
 pub use schemars;
 
+// Derive macro for typed tools.
+#[cfg(feature = "macros")]
+pub use cinch_macros::CinchTool;
+
 // ── Constants ──────────────────────────────────────────────────────
 
 pub const OPENROUTER_URL: &str = "https://openrouter.ai/api/v1/chat/completions";
 
+pub const OPENROUTER_EMBEDDINGS_URL: &str = "https://openrouter.ai/api/v1/embeddings";
+
 /// Default model for all LLM calls.
 pub const DEFAULT_MODEL: &str = "z-ai/glm-5";
 
@@ -200,8 +241,13 @@ pub fn json_schema_for<T: JsonSchema>() -> serde_json::Value {
 
 /// Chat completion request body. Superset of fields supported by the
 /// OpenRouter API — unused optional fields are omitted from serialization.
-#[derive(Serialize, Debug, Default)]
+#[derive(Serialize, Debug, Default, Clone)]
 pub struct ChatRequest {
+    /// Round span id used to key [`DebugRecorder`](api::DebugRecorder)
+    /// recordings. Never sent to the API.
+    #[serde(skip)]
+    pub span_id: Option<String>,
+
     // Model selection — use `model` for a single model, or `models` + `route`
     // for a fallback chain.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -307,19 +353,94 @@ pub enum ResponseFormatType {
 }
 
 /// JSON output mode.
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone)]
 pub struct ResponseFormat {
     #[serde(rename = "type")]
     pub fmt_type: ResponseFormatType,
 }
 
 /// Provider routing preferences.
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone, Default)]
 pub struct ProviderPreferences {
+    /// Providers to try first, in order.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub order: Option<Vec<String>>,
+    /// Whether other providers may serve the request when the preferred
+    /// ones are unavailable.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub allow_fallbacks: Option<bool>,
+    /// Providers to skip for this request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ignore: Option<Vec<String>>,
+    /// Whether providers that store or train on inputs may serve the request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_collection: Option<DataCollection>,
+    /// Only route to providers with a zero data retention policy.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zdr: Option<bool>,
+    /// Skip providers priced above these limits.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_price: Option<MaxPrice>,
+    /// Rank providers by price, throughput, or latency instead of the
+    /// default load balancing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<ProviderSort>,
+    /// Only route to providers that support every request parameter
+    /// (tools, response format, ...).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub require_parameters: Option<bool>,
+}
+
+/// Price ceilings for [`ProviderPreferences::max_price`]. Token prices are
+/// in USD per million tokens.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct MaxPrice {
+    /// Maximum price per million prompt tokens.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<f64>,
+    /// Maximum price per million completion tokens.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completion: Option<f64>,
+    /// Maximum price per request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request: Option<f64>,
+}
+
+/// Provider ranking for [`ProviderPreferences::sort`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderSort {
+    /// Cheapest first.
+    Price,
+    /// Highest tokens per second first; suits interactive use.
+    Throughput,
+    /// Lowest time to first token first.
+    Latency,
+}
+
+impl std::str::FromStr for ProviderSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "price" => Ok(Self::Price),
+            "throughput" => Ok(Self::Throughput),
+            "latency" => Ok(Self::Latency),
+            other => Err(format!(
+                "unknown provider sort '{other}' (expected price, throughput, or latency)"
+            )),
+        }
+    }
+}
+
+/// Provider data collection policy for [`ProviderPreferences`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DataCollection {
+    /// Any provider (the API default).
+    Allow,
+    /// Exclude providers that may store or train on inputs.
+    Deny,
 }
 
 // ── Plugin types ───────────────────────────────────────────────────
@@ -476,30 +597,43 @@ impl CacheControl {
     }
 }
 
-/// A content part within a message, used for prompt caching.
+/// A content part within a message, used for prompt caching and images.
 ///
 /// When a message has `cache_control` set, its content is serialized as
 /// `[{"type": "text", "text": "...", "cache_control": {...}}]` instead
 /// of a plain string. This is the format expected by OpenRouter for
-/// Anthropic/Gemini prompt caching.
+/// Anthropic/Gemini prompt caching. Messages with
+/// [`images`](Message::images) add `{"type": "image_url", ...}` parts.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ContentPart {
-    /// Content type. Currently always `"text"`.
+    /// Content type: `"text"` or `"image_url"`.
     #[serde(rename = "type")]
     pub part_type: String,
-    /// The text content.
+    /// The text content. Empty for image parts.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub text: String,
+    /// The image, for `"image_url"` parts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<ImageUrl>,
     /// Optional cache control directive.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub cache_control: Option<CacheControl>,
 }
 
+/// Location of an image content part: an `https://` URL or a base64
+/// `data:` URL.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ImageUrl {
+    pub url: String,
+}
+
 impl ContentPart {
     /// Create a text content part.
     pub fn text(text: impl Into<String>) -> Self {
         Self {
             part_type: "text".to_string(),
             text: text.into(),
+            image_url: None,
             cache_control: None,
         }
     }
@@ -509,9 +643,20 @@ impl ContentPart {
         Self {
             part_type: "text".to_string(),
             text: text.into(),
+            image_url: None,
             cache_control: Some(cache_control),
         }
     }
+
+    /// Create an image content part from an `https://` or `data:` URL.
+    pub fn image(url: impl Into<String>) -> Self {
+        Self {
+            part_type: "image_url".to_string(),
+            text: String::new(),
+            image_url: Some(ImageUrl { url: url.into() }),
+            cache_control: None,
+        }
+    }
 }
 
 // ── Message types ──────────────────────────────────────────────────
@@ -558,6 +703,11 @@ pub struct Message {
     /// array of content parts with the cache annotation attached.
     #[serde(skip)]
     pub cache_control: Option<CacheControl>,
+    /// Image URLs (`https://` or base64 `data:` URLs) sent after the text
+    /// as image content parts. Like other non-text parts, they are not
+    /// restored when a message is deserialized.
+    #[serde(skip)]
+    pub images: Vec<String>,
 }
 
 /// Deserialize `content` from either a plain string or an array of content parts.
@@ -598,6 +748,9 @@ where
         fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
             let mut text = String::new();
             while let Some(part) = seq.next_element::<ContentPart>()? {
+                if part.text.is_empty() {
+                    continue;
+                }
                 if !text.is_empty() {
                     text.push('\n');
                 }
@@ -622,7 +775,7 @@ impl Serialize for Message {
 
         // Count fields to emit.
         let mut field_count = 1; // role
-        if self.content.is_some() || self.cache_control.is_some() {
+        if self.content.is_some() || self.cache_control.is_some() || !self.images.is_empty() {
             field_count += 1;
         }
         if self.tool_calls.is_some() {
@@ -635,14 +788,21 @@ impl Serialize for Message {
         let mut map = serializer.serialize_map(Some(field_count))?;
         map.serialize_entry("role", &self.role)?;
 
-        if let Some(ref cache) = self.cache_control {
+        if !self.images.is_empty() {
+            // Text (with any cache annotation) followed by the images.
+            let mut parts = Vec::with_capacity(self.images.len() + 1);
+            if let Some(ref text) = self.content {
+                parts.push(ContentPart {
+                    cache_control: self.cache_control.clone(),
+                    ..ContentPart::text(text.clone())
+                });
+            }
+            parts.extend(self.images.iter().map(ContentPart::image));
+            map.serialize_entry("content", &parts)?;
+        } else if let Some(ref cache) = self.cache_control {
             // Serialize content as array of parts with cache annotation.
             if let Some(ref text) = self.content {
-                let parts = vec![ContentPart {
-                    part_type: "text".to_string(),
-                    text: text.clone(),
-                    cache_control: Some(cache.clone()),
-                }];
+                let parts = vec![ContentPart::text_cached(text.clone(), cache.clone())];
                 map.serialize_entry("content", &parts)?;
             }
         } else if let Some(ref content) = self.content {
@@ -668,6 +828,7 @@ impl Message {
             tool_calls: None,
             tool_call_id: None,
             cache_control: None,
+            images: Vec::new(),
         }
     }
 
@@ -678,6 +839,7 @@ impl Message {
             tool_calls: None,
             tool_call_id: None,
             cache_control: None,
+            images: Vec::new(),
         }
     }
 
@@ -688,6 +850,7 @@ impl Message {
             tool_calls: None,
             tool_call_id: None,
             cache_control: None,
+            images: Vec::new(),
         }
     }
 
@@ -698,6 +861,7 @@ impl Message {
             tool_calls: Some(calls),
             tool_call_id: None,
             cache_control: None,
+            images: Vec::new(),
         }
     }
 
@@ -708,6 +872,7 @@ impl Message {
             tool_calls: None,
             tool_call_id: Some(call_id.into()),
             cache_control: None,
+            images: Vec::new(),
         }
     }
 
@@ -730,6 +895,13 @@ impl Message {
     pub fn clear_cache_control(&mut self) {
         self.cache_control = None;
     }
+
+    /// Attach images (`https://` or base64 `data:` URLs) to this message.
+    /// Requires a vision-capable model.
+    pub fn with_images(mut self, images: Vec<String>) -> Self {
+        self.images = images;
+        self
+    }
 }
 
 // ── Tool types ─────────────────────────────────────────────────────
@@ -808,6 +980,23 @@ struct RawChatResponse {
     error: Option<ApiErrorResponse>,
     #[serde(default)]
     usage: Option<UsageInfo>,
+    #[serde(default)]
+    provider: Option<String>,
+}
+
+/// Raw embeddings response (internal deserialization target).
+#[derive(Deserialize, Debug)]
+struct RawEmbeddingResponse {
+    #[serde(default)]
+    data: Vec<RawEmbedding>,
+    error: Option<ApiErrorResponse>,
+}
+
+#[derive(Deserialize, Debug)]
+struct RawEmbedding {
+    embedding: Vec<f32>,
+    #[serde(default)]
+    index: usize,
 }
 
 #[derive(Deserialize, Debug)]
@@ -839,6 +1028,8 @@ pub struct ChatCompletion {
     pub finish_reason: Option<String>,
     /// Reasoning / extended thinking content returned by the model.
     pub reasoning: Option<String>,
+    /// Provider that served the request, when the API reports it.
+    pub provider: Option<String>,
 }
 
 /// Token usage statistics.
@@ -885,51 +1076,130 @@ pub struct UrlCitation {
 
 /// Async HTTP client for the OpenRouter chat completions API.
 pub struct OpenRouterClient {
-    pub(crate) client: reqwest::Client,
+    pub(crate) transport: Arc<dyn api::transport::HttpTransport>,
     pub(crate) api_key: String,
     pub(crate) referer: String,
     pub(crate) title: String,
+    /// Optional per-provider in-flight request cap.
+    pub(crate) pool: Option<Arc<api::pool::ConnectionPool>>,
+    /// Request/response hooks, run in registration order.
+    pub(crate) middleware: Vec<Arc<dyn api::middleware::ClientMiddleware>>,
+    /// Optional raw request/response capture for debugging.
+    pub(crate) recorder: Option<Arc<api::recorder::DebugRecorder>>,
 }
 
 impl OpenRouterClient {
     /// Create a new client with the given API key and default headers.
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn new(api_key: impl Into<String>) -> Result<Self, String> {
         Self::with_headers(api_key, "https://github.com/cinch-rs", "cinch-rs")
     }
 
     /// Create a new client with custom Referer and X-Title headers.
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn with_headers(
         api_key: impl Into<String>,
         referer: impl Into<String>,
         title: impl Into<String>,
     ) -> Result<Self, String> {
-        let client = reqwest::Client::builder()
-            .user_agent("openrouter-client/0.1")
-            .connect_timeout(Duration::from_secs(30))
-            .read_timeout(Duration::from_secs(300))
-            .build()
-            .map_err(|e| format!("failed to build HTTP client: {e}"))?;
-        Ok(Self {
-            client,
+        let transport = api::transport::ReqwestTransport::new()?;
+        Ok(Self::with_transport(api_key, transport).with_attribution(referer, title))
+    }
+
+    /// Create a client that sends requests through `transport` (see
+    /// [`api::transport`]). This is the only constructor on wasm32, where
+    /// embedders supply a `fetch`-based transport.
+    pub fn with_transport(
+        api_key: impl Into<String>,
+        transport: impl api::transport::HttpTransport + 'static,
+    ) -> Self {
+        Self {
+            transport: Arc::new(transport),
             api_key: api_key.into(),
-            referer: referer.into(),
-            title: title.into(),
-        })
+            referer: "https://github.com/cinch-rs".into(),
+            title: "cinch-rs".into(),
+            pool: None,
+            middleware: Vec::new(),
+            recorder: None,
+        }
+    }
+
+    /// Set the Referer and X-Title attribution headers (builder pattern).
+    pub fn with_attribution(
+        mut self,
+        referer: impl Into<String>,
+        title: impl Into<String>,
+    ) -> Self {
+        self.referer = referer.into();
+        self.title = title.into();
+        self
+    }
+
+    /// Route every request through `pool`, capping concurrent in-flight
+    /// requests per provider. Share one pool between clients (e.g. those
+    /// used by sub-agents) to cap them together.
+    pub fn with_pool(mut self, pool: Arc<api::pool::ConnectionPool>) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    /// Add request/response middleware (see [`api::middleware`]). Hooks run
+    /// in the order they were added.
+    pub fn with_middleware(
+        mut self,
+        middleware: impl api::middleware::ClientMiddleware + 'static,
+    ) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Record the exact request and response bodies of every chat call (see
+    /// [`api::recorder`]). Keep a clone of the `Arc` to read recordings.
+    pub fn with_recorder(mut self, recorder: Arc<api::recorder::DebugRecorder>) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// The attached debug recorder, if any.
+    pub fn recorder(&self) -> Option<&Arc<api::recorder::DebugRecorder>> {
+        self.recorder.as_ref()
+    }
+
+    /// Record an exchange when a recorder is attached. `request` is
+    /// serialized the same way it is sent.
+    pub(crate) fn record_exchange(
+        &self,
+        body: &ChatRequest,
+        request: &impl Serialize,
+        status: Option<u16>,
+        response: &str,
+    ) {
+        if let Some(ref recorder) = self.recorder {
+            let request = serde_json::to_string(request).unwrap_or_default();
+            recorder.record(body.span_id.as_deref(), status, &request, response);
+        }
+    }
+
+    /// The attached connection pool, if any (for queue wait metrics).
+    pub fn pool(&self) -> Option<&Arc<api::pool::ConnectionPool>> {
+        self.pool.as_ref()
+    }
+
+    /// Wait for an in-flight slot for `body`'s model when a pool is attached.
+    pub(crate) async fn acquire_slot(&self, body: &ChatRequest) -> Option<api::pool::PoolPermit> {
+        match &self.pool {
+            Some(pool) => Some(pool.acquire(request_model(body)).await),
+            None => None,
+        }
     }
 
     /// Send a chat completion request.
     pub async fn chat(&self, body: &ChatRequest) -> Result<ChatCompletion, String> {
+        let prepared = self.prepare_request(body)?;
+        let body: &ChatRequest = &prepared;
         let msg_count = body.messages.len();
         let tool_count = body.tools.as_ref().map_or(0, |t| t.len());
-        let model_label = body
-            .model
-            .as_deref()
-            .or_else(|| {
-                body.models
-                    .as_ref()
-                    .and_then(|m| m.first().map(|s| s.as_str()))
-            })
-            .unwrap_or("(none)");
+        let model_label = request_model(body);
         debug!(
             "LLM request: model={}, messages={}, tools={}, max_tokens={}, temp={}",
             model_label, msg_count, tool_count, body.max_tokens, body.temperature,
@@ -939,24 +1209,24 @@ impl OpenRouterClient {
             serde_json::to_string(body).map_or(0, |s| s.len())
         );
 
+        let _slot = self.acquire_slot(body).await;
         let start = Instant::now();
 
-        let resp = self
-            .client
-            .post(OPENROUTER_URL)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("HTTP-Referer", &self.referer)
-            .header("X-Title", &self.title)
-            .json(body)
-            .send()
-            .await
-            .map_err(|e| format!("request failed: {e}"))?;
+        let resp = match self.post(OPENROUTER_URL, body).await {
+            Ok(resp) => resp,
+            Err(e) => {
+                let error = format!("request failed: {e}");
+                self.record_exchange(body, body, None, &error);
+                return Err(error);
+            }
+        };
 
-        let status = resp.status();
+        let status = resp.status;
         let text = resp
             .text()
             .await
             .map_err(|e| format!("failed to read response: {e}"))?;
+        self.record_exchange(body, body, Some(status), &text);
 
         let elapsed = start.elapsed();
         debug!(
@@ -966,7 +1236,7 @@ impl OpenRouterClient {
             text.len()
         );
 
-        if !status.is_success() {
+        if !(200..300).contains(&status) {
             return Err(format!("OpenRouter API HTTP {status}: {text}"));
         }
 
@@ -1000,33 +1270,93 @@ impl OpenRouterClient {
             None => debug!("LLM output: empty (no choices)"),
         }
 
-        match choice {
-            Some(c) => Ok(ChatCompletion {
+        let completion = match choice {
+            Some(c) => ChatCompletion {
                 content: c.message.content,
                 tool_calls: c.message.tool_calls.unwrap_or_default(),
                 usage: parsed.usage,
                 annotations: c.message.annotations.unwrap_or_default(),
                 finish_reason: c.finish_reason,
                 reasoning: c.message.reasoning,
-            }),
-            None => Ok(ChatCompletion {
+                provider: parsed.provider,
+            },
+            None => ChatCompletion {
                 content: None,
                 tool_calls: vec![],
                 usage: parsed.usage,
                 annotations: vec![],
                 finish_reason: None,
                 reasoning: None,
-            }),
+                provider: parsed.provider,
+            },
+        };
+        self.finish_response(body, completion)
+    }
+
+    /// Embed `inputs` with an embedding model, returning one vector per
+    /// input in input order.
+    pub async fn embed(&self, model: &str, inputs: &[String]) -> Result<Vec<Vec<f32>>, String> {
+        if inputs.is_empty() {
+            return Ok(Vec::new());
+        }
+        debug!("Embedding request: model={model}, inputs={}", inputs.len());
+
+        let _slot = match &self.pool {
+            Some(pool) => Some(pool.acquire(model).await),
+            None => None,
+        };
+        let body = serde_json::json!({ "model": model, "input": inputs });
+        let resp = self
+            .post(OPENROUTER_EMBEDDINGS_URL, &body)
+            .await
+            .map_err(|e| format!("request failed: {e}"))?;
+
+        let status = resp.status;
+        let text = resp
+            .text()
+            .await
+            .map_err(|e| format!("failed to read response: {e}"))?;
+        if !(200..300).contains(&status) {
+            return Err(format!("OpenRouter API HTTP {status}: {text}"));
+        }
+
+        let parsed: RawEmbeddingResponse =
+            serde_json::from_str(&text).map_err(|e| format!("failed to parse response: {e}"))?;
+        if let Some(err) = parsed.error {
+            return Err(format!("OpenRouter API error: {}", err.message));
         }
+        if parsed.data.len() != inputs.len() {
+            return Err(format!(
+                "expected {} embeddings, got {}",
+                inputs.len(),
+                parsed.data.len()
+            ));
+        }
+        let mut data = parsed.data;
+        data.sort_by_key(|d| d.index);
+        Ok(data.into_iter().map(|d| d.embedding).collect())
     }
 }
 
+/// Model a request targets: `model`, else the first fallback in `models`.
+fn request_model(body: &ChatRequest) -> &str {
+    body.model
+        .as_deref()
+        .or_else(|| {
+            body.models
+                .as_ref()
+                .and_then(|m| m.first().map(|s| s.as_str()))
+        })
+        .unwrap_or("(none)")
+}
+
 // ── Convenience ────────────────────────────────────────────────────
 
 /// Run a quick one-shot LLM completion for data preprocessing.
 ///
 /// Reads the API key from the `OPENROUTER_KEY` environment variable.
 /// Returns `Err` if the key is not set or the API call fails.
+#[cfg(not(target_arch = "wasm32"))]
 pub async fn quick_completion(system: &str, user: &str, model: &str) -> Result<String, String> {
     let api_key =
         std::env::var("OPENROUTER_KEY").map_err(|_| "OPENROUTER_KEY not set".to_string())?;
@@ -1114,6 +1444,27 @@ mod tests {
         assert!(json.get("models").is_none());
     }
 
+    #[test]
+    fn images_serialize_as_content_parts() {
+        let msg = Message::user("what is this?")
+            .with_images(vec!["data:image/png;base64,AAAA".into()])
+            .with_cache_control(CacheControl::ephemeral());
+        let json = serde_json::to_value(&msg).unwrap();
+        assert_eq!(json["content"][0]["text"], "what is this?");
+        assert!(json["content"][0].get("cache_control").is_some());
+        assert_eq!(json["content"][1]["type"], "image_url");
+        assert_eq!(
+            json["content"][1]["image_url"]["url"],
+            "data:image/png;base64,AAAA"
+        );
+        assert!(json["content"][1].get("text").is_none());
+
+        // Only the text survives a round trip.
+        let back: Message = serde_json::from_value(json).unwrap();
+        assert_eq!(back.content.as_deref(), Some("what is this?"));
+        assert!(back.images.is_empty());
+    }
+
     #[test]
     fn format_citations_deduplicates() {
         let anns = vec![
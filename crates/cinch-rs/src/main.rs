@@ -24,9 +24,10 @@
 //! openrouter --user "Latest news" --web-search
 //! ```
 
+use cinch_rs::tools::host::ShellKind;
 use cinch_rs::{
-    ChatRequest, Message, OpenRouterClient, ProviderPreferences, ResponseFormat, ToolDef,
-    format_citations,
+    ChatRequest, MaxPrice, Message, OpenRouterClient, ProviderPreferences, ProviderSort,
+    ResponseFormat, ToolDef, format_citations,
 };
 use clap::Parser;
 use serde::Deserialize;
@@ -53,6 +54,7 @@ struct Cli {
     stdin: bool,
 
     /// Assistant prefill: partial text the model must continue from
+    /// (with --auto-execute, only the final answer is prefilled)
     #[arg(long)]
     prefill: Option<String>,
 
@@ -128,6 +130,22 @@ struct Cli {
     #[arg(long)]
     allow_fallbacks: Option<bool>,
 
+    /// Rank providers by price, throughput, or latency
+    #[arg(long)]
+    provider_sort: Option<ProviderSort>,
+
+    /// Maximum price per million prompt tokens (USD)
+    #[arg(long)]
+    max_prompt_price: Option<f64>,
+
+    /// Maximum price per million completion tokens (USD)
+    #[arg(long)]
+    max_completion_price: Option<f64>,
+
+    /// Only use providers that support every request parameter
+    #[arg(long)]
+    require_parameters: bool,
+
     /// Routing strategy
     #[arg(long)]
     route: Option<String>,
@@ -223,11 +241,7 @@ impl cinch_rs::tools::core::Tool for ShellCommandTool {
 
             eprintln!("  [tool] {name}: {cmd}");
 
-            let output = match std::process::Command::new("sh")
-                .arg("-c")
-                .arg(&cmd)
-                .output()
-            {
+            let output = match ShellKind::host().std_command(&cmd).output() {
                 Ok(o) => o,
                 Err(e) => return format!("Error executing tool '{name}': {e}"),
             };
@@ -281,7 +295,7 @@ fn load_tools(path: &str) -> Result<cinch_rs::tools::core::ToolSet, String> {
     let entries: Vec<ToolFileEntry> = serde_json::from_str(&content)
         .map_err(|e| format!("failed to parse tools file '{path}': {e}"))?;
 
-    let mut set = cinch_rs::tools::core::ToolSet::new();
+    let set = cinch_rs::tools::core::ToolSet::new();
     for entry in entries {
         set.register(ShellCommandTool {
             def: ToolDef {
@@ -408,7 +422,18 @@ fn build_request_body(
         (None, Some(all), Some("fallback".to_string()))
     };
 
-    let provider = if !cli.provider.is_empty() || cli.allow_fallbacks.is_some() {
+    let max_price = (cli.max_prompt_price.is_some() || cli.max_completion_price.is_some())
+        .then_some(MaxPrice {
+            prompt: cli.max_prompt_price,
+            completion: cli.max_completion_price,
+            request: None,
+        });
+    let provider = if !cli.provider.is_empty()
+        || cli.allow_fallbacks.is_some()
+        || cli.provider_sort.is_some()
+        || max_price.is_some()
+        || cli.require_parameters
+    {
         Some(ProviderPreferences {
             order: if cli.provider.is_empty() {
                 None
@@ -416,6 +441,10 @@ fn build_request_body(
                 Some(cli.provider.clone())
             },
             allow_fallbacks: cli.allow_fallbacks,
+            sort: cli.provider_sort,
+            max_price,
+            require_parameters: cli.require_parameters.then_some(true),
+            ..Default::default()
         })
     } else {
         None
@@ -465,6 +494,7 @@ fn build_request_body(
         tools,
         plugins,
         reasoning: None,
+        span_id: None,
     }
 }
 
@@ -513,15 +543,14 @@ async fn send_request(cli: &Cli) -> Result<String, String> {
     }
     messages.push(Message::user(&user_content));
 
-    if let Some(prefill) = &cli.prefill {
-        messages.push(Message::assistant_text(prefill));
-    }
-
     let client =
         OpenRouterClient::with_headers(api_key, "https://crates.io/crates/cinch-rs", "cinch-rs")?;
 
     // ── Single-shot mode ────────────────────────────────────────
     if tool_set.is_none() || !cli.auto_execute {
+        if let Some(prefill) = &cli.prefill {
+            messages.push(Message::assistant_text(prefill));
+        }
         let tool_defs = tool_set.as_ref().map(|s| s.definitions());
         let body = build_request_body(cli, messages, tool_defs);
 
@@ -582,6 +611,7 @@ async fn send_request(cli: &Cli) -> Result<String, String> {
         plugins,
         reasoning: None,
         retry: cinch_rs::api::retry::RetryConfig::default(),
+        final_answer_prefill: cli.prefill.clone(),
         ..Default::default()
     };
 
@@ -591,17 +621,18 @@ async fn send_request(cli: &Cli) -> Result<String, String> {
         .run(messages)
         .await?;
 
-    let output = result.text();
+    // The harness prepends the prefill to the answer it was applied to.
+    let mut text_output = result.text_output.clone();
+    if let (Some(prefill), true) = (&cli.prefill, cli.no_prefill_echo)
+        && let Some(last) = text_output.last_mut()
+        && let Some(rest) = last.strip_prefix(prefill.as_str())
+    {
+        *last = rest.to_string();
+    }
+    let output = text_output.join("\n\n");
     let citations = format_citations(&result.annotations);
 
-    let final_result = match (&cli.prefill, cli.no_prefill_echo) {
-        (Some(prefill), false) if !output.is_empty() => {
-            format!("{prefill}{output}{citations}")
-        }
-        _ => format!("{output}{citations}"),
-    };
-
-    Ok(final_result)
+    Ok(format!("{output}{citations}"))
 }
 
 #[tokio::main]
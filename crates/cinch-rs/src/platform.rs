@@ -0,0 +1,189 @@
+//! Clock and timer shims so the core compiles for `wasm32-unknown-unknown`.
+//!
+//! On that target `std::time::Instant::now()` panics and tokio has no timer
+//! driver, so clocks come from `web-time` and sleeps from `futures-timer`
+//! (backed by `setTimeout`). Native builds use `std::time` and tokio's timer
+//! unchanged. Core modules use these instead of `std::time` / `tokio::time`.
+//! Background tasks likewise go through [`spawn()`], which runs them on the
+//! browser's microtask queue on wasm32 and on tokio elsewhere.
+
+use std::future::Future;
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use std::time::{Instant, SystemTime, UNIX_EPOCH};
+#[cfg(target_arch = "wasm32")]
+pub use web_time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Error returned by [`timeout()`] when the deadline passes first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed;
+
+impl std::fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("deadline has elapsed")
+    }
+}
+
+/// Wait for `duration`.
+pub async fn sleep(duration: Duration) {
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::time::sleep(duration).await;
+    #[cfg(target_arch = "wasm32")]
+    futures_timer::Delay::new(duration).await;
+}
+
+/// Run `future`, giving up once `duration` has passed.
+pub async fn timeout<F: Future>(duration: Duration, future: F) -> Result<F::Output, Elapsed> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        tokio::time::timeout(duration, future)
+            .await
+            .map_err(|_| Elapsed)
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        use futures::future::{Either, select};
+        let future = std::pin::pin!(future);
+        let delay = std::pin::pin!(sleep(duration));
+        match select(future, delay).await {
+            Either::Left((output, _)) => Ok(output),
+            Either::Right(_) => Err(Elapsed),
+        }
+    }
+}
+
+/// Seconds since the Unix epoch.
+pub fn epoch_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Milliseconds since the Unix epoch.
+pub fn epoch_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Handle to a task started with [`spawn()`].
+#[cfg(not(target_arch = "wasm32"))]
+pub use tokio::task::{JoinError, JoinHandle};
+#[cfg(target_arch = "wasm32")]
+pub use wasm_task::{JoinError, JoinHandle};
+
+/// Run `future` in the background, returning a handle to await or abort it.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tokio::spawn(future)
+}
+
+/// Run `future` in the background, returning a handle to await or abort it.
+#[cfg(target_arch = "wasm32")]
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + 'static,
+    F::Output: 'static,
+{
+    wasm_task::spawn(future)
+}
+
+/// The subset of tokio's task handle the core uses, over
+/// `wasm_bindgen_futures::spawn_local`.
+#[cfg(target_arch = "wasm32")]
+mod wasm_task {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::task::{Context, Poll};
+
+    use futures::channel::oneshot;
+    use futures::future::{AbortHandle, Abortable};
+
+    /// Error from awaiting a task that was aborted.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct JoinError;
+
+    impl std::fmt::Display for JoinError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("task was cancelled")
+        }
+    }
+
+    impl std::error::Error for JoinError {}
+
+    pub struct JoinHandle<T> {
+        output: oneshot::Receiver<T>,
+        abort: AbortHandle,
+        finished: Arc<AtomicBool>,
+    }
+
+    impl<T> JoinHandle<T> {
+        pub fn abort(&self) {
+            self.abort.abort();
+        }
+
+        pub fn is_finished(&self) -> bool {
+            self.finished.load(Ordering::Acquire)
+        }
+    }
+
+    impl<T> Future for JoinHandle<T> {
+        type Output = Result<T, JoinError>;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            Pin::new(&mut self.output).poll(cx).map_err(|_| JoinError)
+        }
+    }
+
+    pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + 'static,
+        F::Output: 'static,
+    {
+        let (sender, output) = oneshot::channel();
+        let (abort, registration) = AbortHandle::new_pair();
+        let finished = Arc::new(AtomicBool::new(false));
+        let done = Arc::clone(&finished);
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Ok(value) = Abortable::new(future, registration).await {
+                let _ = sender.send(value);
+            }
+            done.store(true, Ordering::Release);
+        });
+        JoinHandle {
+            output,
+            abort,
+            finished,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn timeout_reports_elapsed() {
+        let slow = sleep(Duration::from_secs(5));
+        assert_eq!(timeout(Duration::from_millis(10), slow).await, Err(Elapsed));
+        assert_eq!(timeout(Duration::from_secs(5), async { 7 }).await, Ok(7));
+    }
+
+    #[tokio::test]
+    async fn spawned_tasks_can_be_awaited_or_aborted() {
+        assert_eq!(spawn(async { 7 }).await.unwrap(), 7);
+
+        let handle = spawn(sleep(Duration::from_secs(3600)));
+        handle.abort();
+        assert!(handle.await.is_err());
+    }
+}
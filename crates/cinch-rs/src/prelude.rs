@@ -20,33 +20,46 @@ pub use crate::{
 };
 
 // ── Agent runtime ───────────────────────────────────────────────────
+#[cfg(feature = "ui")]
+pub use crate::agent::UiGatherObserver;
 pub use crate::agent::{
-    CompositeEventHandler, ContextGatherer, EventHandler, EventObserver, EventResponse,
-    FnEventHandler, GatherEvent, GatherObserver, Harness, HarnessConfig, HarnessEvent,
-    HarnessResult, LoggingHandler, NoopHandler, SharedResources, SystemPromptBuilder,
-    TokenBudgetSemaphore, ToolResultHandler, UiGatherObserver,
+    ApprovalScope, ApprovalStore, CompositeEventHandler, ContextGatherer, EventHandler,
+    EventObserver, EventResponse, FnEventHandler, GatherEvent, GatherObserver, Harness,
+    HarnessConfig, HarnessEvent, HarnessResult, LoggingHandler, NoopHandler, SharedResources,
+    SystemPromptBuilder, TokenBudgetSemaphore, ToolResultHandler,
 };
 
 // ── Context management ──────────────────────────────────────────────
 pub use crate::context::ContextBudget;
 
 // ── Tools ───────────────────────────────────────────────────────────
+#[cfg(feature = "macros")]
+pub use crate::CinchTool;
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::tools::CommonToolsConfig;
+#[cfg(feature = "ui")]
+pub use crate::tools::InteractionToolsConfig;
 pub use crate::tools::spec::ToolSpec;
 pub use crate::tools::{
-    CommonToolsConfig, DisabledTool, FnTool, ReadTracker, Tool, ToolBudget, ToolCategory,
-    ToolFilter, ToolFuture, ToolSet, parse_tool_args,
+    DisabledTool, FnTool, ReadTracker, Tool, ToolBudget, ToolCategory, ToolFilter, ToolFuture,
+    ToolSet, parse_tool_args,
 };
 
 // ── Convenience functions ──────────────────────────────────────────
+#[cfg(not(target_arch = "wasm32"))]
 pub use crate::quick_completion;
 
 // ── UI state ────────────────────────────────────────────────────────
-pub use crate::ui::ask_user_tool::AskUserTool;
+#[cfg(feature = "ui")]
+pub use crate::ui::ask_user_tool::{AskUserTool, HeadlessAnswer};
+#[cfg(feature = "ui")]
 pub use crate::ui::event_handler::UiEventHandler;
-pub use crate::ui::tracing::UiTracingLayer;
+#[cfg(feature = "ui")]
+pub use crate::ui::tracing::{LogFilter, UiTracingLayer};
+#[cfg(feature = "ui")]
 pub use crate::ui::{
-    AgentEntry, LogLevel, LogLine, NoExtension, QuestionChoice, QuestionResponse, UiExtension,
-    UiState, UserQuestion, ask_question, clear_next_cycle, poll_question, push_agent_text,
-    push_agent_text_delta, push_tool_executing, push_tool_result, push_user_message,
-    set_next_cycle, update_phase, update_round,
+    AgentEntry, DiffStats, LogLevel, LogLine, NoExtension, QuestionChoice, QuestionKind,
+    QuestionResponse, UiExtension, UiState, UserQuestion, ask_question, clear_next_cycle,
+    poll_question, push_agent_text, push_agent_text_delta, push_tool_executing, push_tool_result,
+    push_user_message, set_next_cycle, update_phase, update_round,
 };
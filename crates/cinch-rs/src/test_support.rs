@@ -0,0 +1,115 @@
+//! Fixtures for tests that drive a harness against a scripted model.
+//!
+//! Compiled for this crate's own tests and, behind the `test-support`
+//! feature, for the tests of crates built on it. Nothing here talks to the
+//! network: [`ScriptedTransport`] answers each completion request with the
+//! next queued body, and the reply builders produce those bodies.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use serde_json::json;
+
+use crate::agent::config::{HarnessConfig, HarnessPlanExecuteConfig, HarnessSessionConfig};
+use crate::api::transport::{HttpRequest, HttpResponse, HttpTransport, TransportFuture};
+
+/// Transport answering requests with queued completion bodies, in order,
+/// and recording each request body.
+pub struct ScriptedTransport {
+    replies: Mutex<VecDeque<String>>,
+    repeat: Option<String>,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl ScriptedTransport {
+    /// Answer with `replies`, one per request. Panics on a request past the
+    /// end of the script.
+    pub fn new(replies: Vec<String>) -> Self {
+        Self {
+            replies: Mutex::new(replies.into()),
+            repeat: None,
+            requests: Arc::default(),
+        }
+    }
+
+    /// Answer every request with `reply`.
+    pub fn repeating(reply: impl Into<String>) -> Self {
+        Self {
+            repeat: Some(reply.into()),
+            ..Self::new(Vec::new())
+        }
+    }
+
+    /// Handle to the request bodies sent so far, usable after the transport
+    /// has moved into a client.
+    pub fn requests(&self) -> Arc<Mutex<Vec<String>>> {
+        Arc::clone(&self.requests)
+    }
+}
+
+impl HttpTransport for ScriptedTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        self.requests.lock().unwrap().push(request.body);
+        let body = self
+            .replies
+            .lock()
+            .unwrap()
+            .pop_front()
+            .or_else(|| self.repeat.clone())
+            .expect("unexpected request");
+        Box::pin(async move { Ok(HttpResponse::from_body(200, body)) })
+    }
+}
+
+/// A completion body whose message is `text`, finished with `stop`.
+pub fn text_reply(text: &str) -> String {
+    finished_reply(text, "stop")
+}
+
+/// A completion body whose message is `text`, finished with
+/// `finish_reason` (e.g. `length` for a truncated answer).
+pub fn finished_reply(text: &str, finish_reason: &str) -> String {
+    json!({
+        "choices": [{"message": {"content": text}, "finish_reason": finish_reason}],
+    })
+    .to_string()
+}
+
+/// A completion body calling tool `name` with `arguments` (a JSON object).
+pub fn tool_call_reply(id: &str, name: &str, arguments: &serde_json::Value) -> String {
+    json!({
+        "choices": [{
+            "message": {
+                "content": null,
+                "tool_calls": [{
+                    "id": id,
+                    "type": "function",
+                    "function": {"name": name, "arguments": arguments.to_string()},
+                }],
+            },
+            "finish_reason": "tool_calls",
+        }],
+    })
+    .to_string()
+}
+
+/// `reply` with a `usage` block reporting the given token counts.
+pub fn with_usage(reply: &str, prompt_tokens: u32, completion_tokens: u32) -> String {
+    let mut body: serde_json::Value = serde_json::from_str(reply).expect("completion body");
+    body["usage"] = json!({
+        "prompt_tokens": prompt_tokens,
+        "completion_tokens": completion_tokens,
+    });
+    body.to_string()
+}
+
+/// A harness config for scripted runs: non-streaming, with no memory
+/// prompt, sessions, or plan-execute phase.
+pub fn test_config(system_prompt: &str) -> HarnessConfig {
+    let mut config = HarnessConfig::new("test/model", system_prompt)
+        .with_streaming(false)
+        .with_memory_prompt(None);
+    config.session = HarnessSessionConfig::disabled();
+    config.plan_execute = HarnessPlanExecuteConfig::disabled();
+    config
+}
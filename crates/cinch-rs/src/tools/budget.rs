@@ -1,12 +1,19 @@
-//! Tool definition budget management.
+//! Tool definition and tool call budget management.
 //!
 //! Tool definitions are serialized into every API request but can consume
 //! thousands of tokens — especially with many MCP tools. [`ToolBudget`]
 //! estimates the token cost of tool definitions and [`enforce_budget`]
 //! trims descriptions to fit within a budget.
+//!
+//! Calls are budgeted per [`ToolCategory`] with
+//! [`max_calls`](ToolCategory::max_calls): [`CategoryBudgets`] counts a run's
+//! calls and refuses those that would exceed a category's limit (e.g. 20
+//! search calls, 5 mutations).
 
 use std::collections::HashSet;
 
+use super::filter::ToolCategory;
+
 /// Budget configuration for tool definitions.
 #[derive(Debug, Clone)]
 pub struct ToolBudget {
@@ -150,6 +157,85 @@ pub fn enforce_budget(
     }
 }
 
+// ── Category call budgets ───────────────────────────────────────────
+
+/// A call refused because its category's budget is used up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CategoryBudgetExceeded {
+    /// Category name.
+    pub category: String,
+    /// The category's calls-per-run limit.
+    pub max_calls: u32,
+}
+
+impl CategoryBudgetExceeded {
+    /// Tool result returned in place of the refused call.
+    pub fn tool_result(&self, tool_name: &str) -> String {
+        format!(
+            "Error: tool '{tool_name}' was not run: the '{}' budget of {} calls for this run \
+             is used up. Continue with the information you already have, or use tools \
+             outside this category.",
+            self.category, self.max_calls
+        )
+    }
+}
+
+/// Per-run call counts for categories with a
+/// [`max_calls`](ToolCategory::max_calls) limit.
+#[derive(Debug)]
+pub struct CategoryBudgets {
+    /// Limited categories with the calls charged so far.
+    categories: Vec<(ToolCategory, u32)>,
+}
+
+impl CategoryBudgets {
+    /// Track the categories in `categories` that have a limit.
+    pub fn new(categories: &[ToolCategory]) -> Self {
+        Self {
+            categories: categories
+                .iter()
+                .filter(|c| c.max_calls.is_some())
+                .map(|c| (c.clone(), 0))
+                .collect(),
+        }
+    }
+
+    /// Whether no category is limited.
+    pub fn is_empty(&self) -> bool {
+        self.categories.is_empty()
+    }
+
+    /// The first category of `name` whose budget is used up, if any.
+    pub fn check(&self, name: &str, is_mutation: bool) -> Option<CategoryBudgetExceeded> {
+        self.categories.iter().find_map(|(category, used)| {
+            let max_calls = category.max_calls?;
+            (category.contains(name, is_mutation) && *used >= max_calls).then(|| {
+                CategoryBudgetExceeded {
+                    category: category.name.clone(),
+                    max_calls,
+                }
+            })
+        })
+    }
+
+    /// Count a call to `name` against each of its categories.
+    pub fn charge(&mut self, name: &str, is_mutation: bool) {
+        for (category, used) in &mut self.categories {
+            if category.contains(name, is_mutation) {
+                *used += 1;
+            }
+        }
+    }
+
+    /// Calls charged to the category `name` so far.
+    pub fn used(&self, category: &str) -> Option<u32> {
+        self.categories
+            .iter()
+            .find(|(c, _)| c.name == category)
+            .map(|(_, used)| *used)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -265,4 +351,34 @@ mod tests {
             result[0].function.description,
         );
     }
+
+    #[test]
+    fn category_budgets_refuse_calls_past_the_limit() {
+        let mut budgets = CategoryBudgets::new(&[
+            ToolCategory::new("search", &["grep", "find_files"], "When searching").max_calls(2),
+            ToolCategory::mutations(1),
+            ToolCategory::new("unlimited", &["read_file"], "When reading"),
+        ]);
+        assert!(budgets.used("unlimited").is_none());
+
+        budgets.charge("grep", false);
+        assert!(budgets.check("find_files", false).is_none());
+        budgets.charge("find_files", false);
+        let exceeded = budgets.check("grep", false).unwrap();
+        assert_eq!(exceeded.category, "search");
+        assert!(
+            exceeded
+                .tool_result("grep")
+                .contains("'search' budget of 2 calls")
+        );
+
+        assert!(budgets.check("edit_file", true).is_none());
+        budgets.charge("edit_file", true);
+        assert_eq!(
+            budgets.check("write_file", true).unwrap().category,
+            "mutations"
+        );
+        assert!(budgets.check("read_file", false).is_none());
+        assert_eq!(budgets.used("mutations"), Some(1));
+    }
 }
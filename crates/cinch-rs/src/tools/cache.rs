@@ -3,15 +3,23 @@
 //! Avoids re-executing identical tool calls when the result hasn't changed.
 //! Read-only tools (those that return `cacheable() == true`) have their
 //! results cached by `(tool_name, arguments_hash)`. Mutation tools
-//! automatically invalidate relevant cache entries.
+//! automatically invalidate relevant cache entries: a mutation with a `path`
+//! argument (`edit_file`, `write_file`) removes the entries touching that
+//! path, and one without (`shell`) clears the cache. Paths are compared
+//! relative to the workdir, so `/wd/src/a.rs` and `src/a.rs` are one file.
 
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::paths::workdir_relative;
 
 /// A cache entry for a tool result.
 #[derive(Debug, Clone)]
 struct CacheEntry {
     result: String,
     round_produced: u32,
+    /// The call's `path` argument, for targeted invalidation.
+    path: Option<String>,
 }
 
 /// Cache for tool results, keyed by (tool_name, arguments_hash).
@@ -22,6 +30,8 @@ struct CacheEntry {
 #[derive(Debug)]
 pub struct ToolResultCache {
     entries: HashMap<(String, u64), CacheEntry>,
+    /// Directory relative `path` arguments resolve against.
+    workdir: PathBuf,
     /// Maximum number of entries before eviction.
     max_entries: usize,
     /// Hits counter for diagnostics.
@@ -35,12 +45,20 @@ impl ToolResultCache {
     pub fn new(max_entries: usize) -> Self {
         Self {
             entries: HashMap::new(),
+            workdir: PathBuf::from("."),
             max_entries,
             hits: 0,
             misses: 0,
         }
     }
 
+    /// Resolve `path` arguments against `workdir` when invalidating
+    /// (builder pattern). Default: `.`.
+    pub fn with_workdir(mut self, workdir: impl Into<PathBuf>) -> Self {
+        self.workdir = workdir.into();
+        self
+    }
+
     /// Look up a cached result. Returns `Some(result)` on cache hit.
     pub fn get(&mut self, tool_name: &str, arguments: &str) -> Option<&str> {
         let key = (tool_name.to_string(), hash_arguments(arguments));
@@ -65,6 +83,7 @@ impl ToolResultCache {
             CacheEntry {
                 result,
                 round_produced: round,
+                path: path_argument(arguments),
             },
         );
     }
@@ -74,6 +93,23 @@ impl ToolResultCache {
         self.entries.clear();
     }
 
+    /// Invalidate entries whose results may include `path`: calls on the
+    /// path itself, on a directory containing it, or without a path.
+    pub fn invalidate_path(&mut self, path: &str) {
+        let workdir = &self.workdir;
+        self.entries
+            .retain(|_, entry| !path_touches(workdir, entry.path.as_deref(), path));
+    }
+
+    /// Invalidate after a mutation tool ran with `arguments`: targeted when
+    /// the call names a `path`, otherwise everything (e.g. `shell`).
+    pub fn invalidate_for_mutation(&mut self, arguments: &str) {
+        match path_argument(arguments) {
+            Some(path) => self.invalidate_path(&path),
+            None => self.invalidate_all(),
+        }
+    }
+
     /// Invalidate cache entries older than `max_age` rounds.
     pub fn evict_older_than(&mut self, current_round: u32, max_age: u32) {
         self.entries
@@ -129,6 +165,27 @@ impl Default for ToolResultCache {
     }
 }
 
+/// The `path` argument of a tool call, if any.
+pub(crate) fn path_argument(arguments: &str) -> Option<String> {
+    serde_json::from_str::<serde_json::Value>(arguments)
+        .ok()?
+        .get("path")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// Whether a cached call on `entry_path` (`None` for calls without a path,
+/// which search the whole workdir) may include `mutated`. Both paths are
+/// resolved against `workdir` first.
+pub(crate) fn path_touches(workdir: &Path, entry_path: Option<&str>, mutated: &str) -> bool {
+    let Some(entry_path) = entry_path else {
+        return true;
+    };
+    let entry = workdir_relative(workdir, entry_path);
+    let mutated = workdir_relative(workdir, mutated);
+    entry.as_os_str().is_empty() || mutated.starts_with(&entry)
+}
+
 /// Hash tool arguments for cache key. Uses a simple FNV-1a hash.
 fn hash_arguments(arguments: &str) -> u64 {
     let mut hash: u64 = 0xcbf29ce484222325;
@@ -196,6 +253,55 @@ mod tests {
         assert_eq!(cache.len(), 2);
     }
 
+    #[test]
+    fn mutation_invalidates_only_touching_entries() {
+        let mut cache = ToolResultCache::new(10);
+        cache.put("read_file", r#"{"path":"src/a.rs"}"#, "a".into(), 1);
+        cache.put("read_file", r#"{"path":"src/b.rs"}"#, "b".into(), 1);
+        cache.put("list_dir", r#"{"path":"src"}"#, "a.rs b.rs".into(), 1);
+        cache.put("list_dir", r#"{"path":"docs"}"#, "x.md".into(), 1);
+        cache.put("grep", r#"{"pattern":"fn"}"#, "src/a.rs:1".into(), 1);
+
+        cache.invalidate_for_mutation(r#"{"path":"./src/a.rs","content":""}"#);
+        assert!(cache.get("read_file", r#"{"path":"src/a.rs"}"#).is_none());
+        assert!(cache.get("list_dir", r#"{"path":"src"}"#).is_none());
+        assert!(cache.get("grep", r#"{"pattern":"fn"}"#).is_none());
+        assert!(cache.get("read_file", r#"{"path":"src/b.rs"}"#).is_some());
+        assert!(cache.get("list_dir", r#"{"path":"docs"}"#).is_some());
+
+        cache.invalidate_for_mutation(r#"{"command":"rm -rf docs"}"#);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn path_touches_handles_prefixes() {
+        let wd = Path::new("/wd");
+        assert!(path_touches(wd, Some("."), "a.rs"));
+        assert!(path_touches(wd, Some("src/"), "src/a.rs"));
+        assert!(!path_touches(wd, Some("src"), "src2/a.rs"));
+        assert!(!path_touches(wd, Some("src/a.rs"), "src"));
+    }
+
+    #[test]
+    fn mutations_through_path_aliases_invalidate() {
+        let mut cache = ToolResultCache::new(10).with_workdir("/wd");
+        cache.put("read_file", r#"{"path":"src/a.rs"}"#, "old".into(), 1);
+        cache.invalidate_for_mutation(r#"{"path":"/wd/src/a.rs","content":""}"#);
+        assert!(cache.get("read_file", r#"{"path":"src/a.rs"}"#).is_none());
+
+        cache.put("read_file", r#"{"path":"/wd/src/a.rs"}"#, "old".into(), 1);
+        cache.invalidate_for_mutation(r#"{"path":"src/../src/a.rs","content":""}"#);
+        assert!(
+            cache
+                .get("read_file", r#"{"path":"/wd/src/a.rs"}"#)
+                .is_none()
+        );
+
+        cache.put("read_file", r#"{"path":"src/b.rs"}"#, "b".into(), 1);
+        cache.invalidate_for_mutation(r#"{"path":"/elsewhere/src/b.rs","content":""}"#);
+        assert!(cache.get("read_file", r#"{"path":"src/b.rs"}"#).is_some());
+    }
+
     #[test]
     fn hit_rate_computation() {
         let mut cache = ToolResultCache::new(10);
@@ -16,7 +16,7 @@
 //! | [`Grep`] | `grep` | Regex search in files |
 //! | [`FindFiles`] | `find_files` | Glob-based file search |
 //! | [`Shell`] | `shell` | Execute shell commands |
-//! | [`WebSearch`] | `web_search` | Search the web via Brave Search API |
+//! | [`WebSearch`] | `web_search` | Search the web via Brave Search API (`web-search` feature) |
 //!
 //! # Example
 //!
@@ -30,13 +30,19 @@
 //!     .with(Shell::new("/my/project").block_command("rm -rf"));
 //! ```
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tokio::fs;
 use tokio::process::Command;
 
 use crate::ToolDef;
 use crate::tools::core::{Tool, ToolFuture, TruncationStrategy, truncate_with_strategy};
-use crate::tools::spec::ToolSpec;
+use crate::tools::errors::{ToolErrorCatalog, ToolErrorKind};
+use crate::tools::host::{ShellEnv, ShellKind};
+use crate::tools::limits::{LimitTrip, ProcessLimits, SubprocessBudget, output_with_limits};
+use crate::tools::output::ToolOutput;
+use crate::tools::paths::resolve_in_workdir;
+use crate::tools::search::{self, GrepMode, GrepOptions};
+use crate::tools::spec::{DisambiguationExample, ToolCallExample, ToolSpec};
 use schemars::JsonSchema;
 use serde::Deserialize;
 
@@ -135,6 +141,7 @@ pub struct ShellArgs {
 }
 
 /// Typed arguments for `web_search`.
+#[cfg(feature = "web-search")]
 #[derive(Deserialize, JsonSchema)]
 pub struct WebSearchArgs {
     /// The search query (e.g. 'creatine monohydrate dosing research 2024').
@@ -171,10 +178,12 @@ pub struct WriteFileArgs {
 
 /// Read a file from a working directory.
 ///
-/// Path traversal (`..`) is blocked. Results are truncated to
+/// Paths that resolve outside the working directory are rejected. Results are truncated to
 /// `max_result_bytes`.
 pub struct ReadFile {
     workdir: String,
+    allowed_paths: Vec<PathBuf>,
+    errors: Arc<ToolErrorCatalog>,
     max_result_bytes: usize,
     tracker: Option<Arc<ReadTracker>>,
 }
@@ -183,11 +192,26 @@ impl ReadFile {
     pub fn new(workdir: impl Into<String>) -> Self {
         Self {
             workdir: workdir.into(),
+            allowed_paths: Vec::new(),
+            errors: ToolErrorCatalog::shared_default(),
             max_result_bytes: DEFAULT_MAX_RESULT_BYTES,
             tracker: None,
         }
     }
 
+    /// Also allow paths under these directories, outside the working
+    /// directory (see [`resolve_in_workdir`]).
+    pub fn allowed_paths(mut self, paths: Vec<PathBuf>) -> Self {
+        self.allowed_paths = paths;
+        self
+    }
+
+    /// Word errors with `catalog` instead of the stock messages.
+    pub fn error_catalog(mut self, catalog: Arc<ToolErrorCatalog>) -> Self {
+        self.errors = catalog;
+        self
+    }
+
     pub fn max_result_bytes(mut self, max: usize) -> Self {
         self.max_result_bytes = max;
         self
@@ -207,8 +231,8 @@ const DEFAULT_READ_LINE_LIMIT: u32 = 2000;
 /// Maximum characters per line before truncation.
 const MAX_LINE_CHARS: usize = 500;
 
-impl Tool for ReadFile {
-    fn definition(&self) -> ToolDef {
+impl ReadFile {
+    fn spec(&self) -> ToolSpec {
         ToolSpec::builder(super::names::READ_FILE)
             .purpose("Read a file with numbered lines")
             .when_to_use(
@@ -234,8 +258,26 @@ impl Tool for ReadFile {
                 "grep",
                 "grep searches content across files; read_file reads a single known file",
             )
+            .call_example(
+                serde_json::json!({"path": "src/main.rs", "offset": 1, "limit": 3}),
+                "L1: use std::fs;\nL2:\nL3: fn main() {\n\
+                 [Showing lines 1-3 of 40. Next page: read_file(path='src/main.rs', offset=4, limit=3)]",
+            )
             .build()
-            .to_tool_def()
+    }
+}
+
+impl Tool for ReadFile {
+    fn definition(&self) -> ToolDef {
+        self.spec().to_tool_def()
+    }
+
+    fn call_examples(&self) -> Vec<ToolCallExample> {
+        self.spec().call_examples
+    }
+
+    fn disambiguation(&self) -> Vec<DisambiguationExample> {
+        self.spec().disambiguation
     }
 
     fn cacheable(&self) -> bool {
@@ -250,29 +292,29 @@ impl Tool for ReadFile {
 
     fn execute(&self, arguments: &str) -> ToolFuture<'_> {
         let workdir = self.workdir.clone();
+        let allowed = self.allowed_paths.clone();
         let max = self.max_result_bytes;
         let tracker = self.tracker.clone();
+        let errors = self.errors.clone();
         let arguments = arguments.to_string();
         Box::pin(async move {
             let args: ReadFileArgs = match serde_json::from_str(&arguments) {
                 Ok(a) => a,
-                Err(_) => return "Error: 'path' argument is required".to_string(),
+                Err(_) => {
+                    return errors.render(ToolErrorKind::MissingArgument, &[("name", "path")]);
+                }
+            };
+            let full_path = match resolve_in_workdir(&workdir, &args.path, &allowed) {
+                Ok(p) => p,
+                Err(e) => return errors.path_error(&e),
             };
-            if args.path.contains("..") {
-                return "Error: path traversal not allowed".to_string();
-            }
-            let full_path = Path::new(&workdir).join(&args.path);
 
             // Catch directories early so the LLM gets an actionable hint
             // instead of the raw OS error ("Is a directory (os error 21)").
             if let Ok(meta) = fs::metadata(&full_path).await
                 && meta.is_dir()
             {
-                return format!(
-                    "Error: '{}' is a directory, not a file. \
-                     Use list_dir to browse directories.",
-                    args.path
-                );
+                return errors.render(ToolErrorKind::IsDirectory, &[("path", &args.path)]);
             }
 
             match fs::read_to_string(&full_path).await {
@@ -322,7 +364,13 @@ impl Tool for ReadFile {
 
                     truncate_result(output, max)
                 }
-                Err(e) => format!("Error reading '{}': {e}", full_path.display()),
+                Err(e) => errors.render(
+                    ToolErrorKind::ReadFailed,
+                    &[
+                        ("path", &full_path.display().to_string()),
+                        ("error", &e.to_string()),
+                    ],
+                ),
             }
         })
     }
@@ -333,18 +381,35 @@ impl Tool for ReadFile {
 /// List a directory tree under the working directory.
 ///
 /// Uses a native Rust recursive walk (no shell dependency) with
-/// configurable depth, limit, and offset for pagination. Path traversal
-/// (`..`) is blocked.
+/// configurable depth, limit, and offset for pagination. Paths that resolve
+/// outside the working directory are rejected.
 pub struct ListDir {
     workdir: String,
+    allowed_paths: Vec<PathBuf>,
+    errors: Arc<ToolErrorCatalog>,
 }
 
 impl ListDir {
     pub fn new(workdir: impl Into<String>) -> Self {
         Self {
             workdir: workdir.into(),
+            allowed_paths: Vec::new(),
+            errors: ToolErrorCatalog::shared_default(),
         }
     }
+
+    /// Also allow paths under these directories, outside the working
+    /// directory (see [`resolve_in_workdir`]).
+    pub fn allowed_paths(mut self, paths: Vec<PathBuf>) -> Self {
+        self.allowed_paths = paths;
+        self
+    }
+
+    /// Word errors with `catalog` instead of the stock messages.
+    pub fn error_catalog(mut self, catalog: Arc<ToolErrorCatalog>) -> Self {
+        self.errors = catalog;
+        self
+    }
 }
 
 /// Default maximum depth for `list_dir`.
@@ -353,8 +418,8 @@ const DEFAULT_LIST_DIR_DEPTH: u32 = 2;
 /// Default entry limit for `list_dir`.
 const DEFAULT_LIST_DIR_LIMIT: u32 = 50;
 
-impl Tool for ListDir {
-    fn definition(&self) -> ToolDef {
+impl ListDir {
+    fn spec(&self) -> ToolSpec {
         ToolSpec::builder(super::names::LIST_DIR)
             .purpose("List directory contents as an indented tree")
             .when_to_use(
@@ -379,7 +444,16 @@ impl Tool for ListDir {
                 "find_files supports glob patterns across nested directories; list_dir shows a directory tree",
             )
             .build()
-            .to_tool_def()
+    }
+}
+
+impl Tool for ListDir {
+    fn definition(&self) -> ToolDef {
+        self.spec().to_tool_def()
+    }
+
+    fn disambiguation(&self) -> Vec<DisambiguationExample> {
+        self.spec().disambiguation
     }
 
     fn cacheable(&self) -> bool {
@@ -388,16 +462,20 @@ impl Tool for ListDir {
 
     fn execute(&self, arguments: &str) -> ToolFuture<'_> {
         let workdir = self.workdir.clone();
+        let allowed = self.allowed_paths.clone();
+        let errors = self.errors.clone();
         let arguments = arguments.to_string();
         Box::pin(async move {
             let args: ListDirArgs = match serde_json::from_str(&arguments) {
                 Ok(a) => a,
-                Err(_) => return "Error: 'path' argument is required".to_string(),
+                Err(_) => {
+                    return errors.render(ToolErrorKind::MissingArgument, &[("name", "path")]);
+                }
+            };
+            let full_path = match resolve_in_workdir(&workdir, &args.path, &allowed) {
+                Ok(p) => p,
+                Err(e) => return errors.path_error(&e),
             };
-            if args.path.contains("..") {
-                return "Error: path traversal not allowed".to_string();
-            }
-            let full_path = Path::new(&workdir).join(&args.path);
 
             let depth = args.depth.unwrap_or(DEFAULT_LIST_DIR_DEPTH) as usize;
             let limit = args.limit.unwrap_or(DEFAULT_LIST_DIR_LIMIT) as usize;
@@ -497,9 +575,11 @@ async fn collect_dir_entries(
 /// Regex search in file contents under the working directory.
 ///
 /// Supports optional glob filtering and case-insensitive search.
-/// Path traversal (`..`) is blocked.
+/// Paths that resolve outside the working directory are rejected.
 pub struct Grep {
     workdir: String,
+    allowed_paths: Vec<PathBuf>,
+    errors: Arc<ToolErrorCatalog>,
     max_matches: u32,
     max_result_bytes: usize,
 }
@@ -508,11 +588,26 @@ impl Grep {
     pub fn new(workdir: impl Into<String>) -> Self {
         Self {
             workdir: workdir.into(),
+            allowed_paths: Vec::new(),
+            errors: ToolErrorCatalog::shared_default(),
             max_matches: DEFAULT_MAX_GREP_MATCHES,
             max_result_bytes: DEFAULT_MAX_RESULT_BYTES,
         }
     }
 
+    /// Also allow paths under these directories, outside the working
+    /// directory (see [`resolve_in_workdir`]).
+    pub fn allowed_paths(mut self, paths: Vec<PathBuf>) -> Self {
+        self.allowed_paths = paths;
+        self
+    }
+
+    /// Word errors with `catalog` instead of the stock messages.
+    pub fn error_catalog(mut self, catalog: Arc<ToolErrorCatalog>) -> Self {
+        self.errors = catalog;
+        self
+    }
+
     pub fn max_matches(mut self, max: u32) -> Self {
         self.max_matches = max;
         self
@@ -524,8 +619,8 @@ impl Grep {
     }
 }
 
-impl Tool for Grep {
-    fn definition(&self) -> ToolDef {
+impl Grep {
+    fn spec(&self) -> ToolSpec {
         ToolSpec::builder(super::names::GREP)
             .purpose("Search for a regex pattern in file contents")
             .when_to_use(
@@ -561,7 +656,16 @@ impl Tool for Grep {
                 "find_files matches file paths; grep matches file content",
             )
             .build()
-            .to_tool_def()
+    }
+}
+
+impl Tool for Grep {
+    fn definition(&self) -> ToolDef {
+        self.spec().to_tool_def()
+    }
+
+    fn disambiguation(&self) -> Vec<DisambiguationExample> {
+        self.spec().disambiguation
     }
 
     fn cacheable(&self) -> bool {
@@ -570,78 +674,50 @@ impl Tool for Grep {
 
     fn execute(&self, arguments: &str) -> ToolFuture<'_> {
         let workdir = self.workdir.clone();
+        let allowed = self.allowed_paths.clone();
         let max_matches = self.max_matches;
         let max_result_bytes = self.max_result_bytes;
+        let errors = self.errors.clone();
         let arguments = arguments.to_string();
         Box::pin(async move {
             let args: GrepArgs = match serde_json::from_str(&arguments) {
                 Ok(a) => a,
-                Err(_) => return "Error: 'pattern' argument is required".to_string(),
+                Err(_) => {
+                    return errors.render(ToolErrorKind::MissingArgument, &[("name", "pattern")]);
+                }
             };
             let search_path = args.path.as_deref().unwrap_or(".");
-            if search_path.contains("..") {
-                return "Error: path traversal not allowed".to_string();
-            }
-            let full_path = Path::new(&workdir).join(search_path);
-
-            let mode = args.mode.as_deref().unwrap_or("files");
-
-            let mut cmd_args: Vec<String> = vec![
-                "-r".to_string(),
-                "--color=never".to_string(),
-                format!("--max-count={max_matches}"),
-            ];
-
-            match mode {
-                "files" => {
-                    // Files-only mode: return paths only (most token-efficient).
-                    cmd_args.push("-l".to_string());
-                }
-                "content" => {
-                    // Content mode: return matching lines with line numbers.
-                    cmd_args.push("-n".to_string());
-                    if let Some(ctx) = args.context_lines
-                        && ctx > 0
-                    {
-                        cmd_args.push(format!("-C{ctx}"));
-                    }
-                }
-                "count" => {
-                    // Count mode: return match counts per file.
-                    cmd_args.push("-c".to_string());
-                }
-                _ => {
-                    return format!(
-                        "Error: invalid mode '{}'. Use 'files', 'content', or 'count'.",
-                        mode
-                    );
-                }
-            }
-
-            if args.case_insensitive.unwrap_or(false) {
-                cmd_args.push("-i".to_string());
-            }
-
-            if let Some(glob) = &args.glob {
-                cmd_args.push(format!("--include={glob}"));
-            }
-
-            cmd_args.push(args.pattern);
-            cmd_args.push(full_path.to_string_lossy().to_string());
+            let full_path = match resolve_in_workdir(&workdir, search_path, &allowed) {
+                Ok(p) => p,
+                Err(e) => return errors.path_error(&e),
+            };
 
-            let arg_refs: Vec<&str> = cmd_args.iter().map(|s| s.as_str()).collect();
-            // grep returns exit code 1 for "no matches" — not an error.
-            let result = run_command("grep", &arg_refs, &[1]).await;
+            let mode = match args.mode.as_deref().unwrap_or("files") {
+                "files" => GrepMode::Files,
+                "content" => GrepMode::Content,
+                "count" => GrepMode::Count,
+                other => return errors.render(ToolErrorKind::InvalidMode, &[("mode", other)]),
+            };
 
-            // For count mode, strip lines with :0 (no matches in that file).
-            let result = if mode == "count" {
-                result
-                    .lines()
-                    .filter(|line| !line.ends_with(":0"))
-                    .collect::<Vec<_>>()
-                    .join("\n")
-            } else {
-                result
+            let search = tokio::task::spawn_blocking(move || {
+                let options = GrepOptions {
+                    pattern: &args.pattern,
+                    glob: args.glob.as_deref(),
+                    case_insensitive: args.case_insensitive.unwrap_or(false),
+                    mode,
+                    context_lines: args.context_lines.unwrap_or(0) as usize,
+                    max_matches_per_file: max_matches as usize,
+                };
+                search::grep(&full_path, &options)
+            })
+            .await;
+            // Keep the exit codes of the former grep subprocess: 1 means no
+            // matches, 2 a bad pattern or path.
+            let result = match search {
+                Ok(Ok(lines)) if lines.is_empty() => ToolOutput::new(1, "", "").render(),
+                Ok(Ok(lines)) => ToolOutput::new(0, format!("{}\n", lines.join("\n")), "").render(),
+                Ok(Err(e)) => ToolOutput::new(2, "", e.to_string()).render(),
+                Err(e) => errors.render(ToolErrorKind::SearchFailed, &[("error", &e.to_string())]),
             };
 
             truncate_result(result, max_result_bytes)
@@ -653,9 +729,11 @@ impl Tool for Grep {
 
 /// Find files matching a glob pattern under the working directory.
 ///
-/// Path traversal (`..`) is blocked.
+/// Paths that resolve outside the working directory are rejected.
 pub struct FindFiles {
     workdir: String,
+    allowed_paths: Vec<PathBuf>,
+    errors: Arc<ToolErrorCatalog>,
     max_results: u32,
     max_result_bytes: usize,
 }
@@ -664,11 +742,26 @@ impl FindFiles {
     pub fn new(workdir: impl Into<String>) -> Self {
         Self {
             workdir: workdir.into(),
+            allowed_paths: Vec::new(),
+            errors: ToolErrorCatalog::shared_default(),
             max_results: DEFAULT_MAX_FIND_RESULTS,
             max_result_bytes: DEFAULT_MAX_RESULT_BYTES,
         }
     }
 
+    /// Also allow paths under these directories, outside the working
+    /// directory (see [`resolve_in_workdir`]).
+    pub fn allowed_paths(mut self, paths: Vec<PathBuf>) -> Self {
+        self.allowed_paths = paths;
+        self
+    }
+
+    /// Word errors with `catalog` instead of the stock messages.
+    pub fn error_catalog(mut self, catalog: Arc<ToolErrorCatalog>) -> Self {
+        self.errors = catalog;
+        self
+    }
+
     pub fn max_results(mut self, max: u32) -> Self {
         self.max_results = max;
         self
@@ -680,8 +773,8 @@ impl FindFiles {
     }
 }
 
-impl Tool for FindFiles {
-    fn definition(&self) -> ToolDef {
+impl FindFiles {
+    fn spec(&self) -> ToolSpec {
         ToolSpec::builder(super::names::FIND_FILES)
             .purpose(
                 "Find files matching a glob pattern, sorted by modification time (newest first)",
@@ -714,7 +807,16 @@ impl Tool for FindFiles {
                 "list_dir shows a directory tree; find_files searches recursively by pattern",
             )
             .build()
-            .to_tool_def()
+    }
+}
+
+impl Tool for FindFiles {
+    fn definition(&self) -> ToolDef {
+        self.spec().to_tool_def()
+    }
+
+    fn disambiguation(&self) -> Vec<DisambiguationExample> {
+        self.spec().disambiguation
     }
 
     fn cacheable(&self) -> bool {
@@ -723,58 +825,49 @@ impl Tool for FindFiles {
 
     fn execute(&self, arguments: &str) -> ToolFuture<'_> {
         let workdir = self.workdir.clone();
+        let allowed = self.allowed_paths.clone();
         let default_max_results = self.max_results;
         let max_result_bytes = self.max_result_bytes;
+        let errors = self.errors.clone();
         let arguments = arguments.to_string();
         Box::pin(async move {
             let args: FindFilesArgs = match serde_json::from_str(&arguments) {
                 Ok(a) => a,
-                Err(_) => return "Error: 'pattern' argument is required".to_string(),
+                Err(_) => {
+                    return errors.render(ToolErrorKind::MissingArgument, &[("name", "pattern")]);
+                }
             };
             if args.pattern.contains("..") {
-                return "Error: path traversal not allowed".to_string();
+                return errors.render(ToolErrorKind::PathOutsideWorkspace, &[]);
             }
             if let Some(ref p) = args.path
-                && p.contains("..")
+                && let Err(e) = resolve_in_workdir(&workdir, p, &allowed)
             {
-                return "Error: path traversal not allowed".to_string();
+                return errors.path_error(&e);
             }
 
             let limit = args.limit.unwrap_or(default_max_results).min(1000);
-            let search_path = args.path.as_deref().unwrap_or(".");
-            let pattern = &args.pattern;
+            let search_path = args.path.unwrap_or_else(|| ".".into());
+            let pattern = args.pattern.clone();
 
-            // Build the -path argument. When searching from a subdirectory,
-            // the path prefix in find changes from "./" to "{search_path}/".
-            let find_pattern = if search_path == "." {
-                format!("./{pattern}")
-            } else {
-                format!("{search_path}/{pattern}")
-            };
-
-            // Use find + xargs ls -1t for mtime-sorted results.
-            // Cap intermediate results at 1000 for the sort step.
-            let result = run_shell(
-                &workdir,
-                &format!(
-                    "find {search_path} -path '{find_pattern}' -type f 2>/dev/null \
-                     | head -1000 \
-                     | xargs ls -1t 2>/dev/null \
-                     | head -{limit}"
-                ),
-            )
+            let root = PathBuf::from(&workdir);
+            let found = tokio::task::spawn_blocking(move || {
+                search::find_files(&root, &search_path, &args.pattern, limit as usize)
+            })
             .await;
-
-            // Strip the [exit: N] prefix from the inner shell call for clean output.
-            let clean = result
-                .strip_prefix("[exit: 0]\n")
-                .or_else(|| result.strip_prefix("[exit: 1]\n"))
-                .unwrap_or(&result);
+            let clean = match found {
+                Ok(Ok(paths)) => paths.join("\n"),
+                Ok(Err(e)) => return format!("Error: {e}"),
+                Err(e) => {
+                    return errors
+                        .render(ToolErrorKind::SearchFailed, &[("error", &e.to_string())]);
+                }
+            };
 
             if clean.trim().is_empty() {
                 format!("No files found matching '{pattern}'")
             } else {
-                truncate_result(clean.to_string(), max_result_bytes)
+                truncate_result(clean, max_result_bytes)
             }
         })
     }
@@ -785,24 +878,79 @@ impl Tool for FindFiles {
 /// Execute shell commands in the working directory.
 ///
 /// Commands matching any pattern in `blocked_commands` are rejected.
+/// Commands run in the host's [`ShellKind`] (PowerShell on Windows, `sh`
+/// elsewhere) unless another shell is configured, and inherit the host
+/// environment unless a [`ShellEnv`] restricts it. [`ProcessLimits`] and a
+/// shared [`SubprocessBudget`] bound what each command may consume.
 pub struct Shell {
     workdir: String,
+    allowed_paths: Vec<PathBuf>,
+    errors: Arc<ToolErrorCatalog>,
     blocked_commands: Vec<String>,
     max_result_bytes: usize,
+    shell: ShellKind,
+    env: ShellEnv,
+    limits: ProcessLimits,
+    budget: Option<Arc<SubprocessBudget>>,
 }
 
 impl Shell {
     pub fn new(workdir: impl Into<String>) -> Self {
         Self {
             workdir: workdir.into(),
+            allowed_paths: Vec::new(),
+            errors: ToolErrorCatalog::shared_default(),
             blocked_commands: DEFAULT_BLOCKED_COMMANDS
                 .iter()
                 .map(|s| (*s).to_string())
                 .collect(),
             max_result_bytes: DEFAULT_MAX_RESULT_BYTES,
+            shell: ShellKind::host(),
+            env: ShellEnv::default(),
+            limits: ProcessLimits::default(),
+            budget: None,
         }
     }
 
+    /// Also allow paths under these directories, outside the working
+    /// directory (see [`resolve_in_workdir`]).
+    pub fn allowed_paths(mut self, paths: Vec<PathBuf>) -> Self {
+        self.allowed_paths = paths;
+        self
+    }
+
+    /// Word errors with `catalog` instead of the stock messages.
+    pub fn error_catalog(mut self, catalog: Arc<ToolErrorCatalog>) -> Self {
+        self.errors = catalog;
+        self
+    }
+
+    /// Run commands in `shell` instead of the host default.
+    pub fn shell_kind(mut self, shell: ShellKind) -> Self {
+        self.shell = shell;
+        self
+    }
+
+    /// Control the environment commands see. An `env_file` is resolved
+    /// against the tool's working directory.
+    pub fn env(mut self, env: ShellEnv) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// Limit CPU time, memory, and output of each command.
+    pub fn limits(mut self, limits: ProcessLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Charge command wall time against `budget`, refusing commands once it
+    /// is used up. Share one budget between tools to bound a whole run.
+    pub fn budget(mut self, budget: Arc<SubprocessBudget>) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
     /// Add a blocked command pattern (lowercased substring match).
     pub fn block_command(mut self, pattern: impl Into<String>) -> Self {
         self.blocked_commands.push(pattern.into());
@@ -838,10 +986,11 @@ impl Tool for Shell {
             .parameters_for::<ShellArgs>()
             .example(
                 "shell(command='git log --oneline -5')",
-                "[exit: 0]\na1b2c3d First commit\n...",
+                "[exit: 0] [0.02s]\na1b2c3d First commit\n...",
             )
             .output_format(
-                "Prefixed with [exit: N]. Stdout follows; stderr appended on failure. \
+                "A header line with the exit code and run time, then stdout, then stderr \
+                 after a [stderr] line if there was any. \
                  Long output is truncated with head+tail preserved.",
             )
             .build()
@@ -852,59 +1001,113 @@ impl Tool for Shell {
         true
     }
 
+    fn runs_commands(&self) -> bool {
+        true
+    }
+
     fn prompt_guidelines(&self) -> Vec<String> {
-        vec![
+        let mut guidelines = vec![
             "When shell output is truncated, use targeted commands (grep, head, tail) to get specific output ranges.".into(),
-        ]
+        ];
+        if self.shell != ShellKind::Sh {
+            guidelines.push(format!(
+                "shell commands run in {} on {}; use its syntax, not POSIX sh.",
+                self.shell.name(),
+                std::env::consts::OS
+            ));
+        }
+        guidelines
     }
 
     fn execute(&self, arguments: &str) -> ToolFuture<'_> {
         let workdir = self.workdir.clone();
+        let allowed = self.allowed_paths.clone();
+        let shell = self.shell;
+        let env = self.env.clone();
+        let limits = self.limits;
+        let budget = self.budget.clone();
         let blocked = self.blocked_commands.clone();
         let max = self.max_result_bytes;
+        let errors = self.errors.clone();
         let arguments = arguments.to_string();
         Box::pin(async move {
             let args: ShellArgs = match serde_json::from_str(&arguments) {
                 Ok(a) => a,
-                Err(_) => return "Error: 'command' argument is required".to_string(),
+                Err(_) => {
+                    return errors.render(ToolErrorKind::MissingArgument, &[("name", "command")]);
+                }
             };
             let lower = args.command.to_lowercase();
             if blocked.iter().any(|pat| lower.contains(pat)) {
-                return "Error: potentially destructive command blocked".to_string();
+                return errors.render(ToolErrorKind::CommandBlocked, &[]);
             }
 
             // Resolve working directory.
             let effective_workdir = if let Some(ref wd) = args.working_dir {
-                if wd.contains("..") {
-                    return "Error: path traversal not allowed in working_dir".to_string();
+                match resolve_in_workdir(&workdir, wd, &allowed) {
+                    Ok(p) => p.to_string_lossy().to_string(),
+                    Err(e) => return format!("Error: {e} in working_dir"),
                 }
-                let p = std::path::Path::new(&workdir).join(wd);
-                p.to_string_lossy().to_string()
             } else {
                 workdir.clone()
             };
+            let vars = match env.resolve(Path::new(&workdir)) {
+                Ok(vars) => vars,
+                Err(e) => return format!("Error: {e}"),
+            };
+
+            if let Some(ref budget) = budget
+                && budget.is_exhausted()
+            {
+                return format!(
+                    "Error: command not run\n{}",
+                    budget.exhausted_trip().report()
+                );
+            }
 
-            // Timeout: default 120s, cap at 600s.
+            // Timeout: default 120s, cap at 600s, and never past the budget.
             let timeout_secs = args.timeout.unwrap_or(120).min(600);
-            let timeout_dur = std::time::Duration::from_secs(timeout_secs as u64);
+            let mut timeout_dur = std::time::Duration::from_secs(timeout_secs as u64);
+            let budget_bound = budget.as_ref().is_some_and(|b| b.remaining() < timeout_dur);
+            if let Some(ref budget) = budget {
+                timeout_dur = timeout_dur.min(budget.remaining());
+            }
 
-            let result = match tokio::time::timeout(
+            let started = std::time::Instant::now();
+            let outcome = tokio::time::timeout(
                 timeout_dur,
-                run_shell(&effective_workdir, &args.command),
+                run_shell_limited(shell, &effective_workdir, &args.command, vars, &limits),
             )
-            .await
-            {
-                Ok(output) => output,
+            .await;
+            if let Some(ref budget) = budget {
+                budget.record(started.elapsed());
+            }
+            let (result, trip) = match outcome {
+                Ok(outcome) => outcome,
                 Err(_) => {
-                    return format!("Error: command timed out after {timeout_secs} seconds");
+                    return match budget {
+                        Some(budget) if budget_bound => format!(
+                            "Error: command stopped after {}s\n{}",
+                            timeout_dur.as_secs(),
+                            budget.exhausted_trip().report()
+                        ),
+                        _ => errors.render(
+                            ToolErrorKind::CommandTimedOut,
+                            &[("seconds", &timeout_secs.to_string())],
+                        ),
+                    };
                 }
             };
 
-            truncate_with_strategy(
+            let result = truncate_with_strategy(
                 result,
                 max,
                 &TruncationStrategy::HeadAndTail { tail_ratio: 0.4 },
-            )
+            );
+            match trip {
+                Some(trip) => format!("{}\n{}", result.trim_end(), trip.report()),
+                None => result,
+            }
         })
     }
 }
@@ -915,20 +1118,27 @@ impl Tool for Shell {
 ///
 /// Requires the `BRAVE_SEARCH_KEY` environment variable (free tier: 2000
 /// queries/month at <https://brave.com/search/api/>).
+#[cfg(feature = "web-search")]
 pub struct WebSearch {
     max_result_bytes: usize,
+    errors: Arc<ToolErrorCatalog>,
+    cost_per_query_usd: f64,
 }
 
+#[cfg(feature = "web-search")]
 impl Default for WebSearch {
     fn default() -> Self {
         Self::new()
     }
 }
 
+#[cfg(feature = "web-search")]
 impl WebSearch {
     pub fn new() -> Self {
         Self {
             max_result_bytes: DEFAULT_MAX_RESULT_BYTES,
+            errors: ToolErrorCatalog::shared_default(),
+            cost_per_query_usd: 0.0,
         }
     }
 
@@ -936,8 +1146,22 @@ impl WebSearch {
         self.max_result_bytes = max;
         self
     }
+
+    /// Price of one search on a paid plan, added to the run's cost for
+    /// every query that reaches the API. Default: `0.0` (free tier).
+    pub fn cost_per_query(mut self, usd: f64) -> Self {
+        self.cost_per_query_usd = usd;
+        self
+    }
+
+    /// Word errors with `catalog` instead of the stock messages.
+    pub fn error_catalog(mut self, catalog: Arc<ToolErrorCatalog>) -> Self {
+        self.errors = catalog;
+        self
+    }
 }
 
+#[cfg(feature = "web-search")]
 impl Tool for WebSearch {
     fn definition(&self) -> ToolDef {
         ToolSpec::builder(super::names::WEB_SEARCH)
@@ -960,13 +1184,28 @@ impl Tool for WebSearch {
             .to_tool_def()
     }
 
+    fn accesses_network(&self) -> bool {
+        true
+    }
+
+    fn call_cost_usd(&self, _arguments: &str, result: &str) -> f64 {
+        if result.starts_with("Error") {
+            0.0
+        } else {
+            self.cost_per_query_usd
+        }
+    }
+
     fn execute(&self, arguments: &str) -> ToolFuture<'_> {
         let max = self.max_result_bytes;
+        let errors = self.errors.clone();
         let arguments = arguments.to_string();
         Box::pin(async move {
             let args: WebSearchArgs = match serde_json::from_str(&arguments) {
                 Ok(a) => a,
-                Err(_) => return "Error: 'query' argument is required".to_string(),
+                Err(_) => {
+                    return errors.render(ToolErrorKind::MissingArgument, &[("name", "query")]);
+                }
             };
             let count = args.count.unwrap_or(5).min(20);
             match brave_search(&args.query, count).await {
@@ -977,12 +1216,13 @@ impl Tool for WebSearch {
                         truncate_result(results, max)
                     }
                 }
-                Err(e) => format!("Error: web search failed: {e}"),
+                Err(e) => errors.render(ToolErrorKind::WebSearchFailed, &[("error", &e)]),
             }
         })
     }
 }
 
+#[cfg(feature = "web-search")]
 /// Call the Brave Search API and return formatted results.
 async fn brave_search(query: &str, count: u32) -> Result<String, String> {
     let api_key = std::env::var("BRAVE_SEARCH_KEY").map_err(|_| {
@@ -1021,6 +1261,7 @@ async fn brave_search(query: &str, count: u32) -> Result<String, String> {
     Ok(format_brave_results(&body))
 }
 
+#[cfg(feature = "web-search")]
 /// Minimal percent-encoding for URL query parameters.
 fn urlencoded(s: &str) -> String {
     let mut out = String::with_capacity(s.len() * 3);
@@ -1039,6 +1280,7 @@ fn urlencoded(s: &str) -> String {
     out
 }
 
+#[cfg(feature = "web-search")]
 /// Format Brave Search API JSON response into readable text.
 fn format_brave_results(body: &serde_json::Value) -> String {
     let mut out = Vec::new();
@@ -1068,6 +1310,8 @@ fn format_brave_results(body: &serde_json::Value) -> String {
 /// (enforced via [`ReadTracker`]).
 pub struct EditFile {
     workdir: String,
+    allowed_paths: Vec<PathBuf>,
+    errors: Arc<ToolErrorCatalog>,
     tracker: Arc<ReadTracker>,
 }
 
@@ -1075,13 +1319,28 @@ impl EditFile {
     pub fn new(workdir: impl Into<String>, tracker: Arc<ReadTracker>) -> Self {
         Self {
             workdir: workdir.into(),
+            allowed_paths: Vec::new(),
+            errors: ToolErrorCatalog::shared_default(),
             tracker,
         }
     }
+
+    /// Also allow paths under these directories, outside the working
+    /// directory (see [`resolve_in_workdir`]).
+    pub fn allowed_paths(mut self, paths: Vec<PathBuf>) -> Self {
+        self.allowed_paths = paths;
+        self
+    }
+
+    /// Word errors with `catalog` instead of the stock messages.
+    pub fn error_catalog(mut self, catalog: Arc<ToolErrorCatalog>) -> Self {
+        self.errors = catalog;
+        self
+    }
 }
 
-impl Tool for EditFile {
-    fn definition(&self) -> ToolDef {
+impl EditFile {
+    fn spec(&self) -> ToolSpec {
         ToolSpec::builder(super::names::EDIT_FILE)
             .purpose("Edit a file by replacing an exact string")
             .when_to_use(
@@ -1103,8 +1362,29 @@ impl Tool for EditFile {
                 "write_file",
                 "write_file creates or overwrites; edit_file modifies existing content in-place",
             )
+            .call_example(
+                serde_json::json!({
+                    "path": "src/main.rs",
+                    "old_string": "fn main() {\n    run();",
+                    "new_string": "fn main() {\n    init();\n    run();",
+                }),
+                "Edited src/main.rs: replaced 1 occurrence (lines 3-4)",
+            )
             .build()
-            .to_tool_def()
+    }
+}
+
+impl Tool for EditFile {
+    fn definition(&self) -> ToolDef {
+        self.spec().to_tool_def()
+    }
+
+    fn call_examples(&self) -> Vec<ToolCallExample> {
+        self.spec().call_examples
+    }
+
+    fn disambiguation(&self) -> Vec<DisambiguationExample> {
+        self.spec().disambiguation
     }
 
     fn is_mutation(&self) -> bool {
@@ -1119,34 +1399,40 @@ impl Tool for EditFile {
 
     fn execute(&self, arguments: &str) -> ToolFuture<'_> {
         let workdir = self.workdir.clone();
+        let allowed = self.allowed_paths.clone();
         let tracker = self.tracker.clone();
+        let errors = self.errors.clone();
         let arguments = arguments.to_string();
         Box::pin(async move {
             let args: EditFileArgs = match serde_json::from_str(&arguments) {
                 Ok(a) => a,
                 Err(_) => {
-                    return "Error: 'path', 'old_string', and 'new_string' are required"
-                        .to_string();
+                    return errors.render(
+                        ToolErrorKind::MissingArguments,
+                        &[("names", "'path', 'old_string', and 'new_string'")],
+                    );
                 }
             };
-            if args.path.contains("..") {
-                return "Error: path traversal not allowed".to_string();
-            }
-
-            let full_path = Path::new(&workdir).join(&args.path);
+            let full_path = match resolve_in_workdir(&workdir, &args.path, &allowed) {
+                Ok(p) => p,
+                Err(e) => return errors.path_error(&e),
+            };
             let abs_path = full_path.to_string_lossy().to_string();
 
             // Read-before-write enforcement.
             if full_path.exists() && !tracker.has_been_read(&abs_path) {
-                return "Error: You must read this file before editing it. \
-                        Use read_file first."
-                    .to_string();
+                return errors.render(ToolErrorKind::NotReadBeforeEdit, &[]);
             }
 
             // Read current content.
             let content = match fs::read_to_string(&full_path).await {
                 Ok(c) => c,
-                Err(e) => return format!("Error reading '{}': {e}", args.path),
+                Err(e) => {
+                    return errors.render(
+                        ToolErrorKind::ReadFailed,
+                        &[("path", &args.path), ("error", &e.to_string())],
+                    );
+                }
             };
 
             let replace_all = args.replace_all.unwrap_or(false);
@@ -1155,11 +1441,7 @@ impl Tool for EditFile {
             let count = content.matches(&args.old_string).count();
 
             if count == 0 {
-                return format!(
-                    "Error: old_string not found in {}. \
-                     Verify the exact text (including whitespace and indentation).",
-                    args.path
-                );
+                return errors.render(ToolErrorKind::OldStringNotFound, &[("path", &args.path)]);
             }
 
             if count > 1 && !replace_all {
@@ -1169,15 +1451,18 @@ impl Tool for EditFile {
                     .match_indices(&args.old_string)
                     .map(|(byte_offset, _)| content[..byte_offset].lines().count().max(1))
                     .collect();
-                return format!(
-                    "Error: old_string found {count} times in {} (lines: {}). \
-                     Provide more surrounding context to make it unique, or set replace_all=true.",
-                    args.path,
-                    line_nums
-                        .iter()
-                        .map(|n| n.to_string())
-                        .collect::<Vec<_>>()
-                        .join(", ")
+                let lines = line_nums
+                    .iter()
+                    .map(|n| n.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                return errors.render(
+                    ToolErrorKind::OldStringAmbiguous,
+                    &[
+                        ("count", &count.to_string()),
+                        ("path", &args.path),
+                        ("lines", &lines),
+                    ],
                 );
             }
 
@@ -1190,7 +1475,10 @@ impl Tool for EditFile {
 
             // Write back.
             if let Err(e) = fs::write(&full_path, &new_content).await {
-                return format!("Error writing '{}': {e}", args.path);
+                return errors.render(
+                    ToolErrorKind::WriteFailed,
+                    &[("path", &args.path), ("error", &e.to_string())],
+                );
             }
 
             // Update tracker so subsequent edits don't require re-reading.
@@ -1227,6 +1515,8 @@ impl Tool for EditFile {
 /// [`ReadTracker`]). New files can be written without reading first.
 pub struct WriteFile {
     workdir: String,
+    allowed_paths: Vec<PathBuf>,
+    errors: Arc<ToolErrorCatalog>,
     tracker: Arc<ReadTracker>,
 }
 
@@ -1234,13 +1524,28 @@ impl WriteFile {
     pub fn new(workdir: impl Into<String>, tracker: Arc<ReadTracker>) -> Self {
         Self {
             workdir: workdir.into(),
+            allowed_paths: Vec::new(),
+            errors: ToolErrorCatalog::shared_default(),
             tracker,
         }
     }
+
+    /// Also allow paths under these directories, outside the working
+    /// directory (see [`resolve_in_workdir`]).
+    pub fn allowed_paths(mut self, paths: Vec<PathBuf>) -> Self {
+        self.allowed_paths = paths;
+        self
+    }
+
+    /// Word errors with `catalog` instead of the stock messages.
+    pub fn error_catalog(mut self, catalog: Arc<ToolErrorCatalog>) -> Self {
+        self.errors = catalog;
+        self
+    }
 }
 
-impl Tool for WriteFile {
-    fn definition(&self) -> ToolDef {
+impl WriteFile {
+    fn spec(&self) -> ToolSpec {
         ToolSpec::builder(super::names::WRITE_FILE)
             .purpose("Create a new file or overwrite an existing file")
             .when_to_use(
@@ -1263,7 +1568,16 @@ impl Tool for WriteFile {
                 "edit_file is more precise for targeted changes; write_file replaces the whole file",
             )
             .build()
-            .to_tool_def()
+    }
+}
+
+impl Tool for WriteFile {
+    fn definition(&self) -> ToolDef {
+        self.spec().to_tool_def()
+    }
+
+    fn disambiguation(&self) -> Vec<DisambiguationExample> {
+        self.spec().disambiguation
     }
 
     fn is_mutation(&self) -> bool {
@@ -1272,26 +1586,30 @@ impl Tool for WriteFile {
 
     fn execute(&self, arguments: &str) -> ToolFuture<'_> {
         let workdir = self.workdir.clone();
+        let allowed = self.allowed_paths.clone();
         let tracker = self.tracker.clone();
+        let errors = self.errors.clone();
         let arguments = arguments.to_string();
         Box::pin(async move {
             let args: WriteFileArgs = match serde_json::from_str(&arguments) {
                 Ok(a) => a,
-                Err(_) => return "Error: 'path' and 'content' arguments are required".to_string(),
+                Err(_) => {
+                    return errors.render(
+                        ToolErrorKind::MissingArguments,
+                        &[("names", "'path' and 'content' arguments")],
+                    );
+                }
+            };
+            let full_path = match resolve_in_workdir(&workdir, &args.path, &allowed) {
+                Ok(p) => p,
+                Err(e) => return errors.path_error(&e),
             };
-            if args.path.contains("..") {
-                return "Error: path traversal not allowed".to_string();
-            }
-
-            let full_path = Path::new(&workdir).join(&args.path);
             let abs_path = full_path.to_string_lossy().to_string();
 
             // Read-before-overwrite: only enforce for existing files.
             let file_exists = fs::metadata(&full_path).await.is_ok();
             if file_exists && !tracker.has_been_read(&abs_path) {
-                return "Error: You must read this file before overwriting it. \
-                        Use read_file first."
-                    .to_string();
+                return errors.render(ToolErrorKind::NotReadBeforeWrite, &[]);
             }
 
             // Create parent directories if needed.
@@ -1299,12 +1617,18 @@ impl Tool for WriteFile {
                 && !parent.exists()
                 && let Err(e) = fs::create_dir_all(parent).await
             {
-                return format!("Error creating directories for '{}': {e}", args.path);
+                return errors.render(
+                    ToolErrorKind::CreateDirFailed,
+                    &[("path", &args.path), ("error", &e.to_string())],
+                );
             }
 
             // Write the file.
             if let Err(e) = fs::write(&full_path, &args.content).await {
-                return format!("Error writing '{}': {e}", args.path);
+                return errors.render(
+                    ToolErrorKind::WriteFailed,
+                    &[("path", &args.path), ("error", &e.to_string())],
+                );
             }
 
             // Update tracker with the written content.
@@ -1327,49 +1651,51 @@ pub fn parse_args(arguments: &str) -> serde_json::Value {
     serde_json::from_str(arguments).unwrap_or(serde_json::Value::Object(serde_json::Map::new()))
 }
 
-/// Format command output into a result string.
-///
-/// Output is prefixed with `[exit: N]` for consistent machine-readable
-/// parsing. On success, only stdout is included. On failure (or when
-/// stderr is non-empty), both streams are included.
-fn format_output(output: std::process::Output, lenient_exit_codes: &[i32]) -> String {
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-    let code = output.status.code().unwrap_or(-1);
-    let ok = output.status.success() || lenient_exit_codes.contains(&code);
-    if ok {
-        if stderr.is_empty() {
-            format!("[exit: {code}]\n{stdout}")
-        } else {
-            format!("[exit: {code}]\n{stdout}\n[stderr]\n{stderr}")
-        }
-    } else {
-        format!("[exit: {code}]\n{stdout}\n{stderr}")
-    }
-}
-
-/// Run a command with arguments and return its output.
-///
-/// When `lenient_exit_codes` contains extra exit codes (e.g. `&[1]` for grep's
-/// "no matches"), those codes are treated as success.
-pub async fn run_command(cmd: &str, args: &[&str], lenient_exit_codes: &[i32]) -> String {
+/// Run a command with arguments and return its rendered [`ToolOutput`].
+pub async fn run_command(cmd: &str, args: &[&str]) -> String {
+    let started = std::time::Instant::now();
     match Command::new(cmd).args(args).output().await {
-        Ok(output) => format_output(output, lenient_exit_codes),
+        Ok(output) => ToolOutput::from_process(&output, started.elapsed()).render(),
         Err(e) => format!("Error running {cmd}: {e}"),
     }
 }
 
-/// Run a shell command (`sh -c`) in the given working directory.
+/// Run a shell command in the host shell ([`ShellKind::host()`]) in the
+/// given working directory.
 pub async fn run_shell(workdir: &str, command: &str) -> String {
-    match Command::new("sh")
-        .arg("-c")
-        .arg(command)
-        .current_dir(workdir)
-        .output()
+    run_shell_in(ShellKind::host(), workdir, command).await
+}
+
+/// Run a shell command in `shell` in the given working directory.
+pub async fn run_shell_in(shell: ShellKind, workdir: &str, command: &str) -> String {
+    run_shell_limited(shell, workdir, command, None, &ProcessLimits::default())
         .await
-    {
-        Ok(output) => format_output(output, &[]),
-        Err(e) => format!("Error running command: {e}"),
+        .0
+}
+
+/// Run a shell command under `limits` with exactly `vars` as its
+/// environment, or the inherited one when `None` (see
+/// [`ShellEnv::resolve`]). Returns the rendered [`ToolOutput`] and the
+/// limit it tripped, if any.
+async fn run_shell_limited(
+    shell: ShellKind,
+    workdir: &str,
+    command: &str,
+    vars: Option<Vec<(String, String)>>,
+    limits: &ProcessLimits,
+) -> (String, Option<LimitTrip>) {
+    let mut cmd = shell.command(command);
+    cmd.current_dir(workdir);
+    if let Some(vars) = vars {
+        cmd.env_clear().envs(vars);
+    }
+    let started = std::time::Instant::now();
+    match output_with_limits(&mut cmd, limits).await {
+        Ok((output, trip)) => (
+            ToolOutput::from_process(&output, started.elapsed()).render(),
+            trip,
+        ),
+        Err(e) => (format!("Error running command: {e}"), None),
     }
 }
 
@@ -1379,6 +1705,7 @@ pub async fn run_shell(workdir: &str, command: &str) -> String {
 mod tests {
     use super::*;
     use crate::tools::core::ToolSet;
+    use crate::tools::limits::capture_trip;
 
     #[test]
     fn read_file_definition_has_tool_spec_fields() {
@@ -1482,6 +1809,36 @@ mod tests {
         assert_eq!(result, "Error: path traversal not allowed");
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn file_tools_block_absolute_and_symlink_escapes() {
+        let dir = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        std::fs::write(outside.path().join("secret.txt"), "hunter2").unwrap();
+        std::os::unix::fs::symlink(outside.path(), dir.path().join("escape")).unwrap();
+        let wd = dir.path().to_str().unwrap();
+
+        let read = ReadFile::new(wd);
+        let result = read.execute(r#"{"path": "escape/secret.txt"}"#).await;
+        assert_eq!(result, "Error: path traversal not allowed");
+        let secret = outside.path().join("secret.txt");
+        let args = serde_json::json!({ "path": secret }).to_string();
+        assert_eq!(
+            read.execute(&args).await,
+            "Error: path traversal not allowed"
+        );
+
+        let write = WriteFile::new(wd, make_tracker());
+        let result = write
+            .execute(r#"{"path": "escape/new.txt", "content": "x"}"#)
+            .await;
+        assert_eq!(result, "Error: path traversal not allowed");
+        assert!(!outside.path().join("new.txt").exists());
+
+        let read = ReadFile::new(wd).allowed_paths(vec![outside.path().to_path_buf()]);
+        assert!(read.execute(&args).await.contains("hunter2"));
+    }
+
     // ── ListDir upgrade tests ────────────────────────────────────
 
     #[tokio::test]
@@ -1734,6 +2091,54 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn shell_env_restricts_and_injects_variables() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(".env"), "TOKEN=from-file\n").unwrap();
+        let env = ShellEnv::default()
+            .inherit_only(["PATH"])
+            .with_env_file(".env")
+            .with_var("EXTRA", "1");
+        let tool = Shell::new(dir.path().to_str().unwrap()).env(env);
+
+        let result = tool.execute(r#"{"command": "env"}"#).await;
+        assert!(result.contains("TOKEN=from-file"), "{result}");
+        assert!(result.contains("EXTRA=1"), "{result}");
+        assert!(!result.contains("HOME="), "{result}");
+
+        let broken = Shell::new(dir.path().to_str().unwrap())
+            .env(ShellEnv::default().with_env_file("missing.env"));
+        let result = broken.execute(r#"{"command": "env"}"#).await;
+        assert!(
+            result.starts_with("Error: cannot read env file"),
+            "{result}"
+        );
+    }
+
+    #[tokio::test]
+    async fn shell_reports_limit_trips_and_budget() {
+        let limited = Shell::new("/tmp").limits(ProcessLimits::default().max_output_bytes(100));
+        let (result, trip) = capture_trip(limited.execute(r#"{"command": "yes"}"#)).await;
+        let trip = trip.unwrap();
+        assert_eq!(trip.kind, crate::tools::limits::LimitKind::Output);
+        assert!(result.ends_with(&trip.notice()), "{result}");
+
+        let budget = Arc::new(SubprocessBudget::new(std::time::Duration::from_secs(1)));
+        let shell = Shell::new("/tmp").budget(budget.clone());
+        let result = shell.execute(r#"{"command": "sleep 5"}"#).await;
+        assert!(
+            result.starts_with("Error: command stopped after 1s"),
+            "{result}"
+        );
+        assert!(budget.is_exhausted());
+        let (refused, trip) = capture_trip(shell.execute(r#"{"command": "echo hi"}"#)).await;
+        assert!(refused.starts_with("Error: command not run"), "{refused}");
+        assert_eq!(
+            trip.unwrap().kind,
+            crate::tools::limits::LimitKind::WallBudget
+        );
+    }
+
     // ── Missing argument tests ──────────────────────────────────
 
     #[tokio::test]
@@ -2022,8 +2427,10 @@ mod tests {
 
     #[tokio::test]
     async fn write_file_blocks_path_traversal() {
+        let dir = tempfile::tempdir().unwrap();
         let tracker = make_tracker();
-        let tool = WriteFile::new("/tmp", tracker);
+        // Lands in /tmp, outside the workdir, even though it names /tmp.
+        let tool = WriteFile::new(dir.path().to_str().unwrap(), tracker);
         let result = tool
             .execute(r#"{"path": "../../../tmp/evil.sh", "content": "bad"}"#)
             .await;
@@ -6,13 +6,16 @@
 //! dispatch, definition export, and result truncation.
 
 use crate::ToolDef;
+use crate::tools::errors::ToolErrorCatalog;
+use crate::tools::spec::{DisambiguationExample, ToolCallExample};
 use schemars::JsonSchema;
 use serde::Deserialize;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
 use std::future::Future;
 use std::pin::Pin;
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 use tracing::{debug, info, trace};
 
 /// Maximum size (in bytes) for tool output before truncation.
@@ -39,6 +42,7 @@ pub type ToolFuture<'a> = Pin<Box<dyn Future<Output = String> + Send + 'a>>;
 ///     .find_max_results(200)
 ///     .shell_block_command("dangerous-cmd");
 /// ```
+#[cfg(not(target_arch = "wasm32"))]
 #[derive(Debug, Clone)]
 pub struct CommonToolsConfig {
     /// Maximum grep matches per file before truncation.
@@ -50,8 +54,21 @@ pub struct CommonToolsConfig {
     /// Blocked shell command patterns (lowercased substring match).
     /// Default: `["rm -rf /", "mkfs", "> /dev/"]`.
     pub shell_blocked_commands: Vec<String>,
+    /// Directories outside the working directory that file tools may also
+    /// access. Default: none.
+    pub allowed_paths: Vec<std::path::PathBuf>,
+    /// Environment for shell commands. Default: the full host environment.
+    pub shell_env: crate::tools::host::ShellEnv,
+    /// CPU, memory, and output limits for each shell command. Default: none.
+    pub shell_limits: crate::tools::limits::ProcessLimits,
+    /// Wall-time budget shared by every shell command. Default: `None`.
+    pub subprocess_budget: Option<Arc<crate::tools::limits::SubprocessBudget>>,
+    /// Messages and recovery suggestions for the tools' errors. Default:
+    /// the stock English catalog.
+    pub error_catalog: Arc<ToolErrorCatalog>,
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 impl Default for CommonToolsConfig {
     fn default() -> Self {
         use crate::tools::common::{
@@ -64,10 +81,16 @@ impl Default for CommonToolsConfig {
                 .iter()
                 .map(|s| (*s).to_string())
                 .collect(),
+            allowed_paths: Vec::new(),
+            shell_env: Default::default(),
+            shell_limits: Default::default(),
+            subprocess_budget: None,
+            error_catalog: ToolErrorCatalog::shared_default(),
         }
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 impl CommonToolsConfig {
     /// Set the maximum grep matches per file.
     pub fn grep_max_matches(mut self, max: u32) -> Self {
@@ -92,6 +115,112 @@ impl CommonToolsConfig {
         self.shell_blocked_commands.push(command.into());
         self
     }
+
+    /// Allow file tools to access paths under `path`.
+    pub fn allow_path(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.allowed_paths.push(path.into());
+        self
+    }
+
+    /// Set the environment for shell commands.
+    pub fn shell_env(mut self, env: crate::tools::host::ShellEnv) -> Self {
+        self.shell_env = env;
+        self
+    }
+
+    /// Limit CPU time, memory, and output of each shell command.
+    pub fn shell_limits(mut self, limits: crate::tools::limits::ProcessLimits) -> Self {
+        self.shell_limits = limits;
+        self
+    }
+
+    /// Charge shell command wall time against `budget`. Pass the same
+    /// budget to
+    /// [`HarnessConfig::with_subprocess_budget`](crate::agent::config::HarnessConfig::with_subprocess_budget)
+    /// so it resets for each run.
+    pub fn subprocess_budget(
+        mut self,
+        budget: Arc<crate::tools::limits::SubprocessBudget>,
+    ) -> Self {
+        self.subprocess_budget = Some(budget);
+        self
+    }
+
+    /// Word the tools' errors, and their recovery suggestions, from
+    /// `catalog`.
+    pub fn error_catalog(mut self, catalog: Arc<ToolErrorCatalog>) -> Self {
+        self.error_catalog = catalog;
+        self
+    }
+}
+
+// ── InteractionToolsConfig ───────────────────────────────────────────
+
+/// Configuration for [`ToolSet::with_interaction_tools_configured`].
+///
+/// # Example
+///
+/// ```ignore
+/// let config = InteractionToolsConfig::default()
+///     .ask_timeout(Duration::from_secs(300))
+///     .headless_answer(HeadlessAnswer::Error);
+/// ```
+#[cfg(feature = "ui")]
+#[derive(Debug, Clone)]
+pub struct InteractionToolsConfig {
+    /// Time the operator has to answer a question when the model does not
+    /// set one. Default: [`DEFAULT_ASK_TIMEOUT`](crate::ui::ask_user_tool::DEFAULT_ASK_TIMEOUT) (120s).
+    pub ask_timeout: std::time::Duration,
+    /// How questions are answered when no UI is attached. Default:
+    /// [`HeadlessAnswer::AssumeDefault`](crate::ui::ask_user_tool::HeadlessAnswer::AssumeDefault).
+    pub headless_answer: crate::ui::ask_user_tool::HeadlessAnswer,
+    /// Directory `file_path` answers are resolved against. Default: `None`.
+    pub workdir: Option<String>,
+    /// Webhook each question is published to, for remote answers. Default:
+    /// `None`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub webhook: Option<crate::ui::webhook::QuestionWebhook>,
+}
+
+#[cfg(feature = "ui")]
+impl Default for InteractionToolsConfig {
+    fn default() -> Self {
+        Self {
+            ask_timeout: crate::ui::ask_user_tool::DEFAULT_ASK_TIMEOUT,
+            headless_answer: Default::default(),
+            workdir: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            webhook: None,
+        }
+    }
+}
+
+#[cfg(feature = "ui")]
+impl InteractionToolsConfig {
+    /// Set the default time the operator has to answer.
+    pub fn ask_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.ask_timeout = timeout;
+        self
+    }
+
+    /// Set how questions are answered without a UI.
+    pub fn headless_answer(mut self, answer: crate::ui::ask_user_tool::HeadlessAnswer) -> Self {
+        self.headless_answer = answer;
+        self
+    }
+
+    /// Resolve `file_path` answers against `workdir`.
+    pub fn workdir(mut self, workdir: impl Into<String>) -> Self {
+        self.workdir = Some(workdir.into());
+        self
+    }
+
+    /// Publish questions to `webhook`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn webhook(mut self, webhook: crate::ui::webhook::QuestionWebhook) -> Self {
+        self.webhook = Some(webhook);
+        self
+    }
 }
 
 // ── Tool trait ─────────────────────────────────────────────────────
@@ -152,6 +281,41 @@ pub trait Tool: Send + Sync {
         false
     }
 
+    /// Whether the result of a call with `arguments` may be cached. Lets a
+    /// [`cacheable`](Self::cacheable) tool opt out for calls whose answer can
+    /// change, such as a read of a moving git ref. Defaults to `true`.
+    fn caches_call(&self, _arguments: &str) -> bool {
+        true
+    }
+
+    /// Whether a call with `arguments` changes state. Lets a
+    /// [mutation](Self::is_mutation) tool exempt its read-only actions, such
+    /// as listing stashes. Defaults to `true`.
+    fn mutates_call(&self, _arguments: &str) -> bool {
+        true
+    }
+
+    /// Whether this tool runs shell commands or other processes chosen by
+    /// the model. Such calls are recorded in the
+    /// [audit log](crate::agent::audit). Defaults to `false`.
+    fn runs_commands(&self) -> bool {
+        false
+    }
+
+    /// Whether this tool makes network requests. Such calls are recorded in
+    /// the [audit log](crate::agent::audit). Defaults to `false`.
+    fn accesses_network(&self) -> bool {
+        false
+    }
+
+    /// Monetary cost in USD of one call with `arguments` that returned
+    /// `result` (a paid search API, a hosted sandbox). Added to the run's
+    /// [`CostTracker`](crate::api::tracing::CostTracker) so the estimated cost
+    /// covers more than tokens. Defaults to `0.0`.
+    fn call_cost_usd(&self, _arguments: &str, _result: &str) -> f64 {
+        0.0
+    }
+
     /// Extended description loaded on first use. Returns `None` by default.
     /// Override to provide detailed guidance (examples, disambiguation)
     /// that supplements the compact definition().
@@ -159,6 +323,20 @@ pub trait Tool: Send + Sync {
         None
     }
 
+    /// Worked calls used as few-shot demonstrations when
+    /// [`HarnessConfig::few_shot_tool_examples`](crate::agent::config::HarnessConfig::few_shot_tool_examples)
+    /// is set. Returns an empty vec by default.
+    fn call_examples(&self) -> Vec<ToolCallExample> {
+        vec![]
+    }
+
+    /// Situations where another tool is the right choice. Used to suggest
+    /// an alternative when this tool keeps failing (see
+    /// [`reflection`](super::reflection)). Returns an empty vec by default.
+    fn disambiguation(&self) -> Vec<DisambiguationExample> {
+        vec![]
+    }
+
     /// System-prompt-level usage guidelines contributed by this tool.
     ///
     /// These are collected by [`ToolSet::generate_guidelines`] and injected
@@ -175,6 +353,9 @@ pub trait Tool: Send + Sync {
 
 // ── ToolSet ────────────────────────────────────────────────────────
 
+/// Default timeout for tool execution (60 seconds).
+pub const DEFAULT_TOOL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
 /// A collection of tools that can be dispatched by name.
 ///
 /// Manages tool registration, definition export (for the LLM API), and
@@ -203,26 +384,53 @@ pub trait Tool: Send + Sync {
 /// // Export definitions for the LLM API.
 /// let defs = tools.definitions();
 /// ```
-/// Default timeout for tool execution (60 seconds).
-pub const DEFAULT_TOOL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
-
+///
+/// # Sharing
+///
+/// A `ToolSet` is `Send + Sync` and meant to be shared behind an `Arc` by
+/// concurrent harnesses and sub-agents. Registration takes `&self`, so tools
+/// can be added after construction; the registry is behind a lock that is
+/// never held across a tool's execution, and per-tool usage counters are
+/// atomic.
 pub struct ToolSet {
-    tools: HashMap<String, Box<dyn Tool>>,
+    tools: RwLock<HashMap<String, Arc<ToolEntry>>>,
     max_result_bytes: usize,
     /// Whether to validate tool arguments against JSON Schema before execution.
     validate_args: bool,
     /// Default timeout for tool execution. `None` disables timeouts.
     default_timeout: Option<std::time::Duration>,
-    /// Tool names whose results are cacheable (populated from `Tool::cacheable()`).
-    cacheable_tools: HashSet<String>,
-    /// Tool names that mutate state (populated from `Tool::is_mutation()`).
-    mutation_tools: HashSet<String>,
+    /// Recovery suggestions for reflected errors.
+    error_catalog: Arc<ToolErrorCatalog>,
+}
+
+/// A registered tool with its flags and usage counters.
+struct ToolEntry {
+    tool: Arc<dyn Tool>,
+    /// Cached `Tool::cacheable()`.
+    cacheable: bool,
+    /// Cached `Tool::is_mutation()`.
+    mutation: bool,
+    /// Cached `Tool::runs_commands()`.
+    commands: bool,
+    /// Cached `Tool::accesses_network()`.
+    network: bool,
+    calls: AtomicU64,
+    errors: AtomicU64,
+}
+
+/// How often a tool in a [`ToolSet`] has been executed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ToolCallCounts {
+    /// Executions, including failed ones.
+    pub calls: u64,
+    /// Executions whose result was an error.
+    pub errors: u64,
 }
 
 impl fmt::Debug for ToolSet {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("ToolSet")
-            .field("tools", &self.tools.keys().collect::<Vec<_>>())
+            .field("tools", &self.names())
             .field("max_result_bytes", &self.max_result_bytes)
             .finish()
     }
@@ -232,12 +440,11 @@ impl ToolSet {
     /// Create an empty tool set.
     pub fn new() -> Self {
         Self {
-            tools: HashMap::new(),
+            tools: RwLock::new(HashMap::new()),
             max_result_bytes: DEFAULT_MAX_RESULT_BYTES,
             validate_args: false,
             default_timeout: None,
-            cacheable_tools: HashSet::new(),
-            mutation_tools: HashSet::new(),
+            error_catalog: ToolErrorCatalog::shared_default(),
         }
     }
 
@@ -260,20 +467,45 @@ impl ToolSet {
         self
     }
 
+    /// Take recovery suggestions for failed calls from `catalog`. Set by
+    /// [`with_common_tools_configured`](Self::with_common_tools_configured)
+    /// from [`CommonToolsConfig::error_catalog`].
+    pub fn with_error_catalog(mut self, catalog: Arc<ToolErrorCatalog>) -> Self {
+        self.error_catalog = catalog;
+        self
+    }
+
+    /// The catalog reflected errors take their suggestions from.
+    pub fn error_catalog(&self) -> &ToolErrorCatalog {
+        &self.error_catalog
+    }
+
     /// Register a tool. Replaces any existing tool with the same name.
-    pub fn register(&mut self, tool: impl Tool + 'static) {
-        let name = tool.name();
-        if tool.cacheable() {
-            self.cacheable_tools.insert(name.clone());
-        }
-        if tool.is_mutation() {
-            self.mutation_tools.insert(name.clone());
-        }
-        self.tools.insert(name, Box::new(tool));
+    ///
+    /// Takes `&self` so tools can be added to a set that is already shared;
+    /// calls in flight keep using the tool they started with.
+    pub fn register(&self, tool: impl Tool + 'static) {
+        self.register_arc(Arc::new(tool));
+    }
+
+    fn register_arc(&self, tool: Arc<dyn Tool>) {
+        let entry = ToolEntry {
+            cacheable: tool.cacheable(),
+            mutation: tool.is_mutation(),
+            commands: tool.runs_commands(),
+            network: tool.accesses_network(),
+            tool,
+            calls: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+        };
+        self.tools
+            .write()
+            .unwrap()
+            .insert(entry.tool.name(), Arc::new(entry));
     }
 
     /// Register a tool (builder pattern).
-    pub fn with(mut self, tool: impl Tool + 'static) -> Self {
+    pub fn with(self, tool: impl Tool + 'static) -> Self {
         self.register(tool);
         self
     }
@@ -293,20 +525,127 @@ impl ToolSet {
         if condition { self.with(tool) } else { self }
     }
 
+    /// Merge every tool from `other` into this set under a prefixed name
+    /// (`"{prefix}{name}"`), with `description_prefix` prepended to each
+    /// description.
+    ///
+    /// Use this to expose the same tools for several roots side by side,
+    /// e.g. `read_file` for the main workdir plus `infra__read_file` for a
+    /// second repository. Per-tool guidelines of the merged tools are
+    /// dropped, since they refer to the unprefixed names.
+    pub fn with_namespaced(self, prefix: &str, description_prefix: &str, other: ToolSet) -> Self {
+        for entry in other.tools.into_inner().unwrap().into_values() {
+            self.register(NamespacedTool {
+                prefix: prefix.to_string(),
+                description_prefix: description_prefix.to_string(),
+                inner: entry.tool.clone(),
+            });
+        }
+        self
+    }
+
+    /// A new set with the same tools and settings. The tools themselves are
+    /// shared; registrations on either set afterwards don't affect the
+    /// other, and usage counts start at zero.
+    pub fn fork(&self) -> Self {
+        let forked = Self {
+            tools: RwLock::new(HashMap::new()),
+            max_result_bytes: self.max_result_bytes,
+            validate_args: self.validate_args,
+            default_timeout: self.default_timeout,
+            error_catalog: self.error_catalog.clone(),
+        };
+        for entry in self.entries() {
+            forked.register_arc(entry.tool.clone());
+        }
+        forked
+    }
+
+    /// Like [`fork`](Self::fork), keeping only the tools named in `names`.
+    /// Names not in this set are ignored.
+    pub fn fork_only(&self, names: &[impl AsRef<str>]) -> Self {
+        let forked = self.fork();
+        forked
+            .tools
+            .write()
+            .unwrap()
+            .retain(|name, _| names.iter().any(|n| n.as_ref() == name));
+        forked
+    }
+
+    /// Like [`fork`](Self::fork), keeping only tools whose
+    /// [`is_mutation`](Tool::is_mutation) is `false`. Used for the planning
+    /// phase, read-only sub-agents, and read-only permission modes.
+    pub fn read_only_view(&self) -> Self {
+        let forked = self.fork();
+        forked
+            .tools
+            .write()
+            .unwrap()
+            .retain(|_, entry| !entry.mutation);
+        forked
+    }
+
+    /// The entry for `name`, cloned out of the registry so the lock isn't
+    /// held while the caller uses it.
+    fn entry(&self, name: &str) -> Option<Arc<ToolEntry>> {
+        self.tools.read().unwrap().get(name).cloned()
+    }
+
+    /// Every registered tool, cloned out of the registry.
+    fn entries(&self) -> Vec<Arc<ToolEntry>> {
+        self.tools.read().unwrap().values().cloned().collect()
+    }
+
+    /// Names of all registered tools.
+    pub fn names(&self) -> Vec<String> {
+        self.tools.read().unwrap().keys().cloned().collect()
+    }
+
     /// Return all tool definitions for the LLM API.
     pub fn definitions(&self) -> Vec<ToolDef> {
-        self.tools.values().map(|t| t.definition()).collect()
+        self.entries().iter().map(|e| e.tool.definition()).collect()
+    }
+
+    /// Few-shot call examples of every tool, as `(tool name, example)`.
+    pub fn call_examples(&self) -> Vec<(String, ToolCallExample)> {
+        self.entries()
+            .iter()
+            .flat_map(|e| {
+                let name = e.tool.name();
+                e.tool
+                    .call_examples()
+                    .into_iter()
+                    .map(move |example| (name.clone(), example))
+            })
+            .collect()
+    }
+
+    /// Registered tools that `name`'s disambiguation data recommends in
+    /// its place, deduplicated and in declaration order.
+    pub fn alternatives(&self, name: &str) -> Vec<DisambiguationExample> {
+        let Some(entry) = self.entry(name) else {
+            return Vec::new();
+        };
+        let mut seen = HashSet::new();
+        entry
+            .tool
+            .disambiguation()
+            .into_iter()
+            .filter(|d| d.correct_tool != name && self.has_tool(&d.correct_tool))
+            .filter(|d| seen.insert(d.correct_tool.clone()))
+            .collect()
     }
 
     /// Return tool definitions with compact descriptions.
     /// Tools that provide an `extended_description()` get their definition
     /// truncated to the first line. Others are unchanged.
     pub fn compact_definitions(&self) -> Vec<ToolDef> {
-        self.tools
-            .values()
-            .map(|t| {
-                let mut def = t.definition();
-                if t.extended_description().is_some() {
+        self.entries()
+            .iter()
+            .map(|e| {
+                let mut def = e.tool.definition();
+                if e.tool.extended_description().is_some() {
                     // Strip to first line as compact description.
                     if let Some(pos) = def.function.description.find('\n') {
                         def.function.description.truncate(pos);
@@ -319,25 +658,26 @@ impl ToolSet {
 
     /// Get extended description for a tool by name, if available.
     pub fn extended_description(&self, name: &str) -> Option<String> {
-        self.tools.get(name)?.extended_description()
+        self.entry(name)?.tool.extended_description()
     }
 
     /// Number of registered tools.
     pub fn len(&self) -> usize {
-        self.tools.len()
+        self.tools.read().unwrap().len()
     }
 
     /// Whether the set is empty.
     pub fn is_empty(&self) -> bool {
-        self.tools.is_empty()
+        self.len() == 0
     }
 
     /// Register all common tools ([`ReadFile`](super::common::ReadFile),
     /// [`ListDir`](super::common::ListDir), [`Grep`](super::common::Grep),
     /// [`FindFiles`](super::common::FindFiles), [`Shell`](super::common::Shell),
-    /// [`WebSearch`](super::common::WebSearch)) plus the [`ThinkTool`] and
-    /// [`TodoTool`] pseudo-tools. Common tools inherit the `ToolSet`'s
-    /// `max_result_bytes`.
+    /// [`WebSearch`](super::common::WebSearch), with the `web-search` feature)
+    /// plus the [`ThinkTool`], [`TodoTool`], [`ScratchpadTool`], and
+    /// [`PinTool`] pseudo-tools.
+    /// Common tools inherit the `ToolSet`'s `max_result_bytes`.
     ///
     /// This is a convenience method for the typical agent setup pattern.
     /// Use individual `.with()` calls if you need per-tool configuration.
@@ -347,6 +687,7 @@ impl ToolSet {
     /// [`Grep`]: crate::tools::common::Grep
     /// [`FindFiles`]: crate::tools::common::FindFiles
     /// [`Shell`]: crate::tools::common::Shell
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn with_common_tools(self, workdir: impl Into<String>) -> Self {
         self.with_common_tools_configured(workdir, CommonToolsConfig::default())
     }
@@ -368,68 +709,191 @@ impl ToolSet {
     ///         .find_max_results(200)
     ///         .shell_blocked_commands(vec!["rm -rf /".into(), "mkfs".into()]));
     /// ```
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn with_common_tools_configured(
         self,
         workdir: impl Into<String>,
         config: CommonToolsConfig,
     ) -> Self {
+        #[cfg(feature = "web-search")]
+        use crate::tools::common::WebSearch;
         use crate::tools::common::{
-            EditFile, FindFiles, Grep, ListDir, ReadFile, Shell, WebSearch, WriteFile,
+            EditFile, FindFiles, Grep, ListDir, ReadFile, Shell, WriteFile,
         };
         use crate::tools::read_tracker::ReadTracker;
         use std::sync::Arc;
 
         let workdir = workdir.into();
         let max = self.max_result_bytes;
+        let allowed = config.allowed_paths;
+        let errors = config.error_catalog;
 
         // Shared tracker for read-before-write enforcement across
         // ReadFile, EditFile, and WriteFile.
         let tracker = Arc::new(ReadTracker::new());
 
-        self.with(
-            ReadFile::new(workdir.clone())
-                .max_result_bytes(max)
-                .with_tracker(tracker.clone()),
-        )
-        .with(ListDir::new(workdir.clone()))
-        .with(
-            Grep::new(workdir.clone())
-                .max_matches(config.grep_max_matches)
-                .max_result_bytes(max),
-        )
-        .with(
-            FindFiles::new(workdir.clone())
-                .max_results(config.find_max_results)
-                .max_result_bytes(max),
-        )
-        .with(
-            Shell::new(workdir.clone())
-                .blocked_commands(config.shell_blocked_commands)
-                .max_result_bytes(max),
-        )
-        .with_if(
+        let mut shell = Shell::new(workdir.clone())
+            .blocked_commands(config.shell_blocked_commands)
+            .max_result_bytes(max)
+            .allowed_paths(allowed.clone())
+            .env(config.shell_env)
+            .limits(config.shell_limits)
+            .error_catalog(errors.clone());
+        if let Some(budget) = config.subprocess_budget {
+            shell = shell.budget(budget);
+        }
+
+        let tools = self
+            .with_error_catalog(errors.clone())
+            .with(
+                ReadFile::new(workdir.clone())
+                    .max_result_bytes(max)
+                    .with_tracker(tracker.clone())
+                    .allowed_paths(allowed.clone())
+                    .error_catalog(errors.clone()),
+            )
+            .with(
+                ListDir::new(workdir.clone())
+                    .allowed_paths(allowed.clone())
+                    .error_catalog(errors.clone()),
+            )
+            .with(
+                Grep::new(workdir.clone())
+                    .max_matches(config.grep_max_matches)
+                    .max_result_bytes(max)
+                    .allowed_paths(allowed.clone())
+                    .error_catalog(errors.clone()),
+            )
+            .with(
+                FindFiles::new(workdir.clone())
+                    .max_results(config.find_max_results)
+                    .max_result_bytes(max)
+                    .allowed_paths(allowed.clone())
+                    .error_catalog(errors.clone()),
+            )
+            .with(shell);
+        #[cfg(feature = "web-search")]
+        let tools = tools.with_if(
             std::env::var("BRAVE_SEARCH_KEY").is_ok(),
-            WebSearch::new().max_result_bytes(max),
+            WebSearch::new()
+                .max_result_bytes(max)
+                .error_catalog(errors.clone()),
+        );
+        tools
+            .with(
+                EditFile::new(workdir.clone(), tracker.clone())
+                    .allowed_paths(allowed.clone())
+                    .error_catalog(errors.clone()),
+            )
+            .with(
+                WriteFile::new(workdir, tracker)
+                    .allowed_paths(allowed)
+                    .error_catalog(errors),
+            )
+            .with(ThinkTool)
+            .with(TodoTool::new())
+            .with(ScratchpadTool::new())
+            .with(PinTool)
+    }
+
+    /// Register the human-in-the-loop tools: currently
+    /// [`AskUserTool`](crate::ui::ask_user_tool::AskUserTool), which asks
+    /// through `ui_state` (pass `None` for headless runs).
+    ///
+    /// A [`default_timeout`](Self::with_default_timeout) on the set also
+    /// bounds how long a question can wait; keep it above the ask timeout.
+    #[cfg(feature = "ui")]
+    pub fn with_interaction_tools(self, ui_state: Option<Arc<Mutex<crate::ui::UiState>>>) -> Self {
+        self.with_interaction_tools_configured(ui_state, InteractionToolsConfig::default())
+    }
+
+    /// Register the human-in-the-loop tools with configuration overrides.
+    /// See [`with_interaction_tools`](Self::with_interaction_tools).
+    #[cfg(feature = "ui")]
+    pub fn with_interaction_tools_configured(
+        self,
+        ui_state: Option<Arc<Mutex<crate::ui::UiState>>>,
+        config: InteractionToolsConfig,
+    ) -> Self {
+        use crate::ui::ask_user_tool::AskUserTool;
+
+        let mut ask = AskUserTool::new(ui_state)
+            .with_timeout(config.ask_timeout)
+            .with_headless_answer(config.headless_answer);
+        if let Some(workdir) = config.workdir {
+            ask = ask.with_workdir(workdir);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(webhook) = config.webhook {
+            ask = ask.with_webhook(webhook);
+        }
+        self.with(ask)
+    }
+
+    /// One-line display summary of a call to `tool_name`, guided by the
+    /// tool's parameter schema (see [`summary`](super::summary)).
+    pub fn summarize_call(&self, tool_name: &str, arguments: &str) -> String {
+        let definition = self.entry(tool_name).map(|e| e.tool.definition());
+        super::summary::summarize_call(
+            definition.as_ref().map(|d| &d.function.parameters),
+            arguments,
+            super::summary::SUMMARY_CHARS,
         )
-        .with(EditFile::new(workdir.clone(), tracker.clone()))
-        .with(WriteFile::new(workdir, tracker))
-        .with(ThinkTool)
-        .with(TodoTool::new())
     }
 
     /// Whether a tool's results are cacheable (read-only, deterministic).
     pub fn is_cacheable(&self, tool_name: &str) -> bool {
-        self.cacheable_tools.contains(tool_name)
+        self.entry(tool_name).is_some_and(|e| e.cacheable)
     }
 
     /// Whether a tool mutates state and should invalidate cached results.
     pub fn is_mutation_tool(&self, tool_name: &str) -> bool {
-        self.mutation_tools.contains(tool_name)
+        self.entry(tool_name).is_some_and(|e| e.mutation)
+    }
+
+    /// Whether the result of a call to `tool_name` with `arguments` may be
+    /// cached ([`Tool::cacheable`] and [`Tool::caches_call`]).
+    pub fn is_cacheable_call(&self, tool_name: &str, arguments: &str) -> bool {
+        self.entry(tool_name)
+            .is_some_and(|e| e.cacheable && e.tool.caches_call(arguments))
+    }
+
+    /// Whether a call to `tool_name` with `arguments` mutates state
+    /// ([`Tool::is_mutation`] and [`Tool::mutates_call`]).
+    pub fn is_mutation_call(&self, tool_name: &str, arguments: &str) -> bool {
+        self.entry(tool_name)
+            .is_some_and(|e| e.mutation && e.tool.mutates_call(arguments))
+    }
+
+    /// Whether a tool runs commands.
+    pub fn is_command_tool(&self, tool_name: &str) -> bool {
+        self.entry(tool_name).is_some_and(|e| e.commands)
+    }
+
+    /// Whether a tool accesses the network.
+    pub fn is_network_tool(&self, tool_name: &str) -> bool {
+        self.entry(tool_name).is_some_and(|e| e.network)
+    }
+
+    /// Cost in USD of a finished call, as reported by the tool
+    /// ([`Tool::call_cost_usd`]). `0.0` for unknown tools.
+    pub fn call_cost_usd(&self, tool_name: &str, arguments: &str, result: &str) -> f64 {
+        self.entry(tool_name)
+            .map_or(0.0, |e| e.tool.call_cost_usd(arguments, result))
     }
 
     /// Check if a tool is registered by name.
     pub fn has_tool(&self, name: &str) -> bool {
-        self.tools.contains_key(name)
+        self.tools.read().unwrap().contains_key(name)
+    }
+
+    /// Execution counts for a tool, across everything sharing this set.
+    /// `None` if the tool isn't registered.
+    pub fn usage(&self, name: &str) -> Option<ToolCallCounts> {
+        self.entry(name).map(|e| ToolCallCounts {
+            calls: e.calls.load(Ordering::Relaxed),
+            errors: e.errors.load(Ordering::Relaxed),
+        })
     }
 
     /// Generate composition-aware tool usage guidelines for the system prompt.
@@ -452,7 +916,7 @@ impl ToolSet {
         };
 
         // Composition rules: only emitted when both tools exist.
-        let has = |name: &str| self.tools.contains_key(name);
+        let has = |name: &str| self.has_tool(name);
 
         if has(super::names::READ_FILE) && has(super::names::EDIT_FILE) {
             add("Always read a file with read_file before editing it with edit_file.".into());
@@ -499,8 +963,8 @@ impl ToolSet {
         }
 
         // Collect per-tool guidelines.
-        for tool in self.tools.values() {
-            for g in tool.prompt_guidelines() {
+        for entry in self.entries() {
+            for g in entry.tool.prompt_guidelines() {
                 add(g);
             }
         }
@@ -526,24 +990,26 @@ impl ToolSet {
     /// Returns the (possibly truncated) result string.
     /// Returns an error string if the tool name is unknown.
     pub async fn execute(&self, name: &str, arguments: &str) -> String {
-        let tool = match self.tools.get(name) {
-            Some(t) => t,
-            None => return format!("Error: unknown tool '{name}'"),
+        let Some(entry) = self.entry(name) else {
+            return format!("Error: unknown tool '{name}'");
         };
+        let tool = entry.tool.as_ref();
+        entry.calls.fetch_add(1, Ordering::Relaxed);
 
         // Validate arguments against JSON Schema if enabled.
         if self.validate_args
-            && let Some(error) = validate_tool_arguments(tool.as_ref(), arguments)
+            && let Some(error) = validate_tool_arguments(tool, arguments)
         {
+            entry.errors.fetch_add(1, Ordering::Relaxed);
             return error;
         }
 
         log_tool_call(name, arguments);
-        let start = std::time::Instant::now();
+        let start = crate::platform::Instant::now();
 
         // Execute with optional timeout.
         let result = if let Some(timeout_duration) = self.default_timeout {
-            match tokio::time::timeout(timeout_duration, tool.execute(arguments)).await {
+            match crate::platform::timeout(timeout_duration, tool.execute(arguments)).await {
                 Ok(r) => r,
                 Err(_) => {
                     let elapsed = start.elapsed();
@@ -576,9 +1042,18 @@ impl ToolSet {
             trace!("Tool {name} result preview: {preview}");
         }
 
+        if result.starts_with("Error") {
+            entry.errors.fetch_add(1, Ordering::Relaxed);
+        }
+
         // Wrap errors in structured reflection for better LLM self-correction.
-        let result = if result.starts_with("Error:") {
-            super::reflection::format_tool_failure(name, arguments, &result)
+        let result = if result.starts_with("Error:") || super::reflection::is_unrunnable(&result) {
+            super::reflection::format_tool_failure_with(
+                &self.error_catalog,
+                name,
+                arguments,
+                &result,
+            )
         } else {
             result
         };
@@ -666,6 +1141,70 @@ impl Tool for DisabledTool {
     }
 }
 
+// ── NamespacedTool ────────────────────────────────────────────────
+
+/// A tool exposed under a prefixed name. Created by
+/// [`ToolSet::with_namespaced`].
+struct NamespacedTool {
+    prefix: String,
+    description_prefix: String,
+    inner: Arc<dyn Tool>,
+}
+
+impl Tool for NamespacedTool {
+    fn definition(&self) -> ToolDef {
+        let mut def = self.inner.definition();
+        def.function.name = format!("{}{}", self.prefix, def.function.name);
+        def.function.description =
+            format!("{}{}", self.description_prefix, def.function.description);
+        def
+    }
+
+    fn execute(&self, arguments: &str) -> ToolFuture<'_> {
+        self.inner.execute(arguments)
+    }
+
+    fn cacheable(&self) -> bool {
+        self.inner.cacheable()
+    }
+
+    fn is_mutation(&self) -> bool {
+        self.inner.is_mutation()
+    }
+
+    fn caches_call(&self, arguments: &str) -> bool {
+        self.inner.caches_call(arguments)
+    }
+
+    fn mutates_call(&self, arguments: &str) -> bool {
+        self.inner.mutates_call(arguments)
+    }
+
+    fn runs_commands(&self) -> bool {
+        self.inner.runs_commands()
+    }
+
+    fn accesses_network(&self) -> bool {
+        self.inner.accesses_network()
+    }
+
+    fn call_cost_usd(&self, arguments: &str, result: &str) -> f64 {
+        self.inner.call_cost_usd(arguments, result)
+    }
+
+    fn extended_description(&self) -> Option<String> {
+        self.inner.extended_description()
+    }
+
+    fn call_examples(&self) -> Vec<ToolCallExample> {
+        self.inner.call_examples()
+    }
+
+    fn disambiguation(&self) -> Vec<DisambiguationExample> {
+        self.inner.disambiguation()
+    }
+}
+
 // ── FnTool ────────────────────────────────────────────────────────
 
 /// A closure-based tool that auto-parses arguments and delegates to a handler.
@@ -704,6 +1243,14 @@ impl Tool for DisabledTool {
 ///
 /// let tools = ToolSet::new().with(tool);
 /// ```
+///
+/// [`FnTool::typed`] generates the schema from the argument type:
+///
+/// ```ignore
+/// let tool = FnTool::typed("search", "Search the knowledge base", |args: SearchArgs| async move {
+///     format!("Found {} results for: {}", args.limit, args.query)
+/// });
+/// ```
 /// Type-erased async handler for [`FnTool`].
 type ErasedToolHandler =
     Box<dyn Fn(String) -> Pin<Box<dyn Future<Output = String> + Send>> + Send + Sync>;
@@ -712,6 +1259,8 @@ pub struct FnTool {
     def: ToolDef,
     handler: ErasedToolHandler,
     mutation: bool,
+    call_examples: Vec<ToolCallExample>,
+    disambiguation: Vec<DisambiguationExample>,
 }
 
 impl FnTool {
@@ -721,21 +1270,50 @@ impl FnTool {
     /// from the raw JSON string) and returns a future that produces the result
     /// string. Parse errors are automatically formatted for the LLM.
     pub fn new<A, F, Fut>(def: ToolDef, handler: F) -> Self
+    where
+        A: serde::de::DeserializeOwned + Send + 'static,
+        F: Fn(A) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = String> + Send + 'static,
+    {
+        Self::with_parse_hint(def, handler, None)
+    }
+
+    /// Create a closure-based tool whose parameter schema is generated from
+    /// `A` with [`json_schema_for`](crate::json_schema_for).
+    ///
+    /// Arguments are parsed before the handler runs. On a parse failure the
+    /// LLM gets the error plus the expected parameters, e.g.
+    /// `Expected parameters: query (string, required), limit (integer).`
+    pub fn typed<A, F, Fut>(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        handler: F,
+    ) -> Self
+    where
+        A: serde::de::DeserializeOwned + JsonSchema + Send + 'static,
+        F: Fn(A) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = String> + Send + 'static,
+    {
+        let schema = crate::json_schema_for::<A>();
+        let hint = describe_parameters(&schema);
+        Self::with_parse_hint(ToolDef::new(name, description, schema), handler, hint)
+    }
+
+    fn with_parse_hint<A, F, Fut>(def: ToolDef, handler: F, hint: Option<String>) -> Self
     where
         A: serde::de::DeserializeOwned + Send + 'static,
         F: Fn(A) -> Fut + Send + Sync + 'static,
         Fut: Future<Output = String> + Send + 'static,
     {
         let erased = move |raw: String| -> Pin<Box<dyn Future<Output = String> + Send>> {
-            let args: A = match serde_json::from_str(&raw) {
+            let args: A = match parse_tool_args(&raw) {
                 Ok(a) => a,
                 Err(e) => {
-                    return Box::pin(async move {
-                        format!(
-                            "Error: invalid tool arguments: {e}. \
-                                 Please provide valid JSON matching the tool's parameter schema."
-                        )
-                    });
+                    let e = match hint {
+                        Some(ref hint) => format!("{e} Expected parameters: {hint}."),
+                        None => e,
+                    };
+                    return Box::pin(async move { e });
                 }
             };
             Box::pin(handler(args))
@@ -745,6 +1323,8 @@ impl FnTool {
             def,
             handler: Box::new(erased),
             mutation: false,
+            call_examples: Vec::new(),
+            disambiguation: Vec::new(),
         }
     }
 
@@ -753,6 +1333,30 @@ impl FnTool {
         self.mutation = is_mutation;
         self
     }
+
+    /// Add a worked call for few-shot demonstrations (builder pattern).
+    pub fn call_example(mut self, arguments: serde_json::Value, result: impl Into<String>) -> Self {
+        self.call_examples
+            .push(ToolCallExample::new(arguments, result));
+        self
+    }
+
+    /// Name `correct_tool` as the better choice in `scenario` (builder
+    /// pattern). Only used to suggest alternatives after repeated failures;
+    /// it is not added to the description.
+    pub fn disambiguate(
+        mut self,
+        scenario: impl Into<String>,
+        correct_tool: impl Into<String>,
+        reason: impl Into<String>,
+    ) -> Self {
+        self.disambiguation.push(DisambiguationExample {
+            scenario: scenario.into(),
+            correct_tool: correct_tool.into(),
+            reason: reason.into(),
+        });
+        self
+    }
 }
 
 impl Tool for FnTool {
@@ -770,6 +1374,14 @@ impl Tool for FnTool {
     fn is_mutation(&self) -> bool {
         self.mutation
     }
+
+    fn call_examples(&self) -> Vec<ToolCallExample> {
+        self.call_examples.clone()
+    }
+
+    fn disambiguation(&self) -> Vec<DisambiguationExample> {
+        self.disambiguation.clone()
+    }
 }
 
 impl fmt::Debug for FnTool {
@@ -783,6 +1395,25 @@ impl fmt::Debug for FnTool {
 
 // ── Helpers ────────────────────────────────────────────────────────
 
+/// Conversion of a tool method's return value into a result string, so
+/// `#[derive(CinchTool)]` methods can return `String` or
+/// `Result<String, E>`. Errors become `Error: {e}`.
+pub trait IntoToolResult {
+    fn into_tool_result(self) -> String;
+}
+
+impl IntoToolResult for String {
+    fn into_tool_result(self) -> String {
+        self
+    }
+}
+
+impl<E: fmt::Display> IntoToolResult for Result<String, E> {
+    fn into_tool_result(self) -> String {
+        self.unwrap_or_else(|e| format!("Error: {e}"))
+    }
+}
+
 /// Validate tool arguments against the tool's declared JSON Schema.
 ///
 /// Returns `None` if valid, or `Some(error_string)` if validation fails.
@@ -940,6 +1571,42 @@ pub fn parse_tool_args<T: serde::de::DeserializeOwned>(arguments: &str) -> Resul
     })
 }
 
+/// One-line summary of a JSON Schema's properties, e.g.
+/// `query (string, required), limit (integer)`. `None` when the schema has
+/// no properties.
+fn describe_parameters(schema: &serde_json::Value) -> Option<String> {
+    let properties = schema.get("properties")?.as_object()?;
+    if properties.is_empty() {
+        return None;
+    }
+    let required: HashSet<&str> = schema
+        .get("required")
+        .and_then(|r| r.as_array())
+        .map(|r| r.iter().filter_map(|v| v.as_str()).collect())
+        .unwrap_or_default();
+    let described: Vec<String> = properties
+        .iter()
+        .map(|(name, property)| {
+            let kind = match property.get("type") {
+                Some(serde_json::Value::String(kind)) => kind.clone(),
+                Some(serde_json::Value::Array(kinds)) => kinds
+                    .iter()
+                    .filter_map(|k| k.as_str())
+                    .filter(|k| *k != "null")
+                    .collect::<Vec<_>>()
+                    .join("|"),
+                _ => "value".to_string(),
+            };
+            if required.contains(name.as_str()) {
+                format!("{name} ({kind}, required)")
+            } else {
+                format!("{name} ({kind})")
+            }
+        })
+        .collect();
+    Some(described.join(", "))
+}
+
 /// Extract a string value from tool-call arguments JSON.
 pub fn parse_string_arg(args: &serde_json::Value, key: &str) -> Option<String> {
     args.get(key)
@@ -1141,6 +1808,279 @@ impl Tool for TodoTool {
     }
 }
 
+/// Pseudo-tool letting the model mark earlier tool results as important so
+/// context eviction keeps them longest. The harness applies the pin to its
+/// eviction metadata (see [`eviction::apply_pin()`](crate::context::eviction::apply_pin));
+/// the tool itself only acknowledges the call.
+pub struct PinTool;
+
+/// Typed arguments for the `pin` pseudo-tool.
+#[derive(Deserialize, JsonSchema)]
+pub struct PinArgs {
+    /// Name of the tool whose result to pin (e.g. "read_file").
+    pub tool: String,
+    /// Text identifying which call to pin, matched against its arguments
+    /// (e.g. a file path). Omit to pin the tool's most recent result.
+    #[serde(default)]
+    pub target: Option<String>,
+    /// How important the result is: "high" (default), "critical", or
+    /// "normal" to unpin.
+    #[serde(default)]
+    pub importance: Option<crate::context::eviction::Importance>,
+    /// Short note on why the result matters; kept even if the result is
+    /// later cleared from context.
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+impl PinArgs {
+    /// The requested importance, defaulting to `High`.
+    pub fn importance(&self) -> crate::context::eviction::Importance {
+        self.importance
+            .unwrap_or(crate::context::eviction::Importance::High)
+    }
+}
+
+impl Tool for PinTool {
+    fn definition(&self) -> ToolDef {
+        ToolDef::new(
+            super::names::PIN,
+            "Mark an earlier tool result as important so it stays in context \
+             longest when old results are cleared to free space. Use it for \
+             results you will need again (key file contents, test failures). \
+             This does not perform any action.",
+            crate::json_schema_for::<PinArgs>(),
+        )
+    }
+
+    fn execute(&self, arguments: &str) -> ToolFuture<'_> {
+        let arguments = arguments.to_string();
+        Box::pin(async move {
+            match serde_json::from_str::<PinArgs>(&arguments) {
+                Ok(args) => format!("Marked {} result as {:?}.", args.tool, args.importance())
+                    .to_lowercase(),
+                Err(e) => format!("Error: invalid arguments: {e}"),
+            }
+        })
+    }
+}
+
+/// Pseudo-tool for long intermediate notes kept outside the conversation.
+/// Notes are stored in the tool, keyed by name, and only enter context when
+/// the model reads them back. The harness replaces the `content` of stored
+/// `write`/`append` calls with a placeholder (see
+/// [`ScratchpadTool::elide_content`]), so unlike `think` a note costs context
+/// once, in the round that writes it.
+pub struct ScratchpadTool {
+    notes: Mutex<BTreeMap<String, String>>,
+}
+
+impl ScratchpadTool {
+    pub fn new() -> Self {
+        Self {
+            notes: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// The note stored under `key`, if any.
+    pub fn note(&self, key: &str) -> Option<String> {
+        self.notes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(key)
+            .cloned()
+    }
+
+    /// Arguments of a `write`/`append` call with the content replaced by a
+    /// placeholder, for the copy of the call kept in history. `None` for
+    /// other calls.
+    pub fn elide_content(arguments: &str) -> Option<String> {
+        let mut args: serde_json::Value = serde_json::from_str(arguments).ok()?;
+        if !matches!(
+            args.get("action").and_then(|a| a.as_str()),
+            Some("write" | "append")
+        ) {
+            return None;
+        }
+        let chars = args.get("content")?.as_str()?.chars().count();
+        args["content"] = format!("[{chars} chars stored in scratchpad]").into();
+        Some(args.to_string())
+    }
+
+    fn format_index(notes: &BTreeMap<String, String>) -> String {
+        if notes.is_empty() {
+            return "Scratchpad is empty.".into();
+        }
+        let mut out = String::from("Scratchpad notes:\n");
+        for (key, note) in notes {
+            out.push_str(&format!("  {key} ({} chars)\n", note.chars().count()));
+        }
+        out
+    }
+}
+
+impl Default for ScratchpadTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Action for the scratchpad tool.
+#[derive(Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScratchpadAction {
+    Write,
+    Append,
+    Read,
+    List,
+    Delete,
+}
+
+/// Typed arguments for the `scratchpad` pseudo-tool.
+#[derive(Deserialize, JsonSchema)]
+pub struct ScratchpadArgs {
+    /// The action to perform.
+    pub action: ScratchpadAction,
+    /// Name of the note (required except for 'list').
+    #[serde(default)]
+    pub key: Option<String>,
+    /// Text to store (required for 'write' and 'append').
+    #[serde(default)]
+    pub content: Option<String>,
+}
+
+impl Tool for ScratchpadTool {
+    fn definition(&self) -> ToolDef {
+        ToolDef::new(
+            super::names::SCRATCHPAD,
+            "Keep long intermediate notes (drafts, findings, partial results) \
+             outside the conversation. Written content is not repeated in later \
+             rounds, so it costs no context until you read it back. Actions: \
+             'write' (store content under key, replacing it), 'append' (add \
+             content to key), 'read' (return the note for key), 'list' (show \
+             note names and sizes), 'delete' (remove key).",
+            crate::json_schema_for::<ScratchpadArgs>(),
+        )
+    }
+
+    fn execute(&self, arguments: &str) -> ToolFuture<'_> {
+        let arguments = arguments.to_string();
+        Box::pin(async move {
+            let args: ScratchpadArgs = match serde_json::from_str(&arguments) {
+                Ok(a) => a,
+                Err(e) => {
+                    return format!(
+                        "Error: invalid arguments: {e}. Use: write, append, read, list, delete."
+                    );
+                }
+            };
+            let mut notes = self.notes.lock().unwrap_or_else(|e| e.into_inner());
+            if args.action == ScratchpadAction::List {
+                return Self::format_index(&notes);
+            }
+            let key = match args.key {
+                Some(k) if !k.is_empty() => k,
+                _ => {
+                    return format!("Error: 'key' is required for '{:?}'.", args.action)
+                        .to_lowercase();
+                }
+            };
+            match args.action {
+                ScratchpadAction::Write | ScratchpadAction::Append => {
+                    let Some(content) = args.content else {
+                        return "Error: 'content' is required for 'write' and 'append'.".into();
+                    };
+                    let note = notes.entry(key.clone()).or_default();
+                    if args.action == ScratchpadAction::Write {
+                        note.clear();
+                    }
+                    note.push_str(&content);
+                    format!(
+                        "Saved scratchpad note '{key}' ({} chars).",
+                        note.chars().count()
+                    )
+                }
+                ScratchpadAction::Read => match notes.get(&key) {
+                    Some(note) => note.clone(),
+                    None => format!(
+                        "Error: no scratchpad note '{key}'. {}",
+                        Self::format_index(&notes)
+                    ),
+                },
+                ScratchpadAction::Delete => match notes.remove(&key) {
+                    Some(_) => format!("Deleted scratchpad note '{key}'."),
+                    None => format!("Error: no scratchpad note '{key}'."),
+                },
+                ScratchpadAction::List => unreachable!(),
+            }
+        })
+    }
+}
+
+/// Default number of excerpts returned by [`RecallHistoryTool`].
+const DEFAULT_RECALL_EXCERPTS: usize = 5;
+
+/// Searches the [`HistoryArchive`](crate::context::archive::HistoryArchive)
+/// of compacted messages and returns matching excerpts, so the model can
+/// recover details that compaction summarized away. The harness registers
+/// this tool automatically when
+/// [`HarnessConfig::history_archive_dir`](crate::agent::config::HarnessConfig::history_archive_dir)
+/// is set.
+pub struct RecallHistoryTool {
+    archive: crate::context::archive::HistoryArchive,
+}
+
+impl RecallHistoryTool {
+    /// Search the archive stored in `dir`.
+    pub fn new(dir: impl Into<std::path::PathBuf>) -> Self {
+        Self {
+            archive: crate::context::archive::HistoryArchive::new(dir),
+        }
+    }
+}
+
+/// Typed arguments for the `recall_history` tool.
+#[derive(Deserialize, JsonSchema)]
+pub struct RecallHistoryArgs {
+    /// Keywords to search for (e.g. an error code, file name, or function).
+    pub query: String,
+    /// Maximum number of excerpts to return. Default: 5.
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl Tool for RecallHistoryTool {
+    fn definition(&self) -> ToolDef {
+        ToolDef::new(
+            super::names::RECALL_HISTORY,
+            "Search earlier conversation history that was summarized to save \
+             context, and return the original messages that match. Use it when \
+             the summary mentions something you need exact details of (an error \
+             message, a file's content, command output).",
+            crate::json_schema_for::<RecallHistoryArgs>(),
+        )
+    }
+
+    fn execute(&self, arguments: &str) -> ToolFuture<'_> {
+        let arguments = arguments.to_string();
+        Box::pin(async move {
+            let args = match serde_json::from_str::<RecallHistoryArgs>(&arguments) {
+                Ok(args) => args,
+                Err(e) => return format!("Error: invalid arguments: {e}"),
+            };
+            let limit = args.limit.unwrap_or(DEFAULT_RECALL_EXCERPTS).max(1);
+            let hits = self.archive.search(&args.query, limit);
+            if hits.is_empty() {
+                return format!("No archived history matches '{}'.", args.query);
+            }
+            hits.iter()
+                .map(|h| format!("[compaction {}, {}]\n{}", h.compaction, h.role, h.text))
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        })
+    }
+}
+
 // ── Tests ──────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -1191,6 +2131,119 @@ mod tests {
         assert_eq!(tool.name(), "echo");
     }
 
+    #[tokio::test]
+    async fn typed_fn_tool_generates_schema_and_parses_arguments() {
+        #[derive(Deserialize, JsonSchema)]
+        struct SearchArgs {
+            query: String,
+            limit: Option<u32>,
+        }
+
+        let tool = FnTool::typed("search", "Search notes", |args: SearchArgs| async move {
+            format!("{} (limit {})", args.query, args.limit.unwrap_or(10))
+        });
+        let def = tool.definition();
+        assert_eq!(def.function.name, "search");
+        assert_eq!(
+            def.function.parameters["required"],
+            serde_json::json!(["query"])
+        );
+
+        assert_eq!(tool.execute(r#"{"query":"rust"}"#).await, "rust (limit 10)");
+        let error = tool.execute(r#"{"limit":3}"#).await;
+        assert!(error.starts_with("Error: invalid tool arguments: missing field `query`"));
+        assert!(
+            error.ends_with("Expected parameters: limit (integer), query (string, required)."),
+            "{error}"
+        );
+    }
+
+    #[tokio::test]
+    async fn scratchpad_stores_notes_outside_the_call() {
+        let pad = ScratchpadTool::new();
+        let write = r#"{"action":"write","key":"draft","content":"line one\n"}"#;
+        assert_eq!(
+            pad.execute(write).await,
+            "Saved scratchpad note 'draft' (9 chars)."
+        );
+        pad.execute(r#"{"action":"append","key":"draft","content":"line two"}"#)
+            .await;
+        assert_eq!(
+            pad.execute(r#"{"action":"read","key":"draft"}"#).await,
+            "line one\nline two"
+        );
+        assert_eq!(
+            pad.execute(r#"{"action":"list"}"#).await,
+            "Scratchpad notes:\n  draft (17 chars)\n"
+        );
+        assert!(
+            pad.execute(r#"{"action":"read","key":"missing"}"#)
+                .await
+                .starts_with("Error:")
+        );
+
+        let elided = ScratchpadTool::elide_content(write).unwrap();
+        assert!(
+            elided.contains("[9 chars stored in scratchpad]"),
+            "{elided}"
+        );
+        assert!(!elided.contains("line one"));
+        assert_eq!(
+            ScratchpadTool::elide_content(r#"{"action":"read","key":"draft"}"#),
+            None
+        );
+        pad.execute(r#"{"action":"delete","key":"draft"}"#).await;
+        assert_eq!(pad.note("draft"), None);
+    }
+
+    #[tokio::test]
+    async fn toolset_with_namespaced_prefixes_names() {
+        let other = ToolSet::new().with(EchoTool);
+        let set = ToolSet::new()
+            .with(EchoTool)
+            .with_namespaced("b__", "[root b] ", other);
+
+        assert!(set.has_tool("echo"));
+        assert!(set.has_tool("b__echo"));
+        let def = set
+            .definitions()
+            .into_iter()
+            .find(|d| d.function.name == "b__echo")
+            .unwrap();
+        assert_eq!(def.function.description, "[root b] Echo the input");
+        let out = set.execute("b__echo", r#"{"text":"hi"}"#).await;
+        assert_eq!(out, "hi");
+    }
+
+    #[tokio::test]
+    async fn toolset_fork_is_independent() {
+        let set = ToolSet::new().with(EchoTool);
+        set.execute("echo", r#"{"text":"hi"}"#).await;
+        let forked = set.fork().with(FailTool);
+
+        assert!(forked.has_tool("echo"));
+        assert!(!set.has_tool("fail"));
+        assert_eq!(forked.usage("echo").unwrap().calls, 0);
+        assert_eq!(set.usage("echo").unwrap().calls, 1);
+
+        let only = forked.fork_only(&["fail", "missing"]);
+        assert_eq!(only.names(), ["fail"]);
+    }
+
+    #[test]
+    fn read_only_view_drops_mutation_tools() {
+        let write = FnTool::new(
+            ToolDef::new("write", "Write", serde_json::json!({})),
+            |_: serde_json::Value| async { String::new() },
+        )
+        .mutation(true);
+        let set = ToolSet::new().with(EchoTool).with(write);
+
+        let view = set.read_only_view();
+        assert_eq!(view.names(), ["echo"]);
+        assert!(set.has_tool("write"));
+    }
+
     #[test]
     fn toolset_register_and_definitions() {
         let set = ToolSet::new().with(EchoTool).with(FailTool);
@@ -1237,6 +2290,24 @@ mod tests {
         assert!(result.contains("[truncated: 200 bytes total]"));
     }
 
+    #[tokio::test]
+    async fn recall_history_returns_archived_excerpts() {
+        let dir = tempfile::tempdir().unwrap();
+        crate::context::HistoryArchive::new(dir.path())
+            .store(
+                1,
+                &[crate::Message::tool_result("c1", "panic at src/lib.rs:42")],
+            )
+            .unwrap();
+        let tool = RecallHistoryTool::new(dir.path());
+
+        let result = tool.execute(r#"{"query":"panic"}"#).await;
+        assert!(result.starts_with("[compaction 1, tool]"));
+        assert!(result.contains("src/lib.rs:42"));
+        let result = tool.execute(r#"{"query":"timeout"}"#).await;
+        assert!(result.starts_with("No archived history"));
+    }
+
     #[test]
     fn truncate_short_unchanged() {
         assert_eq!(truncate_result("hello".into(), 100), "hello");
@@ -1322,8 +2393,8 @@ mod tests {
     #[test]
     fn with_common_tools_registers_all() {
         let set = ToolSet::new().with_common_tools("/tmp");
-        // 5 common tools + edit_file + write_file + think + todo = 9
-        assert_eq!(set.len(), 9);
+        // 5 common tools + edit_file + write_file + think + todo + scratchpad + pin = 11
+        assert_eq!(set.len(), 11);
 
         let defs = set.definitions();
         let names: Vec<String> = defs.iter().map(|d| d.function.name.clone()).collect();
@@ -1336,6 +2407,7 @@ mod tests {
         assert!(names.contains(&"shell".to_string()));
         assert!(names.contains(&"think".to_string()));
         assert!(names.contains(&"todo".to_string()));
+        assert!(names.contains(&"scratchpad".to_string()));
     }
 
     #[test]
@@ -1343,7 +2415,7 @@ mod tests {
         let set = ToolSet::new()
             .with_max_result_bytes(5000)
             .with_common_tools("/tmp");
-        assert_eq!(set.len(), 9);
+        assert_eq!(set.len(), 11);
         // The ToolSet's own max_result_bytes is set.
         assert_eq!(set.max_result_bytes, 5000);
     }
@@ -1351,7 +2423,31 @@ mod tests {
     #[test]
     fn with_common_tools_composable_with_custom_tools() {
         let set = ToolSet::new().with_common_tools("/tmp").with(EchoTool);
-        assert_eq!(set.len(), 10);
+        assert_eq!(set.len(), 12);
+    }
+
+    #[cfg(feature = "ui")]
+    #[tokio::test]
+    async fn with_interaction_tools_registers_ask_user() {
+        use crate::ui::ask_user_tool::HeadlessAnswer;
+
+        let set = ToolSet::new()
+            .with_common_tools("/tmp")
+            .with_interaction_tools(None);
+        assert_eq!(set.len(), 12);
+        assert!(set.has_tool(super::super::names::ASK_USER));
+
+        let set = ToolSet::new().with_interaction_tools_configured(
+            None,
+            InteractionToolsConfig::default().headless_answer(HeadlessAnswer::Error),
+        );
+        let result = set
+            .execute(
+                super::super::names::ASK_USER,
+                r#"{"prompt": "Pick:", "choices": ["A", "B"]}"#,
+            )
+            .await;
+        assert!(result.contains("Error: no operator"), "got: {result}");
     }
 
     #[test]
@@ -1516,8 +2612,8 @@ mod tests {
     fn with_common_tools_configured_registers_all() {
         let config = CommonToolsConfig::default().grep_max_matches(500);
         let set = ToolSet::new().with_common_tools_configured("/tmp", config);
-        // Same 9 tools as with_common_tools.
-        assert_eq!(set.len(), 9);
+        // Same 11 tools as with_common_tools.
+        assert_eq!(set.len(), 11);
 
         let defs = set.definitions();
         let names: Vec<String> = defs.iter().map(|d| d.function.name.clone()).collect();
@@ -1529,4 +2625,58 @@ mod tests {
         assert!(names.contains(&"think".to_string()));
         assert!(names.contains(&"todo".to_string()));
     }
+
+    #[tokio::test]
+    async fn common_tools_use_the_configured_error_catalog() {
+        use crate::tools::errors::ToolErrorKind;
+
+        let catalog = ToolErrorCatalog::default()
+            .message(ToolErrorKind::CommandBlocked, "Error: not allowed here")
+            .suggestions(ToolErrorKind::CommandBlocked, ["Ask the operator."]);
+        let config = CommonToolsConfig::default().error_catalog(Arc::new(catalog));
+        let set = ToolSet::new().with_common_tools_configured("/tmp", config);
+
+        let result = set.execute("shell", r#"{"command":"rm -rf /"}"#).await;
+        assert!(result.contains("Error: not allowed here"), "{result}");
+        assert!(result.contains("  - Ask the operator."), "{result}");
+    }
+
+    #[tokio::test]
+    async fn shared_toolset_registers_and_counts_concurrently() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<ToolSet>();
+
+        let set = Arc::new(ToolSet::new().with(EchoTool));
+        let mut handles = Vec::new();
+        for i in 0..8 {
+            let set = set.clone();
+            handles.push(tokio::spawn(async move {
+                if i == 0 {
+                    set.register(FailTool);
+                }
+                set.execute("echo", r#"{"text":"hi"}"#).await
+            }));
+        }
+        for handle in handles {
+            assert_eq!(handle.await.unwrap(), "hi");
+        }
+        assert!(set.has_tool("fail"));
+        set.execute("fail", "{}").await;
+
+        assert_eq!(
+            set.usage("echo"),
+            Some(ToolCallCounts {
+                calls: 8,
+                errors: 0
+            })
+        );
+        assert_eq!(
+            set.usage("fail"),
+            Some(ToolCallCounts {
+                calls: 1,
+                errors: 1
+            })
+        );
+        assert!(set.usage("missing").is_none());
+    }
 }
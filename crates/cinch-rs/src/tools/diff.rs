@@ -0,0 +1,326 @@
+//! Line diffs for successful file-editing tool calls.
+//!
+//! After every tool result the harness calls [`FileEdit::from_tool_call_on`]
+//! (or [`FileEdit::from_tool_call`] without the file's prior content). For a
+//! successful `edit_file` or `write_file` call it rebuilds what changed and
+//! emits
+//! [`HarnessEvent::FileEdited`](crate::agent::events::HarnessEvent::FileEdited),
+//! so frontends can render a unified diff instead of parsing result strings.
+//!
+//! When the harness captured the file's content before the call,
+//! [`FileEdit::from_tool_call_on`] replays the call on it and diffs the
+//! whole file, so `write_file` shows what it replaced and each `replace_all`
+//! occurrence gets its own hunk. Otherwise `edit_file` diffs `old_string`
+//! against `new_string`, anchored at the line reported in the tool result,
+//! and `write_file` shows the written content as added lines.
+
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+
+use super::names;
+
+/// The `edit_file` arguments a diff needs. Mirrors
+/// `common::EditFileArgs`, which is not available on wasm32.
+#[derive(Deserialize)]
+struct EditFileArgs {
+    path: String,
+    old_string: String,
+    new_string: String,
+    #[serde(default)]
+    replace_all: Option<bool>,
+}
+
+/// The `write_file` arguments a diff needs.
+#[derive(Deserialize)]
+struct WriteFileArgs {
+    path: String,
+    content: String,
+}
+
+/// Number of lines added and removed by an edit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffStats {
+    pub added: usize,
+    pub removed: usize,
+}
+
+/// A file change made by a tool call, as a unified diff.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileEdit {
+    /// Path as passed to the tool (relative to its workdir).
+    pub path: String,
+    /// Unified diff hunk(s), starting with an `@@` header.
+    pub diff: String,
+    pub stats: DiffStats,
+}
+
+impl FileEdit {
+    /// Reconstruct the change made by a tool call, or `None` if the call is
+    /// not a file edit or did not succeed.
+    ///
+    /// Matches `edit_file` / `write_file` and namespaced variants such as
+    /// `infra__edit_file`.
+    pub fn from_tool_call(name: &str, arguments: &str, result: &str) -> Option<Self> {
+        if result.starts_with("Error") {
+            return None;
+        }
+        if is_tool(name, names::EDIT_FILE) {
+            let args: EditFileArgs = serde_json::from_str(arguments).ok()?;
+            let start = first_line_number(result).unwrap_or(1);
+            let (diff, mut stats) = unified_diff(&args.old_string, &args.new_string, start);
+            let count = occurrence_count(result);
+            stats.added *= count;
+            stats.removed *= count;
+            Some(Self {
+                path: args.path,
+                diff,
+                stats,
+            })
+        } else if is_tool(name, names::WRITE_FILE) {
+            let args: WriteFileArgs = serde_json::from_str(arguments).ok()?;
+            let (diff, stats) = unified_diff("", &args.content, 1);
+            Some(Self {
+                path: args.path,
+                diff,
+                stats,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Like [`from_tool_call`](Self::from_tool_call), given the file's
+    /// content `before` the call (empty if it didn't exist). Returns the
+    /// edit and the file's content after it, or `None` if the call is not
+    /// a successful file edit or doesn't apply to `before`.
+    pub fn from_tool_call_on(
+        name: &str,
+        arguments: &str,
+        result: &str,
+        before: &str,
+    ) -> Option<(Self, String)> {
+        if result.starts_with("Error") {
+            return None;
+        }
+        let (path, after) = if is_tool(name, names::EDIT_FILE) {
+            let args: EditFileArgs = serde_json::from_str(arguments).ok()?;
+            if args.old_string.is_empty() || !before.contains(&args.old_string) {
+                return None;
+            }
+            let after = if args.replace_all.unwrap_or(false) {
+                before.replace(&args.old_string, &args.new_string)
+            } else {
+                before.replacen(&args.old_string, &args.new_string, 1)
+            };
+            (args.path, after)
+        } else if is_tool(name, names::WRITE_FILE) {
+            let args: WriteFileArgs = serde_json::from_str(arguments).ok()?;
+            (args.path, args.content)
+        } else {
+            return None;
+        };
+        let (diff, stats) = file_diff(before, &after);
+        Some((Self { path, diff, stats }, after))
+    }
+
+    /// Whether `name` is the harness workdir's own `edit_file` or
+    /// `write_file`, whose paths are relative to that workdir. Prefixed
+    /// copies for other roots (e.g. `infra__edit_file`) are not.
+    pub fn is_file_edit_tool(name: &str) -> bool {
+        name == names::EDIT_FILE || name == names::WRITE_FILE
+    }
+}
+
+/// Whole-file line diff of `old` → `new`: one hunk per changed region, with
+/// three lines of context.
+fn file_diff(old: &str, new: &str) -> (String, DiffStats) {
+    let text_diff = TextDiff::from_lines(old, new);
+    let mut stats = DiffStats::default();
+    for change in text_diff.iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Delete => stats.removed += 1,
+            ChangeTag::Insert => stats.added += 1,
+            ChangeTag::Equal => {}
+        }
+    }
+    let diff = text_diff.unified_diff().context_radius(3).to_string();
+    (diff, stats)
+}
+
+/// Line diff of `old` → `new` as a single unified-diff hunk whose line
+/// numbers start at `start_line`.
+pub fn unified_diff(old: &str, new: &str, start_line: usize) -> (String, DiffStats) {
+    let text_diff = TextDiff::from_lines(old, new);
+    let mut body = String::new();
+    let mut stats = DiffStats::default();
+    for change in text_diff.iter_all_changes() {
+        let sign = match change.tag() {
+            ChangeTag::Equal => ' ',
+            ChangeTag::Delete => {
+                stats.removed += 1;
+                '-'
+            }
+            ChangeTag::Insert => {
+                stats.added += 1;
+                '+'
+            }
+        };
+        body.push(sign);
+        body.push_str(change.value().trim_end_matches('\n'));
+        body.push('\n');
+    }
+
+    let old_len = old.lines().count();
+    let new_len = new.lines().count();
+    let range = |len: usize| {
+        if len == 0 {
+            format!("{},0", start_line.saturating_sub(1))
+        } else {
+            format!("{start_line},{len}")
+        }
+    };
+    let diff = format!("@@ -{} +{} @@\n{body}", range(old_len), range(new_len));
+    (diff, stats)
+}
+
+fn is_tool(name: &str, tool: &str) -> bool {
+    name.strip_suffix(tool)
+        .is_some_and(|prefix| prefix.is_empty() || prefix.ends_with('_'))
+}
+
+/// First line number from an `edit_file` result such as
+/// `"... (line 12)"` or `"... (lines 12-14)"`.
+fn first_line_number(result: &str) -> Option<usize> {
+    let (_, rest) = result.split_once("(line")?;
+    let rest = rest.trim_start_matches('s').trim_start();
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+/// Occurrence count from a `"replaced N occurrences"` result; 1 otherwise.
+fn occurrence_count(result: &str) -> usize {
+    result
+        .split_once("replaced ")
+        .and_then(|(_, rest)| rest.split_whitespace().next())
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_diff_is_anchored_at_reported_line() {
+        let args = serde_json::json!({
+            "path": "src/lib.rs",
+            "old_string": "fn a() {}\nfn b() {}\n",
+            "new_string": "fn a() {}\nfn c() {}\nfn d() {}\n",
+        })
+        .to_string();
+        let edit = FileEdit::from_tool_call(
+            "edit_file",
+            &args,
+            "Edited src/lib.rs: replaced 1 occurrence (lines 10-11)",
+        )
+        .unwrap();
+        assert_eq!(edit.path, "src/lib.rs");
+        assert_eq!(
+            edit.stats,
+            DiffStats {
+                added: 2,
+                removed: 1
+            }
+        );
+        assert_eq!(
+            edit.diff,
+            "@@ -10,2 +10,3 @@\n fn a() {}\n-fn b() {}\n+fn c() {}\n+fn d() {}\n"
+        );
+    }
+
+    #[test]
+    fn replace_all_scales_stats() {
+        let args = r#"{"path":"a.txt","old_string":"x","new_string":"y","replace_all":true}"#;
+        let edit = FileEdit::from_tool_call(
+            "infra__edit_file",
+            args,
+            "Edited a.txt: replaced 3 occurrences",
+        )
+        .unwrap();
+        assert_eq!(
+            edit.stats,
+            DiffStats {
+                added: 3,
+                removed: 3
+            }
+        );
+        assert!(edit.diff.starts_with("@@ -1,1 +1,1 @@\n"));
+    }
+
+    #[test]
+    fn write_shows_content_as_added() {
+        let args = r#"{"path":"new.rs","content":"a\nb\n"}"#;
+        let edit = FileEdit::from_tool_call("write_file", args, "Wrote 2 lines to new.rs").unwrap();
+        assert_eq!(edit.diff, "@@ -0,0 +1,2 @@\n+a\n+b\n");
+        assert_eq!(
+            edit.stats,
+            DiffStats {
+                added: 2,
+                removed: 0
+            }
+        );
+    }
+
+    #[test]
+    fn write_on_existing_content_shows_what_it_replaced() {
+        let args = r#"{"path":"a.rs","content":"a\nB\nc\n"}"#;
+        let (edit, after) =
+            FileEdit::from_tool_call_on("write_file", args, "Wrote 3 lines", "a\nb\nc\n").unwrap();
+        assert_eq!(after, "a\nB\nc\n");
+        assert_eq!(edit.diff, "@@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n");
+        assert_eq!(
+            edit.stats,
+            DiffStats {
+                added: 1,
+                removed: 1
+            }
+        );
+    }
+
+    #[test]
+    fn only_workdir_tools_are_diffed_against_the_workdir() {
+        assert!(FileEdit::is_file_edit_tool("edit_file"));
+        assert!(FileEdit::is_file_edit_tool("write_file"));
+        assert!(!FileEdit::is_file_edit_tool("infra__edit_file"));
+        assert!(!FileEdit::is_file_edit_tool("read_file"));
+    }
+
+    #[test]
+    fn replace_all_on_content_diffs_each_occurrence() {
+        let before = format!("x\n{}x\n", "keep\n".repeat(10));
+        let args = r#"{"path":"a.txt","old_string":"x","new_string":"y","replace_all":true}"#;
+        let (edit, after) =
+            FileEdit::from_tool_call_on("edit_file", args, "replaced 2 occurrences", &before)
+                .unwrap();
+        assert_eq!(after, before.replace('x', "y"));
+        assert_eq!(edit.diff.matches("@@ -").count(), 2);
+        assert_eq!(
+            edit.stats,
+            DiffStats {
+                added: 2,
+                removed: 2
+            }
+        );
+
+        // Content the call doesn't apply to falls back to the argument diff.
+        assert!(FileEdit::from_tool_call_on("edit_file", args, "ok", "z\n").is_none());
+    }
+
+    #[test]
+    fn ignores_errors_and_other_tools() {
+        let args = r#"{"path":"a","content":"b"}"#;
+        assert!(FileEdit::from_tool_call("write_file", args, "Error: nope").is_none());
+        assert!(FileEdit::from_tool_call("read_file", args, "ok").is_none());
+        assert!(FileEdit::from_tool_call("rewrite_file", args, "ok").is_none());
+    }
+}
@@ -0,0 +1,380 @@
+//! Disk-backed tool result cache shared across runs.
+//!
+//! [`ToolResultCache`](super::cache::ToolResultCache) lives for one run. A
+//! [`DiskToolCache`] persists cacheable tool results (e.g. `read_file`,
+//! `grep`) to a directory so later sessions over the same repository can
+//! reuse them.
+//!
+//! Entries are keyed by tool name, arguments, and a fingerprint of the
+//! content the result depends on: the file's content when the call's `path`
+//! argument names a file, otherwise a fingerprint of the workdir tree (file
+//! paths, sizes, and modification times). Every key also folds in the git
+//! state (`HEAD`, the branch it points to, and the index), which the tree
+//! walk skips, so `git log` or `git diff` results go stale with a commit,
+//! checkout, or `git add`. A changed file therefore never serves a stale
+//! result. Entries also expire after a TTL, and mutation tools
+//! explicitly invalidate entries for the path they touched.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+use tracing::debug;
+
+use super::cache::{path_argument, path_touches};
+use super::read_tracker::fnv1a;
+
+/// Default time-to-live for disk cache entries.
+pub const DEFAULT_DISK_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Directories skipped when fingerprinting the workdir tree.
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules"];
+
+/// One cached result, stored as `<key>.json`.
+#[derive(Debug, Serialize, Deserialize)]
+struct DiskEntry {
+    tool: String,
+    arguments: String,
+    /// The call's `path` argument, for invalidation.
+    path: Option<String>,
+    /// Unix seconds when the entry was written.
+    created_at: u64,
+    result: String,
+}
+
+/// Tool result cache persisted to a directory.
+#[derive(Debug)]
+pub struct DiskToolCache {
+    dir: PathBuf,
+    workdir: PathBuf,
+    ttl: Duration,
+    /// Memoized workdir tree fingerprint, cleared on mutation.
+    tree_fingerprint: Option<u64>,
+    hits: u64,
+    misses: u64,
+}
+
+impl DiskToolCache {
+    /// Cache results in `dir` for tools operating on `workdir`.
+    pub fn new(dir: impl Into<PathBuf>, workdir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            workdir: workdir.into(),
+            ttl: DEFAULT_DISK_CACHE_TTL,
+            tree_fingerprint: None,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Set the entry time-to-live (builder pattern).
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Look up a cached result. Expired entries are removed.
+    pub fn get(&mut self, tool_name: &str, arguments: &str) -> Option<String> {
+        let path = self.entry_path(tool_name, arguments);
+        let entry = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<DiskEntry>(&json).ok())
+            .filter(|e| e.tool == tool_name && e.arguments == arguments);
+        match entry {
+            Some(entry) if !self.expired(entry.created_at) => {
+                self.hits += 1;
+                Some(entry.result)
+            }
+            Some(_) => {
+                let _ = std::fs::remove_file(&path);
+                self.misses += 1;
+                None
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Store a result. Write failures are logged and otherwise ignored.
+    pub fn put(&mut self, tool_name: &str, arguments: &str, result: &str) {
+        let path = self.entry_path(tool_name, arguments);
+        let entry = DiskEntry {
+            tool: tool_name.to_string(),
+            arguments: arguments.to_string(),
+            path: path_argument(arguments),
+            created_at: now_secs(),
+            result: result.to_string(),
+        };
+        let write = std::fs::create_dir_all(&self.dir)
+            .map_err(|e| e.to_string())
+            .and_then(|()| serde_json::to_string(&entry).map_err(|e| e.to_string()))
+            .and_then(|json| std::fs::write(&path, json).map_err(|e| e.to_string()));
+        if let Err(e) = write {
+            debug!("Failed to write disk cache entry: {e}");
+        }
+    }
+
+    /// Invalidate after a mutation tool ran with `arguments`: forget the
+    /// workdir fingerprint and remove entries touching the mutated path (see
+    /// [`ToolResultCache::invalidate_path()`](super::cache::ToolResultCache::invalidate_path)).
+    /// Mutations without a path rely on the content fingerprints instead.
+    pub fn invalidate(&mut self, arguments: &str) {
+        self.tree_fingerprint = None;
+        let Some(mutated) = path_argument(arguments) else {
+            return;
+        };
+        self.retain(|entry| !path_touches(&self.workdir, entry.path.as_deref(), &mutated));
+    }
+
+    /// Remove every expired entry, returning how many were removed.
+    pub fn prune_expired(&self) -> usize {
+        self.retain(|entry| !self.expired(entry.created_at))
+    }
+
+    /// Cache hit count.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Cache miss count.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    fn expired(&self, created_at: u64) -> bool {
+        now_secs().saturating_sub(created_at) > self.ttl.as_secs()
+    }
+
+    /// Remove entries for which `keep` returns false (unreadable entries are
+    /// removed too). Returns the number removed.
+    fn retain(&self, keep: impl Fn(&DiskEntry) -> bool) -> usize {
+        let Ok(dir) = std::fs::read_dir(&self.dir) else {
+            return 0;
+        };
+        let mut removed = 0;
+        for file in dir.flatten() {
+            let path = file.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let entry = std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|json| serde_json::from_str::<DiskEntry>(&json).ok());
+            if entry.is_none_or(|e| !keep(&e)) && std::fs::remove_file(&path).is_ok() {
+                removed += 1;
+            }
+        }
+        removed
+    }
+
+    fn entry_path(&mut self, tool_name: &str, arguments: &str) -> PathBuf {
+        let fingerprint = self.content_fingerprint(arguments);
+        let git = git_fingerprint(&self.workdir);
+        let key = fnv1a(&format!(
+            "{tool_name}\0{arguments}\0{fingerprint:016x}\0{git:016x}"
+        ));
+        self.dir.join(format!("{key:016x}.json"))
+    }
+
+    /// Fingerprint of the content a call's result depends on.
+    fn content_fingerprint(&mut self, arguments: &str) -> u64 {
+        if let Some(path) = path_argument(arguments) {
+            let full = self.workdir.join(&path);
+            if full.is_file() {
+                return std::fs::read(&full)
+                    .map(|bytes| fnv1a(&String::from_utf8_lossy(&bytes)))
+                    .unwrap_or(0);
+            }
+        }
+        if let Some(fingerprint) = self.tree_fingerprint {
+            return fingerprint;
+        }
+        let mut files = Vec::new();
+        collect_file_stamps(&self.workdir, &self.workdir, &mut files);
+        files.sort();
+        let fingerprint = fnv1a(&files.join("\n"));
+        self.tree_fingerprint = Some(fingerprint);
+        fingerprint
+    }
+}
+
+/// Append `relative_path:len:mtime` for every file under `dir`, skipping
+/// hidden and build output directories.
+fn collect_file_stamps(root: &Path, dir: &Path, out: &mut Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        let path = entry.path();
+        if meta.is_dir() {
+            if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_ref()) {
+                collect_file_stamps(root, &path, out);
+            }
+        } else {
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            out.push(format!("{}:{}", relative.display(), file_stamp(&meta)));
+        }
+    }
+}
+
+/// `len:mtime` of a file.
+fn file_stamp(meta: &std::fs::Metadata) -> String {
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_nanos());
+    format!("{}:{mtime}", meta.len())
+}
+
+/// Fingerprint of the git state of the repository containing `workdir`:
+/// `HEAD`, the ref it points to, `packed-refs`, and the index. `0` outside
+/// a repository.
+fn git_fingerprint(workdir: &Path) -> u64 {
+    let Some(git_dir) = find_git_dir(workdir) else {
+        return 0;
+    };
+    let head = std::fs::read_to_string(git_dir.join("HEAD")).unwrap_or_default();
+    let mut parts = vec![head.clone()];
+    // A linked worktree keeps its refs in the main repository's git dir.
+    let common_dir = std::fs::read_to_string(git_dir.join("commondir"))
+        .map_or_else(|_| git_dir.clone(), |dir| git_dir.join(dir.trim()));
+    if let Some(reference) = head.trim().strip_prefix("ref: ") {
+        parts.push(std::fs::read_to_string(common_dir.join(reference)).unwrap_or_default());
+    }
+    for file in [common_dir.join("packed-refs"), git_dir.join("index")] {
+        let stamp = std::fs::metadata(file).map_or_else(|_| String::new(), |m| file_stamp(&m));
+        parts.push(stamp);
+    }
+    fnv1a(&parts.join("\n"))
+}
+
+/// The `.git` directory for `workdir` or its nearest ancestor, following a
+/// worktree's `gitdir:` file.
+fn find_git_dir(workdir: &Path) -> Option<PathBuf> {
+    workdir.ancestors().find_map(|dir| {
+        let dot_git = dir.join(".git");
+        if dot_git.is_dir() {
+            return Some(dot_git);
+        }
+        let pointer = std::fs::read_to_string(&dot_git).ok()?;
+        let target = pointer.trim().strip_prefix("gitdir: ")?;
+        Some(dir.join(target))
+    })
+}
+
+fn now_secs() -> u64 {
+    crate::platform::epoch_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> (tempfile::TempDir, DiskToolCache) {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.rs"), "fn a() {}").unwrap();
+        let cache = DiskToolCache::new(dir.path().join(".cache"), dir.path());
+        (dir, cache)
+    }
+
+    #[test]
+    fn persists_across_instances() {
+        let (dir, mut cache) = setup();
+        let args = r#"{"path":"a.rs"}"#;
+        assert!(cache.get("read_file", args).is_none());
+        cache.put("read_file", args, "fn a() {}");
+
+        let mut reopened = DiskToolCache::new(dir.path().join(".cache"), dir.path());
+        assert_eq!(
+            reopened.get("read_file", args).as_deref(),
+            Some("fn a() {}")
+        );
+        assert_eq!((reopened.hits(), reopened.misses()), (1, 0));
+    }
+
+    #[test]
+    fn file_content_change_misses() {
+        let (dir, mut cache) = setup();
+        let args = r#"{"path":"a.rs"}"#;
+        cache.put("read_file", args, "fn a() {}");
+        std::fs::write(dir.path().join("a.rs"), "fn b() {}").unwrap();
+        assert!(cache.get("read_file", args).is_none());
+    }
+
+    #[test]
+    fn tree_tools_see_new_files_after_invalidation() {
+        let (dir, mut cache) = setup();
+        let args = r#"{"pattern":"fn"}"#;
+        cache.put("grep", args, "a.rs:1");
+        assert!(cache.get("grep", args).is_some());
+
+        std::fs::write(dir.path().join("b.rs"), "fn b() {}").unwrap();
+        cache.invalidate(r#"{"path":"b.rs","content":"fn b() {}"}"#);
+        assert!(cache.get("grep", args).is_none());
+    }
+
+    #[test]
+    fn git_state_change_misses() {
+        let (dir, mut cache) = setup();
+        let git = dir.path().join(".git");
+        std::fs::create_dir_all(git.join("refs/heads")).unwrap();
+        std::fs::write(git.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+        std::fs::write(git.join("refs/heads/main"), "1111\n").unwrap();
+        let args = r#"{"path":"a.rs"}"#;
+        cache.put("git_log", args, "1111 first");
+        assert!(cache.get("git_log", args).is_some());
+
+        // A commit moves the branch without touching the worktree.
+        std::fs::write(git.join("refs/heads/main"), "2222\n").unwrap();
+        assert!(cache.get("git_log", args).is_none());
+    }
+
+    #[test]
+    fn invalidate_removes_entries_for_path() {
+        let (_dir, mut cache) = setup();
+        cache.put("read_file", r#"{"path":"a.rs"}"#, "fn a() {}");
+        cache.put("list_dir", r#"{"path":"."}"#, "a.rs");
+        cache.invalidate(r#"{"path":"a.rs","old_string":"a","new_string":"b"}"#);
+        assert!(cache.get("read_file", r#"{"path":"a.rs"}"#).is_none());
+        assert_eq!(cache.prune_expired(), 0);
+    }
+
+    #[test]
+    fn invalidate_resolves_path_aliases() {
+        let (dir, mut cache) = setup();
+        cache.put("read_file", r#"{"path":"a.rs"}"#, "fn a() {}");
+        let absolute = dir.path().join("a.rs");
+        let args = serde_json::json!({"path": absolute, "content": ""}).to_string();
+        cache.invalidate(&args);
+        assert!(cache.get("read_file", r#"{"path":"a.rs"}"#).is_none());
+
+        cache.put("read_file", r#"{"path":"a.rs"}"#, "fn a() {}");
+        cache.invalidate(r#"{"path":"sub/../a.rs","content":""}"#);
+        assert!(cache.get("read_file", r#"{"path":"a.rs"}"#).is_none());
+    }
+
+    #[test]
+    fn expired_entries_are_pruned() {
+        let (_dir, cache) = setup();
+        let mut cache = cache.with_ttl(Duration::from_secs(60));
+        let args = r#"{"path":"a.rs"}"#;
+        cache.put("read_file", args, "fn a() {}");
+
+        // Backdate the entry past its TTL.
+        let path = cache.entry_path("read_file", args);
+        let mut entry: DiskEntry =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        entry.created_at -= 120;
+        std::fs::write(&path, serde_json::to_string(&entry).unwrap()).unwrap();
+
+        assert_eq!(cache.prune_expired(), 1);
+        assert!(cache.get("read_file", args).is_none());
+    }
+}
@@ -0,0 +1,398 @@
+//! The `Error: ...` messages built-in tools return to the model.
+//!
+//! Every message is a template in a [`ToolErrorCatalog`], keyed by
+//! [`ToolErrorKind`], with recovery suggestions that
+//! [`reflection`](super::reflection) appends when the error is shown to the
+//! model. The default catalog holds the stock English messages; override
+//! templates or suggestions when a different phrasing works better for a
+//! model family, then pass the catalog to
+//! [`CommonToolsConfig::error_catalog`](super::core::CommonToolsConfig::error_catalog).
+//!
+//! ```ignore
+//! let catalog = ToolErrorCatalog::default()
+//!     .message(ToolErrorKind::OldStringNotFound, "Error: no exact match for old_string in {path}.")
+//!     .suggestions(ToolErrorKind::OldStringNotFound, ["Re-read the file and copy the text verbatim."]);
+//! ```
+//!
+//! Templates use `{name}` placeholders; each kind documents the ones it
+//! fills in. Suggestions are shown as-is.
+//!
+//! The harness, audit log, and UIs recognise a failed call by its `Error`
+//! prefix, so every message keeps one: an override that doesn't start with
+//! `Error` (e.g. a translated `Fehler: ...`) is stored as `Error: Fehler: ...`.
+
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock};
+
+/// A class of error a built-in tool can report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ToolErrorKind {
+    /// A required argument is missing or malformed. Placeholder: `{name}`.
+    MissingArgument,
+    /// Several required arguments are missing or malformed. Placeholder:
+    /// `{names}` (e.g. `'path' and 'content' arguments`).
+    MissingArguments,
+    /// The path resolves outside the working directory and allowed paths.
+    PathOutsideWorkspace,
+    /// A file tool was pointed at a directory. Placeholder: `{path}`.
+    IsDirectory,
+    /// Reading a file failed. Placeholders: `{path}`, `{error}`.
+    ReadFailed,
+    /// Writing a file failed. Placeholders: `{path}`, `{error}`.
+    WriteFailed,
+    /// Creating a file's parent directories failed. Placeholders: `{path}`,
+    /// `{error}`.
+    CreateDirFailed,
+    /// `edit_file` on a file that was not read first.
+    NotReadBeforeEdit,
+    /// `write_file` over a file that was not read first.
+    NotReadBeforeWrite,
+    /// `edit_file`'s `old_string` does not occur. Placeholder: `{path}`.
+    OldStringNotFound,
+    /// `edit_file`'s `old_string` occurs more than once. Placeholders:
+    /// `{count}`, `{path}`, `{lines}`.
+    OldStringAmbiguous,
+    /// `grep` was given an unknown mode. Placeholder: `{mode}`.
+    InvalidMode,
+    /// A search failed (bad regex or glob, unreadable tree). Placeholder:
+    /// `{error}`.
+    SearchFailed,
+    /// A shell command matched the blocklist.
+    CommandBlocked,
+    /// A shell command exceeded its timeout. Placeholder: `{seconds}`.
+    CommandTimedOut,
+    /// A web search request failed. Placeholder: `{error}`.
+    WebSearchFailed,
+}
+
+impl ToolErrorKind {
+    /// Every kind, in declaration order.
+    pub const ALL: [ToolErrorKind; 16] = [
+        Self::MissingArgument,
+        Self::MissingArguments,
+        Self::PathOutsideWorkspace,
+        Self::IsDirectory,
+        Self::ReadFailed,
+        Self::WriteFailed,
+        Self::CreateDirFailed,
+        Self::NotReadBeforeEdit,
+        Self::NotReadBeforeWrite,
+        Self::OldStringNotFound,
+        Self::OldStringAmbiguous,
+        Self::InvalidMode,
+        Self::SearchFailed,
+        Self::CommandBlocked,
+        Self::CommandTimedOut,
+        Self::WebSearchFailed,
+    ];
+
+    /// The stock message template.
+    fn default_message(self) -> &'static str {
+        match self {
+            Self::MissingArgument => "Error: '{name}' argument is required",
+            Self::MissingArguments => "Error: {names} are required",
+            Self::PathOutsideWorkspace => "Error: path traversal not allowed",
+            Self::IsDirectory => {
+                "Error: '{path}' is a directory, not a file. Use list_dir to browse directories."
+            }
+            Self::ReadFailed => "Error reading '{path}': {error}",
+            Self::WriteFailed => "Error writing '{path}': {error}",
+            Self::CreateDirFailed => "Error creating directories for '{path}': {error}",
+            Self::NotReadBeforeEdit => {
+                "Error: You must read this file before editing it. Use read_file first."
+            }
+            Self::NotReadBeforeWrite => {
+                "Error: You must read this file before overwriting it. Use read_file first."
+            }
+            Self::OldStringNotFound => {
+                "Error: old_string not found in {path}. \
+                 Verify the exact text (including whitespace and indentation)."
+            }
+            Self::OldStringAmbiguous => {
+                "Error: old_string found {count} times in {path} (lines: {lines}). \
+                 Provide more surrounding context to make it unique, or set replace_all=true."
+            }
+            Self::InvalidMode => {
+                "Error: invalid mode '{mode}'. Use 'files', 'content', or 'count'."
+            }
+            Self::SearchFailed => "Error: search failed: {error}",
+            Self::CommandBlocked => "Error: potentially destructive command blocked",
+            Self::CommandTimedOut => "Error: command timed out after {seconds} seconds",
+            Self::WebSearchFailed => "Error: web search failed: {error}",
+        }
+    }
+
+    /// The stock recovery suggestions.
+    fn default_suggestions(self) -> &'static [&'static str] {
+        match self {
+            Self::MissingArgument | Self::MissingArguments => {
+                &["Check that the arguments are valid JSON with correct field names and types."]
+            }
+            Self::PathOutsideWorkspace => {
+                &["The path must be within the working directory. Use a relative path."]
+            }
+            Self::IsDirectory => &["Use list_dir to see the files in it, then read one of them."],
+            Self::ReadFailed => &[
+                "Check that the file path is correct. Use list_dir or find_files to discover the right path.",
+            ],
+            Self::WriteFailed | Self::CreateDirFailed => {
+                &["The file or directory may have restricted permissions. Try a different path."]
+            }
+            Self::NotReadBeforeEdit | Self::NotReadBeforeWrite => {
+                &["Call read_file on the file, then retry with its current content in mind."]
+            }
+            Self::OldStringNotFound => &[
+                "Re-read the file and copy old_string exactly from the read_file output, without the L{n}: prefixes.",
+                "The file may have changed since you last read it.",
+            ],
+            Self::OldStringAmbiguous => &[
+                "Include neighbouring lines in old_string so it matches once, or set replace_all=true.",
+            ],
+            Self::InvalidMode => &["Use mode 'files', 'content', or 'count'."],
+            Self::SearchFailed => {
+                &["Check the pattern syntax: grep takes a regular expression, find_files a glob."]
+            }
+            Self::CommandBlocked => {
+                &["This command is blocked for safety. Try an alternative approach."]
+            }
+            Self::CommandTimedOut => {
+                &["The operation took too long. Try with smaller input or different arguments."]
+            }
+            Self::WebSearchFailed => &["Retry later or rephrase the query."],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CatalogEntry {
+    message: String,
+    suggestions: Vec<String>,
+}
+
+/// Message templates and recovery suggestions for built-in tool errors.
+///
+/// `Default` is the stock English catalog. Cheap to share behind an
+/// [`Arc`]; the common tools and [`ToolSet`](super::core::ToolSet) hold one.
+#[derive(Debug, Clone)]
+pub struct ToolErrorCatalog {
+    entries: HashMap<ToolErrorKind, CatalogEntry>,
+}
+
+static DEFAULT_CATALOG: LazyLock<Arc<ToolErrorCatalog>> =
+    LazyLock::new(|| Arc::new(ToolErrorCatalog::default()));
+
+impl Default for ToolErrorCatalog {
+    fn default() -> Self {
+        let entries = ToolErrorKind::ALL
+            .into_iter()
+            .map(|kind| {
+                let entry = CatalogEntry {
+                    message: kind.default_message().to_string(),
+                    suggestions: kind
+                        .default_suggestions()
+                        .iter()
+                        .map(|s| (*s).to_string())
+                        .collect(),
+                };
+                (kind, entry)
+            })
+            .collect();
+        Self { entries }
+    }
+}
+
+impl ToolErrorCatalog {
+    /// The shared stock catalog.
+    pub fn shared_default() -> Arc<Self> {
+        DEFAULT_CATALOG.clone()
+    }
+
+    /// Replace the message template for `kind`. Templates that don't start
+    /// with `Error` get an `Error: ` prefix.
+    pub fn message(mut self, kind: ToolErrorKind, template: impl Into<String>) -> Self {
+        let template = template.into();
+        self.entry_mut(kind).message = if template.starts_with("Error") {
+            template
+        } else {
+            format!("Error: {template}")
+        };
+        self
+    }
+
+    /// Replace the recovery suggestions for `kind`. An empty list falls
+    /// back to reflection's generic heuristics.
+    pub fn suggestions<S: Into<String>>(
+        mut self,
+        kind: ToolErrorKind,
+        suggestions: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.entry_mut(kind).suggestions = suggestions.into_iter().map(Into::into).collect();
+        self
+    }
+
+    fn entry_mut(&mut self, kind: ToolErrorKind) -> &mut CatalogEntry {
+        self.entries.entry(kind).or_insert_with(|| CatalogEntry {
+            message: kind.default_message().to_string(),
+            suggestions: Vec::new(),
+        })
+    }
+
+    fn template(&self, kind: ToolErrorKind) -> &str {
+        self.entries
+            .get(&kind)
+            .map_or(kind.default_message(), |e| e.message.as_str())
+    }
+
+    /// Render the message for `kind`, filling `{name}` placeholders from
+    /// `params`. Unknown placeholders are left as written.
+    pub fn render(&self, kind: ToolErrorKind, params: &[(&str, &str)]) -> String {
+        let mut message = self.template(kind).to_string();
+        for (name, value) in params {
+            message = message.replace(&format!("{{{name}}}"), value);
+        }
+        message
+    }
+
+    /// Render the error for a failed [`resolve_in_workdir`](super::paths::resolve_in_workdir).
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn path_error(&self, error: &str) -> String {
+        if error == super::paths::PATH_ESCAPE_ERROR {
+            self.render(ToolErrorKind::PathOutsideWorkspace, &[])
+        } else {
+            format!("Error: {error}")
+        }
+    }
+
+    /// Recovery suggestions for `kind`.
+    pub fn suggestions_for(&self, kind: ToolErrorKind) -> &[String] {
+        self.entries
+            .get(&kind)
+            .map_or(&[], |e| e.suggestions.as_slice())
+    }
+
+    /// The kind whose template `error` was rendered from, if any. When
+    /// several templates match, the one with the most literal text wins.
+    pub fn classify(&self, error: &str) -> Option<ToolErrorKind> {
+        ToolErrorKind::ALL
+            .into_iter()
+            .filter_map(|kind| {
+                template_match_len(self.template(kind), error).map(|len| (kind, len))
+            })
+            .max_by_key(|(_, len)| *len)
+            .map(|(kind, _)| kind)
+    }
+}
+
+/// If `text` could have been rendered from `template`, the number of
+/// literal (non-placeholder) bytes it matched.
+#[allow(clippy::string_slice)] // offsets from find()
+fn template_match_len(template: &str, text: &str) -> Option<usize> {
+    let literals = split_literals(template);
+    let mut rest = text;
+    let mut matched = 0;
+    for (i, literal) in literals.iter().enumerate() {
+        if i == 0 {
+            rest = rest.strip_prefix(literal)?;
+        } else {
+            let at = rest.find(literal)?;
+            rest = &rest[at + literal.len()..];
+        }
+        matched += literal.len();
+    }
+    (matched > 0).then_some(matched)
+}
+
+/// The literal segments of `template` between `{placeholder}`s. The first
+/// segment is always present (possibly empty) so it anchors the match.
+#[allow(clippy::string_slice)] // offsets of ASCII braces from find()
+fn split_literals(template: &str) -> Vec<&str> {
+    let mut literals = Vec::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let Some(close) = rest[open..].find('}') else {
+            break;
+        };
+        literals.push(&rest[..open]);
+        rest = &rest[open + close + 1..];
+    }
+    literals.push(rest);
+    let first = literals[0];
+    let mut out = vec![first];
+    out.extend(literals[1..].iter().copied().filter(|l| !l.is_empty()));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_catalog_renders_stock_messages() {
+        let catalog = ToolErrorCatalog::default();
+        assert_eq!(
+            catalog.render(ToolErrorKind::MissingArgument, &[("name", "path")]),
+            "Error: 'path' argument is required"
+        );
+        assert_eq!(
+            catalog.path_error(crate::tools::paths::PATH_ESCAPE_ERROR),
+            "Error: path traversal not allowed"
+        );
+        assert_eq!(catalog.path_error("boom"), "Error: boom");
+    }
+
+    #[test]
+    fn classify_picks_the_most_specific_template() {
+        let catalog = ToolErrorCatalog::default();
+        let error = catalog.render(
+            ToolErrorKind::OldStringAmbiguous,
+            &[("count", "2"), ("path", "a.rs"), ("lines", "3, 9")],
+        );
+        assert_eq!(
+            catalog.classify(&error),
+            Some(ToolErrorKind::OldStringAmbiguous)
+        );
+        assert_eq!(
+            catalog.classify("Error writing 'a.rs': Permission denied"),
+            Some(ToolErrorKind::WriteFailed)
+        );
+        assert_eq!(catalog.classify("Error: something else"), None);
+    }
+
+    #[test]
+    fn overrides_replace_message_and_suggestions() {
+        let catalog = ToolErrorCatalog::default()
+            .message(
+                ToolErrorKind::CommandBlocked,
+                "Error: command refused ({reason})",
+            )
+            .suggestions(ToolErrorKind::CommandBlocked, ["Ask the user."]);
+        let error = catalog.render(ToolErrorKind::CommandBlocked, &[("reason", "rm")]);
+        assert_eq!(error, "Error: command refused (rm)");
+        assert_eq!(
+            catalog.classify(&error),
+            Some(ToolErrorKind::CommandBlocked)
+        );
+        assert_eq!(
+            catalog.suggestions_for(ToolErrorKind::CommandBlocked),
+            ["Ask the user."]
+        );
+    }
+
+    #[test]
+    fn localized_overrides_keep_the_error_prefix() {
+        let catalog = ToolErrorCatalog::default().message(
+            ToolErrorKind::WriteFailed,
+            "Fehler beim Schreiben von '{path}': {error}",
+        );
+        let error = catalog.render(
+            ToolErrorKind::WriteFailed,
+            &[("path", "a.rs"), ("error", "Zugriff verweigert")],
+        );
+        assert_eq!(
+            error,
+            "Error: Fehler beim Schreiben von 'a.rs': Zugriff verweigert"
+        );
+        assert_eq!(catalog.classify(&error), Some(ToolErrorKind::WriteFailed));
+    }
+}
@@ -6,11 +6,18 @@
 //! - Category-based filtering (group tools by domain)
 //! - Task-based filtering (select tools relevant to the current task)
 //! - Usage-based filtering (promote frequently used tools)
+//! - Profile-based pruning (drop tools an [`AgentProfile`] shows are never
+//!   used or never succeed, and order the rest by past usage)
 
 use crate::ToolDef;
+use crate::agent::profile::AgentProfile;
 use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 
 /// A category of related tools.
+///
+/// Besides grouping tools for filtering, a category can cap how often its
+/// tools are called in a run (see [`CategoryBudgets`](super::budget::CategoryBudgets)).
 #[derive(Debug, Clone)]
 pub struct ToolCategory {
     /// Category name.
@@ -19,6 +26,12 @@ pub struct ToolCategory {
     pub tools: Vec<String>,
     /// Description of when this category is relevant.
     pub when_relevant: String,
+    /// Maximum calls per run to this category's tools. Default: `None`
+    /// (unlimited).
+    pub max_calls: Option<u32>,
+    /// Whether every [mutation](super::core::Tool::is_mutation) tool also
+    /// belongs to the category. Default: `false`.
+    pub includes_mutations: bool,
 }
 
 impl ToolCategory {
@@ -31,6 +44,52 @@ impl ToolCategory {
             name: name.into(),
             tools: tools.iter().map(|s| (*s).to_string()).collect(),
             when_relevant: when_relevant.into(),
+            max_calls: None,
+            includes_mutations: false,
+        }
+    }
+
+    /// A `mutations` category covering every mutation tool, limited to
+    /// `max_calls` per run.
+    pub fn mutations(max_calls: u32) -> Self {
+        Self {
+            includes_mutations: true,
+            ..Self::new("mutations", &[], "When changing files or external state")
+        }
+        .max_calls(max_calls)
+    }
+
+    /// Limit calls to this category's tools to `max` per run (builder
+    /// pattern).
+    pub fn max_calls(mut self, max: u32) -> Self {
+        self.max_calls = Some(max);
+        self
+    }
+
+    /// Whether the tool `name` belongs to this category.
+    pub fn contains(&self, name: &str, is_mutation: bool) -> bool {
+        (self.includes_mutations && is_mutation) || self.tools.iter().any(|t| t == name)
+    }
+}
+
+/// When [`AgentProfile`] statistics are trusted enough to drop a tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProfilePruning {
+    /// Drop tools with no calls once they have been offered in this many runs.
+    pub min_runs: u32,
+    /// Drop tools with at least this many calls and no successes.
+    pub min_failed_calls: u32,
+    /// Offer a dropped tool again once this many runs have passed since it
+    /// was last offered, so its statistics can change. `0` never re-offers.
+    pub explore_after: u32,
+}
+
+impl Default for ProfilePruning {
+    fn default() -> Self {
+        Self {
+            min_runs: 3,
+            min_failed_calls: 3,
+            explore_after: 10,
         }
     }
 }
@@ -46,6 +105,11 @@ pub struct ToolFilter {
     max_tools: usize,
     /// Usage counts for promoting frequently-used tools.
     usage_counts: HashMap<String, u32>,
+    /// Usage statistics from previous runs, updated as tools execute.
+    profile: Option<AgentProfile>,
+    /// Where the profile is persisted by [`save_profile()`](Self::save_profile).
+    profile_path: Option<PathBuf>,
+    pruning: ProfilePruning,
 }
 
 impl ToolFilter {
@@ -55,6 +119,9 @@ impl ToolFilter {
             always_include: HashSet::new(),
             max_tools,
             usage_counts: HashMap::new(),
+            profile: None,
+            profile_path: None,
+            pruning: ProfilePruning::default(),
         }
     }
 
@@ -88,11 +155,88 @@ impl ToolFilter {
         self
     }
 
+    /// Attach usage statistics from previous runs (builder pattern).
+    ///
+    /// Seeds the usage counts from the profile and enables profile-based
+    /// pruning in [`filter_for_task()`](Self::filter_for_task).
+    pub fn with_profile(mut self, profile: AgentProfile) -> Self {
+        for (name, usage) in &profile.tools {
+            *self.usage_counts.entry(name.clone()).or_insert(0) += usage.calls;
+        }
+        self.profile = Some(profile);
+        self
+    }
+
+    /// Load the profile at `path` (empty if missing) and persist it there on
+    /// [`save_profile()`](Self::save_profile) (builder pattern).
+    pub fn with_profile_file(self, path: impl Into<PathBuf>) -> Result<Self, String> {
+        let path = path.into();
+        let profile = AgentProfile::load(&path)?;
+        let mut filter = self.with_profile(profile);
+        filter.profile_path = Some(path);
+        Ok(filter)
+    }
+
+    /// Set the thresholds for profile-based pruning (builder pattern).
+    pub fn with_profile_pruning(mut self, pruning: ProfilePruning) -> Self {
+        self.pruning = pruning;
+        self
+    }
+
+    /// The attached profile, including calls recorded in this run.
+    pub fn profile(&self) -> Option<&AgentProfile> {
+        self.profile.as_ref()
+    }
+
     /// Record a tool usage (call this after each tool execution).
     pub fn record_usage(&mut self, tool_name: &str) {
         *self.usage_counts.entry(tool_name.to_string()).or_insert(0) += 1;
     }
 
+    /// Record in the attached profile that `tools` were offered this run.
+    pub fn record_offered(&mut self, tools: &[ToolDef]) {
+        if let Some(ref mut profile) = self.profile {
+            for tool in tools {
+                profile.record_offered(&tool.function.name);
+            }
+        }
+    }
+
+    /// Record a tool usage and its outcome in the attached profile.
+    pub fn record_outcome(&mut self, tool_name: &str, success: bool) {
+        self.record_usage(tool_name);
+        if let Some(ref mut profile) = self.profile {
+            profile.record_call(tool_name, success);
+        }
+    }
+
+    /// Count the current run in the profile and write it to the profile
+    /// file, if one was set with [`with_profile_file()`](Self::with_profile_file).
+    pub fn save_profile(&mut self) -> Result<(), String> {
+        let (Some(profile), Some(path)) = (&mut self.profile, &self.profile_path) else {
+            return Ok(());
+        };
+        profile.finish_run();
+        profile.save(path)
+    }
+
+    /// Whether the profile shows `tool_name` is never used when offered or
+    /// never succeeds, and it is not yet due to be offered again.
+    fn pruned_by_profile(&self, tool_name: &str) -> bool {
+        let Some(ref profile) = self.profile else {
+            return false;
+        };
+        if self.always_include.contains(tool_name) {
+            return false;
+        }
+        let usage = profile.usage(tool_name);
+        let never_used = usage.calls == 0 && usage.offered_runs >= self.pruning.min_runs;
+        let never_succeeds = usage.successes == 0 && usage.calls >= self.pruning.min_failed_calls;
+        let explore = self.pruning.explore_after > 0
+            && profile.runs.saturating_sub(usage.last_offered) >= self.pruning.explore_after;
+        (never_used || never_succeeds) && !explore
+    }
+
     /// Filter tools based on task keywords.
     ///
     /// Selects categories whose `when_relevant` description matches any of
@@ -130,9 +274,21 @@ impl ToolFilter {
         let mut filtered: Vec<ToolDef> = all_tools
             .iter()
             .filter(|t| selected_names.contains(&t.function.name))
+            .filter(|t| !self.pruned_by_profile(&t.function.name))
             .cloned()
             .collect();
 
+        // With a profile, put rarely used tools last so truncation drops them.
+        if self.profile.is_some() {
+            filtered.sort_by_key(|t| {
+                let name = &t.function.name;
+                (
+                    !self.always_include.contains(name),
+                    std::cmp::Reverse(self.usage_counts.get(name).copied().unwrap_or(0)),
+                )
+            });
+        }
+
         // Truncate to max_tools.
         filtered.truncate(self.max_tools);
         filtered
@@ -205,11 +361,15 @@ mod tests {
             name: "file_ops".into(),
             tools: vec!["read_file".into(), "list_dir".into()],
             when_relevant: "When reading or browsing files".into(),
+            max_calls: None,
+            includes_mutations: false,
         });
         filter.add_category(ToolCategory {
             name: "search".into(),
             tools: vec!["grep".into(), "find_files".into()],
             when_relevant: "When searching for content or files".into(),
+            max_calls: None,
+            includes_mutations: false,
         });
 
         let all_tools = vec![
@@ -359,6 +519,90 @@ mod tests {
         assert!(names.contains(&"shell"), "shell should always be included");
     }
 
+    #[test]
+    fn profile_prunes_unused_and_failing_tools() {
+        let mut profile = AgentProfile::new();
+        for _ in 0..3 {
+            profile.record_offered("never_used");
+            profile.record_call("grep", true);
+            profile.record_call("broken", false);
+            profile.finish_run();
+        }
+        profile.record_call("find_files", true);
+        let filter = ToolFilter::new(3)
+            .with_always_include("think")
+            .with_category(ToolCategory::new(
+                "search",
+                &["grep", "find_files", "broken", "never_used"],
+                "When searching",
+            ))
+            .with_profile(profile);
+
+        let all_tools = vec![
+            make_tool_def("never_used"),
+            make_tool_def("broken"),
+            make_tool_def("find_files"),
+            make_tool_def("grep"),
+            make_tool_def("think"),
+        ];
+        let filtered = filter.filter_for_task(&["search"], &all_tools);
+        let names: Vec<_> = filtered.iter().map(|t| t.function.name.as_str()).collect();
+        assert_eq!(names, vec!["think", "grep", "find_files"]);
+    }
+
+    #[test]
+    fn profile_prunes_only_offered_tools_and_explores_again() {
+        let mut profile = AgentProfile::new();
+        for _ in 0..3 {
+            profile.record_offered("ignored");
+            profile.finish_run();
+        }
+        let all_tools = vec![make_tool_def("ignored"), make_tool_def("new")];
+        let offered = |profile: &AgentProfile| {
+            let filter = ToolFilter::new(5)
+                .with_category(ToolCategory::new("all", &["ignored", "new"], "always"))
+                .with_profile_pruning(ProfilePruning {
+                    explore_after: 5,
+                    ..Default::default()
+                })
+                .with_profile(profile.clone());
+            filter
+                .filter_for_task(&["always"], &all_tools)
+                .into_iter()
+                .map(|t| t.function.name)
+                .collect::<Vec<_>>()
+        };
+        // A tool that was never offered has no evidence against it.
+        assert_eq!(offered(&profile), ["new"]);
+
+        // Runs it sat out accumulate until it is due another chance.
+        for _ in 0..3 {
+            profile.finish_run();
+        }
+        assert_eq!(offered(&profile), ["new"]);
+        profile.finish_run();
+        assert_eq!(offered(&profile), ["ignored", "new"]);
+
+        let mut filter = ToolFilter::new(5).with_profile(profile);
+        filter.record_offered(&all_tools);
+        assert_eq!(filter.profile().unwrap().usage("ignored").offered_runs, 4);
+    }
+
+    #[test]
+    fn profile_file_records_outcomes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("profile.json");
+        let mut filter = ToolFilter::new(10).with_profile_file(&path).unwrap();
+        filter.record_outcome("grep", true);
+        filter.record_outcome("grep", false);
+        filter.save_profile().unwrap();
+
+        let profile = AgentProfile::load(&path).unwrap();
+        assert_eq!(profile.runs, 1);
+        assert_eq!(profile.usage("grep").calls, 2);
+        assert_eq!(profile.usage("grep").successes, 1);
+    }
+
     #[test]
     fn with_common_categories_composable_with_domain_categories() {
         let filter = ToolFilter::new(15)
@@ -0,0 +1,365 @@
+//! Host OS differences for the native tools.
+//!
+//! Shell commands run through [`ShellKind`]: `sh -c` on Unix and Windows
+//! PowerShell on Windows, with `cmd /C` available as an override. Paths shown
+//! to the model go through [`display_path()`] so they use `/` on every
+//! platform. File search (`grep`, `find_files`) doesn't shell out at all; see
+//! [`search`](super::search).
+//!
+//! [`ShellEnv`] controls the environment those commands see: an allowlist of
+//! inherited host variables, a `.env` file, and explicit variables.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+/// The shell that runs `shell` tool commands and script hooks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellKind {
+    /// POSIX `sh -c`.
+    Sh,
+    /// Windows PowerShell (`powershell -NoProfile -NonInteractive -Command`).
+    PowerShell,
+    /// `cmd /C`.
+    Cmd,
+}
+
+impl ShellKind {
+    /// The default shell for the host: PowerShell on Windows, `sh` elsewhere.
+    pub fn host() -> Self {
+        if cfg!(windows) {
+            Self::PowerShell
+        } else {
+            Self::Sh
+        }
+    }
+
+    /// Program and arguments that run `script`.
+    pub fn argv(self, script: &str) -> (&'static str, Vec<&str>) {
+        match self {
+            Self::Sh => ("sh", vec!["-c", script]),
+            Self::PowerShell => (
+                "powershell",
+                vec!["-NoProfile", "-NonInteractive", "-Command", script],
+            ),
+            Self::Cmd => ("cmd", vec!["/C", script]),
+        }
+    }
+
+    /// A tokio command that runs `script`.
+    pub fn command(self, script: &str) -> tokio::process::Command {
+        let (program, args) = self.argv(script);
+        let mut cmd = tokio::process::Command::new(program);
+        cmd.args(args);
+        cmd
+    }
+
+    /// A blocking command that runs `script`.
+    pub fn std_command(self, script: &str) -> std::process::Command {
+        let (program, args) = self.argv(script);
+        let mut cmd = std::process::Command::new(program);
+        cmd.args(args);
+        cmd
+    }
+
+    /// Short name for prompts and logs.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Sh => "sh",
+            Self::PowerShell => "PowerShell",
+            Self::Cmd => "cmd",
+        }
+    }
+}
+
+impl Default for ShellKind {
+    fn default() -> Self {
+        Self::host()
+    }
+}
+
+impl FromStr for ShellKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "sh" | "bash" => Ok(Self::Sh),
+            "powershell" | "pwsh" => Ok(Self::PowerShell),
+            "cmd" => Ok(Self::Cmd),
+            other => Err(format!(
+                "unknown shell '{other}' (expected sh, powershell, or cmd)"
+            )),
+        }
+    }
+}
+
+/// Variables kept on Windows even with an allowlist; many programs fail
+/// without them.
+const WINDOWS_ESSENTIAL_VARS: &[&str] = &["SystemRoot", "ComSpec", "PATHEXT", "TEMP", "TMP"];
+
+/// Environment for shell commands.
+///
+/// By default commands inherit the whole host environment. With `inherit`
+/// set, only the listed host variables are passed (a trailing `*` matches a
+/// prefix, e.g. `CARGO_*`). Variables from `env_file` (resolved against the
+/// working directory and re-read on every command) and then `vars` are
+/// layered on top; their values may reference variables as `${NAME}`. Only
+/// variables already in the command's environment expand, so with an
+/// allowlist a reference can't pull in a host variable left out of it.
+///
+/// ```toml
+/// inherit = ["PATH", "HOME", "CARGO_*"]
+/// env_file = ".env.agent"
+/// vars = { CARGO_TARGET_DIR = "target/agent", PATH = "/opt/tools/bin:${PATH}" }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ShellEnv {
+    /// Host variables to pass through. `None` inherits everything.
+    #[serde(default)]
+    pub inherit: Option<Vec<String>>,
+    /// `.env`-style file of `KEY=value` lines.
+    #[serde(default)]
+    pub env_file: Option<PathBuf>,
+    /// Variables set on every command.
+    #[serde(default)]
+    pub vars: BTreeMap<String, String>,
+}
+
+impl ShellEnv {
+    /// Pass only these host variables through.
+    pub fn inherit_only<S: Into<String>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.inherit = Some(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Load variables from a `.env` file.
+    pub fn with_env_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.env_file = Some(path.into());
+        self
+    }
+
+    /// Set a variable on every command.
+    pub fn with_var(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.vars.insert(key.into(), value.into());
+        self
+    }
+
+    /// Whether the host environment is passed through unchanged.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The variables to set, or `None` when the host environment should be
+    /// inherited unchanged. Errors if the env file can't be read or parsed.
+    pub fn resolve(&self, workdir: &Path) -> Result<Option<Vec<(String, String)>>, String> {
+        if self.is_default() {
+            return Ok(None);
+        }
+        let mut env: BTreeMap<String, String> = match &self.inherit {
+            None => std::env::vars().collect(),
+            Some(patterns) => std::env::vars()
+                .filter(|(key, _)| {
+                    patterns.iter().any(|p| env_name_matches(p, key))
+                        || (cfg!(windows)
+                            && WINDOWS_ESSENTIAL_VARS
+                                .iter()
+                                .any(|v| v.eq_ignore_ascii_case(key)))
+                })
+                .collect(),
+        };
+        if let Some(ref file) = self.env_file {
+            let path = workdir.join(file);
+            let contents = std::fs::read_to_string(&path)
+                .map_err(|e| format!("cannot read env file {}: {e}", display_path(&path)))?;
+            let parsed =
+                parse_env_file(&contents).map_err(|e| format!("{}: {e}", display_path(&path)))?;
+            for (key, value) in parsed {
+                let value = expand_vars(&value, &env);
+                env.insert(key, value);
+            }
+        }
+        for (key, value) in &self.vars {
+            let value = expand_vars(value, &env);
+            env.insert(key.clone(), value);
+        }
+        Ok(Some(env.into_iter().collect()))
+    }
+}
+
+/// Whether `name` matches an allowlist entry (exact, or a `PREFIX*`).
+/// Case-insensitive on Windows, where variable names are.
+fn env_name_matches(pattern: &str, name: &str) -> bool {
+    let eq = |a: &str, b: &str| {
+        if cfg!(windows) {
+            a.eq_ignore_ascii_case(b)
+        } else {
+            a == b
+        }
+    };
+    match pattern.strip_suffix('*') {
+        Some(prefix) => name
+            .get(..prefix.len())
+            .is_some_and(|head| eq(head, prefix)),
+        None => eq(pattern, name),
+    }
+}
+
+/// Parse `.env` contents: `KEY=value` lines, optionally prefixed with
+/// `export`, with `#` comments and single- or double-quoted values.
+pub fn parse_env_file(contents: &str) -> Result<Vec<(String, String)>, String> {
+    let mut vars = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("line {}: expected KEY=value", i + 1));
+        };
+        let key = key.trim();
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("line {}: invalid variable name '{key}'", i + 1));
+        }
+        let value = value.trim();
+        let value = match value.chars().next() {
+            Some(q @ ('"' | '\'')) => value
+                .strip_prefix(q)
+                .and_then(|v| v.strip_suffix(q))
+                .ok_or_else(|| format!("line {}: unterminated quote", i + 1))?,
+            // Unquoted values end at an inline comment.
+            _ => value.split(" #").next().unwrap_or(value).trim_end(),
+        };
+        vars.push((key.to_string(), value.to_string()));
+    }
+    Ok(vars)
+}
+
+/// Replace `${NAME}` with its value in `env` (empty when unset).
+fn expand_vars(value: &str, env: &BTreeMap<String, String>) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let (before, after) = rest.split_at(start);
+        out.push_str(before);
+        match after.find('}') {
+            Some(end) => {
+                let name = after.get(2..end).unwrap_or_default();
+                out.push_str(env.get(name).map(String::as_str).unwrap_or_default());
+                rest = after.get(end + 1..).unwrap_or_default();
+            }
+            None => {
+                out.push_str(after);
+                rest = "";
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `path` with `/` separators, for tool output.
+pub fn display_path(path: &Path) -> String {
+    let text = path.to_string_lossy();
+    if std::path::MAIN_SEPARATOR == '/' {
+        text.into_owned()
+    } else {
+        text.replace(std::path::MAIN_SEPARATOR, "/")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shells_parse_and_build_argv() {
+        assert_eq!("pwsh".parse(), Ok(ShellKind::PowerShell));
+        assert_eq!("CMD".parse(), Ok(ShellKind::Cmd));
+        assert!("fish".parse::<ShellKind>().is_err());
+        assert_eq!(ShellKind::Cmd.argv("dir"), ("cmd", vec!["/C", "dir"]));
+        assert_eq!(ShellKind::Sh.argv("ls").1, ["-c", "ls"]);
+    }
+
+    #[test]
+    fn host_shell_matches_platform() {
+        let expected = if cfg!(windows) {
+            ShellKind::PowerShell
+        } else {
+            ShellKind::Sh
+        };
+        assert_eq!(ShellKind::host(), expected);
+    }
+
+    #[test]
+    fn env_files_parse_quotes_comments_and_exports() {
+        let vars = parse_env_file(
+            "# comment\nexport A=1\nB = \"two words\"\nC='x # y'\nD=plain # note\n\n",
+        )
+        .unwrap();
+        let expected = [
+            ("A", "1"),
+            ("B", "two words"),
+            ("C", "x # y"),
+            ("D", "plain"),
+        ];
+        assert_eq!(vars, expected.map(|(k, v)| (k.to_string(), v.to_string())));
+        assert!(parse_env_file("NOEQUALS").is_err());
+        assert!(parse_env_file("BAD-NAME=1").is_err());
+        assert!(parse_env_file("Q=\"open").is_err());
+    }
+
+    #[test]
+    fn shell_env_layers_allowlist_file_and_vars() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(".env"), "TOKEN=secret\nMODE=file\n").unwrap();
+        let env = ShellEnv::default()
+            .inherit_only(["PATH", "CARGO_*"])
+            .with_env_file(".env")
+            .with_var("MODE", "explicit")
+            .with_var("PATH", "/opt/bin:${PATH}")
+            .with_var("LEAK", "${HOME}")
+            .with_var("SEEN", "${TOKEN}");
+
+        let vars: BTreeMap<_, _> = env
+            .resolve(dir.path())
+            .unwrap()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(vars["TOKEN"], "secret");
+        assert_eq!(vars["MODE"], "explicit");
+        let host_path = std::env::var("PATH").unwrap_or_default();
+        assert_eq!(vars["PATH"], format!("/opt/bin:{host_path}"));
+        assert!(!vars.contains_key("HOME"));
+        // References only see the filtered environment.
+        assert_eq!(vars["LEAK"], "");
+        assert_eq!(vars["SEEN"], "secret");
+
+        assert_eq!(ShellEnv::default().resolve(dir.path()).unwrap(), None);
+        let missing = ShellEnv::default().with_env_file("nope.env");
+        assert!(
+            missing
+                .resolve(dir.path())
+                .unwrap_err()
+                .contains("nope.env")
+        );
+    }
+
+    #[test]
+    fn allowlist_patterns_match_prefixes() {
+        assert!(env_name_matches("CARGO_*", "CARGO_TARGET_DIR"));
+        assert!(env_name_matches("PATH", "PATH"));
+        assert!(!env_name_matches("PATH", "PATHEXT"));
+        assert!(!env_name_matches("CARGO_*", "CARG"));
+    }
+
+    #[test]
+    fn display_paths_use_forward_slashes() {
+        let path = Path::new("src").join("tools").join("mod.rs");
+        assert_eq!(display_path(&path), "src/tools/mod.rs");
+    }
+}
@@ -0,0 +1,459 @@
+//! Resource limits for processes spawned by tools.
+//!
+//! [`ProcessLimits`] caps each child: CPU time and address space through
+//! `setrlimit` (Unix only; ignored elsewhere) and captured output, which is
+//! enforced while reading so a command printing forever is killed instead of
+//! filling memory. [`SubprocessBudget`] tracks cumulative subprocess wall
+//! time across every command of a run and shortens timeouts as it runs out.
+//!
+//! A tripped limit is told to the model on the last line of the tool result
+//! as `[limit: <kind>] <detail>`, and to the harness out of band: tools call
+//! [`LimitTrip::report`], the harness runs each call under [`capture_trip`]
+//! and emits
+//! [`HarnessEvent::ResourceLimitExceeded`](crate::agent::events::HarnessEvent::ResourceLimitExceeded).
+//! The result text is never parsed for trips, since its last line may be the
+//! command's own output.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::future::Future;
+#[cfg(not(target_arch = "wasm32"))]
+use std::process::{Output, Stdio};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::io::{AsyncRead, AsyncReadExt};
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::process::Command;
+
+/// Prefix of the result line that reports a tripped limit.
+const LIMIT_MARKER: &str = "[limit: ";
+
+/// A resource limit that can stop a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitKind {
+    /// The process used more CPU time than [`ProcessLimits::cpu_time`].
+    CpuTime,
+    /// An allocation failed under [`ProcessLimits::memory_bytes`].
+    Memory,
+    /// Output exceeded [`ProcessLimits::max_output_bytes`].
+    Output,
+    /// The run's [`SubprocessBudget`] ran out.
+    WallBudget,
+}
+
+impl LimitKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::CpuTime => "cpu_time",
+            Self::Memory => "memory",
+            Self::Output => "output",
+            Self::WallBudget => "wall_budget",
+        }
+    }
+}
+
+impl fmt::Display for LimitKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+impl FromStr for LimitKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "cpu_time" => Ok(Self::CpuTime),
+            "memory" => Ok(Self::Memory),
+            "output" => Ok(Self::Output),
+            "wall_budget" => Ok(Self::WallBudget),
+            other => Err(format!("unknown limit '{other}'")),
+        }
+    }
+}
+
+/// A tripped limit, as reported in a tool result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimitTrip {
+    pub kind: LimitKind,
+    pub detail: String,
+}
+
+impl LimitTrip {
+    pub fn new(kind: LimitKind, detail: impl Into<String>) -> Self {
+        Self {
+            kind,
+            detail: detail.into(),
+        }
+    }
+
+    /// The `[limit: <kind>] <detail>` line appended to tool results.
+    pub fn notice(&self) -> String {
+        format!("{LIMIT_MARKER}{}] {}", self.kind, self.detail)
+    }
+
+    /// Report the trip to the harness (see [`capture_trip`]) and return the
+    /// notice for the tool result.
+    pub fn report(&self) -> String {
+        let _ = REPORTED_TRIP.try_with(|slot| *slot.borrow_mut() = Some(self.clone()));
+        self.notice()
+    }
+}
+
+tokio::task_local! {
+    /// The trip reported by the tool call running under [`capture_trip`].
+    static REPORTED_TRIP: RefCell<Option<LimitTrip>>;
+}
+
+/// Run a tool call, returning its result and the last trip it
+/// [reported](LimitTrip::report).
+pub async fn capture_trip<F: Future>(call: F) -> (F::Output, Option<LimitTrip>) {
+    REPORTED_TRIP
+        .scope(RefCell::new(None), async move {
+            let output = call.await;
+            let trip = REPORTED_TRIP.with(|slot| slot.borrow_mut().take());
+            (output, trip)
+        })
+        .await
+}
+
+/// Per-process limits for spawned commands. All unset by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProcessLimits {
+    /// CPU time per process (`RLIMIT_CPU`). Each process in a pipeline gets
+    /// its own allowance.
+    pub cpu_time: Option<Duration>,
+    /// Address space per process in bytes (`RLIMIT_AS`).
+    pub memory_bytes: Option<u64>,
+    /// Combined stdout and stderr bytes before the command is killed.
+    pub max_output_bytes: Option<usize>,
+}
+
+impl ProcessLimits {
+    pub fn cpu_time(mut self, limit: Duration) -> Self {
+        self.cpu_time = Some(limit);
+        self
+    }
+
+    pub fn memory_bytes(mut self, limit: u64) -> Self {
+        self.memory_bytes = Some(limit);
+        self
+    }
+
+    pub fn max_output_bytes(mut self, limit: usize) -> Self {
+        self.max_output_bytes = Some(limit);
+        self
+    }
+
+    /// Set the rlimits in the child before it execs.
+    #[cfg(unix)]
+    fn apply(&self, cmd: &mut Command) {
+        let cpu = self.cpu_time.map(|d| d.as_secs().max(1));
+        let memory = self.memory_bytes;
+        if cpu.is_none() && memory.is_none() {
+            return;
+        }
+        let set = |resource, soft: u64, hard: u64| {
+            let limit = libc::rlimit {
+                rlim_cur: soft as libc::rlim_t,
+                rlim_max: hard as libc::rlim_t,
+            };
+            // SAFETY: setrlimit is async-signal-safe and `limit` outlives
+            // the call.
+            if unsafe { libc::setrlimit(resource, &limit) } != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        };
+        // SAFETY: the closure only calls setrlimit, which is safe between
+        // fork and exec, and doesn't allocate.
+        unsafe {
+            cmd.pre_exec(move || {
+                if let Some(secs) = cpu {
+                    // A hard limit one second above the soft one lets
+                    // SIGXCPU arrive before the kernel's SIGKILL, so the
+                    // trip can be told apart from other kills.
+                    set(libc::RLIMIT_CPU, secs, secs + 1)?;
+                }
+                if let Some(bytes) = memory {
+                    set(libc::RLIMIT_AS, bytes, bytes)?;
+                }
+                Ok(())
+            });
+        }
+    }
+
+    #[cfg(all(not(unix), not(target_arch = "wasm32")))]
+    fn apply(&self, _cmd: &mut Command) {}
+
+    /// Which limit, if any, explains how `output` ended. Only the signal
+    /// that ended the process counts: its output is the command's own and
+    /// can claim anything.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn diagnose(&self, output: &Output) -> Option<LimitTrip> {
+        let signal = exit_signal(output)?;
+        if let Some(cpu) = self.cpu_time
+            && signal == SIGXCPU
+        {
+            return Some(LimitTrip::new(
+                LimitKind::CpuTime,
+                format!("killed after {}s of CPU time", cpu.as_secs().max(1)),
+            ));
+        }
+        // Allocation failures surface as these signals, but so do plain
+        // crashes; the detail says so.
+        let name = match signal {
+            SIGABRT => "SIGABRT",
+            SIGKILL => "SIGKILL",
+            SIGSEGV => "SIGSEGV",
+            _ => return None,
+        };
+        if let Some(bytes) = self.memory_bytes {
+            return Some(LimitTrip::new(
+                LimitKind::Memory,
+                format!(
+                    "killed by {name}, possibly by the {} MiB memory limit",
+                    bytes / (1024 * 1024)
+                ),
+            ));
+        }
+        None
+    }
+}
+
+/// Signals inspected by [`ProcessLimits::diagnose`]; the numbers match on
+/// Linux and macOS.
+#[cfg(not(target_arch = "wasm32"))]
+const SIGABRT: i32 = 6;
+#[cfg(not(target_arch = "wasm32"))]
+const SIGKILL: i32 = 9;
+#[cfg(not(target_arch = "wasm32"))]
+const SIGSEGV: i32 = 11;
+#[cfg(not(target_arch = "wasm32"))]
+const SIGXCPU: i32 = 24;
+
+/// The signal that ended the process, either directly or as reported by a
+/// shell through exit code `128 + signal`.
+#[cfg(not(target_arch = "wasm32"))]
+fn exit_signal(output: &Output) -> Option<i32> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = output.status.signal() {
+            return Some(signal);
+        }
+    }
+    output
+        .status
+        .code()
+        .filter(|c| (129..160).contains(c))
+        .map(|c| c - 128)
+}
+
+/// Run `cmd` to completion under `limits`, capturing its output.
+///
+/// The child is killed if its output exceeds the cap, or if the returned
+/// future is dropped (e.g. by a timeout).
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn output_with_limits(
+    cmd: &mut Command,
+    limits: &ProcessLimits,
+) -> std::io::Result<(Output, Option<LimitTrip>)> {
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    limits.apply(cmd);
+    let mut child = cmd.spawn()?;
+    let mut stdout = child.stdout.take();
+    let mut stderr = child.stderr.take();
+
+    let cap = limits.max_output_bytes.unwrap_or(usize::MAX);
+    let (mut out, mut err) = (Vec::new(), Vec::new());
+    let mut overflowed = false;
+    while stdout.is_some() || stderr.is_some() {
+        tokio::select! {
+            done = read_chunk(&mut stdout, &mut out), if stdout.is_some() => {
+                if done { stdout = None; }
+            }
+            done = read_chunk(&mut stderr, &mut err), if stderr.is_some() => {
+                if done { stderr = None; }
+            }
+        }
+        if out.len() + err.len() > cap {
+            overflowed = true;
+            let _ = child.start_kill();
+            break;
+        }
+    }
+    let status = child.wait().await?;
+
+    let output = Output {
+        status,
+        stdout: out,
+        stderr: err,
+    };
+    if overflowed {
+        let mut output = output;
+        output.stdout.truncate(cap);
+        output
+            .stderr
+            .truncate(cap.saturating_sub(output.stdout.len()));
+        let trip = LimitTrip::new(
+            LimitKind::Output,
+            format!("killed after producing more than {cap} bytes of output"),
+        );
+        return Ok((output, Some(trip)));
+    }
+    let trip = limits.diagnose(&output);
+    Ok((output, trip))
+}
+
+/// Read one chunk from `pipe` into `buf`. Returns `true` at end of stream.
+#[cfg(not(target_arch = "wasm32"))]
+async fn read_chunk<R: AsyncRead + Unpin>(pipe: &mut Option<R>, buf: &mut Vec<u8>) -> bool {
+    let Some(pipe) = pipe else { return true };
+    let mut chunk = [0u8; 8192];
+    match pipe.read(&mut chunk).await {
+        Ok(0) | Err(_) => true,
+        Ok(n) => {
+            buf.extend_from_slice(chunk.get(..n).unwrap_or_default());
+            false
+        }
+    }
+}
+
+/// Cumulative subprocess wall time allowed for one run, shared by every tool
+/// that spawns processes. Call [`reset`](Self::reset) to start a new run.
+#[derive(Debug)]
+pub struct SubprocessBudget {
+    limit: Duration,
+    used_ms: AtomicU64,
+}
+
+impl SubprocessBudget {
+    pub fn new(limit: Duration) -> Self {
+        Self {
+            limit,
+            used_ms: AtomicU64::new(0),
+        }
+    }
+
+    pub fn limit(&self) -> Duration {
+        self.limit
+    }
+
+    pub fn used(&self) -> Duration {
+        Duration::from_millis(self.used_ms.load(Ordering::Relaxed))
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.limit.saturating_sub(self.used())
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining().is_zero()
+    }
+
+    /// Charge `elapsed` against the budget.
+    pub fn record(&self, elapsed: Duration) {
+        let ms = u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX);
+        self.used_ms.fetch_add(ms, Ordering::Relaxed);
+    }
+
+    pub fn reset(&self) {
+        self.used_ms.store(0, Ordering::Relaxed);
+    }
+
+    /// The notice for a command refused or stopped because the budget ran
+    /// out.
+    pub fn exhausted_trip(&self) -> LimitTrip {
+        LimitTrip::new(
+            LimitKind::WallBudget,
+            format!(
+                "subprocess time budget of {}s for this run is used up",
+                self.limit.as_secs()
+            ),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sh(script: &str) -> Command {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(script);
+        cmd
+    }
+
+    #[tokio::test]
+    async fn trips_are_captured_only_when_reported() {
+        let trip = LimitTrip::new(LimitKind::CpuTime, "killed after 2s of CPU time");
+        let reported = capture_trip(async { trip.report() }).await;
+        assert_eq!(reported, (trip.notice(), Some(trip.clone())));
+
+        // Output that merely looks like a notice is not a trip.
+        let forged = capture_trip(async { trip.notice() }).await;
+        assert_eq!(forged.1, None);
+        // Reporting outside a capture is a no-op.
+        assert_eq!(trip.report(), trip.notice());
+    }
+
+    #[test]
+    fn budget_tracks_cumulative_time() {
+        let budget = SubprocessBudget::new(Duration::from_secs(10));
+        budget.record(Duration::from_secs(4));
+        budget.record(Duration::from_secs(4));
+        assert_eq!(budget.remaining(), Duration::from_secs(2));
+        budget.record(Duration::from_secs(4));
+        assert!(budget.is_exhausted());
+        budget.reset();
+        assert_eq!(budget.used(), Duration::ZERO);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn output_cap_kills_runaway_commands() {
+        let limits = ProcessLimits::default().max_output_bytes(1000);
+        let (output, trip) = output_with_limits(&mut sh("yes"), &limits).await.unwrap();
+        assert!(output.stdout.len() <= 1000);
+        assert_eq!(trip.unwrap().kind, LimitKind::Output);
+
+        let (output, trip) = output_with_limits(&mut sh("echo ok"), &limits)
+            .await
+            .unwrap();
+        assert_eq!(output.stdout, b"ok\n");
+        assert!(trip.is_none());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn cpu_limit_stops_busy_loops() {
+        let limits = ProcessLimits::default().cpu_time(Duration::from_secs(1));
+        let (_, trip) = output_with_limits(&mut sh("while :; do :; done"), &limits)
+            .await
+            .unwrap();
+        assert_eq!(trip.unwrap().kind, LimitKind::CpuTime);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn output_alone_does_not_report_a_trip() {
+        let limits = ProcessLimits::default().memory_bytes(1 << 30);
+        let script = "echo 'fatal: out of memory' >&2; exit 1";
+        let (_, trip) = output_with_limits(&mut sh(script), &limits).await.unwrap();
+        assert!(trip.is_none());
+
+        let (_, trip) = output_with_limits(&mut sh("kill -ABRT $$"), &limits)
+            .await
+            .unwrap();
+        let trip = trip.unwrap();
+        assert_eq!(trip.kind, LimitKind::Memory);
+        assert!(trip.detail.contains("possibly"), "{}", trip.detail);
+    }
+}
@@ -6,9 +6,13 @@
 //!
 //! # Defining tools
 //!
-//! There are three ways to define a tool, from simplest to most flexible:
+//! There are four ways to define a tool, from simplest to most flexible:
 //!
 //! - **[`FnTool`]** — closure-based, auto-parses arguments. Best for simple tools.
+//! - **`#[derive(CinchTool)]`** (feature `macros`) — generates the `Tool`
+//!   impl and [`ToolSpec`](spec::ToolSpec) from a struct's doc comment, a
+//!   typed argument struct, and an async method. Best for stateful tools
+//!   without hand-written definitions.
 //! - **`impl Tool`** — full struct with manual [`Tool::definition()`] and
 //!   [`Tool::execute()`]. Best for tools with complex state or ownership.
 //! - **[`DisabledTool`]** — wraps a tool definition but always returns an error.
@@ -17,40 +21,95 @@
 //! # Submodules
 //!
 //! - [`core`] — [`Tool`] trait, [`ToolSet`], [`FnTool`], [`DisabledTool`],
-//!   pseudo-tools ([`ThinkTool`], [`TodoTool`]).
+//!   pseudo-tools ([`ThinkTool`], [`TodoTool`], [`ScratchpadTool`]).
 //! - [`common`] — built-in tools: `ReadFile`, `EditFile`, `WriteFile`,
 //!   `ListDir`, `Grep`, `FindFiles`, `Shell`. Register all at once with
 //!   [`ToolSet::with_common_tools()`].
+//! - [`host`] — [`ShellKind`](host::ShellKind) (`sh`, PowerShell, `cmd`) and
+//!   `/`-separated display paths, so the native tools behave the same on
+//!   every OS.
+//! - [`search`] — pure-Rust directory walking behind `grep` and `find_files`.
+//! - [`paths`] — workspace containment checks shared by the file tools,
+//!   resolving symlinks and honoring extra allowed roots.
 //! - [`read_tracker`] — [`ReadTracker`] for read-before-write enforcement
 //!   shared between `ReadFile`, `EditFile`, and `WriteFile`.
 //! - [`spec`] — [`ToolSpec`](spec::ToolSpec) builder for structured tool
 //!   descriptions with `when_to_use` / `when_not_to_use` guidance.
+//! - `wasm` (feature `wasm-tools`) — `WasmTool`, sandboxed tool plugins
+//!   compiled to WebAssembly with a JSON-in/JSON-out ABI.
+//! - `script` (feature `scripting`) — `ScriptTool` and `ScriptHooks`, tools
+//!   and lifecycle hooks written as Rhai scripts with schemas generated from
+//!   their declared parameters.
 //! - [`filter`] — [`ToolFilter`] for dynamic tool selection by category,
-//!   keywords, and usage frequency.
+//!   keywords, usage frequency, and past usage from an agent profile.
+//! - [`selector`] — [`EmbeddingToolSelector`] picking the tools most similar
+//!   to the task by embedding, plus a mandatory core set.
 //! - [`cache`] — tool result caching with FNV-1a hashing and age-based eviction.
+//! - [`disk_cache`] — [`DiskToolCache`] persisting cacheable results across
+//!   runs, keyed by content fingerprints with TTLs.
 //! - [`dag`] — dependency-aware parallel execution with topological ordering.
-//! - [`reflection`] — structured error formatting for LLM self-correction.
+//! - [`diff`] — [`FileEdit`](diff::FileEdit) line diffs reconstructed from
+//!   successful `edit_file` / `write_file` calls.
+//! - [`output`] — [`ToolOutput`]: exit code, duration, stdout, and stderr of
+//!   process-backed tools, rendered into and parsed back from results.
+//! - [`errors`] — [`ToolErrorCatalog`](errors::ToolErrorCatalog): overridable
+//!   templates and recovery suggestions for the built-in tools' errors.
+//! - [`reflection`] — structured error formatting for LLM self-correction,
+//!   and escalation when a tool keeps failing the same way.
+//! - [`repair`] — healing for malformed (near-JSON) tool-call arguments.
+//! - [`summary`] — schema-aware one-line summaries of tool calls for
+//!   display (`src/main.rs (+3/-1 lines)`).
+//! - [`snapshot`] — [`FileSnapshot`](snapshot::FileSnapshot) for rolling back
+//!   file mutations, and [`SnapshotStore`](snapshot::SnapshotStore) for
+//!   reporting a run's net file changes.
 
 pub mod budget;
 pub mod cache;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod common;
 pub mod core;
 pub mod dag;
+pub mod diff;
+pub mod disk_cache;
+pub mod errors;
 pub mod filter;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod host;
+pub mod limits;
 pub mod names;
+pub mod output;
+pub mod paths;
 pub mod read_tracker;
 pub mod reflection;
+pub mod repair;
+#[cfg(all(feature = "scripting", not(target_arch = "wasm32")))]
+pub mod script;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod search;
+pub mod selector;
+pub mod snapshot;
 pub mod spec;
+pub mod summary;
+#[cfg(all(feature = "wasm-tools", not(target_arch = "wasm32")))]
+pub mod wasm;
 
 // Re-export commonly used items at the module level.
-pub use budget::ToolBudget;
+pub use budget::{CategoryBudgets, ToolBudget};
+#[cfg(not(target_arch = "wasm32"))]
+pub use core::CommonToolsConfig;
+#[cfg(feature = "ui")]
+pub use core::InteractionToolsConfig;
 pub use core::{
-    CommonToolsConfig, DisabledTool, FnTool, ThinkTool, TodoTool, Tool, ToolFuture, ToolSet,
+    DEFAULT_MAX_RESULT_BYTES, IntoToolResult, TruncationStrategy, parse_tool_args, truncate_result,
+    truncate_with_strategy, validate_tool_arguments,
 };
 pub use core::{
-    DEFAULT_MAX_RESULT_BYTES, TruncationStrategy, parse_tool_args, truncate_result,
-    truncate_with_strategy, validate_tool_arguments,
+    DisabledTool, FnTool, PinTool, RecallHistoryTool, ScratchpadTool, ThinkTool, TodoTool, Tool,
+    ToolCallCounts, ToolFuture, ToolSet,
 };
-pub use filter::{ToolCategory, ToolFilter};
+pub use disk_cache::DiskToolCache;
+pub use filter::{ProfilePruning, ToolCategory, ToolFilter};
+pub use output::ToolOutput;
 pub use read_tracker::ReadTracker;
 pub use reflection::format_tool_failure;
+pub use selector::{Embedder, EmbeddingToolSelector};
@@ -14,3 +14,30 @@ pub const SHELL: &str = "shell";
 pub const WEB_SEARCH: &str = "web_search";
 pub const THINK: &str = "think";
 pub const TODO: &str = "todo";
+pub const PIN: &str = "pin";
+pub const SCRATCHPAD: &str = "scratchpad";
+pub const ASK_USER: &str = "ask_user";
+pub const RECALL_HISTORY: &str = "recall_history";
+pub const BLACKBOARD_GET: &str = "blackboard_get";
+pub const BLACKBOARD_SET: &str = "blackboard_set";
+
+/// Every name above. User-defined tools (e.g. scripts) may not take these,
+/// even when the built-in is registered later in a run.
+pub const BUILTIN: &[&str] = &[
+    READ_FILE,
+    EDIT_FILE,
+    WRITE_FILE,
+    LIST_DIR,
+    FIND_FILES,
+    GREP,
+    SHELL,
+    WEB_SEARCH,
+    THINK,
+    TODO,
+    PIN,
+    SCRATCHPAD,
+    ASK_USER,
+    RECALL_HISTORY,
+    BLACKBOARD_GET,
+    BLACKBOARD_SET,
+];
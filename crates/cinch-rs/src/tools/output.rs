@@ -0,0 +1,175 @@
+//! Structured output of process-backed tools.
+//!
+//! `shell`, `grep`, the git tools, and command tools all report a run as a
+//! [`ToolOutput`]: exit code, wall time, and the two streams kept apart.
+//! Tool results are still strings, so the output is rendered as
+//!
+//! ```text
+//! [exit: 1] [0.42s]
+//! <stdout>
+//! [stderr]
+//! <stderr>
+//! ```
+//!
+//! and [`ToolOutput::parse`] recovers the fields, letting prompts, UIs, and
+//! [`reflection`](super::reflection) work with exit codes and streams instead
+//! of matching on the text. The `[stderr]` section is omitted when stderr is
+//! empty and the duration when it wasn't measured.
+
+use std::time::Duration;
+
+const EXIT_PREFIX: &str = "[exit: ";
+const STDERR_MARKER: &str = "\n[stderr]\n";
+
+/// One process run, as reported by a tool.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToolOutput {
+    /// Exit code, or `-1` when the process was killed by a signal.
+    pub exit_code: i32,
+    /// Wall time of the run, when measured.
+    pub duration: Option<Duration>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl ToolOutput {
+    pub fn new(exit_code: i32, stdout: impl Into<String>, stderr: impl Into<String>) -> Self {
+        Self {
+            exit_code,
+            duration: None,
+            stdout: stdout.into(),
+            stderr: stderr.into(),
+        }
+    }
+
+    /// Capture a finished process that ran for `duration`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_process(output: &std::process::Output, duration: Duration) -> Self {
+        Self {
+            exit_code: output.status.code().unwrap_or(-1),
+            duration: Some(duration),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        }
+    }
+
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    pub fn success(&self) -> bool {
+        self.exit_code == 0
+    }
+
+    /// The text form passed to the model (see the module docs).
+    pub fn render(&self) -> String {
+        let mut out = format!("{EXIT_PREFIX}{}]", self.exit_code);
+        if let Some(duration) = self.duration {
+            out.push_str(&format!(" [{:.2}s]", duration.as_secs_f64()));
+        }
+        out.push('\n');
+        out.push_str(&self.stdout);
+        if !self.stderr.is_empty() {
+            out.push_str(STDERR_MARKER);
+            out.push_str(&self.stderr);
+        }
+        out
+    }
+
+    /// Recover the fields from a rendered result. Returns `None` for results
+    /// that don't start with an `[exit: N]` header.
+    ///
+    /// Text appended after rendering (a truncation marker or a
+    /// [limit notice](super::limits::LimitTrip)) ends up in the last stream.
+    pub fn parse(result: &str) -> Option<Self> {
+        let (header, body) = result.split_once('\n').unwrap_or((result, ""));
+        let rest = header.strip_prefix(EXIT_PREFIX)?;
+        let (code, rest) = rest.split_once(']')?;
+        let exit_code = code.trim().parse().ok()?;
+        let duration = rest
+            .trim()
+            .strip_prefix('[')
+            .and_then(|d| d.strip_suffix("s]"))
+            .and_then(|secs| secs.parse::<f64>().ok())
+            .and_then(|secs| Duration::try_from_secs_f64(secs).ok());
+        // An empty stdout renders the marker right after the header.
+        let body = format!("\n{body}");
+        let (stdout, stderr) = match body.split_once(STDERR_MARKER) {
+            Some((stdout, stderr)) => (stdout, stderr),
+            None => (body.as_str(), ""),
+        };
+        Some(Self {
+            exit_code,
+            duration,
+            stdout: stdout.strip_prefix('\n').unwrap_or(stdout).to_string(),
+            stderr: stderr.to_string(),
+        })
+    }
+
+    /// `{exit_code, duration_ms, stdout, stderr}` for event streams.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "exit_code": self.exit_code,
+            "duration_ms": self.duration.map(|d| d.as_millis() as u64),
+            "stdout": self.stdout,
+            "stderr": self.stderr,
+        })
+    }
+}
+
+/// `result` with the duration dropped from its `[exit: N]` header, so runs
+/// that differ only in timing compare equal.
+pub fn without_duration(result: &str) -> std::borrow::Cow<'_, str> {
+    match ToolOutput::parse(result) {
+        Some(output) if output.duration.is_some() => ToolOutput {
+            duration: None,
+            ..output
+        }
+        .render()
+        .into(),
+        _ => result.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_and_parse_round_trip() {
+        let outputs = [
+            ToolOutput::new(0, "hello\n", ""),
+            ToolOutput::new(1, "", "boom\n").with_duration(Duration::from_millis(1250)),
+            ToolOutput::new(2, "partial\n", "warning: x\n"),
+            ToolOutput::new(0, "", ""),
+        ];
+        for output in outputs {
+            assert_eq!(ToolOutput::parse(&output.render()), Some(output));
+        }
+    }
+
+    #[test]
+    fn render_keeps_exit_header_first() {
+        let output = ToolOutput::new(0, "ok\n", "").with_duration(Duration::from_millis(40));
+        assert_eq!(output.render(), "[exit: 0] [0.04s]\nok\n");
+        assert_eq!(
+            ToolOutput::new(3, "", "err").render(),
+            "[exit: 3]\n\n[stderr]\nerr"
+        );
+    }
+
+    #[test]
+    fn parse_rejects_other_results() {
+        assert_eq!(ToolOutput::parse("Error: command blocked"), None);
+        assert_eq!(ToolOutput::parse("[exit: x]\n"), None);
+    }
+
+    #[test]
+    fn without_duration_ignores_timing() {
+        let a = ToolOutput::new(1, "out", "").with_duration(Duration::from_millis(10));
+        let b = a.clone().with_duration(Duration::from_secs(3));
+        assert_eq!(without_duration(&a.render()), without_duration(&b.render()));
+        assert_eq!(without_duration("plain"), "plain");
+    }
+}
@@ -0,0 +1,181 @@
+//! Workspace containment for tool paths.
+//!
+//! File tools accept paths from the model, so every path is checked before
+//! it is touched. [`resolve_in_workdir()`] joins the path onto the working
+//! directory, normalizes `.` and `..` lexically, resolves symlinks in the
+//! part that already exists, and accepts the result only if it lands inside
+//! the working directory or one of the extra allowed roots.
+//!
+//! This catches what a `..` substring check misses (absolute paths, and
+//! symlinks pointing outside the tree) while allowing harmless paths such
+//! as `src/../README.md`.
+
+use std::path::{Component, Path, PathBuf};
+
+/// Error returned for paths that resolve outside every allowed root.
+pub const PATH_ESCAPE_ERROR: &str = "path traversal not allowed";
+
+/// Resolve `path` against `workdir`, rejecting it unless it stays inside
+/// `workdir` or one of `allowed` after following symlinks.
+///
+/// Returns the lexically normalized path (relative paths stay joined onto
+/// `workdir`, so callers see the same form as before). Paths that don't
+/// exist yet are checked through their nearest existing ancestor; a
+/// dangling symlink anywhere on the way is rejected, since writing through
+/// it could create a file anywhere.
+pub fn resolve_in_workdir(
+    workdir: &str,
+    path: &str,
+    allowed: &[PathBuf],
+) -> Result<PathBuf, String> {
+    let workdir = Path::new(workdir);
+    let abs_workdir = normalize(&std::path::absolute(workdir).map_err(|e| e.to_string())?);
+    let target = normalize(&abs_workdir.join(path));
+    let resolved = resolve_existing(&target).ok_or(PATH_ESCAPE_ERROR)?;
+
+    let inside = std::iter::once(abs_workdir.as_path())
+        .chain(allowed.iter().map(PathBuf::as_path))
+        .filter_map(|root| root.canonicalize().ok())
+        .any(|root| resolved.starts_with(root));
+    if !inside {
+        return Err(PATH_ESCAPE_ERROR.to_string());
+    }
+    Ok(match target.strip_prefix(&abs_workdir) {
+        Ok(rel) if rel.as_os_str().is_empty() => workdir.to_path_buf(),
+        Ok(rel) => workdir.join(rel),
+        Err(_) => target,
+    })
+}
+
+/// `path` relative to `workdir` after the same lexical normalization as
+/// [`resolve_in_workdir()`], so aliases of one file (`a.rs`, `./a.rs`,
+/// `/wd/a.rs`, `src/../a.rs`) compare equal. Paths outside the workdir stay
+/// absolute; the workdir itself is the empty path. Nothing is checked
+/// against the filesystem.
+pub fn workdir_relative(workdir: &Path, path: &str) -> PathBuf {
+    let abs_workdir =
+        normalize(&std::path::absolute(workdir).unwrap_or_else(|_| workdir.to_path_buf()));
+    let target = normalize(&abs_workdir.join(path));
+    match target.strip_prefix(&abs_workdir) {
+        Ok(rel) => rel.to_path_buf(),
+        Err(_) => target,
+    }
+}
+
+/// Remove `.` components and apply `..` lexically. `..` at the root is
+/// dropped, as the filesystem does.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Canonicalize the longest existing prefix of `path` and append the rest.
+/// `None` if a missing component is a dangling symlink.
+fn resolve_existing(path: &Path) -> Option<PathBuf> {
+    let mut existing = path;
+    let mut tail = Vec::new();
+    loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            return Some(
+                tail.iter()
+                    .rev()
+                    .fold(canonical, |acc, part| acc.join(part)),
+            );
+        }
+        if existing.symlink_metadata().is_ok() {
+            return None;
+        }
+        tail.push(existing.file_name()?);
+        existing = existing.parent()?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workdir() -> (tempfile::TempDir, String) {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "").unwrap();
+        let path = dir.path().to_str().unwrap().to_string();
+        (dir, path)
+    }
+
+    #[test]
+    fn accepts_paths_inside_the_workdir() {
+        let (_dir, wd) = workdir();
+        let wd_path = Path::new(&wd);
+        assert_eq!(
+            resolve_in_workdir(&wd, "src/lib.rs", &[]).unwrap(),
+            wd_path.join("src/lib.rs")
+        );
+        assert_eq!(
+            resolve_in_workdir(&wd, "src/../src/./lib.rs", &[]).unwrap(),
+            wd_path.join("src/lib.rs")
+        );
+        assert_eq!(
+            resolve_in_workdir(&wd, "new/dir/file.rs", &[]).unwrap(),
+            wd_path.join("new/dir/file.rs")
+        );
+        assert_eq!(resolve_in_workdir(&wd, ".", &[]).unwrap(), wd_path);
+        let abs = format!("{wd}/src/lib.rs");
+        assert_eq!(
+            resolve_in_workdir(&wd, &abs, &[]).unwrap(),
+            wd_path.join("src/lib.rs")
+        );
+    }
+
+    #[test]
+    fn rejects_traversal_and_absolute_escapes() {
+        let (_dir, wd) = workdir();
+        for path in ["../outside", "src/../../outside", "/etc/passwd"] {
+            assert_eq!(
+                resolve_in_workdir(&wd, path, &[]).unwrap_err(),
+                PATH_ESCAPE_ERROR,
+                "{path}"
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_symlinks_that_leave_the_workdir() {
+        let (dir, wd) = workdir();
+        let outside = tempfile::tempdir().unwrap();
+        std::fs::write(outside.path().join("secret"), "x").unwrap();
+        std::os::unix::fs::symlink(outside.path(), dir.path().join("link")).unwrap();
+        std::os::unix::fs::symlink("/nonexistent/target", dir.path().join("dangling")).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("src"), dir.path().join("inner")).unwrap();
+
+        assert!(resolve_in_workdir(&wd, "link/secret", &[]).is_err());
+        assert!(resolve_in_workdir(&wd, "link/new_file", &[]).is_err());
+        assert!(resolve_in_workdir(&wd, "dangling", &[]).is_err());
+        assert!(resolve_in_workdir(&wd, "inner/lib.rs", &[]).is_ok());
+    }
+
+    #[test]
+    fn allowed_roots_extend_the_workspace() {
+        let (_dir, wd) = workdir();
+        let shared = tempfile::tempdir().unwrap();
+        std::fs::write(shared.path().join("notes.md"), "").unwrap();
+        let notes = shared.path().join("notes.md");
+        let notes = notes.to_str().unwrap();
+
+        assert!(resolve_in_workdir(&wd, notes, &[]).is_err());
+        let allowed = [shared.path().to_path_buf()];
+        assert_eq!(
+            resolve_in_workdir(&wd, notes, &allowed).unwrap(),
+            shared.path().join("notes.md")
+        );
+    }
+}
@@ -1,7 +1,32 @@
 //! Structured reflection on tool failures.
 //!
 //! When a tool returns an error, wrap it in a structured format that helps
-//! the LLM reason about what went wrong and how to recover.
+//! the LLM reason about what went wrong and how to recover. Failed process
+//! runs are analyzed by their [`ToolOutput`] exit code.
+//!
+//! A single reflected error is often not enough: models retry the same
+//! failing call with small variations. [`FailureEscalation`] counts each
+//! tool's streak of failures with similar errors and, once it reaches
+//! [`HarnessFailureEscalationConfig::threshold`](crate::agent::config::HarnessFailureEscalationConfig::threshold),
+//! escalates one [`EscalationLevel`] per further failure: a detailed
+//! remediation reminder, then an alternative tool from the failing tool's
+//! disambiguation data, then a request to stop and ask the user.
+
+use std::collections::HashMap;
+
+use super::core::ToolSet;
+use super::errors::ToolErrorCatalog;
+use super::output::ToolOutput;
+
+/// Exit codes meaning the command never ran: 126 (not executable) and 127
+/// (not found).
+const UNRUNNABLE_EXIT_CODES: [i32; 2] = [126, 127];
+
+/// Whether `result` is a process run whose command couldn't be started.
+/// Such results are reflected like errors, since the model can fix them.
+pub fn is_unrunnable(result: &str) -> bool {
+    ToolOutput::parse(result).is_some_and(|o| UNRUNNABLE_EXIT_CODES.contains(&o.exit_code))
+}
 
 /// Format a tool failure for structured reflection.
 ///
@@ -9,11 +34,31 @@
 /// - The original error
 /// - Possible causes
 /// - Suggested recovery actions
+///
+/// Suggestions come from the stock [`ToolErrorCatalog`]; use
+/// [`format_tool_failure_with`] for a custom one.
 pub fn format_tool_failure(tool_name: &str, arguments: &str, error: &str) -> String {
+    format_tool_failure_with(
+        &ToolErrorCatalog::shared_default(),
+        tool_name,
+        arguments,
+        error,
+    )
+}
+
+/// [`format_tool_failure`] taking recovery suggestions from `catalog` when
+/// `error` matches one of its messages, and from generic heuristics
+/// otherwise.
+pub fn format_tool_failure_with(
+    catalog: &ToolErrorCatalog,
+    tool_name: &str,
+    arguments: &str,
+    error: &str,
+) -> String {
     let mut msg = format!("Error from tool '{tool_name}':\n  {error}\n");
 
     // Analyze common error patterns and add suggestions.
-    let suggestions = analyze_error(tool_name, error);
+    let suggestions = recovery_suggestions(catalog, tool_name, error);
 
     if !suggestions.is_empty() {
         msg.push_str("\nPossible causes and recovery:\n");
@@ -32,8 +77,27 @@ pub fn format_tool_failure(tool_name: &str, arguments: &str, error: &str) -> Str
     msg
 }
 
+/// Suggestions from `catalog` when `error` is one of its messages, else
+/// from [`analyze_error`].
+fn recovery_suggestions(catalog: &ToolErrorCatalog, tool_name: &str, error: &str) -> Vec<String> {
+    match catalog
+        .classify(error)
+        .map(|kind| catalog.suggestions_for(kind))
+    {
+        Some(suggestions) if !suggestions.is_empty() => suggestions.to_vec(),
+        _ => analyze_error(tool_name, error),
+    }
+}
+
 /// Analyze an error and return recovery suggestions.
 fn analyze_error(tool_name: &str, error: &str) -> Vec<String> {
+    if let Some(output) = ToolOutput::parse(error) {
+        let suggestions = analyze_exit_code(output.exit_code);
+        if !suggestions.is_empty() {
+            return suggestions;
+        }
+    }
+
     let error_lower = error.to_lowercase();
     let mut suggestions = Vec::new();
 
@@ -96,6 +160,215 @@ fn analyze_error(tool_name: &str, error: &str) -> Vec<String> {
     suggestions
 }
 
+/// Recovery suggestions for well-known exit codes.
+fn analyze_exit_code(code: i32) -> Vec<String> {
+    let suggestion = match code {
+        124 => {
+            "The command was stopped by `timeout`. Run a smaller piece of the work or raise the limit."
+        }
+        126 => {
+            "The command exists but is not executable. Check its permissions or run it through an interpreter (e.g. `sh script.sh`)."
+        }
+        127 => {
+            "The command was not found. Check the spelling and whether the program is installed and on PATH."
+        }
+        137 => {
+            "The process was killed (SIGKILL), often for running out of memory. Try a smaller input."
+        }
+        139 => "The process crashed with a segmentation fault. Retrying unchanged will not help.",
+        _ => return Vec::new(),
+    };
+    vec![suggestion.into()]
+}
+
+// ── Escalation ──────────────────────────────────────────────────────
+
+/// How forcefully a repeated failure is addressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EscalationLevel {
+    /// Explain the error in detail with recovery steps.
+    Remediation,
+    /// Recommend a different tool from the disambiguation data.
+    Alternative,
+    /// Stop retrying and ask the user (or report the blocker).
+    AskUser,
+}
+
+impl EscalationLevel {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Remediation => "remediation",
+            Self::Alternative => "alternative tool",
+            Self::AskUser => "ask user",
+        }
+    }
+}
+
+/// A tool failed `failures` times in a row with similar errors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Escalation {
+    /// Tool name.
+    pub name: String,
+    /// Length of the failure streak.
+    pub failures: u32,
+    pub level: EscalationLevel,
+    /// System reminder text for the next round.
+    pub reminder: String,
+}
+
+/// Per-run tracking of repeated tool failures.
+///
+/// Unlike loop detection, which needs identical arguments and results,
+/// failures count as repeats when their errors are similar: the same
+/// [`ToolErrorCatalog`] kind, the same exit code, or the same first line
+/// once numbers and quoted values are masked. A success or a different
+/// error resets the tool's streak.
+pub struct FailureEscalation {
+    threshold: u32,
+    /// Tool name → (error signature, streak length).
+    streaks: HashMap<String, (String, u32)>,
+    /// Reminders produced since the last [`take_reminders`](Self::take_reminders).
+    pending: Vec<String>,
+}
+
+impl FailureEscalation {
+    /// Escalate from the `threshold`-th similar failure in a row (at least 1).
+    pub fn new(threshold: u32) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            streaks: HashMap::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Record a call's (possibly reflected) result. Returns the escalation
+    /// when the tool's failure streak is at or past the threshold.
+    pub fn record(&mut self, tools: &ToolSet, name: &str, result: &str) -> Option<Escalation> {
+        if !(result.starts_with("Error") || is_unrunnable(result)) {
+            self.streaks.remove(name);
+            return None;
+        }
+        let error = original_error(result);
+        let catalog = tools.error_catalog();
+        let signature = error_signature(catalog, error);
+        let streak = self
+            .streaks
+            .entry(name.to_string())
+            .or_insert_with(|| (signature.clone(), 0));
+        if streak.0 == signature {
+            streak.1 += 1;
+        } else {
+            *streak = (signature, 1);
+        }
+        let failures = streak.1;
+        if failures < self.threshold {
+            return None;
+        }
+
+        let alternatives = tools.alternatives(name);
+        let level = match failures - self.threshold {
+            0 => EscalationLevel::Remediation,
+            1 if !alternatives.is_empty() => EscalationLevel::Alternative,
+            _ => EscalationLevel::AskUser,
+        };
+        let first_line = error.lines().next().unwrap_or_default();
+        let reminder = match level {
+            EscalationLevel::Remediation => {
+                let steps: String = recovery_suggestions(catalog, name, error)
+                    .iter()
+                    .map(|s| format!("\n  - {s}"))
+                    .collect();
+                format!(
+                    "[System reminder: `{name}` has failed {failures} times in a row with the \
+                     same error: {first_line}\nRetrying the same way will fail again. Before the \
+                     next call:{steps}\n  - Re-check every argument against the tool's \
+                     parameters.]"
+                )
+            }
+            EscalationLevel::Alternative => {
+                let options: String = alternatives
+                    .iter()
+                    .map(|d| format!("\n  - `{}` ({}): {}", d.correct_tool, d.scenario, d.reason))
+                    .collect();
+                format!(
+                    "[System reminder: `{name}` keeps failing ({failures} times: {first_line}). \
+                     Stop retrying it and use a different tool instead:{options}]"
+                )
+            }
+            EscalationLevel::AskUser if tools.has_tool(super::names::ASK_USER) => format!(
+                "[System reminder: `{name}` has failed {failures} times in a row \
+                 ({first_line}). Stop retrying it. Call {} to explain what is failing and \
+                 ask the user how to proceed.]",
+                super::names::ASK_USER
+            ),
+            EscalationLevel::AskUser => format!(
+                "[System reminder: `{name}` has failed {failures} times in a row \
+                 ({first_line}). Stop retrying it. If no other approach works, finish and \
+                 explain the blocker in your answer so the user can help.]"
+            ),
+        };
+        self.pending.push(reminder.clone());
+        Some(Escalation {
+            name: name.to_string(),
+            failures,
+            level,
+            reminder,
+        })
+    }
+
+    /// Reminders produced since the last call.
+    pub fn take_reminders(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.pending)
+    }
+}
+
+/// The tool's own error inside a [`format_tool_failure`] wrapper, or
+/// `result` unchanged.
+fn original_error(result: &str) -> &str {
+    let Some(rest) = result.strip_prefix("Error from tool '") else {
+        return result;
+    };
+    let Some((_, body)) = rest.split_once(":\n  ") else {
+        return result;
+    };
+    let end = ["\n\nPossible causes and recovery:", "\n\nArguments used:"]
+        .iter()
+        .filter_map(|marker| body.find(marker))
+        .min()
+        .unwrap_or(body.len());
+    #[allow(clippy::string_slice)] // end from find()
+    &body[..end]
+}
+
+/// What makes two errors "similar".
+fn error_signature(catalog: &ToolErrorCatalog, error: &str) -> String {
+    if let Some(kind) = catalog.classify(error) {
+        return format!("{kind:?}");
+    }
+    if let Some(output) = ToolOutput::parse(error) {
+        return format!("exit {}", output.exit_code);
+    }
+    let first_line = error.lines().next().unwrap_or_default().to_lowercase();
+    let mut signature = String::with_capacity(first_line.len());
+    let mut quoted = false;
+    for c in first_line.chars() {
+        match c {
+            '\'' | '"' | '`' => {
+                quoted = !quoted;
+                signature.push('_');
+            }
+            _ if quoted => {}
+            c if c.is_ascii_digit() => {
+                if !signature.ends_with('#') {
+                    signature.push('#');
+                }
+            }
+            c => signature.push(c),
+        }
+    }
+    signature
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -120,4 +393,107 @@ mod tests {
         let result = format_tool_failure("grep", "bad json", "JSON parse error");
         assert!(result.contains("valid JSON"));
     }
+
+    #[test]
+    fn format_failure_keys_on_exit_code() {
+        let output = ToolOutput::new(127, "", "sh: cargo-nextest: not found\n").render();
+        assert!(is_unrunnable(&output));
+        let result = format_tool_failure("shell", r#"{"command": "cargo-nextest"}"#, &output);
+        assert!(result.contains("on PATH"));
+        assert!(!result.contains("file path"));
+
+        assert!(!is_unrunnable(&ToolOutput::new(1, "", "failed").render()));
+        assert!(!is_unrunnable("Error: not found"));
+    }
+
+    #[test]
+    fn catalog_errors_get_catalog_suggestions() {
+        use crate::tools::errors::ToolErrorKind;
+
+        let error = "Error: old_string not found in a.rs. \
+                     Verify the exact text (including whitespace and indentation).";
+        let result = format_tool_failure("edit_file", "{}", error);
+        assert!(result.contains("L{n}: prefixes"), "{result}");
+        assert!(!result.contains("list_dir"));
+
+        let catalog = ToolErrorCatalog::default()
+            .suggestions(ToolErrorKind::OldStringNotFound, ["Read a.rs again."]);
+        let result = format_tool_failure_with(&catalog, "edit_file", "{}", error);
+        assert!(result.contains("  - Read a.rs again.\n"), "{result}");
+    }
+
+    fn failing_tools() -> ToolSet {
+        use crate::tools::core::FnTool;
+
+        ToolSet::new()
+            .with(
+                FnTool::new(
+                    crate::ToolDef::new("fetch", "Fetch a URL", serde_json::json!({})),
+                    |_: serde_json::Value| async { "Error: fetch failed".to_string() },
+                )
+                .disambiguate("Reading local files", "read_local", "No network needed")
+                .disambiguate("Unregistered", "missing_tool", "Not offered"),
+            )
+            .with(FnTool::new(
+                crate::ToolDef::new("read_local", "Read", serde_json::json!({})),
+                |_: serde_json::Value| async { "ok".to_string() },
+            ))
+    }
+
+    #[test]
+    fn escalates_one_level_per_similar_failure() {
+        let tools = failing_tools();
+        let mut escalation = FailureEscalation::new(2);
+        let error = |n: u32| {
+            format_tool_failure(
+                "fetch",
+                "{}",
+                &format!("Error: request to 'host{n}' failed after {n}s"),
+            )
+        };
+
+        assert!(escalation.record(&tools, "fetch", &error(1)).is_none());
+        let first = escalation.record(&tools, "fetch", &error(2)).unwrap();
+        assert_eq!(first.level, EscalationLevel::Remediation);
+        assert!(
+            first.reminder.contains("failed 2 times"),
+            "{}",
+            first.reminder
+        );
+        assert!(first.reminder.contains("\n  - "));
+
+        let second = escalation.record(&tools, "fetch", &error(3)).unwrap();
+        assert_eq!(second.level, EscalationLevel::Alternative);
+        assert!(second.reminder.contains("`read_local`"));
+        assert!(!second.reminder.contains("missing_tool"));
+
+        let third = escalation.record(&tools, "fetch", &error(4)).unwrap();
+        assert_eq!(third.level, EscalationLevel::AskUser);
+        assert!(third.reminder.contains("explain the blocker"));
+        assert_eq!(escalation.take_reminders().len(), 3);
+        assert!(escalation.take_reminders().is_empty());
+    }
+
+    #[test]
+    fn success_or_a_different_error_resets_the_streak() {
+        let tools = failing_tools();
+        let mut escalation = FailureEscalation::new(2);
+        escalation.record(&tools, "fetch", "Error: timeout");
+        escalation.record(&tools, "fetch", "ok");
+        assert!(
+            escalation
+                .record(&tools, "fetch", "Error: timeout")
+                .is_none()
+        );
+        assert!(
+            escalation
+                .record(&tools, "fetch", "Error: permission denied")
+                .is_none()
+        );
+        assert!(
+            escalation
+                .record(&tools, "fetch", "Error: permission denied")
+                .is_some()
+        );
+    }
 }
@@ -0,0 +1,204 @@
+//! Healing for malformed tool-call arguments.
+//!
+//! Smaller models often emit arguments that are almost JSON: wrapped in a
+//! markdown fence, surrounded by prose, with trailing commas, or cut off
+//! before the closing braces. Before executing a round's tool calls the
+//! harness runs [`heal_arguments`] on any call whose arguments don't parse;
+//! if that fails it asks the model to resend just the arguments (see
+//! [`HarnessConfig::resend_malformed_arguments`](crate::agent::config::HarnessConfig::resend_malformed_arguments)).
+//! Only calls that survive both steps reach the tool as invalid JSON and
+//! produce an error result.
+//!
+//! Healing never invents content. An unterminated string is never closed,
+//! and [`heal_format`] (used for mutation tools and for calls cut off by
+//! `max_tokens`) doesn't close brackets either: a truncated `write_file`
+//! call must fail rather than write a truncated file.
+
+/// Whether `arguments` is a JSON object.
+pub fn is_valid_arguments(arguments: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(arguments).is_ok_and(|v| v.is_object())
+}
+
+/// Try to turn near-JSON `arguments` into a valid JSON object, closing
+/// brackets left open by output cut off mid-object.
+///
+/// Returns `None` when the arguments are already valid or can't be healed.
+pub fn heal_arguments(arguments: &str) -> Option<String> {
+    heal(arguments, true)
+}
+
+/// Like [`heal_arguments`], but only fixes formatting (fences, surrounding
+/// prose, trailing commas); truncated arguments are left unhealed.
+pub fn heal_format(arguments: &str) -> Option<String> {
+    heal(arguments, false)
+}
+
+fn heal(arguments: &str, close_truncated: bool) -> Option<String> {
+    if is_valid_arguments(arguments) {
+        return None;
+    }
+    let trimmed = arguments.trim();
+    if trimmed.is_empty() {
+        return Some("{}".to_string());
+    }
+
+    let unfenced = strip_fences(trimmed);
+    let object = extract_object(unfenced);
+    let mut candidates = vec![
+        unfenced.to_string(),
+        object.to_string(),
+        remove_trailing_commas(object),
+    ];
+    if close_truncated {
+        let truncated = from_first_brace(unfenced);
+        candidates.extend(close_brackets(&remove_trailing_commas(truncated)));
+        candidates.extend(close_brackets(&remove_trailing_commas(object)));
+    }
+    candidates.into_iter().find(|c| is_valid_arguments(c))
+}
+
+/// Strip a surrounding markdown code fence (```` ```json ... ``` ````).
+fn strip_fences(text: &str) -> &str {
+    let Some(rest) = text.strip_prefix("```") else {
+        return text;
+    };
+    // Drop the info string (e.g. `json`) on the opening line.
+    let body = rest.split_once('\n').map_or("", |(_, body)| body);
+    body.trim_end().strip_suffix("```").unwrap_or(body).trim()
+}
+
+/// The text from the first `{` to the last `}` (or to the end if there is
+/// no closing brace).
+#[allow(clippy::string_slice)] // indices from find()/rfind() on ASCII braces
+fn extract_object(text: &str) -> &str {
+    let Some(start) = text.find('{') else {
+        return text;
+    };
+    match text.rfind('}') {
+        Some(end) if end > start => &text[start..=end],
+        _ => &text[start..],
+    }
+}
+
+/// The text from the first `{` to the end, for output cut off mid-object.
+#[allow(clippy::string_slice)] // index from find() on an ASCII brace
+fn from_first_brace(text: &str) -> &str {
+    text.find('{').map_or(text, |start| &text[start..])
+}
+
+/// Remove commas that directly precede `}` or `]`, outside strings.
+fn remove_trailing_commas(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    let chars: Vec<char> = text.chars().collect();
+    for (i, &c) in chars.iter().enumerate() {
+        if in_string {
+            out.push(c);
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        if c == '"' {
+            in_string = true;
+        } else if c == ','
+            && chars[i + 1..]
+                .iter()
+                .find(|c| !c.is_whitespace())
+                .is_some_and(|next| *next == '}' || *next == ']')
+        {
+            continue;
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Close any unclosed `{` / `[`. `None` inside an unterminated string,
+/// whose real end is unknown.
+fn close_brackets(text: &str) -> Option<String> {
+    let mut stack = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in text.chars() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => stack.push('}'),
+            '[' => stack.push(']'),
+            '}' | ']' => {
+                stack.pop();
+            }
+            _ => {}
+        }
+    }
+    if in_string {
+        return None;
+    }
+    let mut out = text.trim_end().trim_end_matches(',').to_string();
+    out.extend(stack.into_iter().rev());
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_arguments_need_no_healing() {
+        assert!(heal_arguments(r#"{"path":"a.rs"}"#).is_none());
+    }
+
+    #[test]
+    fn heals_common_near_json() {
+        assert_eq!(heal_arguments("").as_deref(), Some("{}"));
+        assert_eq!(
+            heal_arguments("```json\n{\"path\": \"a.rs\"}\n```").as_deref(),
+            Some("{\"path\": \"a.rs\"}")
+        );
+        assert_eq!(
+            heal_arguments(r#"Here you go: {"path": "a.rs"} hope it helps"#).as_deref(),
+            Some(r#"{"path": "a.rs"}"#)
+        );
+        assert_eq!(
+            heal_arguments(r#"{"paths": ["a", "b",], "n": 1,}"#).as_deref(),
+            Some(r#"{"paths": ["a", "b"], "n": 1}"#)
+        );
+        assert_eq!(
+            heal_arguments(r#"{"pattern": "a,}", "opts": {"x": [1"#).as_deref(),
+            Some(r#"{"pattern": "a,}", "opts": {"x": [1]}}"#)
+        );
+    }
+
+    #[test]
+    fn truncated_content_is_never_completed() {
+        // The rest of the string is unknown: no closing quote is invented.
+        assert!(heal_arguments(r#"{"content": "unterminated"#).is_none());
+        // Formatting-only healing leaves truncated objects alone.
+        let cut = r#"{"path": "a.rs", "opts": {"x": [1"#;
+        assert!(heal_arguments(cut).is_some());
+        assert!(heal_format(cut).is_none());
+        assert_eq!(
+            heal_format(r#"{"path": "a.rs",}"#).as_deref(),
+            Some(r#"{"path": "a.rs"}"#)
+        );
+    }
+
+    #[test]
+    fn gives_up_on_non_json() {
+        assert!(heal_arguments("read the file please").is_none());
+        assert!(heal_arguments("[1, 2]").is_none());
+    }
+}
@@ -0,0 +1,594 @@
+//! Tools and lifecycle hooks written as [Rhai](https://rhai.rs) scripts.
+//!
+//! Drop `.rhai` files into a directory (conventionally `.cinch/tools/`) and
+//! load them with [`load_scripts()`]. A script that defines `fn run(...)`
+//! becomes a tool named after the file stem, which must not be the name of
+//! an existing tool. The function's parameters
+//! become the JSON Schema the model sees; `@param` lines in its doc comment
+//! give each a type (`string` by default, `?` suffix for optional) and a
+//! description:
+//!
+//! ```text
+//! /// Count the words in a piece of text.
+//! /// @param text string The text to count
+//! /// @param min_len integer? Ignore words shorter than this
+//! fn run(text, min_len) {
+//!     let min = if min_len == () { 0 } else { min_len };
+//!     let count = 0;
+//!     for word in text.split(" ") {
+//!         if word.len() >= min { count += 1; }
+//!     }
+//!     `${count} words`
+//! }
+//! ```
+//!
+//! String results are returned as-is; other values are serialized to JSON.
+//!
+//! Any script may also define lifecycle hooks, collected into [`ScriptHooks`]
+//! (a [`LifecycleHook`]):
+//!
+//! | Function | Effect |
+//! |----------|--------|
+//! | `pre_tool_use(tool, args)` | Return a string to block the call with that reason |
+//! | `post_tool_use(tool, result)` | Return a string to inject into the conversation |
+//! | `on_stop()` | Return a string to keep the agent going with that message |
+//! | `session_start(trace_id)` | Called when a run starts |
+//!
+//! Scripts are sandboxed by Rhai itself (no filesystem, process, or network
+//! access) and bounded by an operation limit, so a runaway loop fails the
+//! call instead of hanging the agent. `print` and `debug` go to the log.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use rhai::{AST, Dynamic, Engine, Scope};
+use tracing::{debug, info, warn};
+
+use super::core::{Tool, ToolFuture, ToolSet};
+use super::names;
+use crate::ToolDef;
+use crate::agent::hooks::{HookAction, LifecycleHook, StopAction};
+
+/// File extension of script files.
+pub const SCRIPT_EXTENSION: &str = "rhai";
+
+/// Default cap on operations per script call.
+pub const DEFAULT_MAX_OPERATIONS: u64 = 1_000_000;
+
+/// A compiled script file.
+pub struct Script {
+    name: String,
+    engine: Engine,
+    ast: AST,
+}
+
+impl Script {
+    /// Compile a script file. The file stem becomes the tool name.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .ok_or_else(|| format!("Invalid script path: {}", path.display()))?;
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        Self::from_source(name, &source).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    /// Compile a script from source.
+    pub fn from_source(name: impl Into<String>, source: &str) -> Result<Self, String> {
+        let name = name.into();
+        let mut engine = Engine::new();
+        engine.set_max_operations(DEFAULT_MAX_OPERATIONS);
+        engine.set_max_call_levels(32);
+        engine.set_max_string_size(1024 * 1024);
+        engine.set_max_array_size(100_000);
+        engine.set_max_map_size(100_000);
+        let log_name = name.clone();
+        engine.on_print(move |s| info!("[{log_name}] {s}"));
+        let log_name = name.clone();
+        engine.on_debug(move |s, _, _| debug!("[{log_name}] {s}"));
+        let ast = engine
+            .compile(source)
+            .map_err(|e| format!("Script compile error: {e}"))?;
+        Ok(Self { name, engine, ast })
+    }
+
+    /// Set the operation limit per call (builder pattern).
+    pub fn with_max_operations(mut self, operations: u64) -> Self {
+        self.engine.set_max_operations(operations);
+        self
+    }
+
+    /// The script's name (its file stem).
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Parameter names of script function `name`, if defined.
+    fn function_params(&self, name: &str) -> Option<Vec<String>> {
+        self.ast
+            .iter_functions()
+            .find(|f| f.name == name)
+            .map(|f| f.params.iter().map(|p| p.to_string()).collect())
+    }
+
+    /// Call script function `name`, returning its value.
+    fn call(&self, name: &str, args: Vec<Dynamic>) -> Result<Dynamic, String> {
+        let mut scope = Scope::new();
+        self.engine
+            .call_fn::<Dynamic>(&mut scope, &self.ast, name, args)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Call hook `name` if the script defines it with a matching arity,
+    /// returning a non-empty string result.
+    fn call_hook(&self, name: &str, args: Vec<Dynamic>) -> Option<String> {
+        let params = self.function_params(name)?;
+        if params.len() != args.len() {
+            warn!(
+                "{}: hook {name} takes {} parameter(s), expected {}",
+                self.name,
+                params.len(),
+                args.len()
+            );
+            return None;
+        }
+        match self.call(name, args) {
+            Ok(value) if value.is_string() => {
+                let text = value.into_string().unwrap_or_default();
+                (!text.is_empty()).then_some(text)
+            }
+            Ok(_) => None,
+            Err(e) => {
+                warn!("{}: hook {name} failed: {e}", self.name);
+                None
+            }
+        }
+    }
+
+    /// Whether the script defines any lifecycle hook function.
+    fn has_hooks(&self) -> bool {
+        self.ast.iter_functions().any(|f| {
+            matches!(
+                f.name,
+                "pre_tool_use" | "post_tool_use" | "on_stop" | "session_start"
+            )
+        })
+    }
+}
+
+/// One declared tool parameter.
+#[derive(Debug, Clone, PartialEq)]
+struct ScriptParam {
+    name: String,
+    json_type: &'static str,
+    required: bool,
+    description: Option<String>,
+}
+
+/// Parse the `run` doc comment into a description and `@param` lines.
+fn parse_doc(comments: &[&str]) -> (String, Vec<(String, String, Option<String>)>) {
+    let mut description = Vec::new();
+    let mut params = Vec::new();
+    for comment in comments {
+        for line in comment.lines() {
+            let line = line
+                .trim()
+                .trim_start_matches("///")
+                .trim_start_matches("/**")
+                .trim_end_matches("*/")
+                .trim_start_matches('*')
+                .trim();
+            if let Some(rest) = line.strip_prefix("@param") {
+                let mut parts = rest.split_whitespace();
+                let Some(name) = parts.next() else { continue };
+                let ty = parts.next().unwrap_or("string").to_string();
+                let desc: Vec<&str> = parts.collect();
+                let desc = (!desc.is_empty()).then(|| desc.join(" "));
+                params.push((name.to_string(), ty, desc));
+            } else if !line.is_empty() {
+                description.push(line);
+            }
+        }
+    }
+    (description.join(" "), params)
+}
+
+/// Map a declared type to a JSON Schema type.
+fn json_type(declared: &str) -> Result<&'static str, String> {
+    Ok(match declared {
+        "string" | "str" => "string",
+        "integer" | "int" => "integer",
+        "number" | "float" => "number",
+        "boolean" | "bool" => "boolean",
+        "array" => "array",
+        "object" | "map" => "object",
+        other => return Err(format!("unknown parameter type '{other}'")),
+    })
+}
+
+/// A tool implemented by a script's `run` function.
+#[derive(Clone)]
+pub struct ScriptTool {
+    script: Arc<Script>,
+    definition: ToolDef,
+    params: Vec<ScriptParam>,
+}
+
+impl ScriptTool {
+    /// Build a tool from `script`, or `None` if it defines no `run`.
+    pub fn from_script(script: Arc<Script>) -> Result<Option<Self>, String> {
+        let Some(run) = script.ast.iter_functions().find(|f| f.name == "run") else {
+            return Ok(None);
+        };
+        let (description, declared) = parse_doc(&run.comments);
+        let mut params = Vec::new();
+        for name in &run.params {
+            let decl = declared.iter().find(|(n, ..)| n == name);
+            let (ty, desc) = decl.map_or(("string", None), |(_, ty, desc)| {
+                (ty.as_str(), desc.clone())
+            });
+            let optional = ty.ends_with('?');
+            let json_type = json_type(ty.trim_end_matches('?'))
+                .map_err(|e| format!("{}: parameter '{name}': {e}", script.name))?;
+            params.push(ScriptParam {
+                name: name.to_string(),
+                json_type,
+                required: !optional,
+                description: desc,
+            });
+        }
+        if let Some((name, ..)) = declared
+            .iter()
+            .find(|(n, ..)| !run.params.iter().any(|p| p == n))
+        {
+            return Err(format!(
+                "{}: @param '{name}' is not a parameter of run()",
+                script.name
+            ));
+        }
+
+        let description = if description.is_empty() {
+            format!("Run the {} script", script.name)
+        } else {
+            description
+        };
+        let definition = ToolDef::new(&script.name, description, schema(&params));
+        Ok(Some(Self {
+            script,
+            definition,
+            params,
+        }))
+    }
+
+    /// The tool name (the script's file stem).
+    pub fn name(&self) -> &str {
+        self.script.name()
+    }
+
+    /// Convert JSON arguments into positional script arguments.
+    fn positional_args(&self, arguments: &str) -> Result<Vec<Dynamic>, String> {
+        let args: serde_json::Value = if arguments.trim().is_empty() {
+            serde_json::json!({})
+        } else {
+            serde_json::from_str(arguments).map_err(|e| format!("invalid arguments: {e}"))?
+        };
+        self.params
+            .iter()
+            .map(|param| match args.get(&param.name) {
+                Some(value) if !value.is_null() => {
+                    rhai::serde::to_dynamic(value).map_err(|e| e.to_string())
+                }
+                _ if param.required => Err(format!("missing required argument '{}'", param.name)),
+                _ => Ok(Dynamic::UNIT),
+            })
+            .collect()
+    }
+}
+
+/// JSON Schema for the declared parameters.
+fn schema(params: &[ScriptParam]) -> serde_json::Value {
+    let mut properties = serde_json::Map::new();
+    for param in params {
+        let mut prop = serde_json::json!({ "type": param.json_type });
+        if let Some(ref desc) = param.description {
+            prop["description"] = desc.clone().into();
+        }
+        properties.insert(param.name.clone(), prop);
+    }
+    let required: Vec<&str> = params
+        .iter()
+        .filter(|p| p.required)
+        .map(|p| p.name.as_str())
+        .collect();
+    serde_json::json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+impl Tool for ScriptTool {
+    fn definition(&self) -> ToolDef {
+        self.definition.clone()
+    }
+
+    fn execute(&self, arguments: &str) -> ToolFuture<'_> {
+        let tool = self.clone();
+        let arguments = arguments.to_string();
+        Box::pin(async move {
+            let name = tool.script.name.clone();
+            let run = move || -> Result<String, String> {
+                let args = tool.positional_args(&arguments)?;
+                let value = tool.script.call("run", args)?;
+                if value.is_unit() {
+                    Ok(String::new())
+                } else if value.is_string() {
+                    Ok(value.into_string().unwrap_or_default())
+                } else {
+                    let json: serde_json::Value =
+                        rhai::serde::from_dynamic(&value).map_err(|e| e.to_string())?;
+                    Ok(json.to_string())
+                }
+            };
+            // Scripts run synchronously; keep them off the async workers.
+            match tokio::task::spawn_blocking(run).await {
+                Ok(Ok(result)) => result,
+                Ok(Err(e)) => format!("Error: {name}: {e}"),
+                Err(e) => format!("Error: {name}: script task failed: {e}"),
+            }
+        })
+    }
+}
+
+/// Lifecycle hooks from every loaded script, run in load order.
+#[derive(Clone, Default)]
+pub struct ScriptHooks {
+    scripts: Vec<Arc<Script>>,
+}
+
+impl ScriptHooks {
+    /// Number of scripts that define hooks.
+    pub fn len(&self) -> usize {
+        self.scripts.len()
+    }
+
+    /// Whether no script defines hooks.
+    pub fn is_empty(&self) -> bool {
+        self.scripts.is_empty()
+    }
+}
+
+impl LifecycleHook for ScriptHooks {
+    fn pre_tool_use(&self, tool: &str, args: &str) -> HookAction {
+        let args: Dynamic = serde_json::from_str::<serde_json::Value>(args)
+            .ok()
+            .and_then(|v| rhai::serde::to_dynamic(v).ok())
+            .unwrap_or_else(|| args.into());
+        for script in &self.scripts {
+            if let Some(reason) = script.call_hook("pre_tool_use", vec![tool.into(), args.clone()])
+            {
+                return HookAction::Block(reason);
+            }
+        }
+        HookAction::Proceed
+    }
+
+    fn post_tool_use(&self, tool: &str, result: &str) -> Option<String> {
+        let messages: Vec<String> = self
+            .scripts
+            .iter()
+            .filter_map(|s| s.call_hook("post_tool_use", vec![tool.into(), result.into()]))
+            .collect();
+        (!messages.is_empty()).then(|| messages.join("\n\n"))
+    }
+
+    fn on_stop(&self) -> StopAction {
+        self.scripts
+            .iter()
+            .find_map(|s| s.call_hook("on_stop", Vec::new()))
+            .map_or(StopAction::Allow, StopAction::Continue)
+    }
+
+    fn session_start(&self, trace_id: &str) {
+        for script in &self.scripts {
+            script.call_hook("session_start", vec![trace_id.into()]);
+        }
+    }
+}
+
+/// Tools and hooks loaded from a script directory.
+#[derive(Clone, Default)]
+pub struct LoadedScripts {
+    /// One tool per script that defines `run`.
+    pub tools: Vec<ScriptTool>,
+    /// Hooks from every script that defines any.
+    pub hooks: ScriptHooks,
+}
+
+impl LoadedScripts {
+    /// Register the script tools on `tools`. A script named like a built-in
+    /// tool or a tool already in the set is an error, so a checked-in
+    /// script can't silently replace e.g. `shell` or `write_file`.
+    pub fn register_tools(&self, tools: &ToolSet) -> Result<(), String> {
+        if let Some(tool) = self
+            .tools
+            .iter()
+            .find(|t| names::BUILTIN.contains(&t.name()) || tools.has_tool(t.name()))
+        {
+            return Err(format!(
+                "script tool '{}' has the name of an existing tool; rename the script",
+                tool.name()
+            ));
+        }
+        for tool in &self.tools {
+            tools.register(tool.clone());
+        }
+        Ok(())
+    }
+}
+
+/// Load every `.rhai` file in `dir`, in file name order. A missing
+/// directory yields nothing; a script that fails to compile is an error.
+pub fn load_scripts(dir: impl AsRef<Path>) -> Result<LoadedScripts, String> {
+    let dir = dir.as_ref();
+    let mut loaded = LoadedScripts::default();
+    if !dir.is_dir() {
+        return Ok(loaded);
+    }
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read {}: {e}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == SCRIPT_EXTENSION))
+        .collect();
+    paths.sort();
+    for path in paths {
+        let script = Arc::new(Script::load(&path)?);
+        if let Some(tool) = ScriptTool::from_script(script.clone())? {
+            loaded.tools.push(tool);
+        }
+        if script.has_hooks() {
+            loaded.hooks.scripts.push(script);
+        }
+    }
+    Ok(loaded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORD_COUNT: &str = r#"
+        /// Count the words in a piece of text.
+        /// @param text string The text to count
+        /// @param min_len integer? Ignore words shorter than this
+        fn run(text, min_len) {
+            let min = if min_len == () { 0 } else { min_len };
+            let count = 0;
+            for word in text.split(" ") {
+                if word.len() >= min { count += 1; }
+            }
+            `${count} words`
+        }
+    "#;
+
+    fn tool(name: &str, source: &str) -> ScriptTool {
+        let script = Arc::new(Script::from_source(name, source).unwrap());
+        ScriptTool::from_script(script).unwrap().unwrap()
+    }
+
+    #[test]
+    fn schema_comes_from_declared_parameters() {
+        let def = tool("word_count", WORD_COUNT).definition();
+        assert_eq!(def.function.name, "word_count");
+        assert_eq!(
+            def.function.description,
+            "Count the words in a piece of text."
+        );
+        let params = &def.function.parameters;
+        assert_eq!(params["properties"]["text"]["type"], "string");
+        assert_eq!(params["properties"]["min_len"]["type"], "integer");
+        assert_eq!(params["required"], serde_json::json!(["text"]));
+    }
+
+    #[tokio::test]
+    async fn runs_with_json_arguments() {
+        let tool = tool("word_count", WORD_COUNT);
+        assert_eq!(
+            tool.execute(r#"{"text":"a bb ccc","min_len":2}"#).await,
+            "2 words"
+        );
+        assert_eq!(tool.execute(r#"{"text":"a bb ccc"}"#).await, "3 words");
+        let missing = tool.execute("{}").await;
+        assert!(
+            missing.contains("missing required argument 'text'"),
+            "{missing}"
+        );
+
+        let json = self::tool("stats", "fn run(n) { #{ doubled: n.len() * 2 } }");
+        assert_eq!(json.execute(r#"{"n":"abc"}"#).await, r#"{"doubled":6}"#);
+    }
+
+    #[tokio::test]
+    async fn runaway_scripts_hit_the_operation_limit() {
+        let script = Script::from_source("spin", "fn run() { loop {} }")
+            .unwrap()
+            .with_max_operations(10_000);
+        let tool = ScriptTool::from_script(Arc::new(script)).unwrap().unwrap();
+        let result = tool.execute("{}").await;
+        assert!(result.starts_with("Error: spin:"), "{result}");
+    }
+
+    #[test]
+    fn rejects_unknown_types_and_stray_params() {
+        let bad_type = Script::from_source("t", "/// @param x widget\nfn run(x) { x }").unwrap();
+        let err = ScriptTool::from_script(Arc::new(bad_type)).err().unwrap();
+        assert!(err.contains("unknown parameter type 'widget'"), "{err}");
+
+        let stray = Script::from_source("t", "/// @param y string\nfn run(x) { x }").unwrap();
+        let err = ScriptTool::from_script(Arc::new(stray)).err().unwrap();
+        assert!(err.contains("@param 'y'"), "{err}");
+    }
+
+    #[test]
+    fn loads_tools_and_hooks_from_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("word_count.rhai"), WORD_COUNT).unwrap();
+        std::fs::write(
+            dir.path().join("guard.rhai"),
+            r#"
+            fn pre_tool_use(tool, args) {
+                if tool == "shell" && args.command.contains("rm ") { "no deletions" }
+            }
+            fn on_stop() { "run the tests first" }
+            "#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let loaded = load_scripts(dir.path()).unwrap();
+        assert_eq!(loaded.tools.len(), 1);
+        assert_eq!(loaded.tools[0].name(), "word_count");
+        assert_eq!(loaded.hooks.len(), 1);
+        assert_eq!(
+            loaded
+                .hooks
+                .pre_tool_use("shell", r#"{"command":"rm -rf x"}"#),
+            HookAction::Block("no deletions".into())
+        );
+        assert_eq!(
+            loaded.hooks.pre_tool_use("shell", r#"{"command":"ls"}"#),
+            HookAction::Proceed
+        );
+        assert_eq!(
+            loaded.hooks.on_stop(),
+            StopAction::Continue("run the tests first".into())
+        );
+
+        assert!(
+            load_scripts(dir.path().join("missing"))
+                .unwrap()
+                .tools
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn scripts_cannot_replace_existing_tools() {
+        let loaded = |name: &str| LoadedScripts {
+            tools: vec![tool(name, "fn run() { \"ok\" }")],
+            hooks: ScriptHooks::default(),
+        };
+        let tools = ToolSet::new();
+        tools.register(tool("deploy", "fn run() { \"ok\" }"));
+
+        let err = loaded("shell").register_tools(&tools).unwrap_err();
+        assert!(err.contains("'shell'"), "{err}");
+        assert!(loaded("deploy").register_tools(&tools).is_err());
+        assert!(!tools.has_tool("shell"));
+
+        loaded("word_count").register_tools(&tools).unwrap();
+        assert!(tools.has_tool("word_count"));
+    }
+}
@@ -0,0 +1,293 @@
+//! Pure-Rust file search behind the `grep` and `find_files` tools.
+//!
+//! Walking the tree in-process instead of spawning `grep`, `find`, and
+//! `ls` keeps the tools working on hosts without GNU userland (Windows) and
+//! makes output identical everywhere: paths always use `/`, and the output
+//! format matches what the tools produced when they shelled out.
+
+use std::path::Path;
+
+use regex::{Regex, RegexBuilder};
+use walkdir::WalkDir;
+
+use super::host::display_path;
+
+/// Bytes inspected for a NUL byte when deciding whether a file is binary.
+const BINARY_SNIFF_BYTES: usize = 8192;
+
+/// What `grep` reports for each file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrepMode {
+    /// Paths of matching files.
+    Files,
+    /// `path:line:text` for matches, `path-line-text` for context.
+    Content,
+    /// `path:count` for files with at least one match.
+    Count,
+}
+
+/// Options for [`grep()`].
+#[derive(Debug, Clone)]
+pub struct GrepOptions<'a> {
+    /// Regex (Rust `regex` syntax).
+    pub pattern: &'a str,
+    /// Only search files whose name matches this glob.
+    pub glob: Option<&'a str>,
+    pub case_insensitive: bool,
+    pub mode: GrepMode,
+    /// Lines of context around matches in [`GrepMode::Content`].
+    pub context_lines: usize,
+    /// Stop reading a file after this many matching lines.
+    pub max_matches_per_file: usize,
+}
+
+/// Search files under `root` (a directory or a single file). Binary files
+/// are skipped. Returns output lines in path order.
+pub fn grep(root: &Path, options: &GrepOptions<'_>) -> Result<Vec<String>, String> {
+    let regex = RegexBuilder::new(options.pattern)
+        .case_insensitive(options.case_insensitive)
+        .build()
+        .map_err(|e| format!("invalid regex: {e}"))?;
+    let include = options.glob.map(glob_regex).transpose()?;
+    if !root.exists() {
+        return Err(format!("{}: No such file or directory", display_path(root)));
+    }
+
+    let mut out = Vec::new();
+    for entry in WalkDir::new(root).sort_by_file_name() {
+        let Ok(entry) = entry else { continue };
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy();
+        if include.as_ref().is_some_and(|glob| !glob.is_match(&name)) {
+            continue;
+        }
+        let Ok(bytes) = std::fs::read(entry.path()) else {
+            continue;
+        };
+        if bytes.iter().take(BINARY_SNIFF_BYTES).any(|&b| b == 0) {
+            continue;
+        }
+        let text = String::from_utf8_lossy(&bytes);
+        let lines: Vec<&str> = text.lines().collect();
+        let matches: Vec<usize> = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| regex.is_match(line))
+            .map(|(i, _)| i)
+            .take(options.max_matches_per_file)
+            .collect();
+        if matches.is_empty() {
+            continue;
+        }
+
+        let path = display_path(entry.path());
+        match options.mode {
+            GrepMode::Files => out.push(path),
+            GrepMode::Count => out.push(format!("{path}:{}", matches.len())),
+            GrepMode::Content => {
+                push_content(&mut out, &path, &lines, &matches, options.context_lines)
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Append matches with context, separating non-adjacent groups with `--`.
+fn push_content(out: &mut Vec<String>, path: &str, lines: &[&str], matches: &[usize], ctx: usize) {
+    let mut groups: Vec<(usize, usize)> = Vec::new();
+    for &m in matches {
+        let (start, end) = (m.saturating_sub(ctx), (m + ctx).min(lines.len() - 1));
+        match groups.last_mut() {
+            Some(last) if start <= last.1 + 1 => last.1 = end,
+            _ => groups.push((start, end)),
+        }
+    }
+    for (start, end) in groups {
+        if ctx > 0 && !out.is_empty() {
+            out.push("--".into());
+        }
+        for (i, line) in lines.iter().enumerate().take(end + 1).skip(start) {
+            let sep = if matches.binary_search(&i).is_ok() {
+                ':'
+            } else {
+                '-'
+            };
+            out.push(format!("{path}{sep}{}{sep}{line}", i + 1));
+        }
+    }
+}
+
+/// Files under `workdir.join(search_path)` whose displayed path
+/// (`{search_path}/{relative}`) matches `{search_path}/{pattern}`, newest
+/// first. As with `find -path`, `*` also matches `/`.
+pub fn find_files(
+    workdir: &Path,
+    search_path: &str,
+    pattern: &str,
+    limit: usize,
+) -> Result<Vec<String>, String> {
+    let prefix = search_path.trim_end_matches(['/', '\\']);
+    let prefix = if prefix.is_empty() { "." } else { prefix };
+    let matcher = glob_regex(&format!("{prefix}/{pattern}"))?;
+    let root = workdir.join(search_path);
+
+    let mut found = Vec::new();
+    for entry in WalkDir::new(&root) {
+        let Ok(entry) = entry else { continue };
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Ok(relative) = entry.path().strip_prefix(&root) else {
+            continue;
+        };
+        let shown = format!("{prefix}/{}", display_path(relative));
+        if matcher.is_match(&shown) {
+            let modified = entry.metadata().ok().and_then(|m| m.modified().ok());
+            found.push((modified, shown));
+        }
+    }
+    found.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    Ok(found
+        .into_iter()
+        .take(limit)
+        .map(|(_, path)| path)
+        .collect())
+}
+
+/// Compile a shell glob into an anchored regex. `*` matches any run of
+/// characters, `**/` zero or more directories, `?` one character, and
+/// `[...]` / `[!...]` a class.
+pub fn glob_regex(glob: &str) -> Result<Regex, String> {
+    let mut re = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => {
+                let mut double = false;
+                while chars.peek() == Some(&'*') {
+                    chars.next();
+                    double = true;
+                }
+                // `**/` also matches no directories at all: `src/**/*.rs`
+                // includes `src/lib.rs`.
+                if double && chars.peek() == Some(&'/') {
+                    chars.next();
+                    re.push_str("(?:.*/)?");
+                } else {
+                    re.push_str(".*");
+                }
+            }
+            '?' => re.push('.'),
+            '[' => {
+                re.push('[');
+                if chars.peek() == Some(&'!') {
+                    chars.next();
+                    re.push('^');
+                }
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                    if c == '\\' || c == '[' {
+                        re.push('\\');
+                    }
+                    re.push(c);
+                }
+                re.push(']');
+            }
+            other => re.push_str(&regex::escape(&other.to_string())),
+        }
+    }
+    re.push('$');
+    Regex::new(&re).map_err(|e| format!("invalid glob '{glob}': {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(pattern: &str, mode: GrepMode) -> GrepOptions<'_> {
+        GrepOptions {
+            pattern,
+            glob: None,
+            case_insensitive: false,
+            mode,
+            context_lines: 0,
+            max_matches_per_file: 100,
+        }
+    }
+
+    #[test]
+    fn globs_translate_to_regexes() {
+        assert!(glob_regex("*.rs").unwrap().is_match("main.rs"));
+        assert!(!glob_regex("*.rs").unwrap().is_match("main.rsx"));
+        let nested = glob_regex("./src/**/*.rs").unwrap();
+        assert!(nested.is_match("./src/a/b.rs"));
+        assert!(nested.is_match("./src/lib.rs"));
+        assert!(glob_regex("f?.[!t]xt").unwrap().is_match("f1.sxt"));
+        assert!(!glob_regex("f?.[!t]xt").unwrap().is_match("f1.txt"));
+        assert!(glob_regex("a+b(1).md").unwrap().is_match("a+b(1).md"));
+    }
+
+    #[test]
+    fn grep_content_groups_context() {
+        let dir = tempfile::tempdir().unwrap();
+        let body: String = (1..=9).map(|i| format!("line{i}\n")).collect();
+        std::fs::write(dir.path().join("a.txt"), body).unwrap();
+        std::fs::write(dir.path().join("bin.dat"), b"line1\0").unwrap();
+
+        let mut opts = options("line(2|8)", GrepMode::Content);
+        opts.context_lines = 1;
+        let out = grep(dir.path(), &opts).unwrap();
+        let path = display_path(&dir.path().join("a.txt"));
+        assert_eq!(
+            out,
+            [
+                format!("{path}-1-line1"),
+                format!("{path}:2:line2"),
+                format!("{path}-3-line3"),
+                "--".to_string(),
+                format!("{path}-7-line7"),
+                format!("{path}:8:line8"),
+                format!("{path}-9-line9"),
+            ]
+        );
+    }
+
+    #[test]
+    fn grep_filters_by_glob_and_counts() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("sub/a.rs"), "foo\nFOO\nfoo\n").unwrap();
+        std::fs::write(dir.path().join("b.md"), "foo\n").unwrap();
+
+        let mut opts = options("foo", GrepMode::Count);
+        opts.glob = Some("*.rs");
+        opts.case_insensitive = true;
+        let out = grep(dir.path(), &opts).unwrap();
+        assert_eq!(out.len(), 1);
+        assert!(out[0].ends_with("sub/a.rs:3"), "{out:?}");
+
+        opts.max_matches_per_file = 1;
+        assert!(grep(dir.path(), &opts).unwrap()[0].ends_with(":1"));
+        assert!(grep(dir.path(), &options("(", GrepMode::Files)).is_err());
+    }
+
+    #[test]
+    fn find_matches_paths_under_the_search_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src/nested")).unwrap();
+        std::fs::write(dir.path().join("src/a.rs"), "").unwrap();
+        std::fs::write(dir.path().join("src/nested/b.rs"), "").unwrap();
+        std::fs::write(dir.path().join("README.md"), "").unwrap();
+
+        let mut all = find_files(dir.path(), ".", "*.rs", 10).unwrap();
+        all.sort();
+        assert_eq!(all, ["./src/a.rs", "./src/nested/b.rs"]);
+        let nested = find_files(dir.path(), "src/", "nested/*", 10).unwrap();
+        assert_eq!(nested, ["src/nested/b.rs"]);
+        assert_eq!(find_files(dir.path(), ".", "*.rs", 1).unwrap().len(), 1);
+    }
+}
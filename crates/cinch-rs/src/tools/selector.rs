@@ -0,0 +1,275 @@
+//! Embedding-similarity tool selection.
+//!
+//! [`ToolFilter`](super::filter::ToolFilter) matches task keywords against
+//! category descriptions, which misses synonyms ("locate" vs "find"). An
+//! [`EmbeddingToolSelector`] instead embeds each tool definition (name and
+//! description) once, embeds the task at the start of each run, and keeps the
+//! `top_k` tools whose embeddings are most similar to the task, plus a
+//! mandatory core set that is always included.
+//!
+//! Attach one to a harness with
+//! [`Harness::with_tool_selector()`](crate::agent::harness::Harness::with_tool_selector).
+//! Share the same `Arc` across runs so tool embeddings are only computed
+//! once.
+
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+
+use super::read_tracker::fnv1a;
+use crate::{OpenRouterClient, ToolDef};
+
+/// Default embedding model for tool selection.
+pub const DEFAULT_EMBEDDING_MODEL: &str = "openai/text-embedding-3-small";
+
+/// A boxed future returning one embedding per input.
+pub type EmbedFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<Vec<f32>>, String>> + Send + 'a>>;
+
+/// Something that can embed text. Implemented by [`OpenRouterClient`].
+pub trait Embedder: Send + Sync {
+    /// Embed `inputs` with `model`, returning one vector per input in order.
+    fn embed<'a>(&'a self, model: &'a str, inputs: &'a [String]) -> EmbedFuture<'a>;
+}
+
+impl Embedder for OpenRouterClient {
+    fn embed<'a>(&'a self, model: &'a str, inputs: &'a [String]) -> EmbedFuture<'a> {
+        Box::pin(OpenRouterClient::embed(self, model, inputs))
+    }
+}
+
+/// Cosine similarity of two vectors (`0.0` if either is zero or they differ
+/// in length).
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// Text embedded for a tool definition.
+fn tool_text(tool: &ToolDef) -> String {
+    format!("{}: {}", tool.function.name, tool.function.description)
+}
+
+/// Selects the tools most relevant to a task by embedding similarity.
+pub struct EmbeddingToolSelector {
+    model: String,
+    top_k: usize,
+    /// Tools included regardless of similarity.
+    core: HashSet<String>,
+    /// Tool name → (hash of embedded text, embedding).
+    embeddings: Mutex<HashMap<String, (u64, Vec<f32>)>>,
+}
+
+impl EmbeddingToolSelector {
+    /// Create a selector keeping the `top_k` most relevant non-core tools.
+    pub fn new(top_k: usize) -> Self {
+        Self {
+            model: DEFAULT_EMBEDDING_MODEL.to_string(),
+            top_k,
+            core: HashSet::new(),
+            embeddings: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Use a different embedding model (builder pattern).
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// Always include these tools (builder pattern).
+    pub fn with_core(mut self, tool_names: &[&str]) -> Self {
+        self.core
+            .extend(tool_names.iter().map(|name| (*name).to_string()));
+        self
+    }
+
+    /// Always include the core tools from
+    /// [`ToolSet::with_common_tools`](crate::tools::core::ToolSet::with_common_tools):
+    /// `think`, `todo`, `read_file`, `list_dir`, and `shell`.
+    pub fn with_common_core(self) -> Self {
+        use super::names::*;
+        self.with_core(&[THINK, TODO, READ_FILE, LIST_DIR, SHELL])
+    }
+
+    /// Number of tool embeddings cached so far.
+    pub fn cached_embeddings(&self) -> usize {
+        self.embeddings.lock().unwrap().len()
+    }
+
+    /// Select the core tools plus the `top_k` tools most similar to `task`.
+    ///
+    /// Tools not yet embedded (or whose description changed) are embedded
+    /// in the same request as the task. The result keeps the order of
+    /// `tools`, so the tool definitions stay stable across rounds.
+    pub async fn select(
+        &self,
+        embedder: &dyn Embedder,
+        task: &str,
+        tools: &[ToolDef],
+    ) -> Result<Vec<ToolDef>, String> {
+        let candidates: Vec<&ToolDef> = tools
+            .iter()
+            .filter(|t| !self.core.contains(&t.function.name))
+            .collect();
+        if candidates.len() <= self.top_k {
+            return Ok(tools.to_vec());
+        }
+
+        // Embed the task together with any tools missing from the cache.
+        let missing: Vec<(&str, u64, String)> = {
+            let cache = self.embeddings.lock().unwrap();
+            candidates
+                .iter()
+                .map(|t| {
+                    let text = tool_text(t);
+                    (t.function.name.as_str(), fnv1a(&text), text)
+                })
+                .filter(|(name, hash, _)| cache.get(*name).is_none_or(|(h, _)| h != hash))
+                .collect()
+        };
+        let mut inputs: Vec<String> = missing.iter().map(|(_, _, text)| text.clone()).collect();
+        inputs.push(task.to_string());
+        let mut vectors = embedder.embed(&self.model, &inputs).await?;
+        if vectors.len() != inputs.len() {
+            return Err(format!(
+                "expected {} embeddings, got {}",
+                inputs.len(),
+                vectors.len()
+            ));
+        }
+        let task_vector = vectors.pop().unwrap_or_default();
+
+        let mut cache = self.embeddings.lock().unwrap();
+        for ((name, hash, _), vector) in missing.into_iter().zip(vectors) {
+            cache.insert(name.to_string(), (hash, vector));
+        }
+
+        let mut scored: Vec<(&str, f32)> = candidates
+            .iter()
+            .map(|t| {
+                let name = t.function.name.as_str();
+                let score = cache
+                    .get(name)
+                    .map_or(0.0, |(_, v)| cosine_similarity(v, &task_vector));
+                (name, score)
+            })
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let selected: HashSet<&str> = scored
+            .iter()
+            .take(self.top_k)
+            .map(|(name, _)| *name)
+            .collect();
+
+        Ok(tools
+            .iter()
+            .filter(|t| {
+                self.core.contains(&t.function.name) || selected.contains(t.function.name.as_str())
+            })
+            .cloned()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Embeds text as counts of a few fixed concept words, treating
+    /// synonyms as the same concept.
+    struct ConceptEmbedder {
+        inputs_seen: AtomicUsize,
+    }
+
+    impl Embedder for ConceptEmbedder {
+        fn embed<'a>(&'a self, _model: &'a str, inputs: &'a [String]) -> EmbedFuture<'a> {
+            self.inputs_seen.fetch_add(inputs.len(), Ordering::SeqCst);
+            let concepts: [&[&str]; 3] = [
+                &["search", "find", "locate"],
+                &["web", "internet", "online"],
+                &["edit", "modify", "change"],
+            ];
+            let vectors = inputs
+                .iter()
+                .map(|text| {
+                    let text = text.to_lowercase();
+                    concepts
+                        .iter()
+                        .map(|words| words.iter().filter(|w| text.contains(*w)).count() as f32)
+                        .collect()
+                })
+                .collect();
+            Box::pin(async move { Ok(vectors) })
+        }
+    }
+
+    fn tool(name: &str, description: &str) -> ToolDef {
+        ToolDef::new(name, description, serde_json::json!({}))
+    }
+
+    #[test]
+    fn cosine_similarity_basics() {
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[1.0, 0.0]), 1.0);
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]), 0.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+        assert_eq!(cosine_similarity(&[1.0], &[1.0, 0.0]), 0.0);
+    }
+
+    #[tokio::test]
+    async fn selects_by_similarity_and_caches_tool_embeddings() {
+        let embedder = ConceptEmbedder {
+            inputs_seen: AtomicUsize::new(0),
+        };
+        let selector = EmbeddingToolSelector::new(1).with_core(&["think"]);
+        let tools = vec![
+            tool("think", "Reason step by step"),
+            tool("grep", "Search file contents"),
+            tool("web_search", "Query the internet"),
+            tool("edit_file", "Modify a file"),
+        ];
+
+        let selected = selector
+            .select(&embedder, "Locate the config loader", &tools)
+            .await
+            .unwrap();
+        let names: Vec<_> = selected.iter().map(|t| t.function.name.as_str()).collect();
+        assert_eq!(names, vec!["think", "grep"]);
+        assert_eq!(selector.cached_embeddings(), 3);
+        assert_eq!(embedder.inputs_seen.load(Ordering::SeqCst), 4);
+
+        // Second run embeds only the task.
+        let selected = selector
+            .select(&embedder, "Look this up online", &tools)
+            .await
+            .unwrap();
+        let names: Vec<_> = selected.iter().map(|t| t.function.name.as_str()).collect();
+        assert_eq!(names, vec!["think", "web_search"]);
+        assert_eq!(embedder.inputs_seen.load(Ordering::SeqCst), 5);
+    }
+
+    #[tokio::test]
+    async fn small_tool_sets_are_not_filtered() {
+        let embedder = ConceptEmbedder {
+            inputs_seen: AtomicUsize::new(0),
+        };
+        let selector = EmbeddingToolSelector::new(5);
+        let tools = vec![tool("grep", "Search"), tool("shell", "Run commands")];
+        let selected = selector
+            .select(&embedder, "anything", &tools)
+            .await
+            .unwrap();
+        assert_eq!(selected.len(), 2);
+        assert_eq!(embedder.inputs_seen.load(Ordering::SeqCst), 0);
+    }
+}
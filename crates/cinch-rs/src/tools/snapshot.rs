@@ -0,0 +1,219 @@
+//! File snapshots for rolling back mutations.
+//!
+//! A [`FileSnapshot`] records a file's content (or its absence) before a
+//! mutation tool runs, so the harness can restore it if the round is rolled
+//! back (see [`PartialFailurePolicy::RollBack`](crate::agent::config::PartialFailurePolicy::RollBack)).
+//!
+//! A [`SnapshotStore`] keeps the first snapshot of every file mutated during
+//! a run, so the run's net workspace changes can be reported as
+//! [`FileChange`]s (see
+//! [`HarnessResult::files_changed`](crate::agent::events::HarnessResult::files_changed)).
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+
+use super::diff::DiffStats;
+
+/// A file's content at a point in time.
+#[derive(Debug, Clone)]
+pub struct FileSnapshot {
+    path: PathBuf,
+    /// `None` when the file did not exist.
+    content: Option<Vec<u8>>,
+}
+
+impl FileSnapshot {
+    /// Record the current content of `path`.
+    pub fn capture(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let content = std::fs::read(&path).ok();
+        Self { path, content }
+    }
+
+    /// The snapshotted path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write the recorded content back, or remove the file if it did not
+    /// exist when the snapshot was taken.
+    pub fn restore(&self) -> Result<(), String> {
+        match self.content {
+            Some(ref content) => std::fs::write(&self.path, content)
+                .map_err(|e| format!("Failed to restore {}: {e}", self.path.display())),
+            None if self.path.exists() => std::fs::remove_file(&self.path)
+                .map_err(|e| format!("Failed to remove {}: {e}", self.path.display())),
+            None => Ok(()),
+        }
+    }
+}
+
+/// How a file changed over a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Deleted,
+}
+
+/// Net change to one file over a run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileChange {
+    /// Path relative to the store's root (absolute if outside it).
+    pub path: String,
+    pub kind: ChangeKind,
+    pub stats: DiffStats,
+    /// Unified diff with `a/` and `b/` headers, when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+}
+
+/// First snapshot of each file mutated during a run.
+#[derive(Debug, Default)]
+pub struct SnapshotStore {
+    snapshots: BTreeMap<PathBuf, FileSnapshot>,
+}
+
+impl SnapshotStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Snapshot `path` unless it was already recorded, so the store keeps
+    /// the content from before the run's first mutation.
+    pub fn record(&mut self, path: impl Into<PathBuf>) {
+        let path = path.into();
+        self.snapshots
+            .entry(path.clone())
+            .or_insert_with(|| FileSnapshot::capture(path));
+    }
+
+    /// Number of recorded files.
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Whether no file has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Compare each recorded file against its current content. Files whose
+    /// content is unchanged are left out; paths are made relative to `root`.
+    pub fn changes(&self, root: &Path, include_diffs: bool) -> Vec<FileChange> {
+        self.snapshots
+            .iter()
+            .filter_map(|(path, snapshot)| {
+                let current = std::fs::read(path).ok();
+                let kind = match (&snapshot.content, &current) {
+                    (None, None) => return None,
+                    (Some(before), Some(after)) if before == after => return None,
+                    (None, Some(_)) => ChangeKind::Added,
+                    (Some(_), None) => ChangeKind::Deleted,
+                    (Some(_), Some(_)) => ChangeKind::Modified,
+                };
+                let display = path.strip_prefix(root).unwrap_or(path);
+                let display = display.to_string_lossy().into_owned();
+                let before = text(snapshot.content.as_deref());
+                let after = text(current.as_deref());
+                let diff = TextDiff::from_lines(&before, &after);
+                let mut stats = DiffStats::default();
+                for change in diff.iter_all_changes() {
+                    match change.tag() {
+                        ChangeTag::Insert => stats.added += 1,
+                        ChangeTag::Delete => stats.removed += 1,
+                        ChangeTag::Equal => {}
+                    }
+                }
+                let diff = include_diffs.then(|| {
+                    diff.unified_diff()
+                        .context_radius(3)
+                        .header(&format!("a/{display}"), &format!("b/{display}"))
+                        .to_string()
+                });
+                Some(FileChange {
+                    path: display,
+                    kind,
+                    stats,
+                    diff,
+                })
+            })
+            .collect()
+    }
+}
+
+fn text(content: Option<&[u8]>) -> String {
+    content
+        .map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restores_content_and_absence() {
+        let dir = tempfile::tempdir().unwrap();
+        let existing = dir.path().join("a.rs");
+        let created = dir.path().join("b.rs");
+        std::fs::write(&existing, "old").unwrap();
+
+        let snapshots = [
+            FileSnapshot::capture(&existing),
+            FileSnapshot::capture(&created),
+        ];
+        std::fs::write(&existing, "new").unwrap();
+        std::fs::write(&created, "new").unwrap();
+
+        for snapshot in &snapshots {
+            snapshot.restore().unwrap();
+        }
+        assert_eq!(std::fs::read_to_string(&existing).unwrap(), "old");
+        assert!(!created.exists());
+    }
+
+    #[test]
+    fn store_reports_net_changes_since_first_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("edited.rs"), "a\nb\n").unwrap();
+        std::fs::write(root.join("reverted.rs"), "same\n").unwrap();
+        std::fs::write(root.join("gone.rs"), "x\n").unwrap();
+
+        let mut store = SnapshotStore::new();
+        for name in ["edited.rs", "reverted.rs", "gone.rs", "new.rs"] {
+            store.record(root.join(name));
+        }
+        std::fs::write(root.join("edited.rs"), "a\nc\n").unwrap();
+        // A second mutation must not replace the original snapshot.
+        store.record(root.join("edited.rs"));
+        std::fs::write(root.join("edited.rs"), "a\nc\nd\n").unwrap();
+        std::fs::write(root.join("reverted.rs"), "changed\n").unwrap();
+        std::fs::write(root.join("reverted.rs"), "same\n").unwrap();
+        std::fs::remove_file(root.join("gone.rs")).unwrap();
+        std::fs::write(root.join("new.rs"), "fn main() {}\n").unwrap();
+
+        let changes = store.changes(root, true);
+        let summary: Vec<_> = changes
+            .iter()
+            .map(|c| (c.path.as_str(), c.kind, c.stats.added, c.stats.removed))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("edited.rs", ChangeKind::Modified, 2, 1),
+                ("gone.rs", ChangeKind::Deleted, 0, 1),
+                ("new.rs", ChangeKind::Added, 1, 0),
+            ]
+        );
+        let diff = changes[0].diff.as_deref().unwrap();
+        assert!(diff.starts_with("--- a/edited.rs\n+++ b/edited.rs\n"));
+        assert!(diff.contains("-b\n+c\n+d\n"));
+        assert!(store.changes(root, false)[0].diff.is_none());
+    }
+}
@@ -4,8 +4,14 @@
 //! metadata including purpose, when to use, when not to use, parameter
 //! documentation, and usage examples. This dramatically improves LLM tool
 //! selection accuracy (EASYTOOL ICLR 2024: 70% token cost reduction).
+//!
+//! Weak models often learn a tool's argument format better from a worked
+//! call than from prose. [`ToolCallExample`]s hold real arguments and
+//! results, and [`few_shot_messages`] turns them into assistant tool-call +
+//! tool-result pairs (see
+//! [`HarnessConfig::few_shot_tool_examples`](crate::agent::config::HarnessConfig::few_shot_tool_examples)).
 
-use crate::ToolDef;
+use crate::{CallType, FunctionCallData, Message, ToolCall, ToolDef};
 
 /// A structured tool specification with rich usage guidance.
 ///
@@ -30,6 +36,9 @@ pub struct ToolSpec {
     /// Disambiguation examples: situations where this tool is commonly
     /// confused with another tool, with clarification.
     pub disambiguation: Vec<DisambiguationExample>,
+    /// Structured calls for few-shot demonstrations. Not part of the
+    /// description.
+    pub call_examples: Vec<ToolCallExample>,
 }
 
 /// An example clarifying when to use this tool vs a similar one.
@@ -43,6 +52,59 @@ pub struct DisambiguationExample {
     pub reason: String,
 }
 
+/// A worked tool call: JSON arguments and the result they produce.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolCallExample {
+    pub arguments: serde_json::Value,
+    pub result: String,
+}
+
+impl ToolCallExample {
+    pub fn new(arguments: serde_json::Value, result: impl Into<String>) -> Self {
+        Self {
+            arguments,
+            result: result.into(),
+        }
+    }
+}
+
+/// Id prefix of the tool calls in [`few_shot_messages`], used to detect
+/// conversations that already contain the demonstrations.
+pub const FEW_SHOT_CALL_ID_PREFIX: &str = "example_call_";
+
+/// Opening user message of the few-shot block. Not the task, so code that
+/// looks for the first user message should skip it.
+pub const FEW_SHOT_PREAMBLE: &str = "Before the task, here are example tool calls showing the expected argument format. \
+     They are demonstrations only; their results are not about the current workspace.";
+
+/// Closing assistant message of the few-shot block.
+const FEW_SHOT_CLOSING: &str = "Understood. I will call tools in the same format.";
+
+/// Few-shot demonstrations for `(tool name, example)` pairs: a user
+/// preamble, one assistant tool call and tool result per example, and a
+/// closing assistant message, so the block can sit between the system
+/// prompt and the task. Empty when there are no examples.
+pub fn few_shot_messages(examples: &[(String, ToolCallExample)]) -> Vec<Message> {
+    if examples.is_empty() {
+        return Vec::new();
+    }
+    let mut messages = vec![Message::user(FEW_SHOT_PREAMBLE)];
+    for (i, (name, example)) in examples.iter().enumerate() {
+        let id = format!("{FEW_SHOT_CALL_ID_PREFIX}{}", i + 1);
+        messages.push(Message::assistant_tool_calls(vec![ToolCall {
+            id: id.clone(),
+            call_type: CallType::Function,
+            function: FunctionCallData {
+                name: name.clone(),
+                arguments: example.arguments.to_string(),
+            },
+        }]));
+        messages.push(Message::tool_result(id, &example.result));
+    }
+    messages.push(Message::assistant_text(FEW_SHOT_CLOSING));
+    messages
+}
+
 /// A usage example for a tool.
 #[derive(Debug, Clone)]
 pub struct UsageExample {
@@ -64,6 +126,7 @@ impl ToolSpec {
             examples: Vec::new(),
             output_format: None,
             disambiguation: Vec::new(),
+            call_examples: Vec::new(),
         }
     }
 
@@ -166,6 +229,7 @@ pub struct ToolSpecBuilder {
     examples: Vec<UsageExample>,
     output_format: Option<String>,
     disambiguation: Vec<DisambiguationExample>,
+    call_examples: Vec<ToolCallExample>,
 }
 
 impl ToolSpecBuilder {
@@ -206,6 +270,22 @@ impl ToolSpecBuilder {
         self
     }
 
+    /// Add a structured call for few-shot demonstrations.
+    pub fn call_example(mut self, arguments: serde_json::Value, result: impl Into<String>) -> Self {
+        self.call_examples
+            .push(ToolCallExample::new(arguments, result));
+        self
+    }
+
+    /// [`call_example`](Self::call_example) with the arguments as JSON
+    /// text. Panics on invalid JSON, like [`build`](Self::build) on missing
+    /// fields.
+    pub fn call_example_json(self, arguments: &str, result: impl Into<String>) -> Self {
+        let arguments = serde_json::from_str(arguments)
+            .unwrap_or_else(|e| panic!("ToolSpec call example is not valid JSON: {e}"));
+        self.call_example(arguments, result)
+    }
+
     pub fn output_format(mut self, format: impl Into<String>) -> Self {
         self.output_format = Some(format.into());
         self
@@ -246,6 +326,7 @@ impl ToolSpecBuilder {
             examples: self.examples,
             output_format: self.output_format.unwrap_or_else(|| "Plain text".into()),
             disambiguation: self.disambiguation,
+            call_examples: self.call_examples,
         }
     }
 }
@@ -374,4 +455,36 @@ mod tests {
             .parameters(serde_json::json!({}))
             .build();
     }
+
+    #[test]
+    fn call_examples_become_few_shot_messages() {
+        let spec = ToolSpec::builder("read_file")
+            .purpose("Read a file")
+            .when_to_use("When reading")
+            .when_not_to_use("When searching")
+            .parameters(serde_json::json!({"type": "object", "properties": {}}))
+            .call_example(
+                serde_json::json!({"path": "src/main.rs"}),
+                "L1: fn main() {}",
+            )
+            .build();
+        assert!(!spec.to_description().contains("src/main.rs"));
+
+        let examples: Vec<_> = spec
+            .call_examples
+            .iter()
+            .map(|e| (spec.name.clone(), e.clone()))
+            .collect();
+        let messages = few_shot_messages(&examples);
+        assert_eq!(messages.len(), 4);
+        assert!(matches!(messages[0].role, crate::MessageRole::User));
+        let call = &messages[1].tool_calls.as_ref().unwrap()[0];
+        assert_eq!(call.id, "example_call_1");
+        assert_eq!(call.function.name, "read_file");
+        assert_eq!(call.function.arguments, r#"{"path":"src/main.rs"}"#);
+        assert_eq!(messages[2].tool_call_id.as_deref(), Some("example_call_1"));
+        assert_eq!(messages[2].content.as_deref(), Some("L1: fn main() {}"));
+        assert!(matches!(messages[3].role, crate::MessageRole::Assistant));
+        assert!(few_shot_messages(&[]).is_empty());
+    }
 }
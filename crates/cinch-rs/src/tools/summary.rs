@@ -0,0 +1,196 @@
+//! One-line summaries of tool calls for display.
+//!
+//! [`summarize_call`] turns a call's raw JSON arguments into something a
+//! person can scan — `src/main.rs (+3/-1 lines)` for an edit, `cargo test`
+//! for a shell command — using the tool's parameter schema to pick the
+//! leading argument. The harness computes the summary once per call (see
+//! [`ToolSet::summarize_call`](super::core::ToolSet::summarize_call)) and
+//! passes it on `ToolExecuting` events and
+//! [`AgentEntry::ToolExecuting`](crate::ui::AgentEntry::ToolExecuting), so
+//! every frontend shows the same text instead of its own JSON dump.
+//!
+//! The rules:
+//!
+//! - The lead is the call's main subject: a required single-line string
+//!   argument, preferring [`LEAD_KEYS`] such as `path` and `command`.
+//! - `old_*` / `new_*` string pairs become `(+added/-removed lines)`.
+//! - Other multi-line or long strings (file contents, patches) are only
+//!   counted: `(12 lines)`.
+//! - Remaining arguments follow as `key=value`; `null`, `false`, and empty
+//!   values are left out.
+
+use serde_json::Value;
+
+use super::diff::unified_diff;
+
+/// Default width of a summary, in characters.
+pub const SUMMARY_CHARS: usize = 120;
+
+/// Argument names preferred as the lead, in priority order.
+pub const LEAD_KEYS: &[&str] = &[
+    "path", "file", "command", "pattern", "query", "url", "name", "id",
+];
+
+/// Strings longer than this (or with a newline) are counted, not shown.
+const MAX_INLINE_STRING: usize = 80;
+
+/// Summarize `arguments` for a tool whose JSON schema is `parameters`
+/// (`None` when unknown), in at most `max_len` characters.
+pub fn summarize_call(parameters: Option<&Value>, arguments: &str, max_len: usize) -> String {
+    let Ok(Value::Object(args)) = serde_json::from_str::<Value>(arguments) else {
+        return truncate(&one_line(arguments), max_len);
+    };
+    let required: Vec<&str> = parameters
+        .and_then(|p| p.get("required"))
+        .and_then(Value::as_array)
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    let mut keys: Vec<&str> = args.keys().map(String::as_str).collect();
+    let mut parts = Vec::new();
+
+    // Edits: `old_x` + `new_x` → line stats.
+    let mut edits = Vec::new();
+    for key in keys.clone() {
+        let Some(suffix) = key.strip_prefix("old_") else {
+            continue;
+        };
+        let new_key = format!("new_{suffix}");
+        if let (Some(Value::String(old)), Some(Value::String(new))) =
+            (args.get(key), args.get(&new_key))
+        {
+            let (_, stats) = unified_diff(old, new, 1);
+            edits.push(format!("+{}/-{} lines", stats.added, stats.removed));
+            keys.retain(|k| *k != key && *k != new_key);
+        }
+    }
+
+    // The lead: a single-line string, required ones first.
+    let is_lead = |k: &&str| args.get(*k).and_then(Value::as_str).is_some_and(is_inline);
+    let candidates: Vec<&str> = if required.iter().any(|k| keys.contains(k) && is_lead(k)) {
+        required
+            .iter()
+            .copied()
+            .filter(|k| keys.contains(k))
+            .collect()
+    } else {
+        keys.clone()
+    };
+    let lead = LEAD_KEYS
+        .iter()
+        .copied()
+        .find(|k| candidates.contains(k) && is_lead(k))
+        .or_else(|| candidates.iter().copied().find(is_lead));
+    if let Some(lead) = lead {
+        parts.push(args[lead].as_str().unwrap_or_default().to_string());
+        keys.retain(|k| *k != lead);
+    }
+
+    // Remaining arguments: short values inline, long strings counted.
+    let mut counted = Vec::new();
+    for key in keys {
+        match &args[key] {
+            Value::Null | Value::Bool(false) => {}
+            Value::String(s) if s.is_empty() => {}
+            Value::Array(a) if a.is_empty() => {}
+            Value::String(s) if !is_inline(s) => counted.push((key, s.lines().count())),
+            Value::String(s) => parts.push(format!("{key}={s}")),
+            Value::Bool(true) => parts.push(key.to_string()),
+            other => parts.push(format!("{key}={}", one_line(&other.to_string()))),
+        }
+    }
+    match counted.as_slice() {
+        [] => {}
+        [(_, lines)] => edits.push(format!("{lines} lines")),
+        many => edits.extend(many.iter().map(|(k, n)| format!("{k}: {n} lines"))),
+    }
+    if !edits.is_empty() {
+        parts.push(format!("({})", edits.join(", ")));
+    }
+    truncate(&parts.join(" "), max_len)
+}
+
+fn is_inline(s: &str) -> bool {
+    !s.contains('\n') && s.chars().count() <= MAX_INLINE_STRING
+}
+
+/// Collapse runs of whitespace (including newlines) to single spaces.
+fn one_line(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn truncate(s: &str, max_len: usize) -> String {
+    if s.chars().count() <= max_len {
+        return s.to_string();
+    }
+    let kept: String = s.chars().take(max_len.saturating_sub(3)).collect();
+    format!("{kept}...")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema(required: &[&str]) -> Value {
+        json!({ "type": "object", "required": required })
+    }
+
+    #[test]
+    fn edits_show_path_and_line_stats() {
+        let args = json!({
+            "path": "src/main.rs",
+            "old_string": "fn main() {\n    run();\n}",
+            "new_string": "fn main() {\n    init();\n    run(true);\n}",
+        });
+        let summary = summarize_call(
+            Some(&schema(&["new_string", "old_string", "path"])),
+            &args.to_string(),
+            SUMMARY_CHARS,
+        );
+        assert_eq!(summary, "src/main.rs (+2/-1 lines)");
+    }
+
+    #[test]
+    fn lead_comes_from_required_arguments() {
+        let args = r#"{"glob":"*.rs","pattern":"TODO","case_insensitive":true}"#;
+        assert_eq!(
+            summarize_call(Some(&schema(&["pattern"])), args, SUMMARY_CHARS),
+            "TODO case_insensitive glob=*.rs"
+        );
+        // Without a schema, well-known names still lead.
+        assert_eq!(
+            summarize_call(None, r#"{"timeout":30,"command":"cargo test"}"#, 40),
+            "cargo test timeout=30"
+        );
+    }
+
+    #[test]
+    fn tool_set_uses_the_tool_schema() {
+        let tools = crate::tools::core::ToolSet::new().with_common_tools(".");
+        let args = json!({ "path": "src/lib.rs", "old_string": "a", "new_string": "b\nc" });
+        assert_eq!(
+            tools.summarize_call("edit_file", &args.to_string()),
+            "src/lib.rs (+2/-1 lines)"
+        );
+    }
+
+    #[test]
+    fn long_content_is_counted() {
+        let args = json!({ "path": "notes.md", "content": "a\nb\nc" });
+        assert_eq!(
+            summarize_call(None, &args.to_string(), SUMMARY_CHARS),
+            "notes.md (3 lines)"
+        );
+    }
+
+    #[test]
+    fn unparseable_arguments_are_flattened() {
+        assert_eq!(summarize_call(None, "not\njson", 80), "not json");
+        assert_eq!(summarize_call(None, "{}", 80), "");
+        assert_eq!(
+            summarize_call(None, r#"{"command":"echo 0123456789"}"#, 10),
+            "echo 01..."
+        );
+    }
+}
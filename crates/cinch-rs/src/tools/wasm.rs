@@ -0,0 +1,315 @@
+//! Tool plugins compiled to WebAssembly.
+//!
+//! A [`WasmTool`] runs a tool implementation from a `.wasm` module (or `.wat`
+//! text) inside wasmtime, so tools can ship without recompiling the host
+//! agent. Modules are sandboxed: the host provides no imports (no WASI, so no
+//! filesystem, clock, environment, or network), every call runs in a fresh
+//! instance, and fuel and memory limits bound CPU time and memory.
+//!
+//! # ABI
+//!
+//! JSON in, string out, through the module's exported linear memory:
+//!
+//! | Export | Signature | Purpose |
+//! |--------|-----------|---------|
+//! | `memory` | memory | Linear memory shared with the host |
+//! | `alloc` | `(len: i32) -> i32` | Reserve `len` bytes for the host to write into |
+//! | `definition` | `() -> i64` | Definition JSON: `{"name", "description", "parameters"}` |
+//! | `execute` | `(ptr: i32, len: i32) -> i64` | Run on the arguments JSON at `ptr`; return the result |
+//!
+//! Strings are returned packed as `(ptr << 32) | len` and must be UTF-8. As
+//! with native tools, a result starting with `Error` counts as a failure.
+//!
+//! ```ignore
+//! let mut tools = ToolSet::new().with(WasmTool::load("plugins/word_count.wasm")?);
+//! for tool in load_wasm_tools("plugins")? {
+//!     tools.register(tool);
+//! }
+//! ```
+
+use std::path::Path;
+
+use serde::Deserialize;
+use tracing::debug;
+use wasmtime::{Config, Engine, Instance, Linker, Memory, Module, Store, StoreLimits};
+use wasmtime::{StoreLimitsBuilder, TypedFunc};
+
+use super::core::{Tool, ToolFuture};
+use crate::ToolDef;
+
+/// Default fuel per call (roughly one unit per executed instruction).
+pub const DEFAULT_WASM_FUEL: u64 = 1_000_000_000;
+
+/// Default cap on a plugin instance's linear memory (64 MiB).
+pub const DEFAULT_WASM_MAX_MEMORY: usize = 64 * 1024 * 1024;
+
+/// Definition JSON returned by a plugin's `definition` export.
+#[derive(Deserialize)]
+struct PluginDefinition {
+    name: String,
+    description: String,
+    #[serde(default = "empty_object")]
+    parameters: serde_json::Value,
+}
+
+fn empty_object() -> serde_json::Value {
+    serde_json::json!({ "type": "object", "properties": {} })
+}
+
+/// A tool backed by a sandboxed WebAssembly module.
+#[derive(Clone)]
+pub struct WasmTool {
+    engine: Engine,
+    module: Module,
+    definition: ToolDef,
+    fuel: u64,
+    max_memory_bytes: usize,
+}
+
+impl WasmTool {
+    /// Load a plugin from a `.wasm` (or `.wat`) file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let bytes =
+            std::fs::read(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        Self::from_bytes(&bytes).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    /// Load a plugin from module bytes (binary or text format).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine =
+            Engine::new(&config).map_err(|e| format!("Failed to create wasm engine: {e}"))?;
+        let module =
+            Module::new(&engine, bytes).map_err(|e| format!("Invalid wasm module: {e}"))?;
+        if let Some(import) = module.imports().next() {
+            return Err(format!(
+                "wasm plugins are sandboxed and may not import host functions \
+                 (found import {}::{})",
+                import.module(),
+                import.name()
+            ));
+        }
+
+        let mut tool = Self {
+            engine,
+            module,
+            definition: ToolDef::new("", "", empty_object()),
+            fuel: DEFAULT_WASM_FUEL,
+            max_memory_bytes: DEFAULT_WASM_MAX_MEMORY,
+        };
+        let json = tool.call_definition()?;
+        let def: PluginDefinition = serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid plugin definition JSON: {e}"))?;
+        if def.name.trim().is_empty() {
+            return Err("Plugin definition has an empty name".into());
+        }
+        tool.definition = ToolDef::new(def.name, def.description, def.parameters);
+        Ok(tool)
+    }
+
+    /// Set the fuel available to each call (builder pattern). A call that
+    /// runs out fails with an error result instead of hanging the agent.
+    pub fn with_fuel(mut self, fuel: u64) -> Self {
+        self.fuel = fuel;
+        self
+    }
+
+    /// Set the maximum linear memory per call, in bytes (builder pattern).
+    pub fn with_max_memory(mut self, bytes: usize) -> Self {
+        self.max_memory_bytes = bytes;
+        self
+    }
+
+    /// Instantiate the module in a fresh, limited store.
+    fn instantiate(&self) -> Result<(Store<StoreLimits>, Instance, Memory), String> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(self.max_memory_bytes)
+            .instances(1)
+            .build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store
+            .set_fuel(self.fuel)
+            .map_err(|e| format!("Failed to set fuel: {e}"))?;
+        // The linker has no definitions: this is the sandbox.
+        let instance = Linker::new(&self.engine)
+            .instantiate(&mut store, &self.module)
+            .map_err(|e| format!("Failed to instantiate plugin: {e}"))?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or("Plugin does not export `memory`")?;
+        Ok((store, instance, memory))
+    }
+
+    fn call_definition(&self) -> Result<String, String> {
+        let (mut store, instance, memory) = self.instantiate()?;
+        let definition: TypedFunc<(), i64> = instance
+            .get_typed_func(&mut store, "definition")
+            .map_err(|e| format!("Plugin export `definition`: {e}"))?;
+        let packed = definition
+            .call(&mut store, ())
+            .map_err(|e| format!("Plugin `definition` failed: {e}"))?;
+        read_packed(&store, memory, packed)
+    }
+
+    /// Run the plugin's `execute` export on `arguments`.
+    fn call_execute(&self, arguments: &str) -> Result<String, String> {
+        let (mut store, instance, memory) = self.instantiate()?;
+        let alloc: TypedFunc<i32, i32> = instance
+            .get_typed_func(&mut store, "alloc")
+            .map_err(|e| format!("Plugin export `alloc`: {e}"))?;
+        let execute: TypedFunc<(i32, i32), i64> = instance
+            .get_typed_func(&mut store, "execute")
+            .map_err(|e| format!("Plugin export `execute`: {e}"))?;
+
+        let len = i32::try_from(arguments.len()).map_err(|_| "Arguments too large")?;
+        let ptr = alloc
+            .call(&mut store, len)
+            .map_err(|e| trap_message("alloc", e))?;
+        memory
+            .write(&mut store, ptr as u32 as usize, arguments.as_bytes())
+            .map_err(|e| format!("Plugin `alloc` returned an invalid pointer: {e}"))?;
+        let packed = execute
+            .call(&mut store, (ptr, len))
+            .map_err(|e| trap_message("execute", e))?;
+        let remaining = store.get_fuel().unwrap_or(0);
+        debug!(
+            "wasm tool {} used {} fuel",
+            self.definition.function.name,
+            self.fuel.saturating_sub(remaining)
+        );
+        read_packed(&store, memory, packed)
+    }
+}
+
+/// Describe a trap, calling out fuel exhaustion.
+fn trap_message(export: &str, error: wasmtime::Error) -> String {
+    match error.downcast_ref::<wasmtime::Trap>() {
+        Some(wasmtime::Trap::OutOfFuel) => {
+            format!("Plugin `{export}` ran out of fuel (possible infinite loop)")
+        }
+        _ => format!("Plugin `{export}` trapped: {error}"),
+    }
+}
+
+/// Read a `(ptr << 32) | len` UTF-8 string out of guest memory.
+fn read_packed(store: &Store<StoreLimits>, memory: Memory, packed: i64) -> Result<String, String> {
+    let ptr = (packed as u64 >> 32) as usize;
+    let len = (packed as u64 & 0xffff_ffff) as usize;
+    let data = memory
+        .data(store)
+        .get(ptr..ptr + len)
+        .ok_or("Plugin returned a string outside its memory")?;
+    String::from_utf8(data.to_vec()).map_err(|_| "Plugin returned invalid UTF-8".to_string())
+}
+
+impl Tool for WasmTool {
+    fn definition(&self) -> ToolDef {
+        self.definition.clone()
+    }
+
+    fn execute(&self, arguments: &str) -> ToolFuture<'_> {
+        let tool = self.clone();
+        let arguments = arguments.to_string();
+        Box::pin(async move {
+            let name = tool.definition.function.name.clone();
+            // Compiled wasm runs synchronously; keep it off the async workers.
+            match tokio::task::spawn_blocking(move || tool.call_execute(&arguments)).await {
+                Ok(Ok(result)) => result,
+                Ok(Err(e)) => format!("Error: {name}: {e}"),
+                Err(e) => format!("Error: {name}: plugin task failed: {e}"),
+            }
+        })
+    }
+}
+
+/// Load every `.wasm` file in `dir` as a tool, in file name order.
+pub fn load_wasm_tools(dir: impl AsRef<Path>) -> Result<Vec<WasmTool>, String> {
+    let dir = dir.as_ref();
+    let mut paths: Vec<_> = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read {}: {e}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "wasm"))
+        .collect();
+    paths.sort();
+    paths.iter().map(WasmTool::load).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A plugin that echoes its arguments, with `execute_body` replacing the
+    /// echo when given.
+    fn plugin(extra: &str, execute_body: Option<&str>) -> String {
+        let def =
+            r#"{"name":"echo","description":"Echo the arguments","parameters":{"type":"object"}}"#;
+        let execute = execute_body.unwrap_or(
+            "(i64.or (i64.shl (i64.extend_i32_u (local.get 0)) (i64.const 32)) \
+             (i64.extend_i32_u (local.get 1)))",
+        );
+        format!(
+            r#"(module
+                {extra}
+                (memory (export "memory") 1)
+                (global $heap (mut i32) (i32.const 1024))
+                (data (i32.const 0) "{data}")
+                (func (export "alloc") (param i32) (result i32)
+                    (global.get $heap)
+                    (global.set $heap (i32.add (global.get $heap) (local.get 0))))
+                (func (export "definition") (result i64) (i64.const {len}))
+                (func (export "execute") (param i32 i32) (result i64) {execute}))"#,
+            data = def.replace('"', "\\\""),
+            len = def.len(),
+        )
+    }
+
+    #[tokio::test]
+    async fn runs_plugin_through_json_abi() {
+        let tool = WasmTool::from_bytes(plugin("", None).as_bytes()).unwrap();
+        let def = tool.definition();
+        assert_eq!(def.function.name, "echo");
+        assert_eq!(def.function.description, "Echo the arguments");
+        assert_eq!(tool.execute(r#"{"text":"hi"}"#).await, r#"{"text":"hi"}"#);
+    }
+
+    #[tokio::test]
+    async fn runaway_plugin_runs_out_of_fuel() {
+        let spin = "(loop $l (br $l)) (i64.const 0)";
+        let tool = WasmTool::from_bytes(plugin("", Some(spin)).as_bytes())
+            .unwrap()
+            .with_fuel(100_000);
+        let result = tool.execute("{}").await;
+        assert!(result.starts_with("Error: echo:"), "{result}");
+        assert!(result.contains("out of fuel"), "{result}");
+    }
+
+    #[test]
+    fn rejects_host_imports() {
+        let import = r#"(import "wasi_snapshot_preview1" "fd_write" (func (param i32 i32 i32 i32) (result i32)))"#;
+        let err = WasmTool::from_bytes(plugin(import, None).as_bytes())
+            .err()
+            .unwrap();
+        assert!(err.contains("wasi_snapshot_preview1::fd_write"), "{err}");
+    }
+
+    #[test]
+    fn memory_limit_applies_at_instantiation() {
+        let tool = WasmTool::from_bytes(plugin("", None).as_bytes()).unwrap();
+        let err = tool.with_max_memory(1024).call_execute("{}").unwrap_err();
+        assert!(err.contains("instantiate"), "{err}");
+    }
+
+    #[test]
+    fn loads_plugin_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        // Module loading accepts the text format regardless of extension.
+        std::fs::write(dir.path().join("echo.wasm"), plugin("", None)).unwrap();
+        std::fs::write(dir.path().join("README.md"), "not a plugin").unwrap();
+        let tools = load_wasm_tools(dir.path()).unwrap();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].name(), "echo");
+    }
+}
@@ -1,77 +1,265 @@
 //! LLM-callable tool for asking the human operator a question.
 //!
 //! When the LLM needs human input during its tool-use loop, it calls
-//! `ask_user` with a prompt and choices. The tool presents the question
-//! via [`ask_question`] and polls for a response.
+//! `ask_user` with a prompt and, depending on `kind`, choices to pick one or
+//! several of, a numeric range, or a file-path constraint. The tool presents
+//! the question via [`ask_question`] and polls for a response, which is
+//! returned as a JSON object whose fields depend on the answer type.
 //!
-//! In headless mode (no UI state), the tool returns `timed_out` immediately
-//! so the calling code can implement its own fallback.
+//! If the question times out and the model supplied a `default`, that
+//! answer is returned with `"defaulted": true`. In headless mode (no UI
+//! state) the tool answers immediately according to its [`HeadlessAnswer`]:
+//! by default the same way — the default if one was given, otherwise
+//! `timed_out` — or with an error telling the model to decide on its own.
+//!
+//! Questions can also be answered remotely: cinch-web's `POST /api/answer`
+//! resolves the active question without a TUI, and a
+//! [`QuestionWebhook`](super::webhook::QuestionWebhook) set with
+//! [`AskUserTool::with_webhook`] announces each question so someone knows
+//! to answer it. The tool expires unanswered questions itself when no
+//! frontend does.
+//!
+//! Register it with [`ToolSet::with_interaction_tools`](crate::tools::core::ToolSet::with_interaction_tools).
 
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use schemars::JsonSchema;
 use serde::Deserialize;
 
 use crate::ToolDef;
 use crate::tools::core::{Tool, ToolFuture};
+use crate::tools::names;
 use crate::tools::spec::ToolSpec;
 
-use super::{QuestionChoice, UiState, UserQuestion, ask_question, poll_question};
+use super::{
+    QuestionChoice, QuestionKind, QuestionResponse, UiState, UserQuestion, ask_question,
+    expire_question, poll_question,
+};
+
+/// How long past its deadline a question may stay open before the tool
+/// expires it, leaving interactive frontends time to do so first.
+const EXPIRY_GRACE: Duration = Duration::from_secs(2);
+
+/// Answer type for the `ask_user` tool.
+#[derive(Deserialize, JsonSchema, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum AskKind {
+    /// Pick exactly one of `choices`.
+    #[default]
+    Single,
+    /// Pick any number of `choices` (bounded by `min`/`max`).
+    MultiSelect,
+    /// Enter a number (bounded by `min`/`max`, whole if `integer`).
+    Number,
+    /// Enter a file path relative to the working directory.
+    FilePath,
+}
 
 /// Arguments for the `ask_user` tool.
 #[derive(Deserialize, JsonSchema)]
 struct AskUserArgs {
     /// The question to ask the human operator.
     prompt: String,
-    /// 2-10 selectable options presented to the user.
+    /// Answer type: "single" (default), "multi_select", "number", or "file_path".
+    #[serde(default)]
+    kind: AskKind,
+    /// 2-10 selectable options. Required for "single" and "multi_select".
+    #[serde(default)]
     choices: Vec<String>,
-    /// Whether the user can edit the selected option before confirming.
+    /// Whether the user can edit the selected option before confirming ("single" only).
     #[serde(default)]
     editable: bool,
-    /// Seconds before the question times out (default: 120).
-    #[serde(default = "default_timeout")]
-    timeout: u64,
+    /// Minimum number of selections ("multi_select") or minimum value ("number").
+    #[serde(default)]
+    min: Option<f64>,
+    /// Maximum number of selections ("multi_select") or maximum value ("number").
+    #[serde(default)]
+    max: Option<f64>,
+    /// Require a whole number ("number" only).
+    #[serde(default)]
+    integer: bool,
+    /// Require the path to exist ("file_path" only).
+    #[serde(default)]
+    must_exist: bool,
+    /// Answer used if the operator does not respond in time: a choice index
+    /// ("single"), an array of indices ("multi_select"), a number, or a path.
+    #[serde(default)]
+    default: Option<serde_json::Value>,
+    /// Seconds before the question times out. Defaults to the operator's setting.
+    #[serde(default)]
+    timeout: Option<u64>,
 }
 
-fn default_timeout() -> u64 {
-    120
+/// Default time the operator has to answer a question.
+pub const DEFAULT_ASK_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// How the `ask_user` tool answers when no UI is attached.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HeadlessAnswer {
+    /// Answer as if the question timed out: the model's `default` if it
+    /// gave one, otherwise `timed_out`.
+    #[default]
+    AssumeDefault,
+    /// Return an error telling the model no operator is available, so it
+    /// decides on its own.
+    Error,
 }
 
 /// Tool that lets the LLM ask the human operator a question.
 ///
 /// Internally calls [`ask_question`] on [`UiState`] and polls for a response.
-/// When no UI is attached (headless mode), returns `timed_out` immediately.
+/// When no UI is attached (headless mode), answers immediately as set by
+/// [`with_headless_answer`](Self::with_headless_answer).
 ///
 /// # Example
 ///
 /// ```ignore
-/// let tool = AskUserTool::new(Some(ui_state.clone()));
+/// let tool = AskUserTool::new(Some(ui_state.clone()))
+///     .with_workdir("/repo")
+///     .with_timeout(Duration::from_secs(300));
 /// tool_set.register(tool);
 /// ```
 pub struct AskUserTool {
     ui_state: Option<Arc<Mutex<UiState>>>,
+    workdir: Option<String>,
+    timeout: Duration,
+    headless: HeadlessAnswer,
+    #[cfg(not(target_arch = "wasm32"))]
+    webhook: Option<Arc<super::webhook::QuestionWebhook>>,
 }
 
 impl AskUserTool {
     /// Create a new `ask_user` tool.
     ///
     /// Pass `Some(state)` when a UI frontend is active, or `None` for
-    /// headless mode (the tool will return `timed_out` immediately).
+    /// headless mode (the tool answers immediately).
     pub fn new(ui_state: Option<Arc<Mutex<UiState>>>) -> Self {
-        Self { ui_state }
+        Self {
+            ui_state,
+            workdir: None,
+            timeout: DEFAULT_ASK_TIMEOUT,
+            headless: HeadlessAnswer::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            webhook: None,
+        }
+    }
+
+    /// Publish each question to `webhook` so it can be answered remotely
+    /// (through cinch-web's `POST /api/answer`). Needs a UI state to
+    /// receive the answer; headless tools answer without asking.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_webhook(mut self, webhook: super::webhook::QuestionWebhook) -> Self {
+        self.webhook = Some(Arc::new(webhook));
+        self
+    }
+
+    /// Time the operator has to answer when the model does not pass a
+    /// `timeout`. Default: [`DEFAULT_ASK_TIMEOUT`].
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// How to answer when no UI is attached. Default:
+    /// [`HeadlessAnswer::AssumeDefault`].
+    pub fn with_headless_answer(mut self, headless: HeadlessAnswer) -> Self {
+        self.headless = headless;
+        self
+    }
+
+    /// Resolve and complete `file_path` answers relative to `workdir`.
+    pub fn with_workdir(mut self, workdir: impl Into<String>) -> Self {
+        self.workdir = Some(workdir.into());
+        self
+    }
+
+    /// Build the [`UserQuestion`] for validated arguments.
+    fn build_question(&self, args: &AskUserArgs) -> Result<UserQuestion, String> {
+        let needs_choices = matches!(args.kind, AskKind::Single | AskKind::MultiSelect);
+        if needs_choices && args.choices.len() < 2 {
+            return Err("at least 2 choices are required".into());
+        }
+        if args.choices.len() > 10 {
+            return Err("at most 10 choices are allowed".into());
+        }
+        let kind = match args.kind {
+            AskKind::Single => QuestionKind::Single,
+            AskKind::MultiSelect => QuestionKind::MultiSelect {
+                min: args.min.map_or(0, |m| m.max(0.0) as usize),
+                max: args.max.map(|m| m.max(0.0) as usize),
+            },
+            AskKind::Number => QuestionKind::Number {
+                min: args.min,
+                max: args.max,
+                integer: args.integer,
+            },
+            AskKind::FilePath => QuestionKind::FilePath {
+                base_dir: self.workdir.clone(),
+                must_exist: args.must_exist,
+            },
+        };
+        let choices = if needs_choices {
+            args.choices
+                .iter()
+                .enumerate()
+                .map(|(i, c)| QuestionChoice {
+                    label: format!("Option {}", i + 1),
+                    body: c.clone(),
+                    metadata: String::new(),
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let mut question = UserQuestion {
+            prompt: args.prompt.clone(),
+            choices,
+            editable: args.editable && args.kind == AskKind::Single,
+            max_edit_length: None,
+            kind,
+            default: None,
+        };
+        if let Some(ref value) = args.default {
+            let default = parse_default(args.kind, value)?;
+            question
+                .validate(&default)
+                .map_err(|e| format!("invalid default: {e}"))?;
+            question.default = Some(default);
+        }
+        Ok(question)
     }
 }
 
+/// Convert the model's `default` argument into a response for `kind`.
+fn parse_default(kind: AskKind, value: &serde_json::Value) -> Result<QuestionResponse, String> {
+    let index = |v: &serde_json::Value| v.as_u64().map(|i| i as usize);
+    let parsed = match kind {
+        AskKind::Single => index(value).map(QuestionResponse::Selected),
+        AskKind::MultiSelect => value.as_array().and_then(|items| {
+            let mut indices = items.iter().map(index).collect::<Option<Vec<_>>>()?;
+            indices.sort_unstable();
+            indices.dedup();
+            Some(QuestionResponse::MultiSelected(indices))
+        }),
+        AskKind::Number => value.as_f64().map(QuestionResponse::Number),
+        AskKind::FilePath => value
+            .as_str()
+            .map(|p| QuestionResponse::Path(p.to_string())),
+    };
+    parsed.ok_or_else(|| format!("invalid default: {value}"))
+}
+
 impl Tool for AskUserTool {
     fn definition(&self) -> ToolDef {
-        ToolSpec::builder("ask_user")
+        ToolSpec::builder(names::ASK_USER)
             .purpose(
                 "Ask the human operator a question and wait for their response. \
                  Use this when you need human judgment or approval before proceeding.",
             )
             .when_to_use(
                 "When you need the operator to choose between options, confirm an action, \
-                 or provide input that requires human judgment",
+                 pick several items, enter a number, or point at a file",
             )
             .when_not_to_use(
                 "For routine decisions the agent can make autonomously. \
@@ -80,11 +268,21 @@ impl Tool for AskUserTool {
             .parameters_for::<AskUserArgs>()
             .example(
                 r#"ask_user(prompt="Which tweet should we post?", choices=["Tweet A: ...", "Tweet B: ..."])"#,
-                r#"{"status": "selected", "index": 0, "text": "Tweet A: ..."}"#,
+                r#"{"status": "selected", "kind": "single", "index": 0, "text": "Tweet A: ...", "defaulted": false}"#,
+            )
+            .example(
+                r#"ask_user(prompt="Which crates should be bumped?", kind="multi_select", choices=["core", "tui", "web"], min=1)"#,
+                r#"{"status": "selected", "kind": "multi_select", "indices": [0, 2], "texts": ["core", "web"], "defaulted": false}"#,
+            )
+            .example(
+                r#"ask_user(prompt="How many retries?", kind="number", min=0, max=10, integer=true, default=3)"#,
+                r#"{"status": "answered", "kind": "number", "value": 3, "defaulted": true}"#,
             )
             .output_format(
-                "JSON object with 'status' (selected|edited|skipped|timed_out), \
-                 'index' (selected choice index or null), and 'text' (choice text or null)",
+                "JSON object with 'status' (selected|edited|answered|free_text|skipped|timed_out), \
+                 'kind', 'defaulted' (true when the default was used after a timeout), and the \
+                 answer: 'index'/'text' for single, 'indices'/'texts' for multi_select, \
+                 'value' for number, 'path' for file_path",
             )
             .build()
             .to_tool_def()
@@ -97,77 +295,115 @@ impl Tool for AskUserTool {
                 Ok(a) => a,
                 Err(e) => return format!("Error: invalid arguments: {e}"),
             };
-
-            if args.choices.len() < 2 {
-                return "Error: at least 2 choices are required".to_string();
-            }
-            if args.choices.len() > 10 {
-                return "Error: at most 10 choices are allowed".to_string();
-            }
+            let question = match self.build_question(&args) {
+                Ok(q) => q,
+                Err(e) => return format!("Error: {e}"),
+            };
 
             let Some(ref state) = self.ui_state else {
                 // Headless mode — no UI attached.
-                return r#"{"status": "timed_out", "index": null, "text": null}"#.to_string();
+                return match self.headless {
+                    HeadlessAnswer::AssumeDefault => format_timeout(&question, &args.choices),
+                    HeadlessAnswer::Error => "Error: no operator is available to answer; \
+                         decide without asking and state the assumption you made"
+                        .to_string(),
+                };
             };
 
-            let question = UserQuestion {
-                prompt: args.prompt,
-                choices: args
-                    .choices
-                    .iter()
-                    .enumerate()
-                    .map(|(i, c)| QuestionChoice {
-                        label: format!("Option {}", i + 1),
-                        body: c.clone(),
-                        metadata: String::new(),
-                    })
-                    .collect(),
-                editable: args.editable,
-                max_edit_length: None,
-            };
-
-            ask_question(state, question, args.timeout);
+            let timeout = args.timeout.unwrap_or(self.timeout.as_secs());
+            let question_id = ask_question(state, question.clone(), timeout);
+
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(ref webhook) = self.webhook {
+                let webhook = Arc::clone(webhook);
+                let question = question.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = webhook.publish(&question, question_id, timeout).await {
+                        tracing::warn!("ask_user: failed to publish question to webhook: {e}");
+                    }
+                });
+            }
 
             // Poll until the question is resolved.
             loop {
                 if let Some(response) = poll_question(state) {
+                    if response == QuestionResponse::TimedOut {
+                        return format_timeout(&question, &args.choices);
+                    }
                     return format_response(&response, &args.choices);
                 }
-                tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+                expire_question(state, EXPIRY_GRACE);
+                crate::platform::sleep(std::time::Duration::from_millis(200)).await;
             }
         })
     }
 }
 
-fn format_response(response: &super::QuestionResponse, choices: &[String]) -> String {
+/// Format the answer for a timed-out question: its default, or `timed_out`.
+fn format_timeout(question: &UserQuestion, choices: &[String]) -> String {
+    let mut value = response_json(&question.timeout_response(), choices);
+    value["defaulted"] = question.default.is_some().into();
+    value.to_string()
+}
+
+fn format_response(response: &QuestionResponse, choices: &[String]) -> String {
+    response_json(response, choices).to_string()
+}
+
+fn response_json(response: &QuestionResponse, choices: &[String]) -> serde_json::Value {
+    let text = |i: &usize| choices.get(*i).cloned().unwrap_or_default();
     match response {
-        super::QuestionResponse::Selected(idx) => {
-            let text = choices.get(*idx).cloned().unwrap_or_default();
-            serde_json::json!({
-                "status": "selected",
-                "index": idx,
-                "text": text,
-            })
-            .to_string()
-        }
-        super::QuestionResponse::SelectedEdited { index, edited_text } => serde_json::json!({
+        QuestionResponse::Selected(idx) => serde_json::json!({
+            "status": "selected",
+            "kind": "single",
+            "index": idx,
+            "text": text(idx),
+            "defaulted": false,
+        }),
+        QuestionResponse::SelectedEdited { index, edited_text } => serde_json::json!({
             "status": "edited",
+            "kind": "single",
             "index": index,
             "text": edited_text,
-        })
-        .to_string(),
-        super::QuestionResponse::FreeText(text) => serde_json::json!({
+            "defaulted": false,
+        }),
+        QuestionResponse::MultiSelected(indices) => serde_json::json!({
+            "status": "selected",
+            "kind": "multi_select",
+            "indices": indices,
+            "texts": indices.iter().map(text).collect::<Vec<_>>(),
+            "defaulted": false,
+        }),
+        QuestionResponse::Number(n) => serde_json::json!({
+            "status": "answered",
+            "kind": "number",
+            "value": n,
+            "defaulted": false,
+        }),
+        QuestionResponse::Path(path) => serde_json::json!({
+            "status": "answered",
+            "kind": "file_path",
+            "path": path,
+            "defaulted": false,
+        }),
+        QuestionResponse::FreeText(text) => serde_json::json!({
             "status": "free_text",
             "index": null,
             "text": text,
-        })
-        .to_string(),
-        super::QuestionResponse::Skipped => {
-            r#"{"status": "skipped", "index": null, "text": null}"#.to_string()
-        }
-        super::QuestionResponse::TimedOut => {
-            r#"{"status": "timed_out", "index": null, "text": null}"#.to_string()
-        }
+            "defaulted": false,
+        }),
+        QuestionResponse::Skipped => serde_json::json!({
+            "status": "skipped",
+            "index": null,
+            "text": null,
+            "defaulted": false,
+        }),
+        QuestionResponse::TimedOut => serde_json::json!({
+            "status": "timed_out",
+            "index": null,
+            "text": null,
+            "defaulted": false,
+        }),
     }
 }
 
@@ -182,7 +418,7 @@ mod tests {
         assert_eq!(def.function.name, "ask_user");
         assert!(!def.function.description.is_empty());
 
-        // Check that the schema includes prompt and choices as required.
+        // Only the prompt is required; choices depend on `kind`.
         let schema = &def.function.parameters;
         let required = schema.get("required").and_then(|v| v.as_array());
         assert!(required.is_some());
@@ -192,7 +428,7 @@ mod tests {
             .filter_map(|v| v.as_str())
             .collect();
         assert!(required.contains(&"prompt"));
-        assert!(required.contains(&"choices"));
+        assert!(!required.contains(&"choices"));
     }
 
     #[tokio::test]
@@ -206,6 +442,15 @@ mod tests {
         assert!(parsed["index"].is_null());
     }
 
+    #[tokio::test]
+    async fn ask_user_tool_headless_error_ignores_default() {
+        let tool = AskUserTool::new(None).with_headless_answer(HeadlessAnswer::Error);
+        let result = tool
+            .execute(r#"{"prompt": "Pick one:", "choices": ["A", "B"], "default": 0}"#)
+            .await;
+        assert!(result.starts_with("Error: no operator"), "got: {result}");
+    }
+
     #[tokio::test]
     async fn ask_user_tool_validates_too_few_choices() {
         let tool = AskUserTool::new(None);
@@ -289,4 +534,69 @@ mod tests {
         let v: serde_json::Value = serde_json::from_str(&r).unwrap();
         assert_eq!(v["status"], "timed_out");
     }
+
+    #[tokio::test]
+    async fn ask_user_tool_headless_uses_default() {
+        let tool = AskUserTool::new(None);
+        let result = tool
+            .execute(
+                r#"{"prompt": "Which?", "kind": "multi_select", "choices": ["A", "B", "C"], "default": [2, 0]}"#,
+            )
+            .await;
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(parsed["status"], "selected");
+        assert_eq!(parsed["kind"], "multi_select");
+        assert_eq!(parsed["indices"], serde_json::json!([0, 2]));
+        assert_eq!(parsed["texts"], serde_json::json!(["A", "C"]));
+        assert_eq!(parsed["defaulted"], true);
+    }
+
+    #[tokio::test]
+    async fn ask_user_tool_number_validates_default() {
+        let tool = AskUserTool::new(None);
+        let ok = tool
+            .execute(r#"{"prompt": "Retries?", "kind": "number", "min": 0, "max": 5, "integer": true, "default": 3}"#)
+            .await;
+        let parsed: serde_json::Value = serde_json::from_str(&ok).unwrap();
+        assert_eq!(parsed["kind"], "number");
+        assert_eq!(parsed["value"], 3.0);
+        assert_eq!(parsed["defaulted"], true);
+
+        let bad = tool
+            .execute(r#"{"prompt": "Retries?", "kind": "number", "max": 5, "default": 9}"#)
+            .await;
+        assert!(bad.starts_with("Error: invalid default"), "got: {bad}");
+    }
+
+    #[tokio::test]
+    async fn ask_user_tool_timeout_falls_back_to_default() {
+        let state = Arc::new(Mutex::new(UiState::default()));
+        let tool = AskUserTool::new(Some(state.clone())).with_workdir("/repo");
+
+        let tool_handle = tokio::spawn(async move {
+            tool.execute(
+                r#"{"prompt": "Which file?", "kind": "file_path", "default": "src/lib.rs"}"#,
+            )
+            .await
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        {
+            let mut s = state.lock().unwrap();
+            let aq = s.active_question.as_mut().unwrap();
+            assert_eq!(
+                aq.question.kind,
+                QuestionKind::FilePath {
+                    base_dir: Some("/repo".into()),
+                    must_exist: false,
+                }
+            );
+            aq.done = true;
+        }
+
+        let result = tool_handle.await.unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(parsed["status"], "answered");
+        assert_eq!(parsed["path"], "src/lib.rs");
+        assert_eq!(parsed["defaulted"], true);
+    }
 }
@@ -14,11 +14,13 @@
 use std::sync::{Arc, Mutex};
 
 use crate::agent::events::{EventHandler, EventResponse, HarnessEvent};
+use crate::context::TokenReport;
 
 use super::{
-    ContextBreakdownSnapshot, ContextMessageInfo, ContextSnapshot, UiState, push_agent_text,
-    push_agent_text_delta, push_todo_update, push_tool_executing, push_tool_result,
-    update_context_snapshot, update_phase, update_prompt_cache, update_round,
+    ContextBreakdownSnapshot, ContextMessageInfo, ContextSnapshot, UiState, clear_streaming_buffer,
+    push_agent_text, push_agent_text_delta, push_file_edit, push_todo_update, push_tool_executing,
+    push_tool_result, update_context_snapshot, update_cost, update_phase, update_prompt_cache,
+    update_round,
 };
 
 /// Event handler that bridges [`HarnessEvent`] variants to [`UiState`] updates.
@@ -58,13 +60,15 @@ impl EventHandler for UiEventHandler {
                 push_agent_text_delta(&self.state, delta);
             }
             HarnessEvent::ToolExecuting {
-                name, arguments, ..
+                name,
+                arguments,
+                summary,
             } => {
                 // The todo tool updates in-place; skip the ToolExecuting entry
                 // so the output stream shows only the consolidated checklist.
                 if *name != "todo" {
                     update_phase(&self.state, &format!("Tool: {name}"));
-                    push_tool_executing(&self.state, name, arguments);
+                    push_tool_executing(&self.state, name, arguments, summary);
                 }
             }
             HarnessEvent::ToolResult { name, result, .. } => {
@@ -74,6 +78,23 @@ impl EventHandler for UiEventHandler {
                     push_tool_result(&self.state, name, result);
                 }
             }
+            HarnessEvent::FileEdited {
+                path, diff, stats, ..
+            } => {
+                push_file_edit(&self.state, path, diff, *stats);
+            }
+            HarnessEvent::StreamInterrupted { kept_chars, .. } => {
+                if *kept_chars == 0 {
+                    clear_streaming_buffer(&self.state);
+                }
+            }
+            HarnessEvent::CostUpdate {
+                cost_usd,
+                estimated,
+                ..
+            } => {
+                update_cost(&self.state, *cost_usd, *estimated);
+            }
             HarnessEvent::Reasoning(text) => {
                 push_agent_text(&self.state, &format!("[reasoning] {text}"));
             }
@@ -89,6 +110,23 @@ impl EventHandler for UiEventHandler {
             HarnessEvent::RoundLimitReached { .. } => {
                 update_phase(&self.state, "Round limit reached");
             }
+            HarnessEvent::CostLimitReached {
+                cost_usd,
+                max_cost_usd,
+            } => {
+                push_agent_text(
+                    &self.state,
+                    &format!("[budget] cost ${cost_usd:.4} reached the ${max_cost_usd:.2} limit"),
+                );
+                update_phase(&self.state, "Cost limit reached");
+            }
+            HarnessEvent::ResourceLimitExceeded {
+                name,
+                limit,
+                detail,
+            } => {
+                push_agent_text(&self.state, &format!("[limit] {name}: {limit} — {detail}"));
+            }
             HarnessEvent::ContextSnapshot {
                 messages,
                 max_tokens,
@@ -101,6 +139,8 @@ impl EventHandler for UiEventHandler {
                         middle_tokens: breakdown.middle_tokens,
                         recency_tokens: breakdown.recency_tokens,
                         total_tokens: breakdown.total_tokens,
+                        compaction_count: breakdown.compaction_count,
+                        history_generation: breakdown.history_generation,
                     }),
                     messages: messages
                         .iter()
@@ -117,6 +157,7 @@ impl EventHandler for UiEventHandler {
                         })
                         .collect(),
                     max_tokens: *max_tokens,
+                    token_report: TokenReport::from_details(messages),
                     prompt_cache: None,
                 };
                 update_context_snapshot(&self.state, snapshot);
@@ -143,6 +184,7 @@ impl EventHandler for UiEventHandler {
 mod tests {
     use super::*;
     use crate::context::ContextUsage;
+    use crate::ui::{AgentEntry, DiffStats};
 
     #[test]
     fn ui_event_handler_updates_state() {
@@ -180,6 +222,7 @@ mod tests {
         handler.on_event(&HarnessEvent::ToolExecuting {
             name: "read_file",
             arguments: r#"{"path":"a.md"}"#,
+            summary: "a.md",
         });
         {
             let s = state.lock().unwrap();
@@ -195,10 +238,38 @@ mod tests {
         });
         assert_eq!(state.lock().unwrap().agent_output.len(), 3);
 
+        // FileEdited
+        handler.on_event(&HarnessEvent::FileEdited {
+            name: "edit_file",
+            path: "a.md",
+            diff: "@@ -1,1 +1,1 @@\n-a\n+b\n",
+            stats: DiffStats {
+                added: 1,
+                removed: 1,
+            },
+        });
+        assert!(matches!(
+            state.lock().unwrap().agent_output.last(),
+            Some(AgentEntry::FileEdit { path, stats, .. }) if path == "a.md" && stats.added == 1
+        ));
+
         // Finished
         handler.on_event(&HarnessEvent::Finished);
         assert_eq!(state.lock().unwrap().phase, "Finished");
 
+        // CostUpdate
+        handler.on_event(&HarnessEvent::CostUpdate {
+            prompt_tokens: 1000,
+            completion_tokens: 64,
+            cost_usd: 0.0125,
+            estimated: true,
+        });
+        {
+            let s = state.lock().unwrap();
+            assert!((s.cost_usd - 0.0125).abs() < 1e-9);
+            assert!(s.cost_estimated);
+        }
+
         // RoundLimitReached
         handler.on_event(&HarnessEvent::RoundLimitReached { max_rounds: 30 });
         assert_eq!(state.lock().unwrap().phase, "Round limit reached");
@@ -20,15 +20,21 @@ pub mod event_handler;
 mod question;
 pub mod tracing;
 mod traits;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod webhook;
 
+pub use crate::tools::diff::DiffStats;
 pub use question::{
-    ActiveQuestion, QuestionChoice, QuestionResponse, UserQuestion, ask_question, poll_question,
+    ActiveQuestion, QuestionChoice, QuestionKind, QuestionResponse, UserQuestion, ask_question,
+    complete_path, expire_question, poll_question, resolve_path,
 };
 pub use traits::{NoExtension, UiExtension};
 
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
+use std::time::Duration;
+
+use crate::platform::{Instant, epoch_millis};
 
 /// Maximum log lines kept in memory.
 pub const MAX_LOG_LINES: usize = 2000;
@@ -55,14 +61,29 @@ pub const AGENT_OUTPUT_TRIM_TO: usize = 300;
 pub enum AgentEntry {
     /// Free-form text emitted by the LLM.
     Text(String),
-    /// A tool is about to execute.
-    ToolExecuting { name: String, arguments: String },
+    /// A tool is about to execute. `summary` is the one-line display form
+    /// of the arguments (see [`tools::summary`](crate::tools::summary)).
+    ToolExecuting {
+        name: String,
+        arguments: String,
+        #[serde(default)]
+        summary: String,
+    },
     /// A tool finished executing.
     ToolResult {
         name: String,
         result: String,
         is_error: bool,
     },
+    /// A file changed by a successful `edit_file` / `write_file` call.
+    ///
+    /// Follows the tool's `ToolResult` entry; `diff` is a unified diff
+    /// (see [`FileEdit`](crate::tools::diff::FileEdit)).
+    FileEdit {
+        path: String,
+        diff: String,
+        stats: DiffStats,
+    },
     /// A message sent by the user via the chat UI.
     UserMessage(String),
     /// A consolidated, in-place-updated todo checklist.
@@ -73,6 +94,24 @@ pub enum AgentEntry {
     TodoUpdate(String),
 }
 
+/// Threading metadata of an [`AgentEntry`], kept alongside it in
+/// [`UiState::output_meta`].
+///
+/// An entry's id is its sequence number (`output_offset + index`), stable
+/// for the life of the state. `parent` links entries into threads: a user
+/// message starts one, the agent's text replies to it, tool calls hang
+/// under the round's text (or the user message when the round had none),
+/// and results and file edits under their call.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryMeta {
+    /// Sequence number of the parent entry.
+    pub parent: Option<u64>,
+    /// Agent round the entry was recorded in.
+    pub round: u32,
+    /// When the entry was recorded (or last replaced), in Unix milliseconds.
+    pub timestamp_ms: u64,
+}
+
 // ── Log Types ─────────────────────────────────────────────────────────
 
 /// A single log line captured from tracing.
@@ -120,6 +159,8 @@ pub struct ContextSnapshot {
     pub messages: Vec<ContextMessageInfo>,
     /// Max context window tokens.
     pub max_tokens: usize,
+    /// Token totals per role and tool, for "what's eating my context" views.
+    pub token_report: crate::context::TokenReport,
     /// Prompt cache statistics from the most recent API round.
     ///
     /// Populated after the API response arrives (via `PromptCacheStats`
@@ -136,6 +177,8 @@ pub struct ContextBreakdownSnapshot {
     pub middle_tokens: usize,
     pub recency_tokens: usize,
     pub total_tokens: usize,
+    pub compaction_count: usize,
+    pub history_generation: u32,
 }
 
 /// Info about a single message in the context window, for UI display.
@@ -175,9 +218,15 @@ pub struct UiState {
     pub context_pct: f64,
     pub model: String,
     pub cycle: u32,
+    /// Estimated run cost in USD, updated live while rounds stream.
+    pub cost_usd: f64,
+    /// Whether `cost_usd` includes an in-progress round's estimate.
+    pub cost_estimated: bool,
 
     // ── Agent output stream ──
     pub agent_output: Vec<AgentEntry>,
+    /// Threading metadata for each `agent_output` entry (same indices).
+    pub output_meta: Vec<EntryMeta>,
     /// Buffer for accumulating streaming text deltas. Rendered live by the
     /// frontend; cleared when the complete `Text` event arrives.
     pub streaming_buffer: String,
@@ -207,6 +256,19 @@ pub struct UiState {
 
     // ── Domain-specific extension slot ──
     pub extensions: Box<dyn UiExtension>,
+
+    // ── Incremental sync ──
+    /// Entries trimmed from the front of `agent_output` so far, so that
+    /// `agent_output[i]` has the stable sequence number `output_offset + i`.
+    pub output_offset: u64,
+    /// Bumped each time an existing `agent_output` entry is replaced in
+    /// place rather than appended.
+    pub output_edits: u64,
+    /// Sequence number of the entry most recently replaced in place.
+    pub last_output_edit: u64,
+    /// Lines trimmed from the front of `logs` so far (same scheme as
+    /// `output_offset`).
+    pub log_offset: u64,
 }
 
 impl UiState {
@@ -217,6 +279,16 @@ impl UiState {
             ..Default::default()
         }
     }
+
+    /// Trim `logs` to [`LOG_TRIM_TO`] once it exceeds [`MAX_LOG_LINES`],
+    /// keeping the most recent lines and advancing `log_offset`.
+    pub fn trim_logs(&mut self) {
+        if self.logs.len() > MAX_LOG_LINES {
+            let drain = self.logs.len() - LOG_TRIM_TO;
+            self.logs.drain(..drain);
+            self.log_offset += drain as u64;
+        }
+    }
 }
 
 impl Default for UiState {
@@ -228,7 +300,10 @@ impl Default for UiState {
             context_pct: 0.0,
             model: String::new(),
             cycle: 0,
+            cost_usd: 0.0,
+            cost_estimated: false,
             agent_output: Vec::new(),
+            output_meta: Vec::new(),
             streaming_buffer: String::new(),
             logs: Vec::new(),
             running: true,
@@ -238,6 +313,10 @@ impl Default for UiState {
             next_cycle_at: None,
             context_snapshot: None,
             extensions: Box::new(NoExtension),
+            output_offset: 0,
+            output_edits: 0,
+            last_output_edit: 0,
+            log_offset: 0,
         }
     }
 }
@@ -268,15 +347,76 @@ pub fn update_round(state: &Arc<Mutex<UiState>>, round: u32, max: u32, ctx_pct:
     });
 }
 
+/// Discard streamed text that will not be completed (e.g. a stream that is
+/// restarted after its connection dropped).
+pub fn clear_streaming_buffer(state: &Arc<Mutex<UiState>>) {
+    with_state!(state, |s| { s.streaming_buffer.clear() });
+}
+
+/// Update the running cost display.
+pub fn update_cost(state: &Arc<Mutex<UiState>>, cost_usd: f64, estimated: bool) {
+    with_state!(state, |s| {
+        s.cost_usd = cost_usd;
+        s.cost_estimated = estimated;
+    });
+}
+
 /// Trim `agent_output` when it exceeds `MAX_AGENT_OUTPUT`, keeping the most
 /// recent entries.
 fn trim_agent_output(s: &mut UiState) {
     if s.agent_output.len() > MAX_AGENT_OUTPUT {
         let drain = s.agent_output.len() - AGENT_OUTPUT_TRIM_TO;
         s.agent_output.drain(..drain);
+        s.output_meta.drain(..drain.min(s.output_meta.len()));
+        s.output_offset += drain as u64;
     }
 }
 
+/// Append `entry` with its [`EntryMeta`], then trim.
+fn push_entry(s: &mut UiState, entry: AgentEntry) {
+    let meta = EntryMeta {
+        parent: parent_of(s, &entry),
+        round: s.round,
+        timestamp_ms: epoch_millis(),
+    };
+    // Keep the vectors aligned if entries were pushed without metadata.
+    let len = s.agent_output.len();
+    s.output_meta.resize(len, EntryMeta::default());
+    s.agent_output.push(entry);
+    s.output_meta.push(meta);
+    trim_agent_output(s);
+}
+
+/// Sequence number of the entry `entry` threads under (see [`EntryMeta`]).
+fn parent_of(s: &UiState, entry: &AgentEntry) -> Option<u64> {
+    let output = &s.agent_output;
+    let user = output
+        .iter()
+        .rposition(|e| matches!(e, AgentEntry::UserMessage(_)));
+    let since_user = user.map_or(0, |i| i + 1);
+    let index = match entry {
+        AgentEntry::UserMessage(_) => None,
+        AgentEntry::Text(_) | AgentEntry::TodoUpdate(_) => user,
+        AgentEntry::ToolExecuting { .. } => output
+            .iter()
+            .enumerate()
+            .skip(since_user)
+            .rposition(|(i, e)| {
+                matches!(e, AgentEntry::Text(_))
+                    && s.output_meta.get(i).is_some_and(|m| m.round == s.round)
+            })
+            .map(|i| i + since_user)
+            .or(user),
+        AgentEntry::ToolResult { name, .. } => output
+            .iter()
+            .rposition(|e| matches!(e, AgentEntry::ToolExecuting { name: n, .. } if n == name)),
+        AgentEntry::FileEdit { .. } => output
+            .iter()
+            .rposition(|e| matches!(e, AgentEntry::ToolResult { .. })),
+    };
+    index.map(|i| s.output_offset + i as u64)
+}
+
 /// Push a complete agent text block.
 ///
 /// If there is an in-progress streaming buffer it is discarded because the
@@ -284,8 +424,7 @@ fn trim_agent_output(s: &mut UiState) {
 pub fn push_agent_text(state: &Arc<Mutex<UiState>>, text: &str) {
     with_state!(state, |s| {
         s.streaming_buffer.clear();
-        s.agent_output.push(AgentEntry::Text(text.to_string()));
-        trim_agent_output(&mut s);
+        push_entry(&mut s, AgentEntry::Text(text.to_string()));
     });
 }
 
@@ -298,14 +437,23 @@ pub fn push_agent_text_delta(state: &Arc<Mutex<UiState>>, delta: &str) {
     with_state!(state, |s| { s.streaming_buffer.push_str(delta) });
 }
 
-/// Record that a tool is about to execute.
-pub fn push_tool_executing(state: &Arc<Mutex<UiState>>, name: &str, arguments: &str) {
+/// Record that a tool is about to execute, with the display `summary` of
+/// its arguments.
+pub fn push_tool_executing(
+    state: &Arc<Mutex<UiState>>,
+    name: &str,
+    arguments: &str,
+    summary: &str,
+) {
     with_state!(state, |s| {
-        s.agent_output.push(AgentEntry::ToolExecuting {
-            name: name.to_string(),
-            arguments: arguments.to_string(),
-        });
-        trim_agent_output(&mut s);
+        push_entry(
+            &mut s,
+            AgentEntry::ToolExecuting {
+                name: name.to_string(),
+                arguments: arguments.to_string(),
+                summary: summary.to_string(),
+            },
+        );
     });
 }
 
@@ -314,12 +462,28 @@ pub fn push_tool_executing(state: &Arc<Mutex<UiState>>, name: &str, arguments: &
 pub fn push_tool_result(state: &Arc<Mutex<UiState>>, name: &str, result: &str) {
     let is_error = result.starts_with("Error") || result.starts_with("error:");
     with_state!(state, |s| {
-        s.agent_output.push(AgentEntry::ToolResult {
-            name: name.to_string(),
-            result: result.to_string(),
-            is_error,
-        });
-        trim_agent_output(&mut s);
+        push_entry(
+            &mut s,
+            AgentEntry::ToolResult {
+                name: name.to_string(),
+                result: result.to_string(),
+                is_error,
+            },
+        );
+    });
+}
+
+/// Record a file change made by an edit tool.
+pub fn push_file_edit(state: &Arc<Mutex<UiState>>, path: &str, diff: &str, stats: DiffStats) {
+    with_state!(state, |s| {
+        push_entry(
+            &mut s,
+            AgentEntry::FileEdit {
+                path: path.to_string(),
+                diff: diff.to_string(),
+                stats,
+            },
+        );
     });
 }
 
@@ -330,17 +494,21 @@ pub fn push_tool_result(state: &Arc<Mutex<UiState>>, name: &str, result: &str) {
 /// Otherwise a new entry is appended.
 pub fn push_todo_update(state: &Arc<Mutex<UiState>>, content: &str) {
     with_state!(state, |s| {
-        if let Some(existing) = s
+        if let Some(index) = s
             .agent_output
-            .iter_mut()
-            .rev()
-            .find(|e| matches!(e, AgentEntry::TodoUpdate(_)))
+            .iter()
+            .rposition(|e| matches!(e, AgentEntry::TodoUpdate(_)))
         {
-            *existing = AgentEntry::TodoUpdate(content.to_string());
+            s.agent_output[index] = AgentEntry::TodoUpdate(content.to_string());
+            let round = s.round;
+            if let Some(meta) = s.output_meta.get_mut(index) {
+                meta.round = round;
+                meta.timestamp_ms = epoch_millis();
+            }
+            s.output_edits += 1;
+            s.last_output_edit = s.output_offset + index as u64;
         } else {
-            s.agent_output
-                .push(AgentEntry::TodoUpdate(content.to_string()));
-            trim_agent_output(&mut s);
+            push_entry(&mut s, AgentEntry::TodoUpdate(content.to_string()));
         }
     });
 }
@@ -348,9 +516,7 @@ pub fn push_todo_update(state: &Arc<Mutex<UiState>>, content: &str) {
 /// Record a user message sent from the chat UI.
 pub fn push_user_message(state: &Arc<Mutex<UiState>>, message: &str) {
     with_state!(state, |s| {
-        s.agent_output
-            .push(AgentEntry::UserMessage(message.to_string()));
-        trim_agent_output(&mut s);
+        push_entry(&mut s, AgentEntry::UserMessage(message.to_string()));
     });
 }
 
@@ -496,9 +662,14 @@ mod tests {
     fn tool_executing_and_result_pushed() {
         let state = Arc::new(Mutex::new(UiState::default()));
 
-        push_tool_executing(&state, "read_file", r#"{"path":"docs/voice.md"}"#);
+        push_tool_executing(
+            &state,
+            "read_file",
+            r#"{"path":"docs/voice.md"}"#,
+            "docs/voice.md",
+        );
         push_tool_result(&state, "read_file", "file contents here");
-        push_tool_executing(&state, "shell", r#"{"command":"echo hi"}"#);
+        push_tool_executing(&state, "shell", r#"{"command":"echo hi"}"#, "echo hi");
         push_tool_result(&state, "shell", "Error: command not allowed");
 
         let s = state.lock().unwrap();
@@ -508,6 +679,7 @@ mod tests {
             AgentEntry::ToolExecuting {
                 name: "read_file".into(),
                 arguments: r#"{"path":"docs/voice.md"}"#.into(),
+                summary: "docs/voice.md".into(),
             }
         );
         assert_eq!(
@@ -525,6 +697,40 @@ mod tests {
         }
     }
 
+    #[test]
+    fn entries_thread_under_their_turn() {
+        let state = Arc::new(Mutex::new(UiState::default()));
+        push_user_message(&state, "fix it");
+        update_round(&state, 1, 10, 0.0);
+        push_agent_text(&state, "Looking.");
+        push_tool_executing(&state, "edit_file", "{}", "");
+        push_tool_result(&state, "edit_file", "ok");
+        push_file_edit(&state, "a.rs", "", DiffStats::default());
+        update_round(&state, 2, 10, 0.0);
+        push_tool_executing(&state, "shell", "{}", "");
+
+        let s = state.lock().unwrap();
+        let parents: Vec<_> = s.output_meta.iter().map(|m| m.parent).collect();
+        assert_eq!(
+            parents,
+            vec![None, Some(0), Some(1), Some(2), Some(3), Some(0)]
+        );
+        assert_eq!(s.output_meta[1].round, 1);
+        assert_eq!(s.output_meta[5].round, 2);
+        assert!(s.output_meta[0].timestamp_ms > 0);
+    }
+
+    #[test]
+    fn entry_meta_is_trimmed_with_the_output() {
+        let state = Arc::new(Mutex::new(UiState::default()));
+        for i in 0..=MAX_AGENT_OUTPUT {
+            push_agent_text(&state, &i.to_string());
+        }
+        let s = state.lock().unwrap();
+        assert_eq!(s.output_meta.len(), s.agent_output.len());
+        assert_eq!(s.output_meta.len(), AGENT_OUTPUT_TRIM_TO);
+    }
+
     #[test]
     fn next_cycle_set_and_clear() {
         let state = Arc::new(Mutex::new(UiState::default()));
@@ -556,6 +762,7 @@ mod tests {
             ],
             editable: false,
             max_edit_length: None,
+            ..Default::default()
         };
 
         ask_question(&state, question, 60);
@@ -598,6 +805,7 @@ mod tests {
             }],
             editable: false,
             max_edit_length: None,
+            ..Default::default()
         };
         ask_question(&state, question, 0);
 
@@ -610,4 +818,153 @@ mod tests {
         let response = poll_question(&state).unwrap();
         assert_eq!(response, QuestionResponse::TimedOut);
     }
+
+    #[test]
+    fn expire_question_times_out_after_the_grace_period() {
+        let state = Arc::new(Mutex::new(UiState::default()));
+        ask_question(&state, UserQuestion::default(), 0);
+
+        assert!(!expire_question(&state, Duration::from_secs(60)));
+        assert!(poll_question(&state).is_none());
+        assert!(expire_question(&state, Duration::ZERO));
+        assert_eq!(poll_question(&state), Some(QuestionResponse::TimedOut));
+    }
+
+    #[test]
+    fn answers_parse_from_choice_numbers() {
+        let choice = |body: &str| QuestionChoice {
+            label: body.into(),
+            body: body.into(),
+            metadata: String::new(),
+        };
+        let single = UserQuestion {
+            prompt: "Pick:".into(),
+            choices: vec![choice("a"), choice("b"), choice("c")],
+            ..Default::default()
+        };
+        assert_eq!(
+            single.parse_answer(" 2 "),
+            Ok(QuestionResponse::Selected(1))
+        );
+        assert!(single.parse_answer("4").is_err());
+        assert!(single.parse_answer("1,2").is_err());
+        assert!(single.parse_answer("b").is_err());
+
+        let multi = UserQuestion {
+            kind: QuestionKind::MultiSelect { min: 1, max: None },
+            ..single.clone()
+        };
+        assert_eq!(
+            multi.parse_answer("3, 1 3"),
+            Ok(QuestionResponse::MultiSelected(vec![0, 2]))
+        );
+        assert!(multi.parse_answer("").is_err());
+
+        let number = UserQuestion {
+            kind: QuestionKind::Number {
+                min: None,
+                max: None,
+                integer: false,
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            number.parse_answer("2.5"),
+            Ok(QuestionResponse::Number(2.5))
+        );
+    }
+
+    #[test]
+    fn question_validates_by_kind() {
+        let choice = |body: &str| QuestionChoice {
+            label: body.into(),
+            body: body.into(),
+            metadata: String::new(),
+        };
+        let multi = UserQuestion {
+            prompt: "Which?".into(),
+            choices: vec![choice("a"), choice("b"), choice("c")],
+            kind: QuestionKind::MultiSelect {
+                min: 1,
+                max: Some(2),
+            },
+            ..Default::default()
+        };
+        assert!(
+            multi
+                .validate(&QuestionResponse::MultiSelected(vec![0, 2]))
+                .is_ok()
+        );
+        assert!(
+            multi
+                .validate(&QuestionResponse::MultiSelected(vec![]))
+                .is_err()
+        );
+        assert!(
+            multi
+                .validate(&QuestionResponse::MultiSelected(vec![0, 1, 2]))
+                .is_err()
+        );
+        assert!(
+            multi
+                .validate(&QuestionResponse::MultiSelected(vec![5]))
+                .is_err()
+        );
+        assert!(multi.validate(&QuestionResponse::Selected(0)).is_err());
+        assert!(multi.validate(&QuestionResponse::Skipped).is_ok());
+
+        let number = UserQuestion {
+            prompt: "How many?".into(),
+            kind: QuestionKind::Number {
+                min: Some(1.0),
+                max: Some(10.0),
+                integer: true,
+            },
+            default: Some(QuestionResponse::Number(3.0)),
+            ..Default::default()
+        };
+        assert!(number.is_text_entry());
+        assert_eq!(number.parse_text(" 4 "), Ok(QuestionResponse::Number(4.0)));
+        assert!(number.parse_text("4.5").is_err());
+        assert!(number.parse_text("11").is_err());
+        assert!(number.parse_text("four").is_err());
+        assert_eq!(number.timeout_response(), QuestionResponse::Number(3.0));
+    }
+
+    #[test]
+    fn file_path_questions_resolve_and_complete() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "").unwrap();
+        std::fs::write(dir.path().join("src/main.rs"), "").unwrap();
+        std::fs::write(dir.path().join(".env"), "").unwrap();
+        let base = dir.path().to_str().unwrap();
+
+        let question = UserQuestion {
+            prompt: "Which file?".into(),
+            kind: QuestionKind::FilePath {
+                base_dir: Some(base.into()),
+                must_exist: true,
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            question.parse_text("src/lib.rs"),
+            Ok(QuestionResponse::Path("src/lib.rs".into()))
+        );
+        assert!(question.parse_text("src/nope.rs").is_err());
+        assert_eq!(question.timeout_response(), QuestionResponse::TimedOut);
+
+        assert_eq!(complete_path(Some(base), "s"), vec!["src/"]);
+        assert_eq!(
+            complete_path(Some(base), "src/"),
+            vec!["src/lib.rs", "src/main.rs"]
+        );
+        assert_eq!(complete_path(Some(base), ".e"), vec![".env"]);
+        assert!(
+            complete_path(Some(base), "")
+                .iter()
+                .all(|p| !p.starts_with('.'))
+        );
+    }
 }
@@ -4,13 +4,17 @@
 //! generic question/choice/response pattern that works across any UI frontend.
 
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
+use std::time::Duration;
+
+use crate::platform::Instant;
 
 use super::UiState;
 
 /// A question presented to the user during an agent run.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct UserQuestion {
     /// Short prompt displayed as a header (e.g., "Which tweet should we post?").
     pub prompt: String,
@@ -20,10 +24,38 @@ pub struct UserQuestion {
     pub editable: bool,
     /// Optional validation for edited text (e.g., max character length).
     pub max_edit_length: Option<usize>,
+    /// What kind of answer is expected. Default: [`QuestionKind::Single`].
+    #[serde(default)]
+    pub kind: QuestionKind,
+    /// Answer used when the question times out (see
+    /// [`timeout_response`](Self::timeout_response)).
+    #[serde(default)]
+    pub default: Option<QuestionResponse>,
+}
+
+/// The kind of answer a [`UserQuestion`] expects.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum QuestionKind {
+    /// Pick one choice, or type free text when there are no choices.
+    #[default]
+    Single,
+    /// Pick between `min` and `max` (inclusive) of the choices.
+    MultiSelect { min: usize, max: Option<usize> },
+    /// Type a number, optionally bounded and/or restricted to integers.
+    Number {
+        min: Option<f64>,
+        max: Option<f64>,
+        integer: bool,
+    },
+    /// Type a file path, resolved against `base_dir` when relative.
+    FilePath {
+        base_dir: Option<String>,
+        must_exist: bool,
+    },
 }
 
 /// A single selectable choice within a [`UserQuestion`].
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct QuestionChoice {
     /// Short label (e.g., "Tweet 1 — Technical Explainer").
     pub label: String,
@@ -34,12 +66,19 @@ pub struct QuestionChoice {
 }
 
 /// The user's response to a [`UserQuestion`].
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum QuestionResponse {
     /// User selected a choice (by index).
     Selected(usize),
     /// User selected a choice and edited its body text.
     SelectedEdited { index: usize, edited_text: String },
+    /// User selected several choices ([`QuestionKind::MultiSelect`]), in
+    /// ascending index order.
+    MultiSelected(Vec<usize>),
+    /// User entered a number ([`QuestionKind::Number`]).
+    Number(f64),
+    /// User entered a file path ([`QuestionKind::FilePath`]), as typed.
+    Path(String),
     /// User typed free-form text (no pre-defined choices).
     FreeText(String),
     /// User explicitly skipped / dismissed the question.
@@ -48,9 +87,189 @@ pub enum QuestionResponse {
     TimedOut,
 }
 
+impl UserQuestion {
+    /// Check that `response` is acceptable for this question's kind and
+    /// constraints. `Skipped` and `TimedOut` are always valid.
+    pub fn validate(&self, response: &QuestionResponse) -> Result<(), String> {
+        let choice = |i: usize| {
+            if i < self.choices.len() {
+                Ok(())
+            } else {
+                Err(format!("choice {} does not exist", i + 1))
+            }
+        };
+        match (&self.kind, response) {
+            (_, QuestionResponse::Skipped | QuestionResponse::TimedOut) => Ok(()),
+            (QuestionKind::Single, QuestionResponse::Selected(i)) => choice(*i),
+            (QuestionKind::Single, QuestionResponse::SelectedEdited { index, edited_text }) => {
+                if !self.editable {
+                    return Err("choices are not editable".into());
+                }
+                if let Some(max) = self.max_edit_length
+                    && edited_text.chars().count() > max
+                {
+                    return Err(format!("text is longer than {max} characters"));
+                }
+                choice(*index)
+            }
+            (QuestionKind::Single, QuestionResponse::FreeText(_)) if self.choices.is_empty() => {
+                Ok(())
+            }
+            (QuestionKind::MultiSelect { min, max }, QuestionResponse::MultiSelected(indices)) => {
+                indices.iter().try_for_each(|&i| choice(i))?;
+                if indices.len() < *min {
+                    return Err(format!("select at least {min}"));
+                }
+                if let Some(max) = max
+                    && indices.len() > *max
+                {
+                    return Err(format!("select at most {max}"));
+                }
+                Ok(())
+            }
+            (QuestionKind::Number { min, max, integer }, QuestionResponse::Number(n)) => {
+                if !n.is_finite() {
+                    return Err("not a number".into());
+                }
+                if *integer && n.fract() != 0.0 {
+                    return Err("enter a whole number".into());
+                }
+                if let Some(min) = min
+                    && n < min
+                {
+                    return Err(format!("must be at least {min}"));
+                }
+                if let Some(max) = max
+                    && n > max
+                {
+                    return Err(format!("must be at most {max}"));
+                }
+                Ok(())
+            }
+            (
+                QuestionKind::FilePath {
+                    base_dir,
+                    must_exist,
+                },
+                QuestionResponse::Path(path),
+            ) => {
+                if path.trim().is_empty() {
+                    return Err("enter a path".into());
+                }
+                if *must_exist && !resolve_path(base_dir.as_deref(), path).exists() {
+                    return Err(format!("{path} does not exist"));
+                }
+                Ok(())
+            }
+            (kind, response) => Err(format!("{response:?} is not a valid answer for {kind:?}")),
+        }
+    }
+
+    /// Parse typed text into a response for text-entry kinds (`Number`,
+    /// `FilePath`, and `Single` without choices) and validate it.
+    pub fn parse_text(&self, text: &str) -> Result<QuestionResponse, String> {
+        let text = text.trim();
+        let response = match self.kind {
+            QuestionKind::Number { .. } => QuestionResponse::Number(
+                text.parse::<f64>()
+                    .map_err(|_| format!("'{text}' is not a number"))?,
+            ),
+            QuestionKind::FilePath { .. } => QuestionResponse::Path(text.to_string()),
+            _ => QuestionResponse::FreeText(text.to_string()),
+        };
+        self.validate(&response)?;
+        Ok(response)
+    }
+
+    /// Parse an answer typed outside a picker (a webhook reply, a chat
+    /// message): 1-based choice numbers for choice kinds — one for `Single`,
+    /// comma- or space-separated for `MultiSelect` — or anything
+    /// [`parse_text`](Self::parse_text) accepts for text-entry kinds.
+    pub fn parse_answer(&self, text: &str) -> Result<QuestionResponse, String> {
+        if self.is_text_entry() {
+            return self.parse_text(text);
+        }
+        let mut indices = text
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|part| !part.is_empty())
+            .map(|part| match part.parse::<usize>() {
+                Ok(n) if n > 0 => Ok(n - 1),
+                _ => Err(format!("'{part}' is not a choice number")),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let response = match self.kind {
+            QuestionKind::MultiSelect { .. } => {
+                indices.sort_unstable();
+                indices.dedup();
+                QuestionResponse::MultiSelected(indices)
+            }
+            _ => match indices[..] {
+                [index] => QuestionResponse::Selected(index),
+                _ => return Err("enter one choice number".into()),
+            },
+        };
+        self.validate(&response)?;
+        Ok(response)
+    }
+
+    /// Whether the answer is typed rather than picked from the choices.
+    pub fn is_text_entry(&self) -> bool {
+        match self.kind {
+            QuestionKind::Single => self.choices.is_empty(),
+            QuestionKind::MultiSelect { .. } => false,
+            QuestionKind::Number { .. } | QuestionKind::FilePath { .. } => true,
+        }
+    }
+
+    /// The response to use when the question times out: the configured
+    /// [`default`](Self::default) if any, otherwise `TimedOut`.
+    pub fn timeout_response(&self) -> QuestionResponse {
+        self.default.clone().unwrap_or(QuestionResponse::TimedOut)
+    }
+}
+
+/// Resolve a typed path against an optional base directory.
+pub fn resolve_path(base_dir: Option<&str>, path: &str) -> PathBuf {
+    match base_dir {
+        Some(base) if Path::new(path).is_relative() => Path::new(base).join(path),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Complete a partially typed path: returns the entries of its directory
+/// whose names start with the final component, sorted, with `/` appended to
+/// directories. Hidden entries are only offered when the prefix starts with
+/// a dot.
+pub fn complete_path(base_dir: Option<&str>, partial: &str) -> Vec<String> {
+    let (dir_part, prefix) = match partial.rfind('/') {
+        Some(i) => partial.split_at(i + 1),
+        None => ("", partial),
+    };
+    let dir = resolve_path(base_dir, if dir_part.is_empty() { "." } else { dir_part });
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut out: Vec<String> = entries
+        .flatten()
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.')) {
+                return None;
+            }
+            let slash = if e.path().is_dir() { "/" } else { "" };
+            Some(format!("{dir_part}{name}{slash}"))
+        })
+        .collect();
+    out.sort();
+    out
+}
+
 /// Tracks an in-flight question inside [`UiState`].
 #[derive(Clone)]
 pub struct ActiveQuestion {
+    /// Identifies this question to remote answer channels, so a late answer
+    /// can't land on whichever question is asked next.
+    pub id: u64,
     /// The question being asked.
     pub question: UserQuestion,
     /// When the question expires (if any).
@@ -66,9 +285,14 @@ pub struct ActiveQuestion {
 /// The UI frontend reads `UiState.active_question` and renders the choices.
 /// When the user responds (or the deadline passes), the frontend sets
 /// `active_question.response` and `active_question.done = true`.
-pub fn ask_question(state: &Arc<Mutex<UiState>>, question: UserQuestion, timeout_secs: u64) {
+///
+/// Returns the question's [`id`](ActiveQuestion::id).
+pub fn ask_question(state: &Arc<Mutex<UiState>>, question: UserQuestion, timeout_secs: u64) -> u64 {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
     if let Ok(mut s) = state.lock() {
         s.active_question = Some(ActiveQuestion {
+            id,
             question,
             deadline: Some(Instant::now() + Duration::from_secs(timeout_secs)),
             response: None,
@@ -76,6 +300,26 @@ pub fn ask_question(state: &Arc<Mutex<UiState>>, question: UserQuestion, timeout
         });
         s.phase = "Waiting for user response".to_string();
     }
+    id
+}
+
+/// Resolve the active question as timed out if its deadline passed more
+/// than `grace` ago. Returns whether it did.
+///
+/// Interactive frontends expire questions themselves; this covers answer
+/// channels that don't (remote answers through the web API or a webhook),
+/// so an unanswered question can't block the agent forever.
+pub fn expire_question(state: &Arc<Mutex<UiState>>, grace: Duration) -> bool {
+    if let Ok(mut s) = state.lock()
+        && let Some(ref mut aq) = s.active_question
+        && !aq.done
+        && aq.deadline.is_some_and(|d| Instant::now() >= d + grace)
+    {
+        aq.response = None;
+        aq.done = true;
+        return true;
+    }
+    false
 }
 
 /// Poll for the user's response. Returns `None` if still waiting.
@@ -5,16 +5,172 @@
 //! buffer that any frontend can drain at its own pace.  The buffer uses a
 //! **separate** mutex from `UiState`, so logging never blocks rendering and
 //! vice-versa.
+//!
+//! Which events are captured is decided by a [`LogFilter`]: a default level
+//! plus per-target overrides (`info,hyper=warn,cinch_rs::agent=debug`).
+//! The filter is shared with the [`LogBuffer`], so a frontend can change it
+//! at runtime; changes apply to lines logged afterwards.
+//!
+//! `UiState::logs` is a ring buffer, so early lines of a long run are gone
+//! by the time anyone looks. [`export_logs`] dumps what is still in memory
+//! to a timestamped file, and a [`LogFile`] attached with
+//! [`UiTracingLayer::with_log_file`] keeps every captured line on disk,
+//! rotating by size.
 
-use std::sync::{Arc, Mutex};
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
 
 use chrono::Local;
-use tracing::Subscriber;
+pub use tracing::level_filters::LevelFilter;
+use tracing::{Level, Subscriber};
 use tracing_subscriber::layer::Layer;
 use tracing_subscriber::registry::LookupSpan;
 
 use super::{LOG_TRIM_TO, LogLevel, LogLine, MAX_LOG_LINES};
 
+/// Dependency targets that log at debug level on every request. The
+/// default [`LogFilter`] only shows their warnings.
+pub const NOISY_TARGETS: &[&str] = &[
+    "h2",
+    "hyper",
+    "hyper_util",
+    "reqwest",
+    "rustls",
+    "tokio_tungstenite",
+    "tonic",
+    "tower",
+    "tungstenite",
+];
+
+#[derive(Debug)]
+struct Directives {
+    default: LevelFilter,
+    /// `(target prefix, level)`, most specific (longest) prefix first.
+    targets: Vec<(String, LevelFilter)>,
+}
+
+/// Which log events reach the UI: a default level and per-target overrides.
+///
+/// Cheap to clone; clones share the same directives, so a frontend can
+/// adjust the filter the tracing layer is using. A target directive applies
+/// to the target itself and its submodules (`cinch_rs::agent` covers
+/// `cinch_rs::agent::harness`); the most specific one wins.
+#[derive(Clone, Debug)]
+pub struct LogFilter(Arc<RwLock<Directives>>);
+
+impl LogFilter {
+    /// A filter showing events at `default` or more severe, for all targets.
+    pub fn new(default: LevelFilter) -> Self {
+        Self(Arc::new(RwLock::new(Directives {
+            default,
+            targets: Vec::new(),
+        })))
+    }
+
+    /// Parse `env_logger`-style directives: comma-separated `level` and
+    /// `target=level` entries, e.g. `info,hyper=warn,cinch_rs::agent=debug`.
+    /// Levels are `off`, `error`, `warn`, `info`, `debug`, `trace`. Without
+    /// a bare level the default is `info`.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let filter = Self::new(LevelFilter::INFO);
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            let parse_level = |level: &str| {
+                level
+                    .trim()
+                    .parse::<LevelFilter>()
+                    .map_err(|_| format!("invalid log level '{}' in '{directive}'", level.trim()))
+            };
+            match directive.split_once('=') {
+                Some((target, level)) => filter.set_target(target.trim(), parse_level(level)?),
+                None => filter.set_default(parse_level(directive)?),
+            }
+        }
+        Ok(filter)
+    }
+
+    /// Builder form of [`set_target`](Self::set_target).
+    pub fn with_target(self, target: &str, level: LevelFilter) -> Self {
+        self.set_target(target, level);
+        self
+    }
+
+    /// The level for targets without a directive.
+    pub fn default_level(&self) -> LevelFilter {
+        self.read().default
+    }
+
+    pub fn set_default(&self, level: LevelFilter) {
+        self.write().default = level;
+    }
+
+    /// Set the level for `target` and its submodules, replacing any
+    /// directive for the same target.
+    pub fn set_target(&self, target: &str, level: LevelFilter) {
+        let mut directives = self.write();
+        directives.targets.retain(|(t, _)| t != target);
+        directives.targets.push((target.to_string(), level));
+        directives
+            .targets
+            .sort_by_key(|(t, _)| std::cmp::Reverse(t.len()));
+    }
+
+    /// Remove the directive for `target`, if any.
+    pub fn clear_target(&self, target: &str) {
+        self.write().targets.retain(|(t, _)| t != target);
+    }
+
+    /// Whether an event from `target` at `level` passes the filter.
+    pub fn enabled(&self, target: &str, level: &Level) -> bool {
+        let directives = self.read();
+        let filter = directives
+            .targets
+            .iter()
+            .find(|(prefix, _)| {
+                target
+                    .strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .map_or(directives.default, |(_, level)| *level);
+        *level <= filter
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Directives> {
+        self.0.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, Directives> {
+        self.0.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for LogFilter {
+    /// `info`, with [`NOISY_TARGETS`] at `warn`.
+    fn default() -> Self {
+        NOISY_TARGETS
+            .iter()
+            .fold(Self::new(LevelFilter::INFO), |f, t| {
+                f.with_target(t, LevelFilter::WARN)
+            })
+    }
+}
+
+impl fmt::Display for LogFilter {
+    /// The directives in [`parse`](Self::parse) syntax.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let directives = self.read();
+        write!(f, "{}", directives.default.to_string().to_lowercase())?;
+        let mut targets: Vec<_> = directives.targets.iter().collect();
+        targets.sort();
+        for (target, level) in targets {
+            write!(f, ",{target}={}", level.to_string().to_lowercase())?;
+        }
+        Ok(())
+    }
+}
+
 /// A shared buffer of pending log lines.
 ///
 /// The tracing layer pushes into this buffer; the UI frontend drains it once
@@ -22,11 +178,23 @@ use super::{LOG_TRIM_TO, LogLevel, LogLine, MAX_LOG_LINES};
 /// buffer has its own mutex, `on_event` never contends with the render
 /// thread's `UiState` lock.
 #[derive(Clone)]
-pub struct LogBuffer(Arc<Mutex<Vec<LogLine>>>);
+pub struct LogBuffer {
+    lines: Arc<Mutex<Vec<LogLine>>>,
+    filter: LogFilter,
+}
 
 impl LogBuffer {
-    fn new() -> Self {
-        Self(Arc::new(Mutex::new(Vec::with_capacity(128))))
+    fn new(filter: LogFilter) -> Self {
+        Self {
+            lines: Arc::new(Mutex::new(Vec::with_capacity(128))),
+            filter,
+        }
+    }
+
+    /// The filter deciding which events the layer captures. Adjust it to
+    /// change what the log pane shows from now on.
+    pub fn filter(&self) -> &LogFilter {
+        &self.filter
     }
 
     /// Drain all pending log lines from the buffer, returning them.
@@ -34,7 +202,7 @@ impl LogBuffer {
     /// Call this from the UI frontend once per frame to merge logs into
     /// `UiState::logs`.
     pub fn drain(&self) -> Vec<LogLine> {
-        let mut buf = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let mut buf = self.lines.lock().unwrap_or_else(|e| e.into_inner());
         std::mem::take(&mut *buf)
     }
 
@@ -49,10 +217,126 @@ impl LogBuffer {
         }
         if let Ok(mut s) = state.lock() {
             s.logs.extend(lines);
-            if s.logs.len() > MAX_LOG_LINES {
-                let trim_to = s.logs.len() - LOG_TRIM_TO;
-                s.logs.drain(..trim_to);
+            s.trim_logs();
+        }
+    }
+}
+
+/// `HH:MM:SS LEVEL message`, as shown in the log pane.
+fn format_line(line: &LogLine) -> String {
+    format!("{} {} {}", line.time, line.level.label(), line.message)
+}
+
+/// Write the log lines still held in `state` to
+/// `<dir>/logs-<YYYYMMDD-HHMMSS>.log` and return its path.
+pub fn export_logs(state: &Arc<Mutex<super::UiState>>, dir: &Path) -> io::Result<PathBuf> {
+    let (lines, discarded) = {
+        let s = state.lock().unwrap_or_else(|e| e.into_inner());
+        (s.logs.clone(), s.log_offset)
+    };
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!("logs-{}.log", Local::now().format("%Y%m%d-%H%M%S")));
+    let mut out = io::BufWriter::new(File::create(&path)?);
+    if discarded > 0 {
+        writeln!(out, "# {discarded} earlier line(s) were discarded")?;
+    }
+    for line in &lines {
+        writeln!(out, "{}", format_line(line))?;
+    }
+    out.flush()?;
+    Ok(path)
+}
+
+/// An append-only log file that rotates by size.
+///
+/// When a write would grow the file past `max_bytes`, `app.log` is renamed
+/// to `app.log.1` (shifting older files up to `app.log.<keep>`, the oldest
+/// dropped) and a fresh `app.log` is started. Lines carry the full date.
+pub struct LogFile {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    /// Open file and its current size.
+    file: Mutex<(File, u64)>,
+}
+
+impl LogFile {
+    /// Default size at which the file rotates: 10 MiB.
+    pub const DEFAULT_MAX_BYTES: u64 = 10 * 1024 * 1024;
+    /// Default number of rotated files kept.
+    pub const DEFAULT_KEEP: usize = 3;
+
+    /// Open (or create) `path` for appending, creating parent directories.
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path,
+            max_bytes: Self::DEFAULT_MAX_BYTES,
+            keep: Self::DEFAULT_KEEP,
+            file: Mutex::new((file, size)),
+        })
+    }
+
+    /// Rotate at `max_bytes`, keeping `keep` old files (0 keeps none).
+    pub fn with_rotation(mut self, max_bytes: u64, keep: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self.keep = keep;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// `app.log.<n>`.
+    fn rotated(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{n}"));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&self, file: &mut (File, u64)) -> io::Result<()> {
+        if self.keep == 0 {
+            std::fs::remove_file(&self.path)?;
+        } else {
+            for n in (1..self.keep).rev() {
+                let from = self.rotated(n);
+                if from.exists() {
+                    std::fs::rename(&from, self.rotated(n + 1))?;
+                }
             }
+            std::fs::rename(&self.path, self.rotated(1))?;
+        }
+        *file = (
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?,
+            0,
+        );
+        Ok(())
+    }
+
+    /// Append one line, rotating first if it would not fit. Errors are
+    /// swallowed: logging must never take the agent down.
+    pub fn write(&self, line: &LogLine) {
+        let text = format!(
+            "{} {} {}\n",
+            Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+            line.level.label(),
+            line.message
+        );
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if file.1 > 0 && file.1 + text.len() as u64 > self.max_bytes {
+            let _ = self.rotate(&mut file);
+        }
+        if file.0.write_all(text.as_bytes()).is_ok() {
+            file.1 += text.len() as u64;
         }
     }
 }
@@ -61,22 +345,37 @@ impl LogBuffer {
 /// a [`LogBuffer`] so they can be rendered by any UI frontend.
 pub struct UiTracingLayer {
     buffer: LogBuffer,
+    file: Option<LogFile>,
 }
 
 impl UiTracingLayer {
-    /// Create a new tracing layer and its associated [`LogBuffer`].
+    /// Create a new tracing layer and its associated [`LogBuffer`], using
+    /// the default [`LogFilter`].
     ///
     /// Pass the `LogBuffer` to your UI frontend so it can drain pending
     /// log lines each frame.
     pub fn new() -> (Self, LogBuffer) {
-        let buffer = LogBuffer::new();
+        Self::with_filter(LogFilter::default())
+    }
+
+    /// Like [`new`](Self::new), capturing only events that pass `filter`.
+    pub fn with_filter(filter: LogFilter) -> (Self, LogBuffer) {
+        let buffer = LogBuffer::new(filter);
         (
             Self {
                 buffer: buffer.clone(),
+                file: None,
             },
             buffer,
         )
     }
+
+    /// Also append every captured line to `file`, which survives the
+    /// in-memory ring buffer's trimming.
+    pub fn with_log_file(mut self, file: LogFile) -> Self {
+        self.file = Some(file);
+        self
+    }
 }
 
 impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for UiTracingLayer {
@@ -85,10 +384,19 @@ impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for UiTracingLayer {
         event: &tracing::Event<'_>,
         _ctx: tracing_subscriber::layer::Context<'_, S>,
     ) {
+        let metadata = event.metadata();
+        if !self
+            .buffer
+            .filter
+            .enabled(metadata.target(), metadata.level())
+        {
+            return;
+        }
+
         let mut visitor = MessageVisitor::default();
         event.record(&mut visitor);
 
-        let level = match *event.metadata().level() {
+        let level = match *metadata.level() {
             tracing::Level::TRACE => LogLevel::Trace,
             tracing::Level::DEBUG => LogLevel::Debug,
             tracing::Level::INFO => LogLevel::Info,
@@ -116,9 +424,13 @@ impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for UiTracingLayer {
             message,
         };
 
+        if let Some(ref file) = self.file {
+            file.write(&line);
+        }
+
         // Lock only the log buffer — never the UiState — so log calls
         // from tokio workers can never block on the TUI render thread.
-        if let Ok(mut buf) = self.buffer.0.lock() {
+        if let Ok(mut buf) = self.buffer.lines.lock() {
             buf.push(line);
             // Cap the buffer so a burst of logs before the next drain
             // doesn't consume unbounded memory.
@@ -165,3 +477,102 @@ impl tracing::field::Visit for MessageVisitor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn most_specific_target_wins() {
+        let filter = LogFilter::parse("warn, cinch_rs=info, cinch_rs::agent=debug").unwrap();
+        assert!(filter.enabled("cinch_rs::agent::harness", &Level::DEBUG));
+        assert!(!filter.enabled("cinch_rs::agent", &Level::TRACE));
+        assert!(filter.enabled("cinch_rs::tools", &Level::INFO));
+        assert!(!filter.enabled("cinch_rs::tools", &Level::DEBUG));
+        // Prefixes match whole path segments only.
+        assert!(!filter.enabled("cinch_rs_extra", &Level::INFO));
+        assert!(filter.enabled("other", &Level::WARN));
+        assert_eq!(
+            filter.to_string(),
+            "warn,cinch_rs=info,cinch_rs::agent=debug"
+        );
+
+        assert!(LogFilter::parse("hyper=loud").is_err());
+    }
+
+    #[test]
+    fn default_filter_quiets_dependencies() {
+        let filter = LogFilter::default();
+        assert!(filter.enabled("cinch_rs::agent", &Level::INFO));
+        assert!(!filter.enabled("cinch_rs::agent", &Level::DEBUG));
+        assert!(!filter.enabled("hyper::proto::h1", &Level::INFO));
+        assert!(filter.enabled("reqwest::connect", &Level::WARN));
+
+        // Clones share directives, so a UI can adjust the live filter.
+        let (_layer, buffer) = UiTracingLayer::with_filter(filter.clone());
+        buffer.filter().set_default(LevelFilter::DEBUG);
+        buffer.filter().clear_target("hyper");
+        assert!(filter.enabled("cinch_rs::agent", &Level::DEBUG));
+        assert!(filter.enabled("hyper::proto::h1", &Level::DEBUG));
+    }
+
+    #[test]
+    fn layer_captures_only_enabled_events() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let filter = LogFilter::parse("info,noisy=off").unwrap();
+        let (layer, buffer) = UiTracingLayer::with_filter(filter);
+        let subscriber = tracing_subscriber::registry().with(layer);
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(target: "noisy", "hidden");
+            tracing::debug!("hidden too");
+            tracing::warn!(attempt = 2, "shown");
+        });
+        let lines = buffer.drain();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].message, "shown {attempt=2}");
+    }
+
+    fn line(message: &str) -> LogLine {
+        LogLine {
+            time: "12:00:00".into(),
+            level: LogLevel::Info,
+            message: message.into(),
+        }
+    }
+
+    #[test]
+    fn export_writes_the_in_memory_logs() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = Arc::new(Mutex::new(super::super::UiState::default()));
+        {
+            let mut s = state.lock().unwrap();
+            s.logs.push(line("first"));
+            s.log_offset = 5;
+        }
+        let path = export_logs(&state, &dir.path().join("logs")).unwrap();
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        assert!(name.starts_with("logs-") && name.ends_with(".log"));
+        assert_eq!(
+            std::fs::read_to_string(path).unwrap(),
+            "# 5 earlier line(s) were discarded\n12:00:00 INFO  first\n"
+        );
+    }
+
+    #[test]
+    fn log_file_rotates_by_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("agent.log");
+        let file = LogFile::open(&path).unwrap().with_rotation(60, 2);
+        for n in 0..4 {
+            file.write(&line(&format!("line {n} {}", "x".repeat(20))));
+        }
+        let read = |p: &Path| std::fs::read_to_string(p).unwrap();
+        assert!(read(&path).contains("line 3"));
+        assert!(read(&file.rotated(1)).contains("line 2"));
+        assert!(read(&file.rotated(2)).contains("line 1"));
+        // Only `keep` rotated files survive.
+        assert!(!file.rotated(3).exists());
+        assert!(read(&path).ends_with(&format!("INFO  line 3 {}\n", "x".repeat(20))));
+    }
+}
@@ -0,0 +1,161 @@
+//! Publishing pending questions to a webhook for remote answers.
+//!
+//! A headless agent has no TUI to answer its [`UserQuestion`]s. With a
+//! [`QuestionWebhook`] set on the
+//! [`AskUserTool`](super::ask_user_tool::AskUserTool), every question is
+//! POSTed as JSON to a URL (a chat integration, a paging service) so an
+//! on-call human can see it, and answered through cinch-web's
+//! `POST /api/answer` — e.g. `{"question_id": 3, "text": "2"}` to pick the
+//! second choice. The `question_id` must match the question still waiting,
+//! so a late answer can't resolve a different question.
+//!
+//! The payload:
+//!
+//! ```json
+//! {
+//!   "event": "question",
+//!   "question_id": 3,
+//!   "text": "Deploy now?\n1. Yes\n2. No\n\nReply with a choice number.",
+//!   "question": { "prompt": "Deploy now?", "choices": [...], ... },
+//!   "timeout_secs": 120,
+//!   "answer_url": "https://agent.example.com/api/answer"
+//! }
+//! ```
+//!
+//! `text` is a plain rendering of the question, so services that display a
+//! `text` field (Slack incoming webhooks, for one) show something readable
+//! without a custom formatter.
+
+use std::time::Duration;
+
+use serde_json::json;
+
+use super::{QuestionKind, UserQuestion};
+
+/// Where to publish pending questions.
+#[derive(Debug, Clone)]
+pub struct QuestionWebhook {
+    url: String,
+    answer_url: Option<String>,
+    headers: Vec<(String, String)>,
+    timeout: Duration,
+}
+
+impl QuestionWebhook {
+    /// Publish questions to `url`.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            answer_url: None,
+            headers: Vec::new(),
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// URL answers are POSTed to (cinch-web's `/api/answer`), included in
+    /// the payload.
+    pub fn answer_url(mut self, url: impl Into<String>) -> Self {
+        self.answer_url = Some(url.into());
+        self
+    }
+
+    /// Add a request header (e.g. `Authorization`).
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// The JSON body published for `question`, identified by `question_id`
+    /// (see [`ActiveQuestion::id`](super::ActiveQuestion::id)).
+    pub fn payload(
+        &self,
+        question: &UserQuestion,
+        question_id: u64,
+        timeout_secs: u64,
+    ) -> serde_json::Value {
+        json!({
+            "event": "question",
+            "question_id": question_id,
+            "text": render_text(question),
+            "question": question,
+            "timeout_secs": timeout_secs,
+            "answer_url": self.answer_url,
+        })
+    }
+
+    /// POST `question` to the webhook.
+    pub async fn publish(
+        &self,
+        question: &UserQuestion,
+        question_id: u64,
+        timeout_secs: u64,
+    ) -> Result<(), String> {
+        let client = reqwest::Client::builder()
+            .timeout(self.timeout)
+            .build()
+            .map_err(|e| e.to_string())?;
+        let mut request =
+            client
+                .post(&self.url)
+                .json(&self.payload(question, question_id, timeout_secs));
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        let response = request.send().await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("webhook returned {}", response.status()));
+        }
+        Ok(())
+    }
+}
+
+/// Plain-text rendering of a question with a hint on how to answer it.
+fn render_text(question: &UserQuestion) -> String {
+    let mut text = question.prompt.clone();
+    for (i, choice) in question.choices.iter().enumerate() {
+        text.push_str(&format!("\n{}. {}", i + 1, choice.body));
+    }
+    let hint = match &question.kind {
+        QuestionKind::Single if question.choices.is_empty() => "Reply with your answer.",
+        QuestionKind::Single => "Reply with a choice number.",
+        QuestionKind::MultiSelect { .. } => "Reply with choice numbers, separated by commas.",
+        QuestionKind::Number { .. } => "Reply with a number.",
+        QuestionKind::FilePath { .. } => "Reply with a file path.",
+    };
+    text.push_str("\n\n");
+    text.push_str(hint);
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::QuestionChoice;
+
+    #[test]
+    fn payload_renders_the_question_as_text() {
+        let choice = |body: &str| QuestionChoice {
+            label: body.into(),
+            body: body.into(),
+            metadata: String::new(),
+        };
+        let question = UserQuestion {
+            prompt: "Deploy now?".into(),
+            choices: vec![choice("Yes"), choice("No")],
+            ..Default::default()
+        };
+        let webhook = QuestionWebhook::new("http://hooks.invalid/q")
+            .answer_url("http://agent.invalid/api/answer");
+        let payload = webhook.payload(&question, 3, 120);
+
+        assert_eq!(payload["event"], "question");
+        assert_eq!(payload["question_id"], 3);
+        assert_eq!(
+            payload["text"],
+            "Deploy now?\n1. Yes\n2. No\n\nReply with a choice number."
+        );
+        assert_eq!(payload["question"]["prompt"], "Deploy now?");
+        assert_eq!(payload["timeout_secs"], 120);
+        assert_eq!(payload["answer_url"], "http://agent.invalid/api/answer");
+    }
+}
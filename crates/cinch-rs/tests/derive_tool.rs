@@ -0,0 +1,99 @@
+//! `#[derive(CinchTool)]` tools: generated definitions, argument parsing,
+//! and flags.
+#![cfg(feature = "macros")]
+
+use cinch_rs::CinchTool;
+use cinch_rs::tools::core::Tool;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// Arguments for `CountWords`.
+#[derive(Deserialize, JsonSchema)]
+struct CountArgs {
+    /// Text to count words in.
+    text: String,
+}
+
+/// Count the words in a text.
+///
+/// Later paragraphs are not part of the purpose.
+#[derive(CinchTool)]
+#[tool(
+    args = CountArgs,
+    when_to_use = "When the user asks how long a text is",
+    when_not_to_use = "When counting characters — use char_count instead",
+    example("count_words(text='a b')", "2 words"),
+    call_example(r#"{"text": "a b"}"#, "2 words"),
+    cacheable
+)]
+struct CountWords {
+    suffix: &'static str,
+}
+
+impl CountWords {
+    async fn call(&self, args: CountArgs) -> String {
+        format!("{} {}", args.text.split_whitespace().count(), self.suffix)
+    }
+}
+
+/// Save a note.
+#[derive(CinchTool)]
+#[tool(
+    name = "save_note",
+    args = CountArgs,
+    method = save,
+    when_to_use = "When the user asks to write something down",
+    when_not_to_use = "When the user asks a question",
+    mutation
+)]
+struct Notes;
+
+impl Notes {
+    async fn save(&self, args: CountArgs) -> Result<String, String> {
+        if args.text.is_empty() {
+            return Err("note is empty".into());
+        }
+        Ok("saved".into())
+    }
+}
+
+#[test]
+fn definition_comes_from_the_doc_comment_and_attributes() {
+    let spec = CountWords::tool_spec();
+    assert_eq!(spec.name, "count_words");
+    assert_eq!(spec.purpose, "Count the words in a text");
+    assert_eq!(spec.examples.len(), 1);
+    assert_eq!(spec.parameters["required"], serde_json::json!(["text"]));
+
+    let tool = CountWords { suffix: "words" };
+    let def = tool.definition();
+    assert_eq!(def.function.name, "count_words");
+    assert!(
+        def.function
+            .description
+            .starts_with("Count the words in a text.\nWhen to use:")
+    );
+    assert_eq!(tool.call_examples()[0].arguments["text"], "a b");
+    assert!(tool.cacheable());
+    assert!(!tool.is_mutation());
+}
+
+#[tokio::test]
+async fn execute_parses_arguments_and_calls_the_method() {
+    let tool = CountWords { suffix: "words" };
+    assert_eq!(tool.execute(r#"{"text":"one two three"}"#).await, "3 words");
+    assert!(
+        tool.execute(r#"{"txt":"x"}"#)
+            .await
+            .starts_with("Error: invalid tool arguments")
+    );
+}
+
+#[tokio::test]
+async fn result_methods_report_errors() {
+    let tool = Notes;
+    assert_eq!(tool.name(), "save_note");
+    assert!(tool.is_mutation());
+    assert_eq!(tool.execute(r#"{"text":"hi"}"#).await, "saved");
+    assert_eq!(tool.execute(r#"{"text":""}"#).await, "Error: note is empty");
+}
@@ -0,0 +1,117 @@
+//! The OS-facing tools (`shell`, `grep`, `find_files`) against a real
+//! directory tree. These run on every CI platform, including Windows, so
+//! they only use commands that all host shells understand.
+
+use cinch_rs::tools::common::{FindFiles, Grep, Shell};
+use cinch_rs::tools::core::Tool;
+use cinch_rs::tools::host::ShellKind;
+
+fn tree() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("src").join("nested")).unwrap();
+    std::fs::create_dir_all(dir.path().join("docs")).unwrap();
+    std::fs::write(
+        dir.path().join("src").join("lib.rs"),
+        "fn main() {}\n// TODO: tidy\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("src").join("nested").join("util.rs"),
+        "pub fn util() {}\n",
+    )
+    .unwrap();
+    std::fs::write(dir.path().join("docs").join("guide.md"), "TODO later\n").unwrap();
+    dir
+}
+
+fn workdir(dir: &tempfile::TempDir) -> &str {
+    dir.path().to_str().unwrap()
+}
+
+#[tokio::test]
+async fn grep_reports_forward_slash_paths() {
+    let dir = tree();
+    let grep = Grep::new(workdir(&dir));
+
+    let files = grep.execute(r#"{"pattern": "TODO"}"#).await;
+    assert!(files.contains("src/lib.rs"), "{files}");
+    assert!(files.contains("docs/guide.md"), "{files}");
+
+    let content = grep
+        .execute(r#"{"pattern": "todo", "path": "src", "case_insensitive": true, "mode": "content", "glob": "*.rs"}"#)
+        .await;
+    assert!(content.contains("src/lib.rs:2:// TODO: tidy"), "{content}");
+    assert!(!content.contains('\\'), "{content}");
+
+    let none = grep.execute(r#"{"pattern": "no such text"}"#).await;
+    assert!(none.starts_with("[exit: 1]"), "{none}");
+}
+
+#[tokio::test]
+async fn find_files_matches_nested_globs() {
+    let dir = tree();
+    let find = FindFiles::new(workdir(&dir));
+
+    let result = find.execute(r#"{"pattern": "src/**/*.rs"}"#).await;
+    let mut paths: Vec<&str> = result.lines().collect();
+    paths.sort();
+    assert_eq!(paths, ["./src/lib.rs", "./src/nested/util.rs"]);
+
+    let scoped = find
+        .execute(r#"{"pattern": "*.rs", "path": "src/nested"}"#)
+        .await;
+    assert_eq!(scoped, "src/nested/util.rs");
+
+    let empty = find.execute(r#"{"pattern": "*.py"}"#).await;
+    assert_eq!(empty, "No files found matching '*.py'");
+}
+
+#[tokio::test]
+async fn shell_runs_in_the_host_shell() {
+    let dir = tree();
+    let shell = Shell::new(workdir(&dir));
+
+    let result = shell.execute(r#"{"command": "echo hello"}"#).await;
+    assert!(result.starts_with("[exit: 0]"), "{result}");
+    assert!(result.contains("hello"), "{result}");
+
+    let result = shell
+        .execute(r#"{"command": "echo created > marker.txt", "working_dir": "src"}"#)
+        .await;
+    assert!(result.starts_with("[exit: 0]"), "{result}");
+    assert!(dir.path().join("src").join("marker.txt").exists());
+
+    let escape = shell
+        .execute(r#"{"command": "echo hi", "working_dir": ".."}"#)
+        .await;
+    assert!(escape.contains("path traversal not allowed"), "{escape}");
+}
+
+#[cfg(windows)]
+#[tokio::test]
+async fn windows_defaults_to_powershell_with_cmd_available() {
+    let dir = tree();
+    assert_eq!(ShellKind::host(), ShellKind::PowerShell);
+
+    let ps = Shell::new(workdir(&dir));
+    let result = ps
+        .execute(r#"{"command": "Get-ChildItem -Name src"}"#)
+        .await;
+    assert!(result.contains("lib.rs"), "{result}");
+
+    let cmd = Shell::new(workdir(&dir)).shell_kind(ShellKind::Cmd);
+    let result = cmd.execute(r#"{"command": "dir /b src"}"#).await;
+    assert!(result.contains("lib.rs"), "{result}");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn unix_defaults_to_sh() {
+    let dir = tree();
+    assert_eq!(ShellKind::host(), ShellKind::Sh);
+
+    let result = Shell::new(workdir(&dir))
+        .execute(r#"{"command": "ls src | wc -l"}"#)
+        .await;
+    assert!(result.trim_end().ends_with('2'), "{result}");
+}
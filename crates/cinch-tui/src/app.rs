@@ -1,5 +1,11 @@
 //! TUI-local state (not shared with the agent).
 
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+use cinch_rs::ui::QuestionKind;
+use cinch_rs::ui::tracing::LogFilter;
+
 /// Input mode for the TUI.
 pub(crate) enum InputMode {
     /// Normal mode — arrow keys scroll, `q` quits.
@@ -41,6 +47,10 @@ pub(crate) struct App {
     pub(crate) question_cursor: usize,
     /// Scroll offset for the question choice list (top visible index).
     pub(crate) question_scroll: usize,
+    /// Answer type of the active question (set when it is entered).
+    pub(crate) question_kind: QuestionKind,
+    /// Choices toggled on in a multi-select question.
+    pub(crate) question_marked: BTreeSet<usize>,
     /// True when the agent is actively running (not waiting for input).
     /// Used to show the interrupt hint in the status bar.
     pub(crate) agent_busy: bool,
@@ -50,6 +60,11 @@ pub(crate) struct App {
     pub(crate) context_cursor: usize,
     /// Index of the expanded message (shown in full), or `None`.
     pub(crate) context_expanded: Option<usize>,
+    /// Filter of the tracing layer feeding the log pane, adjusted with
+    /// `+`/`-` while the pane is shown.
+    pub(crate) log_filter: Option<LogFilter>,
+    /// Where `s` in the log pane saves the logs.
+    pub(crate) log_dir: PathBuf,
 }
 
 impl App {
@@ -65,10 +80,14 @@ impl App {
             should_quit: false,
             question_cursor: 0,
             question_scroll: 0,
+            question_kind: QuestionKind::Single,
+            question_marked: BTreeSet::new(),
             agent_busy: false,
             context_scroll: 0,
             context_cursor: 0,
             context_expanded: None,
+            log_filter: None,
+            log_dir: std::env::temp_dir(),
         }
     }
 }
@@ -0,0 +1,343 @@
+//! Inline mode: streaming output on the normal screen.
+//!
+//! Selected with [`TuiMode::Inline`](crate::TuiMode::Inline). Instead of a
+//! full-screen dashboard, new output entries are printed as they arrive —
+//! user messages, agent text, colored tool call and result lines, file edit
+//! stats — with a spinner line showing the phase while the agent works.
+//! Questions are asked on the terminal and answered with a typed line
+//! (choice numbers or text, see
+//! [`UserQuestion::parse_answer`](cinch_rs::ui::UserQuestion::parse_answer)).
+//!
+//! When stdout is not a terminal (CI logs, pipes) the spinner and colors
+//! are left out, so the output stays plain and line-oriented.
+
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use cinch_rs::ui::{AgentEntry, UiState, UserQuestion};
+use crossterm::style::{Color, Stylize};
+
+use crate::TuiConfig;
+use crate::render::{result_preview, summarize_args, truncate_str};
+
+/// Braille spinner frames.
+const SPINNER: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// Maximum width of tool argument summaries and result previews.
+const PREVIEW_CHARS: usize = 100;
+
+/// Turns new [`UiState`] output into printable lines.
+pub(crate) struct InlineRenderer {
+    /// Sequence number of the next entry to print.
+    next: u64,
+    /// `UiState::output_edits` when last seen.
+    edits: u64,
+    color: bool,
+}
+
+impl InlineRenderer {
+    pub(crate) fn new(color: bool) -> Self {
+        Self {
+            next: 0,
+            edits: 0,
+            color,
+        }
+    }
+
+    fn paint(&self, text: &str, color: Color) -> String {
+        if self.color {
+            text.with(color).to_string()
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn bold(&self, text: &str) -> String {
+        if self.color {
+            text.bold().to_string()
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Lines for entries recorded since the last call. A todo list updated
+    /// in place after it was printed is printed again.
+    pub(crate) fn new_lines(&mut self, s: &UiState) -> Vec<String> {
+        let mut lines = Vec::new();
+        if s.output_edits != self.edits {
+            self.edits = s.output_edits;
+            let edited = s
+                .last_output_edit
+                .checked_sub(s.output_offset)
+                .and_then(|i| s.agent_output.get(i as usize));
+            if s.last_output_edit < self.next
+                && let Some(entry) = edited
+            {
+                lines.extend(self.entry_lines(entry));
+            }
+        }
+        let start = self.next.saturating_sub(s.output_offset) as usize;
+        for entry in s.agent_output.iter().skip(start) {
+            lines.extend(self.entry_lines(entry));
+        }
+        self.next = s.output_offset + s.agent_output.len() as u64;
+        lines
+    }
+
+    fn entry_lines(&self, entry: &AgentEntry) -> Vec<String> {
+        match entry {
+            AgentEntry::UserMessage(message) => {
+                let mut lines = vec![String::new()];
+                for (i, line) in message.lines().enumerate() {
+                    let prefix = if i == 0 { "> " } else { "  " };
+                    lines.push(self.bold(&format!("{prefix}{line}")));
+                }
+                lines
+            }
+            AgentEntry::Text(text) => {
+                let mut lines = vec![String::new()];
+                lines.extend(text.trim_end().lines().map(str::to_string));
+                lines
+            }
+            AgentEntry::ToolExecuting {
+                name,
+                arguments,
+                summary,
+            } => {
+                let summary = if summary.is_empty() {
+                    summarize_args(arguments, PREVIEW_CHARS)
+                } else {
+                    truncate_str(summary, PREVIEW_CHARS)
+                };
+                vec![format!(
+                    "{} {} {}",
+                    self.paint("●", Color::Cyan),
+                    self.bold(name),
+                    self.paint(&summary, Color::DarkGrey)
+                )]
+            }
+            AgentEntry::ToolResult {
+                result, is_error, ..
+            } => {
+                let preview = result_preview(result, PREVIEW_CHARS);
+                let line = if *is_error {
+                    // Keep a textual marker for uncolored output.
+                    let marker = if preview.to_ascii_lowercase().starts_with("error") {
+                        ""
+                    } else {
+                        "error: "
+                    };
+                    self.paint(&format!("  ⎿ {marker}{preview}"), Color::Red)
+                } else {
+                    self.paint(&format!("  ⎿ {preview}"), Color::DarkGrey)
+                };
+                vec![line]
+            }
+            AgentEntry::FileEdit { path, stats, .. } => vec![format!(
+                "  {} {} {}",
+                self.paint("±", Color::Yellow),
+                path,
+                self.paint(
+                    &format!("+{} -{}", stats.added, stats.removed),
+                    Color::DarkGrey
+                )
+            )],
+            AgentEntry::TodoUpdate(content) => {
+                let mut lines = vec![self.paint("Todo:", Color::Magenta)];
+                lines.extend(content.trim_end().lines().map(|l| format!("  {l}")));
+                lines
+            }
+        }
+    }
+
+    /// The question, its numbered choices, and what to type.
+    pub(crate) fn question_lines(&self, question: &UserQuestion) -> Vec<String> {
+        let mut lines = vec![String::new(), self.paint(&question.prompt, Color::Yellow)];
+        for (i, choice) in question.choices.iter().enumerate() {
+            let mut line = format!("  {}. {}", i + 1, choice.label);
+            if !choice.metadata.is_empty() {
+                line.push_str(&self.paint(&format!("  {}", choice.metadata), Color::DarkGrey));
+            }
+            lines.push(line);
+        }
+        if !question.is_text_entry() {
+            lines.push(self.paint("Enter a choice number:", Color::DarkGrey));
+        }
+        lines
+    }
+}
+
+/// `⠋ phase · round 3/30`.
+fn status_line(s: &UiState, tick: usize) -> String {
+    let mut line = format!("{} {}", SPINNER[tick % SPINNER.len()], s.phase);
+    if s.round > 0 {
+        line.push_str(&format!(" · round {}/{}", s.round, s.max_rounds));
+    }
+    line
+}
+
+/// Run the inline loop until the agent finishes (blocking).
+pub(crate) fn run_inline(state: Arc<Mutex<UiState>>, config: &TuiConfig) -> io::Result<()> {
+    let tty = io::stdout().is_terminal();
+    let mut renderer = InlineRenderer::new(tty);
+    let mut out = io::stdout();
+    let mut tick = 0;
+
+    loop {
+        let pending_logs = config
+            .log_buffer
+            .as_ref()
+            .map(|buf| buf.drain())
+            .unwrap_or_default();
+
+        let (lines, question, finished, status) = {
+            let mut s = state.lock().unwrap();
+            if !pending_logs.is_empty() {
+                s.logs.extend(pending_logs);
+                s.trim_logs();
+            }
+            let question = s
+                .active_question
+                .as_ref()
+                .filter(|aq| !aq.done)
+                .map(|aq| aq.question.clone());
+            let finished = (!s.running && question.is_none()) || s.quit_requested;
+            (
+                renderer.new_lines(&s),
+                question,
+                finished,
+                status_line(&s, tick),
+            )
+        };
+
+        if tty {
+            // Clear the spinner line before printing over it.
+            write!(out, "\r\x1b[2K")?;
+        }
+        for line in &lines {
+            writeln!(out, "{line}")?;
+        }
+
+        if let Some(question) = question {
+            for line in renderer.question_lines(&question) {
+                writeln!(out, "{line}")?;
+            }
+            let response = ask(&question, &mut out)?;
+            let mut s = state.lock().unwrap();
+            if let Some(ref mut aq) = s.active_question
+                && !aq.done
+            {
+                aq.response = response;
+                aq.done = true;
+            }
+            continue;
+        }
+
+        if finished {
+            break;
+        }
+        if tty {
+            write!(out, "{}", status.dark_grey())?;
+        }
+        out.flush()?;
+        tick += 1;
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    out.flush()
+}
+
+/// Read answers from stdin until one parses. `None` (treated as a timeout)
+/// when stdin is closed.
+fn ask(
+    question: &UserQuestion,
+    out: &mut impl Write,
+) -> io::Result<Option<cinch_rs::ui::QuestionResponse>> {
+    let stdin = io::stdin();
+    loop {
+        write!(out, "> ")?;
+        out.flush()?;
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            writeln!(out)?;
+            return Ok(None);
+        }
+        match question.parse_answer(line.trim()) {
+            Ok(response) => return Ok(Some(response)),
+            Err(e) => writeln!(out, "Invalid answer: {e}.")?,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cinch_rs::ui::{
+        QuestionChoice, push_agent_text, push_tool_executing, push_tool_result, push_user_message,
+    };
+
+    #[test]
+    fn prints_each_entry_once() {
+        let state = Arc::new(Mutex::new(UiState::default()));
+        let mut renderer = InlineRenderer::new(false);
+        push_user_message(&state, "fix the build");
+        push_tool_executing(
+            &state,
+            "shell",
+            r#"{"command":"cargo build"}"#,
+            "cargo build",
+        );
+        push_tool_result(&state, "shell", "Error: exit 101\nerror[E0425]");
+        let lines = renderer.new_lines(&state.lock().unwrap());
+        assert_eq!(
+            lines,
+            vec![
+                "",
+                "> fix the build",
+                "● shell cargo build",
+                "  ⎿ Error: exit 101",
+            ]
+        );
+
+        assert!(renderer.new_lines(&state.lock().unwrap()).is_empty());
+        push_agent_text(&state, "Fixed.\n");
+        assert_eq!(
+            renderer.new_lines(&state.lock().unwrap()),
+            vec!["", "Fixed."]
+        );
+    }
+
+    #[test]
+    fn question_lists_numbered_choices() {
+        let renderer = InlineRenderer::new(false);
+        let question = UserQuestion {
+            prompt: "Deploy?".into(),
+            choices: vec![
+                QuestionChoice {
+                    label: "Yes".into(),
+                    body: String::new(),
+                    metadata: "prod".into(),
+                },
+                QuestionChoice {
+                    label: "No".into(),
+                    body: String::new(),
+                    metadata: String::new(),
+                },
+            ],
+            editable: false,
+            max_edit_length: None,
+            kind: Default::default(),
+            default: None,
+        };
+        assert_eq!(
+            renderer.question_lines(&question),
+            vec![
+                "",
+                "Deploy?",
+                "  1. Yes  prod",
+                "  2. No",
+                "Enter a choice number:"
+            ]
+        );
+    }
+}
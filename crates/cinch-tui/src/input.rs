@@ -2,7 +2,8 @@
 
 use std::sync::{Arc, Mutex};
 
-use cinch_rs::ui::{QuestionResponse, UiState};
+use cinch_rs::ui::tracing::{LevelFilter, export_logs};
+use cinch_rs::ui::{QuestionKind, QuestionResponse, UiState, complete_path};
 use crossterm::event::{KeyCode, KeyModifiers};
 
 use crate::app::{ActivePane, App, InputMode};
@@ -41,6 +42,14 @@ fn handle_normal_key(key: crossterm::event::KeyEvent, app: &mut App, state: &Arc
                 app.active_pane = ActivePane::AgentOutput;
             }
         }
+        KeyCode::Char('+' | '=') if app.show_logs => step_log_level(app, true),
+        KeyCode::Char('-') if app.show_logs => step_log_level(app, false),
+        KeyCode::Char('s') if app.show_logs => {
+            app.status_message = Some(match export_logs(state, &app.log_dir) {
+                Ok(path) => format!("Logs saved to {}", path.display()),
+                Err(e) => format!("Saving logs failed: {e}"),
+            });
+        }
         KeyCode::Char('c') => {
             app.input_mode = InputMode::ContextView;
             app.context_scroll = 0;
@@ -78,6 +87,36 @@ fn handle_normal_key(key: crossterm::event::KeyEvent, app: &mut App, state: &Arc
     }
 }
 
+/// Show more (`verbose`) or fewer log lines from now on by moving the log
+/// filter's default level. Per-target directives are left alone.
+fn step_log_level(app: &mut App, verbose: bool) {
+    const LEVELS: [LevelFilter; 6] = [
+        LevelFilter::OFF,
+        LevelFilter::ERROR,
+        LevelFilter::WARN,
+        LevelFilter::INFO,
+        LevelFilter::DEBUG,
+        LevelFilter::TRACE,
+    ];
+    let Some(ref filter) = app.log_filter else {
+        return;
+    };
+    let current = LEVELS
+        .iter()
+        .position(|level| *level == filter.default_level())
+        .unwrap_or(3);
+    let next = if verbose {
+        (current + 1).min(LEVELS.len() - 1)
+    } else {
+        current.saturating_sub(1)
+    };
+    filter.set_default(LEVELS[next]);
+    app.status_message = Some(format!(
+        "Log level: {}",
+        LEVELS[next].to_string().to_lowercase()
+    ));
+}
+
 /// Returns a mutable reference to the scroll offset of the active pane.
 fn active_scroll_mut(app: &mut App) -> &mut usize {
     match app.active_pane {
@@ -115,6 +154,36 @@ fn handle_question_select_key(
                 app.question_cursor += 1;
             }
         }
+        KeyCode::Char(' ') if matches!(app.question_kind, QuestionKind::MultiSelect { .. }) => {
+            if !app.question_marked.remove(&app.question_cursor) {
+                app.question_marked.insert(app.question_cursor);
+            }
+            app.status_message = None;
+        }
+        KeyCode::Enter if matches!(app.question_kind, QuestionKind::MultiSelect { .. }) => {
+            // Submit all marked choices.
+            let indices: Vec<usize> = app.question_marked.iter().copied().collect();
+            let count = indices.len();
+            let response = QuestionResponse::MultiSelected(indices);
+            let result = {
+                let mut s = state.lock().unwrap();
+                match s.active_question {
+                    Some(ref mut aq) => aq.question.validate(&response).map(|()| {
+                        aq.response = Some(response);
+                        aq.done = true;
+                    }),
+                    None => Ok(()),
+                }
+            };
+            match result {
+                Ok(()) => {
+                    app.question_marked.clear();
+                    app.input_mode = InputMode::Normal;
+                    app.status_message = Some(format!("{count} choice(s) selected."));
+                }
+                Err(e) => app.status_message = Some(format!("Invalid selection: {e}.")),
+            }
+        }
         KeyCode::Enter => {
             // Select and confirm this choice.
             {
@@ -240,17 +309,31 @@ fn handle_free_text_key(
             if text.is_empty() {
                 return;
             }
-            {
+            // Number and path answers are parsed and validated here so the
+            // user can correct them before the question resolves.
+            let result = {
                 let mut s = state.lock().unwrap();
-                if let Some(ref mut aq) = s.active_question {
-                    aq.response = Some(QuestionResponse::FreeText(text));
-                    aq.done = true;
+                match s.active_question {
+                    Some(ref mut aq) => aq.question.parse_text(&text).map(|response| {
+                        aq.response = Some(response);
+                        aq.done = true;
+                    }),
+                    None => Ok(()),
                 }
+            };
+            if let Err(e) = result {
+                app.status_message = Some(format!("Invalid answer: {e}."));
+                return;
             }
             app.input_buffer.clear();
             app.input_mode = InputMode::Normal;
             app.status_message = None;
         }
+        KeyCode::Tab if matches!(app.question_kind, QuestionKind::FilePath { .. }) => {
+            if let QuestionKind::FilePath { base_dir, .. } = app.question_kind.clone() {
+                complete_input_path(app, base_dir.as_deref());
+            }
+        }
         KeyCode::Esc => {
             {
                 let mut s = state.lock().unwrap();
@@ -265,9 +348,11 @@ fn handle_free_text_key(
         }
         KeyCode::Backspace => {
             app.input_buffer.pop();
+            app.status_message = None;
         }
         KeyCode::Char(c) => {
             app.input_buffer.push(c);
+            app.status_message = None;
         }
         // Pass through navigation keys so the user can scroll and
         // switch panes while typing.
@@ -302,6 +387,34 @@ fn handle_free_text_key(
     }
 }
 
+/// Tab-complete the path in the input buffer. A unique match is filled in;
+/// several matches extend the buffer to their common prefix and are listed
+/// in the status line.
+fn complete_input_path(app: &mut App, base_dir: Option<&str>) {
+    let matches = complete_path(base_dir, &app.input_buffer);
+    match matches.as_slice() {
+        [] => app.status_message = Some("No matching paths.".into()),
+        [only] => {
+            app.input_buffer = only.clone();
+            app.status_message = None;
+        }
+        [first, rest @ ..] => {
+            let common = rest.iter().fold(first.as_str(), |acc, m| {
+                let len = acc
+                    .char_indices()
+                    .zip(m.chars())
+                    .find(|((_, a), b)| a != b)
+                    .map_or(acc.len().min(m.len()), |((i, _), _)| i);
+                acc.get(..len).unwrap_or(acc)
+            });
+            if common.len() > app.input_buffer.len() {
+                app.input_buffer = common.to_string();
+            }
+            app.status_message = Some(matches.join("  "));
+        }
+    }
+}
+
 fn handle_context_view_key(key: crossterm::event::KeyEvent, app: &mut App) {
     match key.code {
         KeyCode::Char('c') | KeyCode::Esc => {
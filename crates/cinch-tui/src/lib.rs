@@ -24,29 +24,46 @@ use std::sync::{Arc, Mutex};
 use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
 
-use cinch_rs::ui::UiState;
 use cinch_rs::ui::tracing::LogBuffer;
+use cinch_rs::ui::{QuestionKind, UiState};
 use crossterm::event::{self, Event};
-use crossterm::terminal::{
-    EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
-};
-use crossterm::{cursor, execute};
 use ratatui::prelude::*;
 
 mod app;
 pub mod ext;
+mod inline;
 mod input;
+pub mod notify;
 mod render;
+mod terminal;
 
 pub use ext::{NoTuiExtension, TuiExtensionRenderer};
+pub use notify::{Notification, NotificationConfig};
 pub use render::{format_countdown, log_level_style, result_preview, summarize_args, truncate_str};
+pub use terminal::restore_terminal;
 
 use app::{App, InputMode};
 use input::handle_key_event;
+use notify::Notifier;
 use render::render;
+use terminal::{TerminalGuard, panicked};
+
+/// How the TUI draws.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TuiMode {
+    /// Full-screen dashboard on the alternate screen.
+    #[default]
+    FullScreen,
+    /// Streaming output on the normal screen, with a spinner line and
+    /// colored tool lines (plain text when stdout is not a terminal).
+    /// Questions are answered by typing a line.
+    Inline,
+}
 
 /// Configuration for the TUI.
 pub struct TuiConfig {
+    /// Full-screen or inline rendering. Default: [`TuiMode::FullScreen`].
+    pub mode: TuiMode,
     /// Working directory displayed in the status bar.
     pub workdir: PathBuf,
     /// Optional domain-specific renderer.
@@ -57,15 +74,28 @@ pub struct TuiConfig {
     /// per frame and merges them into `UiState::logs`.  This keeps the
     /// tracing layer's `on_event` completely decoupled from the UiState
     /// lock, preventing log calls from blocking the render thread.
+    ///
+    /// The log pane's `+`/`-` keys adjust the buffer's
+    /// [`LogFilter`](cinch_rs::ui::tracing::LogFilter).
     pub log_buffer: Option<LogBuffer>,
+    /// Bell / desktop notifications when the run finishes, fails, or
+    /// waits on the user.
+    pub notifications: NotificationConfig,
+    /// Directory the log pane's `s` key saves the logs to (see
+    /// [`export_logs`](cinch_rs::ui::tracing::export_logs)). Default:
+    /// `<temp dir>/cinch-logs`.
+    pub log_dir: PathBuf,
 }
 
 impl Default for TuiConfig {
     fn default() -> Self {
         Self {
+            mode: TuiMode::default(),
             workdir: PathBuf::from("."),
             extension_renderer: Box::new(NoTuiExtension),
             log_buffer: None,
+            notifications: NotificationConfig::default(),
+            log_dir: std::env::temp_dir().join("cinch-logs"),
         }
     }
 }
@@ -84,17 +114,29 @@ pub fn spawn_tui(state: Arc<Mutex<UiState>>, config: TuiConfig) -> JoinHandle<()
 
 /// Run the TUI event loop (blocking). Call this from a dedicated OS thread.
 ///
-/// Returns when the user presses `q` or the agent finishes in `--once` mode.
+/// Returns when the user presses `q` or the agent finishes in `--once` mode
+/// (in [`TuiMode::Inline`], as soon as the agent finishes).
+/// The terminal is restored on every exit path, including errors and
+/// panics (see [`restore_terminal`]); after a panic on another thread the
+/// loop stops and returns an error.
 pub fn run_tui(state: Arc<Mutex<UiState>>, config: &TuiConfig) -> io::Result<()> {
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, cursor::Hide)?;
+    if config.mode == TuiMode::Inline {
+        return inline::run_inline(state, config);
+    }
+    let _guard = TerminalGuard::enter()?;
 
-    let backend = CrosstermBackend::new(stdout);
+    let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend)?;
     let mut app = App::new();
+    app.log_filter = config.log_buffer.as_ref().map(|buf| buf.filter().clone());
+    app.log_dir = config.log_dir.clone();
+    let mut notifier = Notifier::new(config.notifications.clone());
 
     loop {
+        if panicked() {
+            return Err(io::Error::other("stopped after a panic"));
+        }
+
         // Drain pending log lines from the tracing buffer *before*
         // acquiring the UiState lock.  `drain()` only touches the
         // LogBuffer's internal mutex, so it never contends with the
@@ -111,33 +153,28 @@ pub fn run_tui(state: Arc<Mutex<UiState>>, config: &TuiConfig) -> io::Result<()>
         // with the agent's async runtime.
         enum QuestionAction {
             None,
-            EnterSelect,
-            EnterFreeText,
+            EnterSelect(QuestionKind),
+            EnterFreeText(QuestionKind),
             TimedOut,
         }
 
-        let (running, quit, question_action, agent_busy) = {
+        let (running, quit, question_action, agent_busy, notifications) = {
             let mut s = state.lock().unwrap();
 
             // Merge drained log lines.
             if !pending_logs.is_empty() {
                 s.logs.extend(pending_logs);
                 // Respect the same trim limits as LogBuffer::flush_into.
-                if s.logs.len() > cinch_rs::ui::MAX_LOG_LINES {
-                    let trim_to = s.logs.len() - cinch_rs::ui::LOG_TRIM_TO;
-                    s.logs.drain(..trim_to);
-                }
+                s.trim_logs();
             }
 
             // Determine question action.
             let qa = match app.input_mode {
                 InputMode::Normal => match s.active_question.as_ref() {
-                    Some(aq) if !aq.done && !aq.question.choices.is_empty() => {
-                        QuestionAction::EnterSelect
-                    }
-                    Some(aq) if !aq.done && aq.question.choices.is_empty() => {
-                        QuestionAction::EnterFreeText
+                    Some(aq) if !aq.done && !aq.question.is_text_entry() => {
+                        QuestionAction::EnterSelect(aq.question.kind.clone())
                     }
+                    Some(aq) if !aq.done => QuestionAction::EnterFreeText(aq.question.kind.clone()),
                     _ => QuestionAction::None,
                 },
                 InputMode::QuestionSelect | InputMode::QuestionEdit | InputMode::FreeText => {
@@ -161,11 +198,14 @@ pub fn run_tui(state: Arc<Mutex<UiState>>, config: &TuiConfig) -> io::Result<()>
             // Agent is "busy" when running and not waiting for user input.
             let busy = s.running && s.active_question.as_ref().is_none_or(|aq| aq.done);
 
-            (s.running, s.quit_requested, qa, busy)
+            let notifications = notifier.observe(&s);
+
+            (s.running, s.quit_requested, qa, busy, notifications)
             // lock released here
         };
 
         app.agent_busy = agent_busy;
+        notifier.fire(&notifications);
 
         // ── Apply results (no lock held) ─────────────────────────
 
@@ -175,14 +215,17 @@ pub fn run_tui(state: Arc<Mutex<UiState>>, config: &TuiConfig) -> io::Result<()>
         }
 
         match question_action {
-            QuestionAction::EnterSelect => {
+            QuestionAction::EnterSelect(kind) => {
                 app.input_mode = InputMode::QuestionSelect;
                 app.question_cursor = 0;
                 app.question_scroll = 0;
+                app.question_kind = kind;
+                app.question_marked.clear();
                 app.status_message = None;
             }
-            QuestionAction::EnterFreeText => {
+            QuestionAction::EnterFreeText(kind) => {
                 app.input_mode = InputMode::FreeText;
+                app.question_kind = kind;
                 app.input_buffer.clear();
                 app.status_message = None;
             }
@@ -221,10 +264,7 @@ pub fn run_tui(state: Arc<Mutex<UiState>>, config: &TuiConfig) -> io::Result<()>
         }
     }
 
-    // Restore terminal.
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen, cursor::Show)?;
-    terminal.show_cursor()?;
+    // The guard restores the terminal.
     Ok(())
 }
 
@@ -247,5 +287,57 @@ mod tests {
         assert_eq!(app.agent_scroll, 0);
         assert_eq!(app.question_cursor, 0);
         assert_eq!(app.question_scroll, 0);
+        assert!(app.log_filter.is_none());
+    }
+
+    #[test]
+    fn log_pane_keys_adjust_the_log_filter() {
+        use cinch_rs::ui::tracing::{LevelFilter, LogFilter};
+        use crossterm::event::{KeyCode, KeyEvent};
+
+        let state = Arc::new(Mutex::new(UiState::default()));
+        let filter = LogFilter::default();
+        let mut app = App::new();
+        app.log_filter = Some(filter.clone());
+
+        // Ignored while the pane is hidden.
+        handle_key_event(KeyEvent::from(KeyCode::Char('+')), &mut app, &state);
+        assert_eq!(filter.default_level(), LevelFilter::INFO);
+
+        app.show_logs = true;
+        handle_key_event(KeyEvent::from(KeyCode::Char('+')), &mut app, &state);
+        assert_eq!(filter.default_level(), LevelFilter::DEBUG);
+        assert_eq!(app.status_message.as_deref(), Some("Log level: debug"));
+        for _ in 0..6 {
+            handle_key_event(KeyEvent::from(KeyCode::Char('-')), &mut app, &state);
+        }
+        assert_eq!(filter.default_level(), LevelFilter::OFF);
+    }
+
+    #[test]
+    fn restore_terminal_without_tui_is_a_noop() {
+        restore_terminal();
+        restore_terminal();
+        assert!(!panicked());
+    }
+
+    #[test]
+    fn log_pane_saves_logs() {
+        use crossterm::event::{KeyCode, KeyEvent};
+
+        let dir = tempfile::tempdir().unwrap();
+        let state = Arc::new(Mutex::new(UiState::default()));
+        let mut app = App::new();
+        app.log_dir = dir.path().to_path_buf();
+        app.show_logs = true;
+        handle_key_event(KeyEvent::from(KeyCode::Char('s')), &mut app, &state);
+
+        let saved = std::fs::read_dir(dir.path()).unwrap().count();
+        assert_eq!(saved, 1);
+        assert!(
+            app.status_message
+                .as_deref()
+                .is_some_and(|m| m.starts_with("Logs saved to "))
+        );
     }
 }
@@ -0,0 +1,196 @@
+//! Attention notifications for long-running sessions.
+//!
+//! Users often switch away from the terminal while the agent works and miss
+//! a question's timeout. [`Notifier`] watches [`UiState`] transitions once per
+//! frame and rings the terminal bell (and, with the `desktop-notify` feature,
+//! posts a desktop notification) when the run finishes, fails, or starts
+//! waiting on the user.
+
+use std::io::Write;
+
+use cinch_rs::ui::UiState;
+
+/// Which notification channels are enabled.
+#[derive(Clone, Debug)]
+pub struct NotificationConfig {
+    /// Emit a terminal bell (`BEL`). Default: `true`.
+    pub bell: bool,
+    /// Post a desktop notification. Only effective when cinch-tui is built
+    /// with the `desktop-notify` feature. Default: `false`.
+    pub desktop: bool,
+    /// Application name shown as the desktop notification title.
+    pub app_name: String,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            bell: true,
+            desktop: false,
+            app_name: "cinch".into(),
+        }
+    }
+}
+
+/// A state transition worth interrupting the user for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Notification {
+    /// The agent stopped running.
+    Finished,
+    /// The agent reported an error (phase starting with `"Error"`).
+    Error(String),
+    /// A question or approval is waiting for the user.
+    InputNeeded(String),
+}
+
+impl Notification {
+    /// One-line body text for the notification.
+    pub fn body(&self) -> String {
+        match self {
+            Self::Finished => "Agent finished.".into(),
+            Self::Error(phase) => phase.clone(),
+            Self::InputNeeded(prompt) => format!("Waiting for input: {prompt}"),
+        }
+    }
+}
+
+/// Edge-triggered detector for [`Notification`]s.
+///
+/// Each kind fires once per transition: a question notifies when it first
+/// appears, not on every frame while it stays open.
+pub(crate) struct Notifier {
+    config: NotificationConfig,
+    was_running: bool,
+    was_error: bool,
+    was_question: bool,
+}
+
+impl Notifier {
+    pub(crate) fn new(config: NotificationConfig) -> Self {
+        Self {
+            config,
+            was_running: true,
+            was_error: false,
+            was_question: false,
+        }
+    }
+
+    /// Compare `state` against the previous frame and return new notifications.
+    pub(crate) fn observe(&mut self, state: &UiState) -> Vec<Notification> {
+        let mut out = Vec::new();
+
+        let pending_prompt = state
+            .active_question
+            .as_ref()
+            .filter(|aq| !aq.done)
+            .map(|aq| aq.question.prompt.clone());
+        if let Some(ref prompt) = pending_prompt
+            && !self.was_question
+        {
+            out.push(Notification::InputNeeded(prompt.clone()));
+        }
+        self.was_question = pending_prompt.is_some();
+
+        let is_error = state.phase.starts_with("Error");
+        if is_error && !self.was_error {
+            out.push(Notification::Error(state.phase.clone()));
+        }
+        self.was_error = is_error;
+
+        if self.was_running && !state.running {
+            out.push(Notification::Finished);
+        }
+        self.was_running = state.running;
+
+        out
+    }
+
+    /// Deliver notifications through the enabled channels.
+    pub(crate) fn fire(&self, notifications: &[Notification]) {
+        if notifications.is_empty() {
+            return;
+        }
+        if self.config.bell {
+            let mut stdout = std::io::stdout();
+            let _ = stdout.write_all(b"\x07");
+            let _ = stdout.flush();
+        }
+        if self.config.desktop {
+            for n in notifications {
+                send_desktop(&self.config.app_name, &n.body());
+            }
+        }
+    }
+}
+
+#[cfg(feature = "desktop-notify")]
+fn send_desktop(title: &str, body: &str) {
+    // Failures (no notification daemon, headless CI) are non-fatal.
+    let _ = notify_rust::Notification::new()
+        .summary(title)
+        .body(body)
+        .show();
+}
+
+#[cfg(not(feature = "desktop-notify"))]
+fn send_desktop(_title: &str, _body: &str) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cinch_rs::ui::{ActiveQuestion, UserQuestion};
+
+    fn question(prompt: &str) -> ActiveQuestion {
+        ActiveQuestion {
+            id: 1,
+            question: UserQuestion {
+                prompt: prompt.into(),
+                choices: vec![],
+                editable: false,
+                max_edit_length: None,
+                ..Default::default()
+            },
+            deadline: None,
+            response: None,
+            done: false,
+        }
+    }
+
+    #[test]
+    fn question_notifies_once() {
+        let mut n = Notifier::new(NotificationConfig::default());
+        let mut state = UiState::default();
+        assert!(n.observe(&state).is_empty());
+
+        state.active_question = Some(question("Approve git_commit?"));
+        assert_eq!(
+            n.observe(&state),
+            vec![Notification::InputNeeded("Approve git_commit?".into())]
+        );
+        assert!(n.observe(&state).is_empty());
+
+        state.active_question = None;
+        assert!(n.observe(&state).is_empty());
+        state.active_question = Some(question("Next?"));
+        assert_eq!(n.observe(&state).len(), 1);
+    }
+
+    #[test]
+    fn finish_and_error_are_edge_triggered() {
+        let mut n = Notifier::new(NotificationConfig::default());
+        let mut state = UiState {
+            phase: "Error: API unreachable".into(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            n.observe(&state),
+            vec![Notification::Error("Error: API unreachable".into())]
+        );
+        assert!(n.observe(&state).is_empty());
+
+        state.running = false;
+        assert_eq!(n.observe(&state), vec![Notification::Finished]);
+        assert!(n.observe(&state).is_empty());
+    }
+}
@@ -3,7 +3,7 @@
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-use cinch_rs::ui::{AgentEntry, ContextSnapshot, LogLevel, UiState};
+use cinch_rs::ui::{AgentEntry, ContextSnapshot, LogLevel, QuestionKind, UiState};
 use ratatui::prelude::*;
 use ratatui::widgets::*;
 
@@ -98,6 +98,8 @@ struct RenderSnapshot {
     context_pct: f64,
     model: String,
     cycle: u32,
+    cost_usd: f64,
+    cost_estimated: bool,
     running: bool,
     next_cycle_at: Option<Instant>,
     active_question: Option<cinch_rs::ui::ActiveQuestion>,
@@ -163,6 +165,8 @@ pub(crate) fn render(
             context_pct: s.context_pct,
             model: s.model.clone(),
             cycle: s.cycle,
+            cost_usd: s.cost_usd,
+            cost_estimated: s.cost_estimated,
             running: s.running,
             next_cycle_at: s.next_cycle_at,
             active_question: s.active_question.clone(),
@@ -227,6 +231,13 @@ fn render_status_from_snap(frame: &mut Frame, area: Rect, snap: &RenderSnapshot)
         "\u{2014}".to_string()
     };
 
+    // Estimated while a round is still streaming.
+    let cost_str = format!(
+        "{}${:.4}",
+        if snap.cost_estimated { "~" } else { "" },
+        snap.cost_usd
+    );
+
     let ctx_pct = (snap.context_pct * 100.0).min(100.0);
     let ctx_bar_width = 20usize;
     let filled = ((ctx_pct / 100.0) * ctx_bar_width as f64) as usize;
@@ -297,6 +308,8 @@ fn render_status_from_snap(frame: &mut Frame, area: Rect, snap: &RenderSnapshot)
             ),
             Span::raw("   "),
             Span::styled(round_str, Style::default().fg(Color::Blue)),
+            Span::raw("   "),
+            Span::styled(cost_str, Style::default().fg(Color::DarkGray)),
         ]),
         Line::from(vec![
             Span::styled("Model: ", Style::default().fg(Color::DarkGray)),
@@ -343,8 +356,9 @@ fn render_logs(frame: &mut Frame, area: Rect, logs: &[cinch_rs::ui::LogLine], ap
     let mut lines: Vec<Line> = Vec::with_capacity(logs.len());
 
     for log in logs {
-        // Filter out trace/debug-level logs — they're too noisy for the TUI.
-        if matches!(log.level, LogLevel::Trace | LogLevel::Debug) {
+        // Without a log filter (which already decided what was captured),
+        // hide trace/debug-level logs — they're too noisy for the TUI.
+        if app.log_filter.is_none() && matches!(log.level, LogLevel::Trace | LogLevel::Debug) {
             continue;
         }
         let level_span = Span::styled(
@@ -377,7 +391,13 @@ fn render_logs(frame: &mut Frame, area: Rect, logs: &[cinch_rs::ui::LogLine], ap
     let block = Block::default()
         .borders(Borders::TOP | Borders::BOTTOM)
         .border_style(Style::default().fg(border_color))
-        .title(" Log ");
+        .title(match app.log_filter {
+            Some(ref filter) => format!(
+                " Log ({}) [+/-] level  [s] save ",
+                filter.default_level().to_string().to_lowercase()
+            ),
+            None => " Log [s] save ".to_string(),
+        });
 
     let paragraph = Paragraph::new(lines)
         .block(block)
@@ -389,6 +409,9 @@ fn render_logs(frame: &mut Frame, area: Rect, logs: &[cinch_rs::ui::LogLine], ap
 
 // ── Agent Output Pane ─────────────────────────────────────────────────
 
+/// Diff lines shown per file edit before the rest is elided.
+const MAX_DIFF_LINES: usize = 40;
+
 fn render_agent_output(
     frame: &mut Frame,
     area: Rect,
@@ -420,8 +443,16 @@ fn render_agent_output(
                     lines.push(Line::from(Span::styled(line, text_style)));
                 }
             }
-            AgentEntry::ToolExecuting { name, arguments } => {
-                let args_summary = summarize_args(arguments, arg_max);
+            AgentEntry::ToolExecuting {
+                name,
+                arguments,
+                summary,
+            } => {
+                let args_summary = if summary.is_empty() {
+                    summarize_args(arguments, arg_max)
+                } else {
+                    truncate_str(summary, arg_max)
+                };
                 lines.push(Line::from(vec![
                     Span::styled(">> ", tool_name_style),
                     Span::styled(name.as_str(), tool_name_style),
@@ -453,6 +484,39 @@ fn render_agent_output(
                     ]));
                 }
             }
+            AgentEntry::FileEdit { path, diff, stats } => {
+                let dim = Style::default()
+                    .fg(Color::Black)
+                    .add_modifier(Modifier::DIM);
+                lines.push(Line::from(vec![
+                    Span::styled("~~ ", tool_name_style),
+                    Span::styled(path.as_str(), tool_name_style),
+                    Span::styled(
+                        format!("  +{}", stats.added),
+                        Style::default().fg(Color::Green),
+                    ),
+                    Span::styled(
+                        format!(" -{}", stats.removed),
+                        Style::default().fg(Color::Red),
+                    ),
+                ]));
+                let diff_lines: Vec<&str> = diff.lines().collect();
+                for line in diff_lines.iter().take(MAX_DIFF_LINES) {
+                    let style = match line.chars().next() {
+                        Some('+') => Style::default().fg(Color::Green),
+                        Some('-') => Style::default().fg(Color::Red),
+                        Some('@') => Style::default().fg(Color::Cyan),
+                        _ => dim,
+                    };
+                    lines.push(Line::from(Span::styled(format!("   {line}"), style)));
+                }
+                if diff_lines.len() > MAX_DIFF_LINES {
+                    lines.push(Line::from(Span::styled(
+                        format!("   … {} more lines", diff_lines.len() - MAX_DIFF_LINES),
+                        dim,
+                    )));
+                }
+            }
             AgentEntry::UserMessage(message) => {
                 let user_style = Style::default()
                     .fg(Color::Magenta)
@@ -531,6 +595,11 @@ fn render_question_select_from_snap(
         for (i, choice) in aq.question.choices.iter().enumerate() {
             let is_selected = i == app.question_cursor;
             let marker = if is_selected { "> " } else { "  " };
+            let check = match (&app.question_kind, app.question_marked.contains(&i)) {
+                (QuestionKind::MultiSelect { .. }, true) => "[x] ",
+                (QuestionKind::MultiSelect { .. }, false) => "[ ] ",
+                _ => "",
+            };
             let label_style = if is_selected {
                 Style::default()
                     .fg(Color::Magenta)
@@ -551,6 +620,7 @@ fn render_question_select_from_snap(
             // Header line: "> Label  (metadata)"
             let mut header = vec![
                 Span::styled(marker, label_style),
+                Span::styled(check, label_style),
                 Span::styled(choice.label.clone(), label_style),
             ];
             if !choice.metadata.is_empty() {
@@ -608,10 +678,16 @@ fn render_question_select_from_snap(
     };
 
     let title = if let Some(ref aq) = snap.active_question {
-        format!(
-            " {} [Up/Down] navigate  [Enter] select  [Esc] skip ",
-            aq.question.prompt
-        )
+        match aq.question.kind {
+            QuestionKind::MultiSelect { .. } => format!(
+                " {} [Up/Down] navigate  [Space] toggle  [Enter] submit  [Esc] skip ",
+                aq.question.prompt
+            ),
+            _ => format!(
+                " {} [Up/Down] navigate  [Enter] select  [Esc] skip ",
+                aq.question.prompt
+            ),
+        }
     } else {
         " Select ".to_string()
     };
@@ -631,6 +707,9 @@ fn render_question_select_from_snap(
 
 // ── Context View ──────────────────────────────────────────────────────
 
+/// Tools listed in the context view's "Top tools" section.
+const TOP_CONSUMERS: usize = 5;
+
 fn render_context_view(frame: &mut Frame, area: Rect, snap: &RenderSnapshot, app: &App) {
     let inner_height = area.height.saturating_sub(2) as usize;
     let content_width = area.width.saturating_sub(4) as usize;
@@ -726,6 +805,70 @@ fn render_context_view(frame: &mut Frame, area: Rect, snap: &RenderSnapshot, app
                 ),
             ]));
         }
+        if bd.compaction_count > 0 {
+            lines.push(Line::from(Span::styled(
+                format!(
+                    "  {} compactions, history re-summarized {}x",
+                    bd.compaction_count, bd.history_generation
+                ),
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+
+        // ── Top consumers: the tools holding the most tokens ──
+        let report = &snapshot.token_report;
+        if !report.by_tool.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "  Top tools",
+                Style::default().fg(Color::DarkGray),
+            )));
+            let name_width = report
+                .by_tool
+                .iter()
+                .take(TOP_CONSUMERS)
+                .map(|share| share.name.len())
+                .max()
+                .unwrap_or(0)
+                .min(20);
+            for share in report.by_tool.iter().take(TOP_CONSUMERS) {
+                let name = if share.name.len() > name_width {
+                    truncate_str(&share.name, name_width - 3)
+                } else {
+                    share.name.clone()
+                };
+                let share_pct = if report.total_tokens > 0 {
+                    share.tokens as f64 / report.total_tokens as f64 * 100.0
+                } else {
+                    0.0
+                };
+                let share_bar_width = 12usize;
+                let share_filled = ((share_pct / 100.0) * share_bar_width as f64) as usize;
+                let share_empty = share_bar_width.saturating_sub(share_filled);
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        format!("  {name:<name_width$}  "),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    Span::styled(
+                        format!("{:>6} tok  ", share.tokens),
+                        Style::default().fg(Color::Black),
+                    ),
+                    Span::styled(
+                        format!(
+                            "{}{}",
+                            "\u{2588}".repeat(share_filled),
+                            "\u{2591}".repeat(share_empty),
+                        ),
+                        Style::default().fg(Color::Yellow),
+                    ),
+                    Span::styled(
+                        format!(" {share_pct:>4.0}%  {}x", share.messages),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                ]));
+            }
+        }
 
         // ── Prompt cache summary (when available) ──
         if let Some(ref cache) = snapshot.prompt_cache {
@@ -1060,10 +1203,19 @@ fn render_input(frame: &mut Frame, area: Rect, app: &App) {
             };
             (format!(" {hint} "), style)
         }
-        InputMode::QuestionSelect => (
-            " [Up/Down] navigate  [Enter] select  [e] edit  [Esc] skip ".to_string(),
-            Style::default().fg(Color::Magenta),
-        ),
+        InputMode::QuestionSelect => {
+            let hint = if let Some(ref msg) = app.status_message {
+                msg.clone()
+            } else if matches!(app.question_kind, QuestionKind::MultiSelect { .. }) {
+                format!(
+                    "[Up/Down] navigate  [Space] toggle ({} marked)  [Enter] submit  [Esc] skip",
+                    app.question_marked.len()
+                )
+            } else {
+                "[Up/Down] navigate  [Enter] select  [e] edit  [Esc] skip".to_string()
+            };
+            (format!(" {hint} "), Style::default().fg(Color::Magenta))
+        }
         InputMode::QuestionEdit => {
             let char_count = app.input_buffer.chars().count();
             (
@@ -1073,12 +1225,36 @@ fn render_input(frame: &mut Frame, area: Rect, app: &App) {
         }
         InputMode::FreeText => {
             let char_count = app.input_buffer.chars().count();
-            (
-                format!(
-                    " Type your message ({char_count} chars) \u{2014} [Enter] send  [Esc] cancel "
-                ),
-                Style::default().fg(Color::Blue),
-            )
+            let hint = if let Some(ref msg) = app.status_message {
+                msg.clone()
+            } else {
+                match app.question_kind {
+                    QuestionKind::Number {
+                        min, max, integer, ..
+                    } => {
+                        let what = if integer {
+                            "a whole number"
+                        } else {
+                            "a number"
+                        };
+                        let range = match (min, max) {
+                            (Some(lo), Some(hi)) => format!(" ({lo}\u{2013}{hi})"),
+                            (Some(lo), None) => format!(" (\u{2265} {lo})"),
+                            (None, Some(hi)) => format!(" (\u{2264} {hi})"),
+                            (None, None) => String::new(),
+                        };
+                        format!("Enter {what}{range} \u{2014} [Enter] submit  [Esc] skip")
+                    }
+                    QuestionKind::FilePath { .. } => {
+                        "Enter a path \u{2014} [Tab] complete  [Enter] submit  [Esc] skip"
+                            .to_string()
+                    }
+                    _ => format!(
+                        "Type your message ({char_count} chars) \u{2014} [Enter] send  [Esc] cancel"
+                    ),
+                }
+            };
+            (format!(" {hint} "), Style::default().fg(Color::Blue))
         }
         InputMode::ContextView => (
             " [Up/Down] navigate  [Enter] expand/collapse  [c/Esc] close ".to_string(),
@@ -0,0 +1,75 @@
+//! Raw-mode terminal setup that always gets undone.
+//!
+//! [`TerminalGuard`] enters raw mode and the alternate screen and restores
+//! both when dropped, so an early `?` return or a panic unwinding through
+//! the TUI thread leaves the terminal usable. A process-wide panic hook does
+//! the same for panics on any other thread (the agent's tokio tasks), before
+//! the panic message is printed, so the message lands on the normal screen
+//! instead of vanishing with the alternate one.
+
+use std::io;
+use std::sync::Once;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crossterm::terminal::{
+    EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
+};
+use crossterm::{cursor, execute};
+
+/// Whether the terminal is currently in raw mode on the alternate screen.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Set once a panic has restored the terminal; the TUI loop stops drawing.
+static PANICKED: AtomicBool = AtomicBool::new(false);
+
+static INSTALL_HOOK: Once = Once::new();
+
+/// Leave raw mode and the alternate screen, and show the cursor.
+///
+/// Idempotent and safe to call from anywhere, e.g. before
+/// `std::process::exit` while the TUI is running (exiting skips the
+/// [`TerminalGuard`]'s destructor).
+pub fn restore_terminal() {
+    if ACTIVE.swap(false, Ordering::SeqCst) {
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen, cursor::Show);
+    }
+}
+
+/// Whether a panic has torn down the TUI's terminal.
+pub(crate) fn panicked() -> bool {
+    PANICKED.load(Ordering::SeqCst)
+}
+
+/// Restores the terminal on drop.
+pub(crate) struct TerminalGuard(());
+
+impl TerminalGuard {
+    /// Enter raw mode and the alternate screen, installing the panic hook
+    /// on first use.
+    pub(crate) fn enter() -> io::Result<Self> {
+        INSTALL_HOOK.call_once(|| {
+            let previous = std::panic::take_hook();
+            std::panic::set_hook(Box::new(move |info| {
+                if ACTIVE.load(Ordering::SeqCst) {
+                    PANICKED.store(true, Ordering::SeqCst);
+                    restore_terminal();
+                }
+                previous(info);
+            }));
+        });
+
+        enable_raw_mode()?;
+        ACTIVE.store(true, Ordering::SeqCst);
+        // From here on, dropping the guard undoes whatever succeeded.
+        let guard = Self(());
+        execute!(io::stdout(), EnterAlternateScreen, cursor::Hide)?;
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
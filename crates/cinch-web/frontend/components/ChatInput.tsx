@@ -2,6 +2,23 @@
 
 import { useCallback, useEffect, useRef, useState } from "react";
 import { useAgentState } from "@/hooks/useAgentState";
+import type { ChatAttachment } from "@/lib/types";
+
+/** Read a pasted or dropped file as a `data:` URL attachment. */
+function readAttachment(file: File): Promise<ChatAttachment> {
+  return new Promise((resolve, reject) => {
+    const reader = new FileReader();
+    reader.onload = () => {
+      resolve({
+        name: file.name || "pasted-image.png",
+        media_type: file.type || undefined,
+        data: String(reader.result),
+      });
+    };
+    reader.onerror = () => { reject(reader.error ?? new Error("read failed")); };
+    reader.readAsDataURL(file);
+  });
+}
 
 /** Arrow-up send icon (inline SVG). */
 function SendIcon(): React.ReactNode {
@@ -18,14 +35,44 @@ function SendIcon(): React.ReactNode {
   );
 }
 
+/** How often typing refreshes the server's input lock (it expires after 15s). */
+const TYPING_REFRESH_MS = 5_000;
+
 /** Floating bottom-pinned chat input with auto-expanding textarea. */
 export function ChatInput(): React.ReactNode {
-  const { connected, state, sendChat } = useAgentState();
+  const { connected, state, sendChat, sendTyping } = useAgentState();
   const [value, setValue] = useState("");
+  const [attachments, setAttachments] = useState<ChatAttachment[]>([]);
   const [focused, setFocused] = useState(false);
   const inputRef = useRef<HTMLTextAreaElement>(null);
+  const lastTypingRef = useRef(0);
+
+  // Another viewer is composing a message; wait for them to send it.
+  const holder =
+    state.inputHolder !== null && state.inputHolder !== state.viewerId
+      ? state.viewers.find((v) => v.id === state.inputHolder)
+      : undefined;
+  const others = state.viewers.filter((v) => v.id !== state.viewerId);
+
+  const canSend =
+    connected &&
+    (value.trim().length > 0 || attachments.length > 0) &&
+    !state.running &&
+    !holder;
 
-  const canSend = connected && value.trim().length > 0 && !state.running;
+  // Take (and keep refreshing) the input lock while there is a draft.
+  useEffect(() => {
+    if (!connected) return;
+    const drafting = value.length > 0 || attachments.length > 0;
+    const now = Date.now();
+    if (drafting && now - lastTypingRef.current > TYPING_REFRESH_MS) {
+      lastTypingRef.current = now;
+      sendTyping(true);
+    } else if (!drafting && lastTypingRef.current > 0) {
+      lastTypingRef.current = 0;
+      sendTyping(false);
+    }
+  }, [value, attachments, connected, sendTyping]);
 
   // Auto-resize textarea height.
   useEffect(() => {
@@ -37,11 +84,41 @@ export function ChatInput(): React.ReactNode {
 
   const submit = useCallback(() => {
     const trimmed = value.trim();
-    if (!trimmed || !connected) return;
-    sendChat(trimmed);
+    if ((!trimmed && attachments.length === 0) || !connected || holder) return;
+    sendChat(trimmed, attachments);
+    // Sending releases the input lock on the server.
+    lastTypingRef.current = 0;
     setValue("");
+    setAttachments([]);
     inputRef.current?.focus();
-  }, [value, connected, sendChat]);
+  }, [value, attachments, connected, holder, sendChat]);
+
+  const addFiles = useCallback((files: File[]) => {
+    void Promise.all(files.map(readAttachment)).then((read) => {
+      setAttachments((prev) => [...prev, ...read]);
+    });
+  }, []);
+
+  // Pasted screenshots and files become attachments; plain text pastes as usual.
+  const handlePaste = useCallback(
+    (e: React.ClipboardEvent<HTMLTextAreaElement>) => {
+      const files = Array.from(e.clipboardData.files);
+      if (files.length === 0) return;
+      e.preventDefault();
+      addFiles(files);
+    },
+    [addFiles],
+  );
+
+  const handleDrop = useCallback(
+    (e: React.DragEvent<HTMLDivElement>) => {
+      const files = Array.from(e.dataTransfer.files);
+      if (files.length === 0) return;
+      e.preventDefault();
+      addFiles(files);
+    },
+    [addFiles],
+  );
 
   const handleKeyDown = useCallback(
     (e: React.KeyboardEvent<HTMLTextAreaElement>) => {
@@ -61,6 +138,8 @@ export function ChatInput(): React.ReactNode {
       )}
 
       <div
+        onDragOver={(e) => { e.preventDefault(); }}
+        onDrop={handleDrop}
         className="relative max-w-3xl mx-auto rounded-2xl border transition-shadow"
         style={{
           background: "var(--bg-surface)",
@@ -70,20 +149,39 @@ export function ChatInput(): React.ReactNode {
             : "0 2px 8px var(--shadow-msg)",
         }}
       >
+        {attachments.length > 0 && (
+          <div className="flex flex-wrap gap-1.5 px-3 pt-2">
+            {attachments.map((a, i) => (
+              <button
+                key={`${a.name}-${String(i)}`}
+                onClick={() => { setAttachments((prev) => prev.filter((_, j) => j !== i)); }}
+                title="Remove attachment"
+                className="rounded-full px-2 py-0.5 text-xs text-[var(--text-secondary)]"
+                style={{ background: "var(--bg-subtle)" }}
+              >
+                {a.name} &times;
+              </button>
+            ))}
+          </div>
+        )}
+
         <div className="flex items-end gap-2 p-2">
           <textarea
             ref={inputRef}
             value={value}
             onChange={(e) => { setValue(e.target.value); }}
             onKeyDown={handleKeyDown}
+            onPaste={handlePaste}
             onFocus={() => { setFocused(true); }}
             onBlur={() => { setFocused(false); }}
             placeholder={
               !connected
                 ? "Disconnected..."
-                : state.running
-                  ? "Agent is running..."
-                  : "Send a message..."
+                : holder
+                  ? `${holder.name} is typing...`
+                  : state.running
+                    ? "Agent is running..."
+                    : "Send a message..."
             }
             disabled={!connected}
             rows={1}
@@ -106,6 +204,13 @@ export function ChatInput(): React.ReactNode {
           </button>
         </div>
 
+        {/* Other viewers watching this session */}
+        {others.length > 0 && (
+          <div className="absolute -top-5 right-3 text-[10px] text-[var(--text-muted)] select-none">
+            {state.chatRejected ?? `Also watching: ${others.map((v) => v.name).join(", ")}`}
+          </div>
+        )}
+
         {/* Keyboard hint */}
         {focused && !state.running && (
           <div className="absolute -bottom-5 left-1/2 -translate-x-1/2 text-[10px] text-[var(--text-muted)] select-none">
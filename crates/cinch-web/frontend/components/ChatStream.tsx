@@ -5,7 +5,7 @@ import { useAgentState } from "@/hooks/useAgentState";
 import { StreamingText } from "./StreamingText";
 import { ToolCall } from "./ToolCall";
 import { Markdown } from "./Markdown";
-import type { AgentEntry } from "@/lib/types";
+import type { AgentEntry, DiffStats } from "@/lib/types";
 
 // ── Pre-resolved entry types (decouples ToolCall look-ahead from render) ──
 
@@ -16,6 +16,7 @@ type ResolvedEntry =
       type: "tool";
       name: string;
       arguments: string;
+      summary: string;
       result: string | undefined;
       isError: boolean | undefined;
     }
@@ -25,6 +26,7 @@ type ResolvedEntry =
       result: string;
       isError: boolean;
     }
+  | { type: "file_edit"; path: string; diff: string; stats: DiffStats }
   | { type: "todo"; content: string };
 
 /**
@@ -58,6 +60,7 @@ function resolveEntries(entries: AgentEntry[]): ResolvedEntry[] {
         type: "tool",
         name: entry.ToolExecuting.name,
         arguments: entry.ToolExecuting.arguments,
+        summary: entry.ToolExecuting.summary ?? "",
         result,
         isError,
       });
@@ -78,6 +81,8 @@ function resolveEntries(entries: AgentEntry[]): ResolvedEntry[] {
         result: entry.ToolResult.result,
         isError: entry.ToolResult.is_error,
       });
+    } else if ("FileEdit" in entry) {
+      resolved.push({ type: "file_edit", ...entry.FileEdit });
     } else if ("TodoUpdate" in entry) {
       resolved.push({ type: "todo", content: entry.TodoUpdate });
     }
@@ -262,7 +267,52 @@ const OrphanToolResult = memo(function OrphanToolResult({
   );
 });
 
-/** Renders the main chat stream: user messages, LLM text, tool calls, and tool results. */
+const FileEditEntry = memo(function FileEditEntry({
+  path,
+  diff,
+  stats,
+}: {
+  path: string;
+  diff: string;
+  stats: DiffStats;
+}) {
+  const lineColor = (line: string): string | undefined => {
+    if (line.startsWith("+")) return "var(--success)";
+    if (line.startsWith("-")) return "var(--error)";
+    if (line.startsWith("@@")) return "var(--accent)";
+    return undefined;
+  };
+  return (
+    <div
+      className="mx-4 my-1.5 px-3 py-2 rounded-lg bg-[var(--bg-surface)]"
+      style={{
+        borderLeft: "3px solid var(--accent)",
+        boxShadow: "0 1px 2px var(--shadow-msg)",
+      }}
+    >
+      <div
+        className="text-sm font-medium flex gap-2"
+        style={{ fontFamily: "var(--font-mono), ui-monospace, monospace" }}
+      >
+        <span className="text-[var(--text-primary)]">{path}</span>
+        <span style={{ color: "var(--success)" }}>+{stats.added}</span>
+        <span style={{ color: "var(--error)" }}>-{stats.removed}</span>
+      </div>
+      <pre
+        className="text-xs text-[var(--text-secondary)] mt-1 overflow-x-auto max-h-80 overflow-y-auto"
+        style={{ fontFamily: "var(--font-mono), ui-monospace, monospace" }}
+      >
+        {diff.trimEnd().split("\n").map((line, i) => (
+          <div key={i} style={{ color: lineColor(line) }}>
+            {line || "\u00a0"}
+          </div>
+        ))}
+      </pre>
+    </div>
+  );
+});
+
+/** Renders the main chat stream: user messages, LLM text, tool calls, tool results, and file diffs. */
 export function ChatStream(): React.ReactNode {
   const { state } = useAgentState();
   const containerRef = useRef<HTMLDivElement>(null);
@@ -362,6 +412,7 @@ export function ChatStream(): React.ReactNode {
                   key={i}
                   name={entry.name}
                   arguments={entry.arguments}
+                  summary={entry.summary}
                   result={entry.result}
                   isError={entry.isError}
                 />
@@ -375,6 +426,15 @@ export function ChatStream(): React.ReactNode {
                   isError={entry.isError}
                 />
               );
+            case "file_edit":
+              return (
+                <FileEditEntry
+                  key={i}
+                  path={entry.path}
+                  diff={entry.diff}
+                  stats={entry.stats}
+                />
+              );
             case "todo":
               return <TodoUpdateEntry key={i} content={entry.content} />;
           }
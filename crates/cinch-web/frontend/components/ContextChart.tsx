@@ -0,0 +1,89 @@
+"use client";
+
+import { useAgentState } from "@/hooks/useAgentState";
+import type { TokenShare } from "@/lib/types";
+
+/** Shares listed per chart section. */
+const TOP_SHARES = 5;
+
+/** One labelled bar, scaled against the context total. */
+function ShareBar({
+  share,
+  total,
+}: {
+  share: TokenShare;
+  total: number;
+}): React.ReactNode {
+  const pct = total > 0 ? (share.tokens / total) * 100 : 0;
+  return (
+    <div className="flex items-center gap-2 text-xs leading-5">
+      <span className="w-28 truncate text-[var(--text-secondary)]" title={share.name}>
+        {share.name}
+      </span>
+      <div className="flex-1 h-1.5 rounded-full bg-[var(--bg-subtle)] overflow-hidden">
+        <div
+          className="h-full rounded-full bg-[var(--accent)]"
+          style={{ width: `${pct}%` }}
+        />
+      </div>
+      <span className="w-20 text-right tabular-nums text-[var(--text-muted)]">
+        {share.tokens.toLocaleString()} tok
+      </span>
+    </div>
+  );
+}
+
+/**
+ * "What's eating my context": a per-message heat strip plus the heaviest
+ * tools and roles from the latest token report.
+ */
+export function ContextChart(): React.ReactNode {
+  const { state } = useAgentState();
+  const report = state.tokenReport;
+  if (!report || report.messages.length === 0) return null;
+
+  const heaviest = Math.max(...report.messages.map((m) => m.tokens), 1);
+
+  return (
+    <div className="px-3 py-2 border-b border-[var(--border-dim)] space-y-2">
+      <div className="flex items-center justify-between text-xs">
+        <span className="font-medium text-[var(--text-primary)]">Context</span>
+        <span className="tabular-nums text-[var(--text-muted)]">
+          {report.total_tokens.toLocaleString()} tok
+        </span>
+      </div>
+
+      {/* One cell per message, darker where more tokens sit. */}
+      <div className="flex gap-px h-3">
+        {report.messages.map((m) => (
+          <div
+            key={m.message_index}
+            className="flex-1 rounded-sm bg-[var(--accent)]"
+            style={{ opacity: 0.1 + 0.9 * (m.tokens / heaviest) }}
+            title={`#${m.message_index} ${m.role}${m.tool_name ? `/${m.tool_name}` : ""} (${m.zone}): ${m.tokens.toLocaleString()} tok`}
+          />
+        ))}
+      </div>
+
+      {report.by_tool.length > 0 && (
+        <div>
+          <div className="text-[10px] uppercase tracking-wide text-[var(--text-muted)]">
+            Tools
+          </div>
+          {report.by_tool.slice(0, TOP_SHARES).map((share) => (
+            <ShareBar key={share.name} share={share} total={report.total_tokens} />
+          ))}
+        </div>
+      )}
+
+      <div>
+        <div className="text-[10px] uppercase tracking-wide text-[var(--text-muted)]">
+          Roles
+        </div>
+        {report.by_role.slice(0, TOP_SHARES).map((share) => (
+          <ShareBar key={share.name} share={share} total={report.total_tokens} />
+        ))}
+      </div>
+    </div>
+  );
+}
@@ -2,6 +2,7 @@
 
 import { useCallback, useEffect } from "react";
 import { useAgentState } from "@/hooks/useAgentState";
+import { ContextChart } from "./ContextChart";
 import { LogViewer } from "./LogViewer";
 
 /** Close icon for the panel header. */
@@ -19,7 +20,7 @@ function CloseIcon(): React.ReactNode {
 }
 
 /**
- * Right-side inspector panel showing agent logs.
+ * Right-side inspector panel showing context usage and agent logs.
  * Slides in/out with a transform transition.
  */
 export function InspectorPanel({
@@ -76,6 +77,8 @@ export function InspectorPanel({
         </div>
       </div>
 
+      <ContextChart />
+
       {/* Logs content */}
       <div className="flex-1 overflow-hidden">
         <LogViewer />
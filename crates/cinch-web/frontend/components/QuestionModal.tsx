@@ -2,7 +2,7 @@
 
 import { useState, useEffect, useCallback } from "react";
 import { useAgentState } from "@/hooks/useAgentState";
-import type { QuestionResponse } from "@/lib/types";
+import type { QuestionKind, QuestionResponse } from "@/lib/types";
 
 /** SVG radial countdown ring for timeout. */
 function TimeoutRing({ remaining, total }: { remaining: number; total: number }): React.ReactNode {
@@ -36,6 +36,51 @@ function TimeoutRing({ remaining, total }: { remaining: number; total: number })
   );
 }
 
+/**
+ * Parse typed text into a response for number and file-path questions.
+ * Returns an error string when the input does not fit the constraints; the
+ * server re-validates either way.
+ */
+function parseTextAnswer(kind: QuestionKind, text: string): QuestionResponse | string {
+  const trimmed = text.trim();
+  if (typeof kind === "object" && "Number" in kind) {
+    const { min, max, integer } = kind.Number;
+    const value = Number(trimmed);
+    if (trimmed === "" || !Number.isFinite(value)) return `'${trimmed}' is not a number`;
+    if (integer && !Number.isInteger(value)) return "Enter a whole number";
+    if (min !== null && value < min) return `Must be at least ${min}`;
+    if (max !== null && value > max) return `Must be at most ${max}`;
+    return { Number: value };
+  }
+  if (trimmed === "") return "Enter a path";
+  return { Path: trimmed };
+}
+
+/** Error for a multi-select submission, or null when the count is valid. */
+function multiSelectError(kind: QuestionKind, count: number): string | null {
+  if (typeof kind !== "object" || !("MultiSelect" in kind)) return null;
+  const { min, max } = kind.MultiSelect;
+  if (count < min) return `Select at least ${min}`;
+  if (max !== null && count > max) return `Select at most ${max}`;
+  return null;
+}
+
+/** Input placeholder describing what a text-entry question expects. */
+function textPlaceholder(kind: QuestionKind): string {
+  if (typeof kind === "object" && "Number" in kind) {
+    const { min, max, integer } = kind.Number;
+    const what = integer ? "A whole number" : "A number";
+    if (min !== null && max !== null) return `${what} (${min}–${max})`;
+    if (min !== null) return `${what} (≥ ${min})`;
+    if (max !== null) return `${what} (≤ ${max})`;
+    return what;
+  }
+  if (typeof kind === "object" && "FilePath" in kind) {
+    return kind.FilePath.base_dir ? `Path relative to ${kind.FilePath.base_dir}` : "Path";
+  }
+  return "";
+}
+
 /**
  * Modal overlay for human-in-the-loop questions.
  * Frosted glass backdrop, card with accent-bordered choices.
@@ -47,6 +92,9 @@ export function QuestionModal(): React.ReactNode {
   const [cursor, setCursor] = useState(0);
   const [editing, setEditing] = useState(false);
   const [editText, setEditText] = useState("");
+  const [marked, setMarked] = useState<number[]>([]);
+  const [textAnswer, setTextAnswer] = useState("");
+  const [inputError, setInputError] = useState<string | null>(null);
 
   // Track total timeout for the ring proportion.
   const [totalTimeout, setTotalTimeout] = useState<number | null>(null);
@@ -60,6 +108,9 @@ export function QuestionModal(): React.ReactNode {
       setCursor(0);
       setEditing(false);
       setEditText("");
+      setMarked([]);
+      setTextAnswer("");
+      setInputError(null);
       setTotalTimeout(question?.remaining_secs ?? null);
     }
   }
@@ -71,9 +122,36 @@ export function QuestionModal(): React.ReactNode {
     [sendAnswer],
   );
 
+  const kind: QuestionKind = question?.question.kind ?? "Single";
+  const isMulti = typeof kind === "object" && "MultiSelect" in kind;
+  const isTextEntry = typeof kind === "object" && ("Number" in kind || "FilePath" in kind);
+
+  const toggle = useCallback((i: number) => {
+    setMarked((m) => (m.includes(i) ? m.filter((x) => x !== i) : [...m, i].sort((a, b) => a - b)));
+    setInputError(null);
+  }, []);
+
+  const submitMarked = useCallback(() => {
+    const error = multiSelectError(kind, marked.length);
+    if (error) {
+      setInputError(error);
+      return;
+    }
+    submit({ MultiSelected: marked });
+  }, [kind, marked, submit]);
+
+  const submitText = useCallback(() => {
+    const parsed = parseTextAnswer(kind, textAnswer);
+    if (typeof parsed === "string") {
+      setInputError(parsed);
+      return;
+    }
+    submit(parsed);
+  }, [kind, textAnswer, submit]);
+
   // Keyboard navigation.
   useEffect(() => {
-    if (!question || question.done || editing) return;
+    if (!question || question.done || editing || isTextEntry) return;
 
     function handleKey(e: KeyboardEvent): void {
       if (!question) return;
@@ -90,8 +168,17 @@ export function QuestionModal(): React.ReactNode {
           e.preventDefault();
           setCursor((c) => (c + 1) % count);
           break;
+        case " ":
+          if (!isMulti) break;
+          e.preventDefault();
+          toggle(cursor);
+          break;
         case "Enter": {
           e.preventDefault();
+          if (isMulti) {
+            submitMarked();
+            break;
+          }
           const selected = question.question.choices[cursor];
           if (selected === undefined) break;
           if (question.question.editable) {
@@ -111,7 +198,7 @@ export function QuestionModal(): React.ReactNode {
 
     window.addEventListener("keydown", handleKey);
     return () => { window.removeEventListener("keydown", handleKey); };
-  }, [question, cursor, editing, submit]);
+  }, [question, cursor, editing, isTextEntry, isMulti, toggle, submitMarked, submit]);
 
   if (!question || question.done) return null;
 
@@ -146,7 +233,36 @@ export function QuestionModal(): React.ReactNode {
           )}
         </div>
 
-        {editing ? (
+        {isTextEntry ? (
+          /* Number / file-path entry */
+          <div className="space-y-3">
+            <input
+              autoFocus
+              type={typeof kind === "object" && "Number" in kind ? "number" : "text"}
+              value={textAnswer}
+              placeholder={textPlaceholder(kind)}
+              onChange={(e) => { setTextAnswer(e.target.value); setInputError(null); }}
+              onKeyDown={(e) => {
+                if (e.key === "Enter") { e.preventDefault(); submitText(); }
+                if (e.key === "Escape") { e.preventDefault(); submit("Skipped"); }
+              }}
+              className="w-full rounded-xl p-3 text-sm text-[var(--text-primary)] border focus:outline-none transition-colors"
+              style={{
+                background: "var(--bg-overlay)",
+                borderColor: inputError ? "var(--error)" : "var(--border)",
+              }}
+            />
+            <div className="flex gap-2">
+              <button
+                onClick={submitText}
+                className="px-4 py-2 rounded-lg text-sm font-medium text-white transition-opacity hover:opacity-90"
+                style={{ background: "var(--accent)" }}
+              >
+                Submit
+              </button>
+            </div>
+          </div>
+        ) : editing ? (
           /* Edit mode */
           <div className="space-y-3">
             <textarea
@@ -196,7 +312,10 @@ export function QuestionModal(): React.ReactNode {
               <button
                 key={i}
                 onClick={() => {
-                  if (editable) {
+                  if (isMulti) {
+                    setCursor(i);
+                    toggle(i);
+                  } else if (editable) {
                     setCursor(i);
                     setEditing(true);
                     setEditText(choice.body);
@@ -217,6 +336,15 @@ export function QuestionModal(): React.ReactNode {
               >
                 <div className="flex items-center justify-between">
                   <span className="text-sm font-medium text-[var(--text-primary)]">
+                    {isMulti && (
+                      <input
+                        type="checkbox"
+                        readOnly
+                        tabIndex={-1}
+                        checked={marked.includes(i)}
+                        className="mr-2 align-middle accent-[var(--accent)]"
+                      />
+                    )}
                     {choice.label}
                   </span>
                   {choice.metadata && (
@@ -233,6 +361,12 @@ export function QuestionModal(): React.ReactNode {
           </div>
         )}
 
+        {inputError && (
+          <p className="mt-3 text-xs" style={{ color: "var(--error)" }}>
+            {inputError}
+          </p>
+        )}
+
         {!editing && (
           <div className="mt-4 flex items-center justify-between">
             <button
@@ -241,9 +375,24 @@ export function QuestionModal(): React.ReactNode {
             >
               Skip (Esc)
             </button>
-            <span className="text-xs text-[var(--text-muted)]">
-              {"\u2191\u2193"} navigate &middot; Enter select
-            </span>
+            {isMulti ? (
+              <div className="flex items-center gap-3">
+                <span className="text-xs text-[var(--text-muted)]">
+                  {"\u2191\u2193"} navigate &middot; Space toggle &middot; Enter submit
+                </span>
+                <button
+                  onClick={submitMarked}
+                  className="px-3 py-1.5 rounded-lg text-sm font-medium text-white transition-opacity hover:opacity-90"
+                  style={{ background: "var(--accent)" }}
+                >
+                  Submit ({marked.length})
+                </button>
+              </div>
+            ) : !isTextEntry && (
+              <span className="text-xs text-[var(--text-muted)]">
+                {"\u2191\u2193"} navigate &middot; Enter select
+              </span>
+            )}
           </div>
         )}
       </div>
@@ -80,6 +80,16 @@ export function StatusBar(): React.ReactNode {
           </span>
         )}
 
+        {/* Run cost (estimated while a round streams) */}
+        {state.costUsd > 0 && (
+          <span
+            className="text-xs text-[var(--text-muted)] tabular-nums"
+            title={state.costEstimated ? "Estimated while streaming" : "Estimated run cost"}
+          >
+            {state.costEstimated ? "~" : ""}${state.costUsd.toFixed(4)}
+          </span>
+        )}
+
         {/* Cycle */}
         {state.cycle > 0 && (
           <span className="text-xs text-[var(--text-secondary)] tabular-nums">
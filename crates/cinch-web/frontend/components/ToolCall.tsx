@@ -28,7 +28,10 @@ function ChevronIcon({ expanded }: { expanded: boolean }): React.ReactNode {
   );
 }
 
-/** Summarize tool arguments to a short preview string. */
+/**
+ * Summarize tool arguments to a short preview string. Fallback for entries
+ * recorded without the server's summary.
+ */
 function summarizeArgs(args: string): string {
   try {
     const parsed = JSON.parse(args) as Record<string, unknown>;
@@ -162,6 +165,8 @@ function ToolArguments({ args }: { args: string }): React.ReactNode {
 interface ToolCallProps {
   name: string;
   arguments: string;
+  /** Server-side one-line summary of the arguments. */
+  summary?: string | undefined;
   result?: string | undefined;
   isError?: boolean | undefined;
 }
@@ -170,6 +175,7 @@ interface ToolCallProps {
 export const ToolCall = memo(function ToolCall({
   name,
   arguments: args,
+  summary,
   result,
   isError,
 }: ToolCallProps) {
@@ -203,7 +209,7 @@ export const ToolCall = memo(function ToolCall({
           {name}
         </span>
         <span className="text-[var(--text-muted)] text-xs truncate">
-          {summarizeArgs(args)}
+          {summary || summarizeArgs(args)}
         </span>
         {result !== undefined && (
           <span
@@ -1,7 +1,7 @@
 "use client";
 
 import { useCallback, useEffect, useRef, useState } from "react";
-import type { AgentState, QuestionResponse } from "@/lib/types";
+import type { AgentState, ChatAttachment, QuestionResponse } from "@/lib/types";
 import { INITIAL_STATE } from "@/lib/types";
 import { applyMessage, type WsServerMessage } from "@/lib/protocol";
 
@@ -11,13 +11,16 @@ const MAX_RETRY_DELAY = 16_000;
  * Central hook managing the WebSocket connection to the cinch-web backend.
  *
  * Returns the current agent state (updated in real-time), connection status,
- * and callbacks for sending answers and quit requests.
+ * and callbacks for sending answers, chat messages, presence updates, and
+ * quit requests.
  */
 export function useAgentSocket(backendUrl: string): {
   state: AgentState;
   connected: boolean;
   sendAnswer: (response: QuestionResponse) => void;
-  sendChat: (message: string) => void;
+  sendChat: (message: string, attachments?: ChatAttachment[]) => void;
+  sendTyping: (typing: boolean) => void;
+  sendName: (name: string) => void;
   sendQuit: () => void;
 } {
   const [state, setState] = useState<AgentState>(INITIAL_STATE);
@@ -82,10 +85,24 @@ export function useAgentSocket(backendUrl: string): {
     }
   }, []);
 
-  const sendChat = useCallback((message: string) => {
+  const sendChat = useCallback((message: string, attachments: ChatAttachment[] = []) => {
     const ws = wsRef.current;
     if (ws?.readyState === WebSocket.OPEN) {
-      ws.send(JSON.stringify({ type: "chat", message }));
+      ws.send(JSON.stringify({ type: "chat", message, attachments }));
+    }
+  }, []);
+
+  const sendTyping = useCallback((typing: boolean) => {
+    const ws = wsRef.current;
+    if (ws?.readyState === WebSocket.OPEN) {
+      ws.send(JSON.stringify({ type: "typing", typing }));
+    }
+  }, []);
+
+  const sendName = useCallback((name: string) => {
+    const ws = wsRef.current;
+    if (ws?.readyState === WebSocket.OPEN) {
+      ws.send(JSON.stringify({ type: "presence", name }));
     }
   }, []);
 
@@ -96,5 +113,5 @@ export function useAgentSocket(backendUrl: string): {
     }
   }, []);
 
-  return { state, connected, sendAnswer, sendChat, sendQuit };
+  return { state, connected, sendAnswer, sendChat, sendTyping, sendName, sendQuit };
 }
@@ -1,14 +1,16 @@
 "use client";
 
 import { createContext, useContext } from "react";
-import type { AgentState, QuestionResponse } from "@/lib/types";
+import type { AgentState, ChatAttachment, QuestionResponse } from "@/lib/types";
 import { INITIAL_STATE } from "@/lib/types";
 
 export interface AgentContextValue {
   state: AgentState;
   connected: boolean;
   sendAnswer: (response: QuestionResponse) => void;
-  sendChat: (message: string) => void;
+  sendChat: (message: string, attachments?: ChatAttachment[]) => void;
+  sendTyping: (typing: boolean) => void;
+  sendName: (name: string) => void;
   sendQuit: () => void;
 }
 
@@ -19,6 +21,8 @@ export const AgentContext = createContext<AgentContextValue>({
   connected: false,
   sendAnswer: noop,
   sendChat: noop,
+  sendTyping: noop,
+  sendName: noop,
   sendQuit: noop,
 });
 
@@ -7,15 +7,24 @@
 
 import type {
   AgentState,
+  EntriesPatch,
   LogLine,
+  OutputEntry,
+  TokenReport,
+  UiStatePatch,
   UiStateSnapshot,
   UserQuestion,
+  Viewer,
 } from "./types";
 
+/** Maximum log lines kept on the client (matches the server snapshot cap). */
+const MAX_CLIENT_LOGS = 200;
+
 // ── Server → Client messages ──────────────────────────────────────────
 
 export type WsServerMessage =
   | { type: "snapshot"; data: UiStateSnapshot }
+  | { type: "patch"; data: UiStatePatch }
   | { type: "text"; text: string }
   | { type: "text_delta"; delta: string }
   | { type: "tool_executing"; name: string; arguments: string }
@@ -29,26 +38,77 @@ export type WsServerMessage =
       context_pct: number;
     }
   | { type: "phase"; phase: string }
-  | { type: "question"; question: UserQuestion }
+  | { type: "question"; id: number; question: UserQuestion }
   | { type: "question_dismissed" }
   | { type: "finished" }
   | { type: "log"; line: LogLine }
   | { type: "extension"; data: Record<string, unknown> }
   | { type: "user_message"; message: string }
   | { type: "token_usage"; prompt_tokens: number; completion_tokens: number }
+  | { type: "cost_update"; cost_usd: number; estimated: boolean }
   | { type: "tool_calls_received"; round: number; count: number }
   | { type: "tool_cache_hit"; name: string; arguments: string }
   | { type: "eviction"; freed_chars: number; evicted_count: number }
   | { type: "compaction"; compaction_number: number }
+  | { type: "token_report"; report: TokenReport }
   | { type: "model_routed"; model: string; round: number }
   | { type: "checkpoint_saved"; round: number; path: string }
   | { type: "checkpoint_resumed"; round: number }
   | { type: "empty_response"; round: number; attempt: number; max_retries: number }
   | { type: "approval_required"; name: string; arguments: string }
-  | { type: "todo_update"; content: string };
+  | { type: "todo_update"; content: string }
+  | { type: "welcome"; viewer_id: number }
+  | { type: "presence"; viewers: Viewer[]; input_holder: number | null }
+  | { type: "chat_rejected"; reason: string };
 
 // ── State reducer ─────────────────────────────────────────────────────
 
+/**
+ * Apply an entries patch to a list whose first element has sequence number
+ * `offset`: keep entries before `patch.from`, append `patch.entries`, then
+ * drop everything older than `patch.offset`.
+ */
+function applyEntries(
+  entries: OutputEntry[],
+  offset: number,
+  patch: EntriesPatch<OutputEntry>,
+): OutputEntry[] {
+  const kept = entries.slice(0, Math.max(0, patch.from - offset));
+  const merged = [...kept, ...patch.entries];
+  return merged.slice(Math.max(0, patch.offset - offset));
+}
+
+/** Apply a state patch. Fields absent from the patch are left unchanged. */
+function applyPatch(prev: AgentState, p: UiStatePatch): AgentState {
+  const next = { ...prev };
+  if (p.phase !== undefined) next.phase = p.phase;
+  if (p.round !== undefined) next.round = p.round;
+  if (p.max_rounds !== undefined) next.maxRounds = p.max_rounds;
+  if (p.context_pct !== undefined) next.contextPct = p.context_pct;
+  if (p.model !== undefined) next.model = p.model;
+  if (p.cycle !== undefined) next.cycle = p.cycle;
+  if (p.running !== undefined) next.running = p.running;
+  if (p.next_cycle_secs !== undefined) next.nextCycleSecs = p.next_cycle_secs;
+  if (p.active_question !== undefined) next.activeQuestion = p.active_question;
+  if (p.extension !== undefined) next.extension = p.extension;
+  if (p.streaming_buffer !== undefined) next.streamingBuffer = p.streaming_buffer;
+  if (p.streaming_append !== undefined) next.streamingBuffer += p.streaming_append;
+  if (p.agent_output) {
+    next.entries = applyEntries(prev.entries, prev.outputOffset, p.agent_output);
+    next.outputOffset = p.agent_output.offset;
+  }
+  if (p.logs) {
+    // Skip lines already received (patches may overlap the snapshot).
+    const end = p.logs.from + p.logs.entries.length;
+    const fresh = p.logs.entries.slice(Math.max(0, prev.logEnd - p.logs.from));
+    if (fresh.length > 0) {
+      next.logs = [...prev.logs, ...fresh].slice(-MAX_CLIENT_LOGS);
+    }
+    next.logEnd = Math.max(prev.logEnd, end);
+  }
+  return next;
+}
+
 /** Apply a server message to the current agent state, returning a new state. */
 export function applyMessage(
   prev: AgentState,
@@ -65,66 +125,33 @@ export function applyMessage(
         model: s.model,
         cycle: s.cycle,
         entries: s.agent_output,
+        outputOffset: s.output_offset,
         streamingBuffer: s.streaming_buffer,
         reasoningBuffer: "",
         logs: s.logs,
+        logEnd: s.log_offset + s.logs.length,
         running: s.running,
         nextCycleSecs: s.next_cycle_secs,
         activeQuestion: s.active_question,
         extension: s.extension,
         totalPromptTokens: prev.totalPromptTokens,
         totalCompletionTokens: prev.totalCompletionTokens,
+        tokenReport: prev.tokenReport,
+        costUsd: prev.costUsd,
+        costEstimated: prev.costEstimated,
+        viewerId: prev.viewerId,
+        viewers: prev.viewers,
+        inputHolder: prev.inputHolder,
+        chatRejected: prev.chatRejected,
       };
     }
 
-    case "text":
-      return {
-        ...prev,
-        entries: [...prev.entries, { Text: msg.text }],
-        streamingBuffer: "", // Complete text replaces the buffer.
-      };
-
-    case "text_delta":
-      return {
-        ...prev,
-        streamingBuffer: prev.streamingBuffer + msg.delta,
-      };
-
-    case "tool_executing":
-      return {
-        ...prev,
-        phase: `Tool: ${msg.name}`,
-        entries: [
-          ...prev.entries,
-          { ToolExecuting: { name: msg.name, arguments: msg.arguments } },
-        ],
-      };
-
-    case "tool_result":
-      return {
-        ...prev,
-        entries: [
-          ...prev.entries,
-          {
-            ToolResult: {
-              name: msg.name,
-              result: msg.result,
-              is_error: msg.is_error,
-            },
-          },
-        ],
-      };
+    case "patch":
+      return applyPatch(prev, msg.data);
 
     case "reasoning":
-      return {
-        ...prev,
-        entries: [
-          ...prev.entries,
-          { ToolExecuting: { name: "think", arguments: JSON.stringify({ reasoning: msg.text }) } },
-          { ToolResult: { name: "think", result: "ok", is_error: false } },
-        ],
-        reasoningBuffer: "", // Complete reasoning replaces the buffer.
-      };
+      // The reasoning entry itself arrives in the next patch.
+      return { ...prev, reasoningBuffer: "" };
 
     case "reasoning_delta":
       return {
@@ -147,6 +174,7 @@ export function applyMessage(
       return {
         ...prev,
         activeQuestion: {
+          id: msg.id,
           question: msg.question,
           remaining_secs: null,
           done: false,
@@ -165,17 +193,18 @@ export function applyMessage(
     case "extension":
       return { ...prev, extension: msg.data };
 
-    case "user_message":
+    case "token_usage":
       return {
         ...prev,
-        entries: [...prev.entries, { UserMessage: msg.message }],
+        totalPromptTokens: prev.totalPromptTokens + msg.prompt_tokens,
+        totalCompletionTokens: prev.totalCompletionTokens + msg.completion_tokens,
       };
 
-    case "token_usage":
+    case "cost_update":
       return {
         ...prev,
-        totalPromptTokens: prev.totalPromptTokens + msg.prompt_tokens,
-        totalCompletionTokens: prev.totalCompletionTokens + msg.completion_tokens,
+        costUsd: msg.cost_usd,
+        costEstimated: msg.estimated,
       };
 
     case "tool_calls_received":
@@ -187,9 +216,13 @@ export function applyMessage(
     case "tool_cache_hit":
       return {
         ...prev,
-        entries: [
-          ...prev.entries,
-          { ToolExecuting: { name: `${msg.name} (cached)`, arguments: msg.arguments } },
+        logs: [
+          ...prev.logs,
+          {
+            time: new Date().toLocaleTimeString(),
+            level: "Debug",
+            message: `Cache hit: ${msg.name}`,
+          },
         ],
       };
 
@@ -222,6 +255,9 @@ export function applyMessage(
         ],
       };
 
+    case "token_report":
+      return { ...prev, tokenReport: msg.report };
+
     case "checkpoint_saved":
       return {
         ...prev,
@@ -275,19 +311,20 @@ export function applyMessage(
         ],
       };
 
-    case "todo_update": {
-      // Replace existing TodoUpdate entry in-place, or append a new one.
-      const idx = prev.entries.findIndex((e) => "TodoUpdate" in e);
-      if (idx >= 0) {
-        const updated = [...prev.entries];
-        updated[idx] = { TodoUpdate: msg.content };
-        return { ...prev, entries: updated };
-      }
+    case "welcome":
+      return { ...prev, viewerId: msg.viewer_id };
+
+    case "presence":
       return {
         ...prev,
-        entries: [...prev.entries, { TodoUpdate: msg.content }],
+        viewers: msg.viewers,
+        inputHolder: msg.input_holder,
+        // A rejection is stale once the floor opens again.
+        chatRejected: msg.input_holder === null ? null : prev.chatRejected,
       };
-    }
+
+    case "chat_rejected":
+      return { ...prev, chatRejected: msg.reason };
 
     default:
       return prev;
@@ -3,11 +3,28 @@
 /** Mirrors cinch_rs::ui::AgentEntry */
 export type AgentEntry =
   | { Text: string }
-  | { ToolExecuting: { name: string; arguments: string } }
+  | { ToolExecuting: { name: string; arguments: string; summary?: string } }
   | { ToolResult: { name: string; result: string; is_error: boolean } }
+  | { FileEdit: { path: string; diff: string; stats: DiffStats } }
   | { UserMessage: string }
   | { TodoUpdate: string };
 
+/** Mirrors cinch_web::snapshot::OutputEntry: an entry plus threading metadata. */
+export type OutputEntry = AgentEntry & {
+  /** Stable sequence number; unique for the life of the server state. */
+  id: number;
+  /** Id of the entry this one threads under (user message → reply → tool call → result). */
+  parent_id: number | null;
+  round: number;
+  timestamp_ms: number;
+};
+
+/** Mirrors cinch_rs::ui::DiffStats */
+export interface DiffStats {
+  added: number;
+  removed: number;
+}
+
 /** Mirrors cinch_rs::ui::LogLevel */
 export type LogLevel = "Trace" | "Debug" | "Info" | "Warn" | "Error";
 
@@ -25,28 +42,49 @@ export interface QuestionChoice {
   metadata: string;
 }
 
+/** Mirrors cinch_rs::ui::QuestionKind */
+export type QuestionKind =
+  | "Single"
+  | { MultiSelect: { min: number; max: number | null } }
+  | { Number: { min: number | null; max: number | null; integer: boolean } }
+  | { FilePath: { base_dir: string | null; must_exist: boolean } };
+
 /** Mirrors cinch_rs::ui::UserQuestion */
 export interface UserQuestion {
   prompt: string;
   choices: QuestionChoice[];
   editable: boolean;
   max_edit_length: number | null;
+  kind: QuestionKind;
+  default: QuestionResponse | null;
 }
 
 /** Mirrors cinch_rs::ui::QuestionResponse */
 export type QuestionResponse =
   | { Selected: number }
   | { SelectedEdited: { index: number; edited_text: string } }
+  | { MultiSelected: number[] }
+  | { Number: number }
+  | { Path: string }
+  | { FreeText: string }
   | "Skipped"
   | "TimedOut";
 
 /** Mirrors cinch_web::snapshot::ActiveQuestionSnapshot */
 export interface ActiveQuestionSnapshot {
+  id: number;
   question: UserQuestion;
   remaining_secs: number | null;
   done: boolean;
 }
 
+/** Mirrors cinch_web::AttachmentUpload. `data` is base64 or a `data:` URL. */
+export interface ChatAttachment {
+  name: string;
+  media_type?: string;
+  data: string;
+}
+
 /** Mirrors cinch_web::ext::StatusField */
 export interface StatusField {
   label: string;
@@ -54,6 +92,33 @@ export interface StatusField {
   variant: string;
 }
 
+/** Mirrors cinch_rs::context::ContextZone */
+export type ContextZone = "prefix" | "compressed_history" | "middle" | "recency";
+
+/** Mirrors cinch_rs::context::MessageTokens */
+export interface MessageTokens {
+  message_index: number;
+  zone: ContextZone;
+  role: string;
+  tool_name: string | null;
+  tokens: number;
+}
+
+/** Mirrors cinch_rs::context::TokenShare */
+export interface TokenShare {
+  name: string;
+  tokens: number;
+  messages: number;
+}
+
+/** Mirrors cinch_rs::context::TokenReport */
+export interface TokenReport {
+  total_tokens: number;
+  messages: MessageTokens[];
+  by_role: TokenShare[];
+  by_tool: TokenShare[];
+}
+
 /** Mirrors cinch_web::snapshot::UiStateSnapshot */
 export interface UiStateSnapshot {
   phase: string;
@@ -62,18 +127,52 @@ export interface UiStateSnapshot {
   context_pct: number;
   model: string;
   cycle: number;
-  agent_output: AgentEntry[];
+  agent_output: OutputEntry[];
+  output_offset: number;
   streaming_buffer: string;
   logs: LogLine[];
+  log_offset: number;
   running: boolean;
   next_cycle_secs: number | null;
   active_question: ActiveQuestionSnapshot | null;
   extension: Record<string, unknown> | null;
 }
 
+/** Mirrors cinch_web::patch::EntriesPatch */
+export interface EntriesPatch<T> {
+  offset: number;
+  from: number;
+  entries: T[];
+}
+
+/** Mirrors cinch_web::patch::UiStatePatch. Absent fields are unchanged. */
+export interface UiStatePatch {
+  phase?: string;
+  round?: number;
+  max_rounds?: number;
+  context_pct?: number;
+  model?: string;
+  cycle?: number;
+  agent_output?: EntriesPatch<OutputEntry>;
+  streaming_buffer?: string;
+  streaming_append?: string;
+  logs?: EntriesPatch<LogLine>;
+  running?: boolean;
+  next_cycle_secs?: number | null;
+  active_question?: ActiveQuestionSnapshot | null;
+  extension?: Record<string, unknown> | null;
+}
+
 // ── Client-side state ─────────────────────────────────────────────────
 
 /** Flattened client state derived from snapshot + incremental updates. */
+/** A browser connected to the session (see cinch-web `presence`). */
+export interface Viewer {
+  id: number;
+  name: string;
+  typing: boolean;
+}
+
 export interface AgentState {
   phase: string;
   round: number;
@@ -81,16 +180,32 @@ export interface AgentState {
   contextPct: number;
   model: string;
   cycle: number;
-  entries: AgentEntry[];
+  entries: OutputEntry[];
+  /** Server sequence number of `entries[0]`. */
+  outputOffset: number;
   streamingBuffer: string;
   reasoningBuffer: string;
   logs: LogLine[];
+  /** Server sequence number one past the last server log line received. */
+  logEnd: number;
   running: boolean;
   nextCycleSecs: number | null;
   activeQuestion: ActiveQuestionSnapshot | null;
   extension: Record<string, unknown> | null;
   totalPromptTokens: number;
   totalCompletionTokens: number;
+  /** Where the context's tokens go, from the latest round. */
+  tokenReport: TokenReport | null;
+  /** Running run cost in USD; estimated while a round streams. */
+  costUsd: number;
+  costEstimated: boolean;
+  /** This client's viewer id, from the server's welcome message. */
+  viewerId: number | null;
+  viewers: Viewer[];
+  /** Viewer holding the chat input lock; others cannot send meanwhile. */
+  inputHolder: number | null;
+  /** Why the last chat message was refused, if it was. */
+  chatRejected: string | null;
 }
 
 export const INITIAL_STATE: AgentState = {
@@ -101,13 +216,22 @@ export const INITIAL_STATE: AgentState = {
   model: "",
   cycle: 0,
   entries: [],
+  outputOffset: 0,
   streamingBuffer: "",
   reasoningBuffer: "",
   logs: [],
+  logEnd: 0,
   running: true,
   nextCycleSecs: null,
   activeQuestion: null,
   extension: null,
   totalPromptTokens: 0,
   totalCompletionTokens: 0,
+  tokenReport: null,
+  costUsd: 0,
+  costEstimated: false,
+  viewerId: null,
+  viewers: [],
+  inputHolder: null,
+  chatRejected: null,
 };
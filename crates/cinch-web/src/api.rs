@@ -2,25 +2,33 @@
 //!
 //! These complement the WebSocket channel for cases where request/response
 //! semantics are more appropriate (initial state load, question answers).
+//! The question endpoints also serve remote answer channels: a headless agent
+//! publishes its questions to a webhook and whoever receives one answers it
+//! with `POST /api/answer`.
 
 use std::sync::{Arc, Mutex};
 
 use axum::Json;
 use axum::extract::State;
 use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
 use cinch_rs::ui::{QuestionResponse, UiState, push_user_message};
 use serde::Deserialize;
 use tokio::sync::{broadcast, mpsc};
 
+use crate::attachments::{AttachmentError, AttachmentStore, AttachmentUpload, ChatMessage};
 use crate::broadcast::WsMessage;
-use crate::snapshot::UiStateSnapshot;
+use crate::presence::{Presence, PresenceSnapshot};
+use crate::snapshot::{ActiveQuestionSnapshot, UiStateSnapshot};
 
 /// Shared application state passed to all handlers via axum's `State` extractor.
 #[derive(Clone)]
 pub struct AppState {
     pub ui_state: Arc<Mutex<UiState>>,
-    pub chat_tx: mpsc::Sender<String>,
+    pub chat_tx: mpsc::Sender<ChatMessage>,
     pub broadcast_tx: broadcast::Sender<WsMessage>,
+    pub attachments: Arc<AttachmentStore>,
+    pub presence: Arc<Presence>,
 }
 
 /// GET /api/state — Full state snapshot.
@@ -35,16 +43,47 @@ pub async fn get_state(State(app): State<AppState>) -> Json<serde_json::Value> {
     Json(serde_json::to_value(snapshot).unwrap_or_default())
 }
 
-/// Request body for POST /api/answer.
+/// GET /api/question — The pending question.
+///
+/// Returns 200 with the question while one awaits an answer, 204 otherwise.
+pub async fn get_question(State(app): State<AppState>) -> Response {
+    let state = app.ui_state.lock().unwrap();
+    match state.active_question {
+        Some(ref aq) if !aq.done => {
+            Json(ActiveQuestionSnapshot::new(aq, std::time::Instant::now())).into_response()
+        }
+        _ => StatusCode::NO_CONTENT.into_response(),
+    }
+}
+
+/// GET /api/presence — Connected viewers and the input lock holder.
+pub async fn get_presence(State(app): State<AppState>) -> Json<PresenceSnapshot> {
+    Json(app.presence.snapshot())
+}
+
+/// Request body for POST /api/answer: a structured `response`, or `text`
+/// parsed with [`UserQuestion::parse_answer`](cinch_rs::ui::UserQuestion::parse_answer)
+/// (choice numbers like `"2"` or `"1,3"`, a number, a path, free text).
 #[derive(Deserialize)]
 pub struct AnswerRequest {
-    pub response: QuestionResponse,
+    /// Id of the question being answered (from the question snapshot or the
+    /// webhook payload). Required.
+    #[serde(default)]
+    pub question_id: Option<u64>,
+    #[serde(default)]
+    pub response: Option<QuestionResponse>,
+    #[serde(default)]
+    pub text: Option<String>,
 }
 
 /// POST /api/answer — Submit a question response.
 ///
 /// Sets the active question's response and marks it as done.
-/// Returns 204 on success, 404 if no active question exists.
+/// Returns 204 on success, 400 if the body lacks `question_id` or has
+/// neither `response` nor `text`, 404 if no active question exists, 409 if
+/// `question_id` names a different question than the one waiting, and 422
+/// if the answer does not fit the question (see
+/// [`UserQuestion::validate`](cinch_rs::ui::UserQuestion::validate)).
 pub async fn post_answer(
     State(app): State<AppState>,
     Json(body): Json<AnswerRequest>,
@@ -53,8 +92,23 @@ pub async fn post_answer(
     if let Some(ref mut aq) = state.active_question
         && !aq.done
     {
-        aq.response = Some(body.response);
+        match body.question_id {
+            None => return StatusCode::BAD_REQUEST,
+            Some(id) if id != aq.id => return StatusCode::CONFLICT,
+            Some(_) => {}
+        }
+        let response = match (body.response, body.text) {
+            (Some(response), _) => aq.question.validate(&response).map(|()| response),
+            (None, Some(text)) => aq.question.parse_answer(&text),
+            (None, None) => return StatusCode::BAD_REQUEST,
+        };
+        let Ok(response) = response else {
+            return StatusCode::UNPROCESSABLE_ENTITY;
+        };
+        aq.response = Some(response);
         aq.done = true;
+        drop(state);
+        let _ = app.broadcast_tx.send(WsMessage::StateChanged);
         return StatusCode::NO_CONTENT;
     }
     StatusCode::NOT_FOUND
@@ -93,23 +147,39 @@ pub async fn post_control(
 /// Request body for POST /api/chat.
 #[derive(Deserialize)]
 pub struct ChatRequest {
+    #[serde(default)]
     pub message: String,
+    /// Files or images sent with the message (see [`crate::attachments`]).
+    #[serde(default)]
+    pub attachments: Vec<AttachmentUpload>,
 }
 
 /// POST /api/chat — Send a user chat message.
 ///
-/// Pushes the message to the UI state, broadcasts it to all WebSocket clients,
-/// and forwards it to the agent loop via an mpsc channel.
-/// Returns 204 on success, 503 if the agent loop is not consuming messages.
+/// Saves any attachments, pushes the message to the UI state, patches all
+/// WebSocket clients, and forwards it to the agent loop via an mpsc channel.
+/// Returns 204 on success, 400 for an empty message or undecodable
+/// attachments, 409 while a WebSocket viewer holds the input lock (see
+/// [`crate::presence`]), 413 if the attachments exceed the upload limit, and
+/// 503 if the agent loop is not consuming messages.
 pub async fn post_chat(State(app): State<AppState>, Json(body): Json<ChatRequest>) -> StatusCode {
+    if body.message.trim().is_empty() && body.attachments.is_empty() {
+        return StatusCode::BAD_REQUEST;
+    }
+    if !app.presence.may_send(None) {
+        return StatusCode::CONFLICT;
+    }
+    let chat = match app.attachments.receive(body.message, body.attachments) {
+        Ok(chat) => chat,
+        Err(AttachmentError::TooLarge { .. }) => return StatusCode::PAYLOAD_TOO_LARGE,
+        Err(AttachmentError::Invalid(_)) => return StatusCode::BAD_REQUEST,
+    };
     // Push user message to UI state for snapshot persistence.
-    push_user_message(&app.ui_state, &body.message);
-    // Broadcast to all connected WebSocket clients.
-    let _ = app.broadcast_tx.send(WsMessage::UserMessage {
-        message: body.message.clone(),
-    });
+    push_user_message(&app.ui_state, &chat.display());
+    // Patch all connected WebSocket clients.
+    let _ = app.broadcast_tx.send(WsMessage::StateChanged);
     // Forward to the agent loop.
-    match app.chat_tx.try_send(body.message) {
+    match app.chat_tx.try_send(chat) {
         Ok(()) => StatusCode::NO_CONTENT,
         Err(_) => StatusCode::SERVICE_UNAVAILABLE,
     }
@@ -121,9 +191,14 @@ mod tests {
 
     #[test]
     fn answer_request_deserializes() {
-        let json = r#"{"response":{"Selected":1}}"#;
+        let json = r#"{"question_id":3,"response":{"Selected":1}}"#;
         let req: AnswerRequest = serde_json::from_str(json).unwrap();
-        assert_eq!(req.response, QuestionResponse::Selected(1));
+        assert_eq!(req.question_id, Some(3));
+        assert_eq!(req.response, Some(QuestionResponse::Selected(1)));
+
+        let req: AnswerRequest = serde_json::from_str(r#"{"text":"2"}"#).unwrap();
+        assert_eq!(req.text.as_deref(), Some("2"));
+        assert!(req.response.is_none());
     }
 
     #[test]
@@ -0,0 +1,363 @@
+//! Files and images attached to chat messages.
+//!
+//! `POST /api/chat` and `{"type":"chat"}` WebSocket messages may carry
+//! `attachments`: `{"name", "media_type"?, "data"}` where `data` is base64
+//! or a `data:` URL (what a browser produces for a pasted screenshot). The
+//! server decodes each one into [`WebConfig::upload_dir`](crate::WebConfig::upload_dir)
+//! and hands the agent loop a [`ChatMessage`] that references the saved
+//! files. [`ChatMessage::to_message`] turns it into the conversation's user
+//! message: images become image content parts (for vision models), text
+//! files are injected as file context, and other files are referenced by
+//! path.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use cinch_rs::Message;
+use serde::Deserialize;
+
+/// Default cap on the decoded size of one chat message's attachments.
+pub const DEFAULT_MAX_UPLOAD_BYTES: usize = 10 * 1024 * 1024;
+
+/// Text files longer than this are referenced by path instead of inlined.
+const MAX_INLINE_TEXT_BYTES: usize = 100_000;
+
+/// An attachment as sent by the browser.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AttachmentUpload {
+    /// File name, e.g. `screenshot.png`.
+    pub name: String,
+    /// MIME type. Taken from a `data:` URL or guessed from the name when
+    /// absent.
+    #[serde(default)]
+    pub media_type: Option<String>,
+    /// Base64 content, or a base64 `data:` URL.
+    pub data: String,
+}
+
+/// An attachment saved to disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    /// File name as uploaded.
+    pub name: String,
+    /// MIME type.
+    pub media_type: String,
+    /// Where the content was saved.
+    pub path: PathBuf,
+}
+
+impl Attachment {
+    pub fn is_image(&self) -> bool {
+        self.media_type.starts_with("image/")
+    }
+}
+
+/// A chat message from the browser, with any attachments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatMessage {
+    /// The typed text.
+    pub message: String,
+    /// Saved attachments, in upload order.
+    pub attachments: Vec<Attachment>,
+}
+
+impl ChatMessage {
+    /// A message without attachments.
+    pub fn text(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            attachments: Vec::new(),
+        }
+    }
+
+    /// The text shown in the chat stream: the message plus the attachment
+    /// names.
+    pub fn display(&self) -> String {
+        if self.attachments.is_empty() {
+            return self.message.clone();
+        }
+        let names: Vec<&str> = self.attachments.iter().map(|a| a.name.as_str()).collect();
+        let attached = format!("[attached: {}]", names.join(", "));
+        if self.message.is_empty() {
+            attached
+        } else {
+            format!("{}\n{attached}", self.message)
+        }
+    }
+
+    /// The user message for the conversation. Text files are injected as
+    /// `<file>` blocks before the message, images are attached as image
+    /// parts, and anything else is referenced by its saved path.
+    pub fn to_message(&self) -> Message {
+        let mut context = String::new();
+        let mut images = Vec::new();
+        for attachment in &self.attachments {
+            let bytes = match std::fs::read(&attachment.path) {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    context.push_str(&format!(
+                        "[Attachment {} could not be read: {e}]\n\n",
+                        attachment.name
+                    ));
+                    continue;
+                }
+            };
+            if attachment.is_image() {
+                images.push(format!(
+                    "data:{};base64,{}",
+                    attachment.media_type,
+                    STANDARD.encode(&bytes)
+                ));
+                continue;
+            }
+            match String::from_utf8(bytes) {
+                Ok(text) if text.len() <= MAX_INLINE_TEXT_BYTES => context.push_str(&format!(
+                    "<file name=\"{}\" path=\"{}\">\n{}\n</file>\n\n",
+                    attachment.name,
+                    attachment.path.display(),
+                    text.trim_end()
+                )),
+                Ok(text) => context.push_str(&format!(
+                    "[Attached file {} saved at {} ({} bytes, too long to inline)]\n\n",
+                    attachment.name,
+                    attachment.path.display(),
+                    text.len()
+                )),
+                Err(e) => context.push_str(&format!(
+                    "[Attached file {} ({}) saved at {} ({} bytes)]\n\n",
+                    attachment.name,
+                    attachment.media_type,
+                    attachment.path.display(),
+                    e.into_bytes().len()
+                )),
+            }
+        }
+        Message::user(format!("{context}{}", self.message)).with_images(images)
+    }
+}
+
+/// Why attachments were rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttachmentError {
+    /// The decoded attachments exceed the upload limit.
+    TooLarge { limit: usize },
+    /// Bad base64, or the file could not be saved.
+    Invalid(String),
+}
+
+impl std::fmt::Display for AttachmentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooLarge { limit } => write!(f, "attachments exceed {limit} bytes"),
+            Self::Invalid(reason) => f.write_str(reason),
+        }
+    }
+}
+
+/// Decodes uploads and saves them under one directory.
+pub(crate) struct AttachmentStore {
+    dir: PathBuf,
+    max_bytes: usize,
+    counter: AtomicU64,
+}
+
+impl AttachmentStore {
+    pub(crate) fn new(dir: PathBuf, max_bytes: usize) -> Self {
+        Self {
+            dir,
+            max_bytes,
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    /// Build the [`ChatMessage`] for `message`, saving its attachments.
+    pub(crate) fn receive(
+        &self,
+        message: String,
+        uploads: Vec<AttachmentUpload>,
+    ) -> Result<ChatMessage, AttachmentError> {
+        let mut decoded = Vec::with_capacity(uploads.len());
+        let mut total = 0;
+        for upload in uploads {
+            let (media_type, data) = split_data_url(&upload.data);
+            let bytes = STANDARD
+                .decode(data.trim())
+                .map_err(|e| AttachmentError::Invalid(format!("{}: {e}", upload.name)))?;
+            total += bytes.len();
+            if total > self.max_bytes {
+                return Err(AttachmentError::TooLarge {
+                    limit: self.max_bytes,
+                });
+            }
+            let media_type = upload
+                .media_type
+                .or(media_type)
+                .unwrap_or_else(|| guess_media_type(&upload.name, &bytes));
+            decoded.push((upload.name, media_type, bytes));
+        }
+
+        let mut attachments = Vec::with_capacity(decoded.len());
+        for (name, media_type, bytes) in decoded {
+            let path = self.dir.join(self.file_name(&name));
+            std::fs::create_dir_all(&self.dir)
+                .and_then(|()| std::fs::write(&path, &bytes))
+                .map_err(|e| AttachmentError::Invalid(format!("saving {name}: {e}")))?;
+            attachments.push(Attachment {
+                name,
+                media_type,
+                path,
+            });
+        }
+        Ok(ChatMessage {
+            message,
+            attachments,
+        })
+    }
+
+    /// `<unix-millis>-<n>-<sanitized name>`, unique within the directory.
+    fn file_name(&self, name: &str) -> String {
+        let stamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let n = self.counter.fetch_add(1, Ordering::Relaxed);
+        let base = Path::new(name)
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let safe: String = base
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let safe = if safe.trim_matches('.').is_empty() {
+            "attachment".to_string()
+        } else {
+            safe
+        };
+        format!("{stamp}-{n}-{safe}")
+    }
+}
+
+/// Split `data:<type>;base64,<data>` into its media type and payload.
+fn split_data_url(data: &str) -> (Option<String>, &str) {
+    let Some(rest) = data.strip_prefix("data:") else {
+        return (None, data);
+    };
+    match rest.split_once(";base64,") {
+        Some((media_type, payload)) => (
+            (!media_type.is_empty()).then(|| media_type.to_string()),
+            payload,
+        ),
+        None => (None, data),
+    }
+}
+
+fn guess_media_type(name: &str, bytes: &[u8]) -> String {
+    let extension = Path::new(name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase);
+    let media_type = match extension.as_deref() {
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        _ if std::str::from_utf8(bytes).is_ok() => "text/plain",
+        _ => "application/octet-stream",
+    };
+    media_type.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upload(name: &str, data: &str) -> AttachmentUpload {
+        AttachmentUpload {
+            name: name.into(),
+            media_type: None,
+            data: data.into(),
+        }
+    }
+
+    #[test]
+    fn saves_images_and_text_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = AttachmentStore::new(dir.path().join("uploads"), 1024);
+        let png = STANDARD.encode([0x89, b'P', b'N', b'G']);
+        let chat = store
+            .receive(
+                "Why is this broken?".into(),
+                vec![
+                    upload("shot", &format!("data:image/png;base64,{png}")),
+                    upload("../log.txt", &STANDARD.encode("panic at line 3\n")),
+                ],
+            )
+            .unwrap();
+
+        assert_eq!(chat.attachments[0].media_type, "image/png");
+        assert!(chat.attachments[0].is_image());
+        assert_eq!(chat.attachments[1].media_type, "text/plain");
+        assert!(
+            chat.attachments[1]
+                .path
+                .starts_with(dir.path().join("uploads"))
+        );
+        assert!(
+            chat.attachments[1]
+                .path
+                .to_string_lossy()
+                .ends_with("-1-log.txt")
+        );
+        assert_eq!(
+            chat.display(),
+            "Why is this broken?\n[attached: shot, ../log.txt]"
+        );
+
+        let message = chat.to_message();
+        assert_eq!(message.images, vec![format!("data:image/png;base64,{png}")]);
+        let content = message.content.unwrap();
+        assert!(content.starts_with("<file name=\"../log.txt\""));
+        assert!(content.contains("panic at line 3\n</file>"));
+        assert!(content.ends_with("\n\nWhy is this broken?"));
+    }
+
+    #[test]
+    fn rejects_bad_and_oversized_uploads() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = AttachmentStore::new(dir.path().to_path_buf(), 4);
+        assert!(matches!(
+            store.receive(String::new(), vec![upload("a.txt", "not base64!")]),
+            Err(AttachmentError::Invalid(_))
+        ));
+        assert_eq!(
+            store.receive(
+                String::new(),
+                vec![upload("a.txt", &STANDARD.encode("12345"))]
+            ),
+            Err(AttachmentError::TooLarge { limit: 4 })
+        );
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn text_message_has_no_attachments() {
+        let chat = ChatMessage::text("hi");
+        assert_eq!(chat.display(), "hi");
+        let message = chat.to_message();
+        assert_eq!(message.content.as_deref(), Some("hi"));
+        assert!(message.images.is_empty());
+    }
+}
@@ -3,19 +3,26 @@
 //! [`WebBroadcastHandler`] intercepts [`HarnessEvent`] variants and serializes
 //! them into [`WsMessage`] values, broadcasting to all connected WebSocket
 //! clients via a `tokio::sync::broadcast` channel.
+//!
+//! Events that [`UiEventHandler`](cinch_rs::ui::event_handler::UiEventHandler)
+//! records in [`UiState`] (text, tool calls, round progress, phase) are not
+//! forwarded one by one. The handler broadcasts [`WsMessage::StateChanged`]
+//! instead, and each connection sends its client a [`UiStatePatch`] with
+//! whatever changed. Only transient events that are not part of `UiState`
+//! (reasoning deltas, token usage, cache hits, ...) travel as their own
+//! messages.
 
 use std::sync::{Arc, Mutex};
 
 use cinch_rs::agent::events::{EventHandler, EventResponse, HarnessEvent};
+use cinch_rs::context::TokenReport;
 use cinch_rs::ui::UiState;
 use serde::Serialize;
 use tokio::sync::broadcast;
 
 use crate::ext::WebExtensionRenderer;
-
-/// Maximum tool result size sent over WebSocket (8 KB).
-/// Full results remain in `UiState` and can be fetched via `/api/state`.
-const MAX_WS_TOOL_RESULT_BYTES: usize = 8 * 1024;
+use crate::patch::UiStatePatch;
+use crate::presence::{Presence, Viewer};
 
 /// A message sent from the server to WebSocket clients.
 ///
@@ -25,6 +32,12 @@ const MAX_WS_TOOL_RESULT_BYTES: usize = 8 * 1024;
 pub enum WsMessage {
     /// Full state snapshot (sent on initial connect and after reconnect).
     Snapshot { data: serde_json::Value },
+    /// Incremental changes since the client's last snapshot or patch.
+    Patch { data: Box<UiStatePatch> },
+    /// The shared [`UiState`] changed. Each connection replaces this with a
+    /// [`WsMessage::Patch`] computed for its own client; it is never sent to
+    /// clients as-is.
+    StateChanged,
     /// Complete LLM text block.
     Text { text: String },
     /// Streaming token delta.
@@ -51,6 +64,7 @@ pub enum WsMessage {
     Phase { phase: String },
     /// A question has been presented to the user.
     Question {
+        id: u64,
         question: cinch_rs::ui::UserQuestion,
     },
     /// The active question has been resolved.
@@ -68,6 +82,8 @@ pub enum WsMessage {
         prompt_tokens: u32,
         completion_tokens: u32,
     },
+    /// Running run cost, estimated while a round streams.
+    CostUpdate { cost_usd: f64, estimated: bool },
     /// The agent received tool calls this round.
     ToolCallsReceived { round: u32, count: usize },
     /// A tool result was served from cache.
@@ -79,6 +95,9 @@ pub enum WsMessage {
     },
     /// Context compaction completed.
     Compaction { compaction_number: usize },
+    /// Where the context's tokens go, sent with each round's context
+    /// snapshot.
+    TokenReport { report: TokenReport },
     /// Model routing selected a different model.
     ModelRouted { model: String, round: u32 },
     /// Checkpoint saved after a round.
@@ -95,6 +114,28 @@ pub enum WsMessage {
     ApprovalRequired { name: String, arguments: String },
     /// Consolidated todo list update (replaces previous state in the client).
     TodoUpdate { content: String },
+    /// Sent to a client right after the snapshot: its own viewer id.
+    Welcome { viewer_id: u64 },
+    /// Who is connected and who holds the input lock (see
+    /// [`crate::presence`]).
+    Presence {
+        viewers: Vec<Viewer>,
+        input_holder: Option<u64>,
+    },
+    /// Sent to a client whose chat message was refused because another
+    /// viewer holds the input lock.
+    ChatRejected { reason: String },
+}
+
+impl WsMessage {
+    /// A [`WsMessage::Presence`] for the current state of `presence`.
+    pub fn presence(presence: &Presence) -> Self {
+        let snapshot = presence.snapshot();
+        Self::Presence {
+            viewers: snapshot.viewers,
+            input_holder: snapshot.input_holder,
+        }
+    }
 }
 
 /// Event handler that broadcasts harness events to WebSocket clients.
@@ -147,27 +188,16 @@ impl WebBroadcastHandler {
 impl EventHandler for WebBroadcastHandler {
     fn on_event(&self, event: &HarnessEvent<'_>) -> Option<EventResponse> {
         match event {
-            HarnessEvent::RoundStart {
-                round,
-                max_rounds,
-                context_usage,
-                ..
-            } => {
-                self.broadcast(WsMessage::Round {
-                    round: *round,
-                    max_rounds: *max_rounds,
-                    context_pct: context_usage.usage_pct,
-                });
-            }
-            HarnessEvent::Text(text) => {
-                self.broadcast(WsMessage::Text {
-                    text: text.to_string(),
-                });
-            }
-            HarnessEvent::TextDelta(delta) => {
-                self.broadcast(WsMessage::TextDelta {
-                    delta: delta.to_string(),
-                });
+            // Recorded in UiState by UiEventHandler; clients get a patch.
+            HarnessEvent::RoundStart { .. }
+            | HarnessEvent::Text(_)
+            | HarnessEvent::TextDelta(_)
+            | HarnessEvent::ToolExecuting { .. }
+            | HarnessEvent::FileEdited { .. }
+            | HarnessEvent::PhaseTransition { .. }
+            | HarnessEvent::PlanSubmitted { .. }
+            | HarnessEvent::StreamInterrupted { .. } => {
+                self.broadcast(WsMessage::StateChanged);
             }
             HarnessEvent::ToolCallsReceived { round, count } => {
                 self.broadcast(WsMessage::ToolCallsReceived {
@@ -175,46 +205,8 @@ impl EventHandler for WebBroadcastHandler {
                     count: *count,
                 });
             }
-            HarnessEvent::ToolExecuting {
-                name, arguments, ..
-            } => {
-                // The todo tool updates in-place; skip ToolExecuting so the
-                // client shows only the consolidated checklist.
-                if *name != "todo" {
-                    self.broadcast(WsMessage::Phase {
-                        phase: format!("Tool: {name}"),
-                    });
-                    self.broadcast(WsMessage::ToolExecuting {
-                        name: name.to_string(),
-                        arguments: arguments.to_string(),
-                    });
-                }
-            }
-            HarnessEvent::ToolResult { name, result, .. } => {
-                if *name == "todo" {
-                    self.broadcast(WsMessage::TodoUpdate {
-                        content: result.to_string(),
-                    });
-                } else {
-                    let is_error = result.starts_with("Error") || result.starts_with("error:");
-                    // Truncate large results for WebSocket transport.
-                    #[allow(clippy::string_slice)] // end from floor_char_boundary
-                    let truncated = if result.len() > MAX_WS_TOOL_RESULT_BYTES {
-                        let end = result.floor_char_boundary(MAX_WS_TOOL_RESULT_BYTES);
-                        let cut = &result[..end];
-                        format!(
-                            "{cut}\n... (truncated, {total} bytes total)",
-                            total = result.len()
-                        )
-                    } else {
-                        result.to_string()
-                    };
-                    self.broadcast(WsMessage::ToolResult {
-                        name: name.to_string(),
-                        result: truncated,
-                        is_error,
-                    });
-                }
+            HarnessEvent::ToolResult { .. } => {
+                self.broadcast(WsMessage::StateChanged);
                 // Tool results may change domain state (e.g. tweet drafted count).
                 self.broadcast_extension();
             }
@@ -227,10 +219,21 @@ impl EventHandler for WebBroadcastHandler {
                     completion_tokens: *completion_tokens,
                 });
             }
+            HarnessEvent::CostUpdate {
+                cost_usd,
+                estimated,
+                ..
+            } => {
+                self.broadcast(WsMessage::CostUpdate {
+                    cost_usd: *cost_usd,
+                    estimated: *estimated,
+                });
+            }
             HarnessEvent::Reasoning(text) => {
                 self.broadcast(WsMessage::Reasoning {
                     text: text.to_string(),
                 });
+                self.broadcast(WsMessage::StateChanged);
             }
             HarnessEvent::ReasoningDelta(delta) => {
                 self.broadcast(WsMessage::ReasoningDelta {
@@ -238,6 +241,7 @@ impl EventHandler for WebBroadcastHandler {
                 });
             }
             HarnessEvent::Finished => {
+                self.broadcast(WsMessage::StateChanged);
                 self.broadcast(WsMessage::Finished);
             }
             HarnessEvent::EmptyResponse {
@@ -251,10 +255,33 @@ impl EventHandler for WebBroadcastHandler {
                     max_retries: *max_retries,
                 });
             }
-            HarnessEvent::RoundLimitReached { .. } => {
+            HarnessEvent::DegenerateResponse {
+                round,
+                reason,
+                attempt,
+                max_retries,
+            } => {
+                self.broadcast(WsMessage::Phase {
+                    phase: format!(
+                        "Round {round}: degenerate response ({reason}), retrying \
+                         ({attempt}/{max_retries})"
+                    ),
+                });
+            }
+            HarnessEvent::LengthContinuation {
+                round,
+                attempt,
+                max_continuations,
+            } => {
                 self.broadcast(WsMessage::Phase {
-                    phase: "Round limit reached".to_string(),
+                    phase: format!(
+                        "Round {round}: response truncated, continuing \
+                         ({attempt}/{max_continuations})"
+                    ),
                 });
+            }
+            HarnessEvent::RoundLimitReached { .. } | HarnessEvent::CostLimitReached { .. } => {
+                self.broadcast(WsMessage::StateChanged);
                 self.broadcast(WsMessage::Finished);
             }
             HarnessEvent::Eviction {
@@ -271,6 +298,11 @@ impl EventHandler for WebBroadcastHandler {
                     compaction_number: *compaction_number,
                 });
             }
+            HarnessEvent::CompactionRejected { reason, .. } => {
+                self.broadcast(WsMessage::Phase {
+                    phase: format!("Compaction summary rejected: {reason}"),
+                });
+            }
             HarnessEvent::PreCompaction => {
                 // No WebSocket message needed for pre-compaction events.
             }
@@ -295,20 +327,62 @@ impl EventHandler for WebBroadcastHandler {
                     arguments: arguments.to_string(),
                 });
             }
-            HarnessEvent::ApprovalRequired { name, arguments } => {
-                self.broadcast(WsMessage::ApprovalRequired {
-                    name: name.to_string(),
-                    arguments: arguments.to_string(),
+            HarnessEvent::ToolRoundAborted {
+                failed,
+                skipped,
+                rolled_back,
+            } => {
+                self.broadcast(WsMessage::Phase {
+                    phase: format!(
+                        "{failed} failed: skipped {skipped} calls, rolled back {rolled_back} files"
+                    ),
+                });
+            }
+            HarnessEvent::ResourceLimitExceeded {
+                name,
+                limit,
+                detail,
+            } => {
+                self.broadcast(WsMessage::Phase {
+                    phase: format!("{name} hit the {limit} limit: {detail}"),
+                });
+            }
+            HarnessEvent::ToolLoopDetected { name, repeats, .. } => {
+                self.broadcast(WsMessage::Phase {
+                    phase: format!("Loop detected: {name} repeated {repeats} times"),
+                });
+            }
+            HarnessEvent::ToolBudgetExceeded { name, category, .. } => {
+                self.broadcast(WsMessage::Phase {
+                    phase: format!("{name} refused: {category} budget used up"),
+                });
+            }
+            HarnessEvent::ToolFailureEscalated {
+                name,
+                failures,
+                level,
+            } => {
+                self.broadcast(WsMessage::Phase {
+                    phase: format!("{name} failed {failures} times: {}", level.label()),
                 });
             }
-            HarnessEvent::PhaseTransition { from, to } => {
+            HarnessEvent::WarmStartStarted { steps } => {
                 self.broadcast(WsMessage::Phase {
-                    phase: format!("{from:?} → {to:?}"),
+                    phase: format!("Gathering context: {}", steps.join(", ")),
                 });
             }
-            HarnessEvent::PlanSubmitted { summary } => {
-                self.broadcast(WsMessage::Text {
-                    text: format!("[plan] {summary}"),
+            HarnessEvent::WarmStartFinished { .. } => {
+                // The first round's phase update follows immediately.
+            }
+            HarnessEvent::SpeculationResolved { .. } => {
+                // Internal latency optimization; not forwarded over WebSocket.
+            }
+            HarnessEvent::ApprovalRequired {
+                name, arguments, ..
+            } => {
+                self.broadcast(WsMessage::ApprovalRequired {
+                    name: name.to_string(),
+                    arguments: arguments.to_string(),
                 });
             }
             HarnessEvent::MemoryConsolidated {
@@ -331,10 +405,33 @@ impl EventHandler for WebBroadcastHandler {
                     ),
                 });
             }
+            HarnessEvent::ContextSnapshot { messages, .. } => {
+                self.broadcast(WsMessage::TokenReport {
+                    report: TokenReport::from_details(messages),
+                });
+            }
             HarnessEvent::SessionStarting { .. }
             | HarnessEvent::SessionFinishing { .. }
-            | HarnessEvent::ContextSnapshot { .. } => {
-                // Session lifecycle / context snapshot events not forwarded over WebSocket.
+            | HarnessEvent::RoundEnd { .. } => {
+                // Session lifecycle / round log events not forwarded over WebSocket.
+            }
+            HarnessEvent::PacingEngaged { round, remaining } => {
+                self.broadcast(WsMessage::Phase {
+                    phase: format!(
+                        "Round {round}: wrapping up ({:.0}s left)",
+                        remaining.as_secs_f64()
+                    ),
+                });
+            }
+            HarnessEvent::Handoff { from, to, .. } => {
+                self.broadcast(WsMessage::Phase {
+                    phase: format!("Handoff: {from} → {to}"),
+                });
+            }
+            HarnessEvent::PromptPrefixBroken { round, cause, .. } => {
+                self.broadcast(WsMessage::Phase {
+                    phase: format!("Round {round}: prompt cache prefix broken ({cause})"),
+                });
             }
             HarnessEvent::PromptCacheStats {
                 cached_tokens,
@@ -412,6 +509,32 @@ mod tests {
         assert_eq!(json["completion_tokens"], 50);
     }
 
+    #[test]
+    fn ws_message_token_report_serializes() {
+        let mut layout = cinch_rs::context::layout::ContextLayout::new(200_000);
+        layout.set_prefix(vec![cinch_rs::Message::system("You are helpful.")]);
+        let msg = WsMessage::TokenReport {
+            report: layout.token_report(),
+        };
+        let json = serde_json::to_value(&msg).unwrap();
+        assert_eq!(json["type"], "token_report");
+        assert_eq!(json["report"]["messages"][0]["zone"], "prefix");
+        assert_eq!(json["report"]["by_role"][0]["name"], "system");
+    }
+
+    #[test]
+    fn ws_message_presence_serializes() {
+        let presence = Presence::new();
+        let id = presence.join();
+        presence.rename(id, "Ana");
+        presence.set_typing(id, true);
+        let json = serde_json::to_value(WsMessage::presence(&presence)).unwrap();
+        assert_eq!(json["type"], "presence");
+        assert_eq!(json["viewers"][0]["name"], "Ana");
+        assert_eq!(json["viewers"][0]["typing"], true);
+        assert_eq!(json["input_holder"], id);
+    }
+
     #[test]
     fn ws_message_reasoning_delta_serializes() {
         let msg = WsMessage::ReasoningDelta {
@@ -422,6 +545,19 @@ mod tests {
         assert_eq!(json["delta"], "thinking...");
     }
 
+    #[test]
+    fn ws_message_patch_serializes() {
+        let msg = WsMessage::Patch {
+            data: Box::new(UiStatePatch {
+                phase: Some("Working".into()),
+                ..Default::default()
+            }),
+        };
+        let json = serde_json::to_value(&msg).unwrap();
+        assert_eq!(json["type"], "patch");
+        assert_eq!(json["data"], serde_json::json!({"phase": "Working"}));
+    }
+
     #[test]
     fn broadcast_handler_creation() {
         let (sender, _) = broadcast::channel(16);
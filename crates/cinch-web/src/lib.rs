@@ -19,8 +19,9 @@
 //! println!("Web UI: http://{addr}");
 //!
 //! // Read user messages sent from the browser:
-//! while let Some(msg) = chat_rx.recv().await {
-//!     println!("User said: {msg}");
+//! while let Some(chat) = chat_rx.recv().await {
+//!     println!("User said: {}", chat.display());
+//!     conversation.push(chat.to_message());
 //! }
 //! ```
 //!
@@ -32,26 +33,42 @@
 //!           Arc<Mutex<UiState>> ◀── /api/answer, /api/control ────────────┘
 //! ```
 //!
+//! Each client receives a [`UiStateSnapshot`] on connect and then
+//! [`UiStatePatch`]es carrying only what changed in the shared state (new
+//! output entries, phase, question, ...), alongside transient messages such
+//! as streaming deltas and token usage.
+//!
 //! The [`WebBroadcastHandler`] implements [`EventHandler`](cinch_rs::agent::events::EventHandler)
 //! and converts harness events into serialized WebSocket messages. Compose it
 //! alongside [`UiEventHandler`](cinch_rs::ui::event_handler::UiEventHandler)
 //! in a [`CompositeEventHandler`](cinch_rs::agent::CompositeEventHandler).
+//!
+//! Several browsers can watch one session. [`presence`] tracks who is
+//! connected and lets one viewer at a time hold the chat input, so two
+//! people don't send conflicting messages to the agent.
 
 mod api;
+pub mod attachments;
 pub mod broadcast;
 pub mod ext;
+pub mod patch;
+pub mod presence;
 mod server;
 pub mod snapshot;
 mod ws;
 
+pub use attachments::{Attachment, AttachmentUpload, ChatMessage};
 pub use broadcast::{WebBroadcastHandler, WsMessage};
 pub use ext::{ChoiceMetadata, NoWebExtension, StatusField, WebExtensionRenderer};
+pub use patch::{PatchTracker, UiStatePatch};
+pub use presence::{Presence, PresenceSnapshot, Viewer};
 pub use snapshot::UiStateSnapshot;
 
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
+use cinch_rs::agent::config::HarnessSessionConfig;
 use cinch_rs::ui::UiState;
 
 /// Configuration for the web server.
@@ -65,9 +82,17 @@ pub struct WebConfig {
     pub static_dir: Option<PathBuf>,
     /// Maximum WebSocket broadcast channel capacity. Default: 256.
     ///
-    /// Clients that fall behind by this many messages receive a fresh
-    /// state snapshot to resynchronize.
+    /// Clients that fall behind by this many messages are resynchronized
+    /// with a state patch.
     pub broadcast_capacity: usize,
+    /// Directory chat attachments are saved to. Default: `uploads` under the
+    /// harness's default sessions directory (`.agents/sessions/uploads`), so
+    /// attachments stay with the sessions that reference them. Point it
+    /// elsewhere when the harness uses another sessions directory.
+    pub upload_dir: PathBuf,
+    /// Maximum decoded size of one chat message's attachments. Default:
+    /// [`DEFAULT_MAX_UPLOAD_BYTES`](attachments::DEFAULT_MAX_UPLOAD_BYTES).
+    pub max_upload_bytes: usize,
 }
 
 impl Default for WebConfig {
@@ -76,6 +101,8 @@ impl Default for WebConfig {
             bind_addr: SocketAddr::from(([127, 0, 0, 1], 3001)),
             static_dir: None,
             broadcast_capacity: 256,
+            upload_dir: HarnessSessionConfig::default().sessions_dir.join("uploads"),
+            max_upload_bytes: attachments::DEFAULT_MAX_UPLOAD_BYTES,
         }
     }
 }
@@ -84,7 +111,8 @@ impl Default for WebConfig {
 ///
 /// Returns the bound address and a receiver for chat messages sent from the
 /// browser (via `POST /api/chat` or `{"type":"chat"}` WebSocket messages).
-/// Read from the receiver in your agent loop to drive conversation turns.
+/// Read from the receiver in your agent loop to drive conversation turns;
+/// [`ChatMessage::to_message`] includes any attachments.
 ///
 /// The server runs until the Tokio runtime shuts down.
 ///
@@ -98,9 +126,16 @@ pub async fn spawn_web(
     ui_state: Arc<Mutex<UiState>>,
     broadcast_tx: tokio::sync::broadcast::Sender<WsMessage>,
     config: WebConfig,
-) -> (SocketAddr, tokio::sync::mpsc::Receiver<String>) {
+) -> (SocketAddr, tokio::sync::mpsc::Receiver<ChatMessage>) {
     let (chat_tx, chat_rx) = tokio::sync::mpsc::channel(32);
-    let router = server::build_router(ui_state, broadcast_tx, chat_tx, config.static_dir);
+    let attachments = attachments::AttachmentStore::new(config.upload_dir, config.max_upload_bytes);
+    let router = server::build_router(
+        ui_state,
+        broadcast_tx,
+        chat_tx,
+        attachments,
+        config.static_dir,
+    );
     let addr = server::start_server(router, config.bind_addr).await;
     (addr, chat_rx)
 }
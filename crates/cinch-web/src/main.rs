@@ -26,6 +26,10 @@
 //! ```json
 //! {"message": "What is creatine monohydrate?"}
 //! ```
+//!
+//! Either form accepts `"attachments": [{"name": "label.png", "data": "<base64 or data: URL>"}]`;
+//! images are sent to the model as image parts (use a vision model) and text
+//! files are included as file context.
 
 use std::sync::{Arc, Mutex};
 
@@ -102,8 +106,8 @@ research, or any facts you are unsure about. Cite URLs from results when \
 available. Do not disclaim that you lack web access.";
     let mut conversation = vec![Message::system(system_prompt)];
 
-    while let Some(user_message) = chat_rx.recv().await {
-        println!("> {user_message}");
+    while let Some(chat) = chat_rx.recv().await {
+        println!("> {}", chat.display());
 
         // Reset UI state for the new turn (keep agent_output for chat history).
         {
@@ -114,7 +118,7 @@ available. Do not disclaim that you lack web access.";
             s.running = true;
         }
 
-        conversation.push(Message::user(&user_message));
+        conversation.push(chat.to_message());
 
         let mut config = HarnessConfig::new(&args.model, system_prompt)
             .with_max_rounds(u32::MAX)
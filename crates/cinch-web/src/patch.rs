@@ -0,0 +1,398 @@
+//! Incremental [`UiState`] sync for WebSocket clients.
+//!
+//! Resending a full [`UiStateSnapshot`] whenever the state changes gets large
+//! once a session has hundreds of output entries. Instead, each WebSocket
+//! connection keeps a [`PatchTracker`] recording what its client was last
+//! sent, and turns every change into a [`UiStatePatch`] holding only the
+//! differences:
+//!
+//! - Small fields (phase, round, question, extension, ...) are compared by
+//!   value and included only when they changed.
+//! - `agent_output` and `logs` are append logs addressed by stable sequence
+//!   numbers ([`UiState::output_offset`], [`UiState::log_offset`]), so a patch
+//!   carries only the new entries plus any entry replaced in place. Output
+//!   entries are sent as [`OutputEntry`]s, with the sequence number as their
+//!   `id` and their thread parent, round, and timestamp.
+//!
+//! Applying a patch is idempotent: a client that receives changes it already
+//! has (e.g. right after its initial snapshot) ends up in the same state.
+
+use std::time::Instant;
+
+use cinch_rs::ui::{AgentEntry, LogLine, UiState, UserQuestion};
+use serde::Serialize;
+
+use crate::snapshot::{ActiveQuestionSnapshot, OutputEntry, SNAPSHOT_MAX_LOGS, secs_until};
+
+/// Maximum tool result or diff size sent in a patch (8 KB).
+/// Full results remain in `UiState` and can be fetched via `/api/state`.
+const MAX_WS_TOOL_RESULT_BYTES: usize = 8 * 1024;
+
+/// New or rewritten entries of a sequence-numbered list.
+#[derive(Clone, Debug, Serialize)]
+pub struct EntriesPatch<T> {
+    /// Sequence number of the oldest entry the server still holds. Clients
+    /// drop anything older.
+    pub offset: u64,
+    /// Sequence number of `entries[0]`. Clients replace everything from this
+    /// sequence number on with `entries`.
+    pub from: u64,
+    pub entries: Vec<T>,
+}
+
+/// Changes to a [`UiStateSnapshot`](crate::UiStateSnapshot) since the
+/// client's last update. Absent fields are unchanged; `null` clears an
+/// optional field.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct UiStatePatch {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phase: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub round: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_rounds: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_pct: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cycle: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_output: Option<EntriesPatch<OutputEntry>>,
+    /// Replacement streaming buffer (e.g. cleared when a text block completes).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub streaming_buffer: Option<String>,
+    /// Text appended to the streaming buffer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub streaming_append: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logs: Option<EntriesPatch<LogLine>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub running: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cycle_secs: Option<Option<f64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_question: Option<Option<ActiveQuestionSnapshot>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extension: Option<Option<serde_json::Value>>,
+}
+
+/// What one client was last sent, used to compute its next [`UiStatePatch`].
+pub struct PatchTracker {
+    phase: String,
+    round: u32,
+    max_rounds: u32,
+    context_pct: f64,
+    model: String,
+    cycle: u32,
+    output_offset: u64,
+    output_end: u64,
+    output_edits: u64,
+    streaming_buffer: String,
+    log_end: u64,
+    running: bool,
+    next_cycle_at: Option<Instant>,
+    question: Option<(UserQuestion, bool)>,
+    extension: Option<serde_json::Value>,
+}
+
+impl PatchTracker {
+    /// Start tracking a client that has just been sent a snapshot of `state`.
+    pub fn new(state: &UiState) -> Self {
+        Self {
+            phase: state.phase.clone(),
+            round: state.round,
+            max_rounds: state.max_rounds,
+            context_pct: state.context_pct,
+            model: state.model.clone(),
+            cycle: state.cycle,
+            output_offset: state.output_offset,
+            output_end: state.output_offset + state.agent_output.len() as u64,
+            output_edits: state.output_edits,
+            streaming_buffer: state.streaming_buffer.clone(),
+            log_end: state.log_offset + state.logs.len() as u64,
+            running: state.running,
+            next_cycle_at: state.next_cycle_at,
+            question: state
+                .active_question
+                .as_ref()
+                .map(|aq| (aq.question.clone(), aq.done)),
+            extension: state.extensions.to_json(),
+        }
+    }
+
+    /// Compute the patch bringing the client up to date with `state`, and
+    /// assume it is delivered. Returns `None` when nothing changed.
+    pub fn diff(&mut self, state: &UiState) -> Option<UiStatePatch> {
+        let mut patch = UiStatePatch::default();
+        let mut changed = false;
+
+        macro_rules! scalar {
+            ($field:ident) => {
+                if self.$field != state.$field {
+                    self.$field = state.$field.clone();
+                    patch.$field = Some(state.$field.clone());
+                    changed = true;
+                }
+            };
+        }
+        scalar!(phase);
+        scalar!(round);
+        scalar!(max_rounds);
+        scalar!(context_pct);
+        scalar!(model);
+        scalar!(cycle);
+        scalar!(running);
+
+        if self.streaming_buffer != state.streaming_buffer {
+            match state
+                .streaming_buffer
+                .strip_prefix(self.streaming_buffer.as_str())
+            {
+                Some(appended) => patch.streaming_append = Some(appended.to_string()),
+                None => patch.streaming_buffer = Some(state.streaming_buffer.clone()),
+            }
+            self.streaming_buffer = state.streaming_buffer.clone();
+            changed = true;
+        }
+
+        if let Some(output) = self.diff_output(state) {
+            patch.agent_output = Some(output);
+            changed = true;
+        }
+        if let Some(logs) = self.diff_logs(state) {
+            patch.logs = Some(logs);
+            changed = true;
+        }
+
+        let now = Instant::now();
+        if self.next_cycle_at != state.next_cycle_at {
+            self.next_cycle_at = state.next_cycle_at;
+            patch.next_cycle_secs = Some(state.next_cycle_at.map(|t| secs_until(t, now)));
+            changed = true;
+        }
+
+        let question = state.active_question.as_ref();
+        let question_changed = match (&self.question, question) {
+            (None, None) => false,
+            (Some((q, done)), Some(aq)) => *q != aq.question || *done != aq.done,
+            _ => true,
+        };
+        if question_changed {
+            self.question = question.map(|aq| (aq.question.clone(), aq.done));
+            patch.active_question = Some(question.map(|aq| ActiveQuestionSnapshot::new(aq, now)));
+            changed = true;
+        }
+
+        let extension = state.extensions.to_json();
+        if self.extension != extension {
+            patch.extension = Some(extension.clone());
+            self.extension = extension;
+            changed = true;
+        }
+
+        changed.then_some(patch)
+    }
+
+    fn diff_output(&mut self, state: &UiState) -> Option<EntriesPatch<OutputEntry>> {
+        let offset = state.output_offset;
+        let end = offset + state.agent_output.len() as u64;
+        let mut from = self.output_end;
+        if state.output_edits != self.output_edits {
+            // A single in-place edit since the last patch is resent from its
+            // position; anything else resends the whole window.
+            from = if state.output_edits == self.output_edits + 1 {
+                from.min(state.last_output_edit)
+            } else {
+                offset
+            };
+        }
+        let from = from.clamp(offset, end);
+        if from == end && end == self.output_end && offset == self.output_offset {
+            self.output_edits = state.output_edits;
+            return None;
+        }
+        self.output_offset = offset;
+        self.output_end = end;
+        self.output_edits = state.output_edits;
+        let start = (from - offset) as usize;
+        Some(EntriesPatch {
+            offset,
+            from,
+            entries: (start..state.agent_output.len())
+                .map(|i| OutputEntry::at(state, i, truncate_for_ws))
+                .collect(),
+        })
+    }
+
+    fn diff_logs(&mut self, state: &UiState) -> Option<EntriesPatch<LogLine>> {
+        let offset = state.log_offset;
+        let end = offset + state.logs.len() as u64;
+        if end == self.log_end {
+            return None;
+        }
+        // Like snapshots, never send more than the most recent lines.
+        let from = self
+            .log_end
+            .max(end.saturating_sub(SNAPSHOT_MAX_LOGS as u64))
+            .clamp(offset, end);
+        self.log_end = end;
+        let start = (from - offset) as usize;
+        Some(EntriesPatch {
+            offset,
+            from,
+            entries: state.logs[start..].to_vec(),
+        })
+    }
+}
+
+/// Clone `entry`, truncating large tool results and diffs for WebSocket
+/// transport.
+fn truncate_for_ws(entry: &AgentEntry) -> AgentEntry {
+    match entry {
+        AgentEntry::ToolResult {
+            name,
+            result,
+            is_error,
+        } if result.len() > MAX_WS_TOOL_RESULT_BYTES => {
+            let end = result.floor_char_boundary(MAX_WS_TOOL_RESULT_BYTES);
+            #[allow(clippy::string_slice)] // end from floor_char_boundary
+            let cut = &result[..end];
+            AgentEntry::ToolResult {
+                name: name.clone(),
+                result: format!(
+                    "{cut}\n... (truncated, {total} bytes total)",
+                    total = result.len()
+                ),
+                is_error: *is_error,
+            }
+        }
+        AgentEntry::FileEdit { path, diff, stats } if diff.len() > MAX_WS_TOOL_RESULT_BYTES => {
+            let end = diff.floor_char_boundary(MAX_WS_TOOL_RESULT_BYTES);
+            #[allow(clippy::string_slice)] // end from floor_char_boundary
+            let cut = &diff[..end];
+            let cut = cut.rsplit_once('\n').map_or(cut, |(head, _)| head);
+            AgentEntry::FileEdit {
+                path: path.clone(),
+                diff: format!(
+                    "{cut}\n... (truncated, {total} bytes total)",
+                    total = diff.len()
+                ),
+                stats: *stats,
+            }
+        }
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cinch_rs::ui::{ActiveQuestion, LogLevel, push_agent_text, push_todo_update, update_phase};
+    use std::sync::{Arc, Mutex};
+
+    fn texts(entries: &[OutputEntry]) -> Vec<String> {
+        entries
+            .iter()
+            .map(|e| match &e.entry {
+                AgentEntry::Text(t) | AgentEntry::TodoUpdate(t) => t.clone(),
+                other => format!("{other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn unchanged_state_yields_no_patch() {
+        let state = UiState::default();
+        let mut tracker = PatchTracker::new(&state);
+        assert!(tracker.diff(&state).is_none());
+    }
+
+    #[test]
+    fn patches_carry_only_changes() {
+        let state = Arc::new(Mutex::new(UiState::default()));
+        push_agent_text(&state, "old");
+        let mut tracker = PatchTracker::new(&state.lock().unwrap());
+
+        update_phase(&state, "Working");
+        push_agent_text(&state, "new");
+        let patch = tracker.diff(&state.lock().unwrap()).unwrap();
+        assert_eq!(patch.phase.as_deref(), Some("Working"));
+        assert!(patch.round.is_none());
+        let output = patch.agent_output.unwrap();
+        assert_eq!((output.offset, output.from), (0, 1));
+        assert_eq!(texts(&output.entries), vec!["new"]);
+
+        let json = serde_json::to_value(tracker.diff(&state.lock().unwrap())).unwrap();
+        assert!(json.is_null());
+    }
+
+    #[test]
+    fn in_place_edits_and_trimming_use_sequence_numbers() {
+        let state = Arc::new(Mutex::new(UiState::default()));
+        push_todo_update(&state, "[ ] a");
+        push_agent_text(&state, "text");
+        let mut tracker = PatchTracker::new(&state.lock().unwrap());
+
+        push_todo_update(&state, "[x] a");
+        let output = tracker
+            .diff(&state.lock().unwrap())
+            .unwrap()
+            .agent_output
+            .unwrap();
+        assert_eq!(output.from, 0);
+        assert_eq!(texts(&output.entries), vec!["[x] a", "text"]);
+
+        for i in 0..cinch_rs::ui::MAX_AGENT_OUTPUT {
+            push_agent_text(&state, &format!("t{i}"));
+        }
+        let s = state.lock().unwrap();
+        assert!(s.output_offset > 0);
+        let output = tracker.diff(&s).unwrap().agent_output.unwrap();
+        assert_eq!(output.offset, s.output_offset);
+        assert_eq!(output.from, s.output_offset);
+        assert_eq!(output.entries.len(), s.agent_output.len());
+    }
+
+    #[test]
+    fn logs_questions_and_deltas() {
+        let mut state = UiState::default();
+        let mut tracker = PatchTracker::new(&state);
+
+        state.streaming_buffer.push_str("Hel");
+        assert_eq!(
+            tracker.diff(&state).unwrap().streaming_append.as_deref(),
+            Some("Hel")
+        );
+        state.streaming_buffer.push_str("lo");
+        state.logs.push(LogLine {
+            time: "00:00:01".into(),
+            level: LogLevel::Info,
+            message: "hello".into(),
+        });
+        state.active_question = Some(ActiveQuestion {
+            id: 1,
+            question: UserQuestion {
+                prompt: "Continue?".into(),
+                ..Default::default()
+            },
+            deadline: None,
+            response: None,
+            done: false,
+        });
+        let patch = tracker.diff(&state).unwrap();
+        assert_eq!(patch.streaming_append.as_deref(), Some("lo"));
+        assert!(patch.streaming_buffer.is_none());
+        let logs = patch.logs.unwrap();
+        assert_eq!((logs.from, logs.entries.len()), (0, 1));
+        let question = patch.active_question.unwrap().unwrap();
+        assert_eq!(question.question.prompt, "Continue?");
+
+        state.active_question = None;
+        state.streaming_buffer.clear();
+        let json = serde_json::to_value(tracker.diff(&state).unwrap()).unwrap();
+        assert_eq!(json.get("active_question"), Some(&serde_json::Value::Null));
+        assert!(json.get("logs").is_none());
+        assert_eq!(json["streaming_buffer"], "");
+    }
+}
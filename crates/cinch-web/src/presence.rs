@@ -0,0 +1,233 @@
+//! Who is watching a session, and whose turn it is to type.
+//!
+//! Several browsers can watch the same agent. Each WebSocket connection
+//! joins [`Presence`] as a [`Viewer`] (named via `{"type":"presence"}`), and
+//! every change is broadcast as [`WsMessage::Presence`](crate::WsMessage::Presence).
+//!
+//! To keep two people from sending conflicting messages into the same
+//! agent, typing (`{"type":"typing","typing":true}`) takes the **input
+//! lock**. While one viewer holds it, chat messages from everyone else are
+//! rejected (`POST /api/chat` answers 409). The lock is released when the
+//! holder sends their message, stops typing, disconnects, or goes quiet for
+//! [`INPUT_LOCK_TTL`].
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// How long the input lock survives without a typing update.
+pub const INPUT_LOCK_TTL: Duration = Duration::from_secs(15);
+
+/// A connected viewer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Viewer {
+    /// Connection id, unique for the life of the server.
+    pub id: u64,
+    /// Display name; `Viewer <id>` until the client sets one.
+    pub name: String,
+    /// Whether the viewer is typing a message.
+    pub typing: bool,
+}
+
+/// Connected viewers and the input lock holder.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct PresenceSnapshot {
+    pub viewers: Vec<Viewer>,
+    /// Id of the viewer holding the input lock.
+    pub input_holder: Option<u64>,
+}
+
+#[derive(Default)]
+struct Inner {
+    viewers: BTreeMap<u64, Viewer>,
+    next_id: u64,
+    /// Holder and expiry of the input lock.
+    lock: Option<(u64, Instant)>,
+}
+
+impl Inner {
+    fn holder(&self, now: Instant) -> Option<u64> {
+        self.lock
+            .filter(|(_, expires)| *expires > now)
+            .map(|(id, _)| id)
+    }
+
+    fn release(&mut self, id: u64) {
+        if self.lock.is_some_and(|(holder, _)| holder == id) {
+            self.lock = None;
+        }
+        if let Some(viewer) = self.viewers.get_mut(&id) {
+            viewer.typing = false;
+        }
+    }
+}
+
+/// Shared presence registry for one server.
+#[derive(Default)]
+pub struct Presence(Mutex<Inner>);
+
+impl Presence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new connection and return its viewer id.
+    pub fn join(&self) -> u64 {
+        let mut inner = self.0.lock().unwrap();
+        inner.next_id += 1;
+        let id = inner.next_id;
+        inner.viewers.insert(
+            id,
+            Viewer {
+                id,
+                name: format!("Viewer {id}"),
+                typing: false,
+            },
+        );
+        id
+    }
+
+    /// Remove a connection, releasing the input lock if it held it.
+    pub fn leave(&self, id: u64) {
+        let mut inner = self.0.lock().unwrap();
+        inner.release(id);
+        inner.viewers.remove(&id);
+    }
+
+    /// Set a viewer's display name. Empty names are ignored.
+    pub fn rename(&self, id: u64, name: &str) {
+        let name = name.trim();
+        if name.is_empty() {
+            return;
+        }
+        if let Some(viewer) = self.0.lock().unwrap().viewers.get_mut(&id) {
+            viewer.name = name.chars().take(64).collect();
+        }
+    }
+
+    /// Record that a viewer started or stopped typing. Starting takes (or
+    /// refreshes) the input lock; returns `false` when another viewer holds
+    /// it.
+    pub fn set_typing(&self, id: u64, typing: bool) -> bool {
+        let mut inner = self.0.lock().unwrap();
+        if !typing {
+            inner.release(id);
+            return true;
+        }
+        let now = Instant::now();
+        if inner.holder(now).is_some_and(|holder| holder != id) {
+            return false;
+        }
+        if let Some((holder, _)) = inner.lock.filter(|(holder, _)| *holder != id)
+            && let Some(viewer) = inner.viewers.get_mut(&holder)
+        {
+            // The previous holder's lock expired.
+            viewer.typing = false;
+        }
+        inner.lock = Some((id, now + INPUT_LOCK_TTL));
+        if let Some(viewer) = inner.viewers.get_mut(&id) {
+            viewer.typing = true;
+        }
+        true
+    }
+
+    /// Whether `viewer` (or an anonymous REST caller, `None`) may send a
+    /// chat message now: nobody else holds the input lock.
+    pub fn may_send(&self, viewer: Option<u64>) -> bool {
+        let inner = self.0.lock().unwrap();
+        match inner.holder(Instant::now()) {
+            Some(holder) => Some(holder) == viewer,
+            None => true,
+        }
+    }
+
+    /// A viewer sent their message; the floor is open again.
+    pub fn sent(&self, id: u64) {
+        self.0.lock().unwrap().release(id);
+    }
+
+    /// Drop an expired input lock. Returns `true` if one was dropped, so the
+    /// caller can broadcast the change.
+    pub fn expire(&self) -> bool {
+        let mut inner = self.0.lock().unwrap();
+        match inner.lock {
+            Some((id, expires)) if expires <= Instant::now() => {
+                inner.release(id);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn snapshot(&self) -> PresenceSnapshot {
+        let inner = self.0.lock().unwrap();
+        PresenceSnapshot {
+            viewers: inner.viewers.values().cloned().collect(),
+            input_holder: inner.holder(Instant::now()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn viewers_join_rename_and_leave() {
+        let presence = Presence::new();
+        let a = presence.join();
+        let b = presence.join();
+        presence.rename(a, "  Ana ");
+        presence.rename(b, "");
+        let names: Vec<_> = presence
+            .snapshot()
+            .viewers
+            .into_iter()
+            .map(|v| v.name)
+            .collect();
+        assert_eq!(names, vec!["Ana".to_string(), format!("Viewer {b}")]);
+
+        presence.leave(a);
+        assert_eq!(presence.snapshot().viewers.len(), 1);
+    }
+
+    #[test]
+    fn typing_takes_the_input_lock() {
+        let presence = Presence::new();
+        let a = presence.join();
+        let b = presence.join();
+
+        assert!(presence.set_typing(a, true));
+        assert!(!presence.set_typing(b, true));
+        assert!(presence.may_send(Some(a)));
+        assert!(!presence.may_send(Some(b)));
+        assert!(!presence.may_send(None));
+        let snapshot = presence.snapshot();
+        assert_eq!(snapshot.input_holder, Some(a));
+        assert!(snapshot.viewers[0].typing && !snapshot.viewers[1].typing);
+
+        presence.sent(a);
+        assert!(presence.may_send(Some(b)));
+        assert!(presence.set_typing(b, true));
+
+        // Disconnecting releases the lock.
+        presence.leave(b);
+        assert_eq!(presence.snapshot().input_holder, None);
+        assert!(!presence.expire());
+    }
+
+    #[test]
+    fn expired_locks_are_released() {
+        let presence = Presence::new();
+        let a = presence.join();
+        presence.set_typing(a, true);
+        presence.0.lock().unwrap().lock = Some((a, Instant::now()));
+
+        let b = presence.join();
+        assert!(presence.may_send(Some(b)));
+        assert!(presence.expire());
+        assert!(!presence.snapshot().viewers[0].typing);
+    }
+}
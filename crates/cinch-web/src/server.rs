@@ -5,6 +5,7 @@ use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
 use axum::Router;
+use axum::extract::DefaultBodyLimit;
 use axum::routing::{get, post};
 use cinch_rs::ui::UiState;
 use tokio::sync::{broadcast, mpsc};
@@ -12,7 +13,9 @@ use tower_http::cors::{Any, CorsLayer};
 use tower_http::services::ServeDir;
 
 use crate::api::{self, AppState};
+use crate::attachments::{AttachmentStore, ChatMessage};
 use crate::broadcast::WsMessage;
+use crate::presence::Presence;
 use crate::ws::{self, WsState};
 
 /// Build the full axum router.
@@ -24,19 +27,29 @@ use crate::ws::{self, WsState};
 pub fn build_router(
     ui_state: Arc<Mutex<UiState>>,
     broadcast_tx: broadcast::Sender<WsMessage>,
-    chat_tx: mpsc::Sender<String>,
+    chat_tx: mpsc::Sender<ChatMessage>,
+    attachments: AttachmentStore,
     static_dir: Option<PathBuf>,
 ) -> Router {
+    // Base64 inflates attachments by a third; leave room for the rest of
+    // the JSON body.
+    let body_limit = attachments.max_bytes() / 3 * 4 + 64 * 1024;
+    let attachments = Arc::new(attachments);
+    let presence = Arc::new(Presence::new());
     let app_state = AppState {
         ui_state: ui_state.clone(),
         chat_tx: chat_tx.clone(),
         broadcast_tx: broadcast_tx.clone(),
+        attachments: attachments.clone(),
+        presence: presence.clone(),
     };
 
     let ws_state = WsState {
         ui_state,
         broadcast_tx,
         chat_tx,
+        attachments,
+        presence,
     };
 
     // CORS layer for development (Next.js dev server on a different port).
@@ -53,9 +66,12 @@ pub fn build_router(
     // REST API routes (own state type).
     let api_routes = Router::new()
         .route("/api/state", get(api::get_state))
+        .route("/api/question", get(api::get_question))
         .route("/api/answer", post(api::post_answer))
         .route("/api/control", post(api::post_control))
         .route("/api/chat", post(api::post_chat))
+        .route("/api/presence", get(api::get_presence))
+        .layer(DefaultBodyLimit::max(body_limit))
         .with_state(app_state);
 
     // Merge into a single router.
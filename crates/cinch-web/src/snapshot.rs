@@ -7,11 +7,11 @@
 
 use std::time::Instant;
 
-use cinch_rs::ui::{AgentEntry, LogLine, UiState, UserQuestion};
+use cinch_rs::ui::{ActiveQuestion, AgentEntry, EntryMeta, LogLine, UiState, UserQuestion};
 use serde::Serialize;
 
 /// Maximum number of log lines included in a snapshot.
-const SNAPSHOT_MAX_LOGS: usize = 200;
+pub(crate) const SNAPSHOT_MAX_LOGS: usize = 200;
 
 /// Serializable view of [`UiState`] sent over WebSocket or REST.
 ///
@@ -28,11 +28,15 @@ pub struct UiStateSnapshot {
     pub cycle: u32,
 
     // ── Agent output ──
-    pub agent_output: Vec<AgentEntry>,
+    pub agent_output: Vec<OutputEntry>,
+    /// Sequence number of `agent_output[0]` (see [`UiStatePatch`](crate::patch::UiStatePatch)).
+    pub output_offset: u64,
     pub streaming_buffer: String,
 
     // ── Logs (capped) ──
     pub logs: Vec<LogLine>,
+    /// Sequence number of `logs[0]`.
+    pub log_offset: u64,
 
     // ── Lifecycle ──
     pub running: bool,
@@ -48,15 +52,73 @@ pub struct UiStateSnapshot {
     pub extension: Option<serde_json::Value>,
 }
 
+/// An [`AgentEntry`] with its threading metadata.
+///
+/// Serializes as the entry's own JSON (`{"Text": "..."}`) plus `id`,
+/// `parent_id`, `round`, and `timestamp_ms`. `id` is the entry's stable
+/// sequence number, so clients can dedupe entries they already have after a
+/// reconnect, thread tool calls under their assistant turn via `parent_id`,
+/// and link to a message by id.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct OutputEntry {
+    pub id: u64,
+    pub parent_id: Option<u64>,
+    pub round: u32,
+    pub timestamp_ms: u64,
+    #[serde(flatten)]
+    pub entry: AgentEntry,
+}
+
+impl OutputEntry {
+    /// `state.agent_output[index]` (mapped through `f`) with its metadata.
+    pub(crate) fn at(state: &UiState, index: usize, f: impl Fn(&AgentEntry) -> AgentEntry) -> Self {
+        let meta = state
+            .output_meta
+            .get(index)
+            .copied()
+            .unwrap_or(EntryMeta::default());
+        Self {
+            id: state.output_offset + index as u64,
+            parent_id: meta.parent,
+            round: meta.round,
+            timestamp_ms: meta.timestamp_ms,
+            entry: f(&state.agent_output[index]),
+        }
+    }
+}
+
 /// Serializable view of an in-flight question.
-#[derive(Debug, Serialize)]
+#[derive(Clone, Debug, Serialize)]
 pub struct ActiveQuestionSnapshot {
+    /// Pass back as `question_id` when answering through `POST /api/answer`.
+    pub id: u64,
     pub question: UserQuestion,
     /// Seconds remaining before timeout, or `null` if no deadline.
     pub remaining_secs: Option<f64>,
     pub done: bool,
 }
 
+impl ActiveQuestionSnapshot {
+    /// Project an [`ActiveQuestion`] as of `now`.
+    pub fn new(aq: &ActiveQuestion, now: Instant) -> Self {
+        Self {
+            id: aq.id,
+            question: aq.question.clone(),
+            remaining_secs: aq.deadline.map(|d| secs_until(d, now)),
+            done: aq.done,
+        }
+    }
+}
+
+/// Seconds from `now` until `t`, clamped at zero.
+pub(crate) fn secs_until(t: Instant, now: Instant) -> f64 {
+    if t > now {
+        t.duration_since(now).as_secs_f64()
+    } else {
+        0.0
+    }
+}
+
 impl UiStateSnapshot {
     /// Build a snapshot from the current `UiState`.
     ///
@@ -65,29 +127,12 @@ impl UiStateSnapshot {
     pub fn from_ui_state(state: &UiState) -> Self {
         let now = Instant::now();
 
-        let next_cycle_secs = state.next_cycle_at.map(|t| {
-            if t > now {
-                t.duration_since(now).as_secs_f64()
-            } else {
-                0.0
-            }
-        });
-
-        let active_question = state.active_question.as_ref().map(|aq| {
-            let remaining_secs = aq.deadline.map(|d| {
-                if d > now {
-                    d.duration_since(now).as_secs_f64()
-                } else {
-                    0.0
-                }
-            });
+        let next_cycle_secs = state.next_cycle_at.map(|t| secs_until(t, now));
 
-            ActiveQuestionSnapshot {
-                question: aq.question.clone(),
-                remaining_secs,
-                done: aq.done,
-            }
-        });
+        let active_question = state
+            .active_question
+            .as_ref()
+            .map(|aq| ActiveQuestionSnapshot::new(aq, now));
 
         // Take only the most recent logs to limit payload size.
         let log_start = state.logs.len().saturating_sub(SNAPSHOT_MAX_LOGS);
@@ -100,9 +145,13 @@ impl UiStateSnapshot {
             context_pct: state.context_pct,
             model: state.model.clone(),
             cycle: state.cycle,
-            agent_output: state.agent_output.clone(),
+            agent_output: (0..state.agent_output.len())
+                .map(|i| OutputEntry::at(state, i, AgentEntry::clone))
+                .collect(),
+            output_offset: state.output_offset,
             streaming_buffer: state.streaming_buffer.clone(),
             logs,
+            log_offset: state.log_offset + log_start as u64,
             running: state.running,
             next_cycle_secs,
             active_question,
@@ -142,6 +191,22 @@ mod tests {
         assert!(json["next_cycle_secs"].is_null());
     }
 
+    #[test]
+    fn snapshot_entries_carry_ids_and_parents() {
+        let state = std::sync::Arc::new(std::sync::Mutex::new(UiState::default()));
+        cinch_rs::ui::push_user_message(&state, "hi");
+        cinch_rs::ui::push_agent_text(&state, "hello");
+        let snap = UiStateSnapshot::from_ui_state(&state.lock().unwrap());
+
+        let json = serde_json::to_value(&snap).unwrap();
+        let reply = &json["agent_output"][1];
+        assert_eq!(reply["Text"], "hello");
+        assert_eq!(reply["id"], 1);
+        assert_eq!(reply["parent_id"], 0);
+        assert!(json["agent_output"][0]["parent_id"].is_null());
+        assert!(reply["timestamp_ms"].as_u64().unwrap() > 0);
+    }
+
     #[test]
     fn snapshot_caps_logs() {
         let mut state = UiState::default();
@@ -2,29 +2,50 @@
 //!
 //! Each connected client receives:
 //! 1. A full [`UiStateSnapshot`] on connect.
-//! 2. Incremental [`WsMessage`] updates as harness events fire.
+//! 2. [`WsMessage::Patch`] updates computed by a per-connection
+//!    [`PatchTracker`] whenever the shared state changes (and at least every
+//!    [`SYNC_INTERVAL`], for changes made outside the harness such as new
+//!    questions and log lines), plus transient [`WsMessage`]s as harness
+//!    events fire.
 //!
-//! Clients can send JSON messages back (question answers, quit requests).
+//! Each connection is also a [`Presence`] viewer: the client learns its id
+//! from [`WsMessage::Welcome`], every viewer sees [`WsMessage::Presence`]
+//! when someone joins, leaves, renames, or starts typing, and chat messages
+//! sent while another viewer holds the input lock are answered with
+//! [`WsMessage::ChatRejected`].
+//!
+//! Clients can send JSON messages back (question answers, chat messages
+//! with optional attachments, presence and typing updates, quit requests).
 
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use axum::extract::State;
 use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
 use axum::response::IntoResponse;
 use cinch_rs::ui::{QuestionResponse, UiState, push_user_message};
 use futures::{SinkExt, StreamExt, stream::SplitSink};
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, mpsc};
 use tracing::{debug, warn};
 
+use crate::attachments::{AttachmentStore, AttachmentUpload, ChatMessage};
 use crate::broadcast::WsMessage;
+use crate::patch::PatchTracker;
+use crate::presence::Presence;
 use crate::snapshot::UiStateSnapshot;
 
+/// How often each connection checks the shared state for changes that did
+/// not come with a [`WsMessage::StateChanged`].
+const SYNC_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Shared state for WebSocket handlers.
 #[derive(Clone)]
 pub struct WsState {
     pub ui_state: Arc<Mutex<UiState>>,
     pub broadcast_tx: broadcast::Sender<WsMessage>,
-    pub chat_tx: tokio::sync::mpsc::Sender<String>,
+    pub chat_tx: mpsc::Sender<ChatMessage>,
+    pub attachments: Arc<AttachmentStore>,
+    pub presence: Arc<Presence>,
 }
 
 /// GET /ws — WebSocket upgrade handler.
@@ -39,10 +60,17 @@ pub async fn ws_upgrade(
 async fn handle_socket(socket: WebSocket, ws_state: WsState) {
     let (mut sink, mut stream) = socket.split();
 
-    // Send initial snapshot.
-    let snapshot = {
+    // Subscribe before taking the snapshot so no change slips between the
+    // two; patches for changes the snapshot already has are harmless.
+    let mut broadcast_rx = ws_state.broadcast_tx.subscribe();
+
+    // Send initial snapshot and start tracking what this client has.
+    let (snapshot, mut tracker) = {
         let state = ws_state.ui_state.lock().unwrap();
-        UiStateSnapshot::from_ui_state(&state)
+        (
+            UiStateSnapshot::from_ui_state(&state),
+            PatchTracker::new(&state),
+        )
     };
     let snapshot_msg = WsMessage::Snapshot {
         data: serde_json::to_value(snapshot).unwrap_or_default(),
@@ -51,49 +79,69 @@ async fn handle_socket(socket: WebSocket, ws_state: WsState) {
         return;
     }
 
-    // Also send active question if one exists.
-    let pending_question = {
-        let state = ws_state.ui_state.lock().unwrap();
-        state
-            .active_question
-            .as_ref()
-            .filter(|aq| !aq.done)
-            .map(|aq| aq.question.clone())
-    };
-    if let Some(question) = pending_question {
-        let _ = ws_send(&mut sink, &WsMessage::Question { question }).await;
+    let viewer_id = ws_state.presence.join();
+    if ws_send(&mut sink, &WsMessage::Welcome { viewer_id })
+        .await
+        .is_err()
+    {
+        ws_state.presence.leave(viewer_id);
+        return;
     }
+    let _ = ws_state
+        .broadcast_tx
+        .send(WsMessage::presence(&ws_state.presence));
 
-    debug!("WebSocket client connected");
+    debug!("WebSocket client {viewer_id} connected");
 
-    // Subscribe to broadcast channel for server→client messages.
-    let mut broadcast_rx = ws_state.broadcast_tx.subscribe();
+    // Messages for this client only (e.g. rejected chats).
+    let (direct_tx, mut direct_rx) = mpsc::unbounded_channel();
 
     // Spawn a task that forwards broadcast messages to this client.
-    let ui_state_for_resync = ws_state.ui_state.clone();
+    let ui_state_for_sync = ws_state.ui_state.clone();
+    let presence = ws_state.presence.clone();
+    let broadcast_tx = ws_state.broadcast_tx.clone();
     let forward_task = tokio::spawn(async move {
+        let mut sync_tick = tokio::time::interval(SYNC_INTERVAL);
         loop {
-            match broadcast_rx.recv().await {
-                Ok(msg) => {
-                    if ws_send(&mut sink, &msg).await.is_err() {
-                        break; // Client disconnected.
+            let received = tokio::select! {
+                received = broadcast_rx.recv() => received,
+                Some(msg) = direct_rx.recv() => Ok(msg),
+                _ = sync_tick.tick() => {
+                    // Whichever connection notices an expired input lock
+                    // first tells everyone.
+                    if presence.expire() {
+                        let _ = broadcast_tx.send(WsMessage::presence(&presence));
                     }
+                    Ok(WsMessage::StateChanged)
                 }
+            };
+            let msg = match received {
+                Ok(WsMessage::StateChanged) => {
+                    let patch = tracker.diff(&ui_state_for_sync.lock().unwrap());
+                    match patch {
+                        Some(data) => WsMessage::Patch {
+                            data: Box::new(data),
+                        },
+                        None => continue,
+                    }
+                }
+                Ok(msg) => msg,
                 Err(broadcast::error::RecvError::Lagged(n)) => {
-                    // Client fell behind — send a fresh snapshot to resynchronize.
-                    warn!("WebSocket client lagged by {n} messages, resending snapshot");
-                    let snapshot = {
-                        let state = ui_state_for_resync.lock().unwrap();
-                        UiStateSnapshot::from_ui_state(&state)
-                    };
-                    let msg = WsMessage::Snapshot {
-                        data: serde_json::to_value(snapshot).unwrap_or_default(),
-                    };
-                    if ws_send(&mut sink, &msg).await.is_err() {
-                        break;
+                    // Client fell behind — the tracker still knows what it
+                    // has, so a patch resynchronizes it.
+                    warn!("WebSocket client lagged by {n} messages, sending patch");
+                    let patch = tracker.diff(&ui_state_for_sync.lock().unwrap());
+                    match patch {
+                        Some(data) => WsMessage::Patch {
+                            data: Box::new(data),
+                        },
+                        None => continue,
                     }
                 }
                 Err(broadcast::error::RecvError::Closed) => break,
+            };
+            if ws_send(&mut sink, &msg).await.is_err() {
+                break; // Client disconnected.
             }
         }
     });
@@ -102,34 +150,47 @@ async fn handle_socket(socket: WebSocket, ws_state: WsState) {
     while let Some(Ok(msg)) = stream.next().await {
         match msg {
             Message::Text(text) => {
-                handle_client_message(
-                    &text,
-                    &ws_state.ui_state,
-                    &ws_state.chat_tx,
-                    &ws_state.broadcast_tx,
-                );
+                handle_client_message(&text, &ws_state, viewer_id, &direct_tx);
             }
             Message::Close(_) => break,
             _ => {} // Ignore binary, ping, pong.
         }
     }
 
-    debug!("WebSocket client disconnected");
+    debug!("WebSocket client {viewer_id} disconnected");
     forward_task.abort();
+    ws_state.presence.leave(viewer_id);
+    let _ = ws_state
+        .broadcast_tx
+        .send(WsMessage::presence(&ws_state.presence));
 }
 
-/// Process a JSON message received from a client.
+/// Process a JSON message received from viewer `viewer_id`. Replies meant
+/// for that client alone go to `direct_tx`.
 fn handle_client_message(
     text: &str,
-    ui_state: &Arc<Mutex<UiState>>,
-    chat_tx: &tokio::sync::mpsc::Sender<String>,
-    broadcast_tx: &broadcast::Sender<WsMessage>,
+    ws_state: &WsState,
+    viewer_id: u64,
+    direct_tx: &mpsc::UnboundedSender<WsMessage>,
 ) {
     #[derive(serde::Deserialize)]
     #[serde(tag = "type", rename_all = "snake_case")]
     enum ClientMessage {
-        Answer { response: QuestionResponse },
-        Chat { message: String },
+        Answer {
+            response: QuestionResponse,
+        },
+        Chat {
+            #[serde(default)]
+            message: String,
+            #[serde(default)]
+            attachments: Vec<AttachmentUpload>,
+        },
+        Presence {
+            name: String,
+        },
+        Typing {
+            typing: bool,
+        },
         Quit,
     }
 
@@ -140,27 +201,69 @@ fn handle_client_message(
 
     match msg {
         ClientMessage::Answer { response } => {
-            let mut state = ui_state.lock().unwrap();
+            let mut state = ws_state.ui_state.lock().unwrap();
             if let Some(ref mut aq) = state.active_question
                 && !aq.done
             {
+                if let Err(e) = aq.question.validate(&response) {
+                    debug!("Ignoring invalid answer: {e}");
+                    return;
+                }
                 aq.response = Some(response);
                 aq.done = true;
             }
         }
-        ClientMessage::Chat { message } => {
+        ClientMessage::Chat {
+            message,
+            attachments,
+        } => {
+            if message.trim().is_empty() && attachments.is_empty() {
+                return;
+            }
+            if !ws_state.presence.may_send(Some(viewer_id)) {
+                let _ = direct_tx.send(WsMessage::ChatRejected {
+                    reason: "another viewer is typing".into(),
+                });
+                return;
+            }
+            let chat = match ws_state.attachments.receive(message, attachments) {
+                Ok(chat) => chat,
+                Err(e) => {
+                    warn!("Rejecting chat message: {e}");
+                    return;
+                }
+            };
             // Push user message to UI state so it appears in the chat stream
             // and persists across reconnects (via snapshot).
-            push_user_message(ui_state, &message);
-            // Broadcast to all connected clients.
-            let _ = broadcast_tx.send(WsMessage::UserMessage {
-                message: message.clone(),
-            });
+            push_user_message(&ws_state.ui_state, &chat.display());
+            // Patch all connected clients.
+            let _ = ws_state.broadcast_tx.send(WsMessage::StateChanged);
             // Forward to the agent loop.
-            let _ = chat_tx.try_send(message);
+            let _ = ws_state.chat_tx.try_send(chat);
+            // The floor is open again.
+            ws_state.presence.sent(viewer_id);
+            let _ = ws_state
+                .broadcast_tx
+                .send(WsMessage::presence(&ws_state.presence));
+        }
+        ClientMessage::Presence { name } => {
+            ws_state.presence.rename(viewer_id, &name);
+            let _ = ws_state
+                .broadcast_tx
+                .send(WsMessage::presence(&ws_state.presence));
+        }
+        ClientMessage::Typing { typing } => {
+            if !ws_state.presence.set_typing(viewer_id, typing) {
+                let _ = direct_tx.send(WsMessage::ChatRejected {
+                    reason: "another viewer is typing".into(),
+                });
+            }
+            let _ = ws_state
+                .broadcast_tx
+                .send(WsMessage::presence(&ws_state.presence));
         }
         ClientMessage::Quit => {
-            let mut state = ui_state.lock().unwrap();
+            let mut state = ws_state.ui_state.lock().unwrap();
             state.quit_requested = true;
         }
     }
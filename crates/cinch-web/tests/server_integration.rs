@@ -6,16 +6,16 @@
 use std::sync::{Arc, Mutex};
 
 use cinch_rs::ui::{
-    ActiveQuestion, QuestionChoice, QuestionResponse, UiState, UserQuestion, push_agent_text,
-    update_phase,
+    ActiveQuestion, QuestionChoice, QuestionKind, QuestionResponse, UiState, UserQuestion,
+    push_agent_text, update_phase,
 };
-use cinch_web::{WebConfig, WsMessage, spawn_web};
+use cinch_web::{ChatMessage, WebConfig, WsMessage, spawn_web};
 
 /// Helper: spawn a test server on port 0 (random available port).
 async fn spawn_test_server() -> (
     Arc<Mutex<UiState>>,
     String,
-    tokio::sync::mpsc::Receiver<String>,
+    tokio::sync::mpsc::Receiver<ChatMessage>,
 ) {
     let state = Arc::new(Mutex::new(UiState::default()));
     let (tx, _) = tokio::sync::broadcast::channel::<WsMessage>(64);
@@ -57,6 +57,7 @@ async fn post_answer_sets_question_response() {
     {
         let mut s = state.lock().unwrap();
         s.active_question = Some(ActiveQuestion {
+            id: 7,
             question: UserQuestion {
                 prompt: "Pick:".into(),
                 choices: vec![
@@ -73,6 +74,7 @@ async fn post_answer_sets_question_response() {
                 ],
                 editable: false,
                 max_edit_length: None,
+                ..Default::default()
             },
             deadline: None,
             response: None,
@@ -83,7 +85,7 @@ async fn post_answer_sets_question_response() {
     let client = reqwest::Client::new();
     let resp = client
         .post(format!("{base}/api/answer"))
-        .json(&serde_json::json!({"response": {"Selected": 1}}))
+        .json(&serde_json::json!({"question_id": 7, "response": {"Selected": 1}}))
         .send()
         .await
         .unwrap();
@@ -103,13 +105,93 @@ async fn post_answer_returns_404_when_no_question() {
     let client = reqwest::Client::new();
     let resp = client
         .post(format!("{base}/api/answer"))
-        .json(&serde_json::json!({"response": "Skipped"}))
+        .json(&serde_json::json!({"question_id": 7, "response": "Skipped"}))
         .send()
         .await
         .unwrap();
     assert_eq!(resp.status(), 404);
 }
 
+#[tokio::test]
+async fn post_answer_rejects_invalid_response() {
+    let (state, base, _chat_rx) = spawn_test_server().await;
+
+    {
+        let mut s = state.lock().unwrap();
+        s.active_question = Some(ActiveQuestion {
+            id: 7,
+            question: UserQuestion {
+                prompt: "How many?".into(),
+                kind: QuestionKind::Number {
+                    min: Some(1.0),
+                    max: Some(5.0),
+                    integer: true,
+                },
+                ..Default::default()
+            },
+            deadline: None,
+            response: None,
+            done: false,
+        });
+    }
+
+    let client = reqwest::Client::new();
+    let resp = client
+        .post(format!("{base}/api/answer"))
+        .json(&serde_json::json!({"question_id": 7, "response": {"Number": 9.0}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 422);
+    assert!(!state.lock().unwrap().active_question.as_ref().unwrap().done);
+
+    let resp = client
+        .post(format!("{base}/api/answer"))
+        .json(&serde_json::json!({"question_id": 7, "response": {"Number": 3.0}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 204);
+}
+
+#[tokio::test]
+async fn post_answer_requires_the_matching_question_id() {
+    let (state, base, _chat_rx) = spawn_test_server().await;
+
+    {
+        let mut s = state.lock().unwrap();
+        s.active_question = Some(ActiveQuestion {
+            id: 7,
+            question: UserQuestion {
+                prompt: "Allow write_file?".into(),
+                ..Default::default()
+            },
+            deadline: None,
+            response: None,
+            done: false,
+        });
+    }
+
+    let client = reqwest::Client::new();
+    let resp = client
+        .post(format!("{base}/api/answer"))
+        .json(&serde_json::json!({"text": "1"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+
+    // A late answer to an earlier question must not resolve this one.
+    let resp = client
+        .post(format!("{base}/api/answer"))
+        .json(&serde_json::json!({"question_id": 6, "text": "1"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 409);
+    assert!(!state.lock().unwrap().active_question.as_ref().unwrap().done);
+}
+
 #[tokio::test]
 async fn post_control_quit() {
     let (state, base, _chat_rx) = spawn_test_server().await;
@@ -143,5 +225,62 @@ async fn post_chat_delivers_message() {
 
     // Verify the message was delivered to the receiver.
     let msg = chat_rx.try_recv().unwrap();
-    assert_eq!(msg, "Hello agent");
+    assert_eq!(msg, ChatMessage::text("Hello agent"));
+}
+
+#[tokio::test]
+async fn post_chat_saves_attachments() {
+    let uploads = tempfile::tempdir().unwrap();
+    let state = Arc::new(Mutex::new(UiState::default()));
+    let (tx, _) = tokio::sync::broadcast::channel::<WsMessage>(64);
+    let config = WebConfig {
+        bind_addr: ([127, 0, 0, 1], 0).into(),
+        upload_dir: uploads.path().to_path_buf(),
+        max_upload_bytes: 64,
+        ..Default::default()
+    };
+    let (addr, mut chat_rx) = spawn_web(state.clone(), tx, config).await;
+
+    let client = reqwest::Client::new();
+    let resp = client
+        .post(format!("http://{addr}/api/chat"))
+        .json(&serde_json::json!({
+            "message": "See the screenshot",
+            // "iVBORw==" is the PNG signature's first bytes.
+            "attachments": [{"name": "bug.png", "data": "data:image/png;base64,iVBORw=="}],
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 204);
+
+    let chat = chat_rx.try_recv().unwrap();
+    assert_eq!(chat.attachments.len(), 1);
+    assert_eq!(chat.attachments[0].media_type, "image/png");
+    assert!(chat.attachments[0].path.starts_with(uploads.path()));
+    assert_eq!(chat.to_message().images.len(), 1);
+
+    let too_large = "A".repeat(200);
+    let resp = client
+        .post(format!("http://{addr}/api/chat"))
+        .json(&serde_json::json!({
+            "message": "",
+            "attachments": [{"name": "big.txt", "data": too_large}],
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 413);
+}
+
+#[tokio::test]
+async fn get_presence_lists_no_viewers_without_websockets() {
+    let (_state, base, _chat_rx) = spawn_test_server().await;
+
+    let resp = reqwest::get(format!("{base}/api/presence")).await.unwrap();
+    assert_eq!(resp.status(), 200);
+
+    let json: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(json["viewers"], serde_json::json!([]));
+    assert!(json["input_holder"].is_null());
 }